@@ -0,0 +1,388 @@
+//! Project activity recorded by git hooks.
+//!
+//! Build directory timestamps only tell when a project was last built.
+//! `hook install` adds `post-checkout` and `post-merge` hooks to a git
+//! repository that run `clean-dev-dirs hook record`, which stores the time
+//! in the activity log (`~/.local/share/clean-dev-dirs/activity.json` on
+//! Linux). When deciding how old a project is, the most recent of its
+//! artifacts' modification time and the activity recorded for its
+//! repository counts, so switching branches in a project that was built
+//! long ago keeps it out of `--keep-days` cleanups.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// The git hooks installed by [`install_hooks`].
+pub const HOOKS: [&str; 2] = ["post-checkout", "post-merge"];
+
+/// Comment ending the line added to each hook, to find it again.
+const HOOK_MARKER: &str = "# clean-dev-dirs activity";
+
+/// The log set with [`set_log`].
+static LOG: OnceLock<ActivityLog> = OnceLock::new();
+
+/// The file the activity log is kept in, if the platform has a data
+/// directory.
+#[must_use]
+pub fn activity_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("clean-dev-dirs").join("activity.json"))
+}
+
+/// Time of the last recorded activity per repository root.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ActivityLog {
+    repos: BTreeMap<PathBuf, DateTime<Utc>>,
+}
+
+impl ActivityLog {
+    /// Load the log from `path`. A missing file holds no activity.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid log.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|source| Error::Invalid {
+                kind: "activity log",
+                path: path.to_path_buf(),
+                source,
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::file("read", path)(e)),
+        }
+    }
+
+    /// Write the log to `path`, creating its directory if needed.
+    ///
+    /// The log is written to a temporary file that then replaces `path`, so
+    /// hooks running at the same time never read a partly written log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(dir).map_err(Error::file("create", dir))?;
+
+        let temp = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&temp, serde_json::to_string_pretty(self)?)
+            .map_err(Error::file("write", &temp))?;
+        fs::rename(&temp, path).map_err(Error::file("write", path))
+    }
+
+    /// Record activity in the repository at `repo` at time `at`.
+    pub fn record(&mut self, repo: PathBuf, at: DateTime<Utc>) {
+        self.repos.insert(repo, at);
+    }
+
+    /// Whether no activity has been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.repos.is_empty()
+    }
+
+    /// The last activity recorded for `dir`: that of the innermost recorded
+    /// repository containing it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use clean_dev_dirs::activity::ActivityLog;
+    /// # use chrono::Utc;
+    /// # use std::path::Path;
+    /// let mut log = ActivityLog::default();
+    /// let now = Utc::now();
+    /// log.record("/work/monorepo".into(), now);
+    /// assert_eq!(log.last_activity(Path::new("/work/monorepo/apps/web")), Some(now));
+    /// assert_eq!(log.last_activity(Path::new("/work/other")), None);
+    /// ```
+    #[must_use]
+    pub fn last_activity(&self, dir: &Path) -> Option<DateTime<Utc>> {
+        dir.ancestors()
+            .find_map(|ancestor| self.repos.get(ancestor).copied())
+    }
+}
+
+/// Use `log` for [`recorded_activity`] for the rest of the process.
+///
+/// Only the first call has an effect.
+pub fn set_log(log: ActivityLog) {
+    let _ = LOG.set(log);
+}
+
+/// The last activity recorded for the project at `dir` in the log set with
+/// [`set_log`], if any.
+#[must_use]
+pub fn recorded_activity(dir: &Path) -> Option<SystemTime> {
+    let log = LOG.get().filter(|log| !log.is_empty())?;
+    // Repositories are recorded by their canonical path
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    log.last_activity(&dir).map(SystemTime::from)
+}
+
+/// Add the activity hooks to the git repository containing `repo`, running
+/// the executable at `exe`. Returns the hook files written.
+///
+/// Existing hooks are kept: the line running `exe` is added to them, or
+/// replaced if they already have one.
+///
+/// # Errors
+///
+/// Returns an error if `repo` is not in a git repository, a hook exists but
+/// is not a shell script, or a hook cannot be written.
+pub fn install_hooks(repo: &Path, exe: &Path) -> Result<Vec<PathBuf>> {
+    let dir = hooks_dir(repo)?;
+    fs::create_dir_all(&dir).map_err(Error::file("create", &dir))?;
+
+    let line = hook_line(exe);
+    let mut written = Vec::new();
+    for hook in HOOKS {
+        let path = dir.join(hook);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => "#!/bin/sh\n".to_string(),
+            Err(e) => return Err(Error::file("read", &path)(e)),
+        };
+        if !is_shell_script(&content) {
+            return Err(Error::Other(format!(
+                "{} is not a shell script; add this line to it instead:\n  {line}",
+                path.display()
+            )));
+        }
+
+        let mut content = without_hook_line(&content);
+        content.push_str(&line);
+        content.push('\n');
+        fs::write(&path, content).map_err(Error::file("write", &path))?;
+        make_executable(&path)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Remove the activity hooks from the git repository containing `repo`.
+/// Hooks left with nothing else to do are deleted. Returns the hook files
+/// changed.
+///
+/// # Errors
+///
+/// Returns an error if `repo` is not in a git repository or a hook cannot
+/// be changed.
+pub fn remove_hooks(repo: &Path) -> Result<Vec<PathBuf>> {
+    let dir = hooks_dir(repo)?;
+    let mut changed = Vec::new();
+    for hook in HOOKS {
+        let path = dir.join(hook);
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if !content.lines().any(|line| line.ends_with(HOOK_MARKER)) {
+            continue;
+        }
+
+        let stripped = without_hook_line(&content);
+        if stripped
+            .lines()
+            .all(|line| line.trim().is_empty() || line.starts_with("#!"))
+        {
+            fs::remove_file(&path)
+        } else {
+            fs::write(&path, stripped)
+        }
+        .map_err(Error::file("update", &path))?;
+        changed.push(path);
+    }
+    Ok(changed)
+}
+
+/// The hooks directory of the repository containing `repo`, which honours
+/// `core.hooksPath`.
+fn hooks_dir(repo: &Path) -> Result<PathBuf> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .map_err(|source| Error::Spawn {
+            command: "git".to_string(),
+            source,
+        })?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "{} is not in a git repository: {}",
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    // The path is relative to `repo` unless it is absolute
+    let dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(repo.join(dir))
+}
+
+/// The line added to the hooks. Failures are ignored so that recording
+/// activity can never get in the way of a checkout.
+fn hook_line(exe: &Path) -> String {
+    let exe = exe.to_string_lossy().replace('\'', r"'\''");
+    format!("'{exe}' hook record >/dev/null 2>&1 || true {HOOK_MARKER}")
+}
+
+/// `content` without the line added by [`install_hooks`].
+fn without_hook_line(content: &str) -> String {
+    let mut lines: Vec<&str> = content
+        .lines()
+        .filter(|line| !line.ends_with(HOOK_MARKER))
+        .collect();
+    lines.push("");
+    lines.join("\n")
+}
+
+/// Whether a hook is run by a POSIX shell, so a shell command can be added.
+fn is_shell_script(content: &str) -> bool {
+    content.lines().next().is_some_and(|shebang| {
+        shebang.starts_with("#!")
+            && ["sh", "bash", "dash", "zsh"]
+                .iter()
+                .any(|shell| shebang.split(['/', ' ']).any(|word| word == *shell))
+    })
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)
+        .map_err(Error::file("read", path))?
+        .permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions).map_err(Error::file("make executable", path))
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git_init(dir: &Path) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["init", "--quiet"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_log_roundtrip() -> Result<()> {
+        let temp = TempDir::new()?;
+        let path = temp.path().join("data").join("activity.json");
+        assert_eq!(ActivityLog::load(&path)?, ActivityLog::default());
+
+        let mut log = ActivityLog::default();
+        let at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        log.record(PathBuf::from("/work/app"), at);
+        log.save(&path)?;
+
+        let loaded = ActivityLog::load(&path)?;
+        assert_eq!(loaded, log);
+        assert_eq!(loaded.last_activity(Path::new("/work/app/src")), Some(at));
+        assert_eq!(loaded.last_activity(Path::new("/work/application")), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_innermost_repository_wins() {
+        let mut log = ActivityLog::default();
+        let outer = DateTime::from_timestamp(1_000, 0).unwrap();
+        let inner = DateTime::from_timestamp(2_000, 0).unwrap();
+        log.record(PathBuf::from("/work"), outer);
+        log.record(PathBuf::from("/work/vendored"), inner);
+
+        assert_eq!(
+            log.last_activity(Path::new("/work/vendored/lib")),
+            Some(inner)
+        );
+        assert_eq!(log.last_activity(Path::new("/work/app")), Some(outer));
+    }
+
+    #[test]
+    fn test_install_and_remove_hooks() -> Result<()> {
+        let temp = TempDir::new()?;
+        git_init(temp.path());
+        let hooks = temp.path().join(".git").join("hooks");
+        fs::create_dir_all(&hooks)?;
+        fs::write(hooks.join("post-merge"), "#!/bin/bash\necho merged\n")?;
+
+        let exe = Path::new("/opt/it's/clean-dev-dirs");
+        let written = install_hooks(temp.path(), exe)?;
+        assert_eq!(written.len(), 2);
+        // Installing again replaces the line rather than adding another
+        install_hooks(temp.path(), exe)?;
+
+        let checkout = fs::read_to_string(hooks.join("post-checkout"))?;
+        assert_eq!(checkout, format!("#!/bin/sh\n{}\n", hook_line(exe)));
+        assert!(checkout.contains(r"'/opt/it'\''s/clean-dev-dirs' hook record"));
+        let merge = fs::read_to_string(hooks.join("post-merge"))?;
+        assert_eq!(
+            merge,
+            format!("#!/bin/bash\necho merged\n{}\n", hook_line(exe))
+        );
+
+        let changed = remove_hooks(temp.path())?;
+        assert_eq!(changed.len(), 2);
+        assert!(!hooks.join("post-checkout").exists());
+        assert_eq!(
+            fs::read_to_string(hooks.join("post-merge"))?,
+            "#!/bin/bash\necho merged\n"
+        );
+        assert_eq!(remove_hooks(temp.path())?, Vec::<PathBuf>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_foreign_hooks_are_not_changed() -> Result<()> {
+        let temp = TempDir::new()?;
+        git_init(temp.path());
+        let hooks = temp.path().join(".git").join("hooks");
+        fs::create_dir_all(&hooks)?;
+        fs::write(hooks.join("post-checkout"), "#!/usr/bin/env python3\n")?;
+
+        assert!(install_hooks(temp.path(), Path::new("clean-dev-dirs")).is_err());
+        assert_eq!(
+            fs::read_to_string(hooks.join("post-checkout"))?,
+            "#!/usr/bin/env python3\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_a_repository() {
+        let temp = TempDir::new().unwrap();
+        assert!(install_hooks(temp.path(), Path::new("clean-dev-dirs")).is_err());
+    }
+
+    #[test]
+    fn test_is_shell_script() {
+        assert!(is_shell_script("#!/bin/sh\n"));
+        assert!(is_shell_script("#!/usr/bin/env bash\nexit 0\n"));
+        assert!(!is_shell_script("#!/usr/bin/env node\n"));
+        assert!(!is_shell_script("echo hi\n"));
+    }
+}
@@ -0,0 +1,397 @@
+//! Read-only breakdowns of the scanned projects.
+//!
+//! Backs the `stats` subcommand: the projects found by a scan are broken
+//! down by type, by age and by parent directory, and their largest
+//! artifacts are listed, without cleaning anything.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "cli")]
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::filtering::last_modified;
+#[cfg(feature = "cli")]
+use crate::project::projects::TYPE_ENTRIES;
+use crate::project::{Project, ProjectType};
+#[cfg(feature = "cli")]
+use crate::utils::{format_size, terminal};
+
+/// Number of artifacts and parent directories listed.
+pub const TOP_COUNT: usize = 20;
+
+/// Upper bounds of the age buckets, in days, and their labels. Projects
+/// older than the last bound (or whose age is unknown) fall in a final
+/// `90d+` bucket.
+const AGE_BOUNDS: [(u64, &str); 3] = [(7, "0–7d"), (30, "7–30d"), (90, "30–90d")];
+
+/// Number of projects and size of their artifacts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Total {
+    /// Number of projects.
+    pub count: usize,
+
+    /// Total size of their artifacts, in bytes.
+    pub size: u64,
+}
+
+impl Total {
+    fn add(&mut self, project: &Project) {
+        self.count += 1;
+        self.size += project.total_size();
+    }
+}
+
+/// Projects of one type.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TypeTotal {
+    /// The project type.
+    pub kind: ProjectType,
+
+    /// Its projects.
+    #[serde(flatten)]
+    pub total: Total,
+}
+
+/// Projects last modified in an age range.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AgeBucket {
+    /// The age range, e.g. `"7–30d"`.
+    pub label: &'static str,
+
+    /// Its projects.
+    #[serde(flatten)]
+    pub total: Total,
+}
+
+/// Projects in the same directory.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ParentTotal {
+    /// The directory holding the projects.
+    pub parent: PathBuf,
+
+    /// Its projects.
+    #[serde(flatten)]
+    pub total: Total,
+}
+
+/// A build artifact and the type of its project.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ArtifactEntry {
+    /// Type of the project the artifact belongs to.
+    pub kind: ProjectType,
+
+    /// Path of the artifact.
+    pub path: PathBuf,
+
+    /// Size of the artifact, in bytes.
+    pub size: u64,
+}
+
+/// Breakdowns of a set of projects.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Breakdown {
+    /// All the projects.
+    pub total: Total,
+
+    /// Projects per type, largest first.
+    pub by_type: Vec<TypeTotal>,
+
+    /// The [`TOP_COUNT`] largest artifacts, largest first.
+    pub largest: Vec<ArtifactEntry>,
+
+    /// Projects per age of their artifacts, youngest first.
+    pub by_age: Vec<AgeBucket>,
+
+    /// Projects per parent directory, the [`TOP_COUNT`] largest first.
+    pub by_parent: Vec<ParentTotal>,
+}
+
+impl Breakdown {
+    /// Break down `projects`, measuring ages from `now`.
+    #[must_use]
+    pub fn new(projects: &[Project], now: SystemTime) -> Self {
+        let mut total = Total::default();
+        let mut by_type: Vec<TypeTotal> = Vec::new();
+        let mut by_age: Vec<AgeBucket> = AGE_BOUNDS
+            .iter()
+            .map(|&(_, label)| label)
+            .chain(["90d+"])
+            .map(|label| AgeBucket {
+                label,
+                total: Total::default(),
+            })
+            .collect();
+        let mut by_parent: HashMap<&Path, Total> = HashMap::new();
+
+        for project in projects {
+            total.add(project);
+
+            let index = by_type
+                .iter()
+                .position(|entry| entry.kind == project.kind)
+                .unwrap_or_else(|| {
+                    by_type.push(TypeTotal {
+                        kind: project.kind.clone(),
+                        total: Total::default(),
+                    });
+                    by_type.len() - 1
+                });
+            by_type[index].total.add(project);
+
+            let bucket = age_bucket(last_modified(project), now);
+            by_age[bucket].total.add(project);
+
+            let parent = project.root_path.parent().unwrap_or(&project.root_path);
+            by_parent.entry(parent).or_default().add(project);
+        }
+
+        by_type.sort_by_key(|entry| std::cmp::Reverse(entry.total.size));
+
+        let mut largest: Vec<ArtifactEntry> = projects
+            .iter()
+            .flat_map(|project| {
+                project.build_arts.iter().map(|artifact| ArtifactEntry {
+                    kind: project.kind.clone(),
+                    path: artifact.path.clone(),
+                    size: artifact.size,
+                })
+            })
+            .collect();
+        largest.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        largest.truncate(TOP_COUNT);
+
+        let mut by_parent: Vec<ParentTotal> = by_parent
+            .into_iter()
+            .map(|(parent, total)| ParentTotal {
+                parent: parent.to_path_buf(),
+                total,
+            })
+            .collect();
+        by_parent.sort_by(|a, b| {
+            b.total
+                .size
+                .cmp(&a.total.size)
+                .then_with(|| a.parent.cmp(&b.parent))
+        });
+        by_parent.truncate(TOP_COUNT);
+
+        Self {
+            total,
+            by_type,
+            largest,
+            by_age,
+            by_parent,
+        }
+    }
+
+    /// Print the breakdowns to stdout.
+    #[cfg(feature = "cli")]
+    pub fn print(&self) {
+        println!("\n{}", "📊 By project type:".bold());
+        for entry in &self.by_type {
+            let (icon, label) = type_entry(&entry.kind);
+            println!("  {icon} {label}: {}", describe(entry.total));
+        }
+        println!(
+            "  💾 Total: {} in {} projects",
+            format_size(self.total.size).bright_green().bold(),
+            self.total.count
+        );
+
+        println!("\n{}", "📦 Largest artifacts:".bold());
+        for artifact in &self.largest {
+            let (icon, _) = type_entry(&artifact.kind);
+            let prefix = format!("  {:>10}  {icon} ", format_size(artifact.size));
+            let path = artifact.path.display().to_string();
+            println!(
+                "{prefix}{}",
+                terminal::fit_path_to(&path, &prefix, terminal::width())
+            );
+        }
+
+        println!("\n{}", "🕰️  By age of the artifacts:".bold());
+        let largest_bucket = self
+            .by_age
+            .iter()
+            .map(|bucket| bucket.total.size)
+            .max()
+            .unwrap_or(0);
+        for bucket in &self.by_age {
+            println!(
+                "  {:>6}  {:>10}  {:>4} projects  {}",
+                bucket.label,
+                format_size(bucket.total.size).bright_white(),
+                bucket.total.count,
+                bar(bucket.total.size, largest_bucket).cyan()
+            );
+        }
+
+        println!("\n{}", "📂 By parent directory:".bold());
+        for entry in &self.by_parent {
+            let rest = format!(": {}", describe(entry.total));
+            let parent = entry.parent.display().to_string();
+            let width = terminal::width().map(|width| width.saturating_sub(2));
+            println!("  {}{rest}", terminal::fit_path_to(&parent, &rest, width));
+        }
+    }
+}
+
+/// Index of the age bucket of a project last modified at `modified`.
+fn age_bucket(modified: Option<SystemTime>, now: SystemTime) -> usize {
+    let Some(age) = modified.and_then(|modified| now.duration_since(modified).ok()) else {
+        // Unknown ages count as old, like for `--keep-days`; times in the
+        // future as new
+        return if modified.is_some() {
+            0
+        } else {
+            AGE_BOUNDS.len()
+        };
+    };
+    AGE_BOUNDS
+        .iter()
+        .position(|&(days, _)| age < Duration::from_hours(days * 24))
+        .unwrap_or(AGE_BOUNDS.len())
+}
+
+/// The icon and label of a project type.
+#[cfg(feature = "cli")]
+fn type_entry(kind: &ProjectType) -> (&'static str, &'static str) {
+    TYPE_ENTRIES
+        .iter()
+        .find(|(entry_kind, _, _)| entry_kind == kind)
+        .map_or(("", ""), |&(_, icon, label)| (icon, label))
+}
+
+/// Describe a total as `size (N projects)`.
+#[cfg(feature = "cli")]
+fn describe(total: Total) -> String {
+    let projects = if total.count == 1 {
+        "project"
+    } else {
+        "projects"
+    };
+    format!(
+        "{} ({} {projects})",
+        format_size(total.size).bright_white(),
+        total.count
+    )
+}
+
+/// A bar of up to 30 characters, as long relative to `max` as `value` is.
+#[cfg(feature = "cli")]
+fn bar(value: u64, max: u64) -> String {
+    const WIDTH: u64 = 30;
+    let length = (value * WIDTH).checked_div(max).unwrap_or(0);
+    "█".repeat(usize::try_from(length).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::BuildArtifacts;
+
+    fn project(kind: ProjectType, root: &str, sizes: &[u64]) -> Project {
+        let build_arts = sizes
+            .iter()
+            .enumerate()
+            .map(|(index, &size)| BuildArtifacts {
+                path: PathBuf::from(format!("{root}/artifact{index}")),
+                size,
+            })
+            .collect();
+        Project::new(kind, PathBuf::from(root), build_arts, None)
+    }
+
+    #[test]
+    fn test_breakdown_by_type_and_parent() {
+        let projects = [
+            project(ProjectType::Node, "/work/web/app", &[300]),
+            project(ProjectType::Rust, "/work/oss/tool", &[500, 100]),
+            project(ProjectType::Node, "/work/oss/site", &[400]),
+        ];
+
+        let breakdown = Breakdown::new(&projects, SystemTime::now());
+
+        assert_eq!(
+            breakdown.total,
+            Total {
+                count: 3,
+                size: 1300
+            }
+        );
+        let types: Vec<_> = breakdown
+            .by_type
+            .iter()
+            .map(|entry| (entry.kind.clone(), entry.total.size))
+            .collect();
+        assert_eq!(types, [(ProjectType::Node, 700), (ProjectType::Rust, 600)]);
+
+        let parents: Vec<_> = breakdown
+            .by_parent
+            .iter()
+            .map(|entry| (entry.parent.clone(), entry.total))
+            .collect();
+        assert_eq!(
+            parents,
+            [
+                (
+                    PathBuf::from("/work/oss"),
+                    Total {
+                        count: 2,
+                        size: 1000
+                    }
+                ),
+                (
+                    PathBuf::from("/work/web"),
+                    Total {
+                        count: 1,
+                        size: 300
+                    }
+                ),
+            ]
+        );
+
+        let largest: Vec<u64> = breakdown.largest.iter().map(|entry| entry.size).collect();
+        assert_eq!(largest, [500, 400, 300, 100]);
+    }
+
+    #[test]
+    fn test_largest_artifacts_are_capped() {
+        let projects: Vec<Project> = (0..30)
+            .map(|index| project(ProjectType::Rust, &format!("/work/p{index}"), &[index]))
+            .collect();
+
+        let breakdown = Breakdown::new(&projects, SystemTime::now());
+
+        assert_eq!(breakdown.largest.len(), TOP_COUNT);
+        assert_eq!(breakdown.largest[0].size, 29);
+        assert_eq!(breakdown.by_parent.len(), 1);
+    }
+
+    #[test]
+    fn test_age_bucket() {
+        let now = SystemTime::now();
+        let days_ago = |days: u64| Some(now - Duration::from_hours(days * 24));
+
+        assert_eq!(age_bucket(days_ago(0), now), 0);
+        assert_eq!(age_bucket(days_ago(6), now), 0);
+        assert_eq!(age_bucket(days_ago(7), now), 1);
+        assert_eq!(age_bucket(days_ago(29), now), 1);
+        assert_eq!(age_bucket(days_ago(60), now), 2);
+        assert_eq!(age_bucket(days_ago(90), now), 3);
+        assert_eq!(age_bucket(days_ago(400), now), 3);
+        assert_eq!(age_bucket(None, now), 3);
+        assert_eq!(age_bucket(Some(now + Duration::from_hours(1)), now), 0);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_bar() {
+        assert_eq!(bar(0, 0), "");
+        assert_eq!(bar(10, 10).chars().count(), 30);
+        assert_eq!(bar(5, 10).chars().count(), 15);
+    }
+}
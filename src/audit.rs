@@ -0,0 +1,198 @@
+//! Audit log of the deleted build directories (`--audit-log`).
+//!
+//! Every build directory (or global cache) the cleaner is about to remove
+//! is appended to the audit log as a JSON line with the outcome `started`,
+//! and the file is synced to disk before the removal begins. Once the
+//! project is done, a line per directory removed records the outcome:
+//! `deleted`, `trashed`, `partially_deleted` or `failed`. With
+//! `--stale-only`, these lines are for the stale entries removed from the
+//! directory instead, and projects skipped because a build directory is a
+//! mount point are not recorded at all. A run that is killed halfway
+//! thus still leaves a record of what it was removing. The file is only
+//! ever appended to, so it can be kept across runs and machines collected.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::project::{Project, ProjectType};
+
+/// What happened to a build directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The removal is about to begin.
+    Started,
+
+    /// The directory was permanently deleted.
+    Deleted,
+
+    /// The directory was moved to the trash.
+    Trashed,
+
+    /// Some entries of the directory could not be removed, the others
+    /// were.
+    PartiallyDeleted,
+
+    /// The directory could not be removed.
+    Failed,
+}
+
+/// A line of the audit log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Time the line was written.
+    pub timestamp: DateTime<Utc>,
+
+    /// The build directory or cache.
+    pub path: PathBuf,
+
+    /// Size of the directory, in bytes, as measured by the scan.
+    pub size: u64,
+
+    /// Root of the project the directory belongs to; `None` for global
+    /// caches.
+    pub project: Option<PathBuf>,
+
+    /// Type of that project.
+    pub project_type: Option<ProjectType>,
+
+    /// What happened to the directory.
+    pub outcome: AuditOutcome,
+
+    /// Why the removal failed, with [`AuditOutcome::Failed`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditRecord {
+    /// A record of `outcome` for the build directory at `path` of `project`,
+    /// or of a global cache without one, timestamped now.
+    #[must_use]
+    pub fn new(path: &Path, size: u64, project: Option<&Project>, outcome: AuditOutcome) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            path: std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+            size,
+            project: project.map(|project| {
+                std::path::absolute(&project.root_path)
+                    .unwrap_or_else(|_| project.root_path.clone())
+            }),
+            project_type: project.map(|project| project.kind.clone()),
+            outcome,
+            error: None,
+        }
+    }
+
+    /// This record with `error` as the reason of its failure.
+    #[must_use]
+    pub fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+}
+
+/// An audit log file, open for appending.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    /// Open the audit log at `path`, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened for appending.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(Error::file("open", path))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// The file the log is written to.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `records` and sync the file to disk, so that they are kept
+    /// even if the run is killed right after.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written or synced.
+    pub fn append(&self, records: &[AuditRecord]) -> Result<()> {
+        let mut lines = String::new();
+        for record in records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        file.write_all(lines.as_bytes())
+            .and_then(|()| file.sync_data())
+            .map_err(Error::file("write", &self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::BuildArtifacts;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_records_are_appended() -> Result<()> {
+        let temp = TempDir::new()?;
+        let path = temp.path().join("audit.jsonl");
+        let project = Project::new(
+            ProjectType::Rust,
+            PathBuf::from("/work/app"),
+            vec![BuildArtifacts {
+                path: PathBuf::from("/work/app/target"),
+                size: 4096,
+            }],
+            None,
+        );
+        let target = Path::new("/work/app/target");
+
+        AuditLog::open(&path)?.append(&[AuditRecord::new(
+            target,
+            4096,
+            Some(&project),
+            AuditOutcome::Started,
+        )])?;
+        AuditLog::open(&path)?.append(&[AuditRecord::new(
+            target,
+            4096,
+            Some(&project),
+            AuditOutcome::Failed,
+        )
+        .with_error("permission denied")])?;
+
+        let records: Vec<AuditRecord> = std::fs::read_to_string(&path)?
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].outcome, AuditOutcome::Started);
+        assert_eq!(records[0].project.as_deref(), Some(Path::new("/work/app")));
+        assert_eq!(records[0].project_type, Some(ProjectType::Rust));
+        assert_eq!(records[1].outcome, AuditOutcome::Failed);
+        assert_eq!(records[1].error.as_deref(), Some("permission denied"));
+        Ok(())
+    }
+}
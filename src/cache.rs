@@ -0,0 +1,425 @@
+//! Global (per-user) build cache locations.
+//!
+//! Besides per-project build directories, toolchains keep caches that are
+//! shared by every project of a user, such as Cargo's registry, the
+//! `sccache` compilation cache, the npm, yarn and pnpm package stores, or Go's
+//! module and build caches.
+//! These live outside any scanned directory tree, so they are not discovered
+//! by the [`Scanner`](crate::scanner::Scanner) but resolved from well-known
+//! locations and reported as [`CacheLocation`]s.
+
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+#[cfg(feature = "cli")]
+use colored::Colorize;
+use rayon::prelude::*;
+use serde::Serialize;
+use walkdir::WalkDir;
+
+/// The kind of global cache a [`CacheLocation`] points to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheKind {
+    /// Downloaded crate archives in `$CARGO_HOME/registry/cache`.
+    CargoRegistryCache,
+
+    /// Extracted crate sources in `$CARGO_HOME/registry/src`.
+    CargoRegistrySrc,
+
+    /// Checked-out git dependencies in `$CARGO_HOME/git/checkouts`.
+    CargoGitCheckouts,
+
+    /// The `sccache` compilation cache.
+    Sccache,
+
+    /// npm's content-addressable cache (`~/.npm/_cacache`).
+    NpmCache,
+
+    /// The yarn package cache.
+    YarnCache,
+
+    /// The pnpm content-addressable store.
+    PnpmStore,
+
+    /// Downloaded Go modules (`$GOMODCACHE`, by default `$GOPATH/pkg/mod`).
+    GoModCache,
+
+    /// The Go build cache (`$GOCACHE`).
+    GoBuildCache,
+}
+
+impl CacheKind {
+    /// Human-readable name of the cache.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::CargoRegistryCache => "Cargo registry cache",
+            Self::CargoRegistrySrc => "Cargo registry sources",
+            Self::CargoGitCheckouts => "Cargo git checkouts",
+            Self::Sccache => "sccache",
+            Self::NpmCache => "npm cache",
+            Self::YarnCache => "yarn cache",
+            Self::PnpmStore => "pnpm store",
+            Self::GoModCache => "Go module cache",
+            Self::GoBuildCache => "Go build cache",
+        }
+    }
+
+    /// The owning tool's own command for cleaning this cache, if it has one.
+    ///
+    /// `pnpm store prune` only removes packages that no project references
+    /// anymore.
+    #[must_use]
+    pub const fn clean_command(self) -> Option<(&'static str, &'static [&'static str])> {
+        match self {
+            Self::PnpmStore => Some(("pnpm", &["store", "prune"])),
+            Self::GoModCache => Some(("go", &["clean", "-modcache"])),
+            Self::GoBuildCache => Some(("go", &["clean", "-cache"])),
+            _ => None,
+        }
+    }
+}
+
+/// A global cache directory and its size.
+#[derive(Clone, Debug)]
+pub struct CacheLocation {
+    /// What kind of cache this is
+    pub kind: CacheKind,
+
+    /// Absolute path to the cache directory
+    pub path: PathBuf,
+
+    /// Size of the cache directory in bytes
+    pub size: u64,
+}
+
+impl CacheLocation {
+    /// Create a new, not yet measured cache location.
+    #[must_use]
+    pub const fn new(kind: CacheKind, path: PathBuf) -> Self {
+        Self {
+            kind,
+            path,
+            size: 0,
+        }
+    }
+}
+
+impl fmt::Display for CacheLocation {
+    /// Format the cache location for display as `🗄️ <label> (<path>)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display().to_string();
+        #[cfg(feature = "cli")]
+        let path = path.dimmed();
+        write!(f, "🗄️  {} ({path})", self.kind.label())
+    }
+}
+
+/// Find the global Rust caches (Cargo registry, git checkouts and `sccache`).
+///
+/// Cargo's home is taken from `$CARGO_HOME`, falling back to `~/.cargo`; the
+/// `sccache` directory from `$SCCACHE_DIR`, falling back to the platform's
+/// default cache location.
+///
+/// # Returns
+///
+/// Every cache that exists and is not empty, with its size computed.
+#[must_use]
+pub fn cargo_cache_locations() -> Vec<CacheLocation> {
+    let mut candidates = cargo_home().map_or_else(Vec::new, |home| cargo_home_caches(&home));
+
+    if let Some(dir) = sccache_dir() {
+        candidates.push(CacheLocation::new(CacheKind::Sccache, dir));
+    }
+
+    measure_existing(candidates)
+}
+
+/// Find the global Node.js package manager caches (npm, yarn and pnpm).
+///
+/// - npm: `$npm_config_cache/_cacache`, falling back to `~/.npm/_cacache`
+///   (`%LOCALAPPDATA%\npm-cache\_cacache` on Windows)
+/// - yarn: `$YARN_CACHE_FOLDER`, falling back to yarn's platform default
+/// - pnpm: the output of `pnpm store path`, falling back to pnpm's platform
+///   default when `pnpm` is not installed
+///
+/// # Returns
+///
+/// Every cache that exists and is not empty, with its size computed.
+#[must_use]
+pub fn node_cache_locations() -> Vec<CacheLocation> {
+    let candidates = [
+        npm_cache_dir().map(|dir| CacheLocation::new(CacheKind::NpmCache, dir)),
+        yarn_cache_dir().map(|dir| CacheLocation::new(CacheKind::YarnCache, dir)),
+        pnpm_store_dir().map(|dir| CacheLocation::new(CacheKind::PnpmStore, dir)),
+    ];
+
+    measure_existing(candidates.into_iter().flatten().collect())
+}
+
+/// Find the global Go caches (module cache and build cache).
+///
+/// Locations are asked from `go env GOMODCACHE GOCACHE`; when `go` is not
+/// installed, `$GOMODCACHE` (or `$GOPATH/pkg/mod`, `~/go/pkg/mod`) and
+/// `$GOCACHE` (or the `go-build` directory in the platform's cache directory)
+/// are used instead.
+///
+/// # Returns
+///
+/// Every cache that exists and is not empty, with its size computed.
+#[must_use]
+pub fn go_cache_locations() -> Vec<CacheLocation> {
+    let (mod_cache, build_cache) =
+        go_env_dirs().unwrap_or_else(|| (go_mod_cache_dir(), go_build_cache_dir()));
+
+    let candidates = [
+        mod_cache.map(|dir| CacheLocation::new(CacheKind::GoModCache, dir)),
+        build_cache.map(|dir| CacheLocation::new(CacheKind::GoBuildCache, dir)),
+    ];
+
+    measure_existing(candidates.into_iter().flatten().collect())
+}
+
+/// The cache directories inside a Cargo home directory.
+///
+/// The returned locations are not measured and may not exist.
+#[must_use]
+pub fn cargo_home_caches(cargo_home: &Path) -> Vec<CacheLocation> {
+    vec![
+        CacheLocation::new(
+            CacheKind::CargoRegistryCache,
+            cargo_home.join("registry").join("cache"),
+        ),
+        CacheLocation::new(
+            CacheKind::CargoRegistrySrc,
+            cargo_home.join("registry").join("src"),
+        ),
+        CacheLocation::new(
+            CacheKind::CargoGitCheckouts,
+            cargo_home.join("git").join("checkouts"),
+        ),
+    ]
+}
+
+/// Keep the candidates that exist and compute their sizes in parallel.
+///
+/// Empty caches are dropped since there is nothing to reclaim.
+#[must_use]
+pub fn measure_existing(candidates: Vec<CacheLocation>) -> Vec<CacheLocation> {
+    candidates
+        .into_par_iter()
+        .filter(|cache| cache.path.is_dir())
+        .map(|mut cache| {
+            cache.size = directory_size(&cache.path);
+            cache
+        })
+        .filter(|cache| cache.size > 0)
+        .collect()
+}
+
+/// Resolve Cargo's home directory.
+fn cargo_home() -> Option<PathBuf> {
+    env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
+}
+
+/// Resolve the `sccache` cache directory.
+///
+/// Mirrors `sccache`'s own defaults: `~/.cache/sccache` on Linux,
+/// `~/Library/Caches/Mozilla.sccache` on macOS and
+/// `%LOCALAPPDATA%\Mozilla\sccache` on Windows.
+fn sccache_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("SCCACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+
+    let cache_dir = dirs::cache_dir()?;
+    if cfg!(target_os = "macos") {
+        Some(cache_dir.join("Mozilla.sccache"))
+    } else if cfg!(windows) {
+        Some(cache_dir.join("Mozilla").join("sccache"))
+    } else {
+        Some(cache_dir.join("sccache"))
+    }
+}
+
+/// Resolve npm's content-addressable cache directory.
+fn npm_cache_dir() -> Option<PathBuf> {
+    let root = env::var_os("npm_config_cache")
+        .map(PathBuf::from)
+        .or_else(|| {
+            if cfg!(windows) {
+                dirs::cache_dir().map(|dir| dir.join("npm-cache"))
+            } else {
+                dirs::home_dir().map(|home| home.join(".npm"))
+            }
+        })?;
+
+    Some(root.join("_cacache"))
+}
+
+/// Resolve the yarn cache directory.
+///
+/// Mirrors yarn's defaults: `~/.cache/yarn` on Linux,
+/// `~/Library/Caches/Yarn` on macOS and `%LOCALAPPDATA%\Yarn\Cache` on
+/// Windows.
+fn yarn_cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("YARN_CACHE_FOLDER") {
+        return Some(PathBuf::from(dir));
+    }
+
+    let cache_dir = dirs::cache_dir()?;
+    if cfg!(target_os = "macos") {
+        Some(cache_dir.join("Yarn"))
+    } else if cfg!(windows) {
+        Some(cache_dir.join("Yarn").join("Cache"))
+    } else {
+        Some(cache_dir.join("yarn"))
+    }
+}
+
+/// Resolve the pnpm store directory.
+///
+/// Asks `pnpm store path` first, since the store location depends on pnpm's
+/// configuration; otherwise falls back to pnpm's defaults:
+/// `~/.local/share/pnpm/store` on Linux, `~/Library/pnpm/store` on macOS and
+/// `%LOCALAPPDATA%\pnpm\store` on Windows.
+fn pnpm_store_dir() -> Option<PathBuf> {
+    if let Some(path) = command_stdout("pnpm", &["store", "path"]) {
+        return Some(PathBuf::from(path));
+    }
+
+    if cfg!(target_os = "macos") {
+        dirs::home_dir().map(|home| home.join("Library").join("pnpm").join("store"))
+    } else if cfg!(windows) {
+        dirs::data_local_dir().map(|dir| dir.join("pnpm").join("store"))
+    } else {
+        dirs::data_dir().map(|dir| dir.join("pnpm").join("store"))
+    }
+}
+
+/// Ask `go env` for the module and build cache directories.
+fn go_env_dirs() -> Option<(Option<PathBuf>, Option<PathBuf>)> {
+    let stdout = command_stdout("go", &["env", "GOMODCACHE", "GOCACHE"])?;
+    let mut lines = stdout.lines().map(str::trim);
+
+    let non_empty = |line: Option<&str>| line.filter(|line| !line.is_empty()).map(PathBuf::from);
+    Some((non_empty(lines.next()), non_empty(lines.next())))
+}
+
+/// Resolve the Go module cache without invoking `go`.
+fn go_mod_cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("GOMODCACHE") {
+        return Some(PathBuf::from(dir));
+    }
+
+    // GOPATH may list several directories; the module cache lives in the first
+    let gopath = env::var_os("GOPATH")
+        .and_then(|paths| env::split_paths(&paths).next())
+        .or_else(|| dirs::home_dir().map(|home| home.join("go")))?;
+
+    Some(gopath.join("pkg").join("mod"))
+}
+
+/// Resolve the Go build cache without invoking `go`.
+fn go_build_cache_dir() -> Option<PathBuf> {
+    env::var_os("GOCACHE")
+        .map(PathBuf::from)
+        .or_else(|| dirs::cache_dir().map(|dir| dir.join("go-build")))
+}
+
+/// Run `program` with `args` and return its trimmed standard output.
+///
+/// Returns `None` if the program cannot be run, fails, or prints nothing.
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|stdout| stdout.trim().to_string())
+        .filter(|stdout| !stdout.is_empty())
+}
+
+/// Sum the sizes of all files below `path`, skipping unreadable entries.
+fn directory_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cargo_home_caches_paths() {
+        let home = Path::new("/home/user/.cargo");
+        let caches = cargo_home_caches(home);
+
+        let paths: Vec<_> = caches.iter().map(|cache| cache.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                home.join("registry/cache"),
+                home.join("registry/src"),
+                home.join("git/checkouts"),
+            ]
+        );
+        assert!(caches.iter().all(|cache| cache.size == 0));
+    }
+
+    #[test]
+    fn test_measure_existing_drops_missing_and_empty_caches() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path();
+
+        fs::create_dir_all(home.join("registry/cache/index")).unwrap();
+        fs::write(home.join("registry/cache/index/serde.crate"), "crate").unwrap();
+        fs::create_dir_all(home.join("registry/src")).unwrap();
+        // git/checkouts does not exist
+
+        let caches = measure_existing(cargo_home_caches(home));
+
+        assert_eq!(caches.len(), 1);
+        assert_eq!(caches[0].kind, CacheKind::CargoRegistryCache);
+        assert_eq!(caches[0].size, 5);
+    }
+
+    #[test]
+    fn test_clean_commands() {
+        assert_eq!(
+            CacheKind::PnpmStore.clean_command(),
+            Some(("pnpm", &["store", "prune"][..]))
+        );
+        assert_eq!(
+            CacheKind::GoModCache.clean_command(),
+            Some(("go", &["clean", "-modcache"][..]))
+        );
+        assert_eq!(
+            CacheKind::GoBuildCache.clean_command(),
+            Some(("go", &["clean", "-cache"][..]))
+        );
+        assert_eq!(CacheKind::NpmCache.clean_command(), None);
+        assert_eq!(CacheKind::YarnCache.clean_command(), None);
+        assert_eq!(CacheKind::CargoRegistryCache.clean_command(), None);
+    }
+
+    #[test]
+    fn test_cache_kind_serializes_as_snake_case() {
+        let json = serde_json::to_string(&CacheKind::CargoGitCheckouts).unwrap();
+        assert_eq!(json, "\"cargo_git_checkouts\"");
+    }
+}
@@ -0,0 +1,110 @@
+//! Cooperative cancellation for long-running operations.
+//!
+//! This module provides the [`CancellationToken`] type, a cheap, cloneable
+//! flag shared between the code driving an operation (a GUI, a signal
+//! handler, …) and the operation itself. Cancellation is cooperative: the
+//! operation checks the token between units of work and stops starting new
+//! ones once it has been cancelled.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// A shared flag used to request cancellation of an in-progress operation.
+///
+/// Clones share the same underlying flag, so cancelling any clone cancels
+/// them all. A freshly created token is not cancelled.
+///
+/// # Examples
+///
+/// ```
+/// # use clean_dev_dirs::CancellationToken;
+/// let token = CancellationToken::new();
+/// let handle = token.clone();
+///
+/// handle.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a new, non-cancelled token.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of every operation observing this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Check whether cancellation has been requested.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Observe a flag the embedding application already has, e.g. one set by
+/// its own signal handler: storing `true` in it cancels the token.
+///
+/// # Examples
+///
+/// ```
+/// # use clean_dev_dirs::CancellationToken;
+/// # use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+/// let stop = Arc::new(AtomicBool::new(false));
+/// let token = CancellationToken::from(Arc::clone(&stop));
+///
+/// stop.store(true, Ordering::SeqCst);
+/// assert!(token.is_cancelled());
+/// ```
+impl From<Arc<AtomicBool>> for CancellationToken {
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        Self(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_token_is_not_cancelled() {
+        assert!(!CancellationToken::new().is_cancelled());
+    }
+
+    #[test]
+    fn test_cancel_is_shared_between_clones() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+
+        clone.cancel();
+
+        assert!(token.is_cancelled());
+        assert!(clone.is_cancelled());
+    }
+
+    #[test]
+    fn test_token_shares_an_existing_flag() {
+        let flag = Arc::new(AtomicBool::new(false));
+        let token = CancellationToken::from(Arc::clone(&flag));
+        assert!(!token.is_cancelled());
+
+        token.cancel();
+        assert!(flag.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_cancel_is_idempotent() {
+        let token = CancellationToken::new();
+
+        token.cancel();
+        token.cancel();
+
+        assert!(token.is_cancelled());
+    }
+}
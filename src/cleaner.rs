@@ -5,21 +5,36 @@
 //! reporting, error handling, and provides detailed statistics about the
 //! cleanup operation.
 
-use anyhow::Result;
-use colored::Colorize;
-use humansize::{DECIMAL, format_size};
-use indicatif::{ProgressBar, ProgressStyle};
+use chrono::{DateTime, Utc};
 use rayon::prelude::*;
-use std::fs;
-use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use crate::audit::{AuditLog, AuditOutcome, AuditRecord};
+use crate::cache::{CacheKind, CacheLocation};
+use crate::cancellation::CancellationToken;
+use crate::error::{Error, Result};
 use crate::executables;
-use crate::project::{Project, Projects};
+use crate::hooks::ProjectHooks;
+use crate::logging::RECORD;
+use crate::project::{ArtifactCategory, BuildArtifacts, Project, ProjectType, Projects};
+use crate::sweep::{self, StaleCriteria};
+use crate::utils::guard::DeletionGuard;
+use crate::utils::retry::{self, Leftover, RetryPolicy, RetryRecord, RetryTelemetry};
+use crate::utils::{format_size, paths};
+use crate::vfs::{EntryKind, FileSystem, FsHandle, RealFs};
 
 /// Strategy for removing build directories.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum RemovalStrategy {
-    /// Permanently delete the directory (default, uses `fs::remove_dir_all`).
+    /// Permanently delete the directory (default, see [`retry::remove_dir_all`]).
+    #[default]
     Permanent,
 
     /// Move the directory to the system trash (recoverable deletion).
@@ -38,6 +53,42 @@ impl RemovalStrategy {
     }
 }
 
+/// How Rust projects are cleaned with `cargo clean` (see `--native-clean`).
+///
+/// The default cleans the whole target directory; `release` and `packages`
+/// narrow `cargo clean` down to the release profile and specific packages.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NativeClean {
+    /// Only clean the release profile (`cargo clean --release`).
+    pub release: bool,
+
+    /// Only clean these packages (`cargo clean -p <spec>`).
+    pub packages: Vec<String>,
+}
+
+impl NativeClean {
+    /// Whether `cargo clean` is narrowed down to part of the target directory.
+    ///
+    /// Partial cleans never fall back to deleting the whole directory.
+    #[must_use]
+    pub const fn is_partial(&self) -> bool {
+        self.release || !self.packages.is_empty()
+    }
+
+    /// The arguments passed to `cargo`.
+    #[must_use]
+    pub fn cargo_args(&self) -> Vec<&str> {
+        let mut args = vec!["clean"];
+        if self.release {
+            args.push("--release");
+        }
+        for package in &self.packages {
+            args.extend(["-p", package.as_str()]);
+        }
+        args
+    }
+}
+
 /// Structured result returned after a cleanup operation.
 ///
 /// Contains all the data needed to render either human-readable or JSON output.
@@ -48,11 +99,301 @@ pub struct CleanResult {
     /// Total bytes actually freed during cleanup.
     pub total_freed: u64,
 
+    /// Bytes actually freed, per project type.
+    pub freed_by_type: HashMap<ProjectType, u64>,
+
     /// Estimated total size before cleanup (from cached scan data).
     pub estimated_size: u64,
 
-    /// Error messages for projects that failed to clean.
+    /// Error messages for the projects and caches that failed to clean,
+    /// the errors of `failed` and `failed_caches` with their causes.
     pub errors: Vec<String>,
+
+    /// Projects whose build directories could only be partly removed.
+    pub partial: Vec<PartialClean>,
+
+    /// Number of projects left untouched because the operation was cancelled.
+    pub cancelled_count: usize,
+
+    /// Projects left untouched because their `pre_project` hook failed or
+    /// one of their build directories is a mount point, with the reason.
+    pub skipped: Vec<String>,
+
+    /// Projects whose build directories were removed, with the number of
+    /// bytes freed. Caches count in `success_count` only.
+    pub cleaned: Vec<(Project, u64)>,
+
+    /// Projects whose build directories could not be removed, with the
+    /// error. Their messages are also in `errors`.
+    pub failed: Vec<(Project, Error)>,
+
+    /// Caches that could not be cleaned, with the error. Their messages are
+    /// also in `errors`.
+    pub failed_caches: Vec<(CacheLocation, Error)>,
+
+    /// Wall-clock time the cleanup took.
+    pub elapsed: Duration,
+
+    /// Paths whose removal failed transiently and had to be retried.
+    pub retried: Vec<RetryRecord>,
+
+    /// Artifact directories of the cleaned and partially cleaned projects,
+    /// in completion order. For cache cleanups, the removed caches, whose
+    /// project path is the cache itself.
+    pub removed: Vec<RemovedArtifact>,
+}
+
+/// An artifact directory removed (or partly removed) during a cleanup.
+#[derive(Clone, Debug, Serialize)]
+pub struct RemovedArtifact {
+    /// Root directory of the project the artifact belongs to.
+    pub project_path: PathBuf,
+
+    /// Path of the artifact directory.
+    pub artifact_path: PathBuf,
+
+    /// Size of the artifact directory when it was scanned, in bytes.
+    pub size: u64,
+
+    /// Time the project's cleanup finished.
+    pub removed_at: DateTime<Utc>,
+}
+
+/// A project whose build directories could only be partly removed.
+#[derive(Clone, Debug, Serialize)]
+pub struct PartialClean {
+    /// Root directory of the project.
+    pub root_path: PathBuf,
+
+    /// Number of bytes freed.
+    pub freed: u64,
+
+    /// Size of the project's build directories before cleaning.
+    pub size: u64,
+
+    /// Entries that could not be removed.
+    pub leftovers: Vec<PathBuf>,
+}
+
+impl CleanResult {
+    /// An empty result for a cleanup expected to free `estimated_size` bytes.
+    fn new(estimated_size: u64) -> Self {
+        Self {
+            success_count: 0,
+            total_freed: 0,
+            freed_by_type: HashMap::new(),
+            estimated_size,
+            errors: Vec::new(),
+            partial: Vec::new(),
+            cancelled_count: 0,
+            skipped: Vec::new(),
+            cleaned: Vec::new(),
+            failed: Vec::new(),
+            failed_caches: Vec::new(),
+            elapsed: Duration::ZERO,
+            retried: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+
+    /// Summarize the result on one line, counting `noun`s (e.g.
+    /// `"project"`): `cleaned 12 projects, 1 failed, freed 4.2 GB`.
+    #[must_use]
+    pub fn summary_line(&self, noun: &str) -> String {
+        let count = |count: usize| {
+            let plural = if count == 1 { "" } else { "s" };
+            format!("{count} {noun}{plural}")
+        };
+        let mut parts = vec![format!("cleaned {}", count(self.success_count))];
+        if !self.partial.is_empty() {
+            parts.push(format!("{} partially", self.partial.len()));
+        }
+        if !self.errors.is_empty() {
+            parts.push(format!("{} failed", self.errors.len()));
+        }
+        if self.cancelled_count > 0 {
+            parts.push(format!("{} cancelled", self.cancelled_count));
+        }
+        if !self.skipped.is_empty() {
+            parts.push(format!("{} skipped", self.skipped.len()));
+        }
+        parts.push(format!("freed {}", format_size(self.total_freed)));
+        parts.join(", ")
+    }
+
+    /// Record the artifacts of `project` as removed.
+    fn record_removed(&mut self, project: &Project) {
+        let removed_at = Utc::now();
+        self.removed
+            .extend(project.build_arts.iter().map(|artifact| RemovedArtifact {
+                project_path: project.root_path.clone(),
+                artifact_path: artifact.path.clone(),
+                size: artifact.size,
+                removed_at,
+            }));
+    }
+
+    /// Update the counters with the outcome carried by `event`.
+    fn record(&mut self, event: CleanEvent) {
+        match event {
+            CleanEvent::Started { .. } => {}
+            CleanEvent::ProjectCleaned { project, freed } => {
+                self.success_count += 1;
+                self.total_freed += freed;
+                *self.freed_by_type.entry(project.kind.clone()).or_default() += freed;
+                self.record_removed(&project);
+                self.cleaned.push((project, freed));
+            }
+            CleanEvent::ProjectPartiallyCleaned {
+                project,
+                freed,
+                leftovers,
+            } => {
+                self.total_freed += freed;
+                *self.freed_by_type.entry(project.kind.clone()).or_default() += freed;
+                self.partial.push(PartialClean {
+                    root_path: project.root_path.clone(),
+                    freed,
+                    size: project.total_size(),
+                    leftovers,
+                });
+                self.record_removed(&project);
+            }
+            CleanEvent::ProjectFailed { project, error } => {
+                self.errors.push(error.display_chain());
+                self.failed.push((project, error));
+            }
+            CleanEvent::ProjectCancelled { .. } => self.cancelled_count += 1,
+            CleanEvent::ProjectSkipped { project, reason } => self
+                .skipped
+                .push(format!("{}: {reason}", project.root_path.display())),
+        }
+    }
+}
+
+impl Default for CleanResult {
+    /// The result of a cleanup with nothing to clean.
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Options controlling how [`Cleaner::clean_with`] removes build directories.
+#[derive(Clone, Debug, Default)]
+pub struct CleanOptions {
+    /// Whether to preserve compiled executables before cleaning.
+    pub keep_executables: bool,
+
+    /// Whether to permanently delete or move to system trash.
+    pub removal_strategy: RemovalStrategy,
+
+    /// Token checked before each project is cleaned; once cancelled, the
+    /// remaining projects are reported as [`CleanEvent::ProjectCancelled`].
+    pub cancellation: CancellationToken,
+
+    /// Global cache kinds to clean with their tool's own command (see
+    /// [`CacheKind::clean_command`]) instead of deleting them. Only used by
+    /// [`Cleaner::clean_caches`].
+    pub tool_cleaned_caches: Vec<CacheKind>,
+
+    /// Clean Rust projects with `cargo clean` instead of deleting their
+    /// target directory. `None` deletes directly.
+    pub native_clean: Option<NativeClean>,
+
+    /// Only remove the stale artifacts inside Rust target directories (see
+    /// [`sweep::stale_entries`]) instead of the whole directory. `None`
+    /// removes everything.
+    pub stale_only: Option<StaleCriteria>,
+
+    /// How transient deletion failures (e.g. files briefly locked by an
+    /// antivirus scanner) are retried.
+    pub retry: RetryPolicy,
+
+    /// Last-resort check of every path right before it is removed; by
+    /// default, paths less than 3 levels deep and the home directory are
+    /// never deleted.
+    pub guard: DeletionGuard,
+
+    /// File system build directories are permanently deleted from; the
+    /// local one by default. Moving to the trash, `cargo clean` and
+    /// preserving executables always work on the local file system.
+    pub fs: FsHandle,
+
+    /// Commands run before and after cleaning each project. Not used by
+    /// [`Cleaner::clean_caches`].
+    pub hooks: ProjectHooks,
+
+    /// Measure each directory again right before removing it, instead of
+    /// reporting the size calculated by the scan as freed. Slower, as every
+    /// directory is walked twice, but accurate when builds ran in between.
+    pub recheck_size: bool,
+
+    /// Append-only log every build directory and cache is recorded in,
+    /// synced to disk before it is removed. `None` records nothing.
+    pub audit_log: Option<Arc<AuditLog>>,
+}
+
+/// Progress event emitted by [`Cleaner::clean_with`].
+///
+/// Exactly one `Started` event is emitted first, followed by one event per
+/// project, in completion order.
+pub enum CleanEvent {
+    /// Cleaning is about to start.
+    Started {
+        /// Number of projects that will be processed.
+        total_projects: usize,
+
+        /// Estimated number of bytes to free, from the scan data.
+        estimated_size: u64,
+    },
+
+    /// A project's build directories were removed.
+    ProjectCleaned {
+        /// The cleaned project.
+        project: Project,
+
+        /// Number of bytes freed.
+        freed: u64,
+    },
+
+    /// Some entries of a project's build directories could not be removed,
+    /// the others were.
+    ProjectPartiallyCleaned {
+        /// The partially cleaned project.
+        project: Project,
+
+        /// Number of bytes freed.
+        freed: u64,
+
+        /// Entries that could not be removed.
+        leftovers: Vec<PathBuf>,
+    },
+
+    /// A project's build directories could not be removed.
+    ProjectFailed {
+        /// The project that failed.
+        project: Project,
+
+        /// Why it failed; [`Error::io_error`] tells e.g. a permission
+        /// problem from a file in use.
+        error: Error,
+    },
+
+    /// A project was not cleaned because cancellation was requested.
+    ProjectCancelled {
+        /// The project that was left untouched.
+        project: Project,
+    },
+
+    /// A project was not cleaned because its `pre_project` hook failed or
+    /// one of its build directories is a mount point.
+    ProjectSkipped {
+        /// The project that was left untouched.
+        project: Project,
+
+        /// Why it was skipped.
+        reason: String,
+    },
 }
 
 /// Handles the cleanup of build directories from development projects.
@@ -82,25 +423,15 @@ impl Cleaner {
 
     /// Clean build directories from a collection of projects.
     ///
-    /// This method performs the main cleanup operation by:
-    /// 1. Setting up a progress bar for user feedback (unless `quiet`)
-    /// 2. Processing projects in parallel for efficiency
-    /// 3. Collecting and reporting any errors that occur
-    /// 4. Returning a [`CleanResult`] with detailed statistics
+    /// This method processes projects in parallel and returns a
+    /// [`CleanResult`] with detailed statistics. It prints nothing; use
+    /// [`Cleaner::clean_with`] to follow the cleanup as it goes, e.g. to draw
+    /// a progress bar.
     ///
     /// # Arguments
     ///
     /// * `projects` - A collection of projects to clean
-    /// * `keep_executables` - Whether to preserve compiled executables before cleaning
-    /// * `quiet` - When `true`, suppresses all human-readable output (progress bars, messages).
-    ///   Used by the `--json` flag so that only the final JSON is printed.
-    /// * `removal_strategy` - Whether to permanently delete or move to system trash
-    ///
-    /// # Panics
-    ///
-    /// This method may panic if the progress bar template string is invalid,
-    /// though this should not occur under normal circumstances as the template
-    /// is hardcoded and valid.
+    /// * `options` - Executable preservation, removal strategy, and cancellation token
     ///
     /// # Returns
     ///
@@ -118,159 +449,510 @@ impl Cleaner {
     /// Individual project cleanup failures do not stop the overall process.
     /// All errors are collected and reported in the returned [`CleanResult`],
     /// allowing the cleanup to proceed for projects that can be successfully processed.
+    ///
+    /// # Cancellation
+    ///
+    /// Once `options.cancellation` is cancelled, no further projects are
+    /// started; the returned [`CleanResult`] covers the projects that were
+    /// cleaned so far and counts the rest in `cancelled_count`.
     #[must_use]
-    pub fn clean_projects(
+    pub fn clean_projects(projects: Projects, options: &CleanOptions) -> CleanResult {
+        Self::clean_with(projects, options, |_| {})
+    }
+
+    /// Clean build directories, reporting progress through a callback.
+    ///
+    /// This is the observable counterpart of [`Cleaner::clean_projects`]: it
+    /// calls `on_event` with a [`CleanEvent`] for
+    /// every step, so that GUI front-ends can drive their own progress bars.
+    /// Projects are still cleaned in parallel, but `on_event` is always called
+    /// on the calling thread and therefore does not need to be `Send`.
+    ///
+    /// Cancelling `options.cancellation` stops new projects from being
+    /// cleaned; projects already being removed are finished, and the returned
+    /// [`CleanResult`] reflects only the work that was actually done.
+    ///
+    /// # Arguments
+    ///
+    /// * `projects` - A collection of projects to clean
+    /// * `options` - Executable preservation, removal strategy, and cancellation token
+    /// * `on_event` - Callback invoked for each [`CleanEvent`]
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use clean_dev_dirs::{CleanEvent, CleanOptions, Cleaner, Projects};
+    /// # fn example(projects: Projects) {
+    /// let options = CleanOptions::default();
+    /// let result = Cleaner::clean_with(projects, &options, |event| {
+    ///     if let CleanEvent::ProjectCleaned { project, freed } = event {
+    ///         println!("{} freed {freed} bytes", project.root_path.display());
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    pub fn clean_with(
         projects: Projects,
-        keep_executables: bool,
-        quiet: bool,
-        removal_strategy: RemovalStrategy,
+        options: &CleanOptions,
+        mut on_event: impl FnMut(&CleanEvent),
     ) -> CleanResult {
-        let total_projects = projects.len();
-        let total_size: u64 = projects.get_total_size();
+        let started = Instant::now();
+        let estimated_size = projects.get_total_size();
+        let mut result = CleanResult::new(estimated_size);
+        let telemetry = RetryTelemetry::new();
 
-        let progress = if quiet {
-            ProgressBar::hidden()
-        } else {
-            let action = match removal_strategy {
-                RemovalStrategy::Permanent => "🧹 Starting cleanup...",
-                RemovalStrategy::Trash => "🗑️  Moving to trash...",
-            };
-            println!("\n{}", action.cyan());
-
-            let pb = ProgressBar::new(total_projects as u64);
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg}")
-                    .unwrap()
-                    .progress_chars("█▉▊▋▌▍▎▏  "),
-            );
-            pb
-        };
+        on_event(&CleanEvent::Started {
+            total_projects: projects.len(),
+            estimated_size,
+        });
+
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            // Clean projects in parallel, forwarding each outcome to the calling thread
+            let telemetry = &telemetry;
+            scope.spawn(move || {
+                projects
+                    .into_par_iter()
+                    .for_each_with(sender, |sender, project| {
+                        let event = clean_project_event(project, options, telemetry);
+                        // The receiver outlives this scope, so sending cannot fail
+                        let _ = sender.send(event);
+                    });
+            });
 
-        let cleaned_size = Arc::new(Mutex::new(0u64));
-        let errors = Arc::new(Mutex::new(Vec::new()));
+            for event in receiver {
+                on_event(&event);
+                result.record(event);
+            }
+        });
 
-        // Clean projects in parallel
-        projects.into_par_iter().for_each(|project| {
-            let result = clean_single_project(&project, keep_executables, removal_strategy);
+        result.elapsed = started.elapsed();
+        result.retried = telemetry.take();
+        result
+    }
 
-            let action = match removal_strategy {
-                RemovalStrategy::Permanent => "Cleaned",
-                RemovalStrategy::Trash => "Trashed",
+    /// List every file that cleaning the given project would delete.
+    ///
+    /// Every build directory is walked recursively and all non-directory
+    /// entries (regular files and symlinks) are returned in a stable,
+    /// file-name-sorted order. This backs `--dry-run --list-files` for users
+    /// who need to audit exactly what a cleanup removes.
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - The project whose build directories should be listed
+    ///
+    /// # Returns
+    ///
+    /// The paths of all files inside the build directories. Entries that cannot
+    /// be read are skipped; a missing build directory yields no entries.
+    #[must_use]
+    pub fn list_files(project: &Project) -> Vec<PathBuf> {
+        project
+            .artifact_paths()
+            .flat_map(|path| {
+                walkdir::WalkDir::new(path)
+                    .sort_by_file_name()
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .filter(|entry| !entry.file_type().is_dir())
+                    .map(walkdir::DirEntry::into_path)
+            })
+            .collect()
+    }
+
+    /// Remove global cache directories.
+    ///
+    /// Caches are removed one after the other according to
+    /// `options.removal_strategy`; `options.keep_executables` does not apply.
+    /// Caches whose kind is listed in `options.tool_cleaned_caches` are
+    /// cleaned by running their tool's own command instead.
+    /// Failures are collected in the returned [`CleanResult`] rather than
+    /// stopping the operation, and once `options.cancellation` is cancelled
+    /// the remaining caches are counted in `cancelled_count`.
+    ///
+    /// # Arguments
+    ///
+    /// * `caches` - The cache locations to remove
+    /// * `options` - Removal strategy and cancellation token
+    #[must_use]
+    pub fn clean_caches(caches: &[CacheLocation], options: &CleanOptions) -> CleanResult {
+        let started = Instant::now();
+        let mut result = CleanResult::new(caches.iter().map(|cache| cache.size).sum());
+        let telemetry = RetryTelemetry::new();
+
+        for cache in caches {
+            if options.cancellation.is_cancelled() {
+                result.cancelled_count += 1;
+                continue;
+            }
+
+            let tool_cleaned = options.tool_cleaned_caches.contains(&cache.kind);
+            let outcome = if tool_cleaned {
+                clean_cache_with_tool(cache)
+            } else {
+                let record = |outcome| AuditRecord::new(&cache.path, cache.size, None, outcome);
+                let outcome = audit(options, || vec![record(AuditOutcome::Started)])
+                    .and_then(|()| remove_build_dir(&cache.path, cache.size, options, &telemetry))
+                    .and_then(Removal::into_freed);
+                let audited = audit(options, || {
+                    vec![match &outcome {
+                        Ok(_) => record(removed_outcome(options.removal_strategy)),
+                        Err(e) => record(AuditOutcome::Failed).with_error(e.display_chain()),
+                    }]
+                });
+                if let Err(e) = audited {
+                    tracing::warn!("{}", e.display_chain());
+                }
+                outcome
             };
 
-            match result {
-                Ok(freed_size) => {
-                    *cleaned_size.lock().unwrap() += freed_size;
-
-                    progress.set_message(format!(
-                        "{action} {} ({})",
-                        project
-                            .root_path
-                            .file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or("unknown"),
-                        format_size(freed_size, DECIMAL)
-                    ));
+            match outcome {
+                Ok(freed) => {
+                    tracing::info!(
+                        target: RECORD,
+                        path = %cache.path.display(),
+                        size = freed,
+                        "{}",
+                        if tool_cleaned {
+                            "cleaned"
+                        } else {
+                            removal_verb(options.removal_strategy)
+                        }
+                    );
+                    result.success_count += 1;
+                    result.total_freed += freed;
+                    if !tool_cleaned {
+                        result.removed.push(RemovedArtifact {
+                            project_path: cache.path.clone(),
+                            artifact_path: cache.path.clone(),
+                            size: freed,
+                            removed_at: Utc::now(),
+                        });
+                    }
                 }
                 Err(e) => {
-                    errors.lock().unwrap().push(format!(
-                        "Failed to clean {}: {e}",
-                        project.build_arts.path.display()
-                    ));
+                    let error = Error::Clean {
+                        path: cache.path.clone(),
+                        source: Box::new(e),
+                    };
+                    tracing::error!(target: RECORD, "{}", error.display_chain());
+                    result.errors.push(error.display_chain());
+                    result.failed_caches.push((cache.clone(), error));
                 }
             }
+        }
 
-            progress.inc(1);
-        });
+        result.elapsed = started.elapsed();
+        result.retried = telemetry.take();
+        result
+    }
+}
 
-        let finish_msg = match removal_strategy {
-            RemovalStrategy::Permanent => "✅ Cleanup complete",
-            RemovalStrategy::Trash => "✅ Moved to trash",
+/// Clean a single project according to `options` and describe the outcome.
+fn clean_project_event(
+    project: Project,
+    options: &CleanOptions,
+    telemetry: &RetryTelemetry,
+) -> CleanEvent {
+    if options.cancellation.is_cancelled() {
+        return CleanEvent::ProjectCancelled { project };
+    }
+    // Removing a mounted volume's contents would reach beyond the project,
+    // and so would `cargo clean` in a mounted target directory
+    let mounted = project
+        .artifact_paths()
+        .find(|path| options.fs.is_mount_point(path))
+        .map(|path| {
+            format!(
+                "{} is a mount point (e.g. a bind mount or a docker volume)",
+                path.display()
+            )
+        });
+    if let Some(reason) = mounted {
+        let event = CleanEvent::ProjectSkipped { project, reason };
+        log_outcome(&event, options.removal_strategy);
+        return event;
+    }
+    if let Err(e) = options.hooks.run_pre(&project) {
+        let event = CleanEvent::ProjectSkipped {
+            project,
+            reason: e.display_chain(),
         };
-        progress.finish_with_message(finish_msg);
+        log_outcome(&event, options.removal_strategy);
+        return event;
+    }
 
-        let final_cleaned_size = *cleaned_size.lock().unwrap();
-        let errors = Arc::try_unwrap(errors)
-            .expect("all parallel tasks should be complete")
-            .into_inner()
-            .unwrap();
+    // Only the directories about to be removed are recorded
+    let audited: Vec<BuildArtifacts> = if options.audit_log.is_some() {
+        project
+            .build_arts
+            .iter()
+            .filter(|artifact| options.fs.exists(&artifact.path))
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let outcome = audit(options, || {
+        audited
+            .iter()
+            .map(|artifact| {
+                AuditRecord::new(
+                    &artifact.path,
+                    artifact.size,
+                    Some(&project),
+                    AuditOutcome::Started,
+                )
+            })
+            .collect()
+    })
+    .and_then(|()| clean_single_project(&project, options, telemetry));
+    if let Err(e) = options.hooks.run_post(&project) {
+        tracing::warn!("{}: {}", project.root_path.display(), e.display_chain());
+    }
+    let outcome = outcome.and_then(|removal| removal.or_failed(&project.root_path));
+    let audited = audit(options, || {
+        audit_outcomes(&project, &outcome, &audited, options.removal_strategy)
+    });
+    let event = match outcome {
+        Ok(removal) if removal.leftovers.is_empty() => CleanEvent::ProjectCleaned {
+            project,
+            freed: removal.freed,
+        },
+        Ok(removal) => CleanEvent::ProjectPartiallyCleaned {
+            project,
+            freed: removal.freed,
+            leftovers: removal
+                .leftovers
+                .into_iter()
+                .map(|leftover| leftover.path)
+                .collect(),
+        },
+        Err(error) => CleanEvent::ProjectFailed { project, error },
+    };
+    log_outcome(&event, options.removal_strategy);
+    if let Err(e) = audited {
+        tracing::warn!("{}", e.display_chain());
+    }
+    event
+}
 
-        let success_count = total_projects - errors.len();
+/// Append the records built by `records` to the audit log of `options`, if
+/// any.
+fn audit(options: &CleanOptions, records: impl FnOnce() -> Vec<AuditRecord>) -> Result<()> {
+    match &options.audit_log {
+        Some(log) => log.append(&records()),
+        None => Ok(()),
+    }
+}
 
-        CleanResult {
-            success_count,
-            total_freed: final_cleaned_size,
-            estimated_size: total_size,
-            errors,
+/// The audit records of the outcome of cleaning a project: the entries it
+/// removed, or the `audited` build directories when it failed.
+fn audit_outcomes(
+    project: &Project,
+    outcome: &Result<Removal>,
+    audited: &[BuildArtifacts],
+    removal_strategy: RemovalStrategy,
+) -> Vec<AuditRecord> {
+    let record = |artifact: &BuildArtifacts, outcome| {
+        AuditRecord::new(&artifact.path, artifact.size, Some(project), outcome)
+    };
+    let removal = match outcome {
+        Ok(removal) => removal,
+        Err(e) => {
+            return audited
+                .iter()
+                .map(|artifact| {
+                    record(artifact, AuditOutcome::Failed).with_error(e.display_chain())
+                })
+                .collect();
         }
+    };
+
+    removal
+        .removed
+        .iter()
+        .map(|removed| {
+            let left_behind = removal
+                .leftovers
+                .iter()
+                .any(|leftover| leftover.path.starts_with(&removed.artifact.path));
+            if removed.partly || left_behind {
+                record(&removed.artifact, AuditOutcome::PartiallyDeleted)
+            } else {
+                record(&removed.artifact, removed_outcome(removal_strategy))
+            }
+        })
+        .collect()
+}
+
+/// How removing a directory with `removal_strategy` is audited.
+const fn removed_outcome(removal_strategy: RemovalStrategy) -> AuditOutcome {
+    match removal_strategy {
+        RemovalStrategy::Permanent => AuditOutcome::Deleted,
+        RemovalStrategy::Trash => AuditOutcome::Trashed,
     }
+}
 
-    /// Print a human-readable cleanup summary to stdout.
-    ///
-    /// This is called from `main` when `--json` is **not** active.
-    pub fn print_summary(result: &CleanResult) {
-        if !result.errors.is_empty() {
-            println!("\n{}", "⚠️  Some errors occurred during cleanup:".yellow());
-            for error in &result.errors {
-                eprintln!("  {}", error.red());
+/// How removing a directory with `removal_strategy` is logged.
+const fn removal_verb(removal_strategy: RemovalStrategy) -> &'static str {
+    match removal_strategy {
+        RemovalStrategy::Permanent => "deleted",
+        RemovalStrategy::Trash => "trashed",
+    }
+}
+
+/// Record the outcome of cleaning a project in the log file.
+fn log_outcome(event: &CleanEvent, removal_strategy: RemovalStrategy) {
+    let removed = removal_verb(removal_strategy);
+    match event {
+        CleanEvent::ProjectCleaned { project, .. } => {
+            for artifact in &project.build_arts {
+                tracing::info!(
+                    target: RECORD,
+                    path = %artifact.path.display(),
+                    size = artifact.size,
+                    "{removed}"
+                );
             }
         }
+        CleanEvent::ProjectPartiallyCleaned {
+            project,
+            freed,
+            leftovers,
+        } => {
+            tracing::warn!(
+                target: RECORD,
+                path = %project.root_path.display(),
+                freed,
+                "partially {removed}"
+            );
+            for leftover in leftovers {
+                tracing::warn!(target: RECORD, path = %leftover.display(), "left behind");
+            }
+        }
+        CleanEvent::ProjectFailed { error, .. } => {
+            tracing::error!(target: RECORD, "{}", error.display_chain());
+        }
+        CleanEvent::ProjectSkipped { project, reason } => tracing::info!(
+            target: RECORD,
+            path = %project.root_path.display(),
+            reason,
+            "skipped"
+        ),
+        CleanEvent::Started { .. } | CleanEvent::ProjectCancelled { .. } => {}
+    }
+}
 
-        println!("\n{}", "📊 Cleanup Summary:".bold());
-        println!(
-            "  ✅ Successfully cleaned: {} projects",
-            result.success_count.to_string().green()
-        );
+/// Outcome of removing build directories.
+#[derive(Default)]
+struct Removal {
+    /// Number of bytes freed.
+    freed: u64,
 
-        if !result.errors.is_empty() {
-            println!(
-                "  ❌ Failed to clean: {} projects",
-                result.errors.len().to_string().red()
-            );
+    /// Entries that could not be removed.
+    leftovers: Vec<Leftover>,
+
+    /// Build directories removed, or with `--stale-only` the stale entries
+    /// removed from them, with their sizes as scanned.
+    removed: Vec<RemovedEntry>,
+}
+
+/// A build directory, or an entry of one, that was removed.
+struct RemovedEntry {
+    /// Its path and size.
+    artifact: BuildArtifacts,
+
+    /// Whether only part of it was to be removed (`cargo clean --release`
+    /// or `-p`).
+    partly: bool,
+}
+
+impl Removal {
+    /// A removal that left nothing behind.
+    const fn complete(freed: u64) -> Self {
+        Self {
+            freed,
+            leftovers: Vec::new(),
+            removed: Vec::new(),
         }
+    }
 
-        println!(
-            "  💾 Total space freed: {}",
-            format_size(result.total_freed, DECIMAL)
-                .bright_green()
-                .bold()
-        );
+    /// Return the number of bytes freed, or an error if anything was left
+    /// behind.
+    fn into_freed(mut self) -> Result<u64> {
+        self.take_leftovers_error().map_or(Ok(self.freed), Err)
+    }
 
-        if result.total_freed != result.estimated_size {
-            let difference = result.estimated_size.abs_diff(result.total_freed);
-            println!(
-                "  📋 Difference from estimate: {}",
-                format_size(difference, DECIMAL).yellow()
-            );
+    /// Fail when entries were left behind and nothing could be removed;
+    /// when something was, the project is not counted as failed.
+    fn or_failed(mut self, root: &Path) -> Result<Self> {
+        if self.freed > 0 {
+            return Ok(self);
+        }
+        match self.take_leftovers_error() {
+            None => Ok(self),
+            Some(e) => Err(Error::Clean {
+                path: root.to_path_buf(),
+                source: Box::new(e),
+            }),
         }
     }
+
+    /// The error describing the entries left behind, if any, which are
+    /// taken out of the removal.
+    fn take_leftovers_error(&mut self) -> Option<Error> {
+        let leftovers = std::mem::take(&mut self.leftovers);
+        let count = leftovers.len();
+        leftovers.into_iter().next().map(|first| Error::Leftovers {
+            count,
+            first: first.path,
+            first_error: first.error,
+        })
+    }
+
+    /// Record `artifact` as removed.
+    fn push_removed(&mut self, artifact: &BuildArtifacts, partly: bool) {
+        self.removed.push(RemovedEntry {
+            artifact: artifact.clone(),
+            partly,
+        });
+    }
 }
 
-/// Clean the build directory for a single project.
+/// Clean the build directories for a single project.
 ///
-/// This function handles the cleanup of an individual project's build directory.
-/// It calculates the actual size before deletion and then removes the entire
+/// This function handles the cleanup of an individual project's build directories.
+/// It calculates the actual size before deletion and then removes each entire
 /// directory tree, either permanently or by moving it to the system trash.
 ///
 /// # Arguments
 ///
 /// * `project` - The project whose build directory should be cleaned
-/// * `keep_executables` - Whether to preserve compiled executables before cleaning
-/// * `removal_strategy` - Whether to permanently delete or move to system trash
+/// * `options` - Executable preservation, removal strategy, and native clean settings
+/// * `telemetry` - Collector for paths whose removal had to be retried
 ///
 /// # Returns
 ///
-/// - `Ok(u64)` - The number of bytes freed by the cleanup
-/// - `Err(anyhow::Error)` - If the cleanup operation failed
+/// - `Ok(Removal)` - The number of bytes freed and the entries left behind
+/// - `Err(Error)` - If the cleanup operation failed
 ///
 /// # Behavior
 ///
-/// 1. Checks if the build directory exists (returns 0 if not)
+/// 1. Checks if any build directory exists (returns 0 if not)
 /// 2. Optionally preserves compiled executables
-/// 3. Calculates the actual size of the directory before deletion
-/// 4. Removes the directory (permanently or via trash, based on `removal_strategy`)
-/// 5. Returns the amount of space freed
+/// 3. For Rust projects with `options.native_clean`, runs `cargo clean`; if it
+///    fails (and the clean is not partial), falls back to the next steps
+/// 4. Calculates the actual size of each directory before deletion
+/// 5. Removes each directory (permanently or via trash, based on `removal_strategy`)
+/// 6. Returns the amount of space freed
+///
+/// Entries that cannot be deleted are skipped, and the rest of the
+/// directories are still removed. Moving to the trash cannot be partial: it
+/// stops at the first directory that cannot be trashed, and the error names
+/// that directory.
 ///
 /// # Error Conditions
 ///
@@ -281,107 +963,286 @@ impl Cleaner {
 /// - The system trash is not available (when using [`RemovalStrategy::Trash`])
 fn clean_single_project(
     project: &Project,
-    keep_executables: bool,
-    removal_strategy: RemovalStrategy,
-) -> Result<u64> {
-    let build_dir = &project.build_arts.path;
-
-    if !build_dir.exists() {
-        return Ok(0);
+    options: &CleanOptions,
+    telemetry: &RetryTelemetry,
+) -> Result<Removal> {
+    if !project.artifact_paths().any(|path| options.fs.exists(path)) {
+        return Ok(Removal::default());
     }
 
     // Preserve executables before deletion if requested
-    if keep_executables {
+    if options.keep_executables {
         match executables::preserve_executables(project) {
             Ok(preserved) => {
                 if !preserved.is_empty() {
-                    eprintln!(
-                        "  Preserved {} executable(s) from {}",
+                    tracing::info!(
+                        "Preserved {} executable(s) from {}",
                         preserved.len(),
-                        project
-                            .root_path
-                            .file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or("unknown")
+                        paths::display_name(&project.root_path)
                     );
                 }
             }
-            Err(e) => {
-                eprintln!(
-                    "  Warning: failed to preserve executables for {}: {e}",
-                    project.root_path.display()
-                );
+            Err(e) => tracing::warn!(
+                "Failed to preserve executables for {}: {}",
+                project.root_path.display(),
+                e.display_chain()
+            ),
+        }
+    }
+
+    if project.kind == ProjectType::Rust
+        && let Some(criteria) = &options.stale_only
+    {
+        return remove_stale_artifacts(project, criteria, options, telemetry);
+    }
+
+    let mut removal = Removal::default();
+    // Cache artifacts are not cargo's to clean, so they are always deleted
+    let mut build_cleaned = false;
+
+    if project.kind == ProjectType::Rust
+        && let Some(native) = &options.native_clean
+    {
+        match cargo_clean(project, native) {
+            Ok(cleaned) => {
+                removal.freed = cleaned;
+                build_cleaned = true;
+                for artifact in project.build_arts.iter().filter(|artifact| {
+                    artifact.category() != ArtifactCategory::Cache
+                        && options.fs.exists(&artifact.path)
+                }) {
+                    removal.push_removed(artifact, native.is_partial());
+                }
             }
+            Err(e) if native.is_partial() => return Err(e),
+            Err(e) => tracing::warn!(
+                "{}; deleting {} directly",
+                e.display_chain(),
+                project.root_path.display()
+            ),
         }
     }
 
-    // Get the actual size before deletion (might be different from the cached size)
-    let actual_size = calculate_directory_size(build_dir);
+    let build_arts = project
+        .build_arts
+        .iter()
+        .filter(|artifact| !build_cleaned || artifact.category() == ArtifactCategory::Cache)
+        .filter(|artifact| options.fs.exists(&artifact.path));
 
-    // Remove the build directory using the chosen strategy
-    match removal_strategy {
-        RemovalStrategy::Permanent => fs::remove_dir_all(build_dir)?,
-        RemovalStrategy::Trash => {
-            trash::delete(build_dir)
-                .map_err(|e| anyhow::anyhow!("failed to move to trash: {e}"))?;
+    for artifact in build_arts {
+        if options
+            .fs
+            .entry_kind(&artifact.path)
+            .is_ok_and(|kind| kind == EntryKind::Other)
+        {
+            tracing::info!(
+                "{} is a symbolic link: removing the link, not what it points to",
+                artifact.path.display()
+            );
         }
+        let dir_removal = options
+            .guard
+            .check_artifact(&*options.fs, project, &artifact.path)
+            .and_then(|()| remove_build_dir(&artifact.path, artifact.size, options, telemetry))
+            .map_err(|e| Error::Clean {
+                path: artifact.path.clone(),
+                source: Box::new(e),
+            })?;
+        removal.freed += dir_removal.freed;
+        removal.leftovers.extend(dir_removal.leftovers);
+        removal.push_removed(artifact, false);
     }
 
-    Ok(actual_size)
+    Ok(removal)
 }
 
-/// Calculate the total size of a directory and all its contents.
-///
-/// This function recursively traverses a directory tree and sums up the sizes
-/// of all files within it. It handles errors gracefully by skipping files
-/// that cannot be accessed.
-///
-/// # Arguments
+/// Run `cargo clean` in a Rust project and return the number of bytes freed.
 ///
-/// * `path` - The directory path to measure
-///
-/// # Returns
-///
-/// The total size of all files in the directory tree, in bytes.
-///
-/// # Error Handling
-///
-/// This function is designed to be robust and will continue processing even
-/// if individual files cannot be accessed. It silently skips:
-/// - Files that cannot be read due to permission issues
-/// - Broken symbolic links
-/// - Files that are deleted while the scan is in progress
-///
-/// # Performance
-///
-/// This function can be I/O intensive for large directories with many files.
-/// It processes files sequentially within each directory but may be called
-/// in parallel for different directories by the cleanup process.
-///
-/// # Examples
+/// The freed size is measured as the difference between the build
+/// directories' sizes before and after, so partial cleans are accounted for.
+fn cargo_clean(project: &Project, native: &NativeClean) -> Result<u64> {
+    let before: u64 = project
+        .artifact_paths()
+        .map(|path| RealFs.tree_size(path))
+        .sum();
+
+    let output = Command::new("cargo")
+        .args(native.cargo_args())
+        .current_dir(&project.root_path)
+        .output()
+        .map_err(|source| Error::Spawn {
+            command: "cargo clean".to_string(),
+            source,
+        })?;
+
+    if !output.status.success() {
+        return Err(Error::Command {
+            command: "cargo clean".to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    let after: u64 = project
+        .artifact_paths()
+        .map(|path| RealFs.tree_size(path))
+        .sum();
+    Ok(before.saturating_sub(after))
+}
+
+/// Remove the stale artifacts of a Rust project, which are returned as
+/// removed with their total size freed.
+fn remove_stale_artifacts(
+    project: &Project,
+    criteria: &StaleCriteria,
+    options: &CleanOptions,
+    telemetry: &RetryTelemetry,
+) -> Result<Removal> {
+    let stale: Vec<_> = project
+        .artifact_paths()
+        .flat_map(|target| sweep::stale_entries(target, criteria))
+        .collect();
+    let freed = stale.iter().map(|entry| entry.size).sum();
+    for target in project.artifact_paths() {
+        options
+            .guard
+            .check_artifact(&*options.fs, project, target)?;
+    }
+    for entry in &stale {
+        options.guard.check(&*options.fs, &entry.path)?;
+    }
+
+    match options.removal_strategy {
+        RemovalStrategy::Permanent => {
+            for entry in &stale {
+                // Report the entry that stayed locked, not the stale entry
+                // holding it
+                retry::remove_path(&entry.path, &options.retry, telemetry).map_err(
+                    |Leftover { path, error }| Error::File {
+                        action: "remove",
+                        path,
+                        source: error,
+                    },
+                )?;
+            }
+        }
+        RemovalStrategy::Trash => {
+            telemetry
+                .run(
+                    &project.root_path,
+                    &options.retry,
+                    is_transient_trash_error,
+                    || trash::delete_all(stale.iter().map(|entry| &entry.path)),
+                )
+                .map_err(|source| Error::Trash {
+                    action: "move to",
+                    source,
+                })?;
+        }
+    }
+
+    let mut removal = Removal::complete(freed);
+    for entry in &stale {
+        removal.push_removed(
+            &BuildArtifacts {
+                path: entry.path.clone(),
+                size: entry.size,
+            },
+            false,
+        );
+    }
+    Ok(removal)
+}
+
+/// Remove a single build directory and return the bytes freed.
 ///
-/// ```
-/// # use std::path::Path;
-/// # use crate::calculate_directory_size;
-/// let size = calculate_directory_size(Path::new("/path/to/directory"));
-/// println!("Directory size: {} bytes", size);
-/// ```
-fn calculate_directory_size(path: &std::path::Path) -> u64 {
-    let mut total_size = 0u64;
-
-    for entry in walkdir::WalkDir::new(path) {
-        if let Ok(entry) = entry {
-            if entry.file_type().is_file()
-                && let Ok(metadata) = entry.metadata()
-            {
-                total_size += metadata.len();
+/// The directory is assumed to hold `scanned_size` bytes, as measured by the
+/// scan, unless `options.recheck_size` is set. Transient failures are retried according to `options.retry`, and the
+/// retried paths recorded in `telemetry`. When deleting permanently, entries
+/// that cannot be removed are skipped and returned as leftovers.
+fn remove_build_dir(
+    build_dir: &Path,
+    scanned_size: u64,
+    options: &CleanOptions,
+    telemetry: &RetryTelemetry,
+) -> Result<Removal> {
+    options.guard.check(&*options.fs, build_dir)?;
+
+    // The scan already walked the directory; only walk it again if asked to
+    let actual_size = if options.recheck_size {
+        options.fs.tree_size(build_dir)
+    } else {
+        scanned_size
+    };
+
+    // Remove the build directory using the chosen strategy
+    match options.removal_strategy {
+        RemovalStrategy::Permanent => {
+            let leftovers =
+                retry::remove_dir_all_lenient(&*options.fs, build_dir, &options.retry, telemetry);
+            if !leftovers.is_empty() {
+                let remaining = options.fs.tree_size(build_dir);
+                return Ok(Removal {
+                    freed: actual_size.saturating_sub(remaining),
+                    leftovers,
+                    removed: Vec::new(),
+                });
             }
-        } else {
-            // Skip errors for individual files
         }
+        RemovalStrategy::Trash => {
+            telemetry
+                .run(build_dir, &options.retry, is_transient_trash_error, || {
+                    trash::delete(build_dir)
+                })
+                .map_err(|source| Error::Trash {
+                    action: "move to",
+                    source,
+                })?;
+        }
+    }
+
+    Ok(Removal::complete(actual_size))
+}
+
+/// Check whether moving to the trash failed for a reason worth retrying.
+fn is_transient_trash_error(error: &trash::Error) -> bool {
+    match error {
+        trash::Error::Os { code, .. } => retry::is_transient(&io::Error::from_raw_os_error(*code)),
+        #[cfg(all(
+            unix,
+            not(target_os = "macos"),
+            not(target_os = "ios"),
+            not(target_os = "android")
+        ))]
+        trash::Error::FileSystem { source, .. } => retry::is_transient(source),
+        _ => false,
+    }
+}
+
+/// Clean a cache with its tool's own command and return the bytes freed.
+fn clean_cache_with_tool(cache: &CacheLocation) -> Result<u64> {
+    let Some((program, args)) = cache.kind.clean_command() else {
+        return Err(Error::Other(format!(
+            "{} has no clean command",
+            cache.kind.label()
+        )));
+    };
+
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|source| Error::Spawn {
+            command: format!("{program} {}", args.join(" ")),
+            source,
+        })?;
+
+    if !output.status.success() {
+        return Err(Error::Command {
+            command: format!("{program} {}", args.join(" ")),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
     }
 
-    total_size
+    Ok(cache.size.saturating_sub(RealFs.tree_size(&cache.path)))
 }
 
 impl Default for Cleaner {
@@ -6,21 +6,118 @@
 //!
 //! Helper methods on [`Cli`] accept a [`FileConfig`] reference so that config-file
 //! values act as defaults that CLI arguments can override (layered config).
+//! Every option can also be set with a `CLEAN_DEV_DIRS_*` environment
+//! variable, which sits between the two layers.
+
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::ArgAction;
+use clap::builder::BoolishValueParser;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use serde::Serialize;
+use toml::Value;
+
+use clean_dev_dirs::cleaner::NativeClean;
+use clean_dev_dirs::config::file::{
+    FileConfig, FileExecutionConfig, FileFilterConfig, FileScanConfig, LocalFilterConfig,
+    expand_tilde,
+};
+use clean_dev_dirs::config::filter::ArtifactSelector;
+use clean_dev_dirs::config::scan::{DuMode, ScanLimits};
+use clean_dev_dirs::config::{
+    ExecutionOptions, FilterOptions, GitChangeFilter, ProjectFilter, ScanOptions, SortCriteria,
+    SortOptions,
+};
+use clean_dev_dirs::hooks::ProjectHooks;
+use clean_dev_dirs::output::OutputFormat;
+use clean_dev_dirs::sweep::StaleCriteria;
+use clean_dev_dirs::utils::duration::{parse_count, parse_duration};
+use clean_dev_dirs::utils::guard::DeletionGuard;
+use clean_dev_dirs::utils::path_filter::PathFilter;
+use clean_dev_dirs::utils::retry::RetryPolicy;
+
+use crate::exit_status::ExitStatus;
+
+/// Cleanups of at least this size must be confirmed by typing "delete".
+const DEFAULT_CONFIRM_THRESHOLD: &str = "50GB";
+
+/// Prefix of the environment variables setting options, e.g.
+/// `CLEAN_DEV_DIRS_KEEP_SIZE` for `--keep-size`.
+pub const ENV_PREFIX: &str = "CLEAN_DEV_DIRS_";
+
+/// Where the value of a setting in effect comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingSource {
+    /// A command-line option.
+    CommandLine,
+
+    /// A `CLEAN_DEV_DIRS_*` environment variable.
+    Environment,
+
+    /// The configuration file.
+    ConfigFile,
+
+    /// The built-in default.
+    Default,
+}
+
+impl SettingSource {
+    /// How the source is shown by `config show`.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::CommandLine => "command line",
+            Self::Environment => "environment",
+            Self::ConfigFile => "config file",
+            Self::Default => "default",
+        }
+    }
+}
 
-use std::path::PathBuf;
+/// A setting in effect, as shown by `config show`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Setting {
+    /// Key of the setting in the configuration file, e.g.
+    /// `filtering.keep_days`.
+    pub key: &'static str,
 
-use clap::{Parser, ValueEnum};
+    /// Its value, or `None` if it is not set (e.g. no `scan_budget`).
+    pub value: Option<Value>,
 
-use clean_dev_dirs::config::file::{FileConfig, expand_tilde};
-use clean_dev_dirs::config::{
-    ExecutionOptions, FilterOptions, ProjectFilter, ScanOptions, SortCriteria, SortOptions,
-};
+    /// Where the value comes from.
+    pub source: SettingSource,
+}
+
+impl Setting {
+    /// The first of the command-line, config-file and default values that is
+    /// set.
+    fn layered(
+        key: &'static str,
+        cli: Option<Value>,
+        file: Option<Value>,
+        default: Option<Value>,
+    ) -> Self {
+        let (value, source) = match (cli, file) {
+            (Some(value), _) => (Some(value), SettingSource::CommandLine),
+            (None, Some(value)) => (Some(value), SettingSource::ConfigFile),
+            (None, None) => (default, SettingSource::Default),
+        };
+        Self { key, value, source }
+    }
+}
 
 /// Command-line arguments for filtering projects during cleanup.
 ///
 /// These options control which projects are considered for cleaning based on
 /// size and modification time criteria.
 #[derive(Parser)]
+#[allow(clippy::struct_excessive_bools)]
 struct FilteringArgs {
     /// Ignore projects with a build dir size smaller than the specified value
     ///
@@ -29,30 +126,101 @@ struct FilteringArgs {
     /// - Binary: KiB, MiB, GiB (base 1024)
     /// - Bytes: plain numbers
     /// - Decimal values: 1.5MB, 2.5GiB, etc.
-    #[arg(short = 's', long)]
+    #[arg(short = 's', long, global = true)]
     keep_size: Option<String>,
 
     /// Ignore projects that have been compiled in the last \[DAYS\] days
     ///
     /// Projects with build directories modified within this timeframe will be
     /// skipped during cleanup. A value of 0 disables time-based filtering.
-    #[arg(short = 'd', long)]
+    #[arg(short = 'd', long, global = true)]
     keep_days: Option<u32>,
 
-    /// Sort projects by the given criterion before display
+    /// Measure --keep-days from the last commit touching each project
+    ///
+    /// Build directories get touched by IDE indexing and stray builds, while
+    /// the git history tells when a project was last worked on. Projects
+    /// outside a git repository, or without commits, fall back to the
+    /// modification time of their build directories.
+    #[arg(long, global = true)]
+    git_age: bool,
+
+    /// Keep only the N largest projects after filtering
+    ///
+    /// Applied after every other filter, e.g. `--top 10 --dry-run` shows the
+    /// ten biggest projects that would be cleaned.
+    #[arg(long, value_name = "N", global = true)]
+    top: Option<usize>,
+
+    /// Clean only the fewest projects needed to free the given size
+    ///
+    /// The largest projects are taken first (the oldest ones first among
+    /// projects of equal size), e.g. `--free 50GB`. Applied after every
+    /// other filter; accepts the same formats as --keep-size.
+    #[arg(long, value_name = "SIZE", global = true)]
+    free: Option<String>,
+
+    /// Sort projects by the given criterion before display [default: size]
     ///
     /// Supported values: size (largest first), age (oldest first),
     /// name (alphabetical), type (grouped by project type).
     /// Use --reverse to flip the order.
-    #[arg(long, value_enum)]
+    #[arg(long, value_enum, global = true)]
     sort: Option<SortCriteria>,
 
     /// Reverse the sort order
     ///
-    /// When used with --sort, reverses the default ordering direction.
-    /// For example, --sort size --reverse shows smallest projects first.
-    #[arg(long)]
+    /// Reverses the ordering direction of --sort (or of the default size
+    /// order). For example, --reverse shows the smallest projects first.
+    #[arg(long, global = true)]
     reverse: bool,
+
+    /// Only consider projects with changes since the given git ref
+    ///
+    /// A project counts as changed if any tracked file below its root
+    /// differs from the ref (commits or uncommitted edits). Projects outside
+    /// a git repository, or whose repository lacks the ref, are skipped.
+    #[arg(
+        long,
+        value_name = "GIT_REF",
+        conflicts_with = "unchanged_since",
+        global = true
+    )]
+    changed_since: Option<String>,
+
+    /// Only consider projects without changes since the given git ref
+    ///
+    /// The inverse of --changed-since, e.g. `--unchanged-since v1.0` cleans
+    /// everything untouched since the v1.0 release tag.
+    #[arg(long, value_name = "GIT_REF", global = true)]
+    unchanged_since: Option<String>,
+
+    /// Only clean projects whose build artifacts are older than their
+    /// lockfile or manifest
+    ///
+    /// Dependencies changed since the last build, so a reinstall or rebuild
+    /// is needed anyway: cleaning such projects costs nothing in rebuild
+    /// time. Projects without a known lockfile or manifest are skipped.
+    #[arg(long, global = true)]
+    only_outdated: bool,
+
+    /// Skip projects with uncommitted changes in their git repository
+    ///
+    /// Modified, staged, deleted and untracked files below the project root
+    /// count, except in its build directories. Projects outside a git
+    /// repository are not affected.
+    #[arg(long, global = true)]
+    skip_dirty: bool,
+
+    /// Clean only the given kinds of artifacts [default: all]
+    ///
+    /// Comma-separated kinds among deps (`node_modules`, venv, vendor), build
+    /// (target, build, `__pycache__`, …) and caches (`.cache`, `.pytest_cache`, …),
+    /// each optionally limited to one project type with `TYPE:KIND`, e.g.
+    /// `--artifacts caches,node:deps` cleans caches everywhere but
+    /// dependencies only in Node.js projects.
+    #[arg(long, value_name = "KIND", value_delimiter = ',', global = true)]
+    artifacts: Vec<ArtifactSelector>,
 }
 
 /// Command-line arguments for controlling cleanup execution behavior.
@@ -66,29 +234,73 @@ struct ExecutionArgs {
     ///
     /// When enabled, it automatically proceeds with cleaning without any user prompts.
     /// Use with caution as this will immediately delete build directories.
-    #[arg(short = 'y', long)]
+    /// Without it, cleaning must be confirmed, which requires a terminal.
+    #[arg(short = 'y', long, global = true)]
     yes: bool,
 
+    /// Require typing "delete" to confirm cleanups of at least this size
+    /// [default: 50GB]
+    ///
+    /// Smaller cleanups are confirmed with y/N. Accepts the same formats as
+    /// --keep-size. Has no effect with --yes.
+    #[arg(long, value_name = "SIZE", global = true)]
+    confirm_threshold: Option<String>,
+
     /// Collect the cleanable projects and list the reclaimable space
     ///
     /// When enabled, performs all scans and filtering but doesn't
     /// delete any files. Useful for previewing what would be cleaned.
-    #[arg(long)]
+    #[arg(long, global = true)]
     dry_run: bool,
 
+    /// List every file that would be deleted for the given project
+    ///
+    /// Only valid with --dry-run. The project is matched by name or by root
+    /// path; the complete file list of its build directory is printed (or
+    /// written to the file given by --list-files-output) for auditing.
+    #[arg(
+        long,
+        value_name = "PROJECT",
+        requires = "dry_run",
+        conflicts_with = "json",
+        global = true
+    )]
+    list_files: Option<String>,
+
+    /// Write the --list-files output to this file instead of stdout
+    #[arg(long, value_name = "FILE", requires = "list_files", global = true)]
+    list_files_output: Option<PathBuf>,
+
+    /// Write the projects that would be cleaned to a plan file, for `apply`
+    ///
+    /// Only valid with --dry-run. The plan lists every project and artifact
+    /// with its size, in JSON, so that it can be reviewed before being
+    /// applied with `clean-dev-dirs apply FILE`.
+    #[arg(long, value_name = "FILE", requires = "dry_run", global = true)]
+    plan: Option<PathBuf>,
+
     /// Use interactive project selection
     ///
     /// When enabled, it presents a list of found projects and allows the user to
     /// select which ones to clean using an interactive interface.
-    #[arg(short = 'i', long)]
+    #[arg(short = 'i', long, global = true)]
     interactive: bool,
 
+    /// Select projects in a full-screen terminal interface
+    ///
+    /// Shows the projects in a table that can be sorted (by size, age, type
+    /// or path) and filtered, with a live total of the selected reclaimable
+    /// space and a confirmation screen before cleaning. Better suited than
+    /// --interactive for hundreds of projects.
+    #[arg(long, conflicts_with_all = ["interactive", "json"], global = true)]
+    tui: bool,
+
     /// Copy compiled executables to <project>/bin/ before cleaning
     ///
     /// When enabled, preserves compiled binaries (e.g. from target/release/
     /// and target/debug/ for Rust projects) by copying them to a bin/ directory
     /// in the project root before deleting build directories.
-    #[arg(short = 'k', long)]
+    #[arg(short = 'k', long, global = true)]
     keep_executables: bool,
 
     /// Permanently delete directories instead of moving them to the system trash
@@ -96,8 +308,91 @@ struct ExecutionArgs {
     /// By default, build directories are moved to the system trash (Recycle Bin
     /// on Windows, Trash on macOS/Linux) so deletions are recoverable. When this
     /// flag is set, directories are permanently removed (`rm -rf` style) instead.
-    #[arg(long)]
+    #[arg(long, global = true)]
     permanent: bool,
+
+    /// Clean projects even if a package manager is running inside them
+    ///
+    /// By default, projects in which a `cargo`, `npm`, `pnpm`, `pip`, or `go`
+    /// process is currently running are skipped as "build in progress", since
+    /// deleting their build directories would break the running command.
+    #[arg(long, global = true)]
+    force: bool,
+
+    /// Skip projects whose build directories hold files open in a running process
+    ///
+    /// Before cleaning, the files held open by every process are listed
+    /// (from `/proc` on Linux, with `lsof` on macOS), and projects with one
+    /// inside their build directories are skipped with a warning naming the
+    /// process, e.g. a dev server serving `node_modules/`. Not supported on
+    /// Windows.
+    #[arg(long, global = true)]
+    check_in_use: bool,
+
+    /// Clean Rust projects with `cargo clean` instead of deleting `target/`
+    ///
+    /// Lets cargo remove its own build output, which respects custom
+    /// `target-dir` configurations and takes cargo's build lock, so a build
+    /// running at the same time is never left with a half-deleted target.
+    /// If `cargo clean` fails, the directory is deleted directly instead.
+    /// `cargo clean` always deletes permanently, even without --permanent.
+    #[arg(long, global = true)]
+    native_clean: bool,
+
+    /// With --native-clean, only clean the release profile (`cargo clean --release`)
+    #[arg(long, requires = "native_clean", global = true)]
+    native_clean_release: bool,
+
+    /// With --native-clean, only clean the given package (`cargo clean -p <SPEC>`)
+    ///
+    /// Can be specified multiple times.
+    #[arg(
+        long,
+        value_name = "SPEC",
+        requires = "native_clean",
+        action = clap::ArgAction::Append,
+        global = true,
+    )]
+    native_clean_package: Vec<String>,
+
+    /// Only remove stale artifacts inside Rust `target/` directories
+    ///
+    /// Instead of deleting the whole directory, removes the compilation
+    /// units (in `deps/`, `build/`, `.fingerprint/`, `incremental/` and
+    /// `examples/`) not rebuilt within --keep-days days, or built by a Rust
+    /// toolchain that is no longer installed. Recent incremental builds stay
+    /// hot. Implies --project-type rust; --keep-days then applies to the
+    /// individual artifacts instead of whole projects.
+    #[arg(long, conflicts_with = "native_clean", global = true)]
+    stale_only: bool,
+
+    /// How often to retry a file or directory that fails to delete [default: 3]
+    ///
+    /// Only transient failures are retried, such as files briefly locked by
+    /// an antivirus scanner on Windows or kept busy by NFS. A value of 0
+    /// disables retrying.
+    #[arg(long, value_name = "N", global = true)]
+    retries: Option<u32>,
+
+    /// Delay before the first retry in milliseconds, doubled for each further retry [default: 100]
+    #[arg(long, value_name = "MS", global = true)]
+    retry_delay: Option<u64>,
+
+    /// Never delete a path with fewer components than this [default: 3]
+    ///
+    /// A final safety check applied to every path right before it is
+    /// removed, once symlinks are resolved: `/home/me/target` has 3
+    /// components. Root and home directories are never deleted either way.
+    #[arg(long, value_name = "N", global = true)]
+    min_delete_depth: Option<usize>,
+
+    /// Measure each directory again right before deleting it
+    ///
+    /// By default, the space freed is the size calculated by the scan, so
+    /// that directories are not walked a second time. Use this when builds
+    /// may have run since the scan, e.g. after a long interactive selection.
+    #[arg(long, global = true)]
+    recheck_size: bool,
 }
 
 /// Command-line arguments for controlling directory scanning behavior.
@@ -110,29 +405,464 @@ struct ScanningArgs {
     ///
     /// A value of 0 uses the default number of threads (typically the number of CPU cores).
     /// Higher values can improve scanning performance on systems with fast storage.
-    #[arg(short = 't', long)]
+    #[arg(short = 't', long, global = true)]
     threads: Option<usize>,
 
-    /// Show access errors that occur while scanning
+    /// Show access errors that occur while scanning (repeat for more detail)
     ///
     /// When enabled, displays errors encountered while accessing files or directories
-    /// during the scanning process. Useful for debugging permission issues.
-    #[arg(short = 'v', long)]
-    verbose: bool,
+    /// during the scanning process, and lists every project that was filtered out
+    /// together with its reason code. Useful for debugging permission issues.
+    /// `-vv` adds debugging details, and `-vvv` traces everything.
+    #[arg(short = 'v', long, action = ArgAction::Count, global = true)]
+    verbose: u8,
 
     /// Directories to ignore by default
     ///
     /// These directories will be completely ignored during scanning. Can be specified
     /// multiple times to ignore multiple directory patterns.
-    #[arg(long, action = clap::ArgAction::Append)]
+    #[arg(long, action = clap::ArgAction::Append, global = true)]
     ignore: Vec<PathBuf>,
 
     /// Directories to skip during scanning
     ///
     /// These directories will be skipped during scans, but their parent directories
     /// may still be processed. Can be specified multiple times.
-    #[arg(long, action = clap::ArgAction::Append)]
+    #[arg(long, action = clap::ArgAction::Append, global = true)]
     skip: Vec<PathBuf>,
+
+    /// Only scan paths matching the glob, e.g. '~/work/**'
+    ///
+    /// Globs are matched against absolute paths: `*` does not cross
+    /// directory separators while `**` does, and a leading `~` stands for
+    /// the home directory. Can be specified multiple times.
+    #[arg(long, value_name = "GLOB", action = clap::ArgAction::Append, global = true)]
+    include: Vec<String>,
+
+    /// Ignore paths matching the glob, e.g. '**/experiments/**'
+    ///
+    /// Matching directories are not searched for projects, and matching
+    /// build artifacts are not cleaned. Takes precedence over --include.
+    /// Can be specified multiple times.
+    #[arg(long, value_name = "GLOB", action = clap::ArgAction::Append, global = true)]
+    exclude: Vec<String>,
+
+    /// Search directories ignored by .gitignore and .ignore files too
+    ///
+    /// By default, directories ignored by the `.gitignore` or `.ignore` file
+    /// of a scanned directory are not searched for projects.
+    #[arg(long, global = true)]
+    no_ignore: bool,
+
+    /// Do not descend into other file systems mounted below the directories scanned
+    ///
+    /// Directories on another device than the directory scanned, such as
+    /// network mounts or backup volumes, are not searched for projects.
+    #[arg(long, global = true)]
+    one_file_system: bool,
+
+    /// Search network file systems mounted below the directories scanned too
+    ///
+    /// By default, NFS, SMB and FUSE mounts found below a directory scanned
+    /// are skipped with a warning: walking them is slow, and the build
+    /// directories on them may not be yours to clean.
+    #[arg(long, global = true)]
+    allow_network_fs: bool,
+
+    /// Search no deeper than this many directories below each directory scanned
+    ///
+    /// With `--max-depth 2`, only the directories scanned, their
+    /// subdirectories and the subdirectories of those are searched for
+    /// projects. `0` only looks at the directories scanned themselves.
+    #[arg(long, value_name = "N", global = true)]
+    max_depth: Option<usize>,
+
+    /// Report only the projects at least this many directories below each
+    /// directory scanned
+    ///
+    /// With `--min-depth 1`, a project at the root of a directory scanned is
+    /// left out. Shallower directories are still searched.
+    #[arg(long, value_name = "N", default_value_t = 0, global = true)]
+    min_depth: usize,
+
+    /// Do not calculate the size of the build directories found
+    ///
+    /// Sizing walks every file of every build directory, which on slow
+    /// disks can take far longer than deleting them. Sizes are then shown
+    /// as `unknown`, and empty build directories are cleaned too.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "keep_size",
+            "top",
+            "free",
+            "exact_sizes",
+            "recheck_size",
+            "du_mode",
+            "refresh",
+        ],
+        global = true,
+    )]
+    no_size: bool,
+
+    /// How to measure sizes [default: apparent]
+    ///
+    /// `apparent` adds up the length of the files. `disk` adds up the space
+    /// they take up on disk, as `du` does, so that the space freed matches
+    /// what `df` shows afterwards: sparse files count less, and files with
+    /// several hard links, such as those of pnpm's `node_modules/`, only
+    /// count once.
+    #[arg(
+        long,
+        value_name = "MODE",
+        conflicts_with = "recheck_size",
+        global = true
+    )]
+    du_mode: Option<DuMode>,
+
+    /// Size every build directory anew instead of reusing the sizes
+    /// recorded by earlier runs
+    ///
+    /// Build directories whose modification time has not changed since the
+    /// last run are not walked again. Changes deep inside them, e.g. a file
+    /// rewritten in place, can go unnoticed until this is used.
+    #[arg(long, global = true)]
+    refresh: bool,
+
+    /// Stop the directory traversal after this long, e.g. 90s, 5m or 1h30m
+    ///
+    /// Projects found so far are still reported, along with a warning that
+    /// the results are partial.
+    #[arg(long, value_name = "DURATION", global = true)]
+    scan_budget: Option<String>,
+
+    /// Stop the directory traversal after visiting this many directories,
+    /// e.g. 500K or 1M
+    ///
+    /// Projects found so far are still reported, along with a warning that
+    /// the results are partial.
+    #[arg(long, value_name = "COUNT", global = true)]
+    max_dirs: Option<String>,
+
+    /// Read the project roots to clean from a file, one per line ('-' for
+    /// stdin)
+    ///
+    /// Only the listed directories are checked for projects and sized: the
+    /// directories to search are not walked, and neither are the listed
+    /// ones. Useful to feed the output of `fd` or a saved list.
+    #[arg(long, value_name = "FILE", global = true)]
+    paths_from: Option<PathBuf>,
+}
+
+/// Command-line arguments for cleaning global (per-user) caches.
+///
+/// These options switch the tool from scanning a directory for projects to
+/// reporting and cleaning toolchain caches shared by every project.
+#[derive(Parser)]
+#[allow(clippy::struct_excessive_bools)]
+struct CacheArgs {
+    /// Clean the global Rust caches instead of scanning for projects
+    ///
+    /// Reports Cargo's registry cache, registry sources and git checkouts
+    /// (under `$CARGO_HOME`, default `~/.cargo`) and the sccache directory
+    /// with their sizes. Combine with --interactive to pick which ones to
+    /// clean, or --dry-run to only report them.
+    #[arg(long, conflicts_with = "list_files", global = true)]
+    cargo_cache: bool,
+
+    /// Clean the global npm, yarn and pnpm caches instead of scanning for projects
+    ///
+    /// Reports npm's `_cacache`, the yarn cache directory and the pnpm store
+    /// (as given by `pnpm store path`) with their sizes. Can be combined with
+    /// --cargo-cache to list both ecosystems' caches at once.
+    #[arg(long, conflicts_with = "list_files", global = true)]
+    node_cache: bool,
+
+    /// Run `pnpm store prune` instead of deleting the pnpm store
+    ///
+    /// Pruning only removes packages that no project references anymore, so
+    /// existing installs keep working. Only valid with --node-cache.
+    #[arg(long, requires = "node_cache", global = true)]
+    pnpm_prune: bool,
+
+    /// Clean the global Go module and build caches instead of scanning for projects
+    ///
+    /// Reports the module cache (`GOMODCACHE`, by default `$GOPATH/pkg/mod`)
+    /// and the build cache (`GOCACHE`) as given by `go env`, with their sizes.
+    #[arg(long, conflicts_with = "list_files", global = true)]
+    go_cache: bool,
+
+    /// Run `go clean -modcache` / `go clean -cache` instead of deleting directly
+    ///
+    /// Lets Go decide what to remove, rather than deleting the cache
+    /// directories wholesale. Only valid with --go-cache.
+    #[arg(long, requires = "go_cache", global = true)]
+    go_clean: bool,
+}
+
+/// Command-line arguments for signing and verifying JSON reports.
+#[derive(Parser)]
+struct ReportArgs {
+    /// Timestamp and sign the --json report
+    ///
+    /// Adds the generation time and the machine's hostname to the report and
+    /// signs it with the Ed25519 key given by --signing-key (or `signing_key`
+    /// in the `[report]` config section), so the report can be archived and
+    /// verified later with --verify-report.
+    #[arg(long, requires = "json", global = true)]
+    sign_report: bool,
+
+    /// Base64-encoded Ed25519 secret key (32 bytes) used by --sign-report
+    #[arg(long, value_name = "FILE", requires = "sign_report", global = true)]
+    signing_key: Option<PathBuf>,
+
+    /// Verify a report signed with --sign-report and exit
+    ///
+    /// Prints the public key the report was signed with; exits with an
+    /// error if the report was modified after signing.
+    #[arg(long, value_name = "FILE", exclusive = true, global = true)]
+    verify_report: Option<PathBuf>,
+}
+
+/// Subcommands. Without one, `clean-dev-dirs` runs `clean`.
+///
+/// Options are shared by all subcommands, and can be given before or after
+/// the subcommand name.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Find build directories and clean them (the default)
+    Clean {
+        /// The directories to search for projects (default: current directory)
+        dirs: Vec<PathBuf>,
+    },
+
+    /// Find build directories and show what would be cleaned, like `clean --dry-run`
+    Scan {
+        /// The directories to search for projects (default: current directory)
+        dirs: Vec<PathBuf>,
+    },
+
+    /// List the projects that would be cleaned, one per line
+    ///
+    /// Prints the size in bytes, type and root directory of each project,
+    /// separated by tabs, and nothing else, for use in scripts. Filtering and sorting
+    /// options apply as usual; with --json, prints the `scan` report.
+    List {
+        /// The directories to search for projects (default: current directory)
+        dirs: Vec<PathBuf>,
+    },
+
+    /// Scan without cleaning and break down the space the artifacts use
+    ///
+    /// Prints the totals per project type, the largest artifacts, a
+    /// histogram of the artifacts' ages and the totals per parent
+    /// directory. Scanning options and `--project-type` apply as usual.
+    Stats {
+        /// The directories to search for projects (default: current directory)
+        dirs: Vec<PathBuf>,
+    },
+
+    /// List large directories that no project detector matches
+    ///
+    /// Adds up the size of every directory, leaving out the artifacts of
+    /// the projects found, and lists the deepest ones reaching --min-size
+    /// with a guess at the tool that created them, e.g. to request support
+    /// for a missing ecosystem. Nothing is cleaned.
+    Discover {
+        /// The directories to search (default: current directory)
+        dirs: Vec<PathBuf>,
+
+        /// Only list directories of at least this size, e.g. 1GB
+        #[arg(long, value_name = "SIZE", default_value = "500MB")]
+        min_size: String,
+    },
+
+    /// Clean the projects of a plan written by `--dry-run --plan`
+    ///
+    /// Each project is checked again before cleaning: it must still be
+    /// detected as a project of the same type, and each artifact must still
+    /// exist with a size within 20% of the planned one. Projects that
+    /// changed are skipped. Cleaning options such as --yes and --permanent
+    /// apply as usual.
+    Apply {
+        /// The plan file
+        #[arg(value_name = "PLAN")]
+        file: PathBuf,
+    },
+
+    /// Create, check or show the configuration file and the settings in effect
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+
+    /// Restore the artifacts removed by a past run
+    ///
+    /// Every cleanup records the artifact directories it removed. Artifacts
+    /// that were moved to the trash are moved back; permanently deleted ones
+    /// are listed so they can be rebuilt.
+    Restore {
+        /// The id of the run to restore (see `history`), or `latest`
+        run_id: String,
+    },
+
+    /// List past runs and the space they freed, or show one of them
+    History {
+        #[command(subcommand)]
+        action: Option<HistoryAction>,
+    },
+
+    /// Clean periodically with the system's job scheduler
+    ///
+    /// Uses a systemd user timer on Linux, a launchd agent on macOS, and a
+    /// Scheduled Task on Windows. The options given before `schedule` are
+    /// passed on to the scheduled cleanups, which do not ask for
+    /// confirmation and read the configuration file as usual.
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+
+    /// Record project activity with git hooks, for --keep-days
+    ///
+    /// Installs `post-checkout` and `post-merge` hooks in a git repository
+    /// that record the time of each checkout and merge. A project counts as
+    /// used at the latest of its artifacts' modification time and the
+    /// activity recorded for its repository.
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+
+    /// Show which crates or packages take the most space in a project's artifacts
+    ///
+    /// For Rust projects, adds up the sizes of each crate's compilation
+    /// units (deps, build script output, incremental state and
+    /// fingerprints) across all profiles, e.g. to find out that a `-sys`
+    /// crate's build directory is most of the target directory. For Node.js
+    /// projects, adds up the sizes of every installed copy of each package
+    /// in `node_modules`, including nested copies and pnpm's store.
+    WhyBig {
+        /// The Rust or Node.js project, or its `target` or `node_modules` directory
+        project: PathBuf,
+
+        /// Number of crates or packages to list
+        #[arg(long, value_name = "N", default_value_t = 15)]
+        top: usize,
+    },
+
+    /// Print a shell completion script
+    ///
+    /// Completes the subcommands, the options and the values they take,
+    /// such as the project types of --project-type. For example:
+    /// `clean-dev-dirs completions bash > ~/.local/share/bash-completion/completions/clean-dev-dirs`
+    Completions {
+        /// The shell to complete for
+        shell: Shell,
+    },
+}
+
+impl Command {
+    /// Whether the subcommand scans for projects to clean: `clean`, `scan`
+    /// and `list`.
+    #[must_use]
+    pub const fn is_cleanup(&self) -> bool {
+        matches!(
+            self,
+            Self::Clean { .. } | Self::Scan { .. } | Self::List { .. }
+        )
+    }
+
+    /// The directories given to the subcommand, if it takes any.
+    #[must_use]
+    pub fn dirs(&self) -> &[PathBuf] {
+        match self {
+            Self::Clean { dirs }
+            | Self::Scan { dirs }
+            | Self::List { dirs }
+            | Self::Stats { dirs }
+            | Self::Discover { dirs, .. } => dirs,
+            _ => &[],
+        }
+    }
+}
+
+/// Actions of the `config` subcommand.
+#[derive(Subcommand, Clone, Default)]
+pub enum ConfigAction {
+    /// Print the path of the configuration file
+    Path,
+
+    /// Print the settings in effect and where each comes from: the command
+    /// line, the configuration file or the defaults (the default)
+    #[default]
+    Show,
+
+    /// Write a configuration file listing every setting, commented out
+    ///
+    /// An existing file is only overwritten with --force.
+    Init,
+
+    /// Check a configuration file for invalid TOML, unknown keys and invalid
+    /// values, reporting the line and column of each problem
+    Validate {
+        /// The file to check (default: the configuration file)
+        file: Option<PathBuf>,
+    },
+}
+
+/// Actions of the `history` subcommand.
+#[derive(Subcommand)]
+pub enum HistoryAction {
+    /// Show the details of a run: its projects, artifacts and errors
+    Show {
+        /// The id of the run to show, or `latest`
+        run_id: String,
+    },
+}
+
+/// Actions of the `hook` subcommand.
+#[derive(Subcommand)]
+pub enum HookAction {
+    /// Add the hooks to a repository, keeping its existing hooks
+    Install {
+        /// The git repository (default: current directory)
+        repo: Option<PathBuf>,
+    },
+
+    /// Remove the hooks from a repository
+    Remove {
+        /// The git repository (default: current directory)
+        repo: Option<PathBuf>,
+    },
+
+    /// Record activity in the current repository (run by the hooks)
+    #[command(hide = true)]
+    Record,
+}
+
+/// Actions of the `schedule` subcommand.
+#[derive(Subcommand)]
+pub enum ScheduleAction {
+    /// Schedule a cleanup, replacing the one already scheduled
+    Install {
+        /// The directories to clean (default: current directory)
+        dirs: Vec<PathBuf>,
+
+        /// Clean every day at 10:00
+        #[arg(long, conflicts_with = "weekly")]
+        daily: bool,
+
+        /// Clean every Monday at 10:00 (default)
+        #[arg(long)]
+        weekly: bool,
+    },
+
+    /// Remove the scheduled cleanup
+    Remove,
+
+    /// Show the scheduled cleanup and its next run
+    Status,
 }
 
 /// Main command-line interface structure.
@@ -141,27 +871,31 @@ struct ScanningArgs {
 /// combining all argument groups and providing the main entry point for command parsing.
 ///
 /// Helper methods accept a [`FileConfig`] reference so that config-file values act as
-/// defaults when the corresponding CLI argument is not provided.
+/// defaults when the corresponding CLI argument is not provided. When parsed with
+/// [`Cli::parse_with_env`], options missing from the command line are taken from
+/// the environment first.
 #[derive(Parser)]
+#[allow(clippy::struct_excessive_bools)]
 #[command(name = "clean-dev-dirs")]
 #[command(
-    about = "Recursively clean development build directories (Rust, Node.js, Python, Go, Java/Kotlin, C/C++, Swift, .NET/C#)"
+    about = "Recursively clean development build directories (Rust, Node.js, Python, Go, Java/Kotlin, C/C++, Swift, .NET/C#, OCaml, Unity, Unreal)"
 )]
 #[command(version)]
 #[command(author)]
 pub struct Cli {
-    /// The directory to search for projects
+    /// The directories to search for projects
     ///
-    /// Specifies the root directory where the tool will recursively search for
+    /// Specifies the root directories where the tool will recursively search for
     /// development projects. Defaults to the current directory if not specified.
+    /// Projects found under more than one of them are only reported once.
     #[arg()]
-    dir: Option<PathBuf>,
+    dirs: Vec<PathBuf>,
 
-    /// Project type to clean (all, rust, node, python, go, java, cpp, swift, dotnet)
+    /// Project type to clean (all, rust, node, python, go, java, cpp, swift, dotnet, ocaml, unity, unreal)
     ///
     /// Restricts cleaning to specific project types. If not specified, all
     /// supported project types will be considered.
-    #[arg(short = 'p', long)]
+    #[arg(short = 'p', long, global = true)]
     project_type: Option<ProjectFilter>,
 
     /// Output results as a single JSON object for scripting/piping
@@ -169,9 +903,112 @@ pub struct Cli {
     /// When enabled, all human-readable output (colors, progress bars, emojis)
     /// is suppressed and a single JSON document is printed to stdout.
     /// Incompatible with `--interactive`.
-    #[arg(long)]
+    #[arg(long, global = true)]
     json: bool,
 
+    /// Output format: text, json (same as --json) or ndjson
+    ///
+    /// With `ndjson`, one JSON object is printed per line for each project as
+    /// soon as its size is known, while the scan goes on, and nothing is
+    /// cleaned. Projects are printed before the filters other than
+    /// --keep-days apply, in no particular order.
+    #[arg(long, value_name = "FORMAT", conflicts_with = "json", global = true)]
+    output: Option<OutputFormat>,
+
+    /// Lay out output for a terminal N columns wide (0 = never shorten lines)
+    ///
+    /// By default, long lines and paths are shortened to fit the terminal,
+    /// and left untouched when the output is not a terminal. Use this to
+    /// choose the width explicitly, e.g. when redirecting output to a file.
+    #[arg(long, value_name = "N", global = true)]
+    width: Option<usize>,
+
+    /// Never page long listings
+    ///
+    /// Listings taller than the terminal (skipped projects, compression
+    /// estimates, rebuild commands, file lists) are shown through `$PAGER`,
+    /// or `less` by default. Use this to print them directly instead.
+    #[arg(long, global = true)]
+    no_pager: bool,
+
+    /// Never show progress bars
+    ///
+    /// Progress bars are only drawn when stderr is a terminal, and redrawn
+    /// less often in SSH sessions with a high latency. Use this to turn them
+    /// off altogether.
+    #[arg(long, global = true)]
+    no_progress: bool,
+
+    /// Show sizes with two decimals and their exact number of bytes
+    ///
+    /// Sizes are shown in decimal units up to exabytes, with two decimals
+    /// except for whole values (e.g. `4 TB`). For audits, this shows them as
+    /// `4.00 TB (4,000,000,000,000 bytes)` instead, in the JSON output too.
+    #[arg(long, global = true)]
+    exact_sizes: bool,
+
+    /// Break the summary of the projects found down by directory searched
+    ///
+    /// With several directories to search, shows the number of projects
+    /// found under each of them and the space they use.
+    #[arg(long, global = true)]
+    group_by_root: bool,
+
+    /// End with a one-line summary for logs, as done by scheduled cleanups
+    ///
+    /// Prints the number of projects cleaned, failed and the space freed on
+    /// a single line, for syslog. On Linux, the summary is also sent to the
+    /// systemd journal as a structured entry. `schedule install` adds this
+    /// flag to the scheduled cleanups.
+    #[arg(long, global = true)]
+    scheduled: bool,
+
+    /// Estimate how small each project's build artifacts would be if compressed
+    ///
+    /// Samples every build directory, compresses the sample with zstd and
+    /// extrapolates the ratio, to help decide whether archiving is worth it
+    /// instead of deleting. Shown next to each project's size, and as
+    /// `compressed_size_estimate` in --json output.
+    #[arg(long, global = true)]
+    estimate_compressed: bool,
+
+    /// Append the statistics of this run as a row to a CSV file
+    ///
+    /// Each run adds the time, the number of projects, the reclaimable size
+    /// and the bytes freed per project type, in a stable schema suited to
+    /// charting artifact growth in Grafana or a spreadsheet. The file and
+    /// its header are created if needed.
+    #[arg(long, value_name = "FILE", global = true)]
+    append_csv: Option<PathBuf>,
+
+    /// Append a timestamped log of the run to a file
+    ///
+    /// Records every deletion, skip decision and error, along with the
+    /// warnings and debugging details, so they can be reviewed once the
+    /// terminal has scrolled past them.
+    #[arg(long, value_name = "FILE", global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Append a record of every deleted build directory to a file
+    ///
+    /// Each build directory or cache is written as a JSON line (path, size,
+    /// project, timestamp and outcome) synced to disk before it is removed,
+    /// followed by a second line once it was deleted, or failed to be. The
+    /// file is only ever appended to, for compliance records of what a
+    /// scheduled cleanup removed.
+    #[arg(long, value_name = "FILE", global = true)]
+    audit_log: Option<PathBuf>,
+
+    /// After cleaning, list the paths whose deletion was interfered with
+    ///
+    /// Lists every file or directory that failed to delete at first (for
+    /// example because Windows Defender was scanning it) and prints the
+    /// PowerShell commands excluding the affected projects from Defender
+    /// scans. On Windows, a hint pointing to this report is shown
+    /// automatically when deletion was unusually slow or often blocked.
+    #[arg(long, conflicts_with = "json", global = true)]
+    windows_defender_report: bool,
+
     /// Execution options
     #[command(flatten)]
     execution: ExecutionArgs,
@@ -183,104 +1020,493 @@ pub struct Cli {
     /// Scanning options
     #[command(flatten)]
     scanning: ScanningArgs,
+
+    /// Global cache options
+    #[command(flatten)]
+    caches: CacheArgs,
+
+    /// Report signing options
+    #[command(flatten)]
+    report: ReportArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Ids of the options taken from environment variables.
+    #[arg(skip)]
+    env_options: Vec<String>,
 }
 
 impl Cli {
-    /// Whether `--json` structured output mode is enabled.
+    /// Parse the command line, exiting with usage on error. Options missing
+    /// from it are taken from their environment variable, if set (see
+    /// [`Cli::command_with_env`]).
+    ///
+    /// Usage errors exit with [`ExitStatus::Failure`] rather than clap's 2,
+    /// which means that nothing was found.
+    #[must_use]
+    pub fn parse_with_env() -> Self {
+        let mut command = Self::command_with_env();
+        let (mut cli, matches) = command
+            .try_get_matches_from_mut(std::env::args_os())
+            .and_then(|matches| Ok((Self::from_arg_matches(&matches)?, matches)))
+            .map_err(|err| err.format(&mut command))
+            .unwrap_or_else(|err| {
+                // --help and --version are not errors
+                if !err.use_stderr() {
+                    err.exit();
+                }
+                let _ = err.print();
+                exit(ExitStatus::Failure as i32);
+            });
+
+        cli.env_options = matches
+            .ids()
+            .filter(|id| matches.value_source(id.as_str()) == Some(ValueSource::EnvVariable))
+            .map(ToString::to_string)
+            .collect();
+        cli
+    }
+
+    /// The command-line interface, with every option also read from an
+    /// environment variable: [`ENV_PREFIX`] followed by its long name in
+    /// upper case, with underscores for dashes. Flags take `true`, `yes`,
+    /// `on` or `1` and `false`, `no`, `off` or `0`; counted flags (`-v`)
+    /// also take the count.
+    #[must_use]
+    pub fn command_with_env() -> clap::Command {
+        <Self as CommandFactory>::command().mut_args(|arg| {
+            if arg.is_positional() {
+                return arg;
+            }
+            let var = env_var(arg.get_id().as_str());
+            let arg = arg.env(var).hide_env(true);
+            match arg.get_action() {
+                ArgAction::SetTrue => arg.value_parser(BoolishValueParser::new()),
+                ArgAction::Count => arg.value_parser(parse_verbosity),
+                _ => arg,
+            }
+        })
+    }
+
+    /// The subcommand to run instead of a scan, if any.
+    #[must_use]
+    pub const fn command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
+
+    /// Whether a directory to search was given on the command line.
+    #[must_use]
+    pub const fn has_directory(&self) -> bool {
+        !self.dirs.is_empty()
+    }
+
+    /// Whether the `list` subcommand was given.
+    #[must_use]
+    pub const fn lists_projects(&self) -> bool {
+        matches!(self.command, Some(Command::List { .. }))
+    }
+
+    /// Whether `--json` (or `--output json`) structured output mode is
+    /// enabled.
     #[must_use]
     pub const fn json(&self) -> bool {
-        self.json
+        self.json || matches!(self.output, Some(OutputFormat::Json))
     }
 
-    /// Resolve the target directory from CLI args, config file, or default.
-    ///
-    /// Priority: CLI argument > config file > current directory (`.`).
-    /// Tilde expansion is applied to paths originating from the config file.
-    ///
-    /// # Examples
-    ///
-    /// ```no_run
-    /// # use clap::Parser;
-    /// # use clean_dev_dirs::config::FileConfig;
-    /// # use std::path::PathBuf;
-    /// # mod cli { include!("cli.rs"); }
-    /// # use cli::Cli;
-    /// let args = Cli::parse_from(&["clean-dev-dirs", "/custom/path"]);
-    /// assert_eq!(args.directory(&FileConfig::default()), PathBuf::from("/custom/path"));
-    /// ```
+    /// Whether projects are streamed as NDJSON while scanning
+    /// (`--output ndjson`).
     #[must_use]
-    pub fn directory(&self, config: &FileConfig) -> PathBuf {
-        if let Some(ref dir) = self.dir {
-            return dir.clone();
-        }
+    pub const fn ndjson(&self) -> bool {
+        matches!(self.output, Some(OutputFormat::Ndjson))
+    }
 
-        if let Some(ref dir) = config.dir {
-            return expand_tilde(dir);
-        }
+    /// The CSV file to append run statistics to, given with `--append-csv`.
+    #[must_use]
+    pub fn append_csv(&self) -> Option<&Path> {
+        self.append_csv.as_deref()
+    }
 
-        PathBuf::from(".")
+    /// The file to append the log of the run to, given with `--log-file`.
+    #[must_use]
+    pub fn log_file(&self) -> Option<&Path> {
+        self.log_file.as_deref()
     }
 
-    /// Extract project filter from CLI args and config file.
-    ///
-    /// Priority: CLI argument > config file > default (`All`).
-    ///
-    /// # Examples
-    ///
-    /// ```no_run
-    /// # use clap::Parser;
-    /// # use clean_dev_dirs::config::{FileConfig, ProjectFilter};
-    /// # mod cli { include!("cli.rs"); }
-    /// # use cli::Cli;
-    /// let args = Cli::parse_from(&["clean-dev-dirs", "--project-type", "rust"]);
-    /// assert_eq!(args.project_filter(&FileConfig::default()), ProjectFilter::Rust);
-    /// ```
+    /// The file to append the audit records of deletions to, given with
+    /// `--audit-log`.
     #[must_use]
-    pub fn project_filter(&self, config: &FileConfig) -> ProjectFilter {
-        self.project_type
+    pub fn audit_log(&self) -> Option<&Path> {
+        self.audit_log.as_deref()
+    }
+
+    /// How much detail to show on stderr: the number of `-v` flags, or 1 if
+    /// none is given and `verbose` is set in the configuration file.
+    #[must_use]
+    pub fn verbosity(&self, config: &FileConfig) -> u8 {
+        if self.scanning.verbose == 0 && config.scanning.verbose == Some(true) {
+            1
+        } else {
+            self.scanning.verbose
+        }
+    }
+
+    /// The git history filter requested with `--changed-since` or
+    /// `--unchanged-since`, if any.
+    #[must_use]
+    pub fn git_change_filter(&self) -> Option<GitChangeFilter> {
+        let filtering = &self.filtering;
+        filtering
+            .changed_since
+            .clone()
+            .map(GitChangeFilter::ChangedSince)
             .or_else(|| {
-                config
-                    .project_type
-                    .as_ref()
-                    .and_then(|s| ProjectFilter::from_str(s, true).ok())
+                filtering
+                    .unchanged_since
+                    .clone()
+                    .map(GitChangeFilter::UnchangedSince)
             })
-            .unwrap_or_default()
     }
 
-    /// Extract execution options from CLI args and config file.
-    ///
-    /// For boolean flags, the CLI flag (if set to `true`) takes priority,
-    /// then the config file value, then `false`.
-    ///
-    /// # Examples
-    ///
-    /// ```no_run
-    /// # use clap::Parser;
-    /// # use clean_dev_dirs::config::FileConfig;
-    /// # mod cli { include!("cli.rs"); }
-    /// # use cli::Cli;
-    /// let args = Cli::parse_from(&["clean-dev-dirs", "--dry-run", "--interactive"]);
-    /// let options = args.execution_options(&FileConfig::default());
-    /// assert!(options.dry_run);
-    /// assert!(options.interactive);
-    /// ```
+    /// Whether `--only-outdated` was given.
     #[must_use]
-    pub fn execution_options(&self, config: &FileConfig) -> ExecutionOptions {
-        ExecutionOptions {
-            dry_run: self.execution.dry_run || config.execution.dry_run.unwrap_or(false),
-            interactive: self.execution.interactive
-                || config.execution.interactive.unwrap_or(false),
-            keep_executables: self.execution.keep_executables
-                || config.execution.keep_executables.unwrap_or(false),
-            use_trash: !self.execution.permanent && config.execution.use_trash.unwrap_or(true),
-        }
+    pub const fn only_outdated(&self) -> bool {
+        self.filtering.only_outdated
     }
 
-    /// Extract scanning options from CLI args and config file.
+    /// Whether `--skip-dirty` was given.
+    #[must_use]
+    pub const fn skip_dirty(&self) -> bool {
+        self.filtering.skip_dirty
+    }
+
+    /// The `--artifacts` selectors; empty selects every artifact.
+    #[must_use]
+    pub fn artifacts(&self) -> &[ArtifactSelector] {
+        &self.filtering.artifacts
+    }
+
+    /// The signed report to verify, given with `--verify-report`.
+    #[must_use]
+    pub fn verify_report(&self) -> Option<&Path> {
+        self.report.verify_report.as_deref()
+    }
+
+    /// Whether JSON reports should be signed.
     ///
-    /// - **threads**: CLI > config > `0` (default)
-    /// - **verbose**: CLI flag `||` config value `||` `false`
-    /// - **skip**: merged from both sources (config values first, then CLI)
+    /// Signing only applies to `--json` output: `true` if `--sign-report` is
+    /// given, or if `--json` is given and the config file sets `sign = true`.
+    #[must_use]
+    pub fn sign_report(&self, config: &FileConfig) -> bool {
+        self.report.sign_report || (self.json && config.report.sign.unwrap_or(false))
+    }
+
+    /// Resolve the report signing key path from CLI args or config file.
+    ///
+    /// Priority: CLI argument > config file (with tilde expansion).
+    #[must_use]
+    pub fn signing_key(&self, config: &FileConfig) -> Option<PathBuf> {
+        self.report
+            .signing_key
+            .clone()
+            .or_else(|| config.report.signing_key.as_deref().map(expand_tilde))
+    }
+
+    /// Whether directories are measured again right before being deleted.
+    #[must_use]
+    pub const fn recheck_size(&self) -> bool {
+        self.execution.recheck_size
+    }
+
+    /// Whether only stale artifacts inside `target/` should be removed.
+    #[must_use]
+    pub const fn stale_only(&self) -> bool {
+        self.execution.stale_only
+    }
+
+    /// The staleness criteria of `--stale-only`, if given.
+    ///
+    /// With `--stale-only`, `--keep-days` applies to artifacts, not whole
+    /// projects.
+    #[must_use]
+    pub fn stale_criteria(&self, config: &FileConfig) -> Option<StaleCriteria> {
+        self.stale_only()
+            .then(|| StaleCriteria::new(self.filter_options(config).keep_days))
+    }
+
+    /// The `cargo clean` narrowing requested with `--native-clean-release`
+    /// and `--native-clean-package`.
+    #[must_use]
+    pub fn native_clean(&self) -> NativeClean {
+        NativeClean {
+            release: self.execution.native_clean_release,
+            packages: self.execution.native_clean_package.clone(),
+        }
+    }
+
+    /// The output width set with `--width`, if any.
+    #[must_use]
+    pub const fn width(&self) -> Option<usize> {
+        self.width
+    }
+
+    /// Whether `--no-pager` was given.
+    #[must_use]
+    pub const fn no_pager(&self) -> bool {
+        self.no_pager
+    }
+
+    /// Whether `--no-progress` was given.
+    #[must_use]
+    pub const fn no_progress(&self) -> bool {
+        self.no_progress
+    }
+
+    /// Whether `--exact-sizes` was given.
+    #[must_use]
+    pub const fn exact_sizes(&self) -> bool {
+        self.exact_sizes
+    }
+
+    /// Whether `--group-by-root` was given.
+    #[must_use]
+    pub const fn group_by_root(&self) -> bool {
+        self.group_by_root
+    }
+
+    /// Whether `--scheduled` was given.
+    #[must_use]
+    pub const fn scheduled(&self) -> bool {
+        self.scheduled
+    }
+
+    /// Whether `--estimate-compressed` was given.
+    #[must_use]
+    pub const fn estimate_compressed(&self) -> bool {
+        self.estimate_compressed
+    }
+
+    /// Whether `--windows-defender-report` was given.
+    #[must_use]
+    pub const fn windows_defender_report(&self) -> bool {
+        self.windows_defender_report
+    }
+
+    /// Whether `--force` was given to clean projects with a build in progress.
+    #[must_use]
+    pub const fn force(&self) -> bool {
+        self.execution.force
+    }
+
+    /// Whether `--check-in-use` was given to skip build directories held
+    /// open by running processes.
+    #[must_use]
+    pub const fn check_in_use(&self) -> bool {
+        self.execution.check_in_use
+    }
+
+    /// Whether `--cargo-cache` global Rust cache mode is enabled.
+    #[must_use]
+    pub const fn cargo_cache(&self) -> bool {
+        self.caches.cargo_cache
+    }
+
+    /// Whether `--node-cache` global Node.js cache mode is enabled.
+    #[must_use]
+    pub const fn node_cache(&self) -> bool {
+        self.caches.node_cache
+    }
+
+    /// Whether `--pnpm-prune` was given to prune the pnpm store instead of deleting it.
+    #[must_use]
+    pub const fn pnpm_prune(&self) -> bool {
+        self.caches.pnpm_prune
+    }
+
+    /// Whether `--go-cache` global Go cache mode is enabled.
+    #[must_use]
+    pub const fn go_cache(&self) -> bool {
+        self.caches.go_cache
+    }
+
+    /// Whether `--go-clean` was given to clean Go caches with `go clean`.
+    #[must_use]
+    pub const fn go_clean(&self) -> bool {
+        self.caches.go_clean
+    }
+
+    /// The project (name or root path) whose files `--list-files` should list.
+    #[must_use]
+    pub fn list_files(&self) -> Option<&str> {
+        self.execution.list_files.as_deref()
+    }
+
+    /// Destination file for the `--list-files` output, if any.
+    #[must_use]
+    pub fn list_files_output(&self) -> Option<&Path> {
+        self.execution.list_files_output.as_deref()
+    }
+
+    /// The file to write the plan of a dry run to (`--plan`).
+    #[must_use]
+    pub fn plan(&self) -> Option<&Path> {
+        self.execution.plan.as_deref()
+    }
+
+    /// Resolve the directories to search from CLI args, config file, or default.
+    ///
+    /// Priority: CLI arguments (given to the subcommand, e.g. `scan <DIR>...`,
+    /// or before it) > config file > current directory (`.`).
+    /// Tilde expansion is applied to paths originating from the config file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use clap::Parser;
+    /// # use clean_dev_dirs::config::FileConfig;
+    /// # use std::path::PathBuf;
+    /// # mod cli { include!("cli.rs"); }
+    /// # use cli::Cli;
+    /// let args = Cli::parse_from(&["clean-dev-dirs", "/work", "/oss"]);
+    /// assert_eq!(
+    ///     args.directories(&FileConfig::default()),
+    ///     [PathBuf::from("/work"), PathBuf::from("/oss")]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn directories(&self, config: &FileConfig) -> Vec<PathBuf> {
+        let dirs = self.command_dirs();
+        if !dirs.is_empty() {
+            return dirs.to_vec();
+        }
+
+        if let Some(ref dir) = config.dir {
+            return vec![expand_tilde(dir)];
+        }
+
+        vec![PathBuf::from(".")]
+    }
+
+    /// The directories given on the command line, to the subcommand or
+    /// before it.
+    fn command_dirs(&self) -> &[PathBuf] {
+        self.command
+            .as_ref()
+            .map(Command::dirs)
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or(&self.dirs)
+    }
+
+    /// Extract project filter from CLI args and config file.
+    ///
+    /// Priority: CLI argument > config file > default (`All`). `--stale-only`
+    /// only applies to Rust projects, so it always selects `Rust`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use clap::Parser;
+    /// # use clean_dev_dirs::config::{FileConfig, ProjectFilter};
+    /// # mod cli { include!("cli.rs"); }
+    /// # use cli::Cli;
+    /// let args = Cli::parse_from(&["clean-dev-dirs", "--project-type", "rust"]);
+    /// assert_eq!(args.project_filter(&FileConfig::default()), ProjectFilter::Rust);
+    /// ```
+    #[must_use]
+    pub fn project_filter(&self, config: &FileConfig) -> ProjectFilter {
+        if self.execution.stale_only {
+            return ProjectFilter::Rust;
+        }
+
+        self.project_type
+            .or_else(|| {
+                config
+                    .project_type
+                    .as_ref()
+                    .and_then(|s| ProjectFilter::from_str(s, true).ok())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Extract execution options from CLI args and config file.
+    ///
+    /// For boolean flags, the CLI flag (if set to `true`) takes priority,
+    /// then the config file value, then `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use clap::Parser;
+    /// # use clean_dev_dirs::config::FileConfig;
+    /// # mod cli { include!("cli.rs"); }
+    /// # use cli::Cli;
+    /// let args = Cli::parse_from(&["clean-dev-dirs", "--dry-run", "--interactive"]);
+    /// let options = args.execution_options(&FileConfig::default());
+    /// assert!(options.dry_run);
+    /// assert!(options.interactive);
+    /// ```
+    #[must_use]
+    pub fn execution_options(&self, config: &FileConfig) -> ExecutionOptions {
+        ExecutionOptions {
+            dry_run: self.execution.dry_run
+                || matches!(
+                    self.command,
+                    Some(Command::Scan { .. } | Command::List { .. })
+                )
+                || config.execution.dry_run.unwrap_or(false),
+            interactive: self.execution.interactive
+                || config.execution.interactive.unwrap_or(false),
+            tui: self.execution.tui,
+            keep_executables: self.execution.keep_executables
+                || config.execution.keep_executables.unwrap_or(false),
+            use_trash: !self.execution.permanent && config.execution.use_trash.unwrap_or(true),
+            native_clean: self.execution.native_clean
+                || config.execution.native_clean.unwrap_or(false),
+            retry: self.retry_policy(config),
+            guard: self
+                .execution
+                .min_delete_depth
+                .or(config.execution.min_delete_depth)
+                .map_or_else(DeletionGuard::default, DeletionGuard::with_min_depth),
+            yes: self.execution.yes,
+            confirm_threshold: self
+                .execution
+                .confirm_threshold
+                .clone()
+                .or_else(|| config.execution.confirm_threshold.clone())
+                .unwrap_or_else(|| DEFAULT_CONFIRM_THRESHOLD.to_string()),
+            hooks: ProjectHooks {
+                pre_project: config.hooks.pre_project.clone(),
+                post_project: config.hooks.post_project.clone(),
+            },
+        }
+    }
+
+    /// Resolve the deletion retry policy from CLI args and config file.
+    ///
+    /// Priority for each value: CLI argument > config file > default.
+    fn retry_policy(&self, config: &FileConfig) -> RetryPolicy {
+        let default = RetryPolicy::default();
+
+        RetryPolicy {
+            retries: self
+                .execution
+                .retries
+                .or(config.execution.retries)
+                .unwrap_or(default.retries),
+            initial_delay: self
+                .execution
+                .retry_delay
+                .or(config.execution.retry_delay_ms)
+                .map_or(default.initial_delay, Duration::from_millis),
+        }
+    }
+
+    /// Extract scanning options from CLI args and config file.
+    ///
+    /// - **threads**: CLI > config > `0` (default)
+    /// - **verbose**: CLI flag `||` config value `||` `false`
+    /// - **skip**: merged from both sources (config values first, then CLI)
     ///
     /// # Examples
     ///
@@ -300,7 +1526,7 @@ impl Cli {
         skip.extend(self.scanning.skip.clone());
 
         ScanOptions {
-            verbose: self.scanning.verbose || config.scanning.verbose.unwrap_or(false),
+            verbose: self.verbosity(config) > 0,
             threads: self
                 .scanning
                 .threads
@@ -310,6 +1536,105 @@ impl Cli {
         }
     }
 
+    /// Extract the `--scan-budget` and `--max-dirs` limits from CLI args and
+    /// config file.
+    ///
+    /// Priority: CLI argument > config file > unlimited.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the budget is not a valid duration or the maximum
+    /// is not a valid count.
+    pub fn scan_limits(&self, config: &FileConfig) -> Result<ScanLimits> {
+        let budget = self
+            .scanning
+            .scan_budget
+            .as_deref()
+            .or(config.scanning.scan_budget.as_deref())
+            .map(parse_duration)
+            .transpose()?;
+        let max_dirs = self
+            .scanning
+            .max_dirs
+            .as_deref()
+            .or(config.scanning.max_dirs.as_deref())
+            .map(parse_count)
+            .transpose()?;
+        Ok(ScanLimits { budget, max_dirs })
+    }
+
+    /// Compile the `--include` and `--exclude` globs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the globs is invalid.
+    pub fn path_filter(&self) -> Result<PathFilter> {
+        Ok(PathFilter::new(
+            &self.scanning.include,
+            &self.scanning.exclude,
+        )?)
+    }
+
+    /// Whether directories ignored by `.gitignore` and `.ignore` files are
+    /// skipped while scanning, i.e. `--no-ignore` was not given.
+    #[must_use]
+    pub const fn ignore_files(&self) -> bool {
+        !self.scanning.no_ignore
+    }
+
+    /// Whether scans stay on the file system of each directory scanned
+    /// (`--one-file-system`).
+    #[must_use]
+    pub const fn one_file_system(&self) -> bool {
+        self.scanning.one_file_system
+    }
+
+    /// Whether scans walk into network file systems (`--allow-network-fs`).
+    #[must_use]
+    pub const fn allow_network_fs(&self) -> bool {
+        self.scanning.allow_network_fs
+    }
+
+    /// The depth below each directory scanned past which directories are
+    /// not searched (`--max-depth`), if set.
+    #[must_use]
+    pub const fn max_depth(&self) -> Option<usize> {
+        self.scanning.max_depth
+    }
+
+    /// The depth below each directory scanned above which projects are not
+    /// reported (`--min-depth`).
+    #[must_use]
+    pub const fn min_depth(&self) -> usize {
+        self.scanning.min_depth
+    }
+
+    /// Whether the build directories found are sized, i.e. `--no-size` was
+    /// not given.
+    #[must_use]
+    pub const fn sizing(&self) -> bool {
+        !self.scanning.no_size
+    }
+
+    /// How sizes are measured, `--du-mode`.
+    #[must_use]
+    pub fn du_mode(&self) -> DuMode {
+        self.scanning.du_mode.unwrap_or_default()
+    }
+
+    /// Whether the sizes recorded by earlier runs are ignored, `--refresh`.
+    #[must_use]
+    pub const fn refresh(&self) -> bool {
+        self.scanning.refresh
+    }
+
+    /// The file listing the project roots to scan instead of searching
+    /// directories, `-` standing for stdin.
+    #[must_use]
+    pub fn paths_from(&self) -> Option<&Path> {
+        self.scanning.paths_from.as_deref()
+    }
+
     /// Extract filtering options from CLI args and config file.
     ///
     /// Priority: CLI argument > config file > hardcoded default.
@@ -328,216 +1653,1391 @@ impl Cli {
     /// ```
     #[must_use]
     pub fn filter_options(&self, config: &FileConfig) -> FilterOptions {
+        self.layered_filter_options(&config.filtering)
+    }
+
+    /// Extract filtering options for a subtree overridden by `.clean-dev-dirs.toml`
+    /// files.
+    ///
+    /// Priority: CLI argument > local files > config file > hardcoded default.
+    #[must_use]
+    pub fn local_filter_options(
+        &self,
+        config: &FileConfig,
+        local: &LocalFilterConfig,
+    ) -> FilterOptions {
+        self.layered_filter_options(&config.filtering.with_local(local))
+    }
+
+    /// Layer the CLI filtering arguments over `filtering` and the defaults.
+    fn layered_filter_options(&self, filtering: &FileFilterConfig) -> FilterOptions {
         FilterOptions {
             keep_size: self
                 .filtering
                 .keep_size
                 .clone()
-                .or_else(|| config.filtering.keep_size.clone())
+                .or_else(|| filtering.keep_size.clone())
+                // Nothing would reach the minimum without sizes
+                .filter(|_| self.sizing())
                 .unwrap_or_else(|| "0".to_string()),
             keep_days: self
                 .filtering
                 .keep_days
-                .or(config.filtering.keep_days)
+                .or(filtering.keep_days)
                 .unwrap_or(0),
+            git_age: self.filtering.git_age || filtering.git_age.unwrap_or(false),
+            top: self.filtering.top,
+            free: self.filtering.free.clone(),
+            protected_artifacts: filtering.protected_artifacts.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Extract sorting options from CLI args and config file.
+    ///
+    /// Priority: CLI argument > config file > default (by size, largest
+    /// first).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use clap::Parser;
+    /// # use clean_dev_dirs::config::{FileConfig, SortCriteria};
+    /// # mod cli { include!("cli.rs"); }
+    /// # use cli::Cli;
+    /// let args = Cli::parse_from(&["clean-dev-dirs", "--sort", "size", "--reverse"]);
+    /// let sort_opts = args.sort_options(&FileConfig::default());
+    /// assert_eq!(sort_opts.criteria, Some(SortCriteria::Size));
+    /// assert!(sort_opts.reverse);
+    /// ```
+    #[must_use]
+    pub fn sort_options(&self, config: &FileConfig) -> SortOptions {
+        SortOptions {
+            criteria: self.filtering.sort.or_else(|| {
+                config
+                    .filtering
+                    .sort
+                    .as_ref()
+                    .and_then(|s| SortCriteria::from_str(s, true).ok())
+                    .or(Some(SortCriteria::Size))
+            }),
+            reverse: self.filtering.reverse || config.filtering.reverse.unwrap_or(false),
+        }
+    }
+
+    /// List the settings in effect, layering the CLI args over the config
+    /// file and the defaults, each with where its value comes from.
+    ///
+    /// Flags can only turn a setting on: `--dry-run` overrides
+    /// `dry_run = false`, but without it `dry_run = true` applies.
+    #[must_use]
+    pub fn settings(&self, config: &FileConfig) -> Vec<Setting> {
+        let command_dirs = self.command_dirs();
+        let project_type = self
+            .project_type
+            .or_else(|| self.execution.stale_only.then_some(ProjectFilter::Rust));
+        let file_project_type = config
+            .project_type
+            .as_deref()
+            .filter(|name| ProjectFilter::from_str(name, true).is_ok());
+
+        let mut settings = vec![
+            Setting::layered(
+                "dir",
+                match command_dirs {
+                    [] => None,
+                    [dir] => Some(path_value(dir)),
+                    dirs => Some(paths_value(dirs)),
+                },
+                config.dir.as_deref().map(path_value),
+                Some(".".into()),
+            ),
+            Setting::layered(
+                "project_type",
+                project_type.as_ref().and_then(value_name),
+                file_project_type.map(Value::from),
+                Some("all".into()),
+            ),
+        ];
+        settings.extend(self.filtering_settings(&config.filtering));
+        settings.extend(self.scanning_settings(&config.scanning));
+        settings.extend(self.execution_settings(&config.execution));
+        settings.extend([
+            Setting::layered(
+                "report.sign",
+                flag(self.report.sign_report),
+                config.report.sign.map(Value::from),
+                Some(false.into()),
+            ),
+            Setting::layered(
+                "report.signing_key",
+                self.report.signing_key.as_deref().map(path_value),
+                config.report.signing_key.as_deref().map(path_value),
+                None,
+            ),
+            Setting::layered(
+                "hooks.pre_project",
+                None,
+                config.hooks.pre_project.as_deref().map(Value::from),
+                None,
+            ),
+            Setting::layered(
+                "hooks.post_project",
+                None,
+                config.hooks.post_project.as_deref().map(Value::from),
+                None,
+            ),
+        ]);
+
+        for setting in &mut settings {
+            if setting.source == SettingSource::CommandLine
+                && self
+                    .env_options
+                    .iter()
+                    .any(|id| id == option_id(setting.key))
+            {
+                setting.source = SettingSource::Environment;
+            }
         }
+        settings
+    }
+
+    /// The `[filtering]` settings in effect.
+    fn filtering_settings(&self, filtering: &FileFilterConfig) -> [Setting; 6] {
+        let file_sort = filtering
+            .sort
+            .as_deref()
+            .filter(|name| SortCriteria::from_str(name, true).is_ok());
+
+        [
+            Setting::layered(
+                "filtering.keep_size",
+                self.filtering.keep_size.as_deref().map(Value::from),
+                filtering.keep_size.as_deref().map(Value::from),
+                Some("0".into()),
+            ),
+            Setting::layered(
+                "filtering.keep_days",
+                self.filtering.keep_days.map(Value::from),
+                filtering.keep_days.map(Value::from),
+                Some(0.into()),
+            ),
+            Setting::layered(
+                "filtering.git_age",
+                flag(self.filtering.git_age),
+                filtering.git_age.map(Value::from),
+                Some(false.into()),
+            ),
+            Setting::layered(
+                "filtering.sort",
+                self.filtering.sort.as_ref().and_then(value_name),
+                file_sort.map(Value::from),
+                Some("size".into()),
+            ),
+            Setting::layered(
+                "filtering.reverse",
+                flag(self.filtering.reverse),
+                filtering.reverse.map(Value::from),
+                Some(false.into()),
+            ),
+            Setting::layered(
+                "filtering.protected_artifacts",
+                None,
+                filtering.protected_artifacts.clone().map(Value::from),
+                Some(Value::Array(Vec::new())),
+            ),
+        ]
+    }
+
+    /// The `[scanning]` settings in effect.
+    fn scanning_settings(&self, scanning: &FileScanConfig) -> [Setting; 6] {
+        // CLI entries are appended to the config file's
+        let cli_skip = (!self.scanning.skip.is_empty()).then(|| {
+            let mut skip = scanning.skip.clone().unwrap_or_default();
+            skip.extend(self.scanning.skip.iter().cloned());
+            skip
+        });
+
+        [
+            Setting::layered(
+                "scanning.threads",
+                number(self.scanning.threads),
+                number(scanning.threads),
+                Some(0.into()),
+            ),
+            Setting::layered(
+                "scanning.verbose",
+                flag(self.scanning.verbose > 0),
+                scanning.verbose.map(Value::from),
+                Some(false.into()),
+            ),
+            Setting::layered(
+                "scanning.skip",
+                cli_skip.as_deref().map(paths_value),
+                scanning.skip.as_deref().map(paths_value),
+                Some(Value::Array(Vec::new())),
+            ),
+            Setting::layered(
+                "scanning.ignore",
+                None,
+                scanning.ignore.as_deref().map(paths_value),
+                Some(Value::Array(Vec::new())),
+            ),
+            Setting::layered(
+                "scanning.scan_budget",
+                self.scanning.scan_budget.as_deref().map(Value::from),
+                scanning.scan_budget.as_deref().map(Value::from),
+                None,
+            ),
+            Setting::layered(
+                "scanning.max_dirs",
+                self.scanning.max_dirs.as_deref().map(Value::from),
+                scanning.max_dirs.as_deref().map(Value::from),
+                None,
+            ),
+        ]
+    }
+
+    /// The `[execution]` settings in effect.
+    fn execution_settings(&self, execution: &FileExecutionConfig) -> [Setting; 9] {
+        let dry_run = self.execution.dry_run
+            || matches!(
+                self.command,
+                Some(Command::Scan { .. } | Command::List { .. })
+            );
+        let retry = RetryPolicy::default();
+
+        [
+            Setting::layered(
+                "execution.keep_executables",
+                flag(self.execution.keep_executables),
+                execution.keep_executables.map(Value::from),
+                Some(false.into()),
+            ),
+            Setting::layered(
+                "execution.interactive",
+                flag(self.execution.interactive),
+                execution.interactive.map(Value::from),
+                Some(false.into()),
+            ),
+            Setting::layered(
+                "execution.dry_run",
+                flag(dry_run),
+                execution.dry_run.map(Value::from),
+                Some(false.into()),
+            ),
+            Setting::layered(
+                "execution.use_trash",
+                self.execution.permanent.then_some(false.into()),
+                execution.use_trash.map(Value::from),
+                Some(true.into()),
+            ),
+            Setting::layered(
+                "execution.native_clean",
+                flag(self.execution.native_clean),
+                execution.native_clean.map(Value::from),
+                Some(false.into()),
+            ),
+            Setting::layered(
+                "execution.retries",
+                self.execution.retries.map(Value::from),
+                execution.retries.map(Value::from),
+                Some(retry.retries.into()),
+            ),
+            Setting::layered(
+                "execution.retry_delay_ms",
+                number(self.execution.retry_delay),
+                number(execution.retry_delay_ms),
+                number(Some(retry.initial_delay.as_millis())),
+            ),
+            Setting::layered(
+                "execution.min_delete_depth",
+                number(self.execution.min_delete_depth),
+                number(execution.min_delete_depth),
+                number(Some(DeletionGuard::default().min_depth)),
+            ),
+            Setting::layered(
+                "execution.confirm_threshold",
+                self.execution.confirm_threshold.as_deref().map(Value::from),
+                execution.confirm_threshold.as_deref().map(Value::from),
+                Some(DEFAULT_CONFIRM_THRESHOLD.into()),
+            ),
+        ]
+    }
+}
+
+/// A flag as a setting value: `true` if given, unset otherwise.
+fn flag(set: bool) -> Option<Value> {
+    set.then_some(Value::Boolean(true))
+}
+
+/// Parse the environment variable of a counted flag: a count, or a flag
+/// value standing for 1 or 0.
+fn parse_verbosity(value: &str) -> std::result::Result<u8, String> {
+    value
+        .parse()
+        .or_else(|_| match value.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" => Ok(1),
+            "false" | "no" | "off" => Ok(0),
+            _ => Err(format!("expected a count or a flag value, not {value:?}")),
+        })
+}
+
+/// A count as a setting value.
+fn number<T: TryInto<i64>>(number: Option<T>) -> Option<Value> {
+    number
+        .and_then(|number| number.try_into().ok())
+        .map(Value::Integer)
+}
+
+/// A path as a setting value.
+fn path_value(path: &Path) -> Value {
+    path.display().to_string().into()
+}
+
+/// A list of paths as a setting value.
+fn paths_value(paths: &[PathBuf]) -> Value {
+    Value::Array(paths.iter().map(|path| path_value(path)).collect())
+}
+
+/// The name of a command-line value as a setting value.
+fn value_name(value: &impl ValueEnum) -> Option<Value> {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().into())
+}
+
+/// The environment variable setting the option with id `id`, e.g.
+/// `CLEAN_DEV_DIRS_KEEP_SIZE` for `keep_size`.
+fn env_var(id: &str) -> String {
+    format!("{ENV_PREFIX}{}", id.to_uppercase())
+}
+
+/// Id of the option setting the configuration key `key`.
+fn option_id(key: &str) -> &str {
+    match key {
+        "dir" => "dirs",
+        "execution.use_trash" => "permanent",
+        "execution.retry_delay_ms" => "retry_delay",
+        "report.sign" => "sign_report",
+        _ => key.rsplit('.').next().unwrap_or(key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use clean_dev_dirs::config::file::{
+        DEFAULT_CONFIG, FileConfig, FileExecutionConfig, FileFilterConfig, FileHooksConfig,
+        FileReportConfig, FileScanConfig,
+    };
+
+    // ── Existing tests (updated for FileConfig parameter) ──────────────
+
+    #[test]
+    fn test_default_values() {
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        let config = FileConfig::default();
+
+        assert_eq!(args.directories(&config), [PathBuf::from(".")]);
+        assert_eq!(args.project_filter(&config), ProjectFilter::All);
+
+        let exec_opts = args.execution_options(&config);
+        assert!(!exec_opts.dry_run);
+        assert!(!exec_opts.interactive);
+        assert!(!exec_opts.keep_executables);
+        assert!(exec_opts.use_trash);
+
+        let scan_opts = args.scan_options(&config);
+        assert!(!scan_opts.verbose);
+        assert_eq!(scan_opts.threads, 0);
+        assert!(scan_opts.skip.is_empty());
+
+        let filter_opts = args.filter_options(&config);
+        assert_eq!(filter_opts.keep_size, "0");
+        assert_eq!(filter_opts.keep_days, 0);
+        assert_eq!(filter_opts.top, None);
+        assert_eq!(filter_opts.free, None);
+    }
+
+    #[test]
+    fn test_project_filters() {
+        let config = FileConfig::default();
+
+        let rust_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "rust"]);
+        assert_eq!(rust_args.project_filter(&config), ProjectFilter::Rust);
+
+        let node_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "node"]);
+        assert_eq!(node_args.project_filter(&config), ProjectFilter::Node);
+
+        let python_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "python"]);
+        assert_eq!(python_args.project_filter(&config), ProjectFilter::Python);
+
+        let go_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "go"]);
+        assert_eq!(go_args.project_filter(&config), ProjectFilter::Go);
+
+        let java_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "java"]);
+        assert_eq!(java_args.project_filter(&config), ProjectFilter::Java);
+
+        let cpp_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "cpp"]);
+        assert_eq!(cpp_args.project_filter(&config), ProjectFilter::Cpp);
+
+        let swift_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "swift"]);
+        assert_eq!(swift_args.project_filter(&config), ProjectFilter::Swift);
+
+        let dotnet_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "dotnet"]);
+        assert_eq!(dotnet_args.project_filter(&config), ProjectFilter::DotNet);
+
+        let ocaml_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "ocaml"]);
+        assert_eq!(ocaml_args.project_filter(&config), ProjectFilter::OCaml);
+
+        let unity_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "unity"]);
+        assert_eq!(unity_args.project_filter(&config), ProjectFilter::Unity);
+
+        let unreal_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "unreal"]);
+        assert_eq!(unreal_args.project_filter(&config), ProjectFilter::Unreal);
+
+        let all_args = Cli::parse_from(["clean-dev-dirs"]);
+        assert_eq!(all_args.project_filter(&config), ProjectFilter::All);
+    }
+
+    #[test]
+    fn test_project_filter_short_flag() {
+        let config = FileConfig::default();
+        let rust_args = Cli::parse_from(["clean-dev-dirs", "-p", "rust"]);
+        assert_eq!(rust_args.project_filter(&config), ProjectFilter::Rust);
+    }
+
+    #[test]
+    fn test_execution_options() {
+        let config = FileConfig::default();
+        let args = Cli::parse_from(["clean-dev-dirs", "--dry-run", "--interactive", "--yes"]);
+        let exec_opts = args.execution_options(&config);
+
+        assert!(exec_opts.dry_run);
+        assert!(exec_opts.interactive);
+        assert!(!exec_opts.keep_executables);
+        assert!(exec_opts.use_trash);
+        assert!(exec_opts.yes);
+    }
+
+    #[test]
+    fn test_confirm_threshold_layering() {
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        let exec_opts = args.execution_options(&FileConfig::default());
+        assert!(!exec_opts.yes);
+        assert_eq!(exec_opts.confirm_threshold, "50GB");
+
+        let config = FileConfig {
+            execution: FileExecutionConfig {
+                confirm_threshold: Some("10GB".to_string()),
+                ..FileExecutionConfig::default()
+            },
+            ..FileConfig::default()
+        };
+        assert_eq!(args.execution_options(&config).confirm_threshold, "10GB");
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--confirm-threshold", "1GiB"]);
+        assert_eq!(args.execution_options(&config).confirm_threshold, "1GiB");
+    }
+
+    #[test]
+    fn test_keep_executables_flag() {
+        let config = FileConfig::default();
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--keep-executables"]);
+        let exec_opts = args.execution_options(&config);
+        assert!(exec_opts.keep_executables);
+
+        let args_short = Cli::parse_from(["clean-dev-dirs", "-k"]);
+        let exec_opts_short = args_short.execution_options(&config);
+        assert!(exec_opts_short.keep_executables);
+    }
+
+    #[test]
+    fn test_trash_is_default() {
+        let config = FileConfig::default();
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        let exec_opts = args.execution_options(&config);
+        assert!(exec_opts.use_trash);
+    }
+
+    #[test]
+    fn test_permanent_flag_disables_trash() {
+        let config = FileConfig::default();
+        let args = Cli::parse_from(["clean-dev-dirs", "--permanent"]);
+        let exec_opts = args.execution_options(&config);
+        assert!(!exec_opts.use_trash);
+    }
+
+    #[test]
+    fn test_config_use_trash_false_disables_trash() {
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        let config = FileConfig {
+            execution: FileExecutionConfig {
+                use_trash: Some(false),
+                ..FileExecutionConfig::default()
+            },
+            ..FileConfig::default()
+        };
+
+        let exec_opts = args.execution_options(&config);
+        assert!(!exec_opts.use_trash);
+    }
+
+    #[test]
+    fn test_permanent_flag_overrides_config_use_trash_true() {
+        let args = Cli::parse_from(["clean-dev-dirs", "--permanent"]);
+        let config = FileConfig {
+            execution: FileExecutionConfig {
+                use_trash: Some(true),
+                ..FileExecutionConfig::default()
+            },
+            ..FileConfig::default()
+        };
+
+        let exec_opts = args.execution_options(&config);
+        assert!(!exec_opts.use_trash);
+    }
+
+    #[test]
+    fn test_scanning_options() {
+        let config = FileConfig::default();
+        let args = Cli::parse_from([
+            "clean-dev-dirs",
+            "--verbose",
+            "--threads",
+            "8",
+            "--skip",
+            "node_modules",
+            "--skip",
+            ".git",
+        ]);
+        let scan_opts = args.scan_options(&config);
+
+        assert!(scan_opts.verbose);
+        assert_eq!(scan_opts.threads, 8);
+        assert_eq!(scan_opts.skip.len(), 2);
+        assert!(scan_opts.skip.contains(&PathBuf::from("node_modules")));
+        assert!(scan_opts.skip.contains(&PathBuf::from(".git")));
+    }
+
+    #[test]
+    fn test_list_files_requires_dry_run() {
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--list-files", "my-app"]).is_err());
+
+        let args = Cli::parse_from([
+            "clean-dev-dirs",
+            "--dry-run",
+            "--list-files",
+            "my-app",
+            "--list-files-output",
+            "files.txt",
+        ]);
+        assert_eq!(args.list_files(), Some("my-app"));
+        assert_eq!(args.list_files_output(), Some(Path::new("files.txt")));
+    }
+
+    #[test]
+    fn test_list_files_conflicts_with_json() {
+        assert!(
+            Cli::try_parse_from([
+                "clean-dev-dirs",
+                "--dry-run",
+                "--json",
+                "--list-files",
+                "my-app"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_estimate_compressed_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).estimate_compressed());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--estimate-compressed"]).estimate_compressed());
+    }
+
+    #[test]
+    fn test_width_option() {
+        assert_eq!(Cli::parse_from(["clean-dev-dirs"]).width(), None);
+        assert_eq!(
+            Cli::parse_from(["clean-dev-dirs", "--width", "60"]).width(),
+            Some(60)
+        );
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--width", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_no_pager_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).no_pager());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--no-pager"]).no_pager());
+    }
+
+    #[test]
+    fn test_append_csv_flag() {
+        assert!(Cli::parse_from(["clean-dev-dirs"]).append_csv().is_none());
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--append-csv", "stats.csv"]);
+        assert_eq!(args.append_csv(), Some(Path::new("stats.csv")));
+    }
+
+    #[test]
+    fn test_scan_limits_layering() {
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        assert_eq!(
+            args.scan_limits(&FileConfig::default()).unwrap(),
+            ScanLimits::default()
+        );
+
+        let config = FileConfig {
+            scanning: FileScanConfig {
+                scan_budget: Some("5m".to_string()),
+                max_dirs: Some("1M".to_string()),
+                ..FileScanConfig::default()
+            },
+            ..FileConfig::default()
+        };
+        let limits = args.scan_limits(&config).unwrap();
+        assert_eq!(limits.budget, Some(Duration::from_mins(5)));
+        assert_eq!(limits.max_dirs, Some(1_000_000));
+
+        let args = Cli::parse_from([
+            "clean-dev-dirs",
+            "--scan-budget",
+            "90s",
+            "--max-dirs",
+            "500K",
+        ]);
+        let limits = args.scan_limits(&config).unwrap();
+        assert_eq!(limits.budget, Some(Duration::from_secs(90)));
+        assert_eq!(limits.max_dirs, Some(500_000));
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--scan-budget", "forever"]);
+        assert!(args.scan_limits(&config).is_err());
+    }
+
+    #[test]
+    fn test_path_filter_globs() {
+        let args = Cli::parse_from([
+            "clean-dev-dirs",
+            "--include",
+            "/work/**",
+            "--exclude",
+            "**/experiments/**",
+        ]);
+        let filter = args.path_filter().unwrap();
+
+        assert!(filter.allows(Path::new("/work/app")));
+        assert!(!filter.allows(Path::new("/work/experiments/app")));
+        assert!(!filter.allows(Path::new("/play/app")));
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--exclude", "[oops"]);
+        assert!(args.path_filter().is_err());
+    }
+
+    #[test]
+    fn test_no_progress_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).no_progress());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--no-progress"]).no_progress());
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).scheduled());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--scheduled"]).scheduled());
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).exact_sizes());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--exact-sizes"]).exact_sizes());
+    }
+
+    #[test]
+    fn test_no_size_ignores_keep_size() {
+        let config = FileConfig {
+            filtering: FileFilterConfig {
+                keep_size: Some("50MB".to_string()),
+                ..FileFilterConfig::default()
+            },
+            ..FileConfig::default()
+        };
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--no-size"]);
+        assert!(!args.sizing());
+        assert_eq!(args.filter_options(&config).keep_size, "0");
+        assert!(Cli::parse_from(["clean-dev-dirs"]).sizing());
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--no-size", "-s", "1GB"]).is_err());
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--no-size", "--top", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_refresh_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).refresh());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--refresh"]).refresh());
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--refresh", "--no-size"]).is_err());
+    }
+
+    #[test]
+    fn test_du_mode() {
+        assert_eq!(
+            Cli::parse_from(["clean-dev-dirs"]).du_mode(),
+            DuMode::Apparent
+        );
+        assert_eq!(
+            Cli::parse_from(["clean-dev-dirs", "--du-mode", "disk"]).du_mode(),
+            DuMode::Disk
+        );
+        assert!(
+            Cli::try_parse_from(["clean-dev-dirs", "--du-mode", "disk", "--recheck-size"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_windows_defender_report_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).windows_defender_report());
+        assert!(
+            Cli::parse_from(["clean-dev-dirs", "--windows-defender-report"])
+                .windows_defender_report()
+        );
+        assert!(
+            Cli::try_parse_from(["clean-dev-dirs", "--windows-defender-report", "--json"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_native_clean_flags() {
+        let config = FileConfig::default();
+
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        assert!(!args.execution_options(&config).native_clean);
+
+        let args = Cli::parse_from([
+            "clean-dev-dirs",
+            "--native-clean",
+            "--native-clean-release",
+            "--native-clean-package",
+            "foo",
+            "--native-clean-package",
+            "bar",
+        ]);
+        assert!(args.execution_options(&config).native_clean);
+        assert_eq!(
+            args.native_clean(),
+            NativeClean {
+                release: true,
+                packages: vec!["foo".to_string(), "bar".to_string()],
+            }
+        );
+
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--native-clean-release"]).is_err());
+    }
+
+    #[test]
+    fn test_report_signing_options() {
+        let config = FileConfig::default();
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--json"]);
+        assert!(!args.sign_report(&config));
+        assert_eq!(args.signing_key(&config), None);
+
+        let args = Cli::parse_from([
+            "clean-dev-dirs",
+            "--json",
+            "--sign-report",
+            "--signing-key",
+            "report.key",
+        ]);
+        assert!(args.sign_report(&config));
+        assert_eq!(args.signing_key(&config), Some(PathBuf::from("report.key")));
+
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--sign-report"]).is_err());
+    }
+
+    #[test]
+    fn test_report_signing_from_config() {
+        let config = FileConfig {
+            report: FileReportConfig {
+                sign: Some(true),
+                signing_key: Some(PathBuf::from("/keys/report.key")),
+            },
+            ..FileConfig::default()
+        };
+
+        // Signing only applies to JSON output
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).sign_report(&config));
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--json"]);
+        assert!(args.sign_report(&config));
+        assert_eq!(
+            args.signing_key(&config),
+            Some(PathBuf::from("/keys/report.key"))
+        );
+    }
+
+    #[test]
+    fn test_verify_report_is_exclusive() {
+        let args = Cli::parse_from(["clean-dev-dirs", "--verify-report", "report.json"]);
+        assert_eq!(args.verify_report(), Some(Path::new("report.json")));
+
+        assert!(
+            Cli::try_parse_from(["clean-dev-dirs", "--verify-report", "report.json", "--json"])
+                .is_err()
+        );
     }
 
-    /// Extract sorting options from CLI args and config file.
-    ///
-    /// Priority: CLI argument > config file > default (no sorting).
-    ///
-    /// # Examples
-    ///
-    /// ```no_run
-    /// # use clap::Parser;
-    /// # use clean_dev_dirs::config::{FileConfig, SortCriteria};
-    /// # mod cli { include!("cli.rs"); }
-    /// # use cli::Cli;
-    /// let args = Cli::parse_from(&["clean-dev-dirs", "--sort", "size", "--reverse"]);
-    /// let sort_opts = args.sort_options(&FileConfig::default());
-    /// assert_eq!(sort_opts.criteria, Some(SortCriteria::Size));
-    /// assert!(sort_opts.reverse);
-    /// ```
-    #[must_use]
-    pub fn sort_options(&self, config: &FileConfig) -> SortOptions {
-        SortOptions {
-            criteria: self.filtering.sort.or_else(|| {
-                config
-                    .filtering
-                    .sort
-                    .as_ref()
-                    .and_then(|s| SortCriteria::from_str(s, true).ok())
-            }),
-            reverse: self.filtering.reverse || config.filtering.reverse.unwrap_or(false),
-        }
+    #[test]
+    fn test_restore_subcommand() {
+        let args = Cli::parse_from(["clean-dev-dirs", "restore", "20260105-093000"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Restore { run_id }) if run_id == "20260105-093000"
+        ));
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "restore"]).is_err());
+
+        let args = Cli::parse_from(["clean-dev-dirs", "~/Projects"]);
+        assert!(args.command().is_none());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use clap::Parser;
-    use clean_dev_dirs::config::file::{
-        FileConfig, FileExecutionConfig, FileFilterConfig, FileScanConfig,
-    };
+    #[test]
+    fn test_history_subcommand() {
+        let args = Cli::parse_from(["clean-dev-dirs", "history"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::History { action: None })
+        ));
+
+        let args = Cli::parse_from(["clean-dev-dirs", "history", "show", "latest"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::History {
+                action: Some(HistoryAction::Show { run_id })
+            }) if run_id == "latest"
+        ));
+    }
 
-    // ── Existing tests (updated for FileConfig parameter) ──────────────
+    #[test]
+    fn test_why_big_subcommand() {
+        let args = Cli::parse_from(["clean-dev-dirs", "why-big", "~/Projects/app"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::WhyBig { project, top: 15 }) if project == Path::new("~/Projects/app")
+        ));
+
+        let args = Cli::parse_from(["clean-dev-dirs", "why-big", "app", "--top", "5"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::WhyBig { top: 5, .. })
+        ));
+    }
 
     #[test]
-    fn test_default_values() {
+    fn test_stats_subcommand() {
+        let args = Cli::parse_from(["clean-dev-dirs", "-p", "rust", "stats", "~/Projects"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Stats { dirs }) if dirs == &[PathBuf::from("~/Projects")]
+        ));
+        assert_eq!(
+            args.project_filter(&FileConfig::default()),
+            ProjectFilter::Rust
+        );
+
+        let args = Cli::parse_from(["clean-dev-dirs", "stats"]);
+        assert!(matches!(args.command(), Some(Command::Stats { dirs }) if dirs.is_empty()));
+    }
+
+    #[test]
+    fn test_audit_log_flag() {
+        assert!(Cli::parse_from(["clean-dev-dirs"]).audit_log().is_none());
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--audit-log", "audit.jsonl"]);
+        assert_eq!(args.audit_log(), Some(Path::new("audit.jsonl")));
+    }
+
+    #[test]
+    fn test_verbosity() {
+        let args = Cli::parse_from(["clean-dev-dirs", "-vv", "--log-file", "run.log"]);
+        assert_eq!(args.verbosity(&FileConfig::default()), 2);
+        assert!(args.scan_options(&FileConfig::default()).verbose);
+        assert_eq!(args.log_file(), Some(Path::new("run.log")));
+
+        let mut config = FileConfig::default();
+        config.scanning.verbose = Some(true);
         let args = Cli::parse_from(["clean-dev-dirs"]);
+        assert_eq!(args.verbosity(&config), 1);
+        assert_eq!(args.verbosity(&FileConfig::default()), 0);
+
+        assert_eq!(parse_verbosity("3"), Ok(3));
+        assert_eq!(parse_verbosity("Yes"), Ok(1));
+        assert_eq!(parse_verbosity("off"), Ok(0));
+        assert!(parse_verbosity("loud").is_err());
+    }
+
+    #[test]
+    fn test_completions() {
+        let args = Cli::parse_from(["clean-dev-dirs", "completions", "zsh"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Completions { shell: Shell::Zsh })
+        ));
+
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut script = Vec::new();
+            clap_complete::generate(
+                shell,
+                &mut Cli::command_with_env(),
+                "clean-dev-dirs",
+                &mut script,
+            );
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("project-type"), "{shell}");
+            assert!(script.contains("unreal"), "{shell}");
+        }
+    }
+
+    #[test]
+    fn test_plan_and_apply() {
+        let args = Cli::parse_from(["clean-dev-dirs", "--dry-run", "--plan", "out.json"]);
+        assert_eq!(args.plan(), Some(Path::new("out.json")));
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--plan", "out.json"]).is_err());
+
+        let args = Cli::parse_from(["clean-dev-dirs", "apply", "out.json", "--dry-run"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Apply { file }) if file == Path::new("out.json")
+        ));
+        assert_eq!(args.plan(), None);
+    }
+
+    #[test]
+    fn test_cleanup_subcommands() {
         let config = FileConfig::default();
 
-        assert_eq!(args.directory(&config), PathBuf::from("."));
-        assert_eq!(args.project_filter(&config), ProjectFilter::All);
+        let args = Cli::parse_from(["clean-dev-dirs", "clean", "~/Projects", "--dry-run"]);
+        assert!(args.command().is_some_and(Command::is_cleanup));
+        assert_eq!(args.directories(&config), [PathBuf::from("~/Projects")]);
+        assert!(args.execution_options(&config).dry_run);
 
-        let exec_opts = args.execution_options(&config);
-        assert!(!exec_opts.dry_run);
-        assert!(!exec_opts.interactive);
-        assert!(!exec_opts.keep_executables);
-        assert!(exec_opts.use_trash);
+        let args = Cli::parse_from(["clean-dev-dirs", "--yes", "scan", "/work"]);
+        assert!(matches!(args.command(), Some(Command::Scan { .. })));
+        assert_eq!(args.directories(&config), [PathBuf::from("/work")]);
+        assert!(args.execution_options(&config).dry_run);
+        assert!(!args.lists_projects());
 
-        let scan_opts = args.scan_options(&config);
-        assert!(!scan_opts.verbose);
-        assert_eq!(scan_opts.threads, 0);
-        assert!(scan_opts.skip.is_empty());
+        let args = Cli::parse_from(["clean-dev-dirs", "list", "-p", "rust"]);
+        assert!(args.lists_projects());
+        assert!(args.execution_options(&config).dry_run);
+        assert_eq!(args.project_filter(&config), ProjectFilter::Rust);
+        assert_eq!(args.directories(&config), [PathBuf::from(".")]);
+
+        // Without a subcommand, `clean-dev-dirs` cleans
+        let args = Cli::parse_from(["clean-dev-dirs", "/work"]);
+        assert!(args.command().is_none());
+        assert!(!args.execution_options(&config).dry_run);
+        assert_eq!(args.directories(&config), [PathBuf::from("/work")]);
+
+        // Several directories are searched at once
+        let args = Cli::parse_from(["clean-dev-dirs", "~/work", "/mnt/oss", "--keep-days", "7"]);
+        assert_eq!(
+            args.directories(&config),
+            [PathBuf::from("~/work"), PathBuf::from("/mnt/oss")]
+        );
+        let args = Cli::parse_from(["clean-dev-dirs", "scan", "/work", "/oss"]);
+        assert_eq!(
+            args.directories(&config),
+            [PathBuf::from("/work"), PathBuf::from("/oss")]
+        );
+    }
 
-        let filter_opts = args.filter_options(&config);
-        assert_eq!(filter_opts.keep_size, "0");
-        assert_eq!(filter_opts.keep_days, 0);
+    #[test]
+    fn test_config_subcommand() {
+        let args = Cli::parse_from(["clean-dev-dirs", "config", "path"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Config {
+                action: Some(ConfigAction::Path)
+            })
+        ));
+
+        let args = Cli::parse_from(["clean-dev-dirs", "config"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Config { action: None })
+        ));
+        assert!(!args.command().is_some_and(Command::is_cleanup));
+
+        let args = Cli::parse_from(["clean-dev-dirs", "config", "init", "--force"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Config {
+                action: Some(ConfigAction::Init)
+            })
+        ));
+        assert!(args.force());
+
+        let args = Cli::parse_from(["clean-dev-dirs", "config", "validate", "other.toml"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Config {
+                action: Some(ConfigAction::Validate { file: Some(file) })
+            }) if file == Path::new("other.toml")
+        ));
     }
 
     #[test]
-    fn test_project_filters() {
-        let config = FileConfig::default();
+    fn test_settings_sources() {
+        let args = Cli::parse_from([
+            "clean-dev-dirs",
+            "--keep-days",
+            "30",
+            "--skip",
+            "vendor",
+            "--permanent",
+            "config",
+        ]);
+        let config = FileConfig {
+            project_type: Some("node".to_string()),
+            filtering: FileFilterConfig {
+                keep_days: Some(7),
+                sort: Some("invalid_sort".to_string()),
+                ..FileFilterConfig::default()
+            },
+            scanning: FileScanConfig {
+                skip: Some(vec![PathBuf::from(".cargo")]),
+                ..FileScanConfig::default()
+            },
+            ..FileConfig::default()
+        };
+        let settings = args.settings(&config);
+        let setting = |key: &str| settings.iter().find(|setting| setting.key == key).unwrap();
+
+        assert_eq!(setting("filtering.keep_days").value, Some(Value::from(30)));
+        assert_eq!(
+            setting("filtering.keep_days").source,
+            SettingSource::CommandLine
+        );
+        assert_eq!(setting("project_type").value, Some(Value::from("node")));
+        assert_eq!(setting("project_type").source, SettingSource::ConfigFile);
+        // An invalid sort in the config file falls back to the default
+        assert_eq!(setting("filtering.sort").value, Some(Value::from("size")));
+        assert_eq!(setting("filtering.sort").source, SettingSource::Default);
+        assert_eq!(
+            setting("scanning.skip").value,
+            Some(Value::from(vec![".cargo", "vendor"]))
+        );
+        assert_eq!(
+            setting("execution.use_trash").value,
+            Some(Value::from(false))
+        );
+        assert_eq!(setting("scanning.scan_budget").value, None);
+        assert_eq!(setting("execution.retries").value, Some(Value::from(3)));
+
+        // Every setting is a key of the configuration file
+        for setting in &settings {
+            let key = setting.key.rsplit('.').next().unwrap();
+            assert!(DEFAULT_CONFIG.contains(&format!("# {key} = ")), "{key}");
+        }
+    }
 
-        let rust_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "rust"]);
-        assert_eq!(rust_args.project_filter(&config), ProjectFilter::Rust);
+    #[test]
+    fn test_schedule_subcommand() {
+        let args = Cli::parse_from(["clean-dev-dirs", "--keep-days", "30", "schedule", "install"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Schedule {
+                action: ScheduleAction::Install {
+                    dirs,
+                    daily: false,
+                    weekly: false
+                }
+            }) if dirs.is_empty()
+        ));
+        assert!(!args.has_directory());
+
+        let args = Cli::parse_from(["clean-dev-dirs", "schedule", "install", "~/src", "--daily"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Schedule {
+                action: ScheduleAction::Install {
+                    dirs,
+                    daily: true,
+                    ..
+                }
+            }) if dirs.len() == 1
+        ));
+
+        assert!(
+            Cli::try_parse_from([
+                "clean-dev-dirs",
+                "schedule",
+                "install",
+                "--daily",
+                "--weekly"
+            ])
+            .is_err()
+        );
+        for action in ["remove", "status"] {
+            assert!(Cli::try_parse_from(["clean-dev-dirs", "schedule", action]).is_ok());
+        }
+    }
 
-        let node_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "node"]);
-        assert_eq!(node_args.project_filter(&config), ProjectFilter::Node);
+    #[test]
+    fn test_env_vars() {
+        let command = Cli::command_with_env();
+        let mut vars = Vec::new();
+        for arg in command.get_arguments() {
+            let var = arg.get_env().map(|var| var.to_string_lossy().into_owned());
+            if arg.is_positional() {
+                assert_eq!(var, None);
+                continue;
+            }
+            let var = var.unwrap();
+            assert!(var.starts_with(ENV_PREFIX), "{var}");
+            vars.push(var);
+        }
+        assert!(vars.contains(&"CLEAN_DEV_DIRS_KEEP_SIZE".to_string()));
+        assert!(vars.contains(&"CLEAN_DEV_DIRS_DRY_RUN".to_string()));
 
-        let python_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "python"]);
-        assert_eq!(python_args.project_filter(&config), ProjectFilter::Python);
+        // Every setting with an option names an option of the CLI
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        for setting in args.settings(&FileConfig::default()) {
+            let id = option_id(setting.key);
+            assert!(
+                command.get_arguments().any(|arg| arg.get_id() == id)
+                    || ["protected_artifacts", "pre_project", "post_project"].contains(&id),
+                "{id}"
+            );
+        }
+    }
 
-        let go_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "go"]);
-        assert_eq!(go_args.project_filter(&config), ProjectFilter::Go);
+    #[test]
+    fn test_settings_from_env() {
+        let mut args = Cli::parse_from(["clean-dev-dirs", "--keep-days", "7", "--permanent"]);
+        args.env_options = vec!["keep_days".to_string(), "permanent".to_string()];
+        let settings = args.settings(&FileConfig::default());
+        let source = |key: &str| {
+            settings
+                .iter()
+                .find(|setting| setting.key == key)
+                .unwrap()
+                .source
+        };
 
-        let java_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "java"]);
-        assert_eq!(java_args.project_filter(&config), ProjectFilter::Java);
+        assert_eq!(source("filtering.keep_days"), SettingSource::Environment);
+        assert_eq!(source("execution.use_trash"), SettingSource::Environment);
+        assert_eq!(source("filtering.keep_size"), SettingSource::Default);
+    }
 
-        let cpp_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "cpp"]);
-        assert_eq!(cpp_args.project_filter(&config), ProjectFilter::Cpp);
+    #[test]
+    fn test_hook_subcommand() {
+        let args = Cli::parse_from(["clean-dev-dirs", "hook", "install"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Hook {
+                action: HookAction::Install { repo: None }
+            })
+        ));
 
-        let swift_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "swift"]);
-        assert_eq!(swift_args.project_filter(&config), ProjectFilter::Swift);
+        let args = Cli::parse_from(["clean-dev-dirs", "hook", "remove", "~/src/app"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Hook {
+                action: HookAction::Remove { repo: Some(_) }
+            })
+        ));
 
-        let dotnet_args = Cli::parse_from(["clean-dev-dirs", "--project-type", "dotnet"]);
-        assert_eq!(dotnet_args.project_filter(&config), ProjectFilter::DotNet);
+        let args = Cli::parse_from(["clean-dev-dirs", "hook", "record"]);
+        assert!(matches!(
+            args.command(),
+            Some(Command::Hook {
+                action: HookAction::Record
+            })
+        ));
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "hook"]).is_err());
+    }
 
-        let all_args = Cli::parse_from(["clean-dev-dirs"]);
-        assert_eq!(all_args.project_filter(&config), ProjectFilter::All);
+    #[test]
+    fn test_depth_limits() {
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        assert_eq!((args.max_depth(), args.min_depth()), (None, 0));
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--max-depth", "2", "--min-depth", "1"]);
+        assert_eq!((args.max_depth(), args.min_depth()), (Some(2), 1));
     }
 
     #[test]
-    fn test_project_filter_short_flag() {
-        let config = FileConfig::default();
-        let rust_args = Cli::parse_from(["clean-dev-dirs", "-p", "rust"]);
-        assert_eq!(rust_args.project_filter(&config), ProjectFilter::Rust);
+    fn test_one_file_system_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).one_file_system());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--one-file-system"]).one_file_system());
     }
 
     #[test]
-    fn test_execution_options() {
-        let config = FileConfig::default();
-        let args = Cli::parse_from(["clean-dev-dirs", "--dry-run", "--interactive", "--yes"]);
-        let exec_opts = args.execution_options(&config);
+    fn test_allow_network_fs_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).allow_network_fs());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--allow-network-fs"]).allow_network_fs());
+    }
 
-        assert!(exec_opts.dry_run);
-        assert!(exec_opts.interactive);
-        assert!(!exec_opts.keep_executables);
-        assert!(exec_opts.use_trash);
+    #[test]
+    fn test_no_ignore_flag() {
+        assert!(Cli::parse_from(["clean-dev-dirs"]).ignore_files());
+        assert!(!Cli::parse_from(["clean-dev-dirs", "--no-ignore"]).ignore_files());
     }
 
     #[test]
-    fn test_keep_executables_flag() {
-        let config = FileConfig::default();
+    fn test_only_outdated_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).only_outdated());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--only-outdated"]).only_outdated());
+    }
 
-        let args = Cli::parse_from(["clean-dev-dirs", "--keep-executables"]);
-        let exec_opts = args.execution_options(&config);
-        assert!(exec_opts.keep_executables);
+    #[test]
+    fn test_skip_dirty_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).skip_dirty());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--skip-dirty"]).skip_dirty());
+    }
 
-        let args_short = Cli::parse_from(["clean-dev-dirs", "-k"]);
-        let exec_opts_short = args_short.execution_options(&config);
-        assert!(exec_opts_short.keep_executables);
+    #[test]
+    fn test_artifacts_flag() {
+        assert!(Cli::parse_from(["clean-dev-dirs"]).artifacts().is_empty());
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--artifacts", "caches,node:deps"]);
+        assert_eq!(args.artifacts().len(), 2);
+        assert_eq!(args.artifacts()[1].ecosystem, ProjectFilter::Node);
+
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--artifacts", "node:all"]).is_err());
     }
 
     #[test]
-    fn test_trash_is_default() {
-        let config = FileConfig::default();
+    fn test_git_change_filter() {
         let args = Cli::parse_from(["clean-dev-dirs"]);
-        let exec_opts = args.execution_options(&config);
-        assert!(exec_opts.use_trash);
+        assert_eq!(args.git_change_filter(), None);
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--changed-since", "main"]);
+        assert_eq!(
+            args.git_change_filter(),
+            Some(GitChangeFilter::ChangedSince("main".to_string()))
+        );
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--unchanged-since", "v1.0"]);
+        assert_eq!(
+            args.git_change_filter(),
+            Some(GitChangeFilter::UnchangedSince("v1.0".to_string()))
+        );
+
+        assert!(
+            Cli::try_parse_from([
+                "clean-dev-dirs",
+                "--changed-since",
+                "main",
+                "--unchanged-since",
+                "v1.0"
+            ])
+            .is_err()
+        );
     }
 
     #[test]
-    fn test_permanent_flag_disables_trash() {
+    fn test_stale_only() {
         let config = FileConfig::default();
-        let args = Cli::parse_from(["clean-dev-dirs", "--permanent"]);
-        let exec_opts = args.execution_options(&config);
-        assert!(!exec_opts.use_trash);
+
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        assert!(!args.stale_only());
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--stale-only", "-p", "node"]);
+        assert!(args.stale_only());
+        assert_eq!(args.project_filter(&config), ProjectFilter::Rust);
+
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--stale-only", "--native-clean"]).is_err());
     }
 
     #[test]
-    fn test_config_use_trash_false_disables_trash() {
+    fn test_retry_policy() {
         let args = Cli::parse_from(["clean-dev-dirs"]);
+        assert_eq!(
+            args.execution_options(&FileConfig::default()).retry,
+            RetryPolicy::default()
+        );
+
         let config = FileConfig {
             execution: FileExecutionConfig {
-                use_trash: Some(false),
+                retries: Some(5),
+                retry_delay_ms: Some(250),
                 ..FileExecutionConfig::default()
             },
             ..FileConfig::default()
         };
-
-        let exec_opts = args.execution_options(&config);
-        assert!(!exec_opts.use_trash);
+        let retry = args.execution_options(&config).retry;
+        assert_eq!(retry.retries, 5);
+        assert_eq!(retry.initial_delay, Duration::from_millis(250));
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--retries", "0", "--retry-delay", "10"]);
+        let retry = args.execution_options(&config).retry;
+        assert_eq!(retry.retries, 0);
+        assert_eq!(retry.initial_delay, Duration::from_millis(10));
     }
 
     #[test]
-    fn test_permanent_flag_overrides_config_use_trash_true() {
-        let args = Cli::parse_from(["clean-dev-dirs", "--permanent"]);
+    fn test_min_delete_depth() {
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        assert_eq!(
+            args.execution_options(&FileConfig::default()).guard,
+            DeletionGuard::default()
+        );
+
         let config = FileConfig {
             execution: FileExecutionConfig {
-                use_trash: Some(true),
+                min_delete_depth: Some(4),
                 ..FileExecutionConfig::default()
             },
             ..FileConfig::default()
         };
+        assert_eq!(args.execution_options(&config).guard.min_depth, 4);
 
-        let exec_opts = args.execution_options(&config);
-        assert!(!exec_opts.use_trash);
+        let args = Cli::parse_from(["clean-dev-dirs", "--min-delete-depth", "2"]);
+        assert_eq!(args.execution_options(&config).guard.min_depth, 2);
     }
 
     #[test]
-    fn test_scanning_options() {
-        let config = FileConfig::default();
-        let args = Cli::parse_from([
-            "clean-dev-dirs",
-            "--verbose",
-            "--threads",
-            "8",
-            "--skip",
-            "node_modules",
-            "--skip",
-            ".git",
-        ]);
-        let scan_opts = args.scan_options(&config);
+    fn test_force_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).force());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--force"]).force());
+    }
 
-        assert!(scan_opts.verbose);
-        assert_eq!(scan_opts.threads, 8);
-        assert_eq!(scan_opts.skip.len(), 2);
-        assert!(scan_opts.skip.contains(&PathBuf::from("node_modules")));
-        assert!(scan_opts.skip.contains(&PathBuf::from(".git")));
+    #[test]
+    fn test_check_in_use_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).check_in_use());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--check-in-use"]).check_in_use());
+    }
+
+    #[test]
+    fn test_cargo_cache_flag() {
+        assert!(!Cli::parse_from(["clean-dev-dirs"]).cargo_cache());
+        assert!(Cli::parse_from(["clean-dev-dirs", "--cargo-cache", "--dry-run"]).cargo_cache());
+        assert!(
+            Cli::try_parse_from([
+                "clean-dev-dirs",
+                "--cargo-cache",
+                "--dry-run",
+                "--list-files",
+                "my-app"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_node_cache_flags() {
+        let args = Cli::parse_from(["clean-dev-dirs", "--node-cache", "--pnpm-prune"]);
+        assert!(args.node_cache());
+        assert!(args.pnpm_prune());
+        assert!(!args.cargo_cache());
+
+        let both = Cli::parse_from(["clean-dev-dirs", "--node-cache", "--cargo-cache"]);
+        assert!(both.node_cache() && both.cargo_cache());
+
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--pnpm-prune"]).is_err());
+    }
+
+    #[test]
+    fn test_go_cache_flags() {
+        let args = Cli::parse_from(["clean-dev-dirs", "--go-cache", "--go-clean"]);
+        assert!(args.go_cache());
+        assert!(args.go_clean());
+
+        assert!(Cli::try_parse_from(["clean-dev-dirs", "--go-clean"]).is_err());
     }
 
     #[test]
@@ -549,18 +3049,24 @@ mod tests {
             "100MB",
             "--keep-days",
             "30",
+            "--top",
+            "10",
+            "--free",
+            "50GB",
         ]);
         let filter_opts = args.filter_options(&config);
 
         assert_eq!(filter_opts.keep_size, "100MB");
         assert_eq!(filter_opts.keep_days, 30);
+        assert_eq!(filter_opts.top, Some(10));
+        assert_eq!(filter_opts.free.as_deref(), Some("50GB"));
     }
 
     #[test]
     fn test_custom_directory() {
         let config = FileConfig::default();
         let args = Cli::parse_from(["clean-dev-dirs", "/custom/path"]);
-        assert_eq!(args.directory(&config), PathBuf::from("/custom/path"));
+        assert_eq!(args.directories(&config), [PathBuf::from("/custom/path")]);
     }
 
     #[test]
@@ -677,16 +3183,28 @@ mod tests {
                 verbose: Some(true),
                 skip: Some(vec![PathBuf::from(".cargo")]),
                 ignore: Some(vec![PathBuf::from(".git")]),
+                scan_budget: None,
+                max_dirs: None,
             },
             execution: FileExecutionConfig {
                 keep_executables: Some(true),
                 interactive: Some(true),
                 dry_run: Some(true),
                 use_trash: Some(true),
+                native_clean: Some(true),
+                retries: Some(5),
+                retry_delay_ms: None,
+                min_delete_depth: None,
+                confirm_threshold: None,
+            },
+            report: FileReportConfig::default(),
+            hooks: FileHooksConfig {
+                pre_project: Some("docker compose stop".to_string()),
+                post_project: None,
             },
         };
 
-        assert_eq!(args.directory(&config), PathBuf::from("/config/dir"));
+        assert_eq!(args.directories(&config), [PathBuf::from("/config/dir")]);
         assert_eq!(args.project_filter(&config), ProjectFilter::Rust);
 
         let filter_opts = args.filter_options(&config);
@@ -703,6 +3221,12 @@ mod tests {
         assert!(exec_opts.interactive);
         assert!(exec_opts.dry_run);
         assert!(exec_opts.use_trash);
+        assert!(exec_opts.native_clean);
+        assert_eq!(
+            exec_opts.hooks.pre_project.as_deref(),
+            Some("docker compose stop")
+        );
+        assert_eq!(exec_opts.hooks.post_project, None);
     }
 
     #[test]
@@ -734,7 +3258,7 @@ mod tests {
             ..FileConfig::default()
         };
 
-        assert_eq!(args.directory(&config), PathBuf::from("/cli/dir"));
+        assert_eq!(args.directories(&config), [PathBuf::from("/cli/dir")]);
         assert_eq!(args.project_filter(&config), ProjectFilter::Node);
 
         let filter_opts = args.filter_options(&config);
@@ -745,6 +3269,34 @@ mod tests {
         assert_eq!(scan_opts.threads, 8);
     }
 
+    #[test]
+    fn test_local_filter_options_layering() {
+        let config = FileConfig {
+            filtering: FileFilterConfig {
+                keep_size: Some("50MB".to_string()),
+                keep_days: Some(7),
+                ..FileFilterConfig::default()
+            },
+            ..FileConfig::default()
+        };
+        let local = LocalFilterConfig {
+            keep_days: Some(90),
+            protected_artifacts: Some(vec![".venv".to_string()]),
+            ..LocalFilterConfig::default()
+        };
+
+        // Local file > config file
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        let filter_opts = args.local_filter_options(&config, &local);
+        assert_eq!(filter_opts.keep_size, "50MB");
+        assert_eq!(filter_opts.keep_days, 90);
+        assert_eq!(filter_opts.protected_artifacts, [".venv"]);
+
+        // CLI > local file
+        let args = Cli::parse_from(["clean-dev-dirs", "--keep-days", "1"]);
+        assert_eq!(args.local_filter_options(&config, &local).keep_days, 1);
+    }
+
     #[test]
     fn test_skip_dirs_merged_from_both_sources() {
         let args = Cli::parse_from(["clean-dev-dirs", "--skip", "node_modules"]);
@@ -772,6 +3324,11 @@ mod tests {
                 interactive: Some(true),
                 keep_executables: Some(false),
                 use_trash: Some(true),
+                native_clean: Some(false),
+                retries: None,
+                retry_delay_ms: Some(50),
+                min_delete_depth: None,
+                confirm_threshold: None,
             },
             ..FileConfig::default()
         };
@@ -791,9 +3348,9 @@ mod tests {
             ..FileConfig::default()
         };
 
-        let dir = args.directory(&config);
+        let directories = args.directories(&config);
         if let Some(home) = dirs::home_dir() {
-            assert_eq!(dir, home.join("Projects"));
+            assert_eq!(directories, [home.join("Projects")]);
         }
     }
 
@@ -833,7 +3390,7 @@ mod tests {
         let config = FileConfig::default();
         let sort_opts = args.sort_options(&config);
 
-        assert!(sort_opts.criteria.is_none());
+        assert_eq!(sort_opts.criteria, Some(SortCriteria::Size));
         assert!(!sort_opts.reverse);
     }
 
@@ -881,7 +3438,7 @@ mod tests {
         let config = FileConfig::default();
         let sort_opts = args.sort_options(&config);
 
-        assert!(sort_opts.criteria.is_none());
+        assert_eq!(sort_opts.criteria, Some(SortCriteria::Size));
         assert!(sort_opts.reverse);
     }
 
@@ -918,7 +3475,7 @@ mod tests {
     }
 
     #[test]
-    fn test_sort_options_invalid_config_falls_back_to_none() {
+    fn test_sort_options_invalid_config_falls_back_to_size() {
         let args = Cli::parse_from(["clean-dev-dirs"]);
         let config = FileConfig {
             filtering: FileFilterConfig {
@@ -929,7 +3486,7 @@ mod tests {
         };
         let sort_opts = args.sort_options(&config);
 
-        assert!(sort_opts.criteria.is_none());
+        assert_eq!(sort_opts.criteria, Some(SortCriteria::Size));
     }
 
     #[test]
@@ -947,6 +3504,24 @@ mod tests {
         assert_eq!(sort_opts.criteria, Some(SortCriteria::Size));
     }
 
+    #[test]
+    fn test_git_age_cli_or_config() {
+        let args = Cli::parse_from(["clean-dev-dirs"]);
+        assert!(!args.filter_options(&FileConfig::default()).git_age);
+
+        let config = FileConfig {
+            filtering: FileFilterConfig {
+                git_age: Some(true),
+                ..FileFilterConfig::default()
+            },
+            ..FileConfig::default()
+        };
+        assert!(args.filter_options(&config).git_age);
+
+        let args = Cli::parse_from(["clean-dev-dirs", "--git-age"]);
+        assert!(args.filter_options(&FileConfig::default()).git_age);
+    }
+
     #[test]
     fn test_sort_reverse_cli_or_config() {
         // CLI reverse=true overrides config reverse=false
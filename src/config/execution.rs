@@ -3,6 +3,10 @@
 //! This module defines the options that control how cleanup operations are executed,
 //! including dry-run mode and interactive selection.
 
+use crate::hooks::ProjectHooks;
+use crate::utils::guard::DeletionGuard;
+use crate::utils::retry::RetryPolicy;
+
 /// Configuration for cleanup execution behavior.
 ///
 /// This struct provides a simplified interface to execution-related options,
@@ -16,6 +20,9 @@ pub struct ExecutionOptions {
     /// Whether to use interactive project selection
     pub interactive: bool,
 
+    /// Whether to select projects in the full-screen terminal interface
+    pub tui: bool,
+
     /// Whether to preserve compiled executables before cleaning
     pub keep_executables: bool,
 
@@ -24,6 +31,26 @@ pub struct ExecutionOptions {
     /// Defaults to `true`. Set to `false` via the `--permanent` CLI flag or
     /// `use_trash = false` in the config file.
     pub use_trash: bool,
+
+    /// Whether to clean Rust projects with `cargo clean` instead of deleting
+    /// `target/` directly.
+    pub native_clean: bool,
+
+    /// How transient deletion failures are retried.
+    pub retry: RetryPolicy,
+
+    /// Which paths are never deleted, whatever was selected.
+    pub guard: DeletionGuard,
+
+    /// Whether to clean without asking for confirmation (`--yes`)
+    pub yes: bool,
+
+    /// Total size from which cleaning must be confirmed by typing "delete"
+    /// rather than answering y/N (e.g. `"50GB"`)
+    pub confirm_threshold: String,
+
+    /// Commands run before and after cleaning each project
+    pub hooks: ProjectHooks,
 }
 
 #[cfg(test)]
@@ -35,14 +62,26 @@ mod tests {
         let exec_opts = ExecutionOptions {
             dry_run: true,
             interactive: false,
+            tui: false,
             keep_executables: false,
             use_trash: false,
+            native_clean: true,
+            retry: RetryPolicy::none(),
+            guard: DeletionGuard::with_min_depth(2),
+            yes: true,
+            confirm_threshold: "50GB".to_string(),
+            hooks: ProjectHooks::default(),
         };
 
         assert!(exec_opts.dry_run);
         assert!(!exec_opts.interactive);
         assert!(!exec_opts.keep_executables);
         assert!(!exec_opts.use_trash);
+        assert!(exec_opts.native_clean);
+        assert_eq!(exec_opts.retry.retries, 0);
+        assert_eq!(exec_opts.guard.min_depth, 2);
+        assert!(exec_opts.yes);
+        assert_eq!(exec_opts.confirm_threshold, "50GB");
     }
 
     #[test]
@@ -50,14 +89,30 @@ mod tests {
         let original = ExecutionOptions {
             dry_run: true,
             interactive: false,
+            tui: true,
             keep_executables: true,
             use_trash: true,
+            native_clean: false,
+            retry: RetryPolicy::default(),
+            guard: DeletionGuard::default(),
+            yes: false,
+            confirm_threshold: "1GB".to_string(),
+            hooks: ProjectHooks {
+                pre_project: Some("docker compose stop".to_string()),
+                post_project: None,
+            },
         };
         let cloned = original.clone();
 
         assert_eq!(original.dry_run, cloned.dry_run);
         assert_eq!(original.interactive, cloned.interactive);
+        assert_eq!(original.tui, cloned.tui);
         assert_eq!(original.keep_executables, cloned.keep_executables);
         assert_eq!(original.use_trash, cloned.use_trash);
+        assert_eq!(original.native_clean, cloned.native_clean);
+        assert_eq!(original.retry, cloned.retry);
+        assert_eq!(original.guard, cloned.guard);
+        assert_eq!(original.confirm_threshold, cloned.confirm_threshold);
+        assert_eq!(original.hooks, cloned.hooks);
     }
 }
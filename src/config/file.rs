@@ -9,6 +9,10 @@
 //!
 //! The precedence order is: **CLI argument > config file > hardcoded default**.
 //!
+//! Filtering options can also be overridden for a subtree by a
+//! `.clean-dev-dirs.toml` file inside the scanned tree (see [`LocalConfigs`]),
+//! giving **CLI argument > local file > config file > hardcoded default**.
+//!
 //! # Example config
 //!
 //! ```toml
@@ -18,26 +22,129 @@
 //! [filtering]
 //! keep_size = "50MB"
 //! keep_days = 7
+//! git_age = false
 //! sort = "size"
 //! reverse = false
+//! protected_artifacts = [".venv"]
 //!
 //! [scanning]
 //! threads = 4
 //! verbose = true
 //! skip = [".cargo", "vendor"]
 //! ignore = [".git"]
+//! scan_budget = "5m"
+//! max_dirs = "1M"
 //!
 //! [execution]
 //! keep_executables = true
 //! interactive = false
 //! dry_run = false
 //! use_trash = true    # default; set to false for permanent deletion
+//! native_clean = false
+//! retries = 3
+//! retry_delay_ms = 100
+//! min_delete_depth = 3
+//! confirm_threshold = "50GB"
+//!
+//! [report]
+//! sign = true
+//! signing_key = "~/.config/clean-dev-dirs/report.key"
+//!
+//! [hooks]
+//! pre_project = "docker compose stop"
+//! post_project = "docker compose start"
 //! ```
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
 
+use crate::error::{Error, Result};
+
+/// Name of the files overriding filtering options for the subtree they are in.
+pub const LOCAL_CONFIG_FILE: &str = ".clean-dev-dirs.toml";
+
+/// Configuration file written by `config init`: every setting, commented
+/// out and set to its default.
+pub const DEFAULT_CONFIG: &str = r#"# clean-dev-dirs configuration
+#
+# Every setting below is commented out and shows its default. Options given
+# on the command line override the values set here; `clean-dev-dirs config
+# show` prints the settings in effect.
+
+# Project types to clean: all, rust, node, python, go, java, cpp, swift,
+# dotnet, ocaml, unity or unreal
+# project_type = "all"
+
+# Directory to scan (~ is expanded)
+# dir = "."
+
+[filtering]
+# Keep projects whose artifacts are smaller than this (e.g. "50MB")
+# keep_size = "0"
+
+# Keep projects modified within this many days
+# keep_days = 0
+
+# Measure keep_days from the last commit touching each project
+# git_age = false
+
+# Order of the projects: size, age, name or type
+# sort = "size"
+# reverse = false
+
+# Build directories never to clean
+# protected_artifacts = [".venv"]
+
+[scanning]
+# Threads scanning the tree (0 for one per CPU core)
+# threads = 0
+# verbose = false
+
+# Directories not to scan
+# skip = [".cargo", "vendor"]
+# ignore = [".git"]
+
+# Stop scanning after this long, or this many directories
+# scan_budget = "5m"
+# max_dirs = "1M"
+
+[execution]
+# keep_executables = false
+# interactive = false
+# dry_run = false
+
+# Move artifacts to the trash; false deletes them permanently
+# use_trash = true
+
+# Clean Rust projects with `cargo clean`
+# native_clean = false
+
+# Retries of deletions failing for a transient reason, after a delay
+# doubled for each retry
+# retries = 3
+# retry_delay_ms = 100
+
+# Never delete paths with fewer components than this
+# min_delete_depth = 3
+
+# Ask to type "delete" to clean this much or more
+# confirm_threshold = "50GB"
+
+[report]
+# Sign --json reports with the Ed25519 key at signing_key
+# sign = false
+# signing_key = "~/.config/clean-dev-dirs/report.key"
+
+[hooks]
+# Shell commands run in each project root before and after cleaning it, with
+# $PROJECT_PATH, $PROJECT_TYPE and $ARTIFACT_PATH set. A project whose
+# pre_project command fails is skipped.
+# pre_project = "docker compose stop"
+# post_project = "docker compose start"
+"#;
+
 /// Top-level configuration file structure.
 ///
 /// All fields are `Option<T>` so we can detect which values are present in the
@@ -61,10 +168,18 @@ pub struct FileConfig {
     /// Execution options
     #[serde(default)]
     pub execution: FileExecutionConfig,
+
+    /// JSON report options
+    #[serde(default)]
+    pub report: FileReportConfig,
+
+    /// Commands run around the cleaning of each project
+    #[serde(default)]
+    pub hooks: FileHooksConfig,
 }
 
 /// Filtering options from the configuration file.
-#[derive(Deserialize, Default, Debug)]
+#[derive(Deserialize, Default, Debug, Clone)]
 pub struct FileFilterConfig {
     /// Minimum size threshold (e.g., `"50MB"`)
     pub keep_size: Option<String>,
@@ -72,11 +187,146 @@ pub struct FileFilterConfig {
     /// Minimum age in days
     pub keep_days: Option<u32>,
 
+    /// Whether to measure the age from the last commit
+    pub git_age: Option<bool>,
+
     /// Sort criterion for project output (`"size"`, `"age"`, `"name"`, `"type"`)
     pub sort: Option<String>,
 
     /// Whether to reverse the sort order
     pub reverse: Option<bool>,
+
+    /// Names of build directories never to clean (e.g. `".venv"`)
+    pub protected_artifacts: Option<Vec<String>>,
+}
+
+impl FileFilterConfig {
+    /// Layer the overrides of a local `.clean-dev-dirs.toml` file on top of
+    /// these options.
+    #[must_use]
+    pub fn with_local(&self, local: &LocalFilterConfig) -> Self {
+        Self {
+            keep_size: local.keep_size.clone().or_else(|| self.keep_size.clone()),
+            keep_days: local.keep_days.or(self.keep_days),
+            protected_artifacts: local
+                .protected_artifacts
+                .clone()
+                .or_else(|| self.protected_artifacts.clone()),
+            ..self.clone()
+        }
+    }
+}
+
+/// A `.clean-dev-dirs.toml` file inside a scanned tree.
+#[derive(Deserialize, Default, Debug)]
+struct LocalConfig {
+    /// Filtering overrides for the subtree
+    #[serde(default)]
+    filtering: LocalFilterConfig,
+}
+
+/// Filtering options a `.clean-dev-dirs.toml` file overrides for its subtree.
+#[derive(Deserialize, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LocalFilterConfig {
+    /// Minimum size threshold (e.g., `"50MB"`)
+    pub keep_size: Option<String>,
+
+    /// Minimum age in days
+    pub keep_days: Option<u32>,
+
+    /// Names of build directories never to clean (e.g. `".venv"`)
+    pub protected_artifacts: Option<Vec<String>>,
+}
+
+impl LocalFilterConfig {
+    /// Fill the options these overrides leave unset from `outer`, the
+    /// overrides of an enclosing directory.
+    fn or(self, outer: &Self) -> Self {
+        Self {
+            keep_size: self.keep_size.or_else(|| outer.keep_size.clone()),
+            keep_days: self.keep_days.or(outer.keep_days),
+            protected_artifacts: self
+                .protected_artifacts
+                .or_else(|| outer.protected_artifacts.clone()),
+        }
+    }
+}
+
+/// The `.clean-dev-dirs.toml` files of the scanned trees, read on first use.
+#[derive(Debug)]
+pub struct LocalConfigs {
+    /// Roots of the scanned trees; files above them are not considered.
+    roots: Vec<PathBuf>,
+
+    /// Overrides of each directory looked at, if it has a local file.
+    files: HashMap<PathBuf, Option<LocalFilterConfig>>,
+}
+
+impl LocalConfigs {
+    /// Create an empty cache of the local files below `roots`.
+    #[must_use]
+    pub fn new(roots: &[PathBuf]) -> Self {
+        Self {
+            roots: roots.to_vec(),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Merge the local files of `dir` and its ancestors up to the first
+    /// scanned root it is under, a file deeper in the tree overriding the
+    /// ones above it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the files cannot be read or parsed, rather
+    /// than cleaning a subtree its user meant to protect.
+    pub fn resolve(&mut self, dir: &Path) -> Result<LocalFilterConfig> {
+        let mut merged = LocalFilterConfig::default();
+        let Some(root) = self
+            .roots
+            .iter()
+            .find(|root| dir.starts_with(root))
+            .cloned()
+        else {
+            return Ok(merged);
+        };
+
+        for ancestor in dir.ancestors() {
+            if let Some(local) = self.file(ancestor)? {
+                merged = merged.or(local);
+            }
+            if ancestor == root {
+                break;
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Return the overrides of the local file of `dir`, if it has one.
+    fn file(&mut self, dir: &Path) -> Result<Option<&LocalFilterConfig>> {
+        if !self.files.contains_key(dir) {
+            let local = Self::load(&dir.join(LOCAL_CONFIG_FILE))?;
+            self.files.insert(dir.to_path_buf(), local);
+        }
+        Ok(self.files[dir].as_ref())
+    }
+
+    /// Load the local file at `path`, if it exists.
+    fn load(path: &Path) -> Result<Option<LocalFilterConfig>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let content =
+            std::fs::read_to_string(path).map_err(Error::file("read config file at", path))?;
+
+        let config: LocalConfig = toml::from_str(&content).map_err(|source| Error::Config {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Ok(Some(config.filtering))
+    }
 }
 
 /// Scanning options from the configuration file.
@@ -93,6 +343,12 @@ pub struct FileScanConfig {
 
     /// Directories to ignore during scanning
     pub ignore: Option<Vec<PathBuf>>,
+
+    /// Wall-clock time the directory traversal may take (e.g. `"5m"`)
+    pub scan_budget: Option<String>,
+
+    /// Number of directories the traversal may visit (e.g. `"1M"`)
+    pub max_dirs: Option<String>,
 }
 
 /// Execution options from the configuration file.
@@ -110,6 +366,42 @@ pub struct FileExecutionConfig {
     /// Whether to move directories to the system trash instead of permanently deleting them.
     /// Defaults to `true` when absent. Set to `false` for permanent deletion.
     pub use_trash: Option<bool>,
+
+    /// Whether to clean Rust projects with `cargo clean`
+    pub native_clean: Option<bool>,
+
+    /// How often to retry transient deletion failures
+    pub retries: Option<u32>,
+
+    /// Delay before the first retry in milliseconds, doubled for each retry
+    pub retry_delay_ms: Option<u64>,
+
+    /// Minimum number of components of a deleted path
+    pub min_delete_depth: Option<usize>,
+
+    /// Total size from which cleaning must be confirmed by typing "delete"
+    pub confirm_threshold: Option<String>,
+}
+
+/// JSON report options from the configuration file.
+#[derive(Deserialize, Default, Debug)]
+pub struct FileReportConfig {
+    /// Whether to sign `--json` reports
+    pub sign: Option<bool>,
+
+    /// Path to the base64-encoded Ed25519 key used to sign reports
+    pub signing_key: Option<PathBuf>,
+}
+
+/// Per-project hooks from the configuration file.
+#[derive(Deserialize, Default, Debug)]
+pub struct FileHooksConfig {
+    /// Shell command run before cleaning each project; the project is
+    /// skipped if it fails
+    pub pre_project: Option<String>,
+
+    /// Shell command run after cleaning each project
+    pub post_project: Option<String>,
 }
 
 /// Expand a leading `~` in a path to the user's home directory.
@@ -160,7 +452,7 @@ impl FileConfig {
     /// Returns an error if:
     /// - The config file exists but cannot be read
     /// - The config file exists but contains invalid TOML or unexpected fields
-    pub fn load() -> anyhow::Result<Self> {
+    pub fn load() -> Result<Self> {
         let Some(path) = Self::config_path() else {
             return Ok(Self::default());
         };
@@ -169,13 +461,11 @@ impl FileConfig {
             return Ok(Self::default());
         }
 
-        let content = std::fs::read_to_string(&path).map_err(|e| {
-            anyhow::anyhow!("Failed to read config file at {}: {e}", path.display())
-        })?;
+        let content =
+            std::fs::read_to_string(&path).map_err(Error::file("read config file at", &path))?;
 
-        let config: Self = toml::from_str(&content).map_err(|e| {
-            anyhow::anyhow!("Failed to parse config file at {}: {e}", path.display())
-        })?;
+        let config: Self =
+            toml::from_str(&content).map_err(|source| Error::Config { path, source })?;
 
         Ok(config)
     }
@@ -195,6 +485,7 @@ mod tests {
         assert!(config.filtering.keep_days.is_none());
         assert!(config.filtering.sort.is_none());
         assert!(config.filtering.reverse.is_none());
+        assert!(config.filtering.protected_artifacts.is_none());
         assert!(config.scanning.threads.is_none());
         assert!(config.scanning.verbose.is_none());
         assert!(config.scanning.skip.is_none());
@@ -203,6 +494,14 @@ mod tests {
         assert!(config.execution.interactive.is_none());
         assert!(config.execution.dry_run.is_none());
         assert!(config.execution.use_trash.is_none());
+        assert!(config.execution.native_clean.is_none());
+        assert!(config.execution.retries.is_none());
+        assert!(config.execution.retry_delay_ms.is_none());
+        assert!(config.execution.confirm_threshold.is_none());
+        assert!(config.report.sign.is_none());
+        assert!(config.report.signing_key.is_none());
+        assert!(config.hooks.pre_project.is_none());
+        assert!(config.hooks.post_project.is_none());
     }
 
     #[test]
@@ -214,6 +513,7 @@ dir = "~/Projects"
 [filtering]
 keep_size = "50MB"
 keep_days = 7
+git_age = true
 sort = "size"
 reverse = true
 
@@ -222,12 +522,22 @@ threads = 4
 verbose = true
 skip = [".cargo", "vendor"]
 ignore = [".git"]
+scan_budget = "5m"
+max_dirs = "1M"
 
 [execution]
 keep_executables = true
 interactive = false
 dry_run = false
 use_trash = true
+
+[report]
+sign = true
+signing_key = "~/.config/clean-dev-dirs/report.key"
+
+[hooks]
+pre_project = "docker compose stop"
+post_project = "docker compose start"
 "#;
 
         let config: FileConfig = toml::from_str(toml_content).unwrap();
@@ -236,6 +546,7 @@ use_trash = true
         assert_eq!(config.dir, Some(PathBuf::from("~/Projects")));
         assert_eq!(config.filtering.keep_size, Some("50MB".to_string()));
         assert_eq!(config.filtering.keep_days, Some(7));
+        assert_eq!(config.filtering.git_age, Some(true));
         assert_eq!(config.filtering.sort, Some("size".to_string()));
         assert_eq!(config.filtering.reverse, Some(true));
         assert_eq!(config.scanning.threads, Some(4));
@@ -245,10 +556,25 @@ use_trash = true
             Some(vec![PathBuf::from(".cargo"), PathBuf::from("vendor")])
         );
         assert_eq!(config.scanning.ignore, Some(vec![PathBuf::from(".git")]));
+        assert_eq!(config.scanning.scan_budget.as_deref(), Some("5m"));
+        assert_eq!(config.scanning.max_dirs.as_deref(), Some("1M"));
         assert_eq!(config.execution.keep_executables, Some(true));
         assert_eq!(config.execution.interactive, Some(false));
         assert_eq!(config.execution.dry_run, Some(false));
         assert_eq!(config.execution.use_trash, Some(true));
+        assert_eq!(config.report.sign, Some(true));
+        assert_eq!(
+            config.report.signing_key,
+            Some(PathBuf::from("~/.config/clean-dev-dirs/report.key"))
+        );
+        assert_eq!(
+            config.hooks.pre_project.as_deref(),
+            Some("docker compose stop")
+        );
+        assert_eq!(
+            config.hooks.post_project.as_deref(),
+            Some("docker compose start")
+        );
     }
 
     #[test]
@@ -437,18 +763,95 @@ keep_days = "not_a_number"
 
     #[test]
     fn test_file_config_all_execution_options_parse() {
-        let toml_content = r"
+        let toml_content = r#"
 [execution]
 keep_executables = true
 interactive = false
 dry_run = true
 use_trash = false
-";
+native_clean = true
+retries = 5
+retry_delay_ms = 250
+confirm_threshold = "10GB"
+"#;
         let config: FileConfig = toml::from_str(toml_content).unwrap();
 
         assert_eq!(config.execution.keep_executables, Some(true));
         assert_eq!(config.execution.interactive, Some(false));
         assert_eq!(config.execution.dry_run, Some(true));
         assert_eq!(config.execution.use_trash, Some(false));
+        assert_eq!(config.execution.native_clean, Some(true));
+        assert_eq!(config.execution.retries, Some(5));
+        assert_eq!(config.execution.retry_delay_ms, Some(250));
+        assert_eq!(config.execution.confirm_threshold.as_deref(), Some("10GB"));
+    }
+
+    // ── Local config files ──────────────────────────────────────────────
+
+    #[test]
+    fn test_local_overrides_layer_over_config_file() {
+        let global = FileFilterConfig {
+            keep_size: Some("50MB".to_string()),
+            keep_days: Some(7),
+            sort: Some("age".to_string()),
+            ..FileFilterConfig::default()
+        };
+        let local = LocalFilterConfig {
+            keep_days: Some(90),
+            protected_artifacts: Some(vec![".venv".to_string()]),
+            ..LocalFilterConfig::default()
+        };
+
+        let layered = global.with_local(&local);
+
+        assert_eq!(layered.keep_size, Some("50MB".to_string()));
+        assert_eq!(layered.keep_days, Some(90));
+        assert_eq!(layered.sort, Some("age".to_string()));
+        assert_eq!(layered.protected_artifacts, Some(vec![".venv".to_string()]));
+    }
+
+    #[test]
+    fn test_local_configs_deeper_file_wins() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        let app = root.join("work").join("app");
+        std::fs::create_dir_all(&app).unwrap();
+        std::fs::write(
+            root.join(LOCAL_CONFIG_FILE),
+            "[filtering]\nkeep_days = 30\nkeep_size = \"1GB\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("work").join(LOCAL_CONFIG_FILE),
+            "[filtering]\nkeep_days = 90\n",
+        )
+        .unwrap();
+
+        let mut local_configs = LocalConfigs::new(&[root.to_path_buf()]);
+
+        let local = local_configs.resolve(&app).unwrap();
+        assert_eq!(local.keep_days, Some(90));
+        assert_eq!(local.keep_size, Some("1GB".to_string()));
+        assert_eq!(local_configs.resolve(root).unwrap().keep_days, Some(30));
+        // Paths outside the scanned tree get no overrides
+        assert_eq!(
+            local_configs.resolve(Path::new("/elsewhere")).unwrap(),
+            LocalFilterConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_local_configs_malformed_file_errors() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            tmp.path().join(LOCAL_CONFIG_FILE),
+            "[filtering]\nkeep_days = \"soon\"\n",
+        )
+        .unwrap();
+
+        let err = LocalConfigs::new(&[tmp.path().to_path_buf()])
+            .resolve(tmp.path())
+            .unwrap_err();
+        assert!(err.to_string().contains(LOCAL_CONFIG_FILE));
     }
 }
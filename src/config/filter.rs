@@ -4,13 +4,16 @@
 //! criteria used to determine which projects should be scanned, cleaned, and
 //! how they should be ordered in the output.
 
-use clap::ValueEnum;
+use std::str::FromStr;
+
+use crate::project::{ArtifactCategory, BuildArtifacts, DEPENDENCY_ARTIFACTS, ProjectType};
 
 /// Enumeration of supported project type filters.
 ///
 /// This enum is used to restrict scanning and cleaning to specific types of
 /// development projects.
-#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum, Default)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum ProjectFilter {
     /// Include all supported project types
     #[default]
@@ -38,8 +41,164 @@ pub enum ProjectFilter {
     Swift,
 
     /// Include only .NET/C# projects (.csproj + bin/ + obj/)
-    #[value(name = "dotnet")]
+    #[cfg_attr(feature = "cli", value(name = "dotnet"))]
     DotNet,
+
+    /// Include only `OCaml` projects (dune-project + `_build`/)
+    #[cfg_attr(feature = "cli", value(name = "ocaml"))]
+    OCaml,
+
+    /// Include only Unity projects (`ProjectSettings/ProjectVersion.txt` + Library/, Temp/, obj/, Logs/)
+    Unity,
+
+    /// Include only Unreal Engine projects (`.uproject` + Intermediate/, `DerivedDataCache`/, Saved/, Binaries/)
+    Unreal,
+}
+
+impl ProjectFilter {
+    /// Every filter, with its name on the command line and in the
+    /// configuration file.
+    const NAMES: [(Self, &'static str); 12] = [
+        (Self::All, "all"),
+        (Self::Rust, "rust"),
+        (Self::Node, "node"),
+        (Self::Python, "python"),
+        (Self::Go, "go"),
+        (Self::Java, "java"),
+        (Self::Cpp, "cpp"),
+        (Self::Swift, "swift"),
+        (Self::DotNet, "dotnet"),
+        (Self::OCaml, "ocaml"),
+        (Self::Unity, "unity"),
+        (Self::Unreal, "unreal"),
+    ];
+
+    /// Parse a filter from its name, ignoring case, e.g. `dotnet`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if no filter has this name.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        from_name(&Self::NAMES, name)
+    }
+
+    /// Check whether projects of `project_type` pass this filter.
+    #[must_use]
+    pub const fn includes(self, project_type: &ProjectType) -> bool {
+        matches!(
+            (self, project_type),
+            (Self::All, _)
+                | (Self::Rust, ProjectType::Rust)
+                | (Self::Node, ProjectType::Node)
+                | (Self::Python, ProjectType::Python)
+                | (Self::Go, ProjectType::Go)
+                | (Self::Java, ProjectType::Java)
+                | (Self::Cpp, ProjectType::Cpp)
+                | (Self::Swift, ProjectType::Swift)
+                | (Self::DotNet, ProjectType::DotNet)
+                | (Self::OCaml, ProjectType::OCaml)
+                | (Self::Unity, ProjectType::Unity)
+                | (Self::Unreal, ProjectType::Unreal)
+        )
+    }
+}
+
+/// Kind of build artifact selectable with `--artifacts`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ArtifactKind {
+    /// Installed dependencies (see [`DEPENDENCY_ARTIFACTS`])
+    Deps,
+
+    /// Build outputs (`target/`, `build/`, `__pycache__/`, …)
+    Build,
+
+    /// Generic tool caches (see [`crate::project::CACHE_ARTIFACTS`])
+    Caches,
+}
+
+impl ArtifactKind {
+    /// Every kind, with its name on the command line.
+    const NAMES: [(Self, &'static str); 3] = [
+        (Self::Deps, "deps"),
+        (Self::Build, "build"),
+        (Self::Caches, "caches"),
+    ];
+
+    /// Parse a kind from its name, ignoring case, e.g. `caches`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if no kind has this name.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        from_name(&Self::NAMES, name)
+    }
+
+    /// Kind of `artifact`, derived from its name.
+    #[must_use]
+    pub fn of(artifact: &BuildArtifacts) -> Self {
+        if artifact.category() == ArtifactCategory::Cache {
+            Self::Caches
+        } else if artifact
+            .path
+            .file_name()
+            .is_some_and(|name| DEPENDENCY_ARTIFACTS.iter().any(|deps| name == *deps))
+        {
+            Self::Deps
+        } else {
+            Self::Build
+        }
+    }
+}
+
+/// An `--artifacts` selector: a kind of artifact to clean, in every project
+/// or only in projects of one ecosystem.
+///
+/// Parsed from `KIND` or `ECOSYSTEM:KIND`, e.g. `caches` or `node:deps`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ArtifactSelector {
+    /// Kind of artifact selected
+    pub kind: ArtifactKind,
+
+    /// Projects the selector applies to
+    pub ecosystem: ProjectFilter,
+}
+
+impl ArtifactSelector {
+    /// Check whether `artifact` of a project of `project_type` is selected.
+    #[must_use]
+    pub fn selects(&self, project_type: &ProjectType, artifact: &BuildArtifacts) -> bool {
+        self.ecosystem.includes(project_type) && ArtifactKind::of(artifact) == self.kind
+    }
+
+    /// Check whether `selectors` select `artifact`; no selector selects
+    /// everything.
+    #[must_use]
+    pub fn any_selects(
+        selectors: &[Self],
+        project_type: &ProjectType,
+        artifact: &BuildArtifacts,
+    ) -> bool {
+        selectors.is_empty()
+            || selectors
+                .iter()
+                .any(|selector| selector.selects(project_type, artifact))
+    }
+}
+
+impl FromStr for ArtifactSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ecosystem, kind) = match s.split_once(':') {
+            Some((ecosystem, kind)) => (ProjectFilter::from_name(ecosystem)?, kind),
+            None => (ProjectFilter::All, s),
+        };
+        Ok(Self {
+            kind: ArtifactKind::from_name(kind)?,
+            ecosystem,
+        })
+    }
 }
 
 /// Configuration for project filtering criteria.
@@ -53,6 +212,52 @@ pub struct FilterOptions {
 
     /// Minimum age in days for projects to be considered
     pub keep_days: u32,
+
+    /// Measure the age compared with `keep_days` from the last commit
+    /// touching the project rather than from its build directories'
+    /// modification time
+    pub git_age: bool,
+
+    /// Keep only this many of the largest projects, if set
+    pub top: Option<usize>,
+
+    /// Keep only the fewest projects freeing this size (e.g. `"50GB"`), if set
+    pub free: Option<String>,
+
+    /// Names of build directories never to clean (e.g. `".venv"`)
+    pub protected_artifacts: Vec<String>,
+}
+
+/// Git-history-based project filter.
+///
+/// Selects projects by whether any of their tracked files changed since a git
+/// ref, for policies like "clean everything untouched since the last release".
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GitChangeFilter {
+    /// Keep only projects with changes since the given ref
+    ChangedSince(String),
+
+    /// Keep only projects without changes since the given ref
+    UnchangedSince(String),
+}
+
+impl GitChangeFilter {
+    /// The git ref projects are compared against.
+    #[must_use]
+    pub fn git_ref(&self) -> &str {
+        match self {
+            Self::ChangedSince(git_ref) | Self::UnchangedSince(git_ref) => git_ref,
+        }
+    }
+
+    /// Whether a project with (`changed = true`) or without changes is kept.
+    #[must_use]
+    pub const fn keeps(&self, changed: bool) -> bool {
+        match self {
+            Self::ChangedSince(_) => changed,
+            Self::UnchangedSince(_) => !changed,
+        }
+    }
 }
 
 /// Enumeration of supported sorting criteria for project output.
@@ -63,7 +268,8 @@ pub struct FilterOptions {
 /// - `Age`: oldest first (ascending)
 /// - `Name`: alphabetical (ascending)
 /// - `Type`: grouped by type name alphabetically
-#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum SortCriteria {
     /// Sort by build artifacts size (largest first by default)
     Size,
@@ -78,6 +284,36 @@ pub enum SortCriteria {
     Type,
 }
 
+impl SortCriteria {
+    /// Every criterion, with its name on the command line and in the
+    /// configuration file.
+    const NAMES: [(Self, &'static str); 4] = [
+        (Self::Size, "size"),
+        (Self::Age, "age"),
+        (Self::Name, "name"),
+        (Self::Type, "type"),
+    ];
+
+    /// Parse a criterion from its name, ignoring case, e.g. `size`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if no criterion has this name.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        from_name(&Self::NAMES, name)
+    }
+}
+
+/// Find the value named `name` in `names`, ignoring case, with the same
+/// error message as the command line parser.
+fn from_name<T: Copy>(names: &[(T, &str)], name: &str) -> Result<T, String> {
+    names
+        .iter()
+        .find(|(_, candidate)| candidate.eq_ignore_ascii_case(name))
+        .map(|&(value, _)| value)
+        .ok_or_else(|| format!("invalid variant: {name}"))
+}
+
 /// Configuration for project sorting behavior.
 ///
 /// Controls how the list of projects is ordered before display or processing.
@@ -95,6 +331,123 @@ pub struct SortOptions {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_git_change_filter() {
+        let changed = GitChangeFilter::ChangedSince("v1.0".to_string());
+        assert_eq!(changed.git_ref(), "v1.0");
+        assert!(changed.keeps(true));
+        assert!(!changed.keeps(false));
+
+        let unchanged = GitChangeFilter::UnchangedSince("main".to_string());
+        assert_eq!(unchanged.git_ref(), "main");
+        assert!(!unchanged.keeps(true));
+        assert!(unchanged.keeps(false));
+    }
+
+    #[test]
+    fn test_project_filter_includes() {
+        assert!(ProjectFilter::All.includes(&ProjectType::Go));
+        assert!(ProjectFilter::Node.includes(&ProjectType::Node));
+        assert!(!ProjectFilter::Node.includes(&ProjectType::Rust));
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(
+            ProjectFilter::from_name("DotNet"),
+            Ok(ProjectFilter::DotNet)
+        );
+        assert_eq!(ArtifactKind::from_name("caches"), Ok(ArtifactKind::Caches));
+        assert_eq!(SortCriteria::from_name("age"), Ok(SortCriteria::Age));
+        assert_eq!(
+            ProjectFilter::from_name("cobol"),
+            Err("invalid variant: cobol".to_string())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_names_match_the_command_line() {
+        use clap::ValueEnum;
+
+        fn check<T: ValueEnum + PartialEq + std::fmt::Debug>(
+            from_name: fn(&str) -> Result<T, String>,
+        ) {
+            for value in T::value_variants() {
+                let name = value.to_possible_value().unwrap();
+                assert_eq!(from_name(name.get_name()).as_ref(), Ok(value));
+            }
+        }
+        check(ProjectFilter::from_name);
+        check(ArtifactKind::from_name);
+        check(SortCriteria::from_name);
+    }
+
+    #[test]
+    fn test_artifact_kind_of() {
+        let kind = |path: &str| {
+            ArtifactKind::of(&BuildArtifacts {
+                path: path.into(),
+                size: 0,
+            })
+        };
+
+        assert_eq!(kind("/app/node_modules"), ArtifactKind::Deps);
+        assert_eq!(kind("/app/.venv"), ArtifactKind::Deps);
+        assert_eq!(kind("/app/target"), ArtifactKind::Build);
+        assert_eq!(kind("/app/__pycache__"), ArtifactKind::Build);
+        assert_eq!(kind("/app/.cache"), ArtifactKind::Caches);
+    }
+
+    #[test]
+    fn test_artifact_selector_parse() {
+        assert_eq!(
+            "caches".parse(),
+            Ok(ArtifactSelector {
+                kind: ArtifactKind::Caches,
+                ecosystem: ProjectFilter::All,
+            })
+        );
+        assert_eq!(
+            "node:deps".parse(),
+            Ok(ArtifactSelector {
+                kind: ArtifactKind::Deps,
+                ecosystem: ProjectFilter::Node,
+            })
+        );
+        assert!("node:everything".parse::<ArtifactSelector>().is_err());
+        assert!("cobol:deps".parse::<ArtifactSelector>().is_err());
+    }
+
+    #[test]
+    fn test_artifact_selectors_select() {
+        // Caches everywhere, dependencies only for Node
+        let selectors: Vec<ArtifactSelector> = ["caches", "node:deps"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let artifact = |path: &str| BuildArtifacts {
+            path: path.into(),
+            size: 0,
+        };
+        let selects = |project_type, path| {
+            ArtifactSelector::any_selects(&selectors, &project_type, &artifact(path))
+        };
+
+        assert!(selects(ProjectType::Node, "/web/node_modules"));
+        assert!(selects(ProjectType::Node, "/web/.cache"));
+        assert!(selects(ProjectType::Python, "/tool/.pytest_cache"));
+        assert!(!selects(ProjectType::Python, "/tool/.venv"));
+        assert!(!selects(ProjectType::Rust, "/app/target"));
+
+        // No selector selects everything
+        assert!(ArtifactSelector::any_selects(
+            &[],
+            &ProjectType::Rust,
+            &artifact("/app/target")
+        ));
+    }
+
     #[test]
     fn test_project_filter_equality() {
         assert_eq!(ProjectFilter::All, ProjectFilter::All);
@@ -106,6 +459,9 @@ mod tests {
         assert_eq!(ProjectFilter::Cpp, ProjectFilter::Cpp);
         assert_eq!(ProjectFilter::Swift, ProjectFilter::Swift);
         assert_eq!(ProjectFilter::DotNet, ProjectFilter::DotNet);
+        assert_eq!(ProjectFilter::OCaml, ProjectFilter::OCaml);
+        assert_eq!(ProjectFilter::Unity, ProjectFilter::Unity);
+        assert_eq!(ProjectFilter::Unreal, ProjectFilter::Unreal);
 
         assert_ne!(ProjectFilter::All, ProjectFilter::Rust);
         assert_ne!(ProjectFilter::Rust, ProjectFilter::Node);
@@ -115,6 +471,9 @@ mod tests {
         assert_ne!(ProjectFilter::Java, ProjectFilter::Cpp);
         assert_ne!(ProjectFilter::Cpp, ProjectFilter::Swift);
         assert_ne!(ProjectFilter::Swift, ProjectFilter::DotNet);
+        assert_ne!(ProjectFilter::DotNet, ProjectFilter::OCaml);
+        assert_ne!(ProjectFilter::OCaml, ProjectFilter::Unity);
+        assert_ne!(ProjectFilter::Unity, ProjectFilter::Unreal);
     }
 
     #[test]
@@ -136,10 +495,17 @@ mod tests {
         let filter_opts = FilterOptions {
             keep_size: "100MB".to_string(),
             keep_days: 30,
+            git_age: false,
+            top: Some(10),
+            free: Some("50GB".to_string()),
+            protected_artifacts: vec![".venv".to_string()],
         };
 
         assert_eq!(filter_opts.keep_size, "100MB");
         assert_eq!(filter_opts.keep_days, 30);
+        assert_eq!(filter_opts.top, Some(10));
+        assert_eq!(filter_opts.free.as_deref(), Some("50GB"));
+        assert_eq!(filter_opts.protected_artifacts, [".venv"]);
     }
 
     #[test]
@@ -147,6 +513,10 @@ mod tests {
         let original = FilterOptions {
             keep_size: "100MB".to_string(),
             keep_days: 30,
+            git_age: false,
+            top: None,
+            free: None,
+            protected_artifacts: vec![],
         };
         let cloned = original.clone();
 
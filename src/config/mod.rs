@@ -7,8 +7,9 @@ pub mod execution;
 pub mod file;
 pub mod filter;
 pub mod scan;
+pub mod validate;
 
 pub use execution::ExecutionOptions;
 pub use file::FileConfig;
-pub use filter::{FilterOptions, ProjectFilter, SortCriteria, SortOptions};
+pub use filter::{FilterOptions, GitChangeFilter, ProjectFilter, SortCriteria, SortOptions};
 pub use scan::ScanOptions;
@@ -4,14 +4,17 @@
 //! and what information is collected during the scanning process.
 
 use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
 
 /// Configuration for directory scanning behavior.
 ///
 /// This struct contains options that control how directories are traversed
 /// and what information is collected during the scanning process.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct ScanOptions {
-    /// Whether to show verbose output including scan errors
+    /// Whether to show verbose output, such as the projects filtered out
     pub verbose: bool,
 
     /// Number of threads to use for scanning (0 = default)
@@ -21,6 +24,61 @@ pub struct ScanOptions {
     pub skip: Vec<PathBuf>,
 }
 
+/// Limits on the directory traversal, so that a scan of an accidentally huge
+/// root (e.g. `/`) stops instead of running for hours.
+///
+/// Once a limit is reached, the traversal stops and only the directories
+/// visited so far are searched for projects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScanLimits {
+    /// Wall-clock time the traversal may take (`--scan-budget`)
+    pub budget: Option<Duration>,
+
+    /// Number of directories the traversal may visit (`--max-dirs`)
+    pub max_dirs: Option<u64>,
+}
+
+/// How the size of build directories is measured (`--du-mode`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum DuMode {
+    /// The length of the files, as `ls -l` and `du --apparent-size` show
+    #[default]
+    Apparent,
+
+    /// The space taken up on disk, as `du` and `df` show: allocated blocks,
+    /// so sparse files count less, and files with several hard links
+    /// (e.g. pnpm's `node_modules/`) only once
+    Disk,
+}
+
+/// The limit that stopped a scan early.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScanLimit {
+    /// The scan budget was exhausted.
+    Budget(Duration),
+
+    /// The maximum number of directories was visited.
+    MaxDirs(u64),
+}
+
+impl ScanLimits {
+    /// Return the limit exceeded after visiting `dirs_visited` directories in
+    /// `elapsed`, if any.
+    #[must_use]
+    pub fn exceeded(&self, elapsed: Duration, dirs_visited: u64) -> Option<ScanLimit> {
+        if let Some(budget) = self.budget
+            && elapsed >= budget
+        {
+            return Some(ScanLimit::Budget(budget));
+        }
+        self.max_dirs
+            .filter(|max_dirs| dirs_visited > *max_dirs)
+            .map(ScanLimit::MaxDirs)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -38,6 +96,28 @@ mod tests {
         assert_eq!(scan_opts.skip.len(), 1);
     }
 
+    #[test]
+    fn test_scan_limits_exceeded() {
+        let limits = ScanLimits {
+            budget: Some(Duration::from_mins(1)),
+            max_dirs: Some(100),
+        };
+
+        assert_eq!(limits.exceeded(Duration::from_secs(10), 100), None);
+        assert_eq!(
+            limits.exceeded(Duration::from_secs(10), 101),
+            Some(ScanLimit::MaxDirs(100))
+        );
+        assert_eq!(
+            limits.exceeded(Duration::from_mins(1), 0),
+            Some(ScanLimit::Budget(Duration::from_mins(1)))
+        );
+        assert_eq!(
+            ScanLimits::default().exceeded(Duration::MAX, u64::MAX),
+            None
+        );
+    }
+
     #[test]
     fn test_scan_options_clone() {
         let original = ScanOptions {
@@ -0,0 +1,358 @@
+//! Validation of configuration files, for `config validate`.
+//!
+//! Loading a configuration file only fails on invalid TOML or values of the
+//! wrong type: unknown keys are ignored, and values such as sizes are only
+//! parsed when used. [`validate`] reports all of these problems at once,
+//! each with the line and column it was found at.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Deserialize;
+use serde::de::{Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use toml::Spanned;
+
+use crate::config::{FileConfig, ProjectFilter, SortCriteria};
+use crate::utils::duration::{parse_count, parse_duration};
+use crate::utils::parse_size;
+
+/// Keys of the configuration file per section, the top level first.
+const KEYS: [(&str, &[&str]); 6] = [
+    ("", &["project_type", "dir"]),
+    (
+        "filtering",
+        &[
+            "keep_size",
+            "keep_days",
+            "git_age",
+            "sort",
+            "reverse",
+            "protected_artifacts",
+        ],
+    ),
+    (
+        "scanning",
+        &[
+            "threads",
+            "verbose",
+            "skip",
+            "ignore",
+            "scan_budget",
+            "max_dirs",
+        ],
+    ),
+    (
+        "execution",
+        &[
+            "keep_executables",
+            "interactive",
+            "dry_run",
+            "use_trash",
+            "native_clean",
+            "retries",
+            "retry_delay_ms",
+            "min_delete_depth",
+            "confirm_threshold",
+        ],
+    ),
+    ("report", &["sign", "signing_key"]),
+    ("hooks", &["pre_project", "post_project"]),
+];
+
+/// A problem found in a configuration file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// Line of the problem, starting at 1.
+    pub line: usize,
+
+    /// Column of the problem, starting at 1.
+    pub column: usize,
+
+    /// What is wrong.
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// Check the content of a configuration file for invalid TOML, unknown
+/// keys and invalid values.
+///
+/// Returns the problems found, in the order they appear in the file; an
+/// empty list means the file is valid.
+///
+/// # Examples
+///
+/// ```
+/// # use clean_dev_dirs::config::validate::validate;
+/// let problems = validate("[filtering]\nkeep_dayz = 7\n");
+/// assert_eq!(problems[0].to_string(), "line 2, column 1: unknown key `keep_dayz` in [filtering]");
+/// ```
+#[must_use]
+pub fn validate(content: &str) -> Vec<ConfigProblem> {
+    let problem = |offset: usize, message: String| {
+        let (line, column) = line_column(content, offset);
+        ConfigProblem {
+            line,
+            column,
+            message,
+        }
+    };
+
+    let document: BTreeMap<Spanned<String>, Spanned<Item>> = match toml::from_str(content) {
+        Ok(document) => document,
+        Err(err) => {
+            let offset = err.span().map_or(0, |span| span.start);
+            return vec![problem(offset, err.message().to_string())];
+        }
+    };
+
+    let mut problems = Vec::new();
+    if let Err(err) = toml::from_str::<FileConfig>(content) {
+        let offset = err.span().map_or(0, |span| span.start);
+        problems.push(problem(offset, err.message().to_string()));
+    }
+
+    for (key, item) in &document {
+        let key_offset = key.span().start;
+        let value_offset = item.span().start;
+        match (
+            KEYS.iter().find(|(section, _)| section == key.get_ref()),
+            item.get_ref(),
+        ) {
+            (Some((section, keys)), Item::Table(entries)) => {
+                for (entry, value) in entries {
+                    if !keys.contains(&entry.get_ref().as_str()) {
+                        let message = format!("unknown key `{}` in [{section}]", entry.get_ref());
+                        problems.push(problem(entry.span().start, message));
+                    } else if let toml::Value::String(value_str) = value.get_ref()
+                        && let Err(message) = check_value(section, entry.get_ref(), value_str)
+                    {
+                        problems.push(problem(value.span().start, message));
+                    }
+                }
+            }
+            // A known section given as a value is reported by `FileConfig`
+            (Some(_), Item::Value(_)) => {}
+            (None, item) if KEYS[0].1.contains(&key.get_ref().as_str()) => {
+                if let Item::Value(Some(value)) = item
+                    && let Err(message) = check_value("", key.get_ref(), value)
+                {
+                    problems.push(problem(value_offset, message));
+                }
+            }
+            (None, _) => {
+                problems.push(problem(
+                    key_offset,
+                    format!("unknown key `{}`", key.get_ref()),
+                ));
+            }
+        }
+    }
+
+    problems.sort_by_key(|problem| (problem.line, problem.column));
+    problems.dedup();
+    problems
+}
+
+/// Check a string value that is only parsed when used.
+fn check_value(section: &str, key: &str, value: &str) -> Result<(), String> {
+    let result = match (section, key) {
+        ("", "project_type") => ProjectFilter::from_name(value).map(|_| ()),
+        ("filtering", "sort") => SortCriteria::from_name(value).map(|_| ()),
+        ("filtering", "keep_size") | ("execution", "confirm_threshold") => {
+            parse_size(value).map(|_| ()).map_err(|err| err.to_string())
+        }
+        ("scanning", "scan_budget") => parse_duration(value)
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        ("scanning", "max_dirs") => parse_count(value)
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        _ => Ok(()),
+    };
+    let name = if section.is_empty() {
+        key.to_string()
+    } else {
+        format!("{section}.{key}")
+    };
+    result.map_err(|err| format!("invalid value for `{name}`: {err}"))
+}
+
+/// Line and column, both starting at 1, of the byte at `offset`.
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// A top-level entry of a configuration file: a section, whose keys are
+/// kept with their location, or a value (kept only if it is a string).
+enum Item {
+    Table(Vec<(Spanned<String>, Spanned<toml::Value>)>),
+    Value(Option<String>),
+}
+
+impl<'de> Deserialize<'de> for Item {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ItemVisitor)
+    }
+}
+
+struct ItemVisitor;
+
+impl<'de> Visitor<'de> for ItemVisitor {
+    type Value = Item;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a TOML value")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Item, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Item::Table(entries))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Item, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(Item::Value(None))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Item, E> {
+        Ok(Item::Value(Some(value.to_string())))
+    }
+
+    fn visit_bool<E>(self, _value: bool) -> Result<Item, E> {
+        Ok(Item::Value(None))
+    }
+
+    fn visit_i64<E>(self, _value: i64) -> Result<Item, E> {
+        Ok(Item::Value(None))
+    }
+
+    fn visit_f64<E>(self, _value: f64) -> Result<Item, E> {
+        Ok(Item::Value(None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::file::DEFAULT_CONFIG;
+
+    #[test]
+    fn test_valid_config_has_no_problems() {
+        let content = r#"
+project_type = "rust"
+dir = "~/Projects"
+
+[filtering]
+keep_size = "50MB"
+keep_days = 7
+sort = "age"
+protected_artifacts = [".venv"]
+
+[scanning]
+threads = 4
+scan_budget = "5m"
+max_dirs = "1M"
+
+[execution]
+retries = 3
+confirm_threshold = "10GB"
+
+[report]
+sign = true
+"#;
+        assert_eq!(validate(content), []);
+        assert_eq!(validate(""), []);
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert_eq!(validate(DEFAULT_CONFIG), []);
+        // Every setting of the template, uncommented, is known and valid
+        let uncommented: Vec<&str> = DEFAULT_CONFIG
+            .lines()
+            .map(|line| {
+                line.strip_prefix("# ")
+                    .filter(|line| line.contains(" = "))
+                    .unwrap_or(line)
+            })
+            .collect();
+        assert_eq!(validate(&uncommented.join("\n")), []);
+        for (_, keys) in KEYS {
+            for key in keys {
+                assert!(DEFAULT_CONFIG.contains(&format!("# {key} = ")), "{key}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let content = "project_typ = \"rust\"\n\n[filtering]\nkeep_days = 7\nkeep_dayz = 7\n\n[scan]\nthreads = 1\n";
+        let problems: Vec<String> = validate(content).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            problems,
+            [
+                "line 1, column 1: unknown key `project_typ`",
+                "line 5, column 1: unknown key `keep_dayz` in [filtering]",
+                "line 7, column 2: unknown key `scan`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let content = "project_type = \"cobol\"\n[filtering]\nkeep_size = \"5XB\"\nsort = \"size\"\n[scanning]\nscan_budget = \"soon\"\n";
+        let problems = validate(content);
+        let locations: Vec<_> = problems.iter().map(|p| (p.line, p.column)).collect();
+        assert_eq!(locations, [(1, 16), (3, 13), (6, 15)]);
+        assert!(
+            problems[0]
+                .message
+                .starts_with("invalid value for `project_type`")
+        );
+        assert!(
+            problems[1]
+                .message
+                .starts_with("invalid value for `filtering.keep_size`")
+        );
+        assert!(
+            problems[2]
+                .message
+                .starts_with("invalid value for `scanning.scan_budget`")
+        );
+    }
+
+    #[test]
+    fn test_type_and_syntax_errors() {
+        let problems = validate("[filtering]\nkeep_days = \"seven\"\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!((problems[0].line, problems[0].column), (2, 13));
+
+        let problems = validate("[filtering\nkeep_days = 7\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, 1);
+    }
+
+    #[test]
+    fn test_line_column() {
+        assert_eq!(line_column("a\nbc", 0), (1, 1));
+        assert_eq!(line_column("a\nbc", 3), (2, 2));
+        assert_eq!(line_column("é\nx", 3), (2, 1));
+    }
+}
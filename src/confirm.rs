@@ -0,0 +1,119 @@
+//! Answers to the confirmation prompt shown before cleaning.
+//!
+//! Besides confirming or cancelling the cleanup, an answer can narrow it
+//! down by project type: `rust-only` keeps only the Rust projects and
+//! `skip-node` leaves out the Node.js ones, after which the prompt is shown
+//! again for what remains. This sits between `--interactive`, which asks
+//! about every project, and `--yes`, which asks nothing.
+
+use crate::config::ProjectFilter;
+use crate::project::ProjectType;
+
+/// An answer to the confirmation prompt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Answer {
+    /// `y` or `yes`: clean, unless the cleanup is large enough to require
+    /// [`Answer::Delete`].
+    Yes,
+
+    /// `delete`: clean, whatever the size of the cleanup.
+    Delete,
+
+    /// `n`, `no` or nothing: clean nothing.
+    No,
+
+    /// `<type>-only`: keep only the projects of a type.
+    Only(ProjectFilter),
+
+    /// `skip-<type>`: leave out the projects of a type.
+    Skip(ProjectFilter),
+}
+
+impl Answer {
+    /// Parse an answer, ignoring case and surrounding whitespace. Types are
+    /// named as for `--project-type`.
+    ///
+    /// Returns `None` if the answer is not understood.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use clean_dev_dirs::confirm::Answer;
+    /// # use clean_dev_dirs::ProjectFilter;
+    /// assert_eq!(Answer::parse(" Rust-Only "), Some(Answer::Only(ProjectFilter::Rust)));
+    /// assert_eq!(Answer::parse("skip-node"), Some(Answer::Skip(ProjectFilter::Node)));
+    /// assert_eq!(Answer::parse("maybe"), None);
+    /// ```
+    #[must_use]
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        let project_type = |name: &str| {
+            ProjectFilter::from_name(name)
+                .ok()
+                .filter(|filter| *filter != ProjectFilter::All)
+        };
+
+        match input.as_str() {
+            "y" | "yes" => Some(Self::Yes),
+            "delete" => Some(Self::Delete),
+            "" | "n" | "no" => Some(Self::No),
+            _ => input
+                .strip_suffix("-only")
+                .map(|name| project_type(name).map(Self::Only))
+                .or_else(|| {
+                    input
+                        .strip_prefix("skip-")
+                        .map(|name| project_type(name).map(Self::Skip))
+                })
+                .flatten(),
+        }
+    }
+
+    /// Whether projects of `kind` remain in the cleanup after this answer.
+    #[must_use]
+    pub const fn keeps(self, kind: &ProjectType) -> bool {
+        match self {
+            Self::Only(filter) => filter.includes(kind),
+            Self::Skip(filter) => !filter.includes(kind),
+            Self::Yes | Self::Delete | Self::No => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Answer::parse("y"), Some(Answer::Yes));
+        assert_eq!(Answer::parse("YES"), Some(Answer::Yes));
+        assert_eq!(Answer::parse("delete"), Some(Answer::Delete));
+        assert_eq!(Answer::parse(""), Some(Answer::No));
+        assert_eq!(Answer::parse("n"), Some(Answer::No));
+        assert_eq!(
+            Answer::parse("dotnet-only"),
+            Some(Answer::Only(ProjectFilter::DotNet))
+        );
+        assert_eq!(
+            Answer::parse("skip-python"),
+            Some(Answer::Skip(ProjectFilter::Python))
+        );
+        assert_eq!(Answer::parse("all-only"), None);
+        assert_eq!(Answer::parse("skip-cobol"), None);
+        assert_eq!(Answer::parse("rust"), None);
+    }
+
+    #[test]
+    fn test_keeps() {
+        let only_rust = Answer::Only(ProjectFilter::Rust);
+        assert!(only_rust.keeps(&ProjectType::Rust));
+        assert!(!only_rust.keeps(&ProjectType::Node));
+
+        let skip_node = Answer::Skip(ProjectFilter::Node);
+        assert!(skip_node.keeps(&ProjectType::Rust));
+        assert!(!skip_node.keeps(&ProjectType::Node));
+
+        assert!(Answer::Yes.keeps(&ProjectType::Go));
+    }
+}
@@ -0,0 +1,274 @@
+//! Large directories that no project detector matches.
+//!
+//! Backs the `discover` subcommand: the directories to scan are walked and
+//! those holding more than a threshold, once the artifacts of the projects
+//! found are left out, are listed with a guess at the tool that created
+//! them. This points at the ecosystems worth supporting next, without
+//! cleaning anything.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(feature = "cli")]
+use colored::Colorize;
+use rayon::prelude::*;
+use serde::Serialize;
+
+#[cfg(feature = "cli")]
+use crate::utils::{format_size, terminal};
+
+/// Directory names created by tools without a detector, and the tool.
+const NAME_ORIGINS: &[(&str, &str)] = &[
+    (".gradle", "Gradle"),
+    (".terraform", "Terraform"),
+    ("bower_components", "Bower"),
+    (".stack-work", "Haskell Stack"),
+    ("dist-newstyle", "Haskell Cabal"),
+    ("zig-cache", "Zig"),
+    (".zig-cache", "Zig"),
+    ("zig-out", "Zig"),
+    (".dart_tool", "Dart"),
+    ("Pods", "CocoaPods"),
+    ("DerivedData", "Xcode"),
+    ("elm-stuff", "Elm"),
+    (".bundle", "Ruby Bundler"),
+    (".tox", "tox"),
+    (".nox", "nox"),
+    (".cpcache", "Clojure CLI"),
+    (".shadow-cljs", "shadow-cljs"),
+    ("_opam", "opam"),
+    (".direnv", "direnv"),
+    (".vagrant", "Vagrant"),
+    (".pixi", "pixi"),
+];
+
+/// Files marking the root of a project of an ecosystem without a detector,
+/// and the ecosystem.
+const MARKER_ORIGINS: &[(&str, &str)] = &[
+    ("mix.exs", "Elixir"),
+    ("pubspec.yaml", "Dart/Flutter"),
+    ("build.zig", "Zig"),
+    ("stack.yaml", "Haskell"),
+    ("cabal.project", "Haskell"),
+    ("Gemfile", "Ruby"),
+    ("composer.json", "PHP"),
+    ("elm.json", "Elm"),
+    ("deps.edn", "Clojure"),
+    ("project.clj", "Clojure"),
+    ("build.sbt", "Scala"),
+    ("Podfile", "CocoaPods"),
+    ("MODULE.bazel", "Bazel"),
+    ("WORKSPACE", "Bazel"),
+    ("flake.nix", "Nix"),
+    ("main.tf", "Terraform"),
+];
+
+/// A large directory that no detector matches.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UnknownDir {
+    /// The directory.
+    pub path: PathBuf,
+
+    /// Size of its contents, leaving out the artifacts of known projects,
+    /// in bytes.
+    pub size: u64,
+
+    /// The tool or ecosystem that probably created it, if one is recognized
+    /// from its name or the files next to it.
+    pub origin: Option<&'static str>,
+}
+
+/// Find the directories under `roots` holding at least `min_size` bytes
+/// once the `known` artifact directories are left out, largest first.
+///
+/// Only the deepest such directories are reported: a directory is left out
+/// if one below it already is. `.git` directories and symbolic links are
+/// not followed, and unreadable directories are skipped.
+#[must_use]
+pub fn find_unknown_dirs(roots: &[PathBuf], known: &[PathBuf], min_size: u64) -> Vec<UnknownDir> {
+    let known: HashSet<&Path> = known.iter().map(PathBuf::as_path).collect();
+    let found = Mutex::new(Vec::new());
+    for root in roots {
+        measure(root, &known, min_size, &found);
+    }
+
+    let mut found = found.into_inner().unwrap_or_else(PoisonError::into_inner);
+    found.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    found.dedup_by(|a, b| a.path == b.path);
+    found
+}
+
+/// Add up the size of `dir`, leaving out the `known` directories, and
+/// record it in `found` if it reaches `min_size` and no directory below it
+/// did.
+///
+/// Returns the size and whether `dir` or a directory below it was recorded.
+fn measure(
+    dir: &Path,
+    known: &HashSet<&Path>,
+    min_size: u64,
+    found: &Mutex<Vec<UnknownDir>>,
+) -> (u64, bool) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (0, false);
+    };
+
+    let mut size = 0;
+    let mut subdirs = Vec::new();
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let path = entry.path();
+        if metadata.is_dir() {
+            if entry.file_name() != ".git" && !known.contains(path.as_path()) {
+                subdirs.push(path);
+            }
+        } else if metadata.is_file() {
+            size += metadata.len();
+        }
+    }
+
+    let (subdirs_size, reported_below) = subdirs
+        .par_iter()
+        .map(|subdir| measure(subdir, known, min_size, found))
+        .reduce(|| (0, false), |a, b| (a.0 + b.0, a.1 || b.1));
+    size += subdirs_size;
+
+    if reported_below || size < min_size {
+        return (size, reported_below);
+    }
+    found
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(UnknownDir {
+            path: dir.to_path_buf(),
+            size,
+            origin: guess_origin(dir),
+        });
+    (size, true)
+}
+
+/// Guess which tool created `dir`, from its name or from the project
+/// markers in it or next to it.
+#[must_use]
+pub fn guess_origin(dir: &Path) -> Option<&'static str> {
+    let name = dir.file_name()?.to_str()?;
+    if let Some((_, origin)) = NAME_ORIGINS.iter().find(|(known, _)| *known == name) {
+        return Some(origin);
+    }
+
+    [Some(dir), dir.parent()]
+        .into_iter()
+        .flatten()
+        .find_map(|candidate| {
+            MARKER_ORIGINS
+                .iter()
+                .find(|(marker, _)| candidate.join(marker).is_file())
+                .map(|(_, origin)| *origin)
+        })
+}
+
+/// Print the directories found, or a message if there are none.
+#[cfg(feature = "cli")]
+pub fn print(dirs: &[UnknownDir], min_size: u64) {
+    if dirs.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "✨ No directory of {} or more outside the projects found!",
+                format_size(min_size)
+            )
+            .green()
+        );
+        return;
+    }
+
+    println!(
+        "\n{}",
+        format!(
+            "🔍 Directories of {} or more that no detector matches:",
+            format_size(min_size)
+        )
+        .bold()
+    );
+    for dir in dirs {
+        let origin = dir.origin.unwrap_or("unknown origin");
+        let prefix = format!("  {:>10}  ", format_size(dir.size));
+        let suffix = format!("  ({origin})");
+        let path = dir.path.display().to_string();
+        println!(
+            "{prefix}{}{}",
+            terminal::fit_path_to(&path, &format!("{prefix}{suffix}"), terminal::width()),
+            suffix.dimmed()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_file(path: &Path, len: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0; len]).unwrap();
+    }
+
+    #[test]
+    fn test_find_unknown_dirs() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        write_file(&root.join("app/_build/a/lib.beam"), 600);
+        write_file(&root.join("app/_build/b/lib.beam"), 600);
+        fs::write(root.join("app/mix.exs"), "").unwrap();
+        write_file(&root.join("rust/target/debug/app"), 5000);
+        write_file(&root.join("small/.gradle/cache"), 100);
+        write_file(&root.join("repo/.git/objects/pack"), 5000);
+
+        let known = [root.join("rust/target")];
+        let found = find_unknown_dirs(&[root.to_path_buf()], &known, 1000);
+
+        assert_eq!(
+            found,
+            vec![UnknownDir {
+                path: root.join("app/_build"),
+                size: 1200,
+                origin: Some("Elixir"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_spread_size_reports_the_common_parent() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        write_file(&root.join("data/a/file"), 600);
+        write_file(&root.join("data/b/file"), 600);
+
+        let found = find_unknown_dirs(&[root.join("data")], &[], 1000);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, root.join("data"));
+        assert_eq!(found[0].origin, None);
+    }
+
+    #[test]
+    fn test_guess_origin() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("app/deps")).unwrap();
+        fs::write(temp.path().join("app/pubspec.yaml"), "").unwrap();
+
+        assert_eq!(
+            guess_origin(Path::new("/x/.stack-work")),
+            Some("Haskell Stack")
+        );
+        assert_eq!(
+            guess_origin(&temp.path().join("app/deps")),
+            Some("Dart/Flutter")
+        );
+        assert_eq!(guess_origin(&temp.path().join("app")), Some("Dart/Flutter"));
+        assert_eq!(guess_origin(temp.path()), None);
+    }
+}
@@ -0,0 +1,294 @@
+//! Errors returned by the library.
+//!
+//! Every fallible function of the library returns an [`Error`], so that
+//! callers can tell failures apart: a build directory that could not be
+//! removed carries the [`io::Error`] of the entry that failed, whose kind
+//! tells a permission problem from a file in use or already gone.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+/// Result of the fallible functions of the library.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// An error of the library.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// A file or directory could not be read, written, created or removed.
+    #[error("Failed to {action} {}", path.display())]
+    File {
+        /// What was attempted, e.g. `"read"`.
+        action: &'static str,
+
+        /// The file or directory.
+        path: PathBuf,
+
+        /// Why it failed.
+        #[source]
+        source: io::Error,
+    },
+
+    /// A build directory could not be cleaned.
+    #[error("Failed to clean {}", path.display())]
+    Clean {
+        /// The build directory.
+        path: PathBuf,
+
+        /// Why it could not be cleaned.
+        #[source]
+        source: Box<Self>,
+    },
+
+    /// Some entries of a directory could not be removed, the others were.
+    #[error(
+        "{} could not be removed (first: {}: {first_error})",
+        entries(.count),
+        .first.display()
+    )]
+    Leftovers {
+        /// Number of entries left behind.
+        count: usize,
+
+        /// The first entry left behind.
+        first: PathBuf,
+
+        /// Why the first entry could not be removed.
+        first_error: io::Error,
+    },
+
+    /// The system trash could not be used.
+    #[error("failed to {action} the trash")]
+    Trash {
+        /// What was attempted, e.g. `"move to"`.
+        action: &'static str,
+
+        /// Why it failed.
+        #[source]
+        source: trash::Error,
+    },
+
+    /// A path was not deleted because it is too shallow or holds the home
+    /// directory.
+    #[error("refusing to delete {}: {reason}", path.display())]
+    Refused {
+        /// The path, resolved.
+        path: PathBuf,
+
+        /// Why it was not deleted.
+        reason: String,
+    },
+
+    /// A size (e.g. `--keep-size`) could not be parsed.
+    #[error("{0}")]
+    SizeParse(String),
+
+    /// A duration or a count (e.g. `--max-time`) could not be parsed.
+    #[error("{0}")]
+    DurationParse(String),
+
+    /// An `--include` or `--exclude` glob is invalid.
+    #[error("Invalid glob pattern '{pattern}'")]
+    Glob {
+        /// The pattern.
+        pattern: String,
+
+        /// Why it is invalid.
+        #[source]
+        source: globset::Error,
+    },
+
+    /// A configuration file is invalid.
+    #[error("Failed to parse config file at {}", path.display())]
+    Config {
+        /// The configuration file.
+        path: PathBuf,
+
+        /// Why it is invalid.
+        #[source]
+        source: toml::de::Error,
+    },
+
+    /// A file written by a previous run (scan index, history manifest,
+    /// activity log or plan) is invalid.
+    #[error("Invalid {kind} {}", path.display())]
+    Invalid {
+        /// What the file holds, e.g. `"scan index"`.
+        kind: &'static str,
+
+        /// The file.
+        path: PathBuf,
+
+        /// Why it is invalid.
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// A command (`cargo clean`, `git`, a scheduler) could not be started.
+    #[error("failed to run `{command}`")]
+    Spawn {
+        /// The command.
+        command: String,
+
+        /// Why it could not be started.
+        #[source]
+        source: io::Error,
+    },
+
+    /// A command (`cargo clean`, `git`, a scheduler) failed.
+    #[error("`{command}` failed: {stderr}")]
+    Command {
+        /// The command.
+        command: String,
+
+        /// What it printed on stderr.
+        stderr: String,
+    },
+
+    /// A `pre_project` or `post_project` hook failed.
+    #[error("{hook} hook failed ({status})")]
+    Hook {
+        /// The hook, e.g. `"pre_project"`.
+        hook: &'static str,
+
+        /// How it exited.
+        status: ExitStatus,
+    },
+
+    /// A git query failed, e.g. because the directory is not in a
+    /// repository.
+    #[error("{0}")]
+    Git(String),
+
+    /// A report could not be signed or verified.
+    #[error("{0}")]
+    Report(String),
+
+    /// An interactive prompt failed or was cancelled.
+    #[cfg(feature = "cli")]
+    #[error(transparent)]
+    Prompt(#[from] inquire::InquireError),
+
+    /// The user cancelled a selection.
+    #[error("Selection cancelled")]
+    SelectionCancelled,
+
+    /// Logging could not be set up.
+    #[cfg(feature = "cli")]
+    #[error("Failed to set up logging")]
+    Logging(#[source] tracing_subscriber::util::TryInitError),
+
+    /// Data could not be converted to or from JSON.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// An input or output error without a path, e.g. on the terminal.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// Any other failure.
+    #[error("{0}")]
+    Other(String),
+}
+
+impl Error {
+    /// An error for `action` (e.g. `"read"`) failing on `path`.
+    pub(crate) fn file(action: &'static str, path: &Path) -> impl FnOnce(io::Error) -> Self {
+        move |source| Self::File {
+            action,
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// This error followed by its sources, separated by colons, as `{:#}`
+    /// shows an `anyhow::Error`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use clean_dev_dirs::Error;
+    /// let error = Error::File {
+    ///     action: "read",
+    ///     path: "/work/plan.json".into(),
+    ///     source: std::io::ErrorKind::NotFound.into(),
+    /// };
+    /// assert_eq!(error.to_string(), "Failed to read /work/plan.json");
+    /// assert_eq!(error.display_chain(), "Failed to read /work/plan.json: entity not found");
+    /// ```
+    #[must_use]
+    pub fn display_chain(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(error) = source {
+            message.push_str(&format!(": {error}"));
+            source = error.source();
+        }
+        message
+    }
+
+    /// The [`io::Error`] behind this error, if any, e.g. to tell whether a
+    /// directory could not be removed because permission was denied.
+    #[must_use]
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            Self::File { source, .. } | Self::Spawn { source, .. } | Self::Io(source) => {
+                Some(source)
+            }
+            Self::Leftovers { first_error, .. } => Some(first_error),
+            Self::Clean { source, .. } => source.io_error(),
+            _ => None,
+        }
+    }
+}
+
+/// `count` entries, in words.
+fn entries(count: &usize) -> String {
+    if *count == 1 {
+        "1 entry".to_string()
+    } else {
+        format!("{count} entries")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_error_of_a_failed_clean() {
+        let error = Error::Clean {
+            path: PathBuf::from("/work/app/target"),
+            source: Box::new(Error::File {
+                action: "remove",
+                path: PathBuf::from("/work/app/target/debug"),
+                source: io::ErrorKind::PermissionDenied.into(),
+            }),
+        };
+
+        assert_eq!(
+            error.io_error().map(io::Error::kind),
+            Some(io::ErrorKind::PermissionDenied)
+        );
+        assert_eq!(
+            error.display_chain(),
+            "Failed to clean /work/app/target: Failed to remove /work/app/target/debug: \
+             permission denied"
+        );
+    }
+
+    #[test]
+    fn test_leftovers_message() {
+        let error = Error::Leftovers {
+            count: 2,
+            first: PathBuf::from("/work/web/node_modules/lib/stuck.js"),
+            first_error: io::ErrorKind::ResourceBusy.into(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "2 entries could not be removed (first: /work/web/node_modules/lib/stuck.js: \
+             resource busy)"
+        );
+    }
+}
@@ -7,8 +7,7 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
-
+use crate::error::{Error, Result};
 use crate::project::{Project, ProjectType};
 
 /// Extensions to exclude when looking for Rust executables.
@@ -49,7 +48,7 @@ pub struct PreservedExecutable {
 ///
 /// - **Rust**: copies executables from `target/release/` and `target/debug/`
 /// - **Python**: copies `.whl` files from `dist/` and `.so`/`.pyd` extensions from `build/`
-/// - **Node / Go / Java / C++ / Swift / .NET**: no-op (their cleanable dirs are dependencies or build outputs not easily preservable)
+/// - **Node / Go / Java / C++ / Swift / .NET / OCaml / Unity / Unreal**: no-op (their cleanable dirs are dependencies or build outputs not easily preservable)
 ///
 /// # Errors
 ///
@@ -63,13 +62,18 @@ pub fn preserve_executables(project: &Project) -> Result<Vec<PreservedExecutable
         | ProjectType::Java
         | ProjectType::Cpp
         | ProjectType::Swift
-        | ProjectType::DotNet => Ok(Vec::new()),
+        | ProjectType::DotNet
+        | ProjectType::OCaml
+        | ProjectType::Unity
+        | ProjectType::Unreal => Ok(Vec::new()),
     }
 }
 
 /// Preserve Rust executables from `target/release/` and `target/debug/`.
 fn preserve_rust_executables(project: &Project) -> Result<Vec<PreservedExecutable>> {
-    let target_dir = &project.build_arts.path;
+    let Some(target_dir) = project.artifact_paths().next() else {
+        return Ok(Vec::new());
+    };
     let bin_dir = project.root_path.join("bin");
     let mut preserved = Vec::new();
 
@@ -86,8 +90,7 @@ fn preserve_rust_executables(project: &Project) -> Result<Vec<PreservedExecutabl
             continue;
         }
 
-        fs::create_dir_all(&dest_dir)
-            .with_context(|| format!("Failed to create {}", dest_dir.display()))?;
+        fs::create_dir_all(&dest_dir).map_err(Error::file("create", &dest_dir))?;
 
         for exe_path in executables {
             let file_name = exe_path
@@ -95,13 +98,7 @@ fn preserve_rust_executables(project: &Project) -> Result<Vec<PreservedExecutabl
                 .expect("executable path should have a file name");
             let dest_path = dest_dir.join(file_name);
 
-            fs::copy(&exe_path, &dest_path).with_context(|| {
-                format!(
-                    "Failed to copy {} to {}",
-                    exe_path.display(),
-                    dest_path.display()
-                )
-            })?;
+            fs::copy(&exe_path, &dest_path).map_err(Error::file("copy to", &dest_path))?;
 
             preserved.push(PreservedExecutable {
                 source: exe_path,
@@ -121,8 +118,7 @@ fn preserve_rust_executables(project: &Project) -> Result<Vec<PreservedExecutabl
 fn find_rust_executables(profile_dir: &Path) -> Result<Vec<PathBuf>> {
     let mut executables = Vec::new();
 
-    let entries = fs::read_dir(profile_dir)
-        .with_context(|| format!("Failed to read {}", profile_dir.display()))?;
+    let entries = fs::read_dir(profile_dir).map_err(Error::file("read", profile_dir))?;
 
     for entry in entries {
         let entry = entry?;
@@ -224,21 +220,14 @@ fn copy_to_bin(
     bin_dir: &Path,
     preserved: &mut Vec<PreservedExecutable>,
 ) -> Result<()> {
-    fs::create_dir_all(bin_dir)
-        .with_context(|| format!("Failed to create {}", bin_dir.display()))?;
+    fs::create_dir_all(bin_dir).map_err(Error::file("create", bin_dir))?;
 
     let file_name = source
         .file_name()
         .expect("source path should have a file name");
     let dest_path = bin_dir.join(file_name);
 
-    fs::copy(source, &dest_path).with_context(|| {
-        format!(
-            "Failed to copy {} to {}",
-            source.display(),
-            dest_path.display()
-        )
-    })?;
+    fs::copy(source, &dest_path).map_err(Error::file("copy to", &dest_path))?;
 
     preserved.push(PreservedExecutable {
         source: source.to_path_buf(),
@@ -264,6 +253,9 @@ mod tests {
             ProjectType::Cpp => root.join("build"),
             ProjectType::Swift => root.join(".build"),
             ProjectType::DotNet => root.join("obj"),
+            ProjectType::OCaml => root.join("_build"),
+            ProjectType::Unity => root.join("Library"),
+            ProjectType::Unreal => root.join("Intermediate"),
         };
 
         fs::create_dir_all(&build_dir).unwrap();
@@ -271,10 +263,10 @@ mod tests {
         Project::new(
             kind,
             root,
-            BuildArtifacts {
+            vec![BuildArtifacts {
                 path: build_dir,
                 size: 0,
-            },
+            }],
             Some("test-project".to_string()),
         )
     }
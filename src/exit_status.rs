@@ -0,0 +1,111 @@
+//! The exit codes of clean-dev-dirs, so that scripts can tell "nothing to
+//! do" from "something failed".
+
+use std::process::ExitCode;
+
+use clean_dev_dirs::cleaner::CleanResult;
+
+/// How a run ended, reported as the exit code of the process.
+///
+/// The values are a stable contract: they are documented in the README and
+/// never reassigned. A second Ctrl+C exits immediately with 130, as shells
+/// do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// Projects were cleaned, or listed or reported without cleaning.
+    Success = 0,
+
+    /// An error stopped the run.
+    Failure = 1,
+
+    /// No project was found, or none matched the filters.
+    NothingFound = 2,
+
+    /// Some projects could not be cleaned, only partially, or were skipped
+    /// by their `pre_project` hook or because a build directory is a mount
+    /// point.
+    PartialFailure = 3,
+
+    /// The user selected nothing, declined the confirmation or cancelled the
+    /// run with Ctrl+C.
+    Aborted = 4,
+}
+
+impl ExitStatus {
+    /// The status of a run that ended with the cleanup described by `result`.
+    pub const fn of_cleanup(result: &CleanResult) -> Self {
+        if !result.errors.is_empty() || !result.partial.is_empty() || !result.skipped.is_empty() {
+            Self::PartialFailure
+        } else if result.cancelled_count > 0 {
+            Self::Aborted
+        } else {
+            Self::Success
+        }
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        Self::from(status as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_of_cleanup() {
+        let mut result = CleanResult {
+            success_count: 2,
+            ..CleanResult::default()
+        };
+        assert_eq!(ExitStatus::of_cleanup(&result), ExitStatus::Success);
+
+        result.cancelled_count = 1;
+        assert_eq!(ExitStatus::of_cleanup(&result), ExitStatus::Aborted);
+
+        // Failures are reported over the cancellation
+        result.errors.push("Failed to clean /work/app".to_string());
+        assert_eq!(ExitStatus::of_cleanup(&result), ExitStatus::PartialFailure);
+
+        result.errors.clear();
+        result
+            .skipped
+            .push("/work/web: pre_project hook failed".to_string());
+        assert_eq!(ExitStatus::of_cleanup(&result), ExitStatus::PartialFailure);
+    }
+
+    #[test]
+    fn test_of_cleanup_with_a_mounted_build_directory() {
+        use std::path::PathBuf;
+        use std::sync::Arc;
+
+        use clean_dev_dirs::cleaner::{CleanOptions, Cleaner, RemovalStrategy};
+        use clean_dev_dirs::project::{BuildArtifacts, Project, ProjectType};
+        use clean_dev_dirs::vfs::{FsHandle, MemoryFs};
+
+        let fs = MemoryFs::new()
+            .with_file("/work/web/package.json", "{}")
+            .with_file_of_len("/work/web/node_modules/index.js", 1000)
+            .with_mount_point("/work/web/node_modules");
+        let project = Project::new(
+            ProjectType::Node,
+            PathBuf::from("/work/web"),
+            vec![BuildArtifacts {
+                path: PathBuf::from("/work/web/node_modules"),
+                size: 1000,
+            }],
+            None,
+        );
+        let options = CleanOptions {
+            removal_strategy: RemovalStrategy::Permanent,
+            fs: FsHandle::new(Arc::new(fs)),
+            ..CleanOptions::default()
+        };
+        let result = Cleaner::clean_projects(vec![project].into(), &options);
+
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(ExitStatus::of_cleanup(&result), ExitStatus::PartialFailure);
+    }
+}
@@ -1,25 +1,473 @@
 //! Project filtering functionality.
 //!
 //! This module provides functions for filtering projects based on various criteria
-//! such as size and modification time.
+//! such as size, modification time, and whether the current user is able to
+//! delete their build artifacts. Every project that is filtered out is tagged
+//! with a [`SkipReason`] so callers can report the decision without parsing
+//! human-readable text.
 
-use anyhow::Result;
 use chrono::{DateTime, Local};
+use rayon::iter::Either;
 use rayon::prelude::*;
+use serde::Serialize;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
+use walkdir::WalkDir;
 
+use crate::activity::recorded_activity;
 use crate::config::filter::SortCriteria;
-use crate::config::{FilterOptions, SortOptions};
+use crate::config::{FilterOptions, GitChangeFilter, SortOptions};
+use crate::error::Result;
 use crate::project::{Project, ProjectType};
+use crate::utils::git::{has_changes_since, has_uncommitted_changes, last_commit_time};
+use crate::utils::gitattributes::GitAttributes;
 use crate::utils::parse_size;
+use crate::utils::processes::{OpenFile, is_busy, open_file_below, resolve};
 
-/// Filter projects based on size and modification time criteria.
+/// Name of the marker file protecting the project it is in from cleaning.
+pub const KEEP_MARKER: &str = ".keep-dev-dirs";
+
+/// Machine-readable reason explaining why a project was not selected for cleaning.
 ///
-/// This function applies parallel filtering to remove projects that don't meet
-/// the specified criteria:
-/// - Projects smaller than the minimum size threshold
-/// - Projects modified more recently than the specified number of days
+/// Serialized as a stable `SCREAMING_SNAKE_CASE` code (e.g. `"SKIPPED_BY_SIZE"`)
+/// in JSON output and shown verbatim in verbose mode, so wrappers can present
+/// decisions faithfully without parsing prose.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SkipReason {
+    /// Build artifacts are smaller than the `--keep-size` threshold.
+    SkippedBySize,
+
+    /// Build artifacts were modified within the last `--keep-days` days (or,
+    /// with `--git-age`, the project had a commit within them).
+    SkippedRecent,
+
+    /// The project has been explicitly protected by the user, with a
+    /// [`KEEP_MARKER`] file in its root.
+    Protected,
+
+    /// The build artifacts (or the directory holding them) are not writable,
+    /// so they cannot be removed.
+    ReadOnly,
+
+    /// The build artifacts belong to another user.
+    Unowned,
+
+    /// A package manager (`cargo`, `npm`, …) is currently running inside the
+    /// project.
+    BuildInProgress,
+
+    /// With `--check-in-use`: a running process holds files open inside the
+    /// build artifacts.
+    InUse,
+
+    /// No tracked file changed since the `--changed-since` ref.
+    UnchangedSinceRef,
+
+    /// Tracked files changed since the `--unchanged-since` ref.
+    ChangedSinceRef,
+
+    /// The project's git history could not be compared with the ref (not in
+    /// a git repository, or unknown ref), or, with `--skip-dirty`, its
+    /// repository could not be queried.
+    GitUnavailable,
+
+    /// With `--skip-dirty`: the project has uncommitted changes.
+    Dirty,
+
+    /// With `--only-outdated`: the build artifacts are newer than the
+    /// project's lockfile and manifest (or the project has none).
+    UpToDate,
+
+    /// With `--top N`: the project is not among the N largest ones.
+    NotInTop,
+
+    /// With `--free SIZE`: the other selected projects already free enough.
+    FreeTargetReached,
+
+    /// With `apply`: the project is gone, or it or its artifacts changed
+    /// since the plan was written.
+    ChangedSincePlan,
+}
+
+impl SkipReason {
+    /// Return the stable reason code, e.g. `"SKIPPED_BY_SIZE"`.
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::SkippedBySize => "SKIPPED_BY_SIZE",
+            Self::SkippedRecent => "SKIPPED_RECENT",
+            Self::Protected => "PROTECTED",
+            Self::ReadOnly => "READ_ONLY",
+            Self::Unowned => "UNOWNED",
+            Self::BuildInProgress => "BUILD_IN_PROGRESS",
+            Self::InUse => "IN_USE",
+            Self::UnchangedSinceRef => "UNCHANGED_SINCE_REF",
+            Self::ChangedSinceRef => "CHANGED_SINCE_REF",
+            Self::GitUnavailable => "GIT_UNAVAILABLE",
+            Self::Dirty => "DIRTY",
+            Self::UpToDate => "UP_TO_DATE",
+            Self::NotInTop => "NOT_IN_TOP",
+            Self::FreeTargetReached => "FREE_TARGET_REACHED",
+            Self::ChangedSincePlan => "CHANGED_SINCE_PLAN",
+        }
+    }
+
+    /// Return a short human-readable explanation of the reason.
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::SkippedBySize => "smaller than --keep-size",
+            Self::SkippedRecent => "modified within --keep-days",
+            Self::Protected => "protected by the user",
+            Self::ReadOnly => "build artifacts are not writable",
+            Self::Unowned => "build artifacts belong to another user",
+            Self::BuildInProgress => "build in progress",
+            Self::InUse => "build artifacts in use by a running process",
+            Self::UnchangedSinceRef => "unchanged since --changed-since ref",
+            Self::ChangedSinceRef => "changed since --unchanged-since ref",
+            Self::GitUnavailable => "git history unavailable",
+            Self::Dirty => "uncommitted changes in the repository",
+            Self::UpToDate => "built after the last lockfile/manifest change",
+            Self::NotInTop => "not among the --top largest projects",
+            Self::FreeTargetReached => "not needed to reach the --free target",
+            Self::ChangedSincePlan => "changed since the plan was written",
+        }
+    }
+}
+
+/// A project that was filtered out, together with the reason why.
+#[derive(Clone)]
+pub struct SkippedProject {
+    /// The project that was skipped.
+    pub project: Project,
+
+    /// Why the project was skipped.
+    pub reason: SkipReason,
+}
+
+/// Result of classifying projects against the filtering criteria.
+#[derive(Clone, Default)]
+pub struct FilterOutcome {
+    /// Projects that meet every criterion and are eligible for cleaning.
+    pub kept: Vec<Project>,
+
+    /// Projects that were filtered out, each with its [`SkipReason`].
+    pub skipped: Vec<SkippedProject>,
+}
+
+impl FilterOutcome {
+    /// Move kept projects that contain any of `busy_dirs` to the skipped list.
+    ///
+    /// `busy_dirs` are the working directories of running package managers
+    /// (see [`package_manager_dirs`](crate::utils::processes::package_manager_dirs));
+    /// those projects are tagged [`SkipReason::BuildInProgress`].
+    pub fn skip_busy(&mut self, busy_dirs: &[PathBuf]) {
+        if busy_dirs.is_empty() {
+            return;
+        }
+
+        // Resolved once here rather than for every project
+        let busy_dirs: Vec<PathBuf> = busy_dirs.iter().map(|dir| resolve(dir)).collect();
+        let (busy, kept) = std::mem::take(&mut self.kept)
+            .into_iter()
+            .partition(|project| is_busy(&project.root_path, &busy_dirs));
+        self.kept = kept;
+
+        self.skipped
+            .extend(busy.into_iter().map(|project| SkippedProject {
+                project,
+                reason: SkipReason::BuildInProgress,
+            }));
+    }
+
+    /// Move kept projects with build artifacts holding any of `open_files`
+    /// to the skipped list, tagged [`SkipReason::InUse`].
+    ///
+    /// `open_files` are the files held open by running processes (see
+    /// [`open_files`](crate::utils::processes::open_files)). A warning names
+    /// the process holding each skipped project's artifacts.
+    pub fn skip_in_use(&mut self, open_files: &[OpenFile]) {
+        if open_files.is_empty() {
+            return;
+        }
+
+        let (kept, in_use): (Vec<_>, Vec<_>) = std::mem::take(&mut self.kept)
+            .into_par_iter()
+            .partition_map(|project| {
+                let holder = project
+                    .artifact_paths()
+                    .find_map(|artifact| open_file_below(artifact, open_files));
+                let Some(file) = holder else {
+                    return Either::Left(project);
+                };
+                tracing::warn!(
+                    "Skipped {project}: {} is in use by {} (pid {})",
+                    file.path.display(),
+                    file.process,
+                    file.pid
+                );
+                Either::Right(SkippedProject {
+                    project,
+                    reason: SkipReason::InUse,
+                })
+            });
+
+        self.kept = kept;
+        self.skipped.extend(in_use);
+    }
+
+    /// Move kept projects that `filter` rejects to the skipped list.
+    ///
+    /// Each project root is compared with the filter's git ref in parallel.
+    /// Projects whose history cannot be compared are never kept, so a typo in
+    /// the ref cannot lead to everything being cleaned.
+    pub fn skip_by_git(&mut self, filter: &GitChangeFilter) {
+        let (kept, skipped): (Vec<_>, Vec<_>) = std::mem::take(&mut self.kept)
+            .into_par_iter()
+            .partition_map(|project| {
+                let reason = match has_changes_since(&project.root_path, filter.git_ref()) {
+                    Ok(changed) if filter.keeps(changed) => return Either::Left(project),
+                    Ok(true) => SkipReason::ChangedSinceRef,
+                    Ok(false) => SkipReason::UnchangedSinceRef,
+                    Err(_) => SkipReason::GitUnavailable,
+                };
+                Either::Right(SkippedProject { project, reason })
+            });
+
+        self.kept = kept;
+        self.skipped.extend(skipped);
+    }
+
+    /// Move kept projects with uncommitted changes to the skipped list,
+    /// tagged [`SkipReason::Dirty`].
+    ///
+    /// Backs `--skip-dirty`. Changes in the project's build directories are
+    /// ignored. Projects whose repository cannot be queried are skipped too,
+    /// tagged [`SkipReason::GitUnavailable`].
+    pub fn skip_dirty(&mut self) {
+        let (kept, skipped): (Vec<_>, Vec<_>) = std::mem::take(&mut self.kept)
+            .into_par_iter()
+            .partition_map(|project| {
+                let artifacts: Vec<_> = project.artifact_paths().collect();
+                let reason = match has_uncommitted_changes(&project.root_path, &artifacts) {
+                    Ok(false) => return Either::Left(project),
+                    Ok(true) => SkipReason::Dirty,
+                    Err(_) => SkipReason::GitUnavailable,
+                };
+                Either::Right(SkippedProject { project, reason })
+            });
+
+        self.kept = kept;
+        self.skipped.extend(skipped);
+    }
+
+    /// Move kept projects whose build artifacts are not older than their
+    /// lockfile or manifest to the skipped list.
+    ///
+    /// Backs `--only-outdated`: only projects whose dependencies changed
+    /// since the last build (and thus need a rebuild anyway) stay kept.
+    /// Projects without a known lockfile or manifest are skipped too.
+    pub fn skip_up_to_date(&mut self) {
+        let (kept, skipped): (Vec<_>, Vec<_>) = std::mem::take(&mut self.kept)
+            .into_par_iter()
+            .partition_map(|project| {
+                if is_outdated(&project) {
+                    Either::Left(project)
+                } else {
+                    Either::Right(SkippedProject {
+                        project,
+                        reason: SkipReason::UpToDate,
+                    })
+                }
+            });
+
+        self.kept = kept;
+        self.skipped.extend(skipped);
+    }
+
+    /// Move all kept projects but the `count` largest ones to the skipped
+    /// list, tagged [`SkipReason::NotInTop`].
+    ///
+    /// Backs `--top N`. The relative order of the kept projects is preserved;
+    /// among projects of equal size, the oldest ones are kept.
+    pub fn keep_largest(&mut self, count: usize) {
+        if self.kept.len() <= count {
+            return;
+        }
+
+        let order = self.largest_first();
+        self.keep_selected(&order[..count], SkipReason::NotInTop);
+    }
+
+    /// Keep the fewest projects whose combined size reaches `target` bytes,
+    /// and move the others to the skipped list, tagged
+    /// [`SkipReason::FreeTargetReached`].
+    ///
+    /// Backs `--free SIZE`. The largest projects (the oldest ones first,
+    /// among projects of equal size) are taken until the remainder fits in a
+    /// single project; the smallest project covering the remainder is then
+    /// taken instead of the next largest, so as not to free much more than
+    /// asked. All projects are kept if together they do not reach `target`.
+    pub fn keep_until_freed(&mut self, target: u64) {
+        let order = self.largest_first();
+        let size = |index: usize| self.kept[index].total_size();
+
+        let mut selected = Vec::new();
+        let mut remaining = target;
+        let mut rest = &order[..];
+        while remaining > 0
+            && let Some((&largest, tail)) = rest.split_first()
+        {
+            if size(largest) >= remaining {
+                let covering = rest.partition_point(|&index| size(index) >= remaining);
+                selected.push(rest[covering - 1]);
+                remaining = 0;
+            } else {
+                selected.push(largest);
+                remaining -= size(largest);
+                rest = tail;
+            }
+        }
+
+        if remaining == 0 {
+            self.keep_selected(&selected, SkipReason::FreeTargetReached);
+        }
+    }
+
+    /// Return the indices of the kept projects, largest first and, among
+    /// projects of equal size, oldest first.
+    fn largest_first(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.kept.len()).collect();
+        order.sort_by_cached_key(|&index| {
+            let project = &self.kept[index];
+            (
+                std::cmp::Reverse(project.total_size()),
+                last_modified(project).unwrap_or_else(SystemTime::now),
+            )
+        });
+        order
+    }
+
+    /// Move the kept projects whose indices are not in `selected` to the
+    /// skipped list, tagged `reason`, preserving their relative order.
+    fn keep_selected(&mut self, selected: &[usize], reason: SkipReason) {
+        let mut is_selected = vec![false; self.kept.len()];
+        for &index in selected {
+            is_selected[index] = true;
+        }
+
+        let (kept, skipped): (Vec<_>, Vec<_>) = std::mem::take(&mut self.kept)
+            .into_iter()
+            .zip(is_selected)
+            .partition(|(_, is_selected)| *is_selected);
+
+        self.kept = kept.into_iter().map(|(project, _)| project).collect();
+        self.skipped.extend(
+            skipped
+                .into_iter()
+                .map(|(project, _)| SkippedProject { project, reason }),
+        );
+    }
+}
+
+/// Check whether a project's lockfile or manifest changed after its build
+/// artifacts were last written.
+fn is_outdated(project: &Project) -> bool {
+    let Some(dependencies_changed) = dependency_files(project)
+        .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .max()
+    else {
+        return false;
+    };
+
+    // The artifact directories themselves are rarely rewritten (e.g. cargo
+    // writes into `target/debug/`), so their direct children are looked at
+    // as well.
+    let built = project
+        .artifact_paths()
+        .flat_map(|path| WalkDir::new(path).max_depth(2))
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max();
+
+    built.is_some_and(|built| built < dependencies_changed)
+}
+
+/// Iterate over the lockfiles and manifests of a project that exist.
+///
+/// Besides fixed names, entries starting with `*.` match any file with that
+/// extension in the project root (e.g. `*.csproj`).
+fn dependency_files(project: &Project) -> impl Iterator<Item = PathBuf> + '_ {
+    let names: &[&str] = match project.kind {
+        ProjectType::Rust => &["Cargo.lock", "Cargo.toml"],
+        ProjectType::Node => &[
+            "package-lock.json",
+            "yarn.lock",
+            "pnpm-lock.yaml",
+            "bun.lock",
+            "bun.lockb",
+            "package.json",
+        ],
+        ProjectType::Python => &[
+            "uv.lock",
+            "poetry.lock",
+            "pdm.lock",
+            "Pipfile.lock",
+            "Pipfile",
+            "requirements.txt",
+            "pyproject.toml",
+            "setup.py",
+            "setup.cfg",
+        ],
+        ProjectType::Go => &["go.sum", "go.mod"],
+        ProjectType::Java => &[
+            "pom.xml",
+            "build.gradle",
+            "build.gradle.kts",
+            "gradle.lockfile",
+            "settings.gradle",
+            "settings.gradle.kts",
+        ],
+        ProjectType::Cpp => &["CMakeLists.txt", "Makefile", "conanfile.txt", "vcpkg.json"],
+        ProjectType::Swift => &["Package.resolved", "Package.swift"],
+        ProjectType::DotNet => &["packages.lock.json", "*.csproj", "*.fsproj", "*.sln"],
+        ProjectType::OCaml => &["dune-project", "*.opam"],
+        ProjectType::Unity => &["Packages/packages-lock.json", "Packages/manifest.json"],
+        ProjectType::Unreal => &["*.uproject"],
+    };
+
+    let root = &project.root_path;
+    let (patterns, files): (Vec<&str>, Vec<&str>) =
+        names.iter().partition(|name| name.starts_with("*."));
+
+    let by_extension = (!patterns.is_empty())
+        .then(|| fs::read_dir(root).ok())
+        .flatten()
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(move |path| {
+            path.extension().is_some_and(|extension| {
+                patterns
+                    .iter()
+                    .any(|pattern| extension.to_string_lossy() == pattern[2..])
+            })
+        });
+
+    files
+        .into_iter()
+        .map(|name| root.join(name))
+        .filter(|path| path.exists())
+        .chain(by_extension)
+}
+
+/// Filter projects based on size, modification time, and permission criteria.
+///
+/// This is a convenience wrapper around [`classify_projects`] that discards
+/// the skipped projects and their reasons.
 ///
 /// # Arguments
 ///
@@ -29,7 +477,7 @@ use crate::utils::parse_size;
 /// # Returns
 ///
 /// - `Ok(Vec<Project>)` - Filtered list of projects that meet all criteria
-/// - `Err(anyhow::Error)` - If size parsing fails, or file system errors occur
+/// - `Err(Error)` - If size parsing fails, or file system errors occur
 ///
 /// # Errors
 ///
@@ -41,11 +489,14 @@ use crate::utils::parse_size;
 ///
 /// ```no_run
 /// # use clean_dev_dirs::{filtering::filter_projects, config::FilterOptions, project::Project};
-/// # use anyhow::Result;
-/// # fn example(projects: Vec<Project>) -> Result<()> {
+/// # fn example(projects: Vec<Project>) -> clean_dev_dirs::error::Result<()> {
 /// let filter_opts = FilterOptions {
 ///     keep_size: "100MB".to_string(),
 ///     keep_days: 30,
+///     git_age: false,
+///     top: None,
+///     free: None,
+///     protected_artifacts: vec![],
 /// };
 /// let filtered = filter_projects(projects, &filter_opts)?;
 /// # Ok(())
@@ -55,37 +506,186 @@ pub fn filter_projects(
     projects: Vec<Project>,
     filter_opts: &FilterOptions,
 ) -> Result<Vec<Project>> {
+    Ok(classify_projects(projects, filter_opts)?.kept)
+}
+
+/// Split projects into those eligible for cleaning and those filtered out.
+///
+/// Projects are checked in parallel against the following criteria, and the
+/// first one that fails determines the [`SkipReason`]:
+/// 1. A [`KEEP_MARKER`] file in the project root, or only build directories
+///    named in `filter_opts.protected_artifacts` or marked
+///    `clean-dev-dirs=keep` in `.gitattributes` (see
+///    [`gitattributes`](crate::utils::gitattributes)) ([`SkipReason::Protected`])
+/// 2. Build artifacts smaller than the minimum size ([`SkipReason::SkippedBySize`])
+/// 3. Build artifacts modified too recently, or with `filter_opts.git_age`
+///    a commit too recent ([`SkipReason::SkippedRecent`])
+/// 4. Build artifacts owned by another user ([`SkipReason::Unowned`], Unix only)
+/// 5. Build artifacts that cannot be removed ([`SkipReason::ReadOnly`])
+///
+/// With `filter_opts.top` and `filter_opts.free`, only the largest remaining
+/// projects are then kept (see [`FilterOutcome::keep_largest`] and
+/// [`FilterOutcome::keep_until_freed`]). The relative order of projects is
+/// preserved in both lists.
+///
+/// # Errors
+///
+/// Returns an error if `filter_opts.keep_size` or `filter_opts.free` cannot
+/// be parsed.
+pub fn classify_projects(
+    projects: Vec<Project>,
+    filter_opts: &FilterOptions,
+) -> Result<FilterOutcome> {
     let keep_size_bytes = parse_size(&filter_opts.keep_size)?;
-    let keep_days = filter_opts.keep_days;
+    let free_bytes = filter_opts.free.as_deref().map(parse_size).transpose()?;
+
+    let attributes = GitAttributes::new();
+
+    let (kept, skipped): (Vec<_>, Vec<_>) =
+        projects.into_par_iter().partition_map(|mut project| {
+            let has_artifacts = !project.build_arts.is_empty();
+            project.build_arts.retain(|artifact| {
+                !is_protected(&artifact.path, &filter_opts.protected_artifacts)
+                    && !attributes.is_kept(&artifact.path)
+            });
+            if has_artifacts && project.build_arts.is_empty() {
+                return Either::Right(SkippedProject {
+                    project,
+                    reason: SkipReason::Protected,
+                });
+            }
+
+            match skip_reason(&project, keep_size_bytes, filter_opts) {
+                Some(reason) => Either::Right(SkippedProject { project, reason }),
+                None => Either::Left(project),
+            }
+        });
+
+    let mut outcome = FilterOutcome { kept, skipped };
+    if let Some(top) = filter_opts.top {
+        outcome.keep_largest(top);
+    }
+    if let Some(target) = free_bytes {
+        outcome.keep_until_freed(target);
+    }
+    Ok(outcome)
+}
+
+/// Determine why a project should be skipped, if at all.
+fn skip_reason(
+    project: &Project,
+    min_size: u64,
+    filter_opts: &FilterOptions,
+) -> Option<SkipReason> {
+    if project.root_path.join(KEEP_MARKER).exists() {
+        return Some(SkipReason::Protected);
+    }
+
+    if !meets_size_criteria(project, min_size) {
+        return Some(SkipReason::SkippedBySize);
+    }
+
+    if !meets_time_criteria(project, filter_opts) {
+        return Some(SkipReason::SkippedRecent);
+    }
+
+    if project.artifact_paths().any(is_unowned) {
+        return Some(SkipReason::Unowned);
+    }
+
+    if project.artifact_paths().any(is_read_only) {
+        return Some(SkipReason::ReadOnly);
+    }
+
+    None
+}
 
-    Ok(projects
-        .into_par_iter()
-        .filter(|project| meets_size_criteria(project, keep_size_bytes))
-        .filter(|project| meets_time_criteria(project, keep_days))
-        .collect())
+/// Check whether `project` is skipped as [`SkipReason::SkippedRecent`] by
+/// `filter_opts.keep_days`.
+///
+/// Only the modification times of its build directories (or its last commit
+/// with `filter_opts.git_age`) are read, so this
+/// can run before they are sized (see [`crate::Scanner::find_projects`]),
+/// sparing the walk of artifacts that would be skipped anyway. Projects
+/// with a [`KEEP_MARKER`] are never considered recent: they are skipped as
+/// protected instead.
+#[must_use]
+pub fn is_recent(project: &Project, filter_opts: &FilterOptions) -> bool {
+    !meets_time_criteria(project, filter_opts) && !project.root_path.join(KEEP_MARKER).exists()
+}
+
+/// Check whether the build directory at `path` is named in `protected`.
+fn is_protected(path: &Path, protected: &[String]) -> bool {
+    path.file_name()
+        .is_some_and(|name| protected.iter().any(|protected| name == protected.as_str()))
+}
+
+/// Check whether `path` exists and belongs to a user other than the current one.
+#[cfg(unix)]
+fn is_unowned(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::symlink_metadata(path)
+        .is_ok_and(|metadata| metadata.uid() != rustix::process::geteuid().as_raw())
+}
+
+/// Ownership is not checked on non-Unix platforms.
+#[cfg(not(unix))]
+const fn is_unowned(_path: &Path) -> bool {
+    false
+}
+
+/// Check whether `path` exists but cannot be removed by the current user.
+///
+/// Removing a directory requires write access both to the directory itself
+/// (to unlink its contents) and to its parent (to unlink the directory).
+fn is_read_only(path: &Path) -> bool {
+    if fs::symlink_metadata(path).is_err() {
+        return false; // Nothing to remove, let the cleaner report it
+    }
+
+    !is_writable(path) || path.parent().is_some_and(|parent| !is_writable(parent))
+}
+
+/// Check whether the current user may write to `path`.
+#[cfg(unix)]
+fn is_writable(path: &Path) -> bool {
+    use rustix::fs::{Access, access};
+
+    access(path, Access::WRITE_OK).is_ok()
+}
+
+/// Check whether the current user may write to `path`.
+#[cfg(not(unix))]
+fn is_writable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| !metadata.permissions().readonly())
 }
 
 /// Check if a project meets the size criteria.
-const fn meets_size_criteria(project: &Project, min_size: u64) -> bool {
-    project.build_arts.size >= min_size
+fn meets_size_criteria(project: &Project, min_size: u64) -> bool {
+    project.total_size() >= min_size
 }
 
 /// Check if a project meets the time criteria.
-fn meets_time_criteria(project: &Project, keep_days: u32) -> bool {
-    if keep_days == 0 {
+fn meets_time_criteria(project: &Project, filter_opts: &FilterOptions) -> bool {
+    if filter_opts.keep_days == 0 {
         return true;
     }
 
-    is_project_old_enough(project, keep_days)
+    is_project_old_enough(project, filter_opts.keep_days, filter_opts.git_age)
 }
 
 /// Check if a project is old enough based on its modification time.
-fn is_project_old_enough(project: &Project, keep_days: u32) -> bool {
-    let Result::Ok(metadata) = fs::metadata(&project.build_arts.path) else {
-        return true; // If we can't read metadata, don't filter it out
-    };
-
-    let Result::Ok(modified) = metadata.modified() else {
+///
+/// For projects with several build directories, the most recently modified
+/// one decides. With `git_age`, the time of the last commit touching the
+/// project decides instead, unless it is not in a git repository or has no
+/// commit yet.
+fn is_project_old_enough(project: &Project, keep_days: u32, git_age: bool) -> bool {
+    let last_commit = git_age
+        .then(|| last_commit_time(&project.root_path).ok())
+        .flatten();
+    let Some(modified) = last_commit.or_else(|| last_modified(project)) else {
         return true; // If we can't read modification time, don't filter it out
     };
 
@@ -95,6 +695,20 @@ fn is_project_old_enough(project: &Project, keep_days: u32) -> bool {
     modified_time <= cutoff_time
 }
 
+/// Return the most recent modification time across a project's build directories.
+///
+/// Activity recorded by the git hooks for the project's repository (see
+/// [`activity`](crate::activity)) counts as a modification too.
+/// Directories whose metadata cannot be read are ignored; `None` is returned
+/// when no modification time could be read at all.
+pub(crate) fn last_modified(project: &Project) -> Option<SystemTime> {
+    project
+        .artifact_paths()
+        .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .chain(recorded_activity(&project.root_path))
+        .max()
+}
+
 /// Sort projects in place according to the given sorting options.
 ///
 /// When `sort_opts.criteria` is `None`, the list is left in its current order.
@@ -134,7 +748,7 @@ pub fn sort_projects(projects: &mut Vec<Project>, sort_opts: &SortOptions) {
 
     match criteria {
         SortCriteria::Size => {
-            projects.sort_by(|a, b| b.build_arts.size.cmp(&a.build_arts.size));
+            projects.sort_by_key(|p| std::cmp::Reverse(p.total_size()));
         }
         SortCriteria::Age => {
             sort_by_age(projects);
@@ -147,7 +761,7 @@ pub fn sort_projects(projects: &mut Vec<Project>, sort_opts: &SortOptions) {
             });
         }
         SortCriteria::Type => {
-            projects.sort_by(|a, b| type_order(&a.kind).cmp(&type_order(&b.kind)));
+            projects.sort_by_key(|p| type_order(&p.kind));
         }
     }
 
@@ -164,14 +778,12 @@ fn sort_by_age(projects: &mut Vec<Project>) {
     let mut decorated: Vec<(Project, SystemTime)> = projects
         .drain(..)
         .map(|p| {
-            let mtime = fs::metadata(&p.build_arts.path)
-                .and_then(|m| m.modified())
-                .unwrap_or(SystemTime::UNIX_EPOCH);
+            let mtime = last_modified(&p).unwrap_or(SystemTime::UNIX_EPOCH);
             (p, mtime)
         })
         .collect();
 
-    decorated.sort_by(|a, b| a.1.cmp(&b.1));
+    decorated.sort_by_key(|(_, mtime)| *mtime);
 
     projects.extend(decorated.into_iter().map(|(p, _)| p));
 }
@@ -179,7 +791,7 @@ fn sort_by_age(projects: &mut Vec<Project>) {
 /// Map a `ProjectType` to an ordering index for type-based sorting.
 ///
 /// Types are ordered alphabetically by their display name:
-/// C/C++, .NET, Go, Java, Node, Python, Rust, Swift
+/// C/C++, .NET, Go, Java, Node, OCaml, Python, Rust, Swift, Unity, Unreal
 const fn type_order(kind: &ProjectType) -> u8 {
     match kind {
         ProjectType::Cpp => 0,
@@ -187,9 +799,12 @@ const fn type_order(kind: &ProjectType) -> u8 {
         ProjectType::Go => 2,
         ProjectType::Java => 3,
         ProjectType::Node => 4,
-        ProjectType::Python => 5,
-        ProjectType::Rust => 6,
-        ProjectType::Swift => 7,
+        ProjectType::OCaml => 5,
+        ProjectType::Python => 6,
+        ProjectType::Rust => 7,
+        ProjectType::Swift => 8,
+        ProjectType::Unity => 9,
+        ProjectType::Unreal => 10,
     }
 }
 
@@ -210,10 +825,10 @@ mod tests {
         Project::new(
             kind,
             PathBuf::from(root_path),
-            BuildArtifacts {
+            vec![BuildArtifacts {
                 path: PathBuf::from(build_path),
                 size,
-            },
+            }],
             name,
         )
     }
@@ -244,7 +859,533 @@ mod tests {
         );
 
         // When keep_days is 0, should always return true
-        assert!(meets_time_criteria(&project, 0));
+        let filter_opts = FilterOptions {
+            keep_size: "0".to_string(),
+            keep_days: 0,
+            git_age: true,
+            top: None,
+            free: None,
+            protected_artifacts: vec![],
+        };
+        assert!(meets_time_criteria(&project, &filter_opts));
+    }
+
+    // ── Skip reason tests ───────────────────────────────────────────────
+
+    #[test]
+    fn test_skip_reason_codes() {
+        assert_eq!(SkipReason::SkippedBySize.code(), "SKIPPED_BY_SIZE");
+        assert_eq!(SkipReason::SkippedRecent.code(), "SKIPPED_RECENT");
+        assert_eq!(SkipReason::Protected.code(), "PROTECTED");
+        assert_eq!(SkipReason::ReadOnly.code(), "READ_ONLY");
+        assert_eq!(SkipReason::Unowned.code(), "UNOWNED");
+        assert_eq!(SkipReason::BuildInProgress.code(), "BUILD_IN_PROGRESS");
+        assert_eq!(SkipReason::UnchangedSinceRef.code(), "UNCHANGED_SINCE_REF");
+        assert_eq!(SkipReason::ChangedSinceRef.code(), "CHANGED_SINCE_REF");
+        assert_eq!(SkipReason::GitUnavailable.code(), "GIT_UNAVAILABLE");
+    }
+
+    #[test]
+    fn test_skip_reason_serializes_as_code() {
+        for reason in [
+            SkipReason::SkippedBySize,
+            SkipReason::SkippedRecent,
+            SkipReason::Protected,
+            SkipReason::ReadOnly,
+            SkipReason::Unowned,
+            SkipReason::BuildInProgress,
+            SkipReason::UnchangedSinceRef,
+            SkipReason::ChangedSinceRef,
+            SkipReason::GitUnavailable,
+        ] {
+            let json = serde_json::to_string(&reason).unwrap();
+            assert_eq!(json, format!("\"{}\"", reason.code()));
+        }
+    }
+
+    #[test]
+    fn test_skip_busy_moves_projects_with_running_package_manager() {
+        let mut outcome = FilterOutcome {
+            kept: vec![
+                create_test_project(
+                    ProjectType::Rust,
+                    "/busy",
+                    "/busy/target",
+                    1000,
+                    Some("busy".into()),
+                ),
+                create_test_project(
+                    ProjectType::Node,
+                    "/idle",
+                    "/idle/node_modules",
+                    1000,
+                    Some("idle".into()),
+                ),
+            ],
+            skipped: Vec::new(),
+        };
+
+        outcome.skip_busy(&[PathBuf::from("/busy/src")]);
+
+        assert_eq!(outcome.kept.len(), 1);
+        assert_eq!(outcome.kept[0].name.as_deref(), Some("idle"));
+        assert_eq!(outcome.skipped.len(), 1);
+        assert_eq!(outcome.skipped[0].project.name.as_deref(), Some("busy"));
+        assert_eq!(outcome.skipped[0].reason, SkipReason::BuildInProgress);
+    }
+
+    #[test]
+    fn test_skip_in_use_moves_projects_with_open_artifacts() {
+        let mut outcome = FilterOutcome {
+            kept: vec![
+                create_test_project(
+                    ProjectType::Node,
+                    "/served",
+                    "/served/node_modules",
+                    1000,
+                    Some("served".into()),
+                ),
+                create_test_project(
+                    ProjectType::Node,
+                    "/idle",
+                    "/idle/node_modules",
+                    1000,
+                    Some("idle".into()),
+                ),
+            ],
+            skipped: Vec::new(),
+        };
+
+        outcome.skip_in_use(&[
+            OpenFile {
+                pid: 412,
+                process: "node".to_string(),
+                path: PathBuf::from("/served/node_modules/esbuild/bin/esbuild"),
+            },
+            OpenFile {
+                pid: 412,
+                process: "node".to_string(),
+                path: PathBuf::from("/idle/package.json"),
+            },
+        ]);
+
+        assert_eq!(outcome.kept.len(), 1);
+        assert_eq!(outcome.kept[0].name.as_deref(), Some("idle"));
+        assert_eq!(outcome.skipped[0].project.name.as_deref(), Some("served"));
+        assert_eq!(outcome.skipped[0].reason, SkipReason::InUse);
+    }
+
+    #[test]
+    fn test_skip_by_git_never_keeps_projects_outside_git() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().to_string_lossy().into_owned();
+        let mut outcome = FilterOutcome {
+            kept: vec![create_test_project(
+                ProjectType::Rust,
+                &root,
+                &format!("{root}/target"),
+                1000,
+                Some("untracked".into()),
+            )],
+            skipped: Vec::new(),
+        };
+
+        outcome.skip_by_git(&GitChangeFilter::UnchangedSince("HEAD".to_string()));
+
+        assert!(outcome.kept.is_empty());
+        assert_eq!(outcome.skipped[0].reason, SkipReason::GitUnavailable);
+    }
+
+    #[test]
+    fn test_skip_dirty_keeps_projects_outside_git() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().to_string_lossy().into_owned();
+        let mut outcome = FilterOutcome {
+            kept: vec![create_test_project(
+                ProjectType::Rust,
+                &root,
+                &format!("{root}/target"),
+                1000,
+                Some("untracked".into()),
+            )],
+            skipped: Vec::new(),
+        };
+
+        outcome.skip_dirty();
+
+        assert_eq!(outcome.kept.len(), 1);
+        assert!(outcome.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_skip_up_to_date_keeps_projects_with_newer_lockfiles() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let hour = std::time::Duration::from_hours(1);
+
+        let mut projects = Vec::new();
+        for (name, lockfile_changed) in [("outdated", true), ("fresh", false), ("bare", false)] {
+            let root = temp_dir.path().join(name);
+            fs::create_dir_all(root.join("node_modules/dep")).unwrap();
+            if name != "bare" {
+                let changed = if lockfile_changed {
+                    SystemTime::now() + hour
+                } else {
+                    SystemTime::now() - hour
+                };
+                fs::File::create(root.join("package-lock.json"))
+                    .unwrap()
+                    .set_modified(changed)
+                    .unwrap();
+            }
+
+            projects.push(create_test_project(
+                ProjectType::Node,
+                &root.to_string_lossy(),
+                &root.join("node_modules").to_string_lossy(),
+                1000,
+                Some(name.into()),
+            ));
+        }
+
+        let mut outcome = FilterOutcome {
+            kept: projects,
+            skipped: Vec::new(),
+        };
+        outcome.skip_up_to_date();
+
+        let kept: Vec<_> = outcome.kept.iter().map(|p| p.name.as_deref()).collect();
+        assert_eq!(kept, [Some("outdated")]);
+        assert!(
+            outcome
+                .skipped
+                .iter()
+                .all(|skipped| skipped.reason == SkipReason::UpToDate)
+        );
+    }
+
+    #[test]
+    fn test_dependency_files_match_extensions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("App.csproj"), "").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+
+        let root = root.to_string_lossy();
+        let project =
+            create_test_project(ProjectType::DotNet, &root, &format!("{root}/bin"), 0, None);
+
+        let files: Vec<_> = dependency_files(&project).collect();
+        assert_eq!(files, [temp_dir.path().join("App.csproj")]);
+    }
+
+    #[test]
+    fn test_classify_projects_tags_small_projects() {
+        let projects = vec![
+            create_test_project(
+                ProjectType::Rust,
+                "/big",
+                "/big/target",
+                2_000_000,
+                Some("big".into()),
+            ),
+            create_test_project(
+                ProjectType::Rust,
+                "/small",
+                "/small/target",
+                1_000,
+                Some("small".into()),
+            ),
+        ];
+        let filter_opts = FilterOptions {
+            keep_size: "1MB".to_string(),
+            keep_days: 0,
+            git_age: false,
+            top: None,
+            free: None,
+            protected_artifacts: vec![],
+        };
+
+        let outcome = classify_projects(projects, &filter_opts).unwrap();
+
+        assert_eq!(outcome.kept.len(), 1);
+        assert_eq!(outcome.kept[0].name.as_deref(), Some("big"));
+        assert_eq!(outcome.skipped.len(), 1);
+        assert_eq!(outcome.skipped[0].project.name.as_deref(), Some("small"));
+        assert_eq!(outcome.skipped[0].reason, SkipReason::SkippedBySize);
+    }
+
+    #[test]
+    fn test_classify_projects_keeps_top_largest_after_size_filter() {
+        let projects = [
+            ("a", 3_000_000),
+            ("tiny", 1_000),
+            ("b", 5_000_000),
+            ("c", 2_000_000),
+        ]
+        .into_iter()
+        .map(|(name, size)| {
+            create_test_project(
+                ProjectType::Rust,
+                &format!("/{name}"),
+                &format!("/{name}/target"),
+                size,
+                Some(name.into()),
+            )
+        })
+        .collect();
+        let filter_opts = FilterOptions {
+            keep_size: "1MB".to_string(),
+            keep_days: 0,
+            git_age: false,
+            top: Some(2),
+            free: None,
+            protected_artifacts: vec![],
+        };
+
+        let outcome = classify_projects(projects, &filter_opts).unwrap();
+
+        let kept: Vec<_> = outcome.kept.iter().map(|p| p.name.as_deref()).collect();
+        assert_eq!(kept, [Some("a"), Some("b")]);
+        let reasons: Vec<_> = outcome
+            .skipped
+            .iter()
+            .map(|s| (s.project.name.as_deref(), s.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                (Some("tiny"), SkipReason::SkippedBySize),
+                (Some("c"), SkipReason::NotInTop)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keep_until_freed_takes_fewest_projects() {
+        let outcome = || FilterOutcome {
+            kept: [("a", 10), ("b", 6), ("c", 5), ("d", 3), ("e", 1)]
+                .into_iter()
+                .map(|(name, size)| {
+                    create_test_project(
+                        ProjectType::Rust,
+                        &format!("/{name}"),
+                        &format!("/{name}/target"),
+                        size,
+                        Some(name.into()),
+                    )
+                })
+                .collect(),
+            skipped: Vec::new(),
+        };
+        let kept_after = |target| {
+            let mut outcome = outcome();
+            outcome.keep_until_freed(target);
+            assert!(
+                outcome
+                    .skipped
+                    .iter()
+                    .all(|s| s.reason == SkipReason::FreeTargetReached)
+            );
+            outcome
+                .kept
+                .into_iter()
+                .filter_map(|p| p.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(kept_after(9), ["a"]);
+        // The remainder of 3 is covered by "d" rather than "b"
+        assert_eq!(kept_after(13), ["a", "d"]);
+        assert_eq!(kept_after(25), ["a", "b", "c", "d", "e"]);
+        // Everything is kept when the target cannot be reached
+        assert_eq!(kept_after(100), ["a", "b", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn test_classify_projects_tags_recent_projects() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let build_dir = tmp.path().join("target");
+        fs::create_dir(&build_dir).unwrap();
+
+        let project = create_test_project(
+            ProjectType::Rust,
+            tmp.path().to_str().unwrap(),
+            build_dir.to_str().unwrap(),
+            1_000,
+            Some("fresh".into()),
+        );
+        let filter_opts = FilterOptions {
+            keep_size: "0".to_string(),
+            keep_days: 7,
+            git_age: false,
+            top: None,
+            free: None,
+            protected_artifacts: vec![],
+        };
+
+        let outcome = classify_projects(vec![project], &filter_opts).unwrap();
+
+        assert!(outcome.kept.is_empty());
+        assert_eq!(outcome.skipped[0].reason, SkipReason::SkippedRecent);
+    }
+
+    #[test]
+    fn test_is_recent() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let build_dir = tmp.path().join("target");
+        fs::create_dir(&build_dir).unwrap();
+
+        // Not sized yet, as right after `Scanner::find_projects`
+        let project = create_test_project(
+            ProjectType::Rust,
+            tmp.path().to_str().unwrap(),
+            build_dir.to_str().unwrap(),
+            0,
+            None,
+        );
+
+        let mut filter_opts = FilterOptions {
+            keep_size: "0".to_string(),
+            keep_days: 7,
+            git_age: false,
+            top: None,
+            free: None,
+            protected_artifacts: vec![],
+        };
+        assert!(is_recent(&project, &filter_opts));
+        // Outside a git repository, the modification time still decides
+        filter_opts.git_age = true;
+        assert!(is_recent(&project, &filter_opts));
+        filter_opts.keep_days = 0;
+        assert!(!is_recent(&project, &filter_opts));
+
+        fs::write(tmp.path().join(KEEP_MARKER), "").unwrap();
+        filter_opts.keep_days = 7;
+        assert!(!is_recent(&project, &filter_opts));
+    }
+
+    #[test]
+    fn test_classify_projects_tags_protected_projects() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let build_dir = tmp.path().join("target");
+        fs::create_dir(&build_dir).unwrap();
+        fs::write(tmp.path().join(KEEP_MARKER), "").unwrap();
+
+        let project = create_test_project(
+            ProjectType::Rust,
+            tmp.path().to_str().unwrap(),
+            build_dir.to_str().unwrap(),
+            1_000,
+            Some("kept".into()),
+        );
+        let filter_opts = FilterOptions {
+            keep_size: "0".to_string(),
+            keep_days: 0,
+            git_age: false,
+            top: None,
+            free: None,
+            protected_artifacts: vec![],
+        };
+
+        let outcome = classify_projects(vec![project], &filter_opts).unwrap();
+
+        assert!(outcome.kept.is_empty());
+        assert_eq!(outcome.skipped[0].reason, SkipReason::Protected);
+    }
+
+    #[test]
+    fn test_classify_projects_drops_protected_artifacts() {
+        let artifact = |name: &str| BuildArtifacts {
+            path: PathBuf::from("/app").join(name),
+            size: 1_000,
+        };
+        let python = |name: &str| {
+            Project::new(
+                ProjectType::Python,
+                PathBuf::from("/app"),
+                vec![artifact(".venv"), artifact("__pycache__")],
+                Some(name.into()),
+            )
+        };
+        let filter_opts = |protected: &[&str]| FilterOptions {
+            keep_size: "0".to_string(),
+            keep_days: 0,
+            git_age: false,
+            top: None,
+            free: None,
+            protected_artifacts: protected.iter().map(ToString::to_string).collect(),
+        };
+
+        let outcome = classify_projects(vec![python("app")], &filter_opts(&[".venv"])).unwrap();
+        assert_eq!(outcome.kept.len(), 1);
+        assert_eq!(outcome.kept[0].build_arts.len(), 1);
+        assert!(outcome.kept[0].build_arts[0].path.ends_with("__pycache__"));
+
+        // A project left with nothing to clean is reported as protected
+        let outcome =
+            classify_projects(vec![python("app")], &filter_opts(&[".venv", "__pycache__"]))
+                .unwrap();
+        assert!(outcome.kept.is_empty());
+        assert_eq!(outcome.skipped[0].reason, SkipReason::Protected);
+    }
+
+    #[test]
+    fn test_classify_projects_respects_gitattributes() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join(".git")).unwrap();
+        fs::write(
+            tmp.path().join(".gitattributes"),
+            "node_modules clean-dev-dirs=keep\n",
+        )
+        .unwrap();
+        let artifact = |name: &str| BuildArtifacts {
+            path: tmp.path().join(name),
+            size: 1_000,
+        };
+        let project = Project::new(
+            ProjectType::Node,
+            tmp.path().to_path_buf(),
+            vec![artifact("node_modules"), artifact(".next")],
+            Some("web".into()),
+        );
+        let filter_opts = FilterOptions {
+            keep_size: "0".to_string(),
+            keep_days: 0,
+            git_age: false,
+            top: None,
+            free: None,
+            protected_artifacts: vec![],
+        };
+
+        let outcome = classify_projects(vec![project.clone()], &filter_opts).unwrap();
+        assert_eq!(outcome.kept[0].build_arts.len(), 1);
+        assert!(outcome.kept[0].build_arts[0].path.ends_with(".next"));
+
+        fs::write(
+            tmp.path().join(".gitattributes"),
+            "/* clean-dev-dirs=keep\n",
+        )
+        .unwrap();
+        let outcome = classify_projects(vec![project], &filter_opts).unwrap();
+        assert!(outcome.kept.is_empty());
+        assert_eq!(outcome.skipped[0].reason, SkipReason::Protected);
+    }
+
+    #[test]
+    fn test_missing_artifacts_are_not_read_only_or_unowned() {
+        let path = Path::new("/nonexistent/path/for/sure/target");
+        assert!(!is_read_only(path));
+        assert!(!is_unowned(path));
+    }
+
+    #[test]
+    fn test_own_temp_dir_is_writable_and_owned() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let build_dir = tmp.path().join("target");
+        fs::create_dir(&build_dir).unwrap();
+
+        assert!(!is_read_only(&build_dir));
+        assert!(!is_unowned(&build_dir));
     }
 
     // ── Sorting tests ───────────────────────────────────────────────────
@@ -281,9 +1422,9 @@ mod tests {
         };
         sort_projects(&mut projects, &sort_opts);
 
-        assert_eq!(projects[0].build_arts.size, 300);
-        assert_eq!(projects[1].build_arts.size, 200);
-        assert_eq!(projects[2].build_arts.size, 100);
+        assert_eq!(projects[0].total_size(), 300);
+        assert_eq!(projects[1].total_size(), 200);
+        assert_eq!(projects[2].total_size(), 100);
     }
 
     #[test]
@@ -318,9 +1459,9 @@ mod tests {
         };
         sort_projects(&mut projects, &sort_opts);
 
-        assert_eq!(projects[0].build_arts.size, 100);
-        assert_eq!(projects[1].build_arts.size, 200);
-        assert_eq!(projects[2].build_arts.size, 300);
+        assert_eq!(projects[0].total_size(), 100);
+        assert_eq!(projects[1].total_size(), 200);
+        assert_eq!(projects[2].total_size(), 300);
     }
 
     #[test]
@@ -620,8 +1761,11 @@ mod tests {
         assert!(type_order(&ProjectType::DotNet) < type_order(&ProjectType::Go));
         assert!(type_order(&ProjectType::Go) < type_order(&ProjectType::Java));
         assert!(type_order(&ProjectType::Java) < type_order(&ProjectType::Node));
-        assert!(type_order(&ProjectType::Node) < type_order(&ProjectType::Python));
+        assert!(type_order(&ProjectType::Node) < type_order(&ProjectType::OCaml));
+        assert!(type_order(&ProjectType::OCaml) < type_order(&ProjectType::Python));
         assert!(type_order(&ProjectType::Python) < type_order(&ProjectType::Rust));
         assert!(type_order(&ProjectType::Rust) < type_order(&ProjectType::Swift));
+        assert!(type_order(&ProjectType::Swift) < type_order(&ProjectType::Unity));
+        assert!(type_order(&ProjectType::Unity) < type_order(&ProjectType::Unreal));
     }
 }
@@ -0,0 +1,517 @@
+//! Cleanup history and restoring.
+//!
+//! Every cleanup writes a manifest of the run (its directory, outcome and
+//! the artifact directories it removed) to the history directory
+//! (`~/.local/share/clean-dev-dirs/history/` on Linux), one JSON file per
+//! run named after its run id. Backs the `history` subcommand, which lists
+//! past runs and the space they freed, and the `restore` subcommand:
+//! artifacts that were moved to the trash are restored from it, and
+//! permanently deleted ones are listed so they can be rebuilt.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cleaner::{CleanResult, RemovalStrategy};
+use crate::error::{Error, Result};
+
+/// The directory manifests are written to, if the platform has a data
+/// directory.
+#[must_use]
+pub fn history_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("clean-dev-dirs").join("history"))
+}
+
+/// `path` made absolute, so that the manifest does not depend on the
+/// directory the run was started from.
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The outcome of one run and the artifact directories it removed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Identifier of the run, e.g. `20260105-093000`.
+    pub run_id: String,
+
+    /// Time the run finished.
+    pub created_at: DateTime<Utc>,
+
+    /// Directory the run scanned; `None` for global cache cleanups.
+    #[serde(default)]
+    pub root: Option<PathBuf>,
+
+    /// Whether the artifacts were moved to the trash rather than deleted.
+    pub trashed: bool,
+
+    /// Number of projects (or caches) cleaned, fully or partly.
+    #[serde(default)]
+    pub cleaned_count: usize,
+
+    /// Total bytes freed.
+    #[serde(default)]
+    pub bytes_freed: u64,
+
+    /// Number of projects (or caches) left untouched because the run was
+    /// cancelled.
+    #[serde(default)]
+    pub cancelled_count: usize,
+
+    /// Error messages for the projects that failed to clean.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+
+    /// The removed artifact directories.
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// An artifact directory recorded in a [`Manifest`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Root directory of the project the artifact belongs to.
+    pub project_path: PathBuf,
+
+    /// Path of the artifact directory.
+    pub artifact_path: PathBuf,
+
+    /// Size of the artifact directory, in bytes.
+    pub size: u64,
+
+    /// Time the artifact was removed.
+    pub deleted_at: DateTime<Utc>,
+
+    /// Identifier of the artifact in the system trash (the `.trashinfo`
+    /// file on Linux), if it was found there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trash_location: Option<PathBuf>,
+}
+
+/// What [`restore`] did with a manifest entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RestoreOutcome {
+    /// The artifact was moved back from the trash.
+    Restored,
+
+    /// Something already exists at the artifact's path, so the trashed copy
+    /// was left in the trash.
+    AlreadyExists,
+
+    /// The artifact was not found in the trash (it was emptied, or restored
+    /// by hand).
+    NotInTrash,
+
+    /// The artifact was permanently deleted and can only be rebuilt.
+    Deleted,
+
+    /// Restoring failed.
+    Failed(String),
+}
+
+impl Manifest {
+    /// The manifest of a cleanup of `root` with outcome `result`. `root` is
+    /// `None` for global cache cleanups.
+    #[must_use]
+    pub fn from_cleanup(
+        result: &CleanResult,
+        strategy: RemovalStrategy,
+        root: Option<&Path>,
+    ) -> Self {
+        let created_at = Utc::now();
+        Self {
+            run_id: created_at
+                .with_timezone(&Local)
+                .format("%Y%m%d-%H%M%S")
+                .to_string(),
+            created_at,
+            root: root.map(absolute),
+            trashed: strategy == RemovalStrategy::Trash,
+            cleaned_count: result.success_count + result.partial.len(),
+            bytes_freed: result.total_freed,
+            cancelled_count: result.cancelled_count,
+            errors: result.errors.clone(),
+            entries: result
+                .removed
+                .iter()
+                .map(|removed| ManifestEntry {
+                    project_path: absolute(&removed.project_path),
+                    artifact_path: absolute(&removed.artifact_path),
+                    size: removed.size,
+                    deleted_at: removed.removed_at,
+                    trash_location: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Total size of the recorded artifacts, in bytes.
+    #[must_use]
+    pub fn total_size(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.size).sum()
+    }
+
+    /// The recorded artifacts grouped by project, in order of first removal.
+    #[must_use]
+    pub fn by_project(&self) -> Vec<(&Path, Vec<&ManifestEntry>)> {
+        let mut projects: Vec<(&Path, Vec<&ManifestEntry>)> = Vec::new();
+        for entry in &self.entries {
+            match projects
+                .iter_mut()
+                .find(|(project, _)| *project == entry.project_path)
+            {
+                Some((_, entries)) => entries.push(entry),
+                None => projects.push((&entry.project_path, vec![entry])),
+            }
+        }
+        projects
+    }
+
+    /// Fill in the trash location of every entry, by looking up the most
+    /// recently trashed item with the same original path.
+    ///
+    /// Listing the trash is only supported on Linux and Windows; elsewhere
+    /// the locations are left empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the trash cannot be listed.
+    pub fn locate_in_trash(&mut self) -> Result<()> {
+        #[cfg(any(
+            target_os = "windows",
+            all(
+                unix,
+                not(target_os = "macos"),
+                not(target_os = "ios"),
+                not(target_os = "android")
+            )
+        ))]
+        {
+            let items = trash::os_limited::list().map_err(|source| Error::Trash {
+                action: "list",
+                source,
+            })?;
+            for entry in &mut self.entries {
+                entry.trash_location = items
+                    .iter()
+                    .filter(|item| item.original_path() == entry.artifact_path)
+                    .max_by_key(|item| item.time_deleted)
+                    .map(|item| PathBuf::from(&item.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the manifest to `dir` as `<run_id>.json`, creating `dir` if
+    /// needed. If a run with the same id exists, a suffix is appended to the
+    /// id. Returns the path written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or the file cannot be written.
+    pub fn save(&mut self, dir: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(dir).map_err(Error::file("create", dir))?;
+
+        let base_id = self.run_id.clone();
+        let mut path = dir.join(format!("{base_id}.json"));
+        for suffix in 2.. {
+            if !path.exists() {
+                break;
+            }
+            self.run_id = format!("{base_id}-{suffix}");
+            path = dir.join(format!("{}.json", self.run_id));
+        }
+
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json).map_err(Error::file("write", &path))?;
+        Ok(path)
+    }
+
+    /// Load the manifest of run `run_id` from `dir`. `latest` loads the most
+    /// recent run.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no such run or its manifest is invalid.
+    pub fn load(dir: &Path, run_id: &str) -> Result<Self> {
+        if run_id == "latest" {
+            return list_runs(dir)?
+                .pop()
+                .ok_or_else(|| Error::Other("No cleanup has been recorded yet".to_string()));
+        }
+
+        let path = dir.join(format!("{run_id}.json"));
+        let json = match fs::read_to_string(&path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(Error::Other(format!(
+                    "No recorded run with id {run_id:?} (list them with `clean-dev-dirs restore`)"
+                )));
+            }
+            Err(e) => return Err(Error::file("read", &path)(e)),
+        };
+        serde_json::from_str(&json).map_err(|source| Error::Invalid {
+            kind: "manifest",
+            path,
+            source,
+        })
+    }
+}
+
+/// Load every manifest in `dir`, oldest first. A missing directory holds no
+/// runs.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read or a manifest is invalid.
+pub fn list_runs(dir: &Path) -> Result<Vec<Manifest>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::file("read", dir)(e)),
+    };
+
+    let mut runs = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(Error::file("read", dir))?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let json = fs::read_to_string(&path).map_err(Error::file("read", &path))?;
+            let manifest: Manifest =
+                serde_json::from_str(&json).map_err(|source| Error::Invalid {
+                    kind: "manifest",
+                    path,
+                    source,
+                })?;
+            runs.push(manifest);
+        }
+    }
+    runs.sort_by(|a, b| (a.created_at, &a.run_id).cmp(&(b.created_at, &b.run_id)));
+    Ok(runs)
+}
+
+/// Total bytes freed by `runs` and the time of the oldest one, or `None`
+/// without runs.
+#[must_use]
+pub fn total_freed(runs: &[Manifest]) -> Option<(u64, DateTime<Utc>)> {
+    let since = runs.iter().map(|run| run.created_at).min()?;
+    Some((runs.iter().map(|run| run.bytes_freed).sum(), since))
+}
+
+/// Restore the entries of `manifest` from the trash, returning what was done
+/// with each of them, in order.
+///
+/// Entries of runs that deleted permanently are reported as
+/// [`RestoreOutcome::Deleted`]. Restoring from the trash is only supported
+/// on Linux and Windows; elsewhere trashed entries fail with a hint to
+/// restore them from the system trash by hand.
+#[must_use]
+pub fn restore(manifest: &Manifest) -> Vec<RestoreOutcome> {
+    if !manifest.trashed {
+        return vec![RestoreOutcome::Deleted; manifest.entries.len()];
+    }
+    restore_from_trash(&manifest.entries)
+}
+
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+fn restore_from_trash(entries: &[ManifestEntry]) -> Vec<RestoreOutcome> {
+    let items = match trash::os_limited::list() {
+        Ok(items) => items,
+        Err(e) => {
+            let error = format!("failed to list the trash: {e}");
+            return vec![RestoreOutcome::Failed(error); entries.len()];
+        }
+    };
+
+    entries
+        .iter()
+        .map(|entry| {
+            let Some(item) = entry.trash_location.as_ref().and_then(|location| {
+                items
+                    .iter()
+                    .find(|item| Path::new(&item.id) == location.as_path())
+            }) else {
+                return RestoreOutcome::NotInTrash;
+            };
+            if entry.artifact_path.exists() {
+                return RestoreOutcome::AlreadyExists;
+            }
+            match trash::os_limited::restore_all([item.clone()]) {
+                Ok(()) => RestoreOutcome::Restored,
+                Err(trash::Error::RestoreCollision { .. }) => RestoreOutcome::AlreadyExists,
+                Err(e) => RestoreOutcome::Failed(e.to_string()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+fn restore_from_trash(entries: &[ManifestEntry]) -> Vec<RestoreOutcome> {
+    let error = "restoring is not supported on this platform, use the system trash".to_string();
+    vec![RestoreOutcome::Failed(error); entries.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    use crate::cleaner::RemovedArtifact;
+
+    fn manifest(run_id: &str, trashed: bool) -> Manifest {
+        let result = CleanResult {
+            success_count: 1,
+            total_freed: 300,
+            freed_by_type: HashMap::new(),
+            estimated_size: 300,
+            errors: Vec::new(),
+            partial: Vec::new(),
+            cancelled_count: 0,
+            skipped: Vec::new(),
+            cleaned: Vec::new(),
+            failed: Vec::new(),
+            failed_caches: Vec::new(),
+            elapsed: Duration::ZERO,
+            retried: Vec::new(),
+            removed: vec![RemovedArtifact {
+                project_path: PathBuf::from("/work/app"),
+                artifact_path: PathBuf::from("/work/app/target"),
+                size: 300,
+                removed_at: Utc::now(),
+            }],
+        };
+        let strategy = RemovalStrategy::from_use_trash(trashed);
+        Manifest {
+            run_id: run_id.to_string(),
+            ..Manifest::from_cleanup(&result, strategy, Some(Path::new("/work")))
+        }
+    }
+
+    #[test]
+    fn test_from_cleanup_records_removed_artifacts() {
+        let manifest = manifest("run", true);
+
+        assert!(manifest.trashed);
+        assert_eq!(manifest.root, Some(PathBuf::from("/work")));
+        assert_eq!(manifest.cleaned_count, 1);
+        assert_eq!(manifest.bytes_freed, 300);
+        assert_eq!(manifest.cancelled_count, 0);
+        assert_eq!(manifest.total_size(), 300);
+        assert_eq!(
+            manifest.entries[0].artifact_path,
+            PathBuf::from("/work/app/target")
+        );
+        assert_eq!(manifest.entries[0].trash_location, None);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("history");
+        let mut saved = manifest("20260105-093000", false);
+
+        let path = saved.save(&dir).unwrap();
+
+        assert_eq!(path, dir.join("20260105-093000.json"));
+        assert_eq!(Manifest::load(&dir, "20260105-093000").unwrap(), saved);
+        assert_eq!(Manifest::load(&dir, "latest").unwrap(), saved);
+        assert!(Manifest::load(&dir, "20200101-000000").is_err());
+    }
+
+    #[test]
+    fn test_save_does_not_overwrite_a_run_with_the_same_id() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut first = manifest("20260105-093000", false);
+        let mut second = first.clone();
+
+        first.save(temp_dir.path()).unwrap();
+        second.save(temp_dir.path()).unwrap();
+
+        assert_eq!(second.run_id, "20260105-093000-2");
+        assert_eq!(list_runs(temp_dir.path()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_list_runs_of_missing_directory_is_empty() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(
+            list_runs(&temp_dir.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(Manifest::load(&temp_dir.path().join("missing"), "latest").is_err());
+    }
+
+    #[test]
+    fn test_total_freed_sums_all_runs() {
+        assert_eq!(total_freed(&[]), None);
+
+        let first = manifest("first", true);
+        let second = Manifest {
+            created_at: first.created_at + chrono::Duration::days(30),
+            ..manifest("second", false)
+        };
+        assert_eq!(
+            total_freed(&[second, first.clone()]),
+            Some((600, first.created_at))
+        );
+    }
+
+    #[test]
+    fn test_by_project_groups_entries() {
+        let mut manifest = manifest("run", true);
+        let mut other = manifest.entries[0].clone();
+        other.project_path = PathBuf::from("/work/web");
+        let mut cache = manifest.entries[0].clone();
+        cache.artifact_path = PathBuf::from("/work/app/.cache");
+        manifest.entries.extend([other, cache]);
+
+        let projects = manifest.by_project();
+
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].0, Path::new("/work/app"));
+        assert_eq!(projects[0].1.len(), 2);
+        assert_eq!(projects[1].0, Path::new("/work/web"));
+    }
+
+    #[test]
+    fn test_manifest_without_run_summary_still_loads() {
+        let json = r#"{
+            "run_id": "20260105-093000",
+            "created_at": "2026-01-05T09:30:00Z",
+            "trashed": true,
+            "entries": []
+        }"#;
+
+        let manifest: Manifest = serde_json::from_str(json).unwrap();
+
+        assert_eq!(manifest.root, None);
+        assert_eq!(manifest.bytes_freed, 0);
+    }
+
+    #[test]
+    fn test_restore_of_permanent_deletion_is_a_replay() {
+        let manifest = manifest("run", false);
+
+        assert_eq!(restore(&manifest), vec![RestoreOutcome::Deleted]);
+    }
+}
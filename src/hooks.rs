@@ -0,0 +1,152 @@
+//! Commands run before and after cleaning each project.
+//!
+//! The `[hooks]` section of the configuration file sets a `pre_project`
+//! command, run before a project is cleaned, and a `post_project` command,
+//! run once it was, e.g. to stop the dev server or docker container holding
+//! files open in `node_modules` and start it again:
+//!
+//! ```toml
+//! [hooks]
+//! pre_project = "docker compose stop"
+//! post_project = "docker compose start"
+//! ```
+//!
+//! Commands run through the shell (`sh -c`, or `cmd /C` on Windows) in the
+//! project root, with `PROJECT_PATH`, `PROJECT_TYPE` (as in the JSON output)
+//! and `ARTIFACT_PATH` (the artifacts, separated like `PATH` entries when
+//! there are several) set. Their output goes to stderr, so that it never
+//! mixes with `--json` output. A project whose `pre_project` command fails
+//! is skipped; `post_project` runs whenever `pre_project` succeeded, even if
+//! cleaning failed, so that whatever was stopped is started again.
+
+use std::env;
+use std::io;
+use std::process::{Command, Stdio};
+
+use crate::error::{Error, Result};
+use crate::project::Project;
+
+/// The commands run around the cleaning of each project.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProjectHooks {
+    /// Command run before cleaning a project; the project is skipped if it
+    /// fails.
+    pub pre_project: Option<String>,
+
+    /// Command run after cleaning a project.
+    pub post_project: Option<String>,
+}
+
+impl ProjectHooks {
+    /// Run the `pre_project` command for `project`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be started or fails, in which
+    /// case the project must not be cleaned.
+    pub fn run_pre(&self, project: &Project) -> Result<()> {
+        run("pre_project", self.pre_project.as_deref(), project)
+    }
+
+    /// Run the `post_project` command for `project`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be started or fails.
+    pub fn run_post(&self, project: &Project) -> Result<()> {
+        run("post_project", self.post_project.as_deref(), project)
+    }
+}
+
+/// Run the hook `name`, set to `command`, for `project`.
+fn run(name: &'static str, command: Option<&str>, project: &Project) -> Result<()> {
+    let Some(command) = command else {
+        return Ok(());
+    };
+
+    let kind = serde_json::to_value(&project.kind)?;
+    let artifacts = env::join_paths(project.artifact_paths())
+        .map_err(|e| Error::Other(format!("{name} hook: invalid artifact path: {e}")))?;
+    let status = shell(command)
+        .current_dir(&project.root_path)
+        .env("PROJECT_PATH", &project.root_path)
+        .env("PROJECT_TYPE", kind.as_str().unwrap_or_default())
+        .env("ARTIFACT_PATH", artifacts)
+        .stdin(Stdio::null())
+        .stdout(io::stderr())
+        .status()
+        .map_err(|source| Error::Spawn {
+            command: command.to_string(),
+            source,
+        })?;
+    if !status.success() {
+        return Err(Error::Hook { hook: name, status });
+    }
+    Ok(())
+}
+
+/// A command running `command` through the shell.
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::project::{BuildArtifacts, ProjectType};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn project(root: &std::path::Path) -> Project {
+        Project::new(
+            ProjectType::Node,
+            root.to_path_buf(),
+            vec![BuildArtifacts {
+                path: root.join("node_modules"),
+                size: 0,
+            }],
+            Some("app".to_string()),
+        )
+    }
+
+    #[test]
+    fn test_hooks_see_the_project() {
+        let temp = TempDir::new().unwrap();
+        let hooks = ProjectHooks {
+            pre_project: Some(
+                r#"printf '%s %s %s' "$PROJECT_TYPE" "$PROJECT_PATH" "$ARTIFACT_PATH" > seen"#
+                    .to_string(),
+            ),
+            post_project: None,
+        };
+
+        hooks.run_pre(&project(temp.path())).unwrap();
+        hooks.run_post(&project(temp.path())).unwrap();
+        let root = temp.path().display();
+        assert_eq!(
+            fs::read_to_string(temp.path().join("seen")).unwrap(),
+            format!("node {root} {root}/node_modules")
+        );
+    }
+
+    #[test]
+    fn test_failing_hook_is_an_error() {
+        let temp = TempDir::new().unwrap();
+        let hooks = ProjectHooks {
+            pre_project: Some("exit 3".to_string()),
+            post_project: Some("true".to_string()),
+        };
+
+        let error = hooks.run_pre(&project(temp.path())).unwrap_err();
+        assert!(error.to_string().contains("pre_project"), "{error}");
+        assert!(hooks.run_post(&project(temp.path())).is_ok());
+    }
+}
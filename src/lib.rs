@@ -6,23 +6,52 @@
 //! This library provides the core functionality for the clean-dev-dirs CLI tool,
 //! allowing for scanning, filtering, and cleaning development project build artifacts.
 
+pub mod activity;
+pub mod analytics;
+pub mod audit;
+pub mod cache;
+pub mod cancellation;
 pub mod cleaner;
 pub mod config;
+pub mod confirm;
+pub mod discover;
+pub mod error;
 pub mod executables;
 pub mod filtering;
+pub mod history;
+pub mod hooks;
+pub mod logging;
+pub mod node_usage;
 pub mod output;
+#[cfg(feature = "cli")]
+pub mod picker;
+pub mod plan;
 pub mod project;
+pub mod report;
+pub mod scan_index;
 pub mod scanner;
+pub mod schedule;
+pub mod stats;
+pub mod sweep;
+pub mod target_usage;
+#[cfg(feature = "cli")]
+pub mod tui;
 pub mod utils;
+pub mod vfs;
 
 // Re-export commonly used types for convenience
-pub use cleaner::{CleanResult, Cleaner};
+pub use cache::{CacheKind, CacheLocation};
+pub use cancellation::CancellationToken;
+pub use cleaner::{
+    CleanEvent, CleanOptions, CleanResult, Cleaner, NativeClean, RemovalStrategy, RemovedArtifact,
+};
 pub use config::{
     ExecutionOptions, FileConfig, FilterOptions, ProjectFilter, ScanOptions, SortCriteria,
     SortOptions,
 };
-pub use filtering::filter_projects;
+pub use error::Error;
+pub use filtering::{SkipReason, classify_projects, filter_projects};
 pub use output::JsonOutput;
 pub use project::{BuildArtifacts, Project, ProjectType, Projects};
-pub use scanner::Scanner;
+pub use scanner::{ScanEvent, ScanTruncation, Scanner, ScannerBuilder, SkipStats};
 pub use utils::parse_size;
@@ -0,0 +1,140 @@
+//! Diagnostics on stderr and in the `--log-file`.
+//!
+//! Warnings, errors and scan diagnostics are reported as `tracing` events.
+//! On stderr, only warnings and errors are shown by default; `-v` adds the
+//! access errors met while scanning, `-vv` debugging details and `-vvv`
+//! everything.
+//!
+//! The log file, if any, gets every event from the debug level up, each with
+//! a timestamp. This includes the audit records logged with the [`RECORD`]
+//! target (every deletion, skip decision and cleanup failure), which are kept
+//! off stderr since the terminal output already reports them.
+//!
+//! The library only emits these events; installing the subscriber that
+//! writes them out ([`init`]) is left to the command line, behind the `cli`
+//! feature, so that applications embedding the library keep their own.
+
+#[cfg(feature = "cli")]
+use std::fs::OpenOptions;
+#[cfg(feature = "cli")]
+use std::io;
+#[cfg(feature = "cli")]
+use std::path::Path;
+#[cfg(feature = "cli")]
+use std::sync::Mutex;
+
+#[cfg(feature = "cli")]
+use colored::Colorize;
+use tracing::level_filters::LevelFilter;
+#[cfg(feature = "cli")]
+use tracing::{Event, Level, Subscriber};
+#[cfg(feature = "cli")]
+use tracing_subscriber::{
+    Layer,
+    filter::filter_fn,
+    fmt::format::Writer,
+    fmt::{self, FmtContext, FormatEvent, FormatFields},
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+};
+
+#[cfg(feature = "cli")]
+use crate::error::{Error, Result};
+
+/// Target of the audit records, which only go to the log file.
+pub const RECORD: &str = "record";
+
+/// The most detailed level shown on stderr for a `-v` count.
+#[must_use]
+pub const fn stderr_level(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Install the global subscriber: stderr at the level chosen by
+/// `verbosity`, and the log file at `log_file`, appended to, if given.
+///
+/// # Errors
+///
+/// Returns an error if the log file cannot be opened or a subscriber is
+/// already installed.
+#[cfg(feature = "cli")]
+pub fn init(verbosity: u8, log_file: Option<&Path>) -> Result<()> {
+    let level = stderr_level(verbosity);
+    let stderr = fmt::layer()
+        .with_writer(io::stderr)
+        .event_format(StderrFormat)
+        .with_filter(filter_fn(move |metadata| {
+            metadata.target() != RECORD && *metadata.level() <= level
+        }));
+
+    let file = log_file
+        .map(|path| {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(Error::file("open log file", path))?;
+            Ok::<_, Error>(
+                fmt::layer()
+                    .with_writer(Mutex::new(file))
+                    .with_target(false)
+                    .with_filter(level.max(LevelFilter::DEBUG)),
+            )
+        })
+        .transpose()?;
+
+    tracing_subscriber::registry()
+        .with(stderr)
+        .with(file)
+        .try_init()
+        .map_err(Error::Logging)
+}
+
+/// Formats events on stderr like the rest of the terminal output: the
+/// message alone, prefixed for warnings and errors.
+#[cfg(feature = "cli")]
+struct StderrFormat;
+
+#[cfg(feature = "cli")]
+impl<S, N> FormatEvent<S, N> for StderrFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let mut message = String::new();
+        ctx.format_fields(Writer::new(&mut message), event)?;
+
+        match *event.metadata().level() {
+            Level::ERROR => writeln!(writer, "{} {message}", "Error:".red()),
+            Level::WARN => writeln!(writer, "{} {message}", "Warning:".yellow()),
+            Level::INFO => writeln!(writer, "{message}"),
+            _ => writeln!(writer, "{}", message.dimmed()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stderr_level() {
+        assert_eq!(stderr_level(0), LevelFilter::WARN);
+        assert_eq!(stderr_level(1), LevelFilter::INFO);
+        assert_eq!(stderr_level(2), LevelFilter::DEBUG);
+        assert_eq!(stderr_level(3), LevelFilter::TRACE);
+        assert_eq!(stderr_level(9), LevelFilter::TRACE);
+    }
+}
@@ -9,7 +9,7 @@
 //!
 //! ## Features
 //!
-//! - Multi-language support (Rust, Node.js, Python, Go, Java/Kotlin, C/C++, Swift, .NET/C#)
+//! - Multi-language support (Rust, Node.js, Python, Go, Java/Kotlin, C/C++, Swift, .NET/C#, OCaml, Unity, Unreal)
 //! - Parallel directory scanning
 //! - Size and time-based filtering
 //! - Interactive project selection
@@ -31,33 +31,102 @@
 //! ```
 
 mod cli;
+mod exit_status;
 
 use anyhow::{Ok, Result, bail};
-use clap::Parser;
+use clap_complete::Shell;
 use clean_dev_dirs::{
-    cleaner::{Cleaner, RemovalStrategy},
-    config::FileConfig,
-    filtering::{filter_projects, sort_projects},
-    output::JsonOutput,
-    project::Projects,
-    scanner::Scanner,
+    CancellationToken,
+    activity::{self, ActivityLog},
+    analytics::Breakdown,
+    audit::AuditLog,
+    cache::{self, CacheKind, CacheLocation},
+    cleaner::{CleanEvent, CleanOptions, CleanResult, Cleaner, RemovalStrategy},
+    config::{
+        FileConfig, ProjectFilter, ScanOptions,
+        file::{DEFAULT_CONFIG, LocalConfigs, LocalFilterConfig, expand_tilde},
+        validate::validate,
+    },
+    confirm::Answer,
+    discover,
+    filtering::{
+        FilterOutcome, SkipReason, SkippedProject, classify_projects, is_recent, sort_projects,
+    },
+    history::{self, Manifest, RestoreOutcome},
+    hooks::ProjectHooks,
+    logging::{self, RECORD},
+    node_usage,
+    output::{JsonCacheOutput, JsonOutput, JsonProjectEntry},
+    plan::{Mismatch, Plan},
+    project::{ArtifactCategory, Project, Projects, rebuild_command},
+    report::{self, ReportMetadata, ReportSigner},
+    scan_index::{self, ScanIndex},
+    scanner::{ScanEvent, Scanner},
+    schedule::{Frequency, Scheduler},
+    stats::{self, RunStats, TYPE_COLUMNS},
+    sweep::{self, StaleCriteria},
+    target_usage::{self, CrateUsage, UnitDir},
+    tui::{self, StreamEvent, TuiSelection},
+    utils::{
+        compression, defender,
+        disk::{self, SpaceCheck},
+        format_size,
+        journal::{self, Priority},
+        pager, parse_size, paths, processes, progress,
+        size::{self, SizeFormat},
+        terminal,
+    },
+    vfs::FsHandle,
 };
-use cli::Cli;
+use cli::{Cli, Command, ConfigAction, HistoryAction, HookAction, ScheduleAction, Setting};
 use colored::Colorize;
-use humansize::{DECIMAL, format_size};
-use inquire::Confirm;
-use std::process::exit;
+use exit_status::ExitStatus;
+use indicatif::{ProgressBar, ProgressStyle};
+use inquire::{Confirm, InquireError, MultiSelect, Text};
+use rayon::{iter::Either, prelude::*};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{ExitCode, exit};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, mpsc};
+use std::thread;
+use std::time::SystemTime;
+
+/// Signer for `--json` reports, set once at startup when `--sign-report` is
+/// active.
+static REPORT_SIGNER: OnceLock<ReportSigner> = OnceLock::new();
+
+/// Whether directories are being removed, for the message shown on Ctrl+C.
+static CLEANING: AtomicBool = AtomicBool::new(false);
 
 /// Entry point for the clean-dev-dirs application.
 ///
-/// This function handles all errors gracefully by calling [`inner_main`] and printing
-/// any errors to stderr before exiting with a non-zero status code.
-fn main() {
-    if let Err(err) = inner_main() {
-        eprintln!("Error: {err}");
+/// This function handles all errors gracefully by calling [`inner_main`] and logging
+/// any errors before exiting with [`ExitStatus::Failure`]. A prompt cancelled
+/// with Esc or Ctrl+C exits with [`ExitStatus::Aborted`].
+fn main() -> ExitCode {
+    let err = match inner_main() {
+        Result::Ok(status) => return status.into(),
+        Err(err) => err,
+    };
+    let prompt_error = err.downcast_ref().or_else(|| match err.downcast_ref() {
+        Some(clean_dev_dirs::Error::Prompt(e)) => Some(e),
+        _ => None,
+    });
+    if let Some(InquireError::OperationCanceled | InquireError::OperationInterrupted) = prompt_error
+    {
+        return ExitStatus::Aborted.into();
+    }
 
-        exit(1);
+    if tracing::dispatcher::has_been_set() {
+        tracing::error!("{err}");
+    } else {
+        eprintln!("Error: {err}");
     }
+    ExitStatus::Failure.into()
 }
 
 /// Main application logic that can return errors.
@@ -70,156 +139,2744 @@ fn main() {
 /// Returns errors from thread-pool configuration, directory scanning,
 /// project filtering, interactive selection, file-system operations, or
 /// JSON serialization.
-fn inner_main() -> Result<()> {
-    let args = Cli::parse();
+fn inner_main() -> Result<ExitStatus> {
+    let args = Cli::parse_with_env();
+    // Failures to load the configuration are reported once logging is set up
+    let verbosity = args.verbosity(&FileConfig::load().unwrap_or_default());
+    logging::init(verbosity, args.log_file())?;
+
+    if let Some(path) = args.verify_report() {
+        verify_report(path)?;
+        return Ok(ExitStatus::Success);
+    }
+    match args.command() {
+        Some(command) if !command.is_cleanup() => run_command(&args, command),
+        _ => run_clean(&args),
+    }
+}
+
+/// Print the summary of the projects found, grouped by root directory if
+/// `roots` are given.
+fn print_found_projects(projects: &Projects, roots: Option<&[PathBuf]>) {
+    println!("\n{}", "📊 Found projects:".bold());
+    projects.print_summary(projects.get_total_size());
+    if let Some(roots) = roots {
+        projects.print_root_summary(roots);
+    }
+}
+
+/// Scan for projects and clean them, or show what would be cleaned: the
+/// `clean` (default), `scan` and `list` subcommands.
+fn run_clean(args: &Cli) -> Result<ExitStatus> {
     let json_mode = args.json();
     let file_config = load_config(json_mode);
+    configure_output(args);
+
+    init_report_signer(args, &file_config)?;
 
-    let dir = args.directory(&file_config);
+    let dirs = args.directories(&file_config);
     let project_filter = args.project_filter(&file_config);
-    let execution_options = args.execution_options(&file_config);
+    let mut execution_options = args.execution_options(&file_config);
     let scan_options = args.scan_options(&file_config);
-    let filter_options = args.filter_options(&file_config);
+    let stale_only = args.stale_criteria(&file_config);
 
     if json_mode && execution_options.interactive {
         bail!("--json and --interactive cannot be used together");
     }
 
-    if scan_options.threads > 0 {
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(scan_options.threads)
-            .build_global()?;
+    configure_threads(scan_options.threads)?;
+
+    let cancellation = install_cancel_handler()?;
+
+    if args.cargo_cache() || args.node_cache() || args.go_cache() {
+        return run_cache_mode(args, &execution_options, cancellation);
     }
 
-    let scanner = Scanner::new(scan_options, project_filter).with_quiet(json_mode);
-    let projects = scanner.scan_directory(&dir);
+    // `list` only prints the projects, one per line
+    let quiet = json_mode || args.ndjson() || args.lists_projects();
+    let verbose = scan_options.verbose;
+    let scanner = clean_scanner(args, &file_config, scan_options, project_filter)?
+        .with_cancellation(cancellation.clone());
+    let scanner = with_scan_spinner(scanner, quiet);
+    if args.ndjson() {
+        return stream_projects(scanner, args, &file_config, &dirs, &cancellation);
+    }
+    if execution_options.tui && streams_into_tui(args, &file_config, stale_only.is_some()) {
+        return clean_streamed(
+            scanner,
+            args,
+            &file_config,
+            &dirs,
+            execution_options,
+            cancellation,
+        );
+    }
+    let (projects, recent) = scan(&scanner, args, &file_config, &dirs, stale_only.is_some())?;
 
-    if !json_mode {
-        println!("Found {} projects", projects.len());
+    // An interrupted scan only reports what it found; nothing is cleaned.
+    let scan_cancelled = cancellation.is_cancelled();
+
+    if !quiet {
+        print_scan_outcome(&scanner, scan_cancelled, projects.len() + recent.len());
     }
 
-    if projects.is_empty() {
-        return print_empty_result(json_mode, "✨ No development directories found!");
+    if projects.is_empty() && recent.is_empty() {
+        return print_empty_result(args, "✨ No development directories found!", &[]);
     }
 
     let sort_opts = args.sort_options(&file_config);
-    let mut filtered_projects = filter_projects(projects, &filter_options)?;
+    let mut outcome = classify(projects, args, &file_config, &dirs, stale_only.as_ref())?;
+    outcome.skipped.extend(recent);
+    let skipped = outcome.skipped;
+    log_skipped(&skipped);
+    let mut filtered_projects = outcome.kept;
     sort_projects(&mut filtered_projects, &sort_opts);
 
+    if !quiet {
+        let print = if verbose { print_skipped } else { warn_skips };
+        print(&skipped);
+    }
+
     if filtered_projects.is_empty() {
-        return print_empty_result(json_mode, "✨ No directories match the specified criteria!");
+        return print_empty_result(
+            args,
+            "✨ No directories match the specified criteria!",
+            &skipped,
+        );
+    }
+
+    if args.lists_projects() && !json_mode {
+        print_project_list(&filtered_projects);
+        return Ok(ExitStatus::Success);
     }
 
-    let total_size: u64 = filtered_projects.iter().map(|p| p.build_arts.size).sum();
     let projects: Projects = filtered_projects.into();
 
     if !json_mode {
-        println!("\n{}", "📊 Found projects:".bold());
-        projects.print_summary(total_size);
+        print_found_projects(&projects, args.group_by_root().then_some(&*dirs));
     }
 
-    let Some(keep_executables) = resolve_keep_executables(&projects, &execution_options)? else {
-        return Ok(());
+    let compressed = args
+        .estimate_compressed()
+        .then(|| estimate_compressed_sizes(&projects, json_mode));
+
+    let Some((projects, keep_executables)) = resolve_selection(projects, &execution_options)?
+    else {
+        return Ok(ExitStatus::Aborted);
     };
 
-    if execution_options.dry_run {
-        return print_dry_run(&projects, json_mode);
+    if execution_options.dry_run || scan_cancelled {
+        print_dry_run(&projects, &skipped, compressed.as_deref(), args)?;
+        return Ok(if scan_cancelled {
+            ExitStatus::Aborted
+        } else {
+            ExitStatus::Success
+        });
+    }
+
+    let (projects, compressed) = if json_mode {
+        (projects, compressed)
+    } else {
+        let Some(selected) = confirm_cleanup(&projects, &execution_options, args.sizing())? else {
+            return Ok(ExitStatus::Aborted);
+        };
+        keep_selected(&projects, compressed.as_deref(), &selected)
+    };
+    if !confirm_trash_space(&projects, &mut execution_options, args.sizing(), json_mode)? {
+        return Ok(ExitStatus::Aborted);
     }
 
+    let clean_options = CleanOptions {
+        keep_executables,
+        stale_only,
+        ..clean_options(args, execution_options, cancellation)?
+    };
+
     run_cleanup(
         projects,
-        keep_executables,
-        json_mode,
-        execution_options.use_trash,
+        &skipped,
+        compressed.as_deref(),
+        &clean_options,
+        &dirs,
+        Reporting::new(args, verbose),
     )
 }
 
-// ── Helper functions ────────────────────────────────────────────────────
+/// Print each project found under `dirs` as a line of JSON as soon as it is
+/// sized, for `--output ndjson`. Nothing is cleaned.
+///
+/// The scan stops once stdout is closed, e.g. by `head`.
+fn stream_projects(
+    scanner: Scanner,
+    args: &Cli,
+    file_config: &FileConfig,
+    dirs: &[PathBuf],
+    cancellation: &CancellationToken,
+) -> Result<ExitStatus> {
+    let stop = cancellation.clone();
+    let scanner = scanner.with_observer(move |event| {
+        let ScanEvent::SizeComputed(project) = event else {
+            return;
+        };
+        let line = serde_json::to_string(&JsonProjectEntry::from_project(project))
+            .expect("projects serialize to JSON");
+        let mut stdout = std::io::stdout().lock();
+        if writeln!(stdout, "{line}")
+            .and_then(|()| stdout.flush())
+            .is_err()
+        {
+            stop.cancel();
+        }
+    });
+    let (projects, recent) = scan(&scanner, args, file_config, dirs, false)?;
+    Ok(if projects.is_empty() && recent.is_empty() {
+        ExitStatus::NothingFound
+    } else {
+        ExitStatus::Success
+    })
+}
 
-/// Load the configuration file, falling back to defaults on failure.
-fn load_config(json_mode: bool) -> FileConfig {
-    match FileConfig::load() {
-        std::result::Result::Ok(config) => config,
-        Err(e) => {
-            if !json_mode {
-                eprintln!("{} {e}", "Warning: Failed to load config file:".yellow());
-            }
-            FileConfig::default()
+/// Clean the projects of the plan at `path` that did not change since it
+/// was written: the `apply` subcommand.
+fn run_apply(args: &Cli, path: &Path) -> Result<ExitStatus> {
+    let json_mode = args.json();
+    let file_config = load_config(json_mode);
+    configure_output(args);
+
+    let plan = Plan::load(&expand_tilde(path))?;
+    let mut execution_options = args.execution_options(&file_config);
+    let scan_options = args.scan_options(&file_config);
+    let verbose = scan_options.verbose;
+    configure_threads(scan_options.threads)?;
+    let cancellation = install_cancel_handler()?;
+
+    let scanner = Scanner::new(scan_options, ProjectFilter::All);
+    let (projects, changed) = verify_plan(&plan, &scanner);
+    if !json_mode {
+        for (project, mismatch) in &changed {
+            tracing::warn!("Changed since the plan, skipped {project}: {mismatch}");
         }
     }
-}
+    let skipped: Vec<_> = changed
+        .into_iter()
+        .map(|(project, _)| SkippedProject {
+            project,
+            reason: SkipReason::ChangedSincePlan,
+        })
+        .collect();
+    log_skipped(&skipped);
 
-/// Emit an empty-projects result in JSON or human-readable form.
-fn print_empty_result(json_mode: bool, message: &str) -> Result<()> {
-    if json_mode {
-        let output = JsonOutput::from_projects_dry_run(&[]);
-        println!("{}", serde_json::to_string_pretty(&output)?);
+    if projects.is_empty() {
+        return print_empty_result(args, "✨ Nothing left to clean in the plan!", &skipped);
+    }
+    let projects: Projects = projects.into();
+    if !json_mode {
+        print_found_projects(&projects, None);
+    }
+    if execution_options.dry_run {
+        print_dry_run(&projects, &skipped, None, args)?;
+        return Ok(ExitStatus::Success);
+    }
+
+    let projects = if json_mode {
+        projects
     } else {
-        println!("{}", message.green());
+        let Some(selected) = confirm_cleanup(&projects, &execution_options, args.sizing())? else {
+            return Ok(ExitStatus::Aborted);
+        };
+        keep_selected(&projects, None, &selected).0
+    };
+    if !confirm_trash_space(&projects, &mut execution_options, args.sizing(), json_mode)? {
+        return Ok(ExitStatus::Aborted);
     }
-    Ok(())
+
+    let clean_options = clean_options(args, execution_options, cancellation)?;
+    let roots: Vec<PathBuf> = plan.common_root().into_iter().collect();
+    run_cleanup(
+        projects,
+        &skipped,
+        None,
+        &clean_options,
+        &roots,
+        Reporting::new(args, verbose),
+    )
+}
+
+/// Whether the TUI can list projects while the scan goes on: not when the
+/// projects to show depend on all the others, with `--top`, `--free` or
+/// `--stale-only`, nor with `--estimate-compressed`, which needs them all.
+fn streams_into_tui(args: &Cli, file_config: &FileConfig, stale_only: bool) -> bool {
+    let filter_options = args.filter_options(file_config);
+    filter_options.top.is_none()
+        && filter_options.free.is_none()
+        && !stale_only
+        && !args.estimate_compressed()
+}
+
+/// Let the user pick projects in the TUI while the scan goes on, then clean
+/// them, or only show them with `--dry-run`.
+fn clean_streamed(
+    scanner: Scanner,
+    args: &Cli,
+    file_config: &FileConfig,
+    dirs: &[PathBuf],
+    mut execution_options: clean_dev_dirs::ExecutionOptions,
+    cancellation: CancellationToken,
+) -> Result<ExitStatus> {
+    let (selection, skipped) = stream_into_tui(
+        scanner,
+        args,
+        file_config,
+        dirs,
+        execution_options.keep_executables,
+    )?;
+    log_skipped(&skipped);
+    let Some(selection) = selection else {
+        println!("{}", "✨ No projects selected for cleaning!".green());
+        return Ok(ExitStatus::Aborted);
+    };
+    let projects: Projects = selection.projects.into();
+
+    if execution_options.dry_run {
+        print_dry_run(&projects, &skipped, None, args)?;
+        return Ok(ExitStatus::Success);
+    }
+
+    let Some(selected) = confirm_cleanup(&projects, &execution_options, args.sizing())? else {
+        return Ok(ExitStatus::Aborted);
+    };
+    let (projects, _) = keep_selected(&projects, None, &selected);
+    if !confirm_trash_space(&projects, &mut execution_options, args.sizing(), false)? {
+        return Ok(ExitStatus::Aborted);
+    }
+
+    let verbose = args.scan_options(file_config).verbose;
+    let clean_options = CleanOptions {
+        keep_executables: selection.keep_executables,
+        ..clean_options(args, execution_options, cancellation)?
+    };
+    run_cleanup(
+        projects,
+        &skipped,
+        None,
+        &clean_options,
+        dirs,
+        Reporting::new(args, verbose),
+    )
 }
 
-/// Handle interactive project selection and the keep-executables prompt.
+/// Scan `dirs` in the background while the TUI lists the projects as they
+/// are found, sized and filtered.
 ///
-/// Returns `Ok(Some(keep))` to continue with the resolved flag, or
-/// `Ok(None)` when the user selected zero projects (caller should exit).
-fn resolve_keep_executables(
-    projects: &Projects,
-    opts: &clean_dev_dirs::ExecutionOptions,
-) -> Result<Option<bool>> {
-    let mut keep = opts.keep_executables;
+/// Sized projects are filtered in batches, as running package managers are
+/// looked up for each batch. Once the user confirms or quits, the scan is
+/// stopped: projects still being sized are not cleaned. Returns the
+/// selection and the projects skipped by the filters so far.
+fn stream_into_tui(
+    scanner: Scanner,
+    args: &Cli,
+    file_config: &FileConfig,
+    dirs: &[PathBuf],
+    keep_executables: bool,
+) -> Result<(Option<TuiSelection>, Vec<SkippedProject>)> {
+    let stop = CancellationToken::new();
+    let (events, received) = mpsc::channel();
+    let (sized_sender, sized) = mpsc::channel();
+    let scanner = scanner
+        .with_cancellation(stop.clone())
+        .with_observer(move |event| {
+            if let ScanEvent::SizeComputed(project) = event {
+                let _ = sized_sender.send(project.clone());
+            }
+        });
 
-    if opts.interactive {
-        let selected = projects.interactive_selection()?;
-        if selected.is_empty() {
-            println!("{}", "✨ No projects selected for cleaning!".green());
-            return Ok(None);
+    thread::scope(|scope| {
+        let found = events.clone();
+        // Dropping the scanner once done ends the batches below
+        let scanning = scope.spawn(move || -> Result<Vec<SkippedProject>> {
+            let projects = find(&scanner, args, dirs)?;
+            for project in &projects {
+                let _ = found.send(StreamEvent::Found(project.clone()));
+            }
+            let (_, recent) = size_found(&scanner, args, file_config, dirs, projects, false)?;
+            for skipped in &recent {
+                let _ = found.send(StreamEvent::Dropped(skipped.project.clone()));
+            }
+            Ok(recent)
+        });
+        let filtering = scope.spawn(move || -> Result<Vec<SkippedProject>> {
+            let mut skipped = Vec::new();
+            while let std::result::Result::Ok(first) = sized.recv() {
+                let batch = std::iter::once(first).chain(sized.try_iter()).collect();
+                let outcome = classify(batch, args, file_config, dirs, None)?;
+                for project in outcome.kept {
+                    let _ = events.send(StreamEvent::Sized(project));
+                }
+                for project in &outcome.skipped {
+                    let _ = events.send(StreamEvent::Dropped(project.project.clone()));
+                }
+                skipped.extend(outcome.skipped);
+            }
+            Ok(skipped)
+        });
+
+        let selection = tui::select_streamed_projects(&received, keep_executables);
+        stop.cancel();
+        drop(received);
+        let mut skipped = scanning.join().expect("the scan does not panic")?;
+        skipped.extend(filtering.join().expect("filtering does not panic")?);
+        Ok((selection?, skipped))
+    })
+}
+
+/// Check each project of `plan` against the tree as it is now.
+///
+/// Returns the projects to clean, with their planned artifacts at their
+/// current sizes, and the planned projects that changed with the reason.
+fn verify_plan(plan: &Plan, scanner: &Scanner) -> (Vec<Project>, Vec<(Project, Mismatch)>) {
+    let roots: Vec<PathBuf> = plan.roots().map(Path::to_path_buf).collect();
+    let mut detected = scanner.find_listed_projects(&roots);
+    // Only the planned artifacts are sized
+    for project in &mut detected {
+        if let Some(planned) = plan
+            .projects
+            .iter()
+            .find(|planned| planned.root_path == project.root_path)
+        {
+            project.build_arts.retain(|artifact| {
+                planned
+                    .artifacts
+                    .iter()
+                    .any(|planned| planned.path == artifact.path)
+            });
         }
+    }
+    let unsized_projects = detected.clone();
+    let sized = scanner.size_projects(detected);
 
-        if !keep {
-            keep = Confirm::new("Keep compiled executables before cleaning?")
-                .with_default(false)
-                .prompt()?;
+    let mut verified = Vec::new();
+    let mut changed = Vec::new();
+    for planned in &plan.projects {
+        let current = sized
+            .iter()
+            .find(|project| project.root_path == planned.root_path)
+            .cloned()
+            .or_else(|| {
+                // Projects whose artifacts are all empty now are still there
+                unsized_projects
+                    .iter()
+                    .find(|project| project.root_path == planned.root_path)
+                    .map(|project| Project {
+                        build_arts: Vec::new(),
+                        ..project.clone()
+                    })
+            });
+        match planned.verify(current.as_ref()) {
+            Result::Ok(project) => verified.push(project),
+            Err(mismatch) => changed.push((planned.to_project(), mismatch)),
         }
     }
+    (verified, changed)
+}
+
+/// The scanner finding the projects to clean, from the command line and the
+/// configuration file.
+///
+/// With `--no-size`, sizes are shown as unknown from then on.
+fn clean_scanner(
+    args: &Cli,
+    file_config: &FileConfig,
+    scan_options: ScanOptions,
+    project_filter: ProjectFilter,
+) -> Result<Scanner> {
+    if !args.sizing() {
+        size::set_format(SizeFormat::Unknown);
+    }
+    let scanner = Scanner::new(scan_options, project_filter)
+        .with_path_filter(args.path_filter()?)
+        .with_artifacts(args.artifacts().to_vec())
+        .with_limits(args.scan_limits(file_config)?)
+        .with_ignore_files(args.ignore_files())
+        .with_one_file_system(args.one_file_system())
+        .with_network_fs(args.allow_network_fs())
+        .with_max_depth(args.max_depth())
+        .with_min_depth(args.min_depth())
+        .with_sizing(args.sizing())
+        .with_du_mode(args.du_mode());
+    Ok(match load_scan_index(args) {
+        Some(index) => scanner.with_index(index),
+        None => scanner,
+    })
+}
 
-    Ok(Some(keep))
+/// The options to clean projects with, from the command line and the
+/// configuration file.
+fn clean_options(
+    args: &Cli,
+    execution_options: clean_dev_dirs::ExecutionOptions,
+    cancellation: CancellationToken,
+) -> Result<CleanOptions> {
+    Ok(CleanOptions {
+        keep_executables: execution_options.keep_executables,
+        removal_strategy: RemovalStrategy::from_use_trash(execution_options.use_trash),
+        cancellation,
+        tool_cleaned_caches: Vec::new(),
+        native_clean: execution_options.native_clean.then(|| args.native_clean()),
+        stale_only: None,
+        retry: execution_options.retry,
+        guard: execution_options.guard,
+        fs: FsHandle::default(),
+        hooks: execution_options.hooks,
+        recheck_size: args.recheck_size(),
+        audit_log: open_audit_log(args)?,
+    })
 }
 
-/// Print dry-run results in JSON or human-readable format.
-fn print_dry_run(projects: &Projects, json_mode: bool) -> Result<()> {
-    if json_mode {
-        let output = JsonOutput::from_projects_dry_run(projects.as_slice());
-        println!("{}", serde_json::to_string_pretty(&output)?);
+/// The audit log given with `--audit-log`, open for appending.
+fn open_audit_log(args: &Cli) -> Result<Option<Arc<AuditLog>>> {
+    Ok(match args.audit_log() {
+        Some(path) => Some(Arc::new(AuditLog::open(path)?)),
+        None => None,
+    })
+}
+
+// ── Helper functions ────────────────────────────────────────────────────
+
+/// Print how many projects the scan found, warning when the results are
+/// partial because the scan was cancelled or stopped by a limit.
+fn print_scan_outcome(scanner: &Scanner, cancelled: bool, found: usize) {
+    if cancelled {
+        println!(
+            "{}",
+            "⏹️  Scan cancelled, showing partial results (nothing will be cleaned)".yellow()
+        );
+    } else if let Some(truncation) = scanner.truncation() {
+        println!(
+            "{}",
+            format!("⏳ {truncation}: results are partial").yellow()
+        );
+        println!(
+            "{}",
+            "   Projects outside the visited directories are missing; narrow the root \
+             (e.g. ~/Projects) or raise the limit"
+                .dimmed()
+        );
+    }
+    println!("Found {found} projects");
+}
+
+/// Draw a spinner while `scanner` searches for projects, unless `quiet`.
+fn with_scan_spinner(scanner: Scanner, quiet: bool) -> Scanner {
+    if quiet {
+        return scanner;
+    }
+    let spinner = Mutex::new(None);
+    scanner.with_observer(move |event| {
+        let mut spinner = spinner.lock().unwrap_or_else(PoisonError::into_inner);
+        match event {
+            ScanEvent::WalkStarted => {
+                let pb = progress::new(None);
+                pb.set_style(
+                    ProgressStyle::default_spinner()
+                        .template("{spinner:.green} {msg}")
+                        .expect("the spinner template is valid"),
+                );
+                pb.set_message("Scanning directories...");
+                *spinner = Some(pb);
+            }
+            ScanEvent::WalkFinished {
+                cancelled,
+                truncation,
+            } => {
+                let Some(pb) = spinner.take() else {
+                    return;
+                };
+                if cancelled {
+                    pb.finish_with_message("⏹️  Directory scan cancelled");
+                } else if truncation.is_some() {
+                    pb.finish_with_message("⏳ Directory scan stopped early");
+                } else {
+                    pb.finish_with_message("✅ Directory scan complete");
+                }
+            }
+            ScanEvent::ProjectFound(_) | ScanEvent::SizeComputed(_) => {}
+        }
+    })
+}
+
+/// Clean `projects`, drawing a progress bar unless `quiet` (`--json`).
+fn clean_with_progress(projects: Projects, options: &CleanOptions, quiet: bool) -> CleanResult {
+    let removal_strategy = options.removal_strategy;
+    let progress = if quiet {
+        ProgressBar::hidden()
     } else {
-        let size = projects.get_total_size();
+        let action = match removal_strategy {
+            RemovalStrategy::Permanent => "🧹 Starting cleanup...",
+            RemovalStrategy::Trash => "🗑️  Moving to trash...",
+        };
+        println!("\n{}", action.cyan());
+
+        let pb = progress::new(Some(projects.len() as u64));
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg}")
+                .expect("the progress bar template is valid")
+                .progress_chars("█▉▊▋▌▍▎▏  "),
+        );
+        pb
+    };
+
+    let action = match removal_strategy {
+        RemovalStrategy::Permanent => "Cleaned",
+        RemovalStrategy::Trash => "Trashed",
+    };
+
+    let result = Cleaner::clean_with(projects, options, |event| {
+        if let CleanEvent::ProjectCleaned { project, freed }
+        | CleanEvent::ProjectPartiallyCleaned { project, freed, .. } = event
+        {
+            progress.set_message(format!(
+                "{action} {} ({})",
+                paths::display_name(&project.root_path),
+                format_size(*freed)
+            ));
+        }
+
+        if !matches!(event, CleanEvent::Started { .. }) {
+            progress.inc(1);
+        }
+    });
+
+    let finish_msg = match removal_strategy {
+        _ if result.cancelled_count > 0 => "⏹️  Cleanup cancelled",
+        RemovalStrategy::Permanent => "✅ Cleanup complete",
+        RemovalStrategy::Trash => "✅ Moved to trash",
+    };
+    progress.finish_with_message(finish_msg);
+
+    result
+}
+
+/// Print the summary of a cleanup, counting items as `noun` (`"projects"`
+/// or `"caches"`).
+fn print_clean_summary(result: &CleanResult, noun: &str) {
+    if !result.errors.is_empty() {
+        println!("\n{}", "⚠️  Some errors occurred during cleanup:".yellow());
+        for error in &result.errors {
+            eprintln!("  {}", error.red());
+        }
+    }
+
+    if !result.skipped.is_empty() {
+        println!("\n{}", "⏭️  Skipped by the pre_project hook:".yellow());
+        for skipped in &result.skipped {
+            println!("  {skipped}");
+        }
+    }
+
+    if !result.partial.is_empty() {
         println!(
-            "\n{} {}",
-            "🧪 Dry run complete!".yellow(),
-            format!("Would free up {}", format_size(size, DECIMAL)).bright_white()
+            "\n{}",
+            "⚠️  Some files could not be removed and were left in place:".yellow()
+        );
+        for partial in &result.partial {
+            println!(
+                "  {} partially cleaned (freed {} of {})",
+                partial.root_path.display(),
+                format_size(partial.freed),
+                format_size(partial.size)
+            );
+        }
+    }
+
+    if result.cancelled_count > 0 {
+        println!(
+            "\n{}",
+            format!(
+                "⏹️  Cancelled: stopped after the {noun} being cleaned, the others were left \
+                 untouched"
+            )
+            .yellow()
+        );
+    }
+
+    println!("\n{}", "📊 Cleanup Summary:".bold());
+    println!(
+        "  ✅ Successfully cleaned: {} {noun}",
+        result.success_count.to_string().green()
+    );
+
+    if !result.partial.is_empty() {
+        println!(
+            "  ⚠️  Partially cleaned: {} {noun}",
+            result.partial.len().to_string().yellow()
+        );
+    }
+
+    if !result.errors.is_empty() {
+        println!(
+            "  ❌ Failed to clean: {} {noun}",
+            result.errors.len().to_string().red()
+        );
+    }
+
+    if result.cancelled_count > 0 {
+        println!(
+            "  ⏹️  Not cleaned (cancelled): {} {noun}",
+            result.cancelled_count.to_string().yellow()
+        );
+    }
+
+    if !result.skipped.is_empty() {
+        println!(
+            "  ⏭️  Skipped by the pre_project hook: {} {noun}",
+            result.skipped.len().to_string().yellow()
+        );
+    }
+
+    println!(
+        "  💾 Total space freed: {}",
+        format_size(result.total_freed).bright_green().bold()
+    );
+
+    if result.total_freed != result.estimated_size
+        && result.cancelled_count == 0
+        && result.skipped.is_empty()
+    {
+        let difference = result.estimated_size.abs_diff(result.total_freed);
+        println!(
+            "  📋 Difference from estimate: {}",
+            format_size(difference).yellow()
         );
     }
-    Ok(())
 }
 
-/// Perform the actual cleanup and print results.
-fn run_cleanup(
-    projects: Projects,
-    keep_executables: bool,
-    json_mode: bool,
-    use_trash: bool,
-) -> Result<()> {
-    let removal_strategy = RemovalStrategy::from_use_trash(use_trash);
-    let snapshot: Vec<_> = projects.as_slice().to_vec();
-    let result = Cleaner::clean_projects(projects, keep_executables, json_mode, removal_strategy);
+/// Print the entries left behind in each partially cleaned project, after
+/// the summary with `--verbose`.
+fn print_leftovers(result: &CleanResult) {
+    for partial in &result.partial {
+        println!(
+            "\n{}",
+            format!("📂 Left in {}:", partial.root_path.display()).yellow()
+        );
+        for leftover in &partial.leftovers {
+            println!("  {}", leftover.display());
+        }
+    }
+}
 
-    if json_mode {
-        let output = JsonOutput::from_projects_cleanup(&snapshot, &result);
-        println!("{}", serde_json::to_string_pretty(&output)?);
+/// Find the projects under `dirs` and size them, except those skipped as
+/// recently modified by `--keep-days` (or a local `keep_days`), which are
+/// returned apart with their artifacts left unsized.
+///
+/// Modification times are cheap to read while sizing walks every file of
+/// the artifacts, so on trees of mostly active projects this spares most of
+/// the scan. With `--stale-only`, `--keep-days` applies to artifacts rather
+/// than projects and everything is sized. Activity recorded by the git
+/// hooks counts as a modification; with `--git-age`, the last commit decides
+/// instead.
+fn scan(
+    scanner: &Scanner,
+    args: &Cli,
+    file_config: &FileConfig,
+    dirs: &[PathBuf],
+    stale_only: bool,
+) -> Result<(Vec<Project>, Vec<SkippedProject>)> {
+    let projects = find(scanner, args, dirs)?;
+    size_found(scanner, args, file_config, dirs, projects, stale_only)
+}
+
+/// Find the projects under `dirs`, or those listed by `--paths-from`,
+/// without sizing them: the first half of [`scan`].
+fn find(scanner: &Scanner, args: &Cli, dirs: &[PathBuf]) -> Result<Vec<Project>> {
+    load_activity(args.json());
+    Ok(match args.paths_from() {
+        Some(source) => scanner.find_listed_projects(&read_path_list(source)?),
+        None => scanner.find_projects(dirs),
+    })
+}
+
+/// Size the `projects` found by [`find`], leaving the recent ones apart:
+/// the second half of [`scan`].
+fn size_found(
+    scanner: &Scanner,
+    args: &Cli,
+    file_config: &FileConfig,
+    dirs: &[PathBuf],
+    projects: Vec<Project>,
+    stale_only: bool,
+) -> Result<(Vec<Project>, Vec<SkippedProject>)> {
+    if stale_only {
+        let sized = scanner.size_projects(projects);
+        save_scan_index(scanner, args.json());
+        return Ok((sized, Vec::new()));
+    }
+
+    let mut local_configs = LocalConfigs::new(dirs);
+    let filter_opts = projects
+        .iter()
+        .map(|project| {
+            let local = local_configs.resolve(&project.root_path)?;
+            Ok(args.local_filter_options(file_config, &local))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let (projects, recent): (Vec<_>, Vec<_>) = projects
+        .into_par_iter()
+        .zip(filter_opts)
+        .partition_map(|(project, filter_opts)| {
+            if is_recent(&project, &filter_opts) {
+                Either::Right(SkippedProject {
+                    project,
+                    reason: SkipReason::SkippedRecent,
+                })
+            } else {
+                Either::Left(project)
+            }
+        });
+    let sized = scanner.size_projects(projects);
+    save_scan_index(scanner, args.json());
+    Ok((sized, recent))
+}
+
+/// Read the project roots listed one per line in `source`, or in stdin if
+/// it is `-`, for `--paths-from`. Blank lines are skipped and a leading `~`
+/// stands for the home directory.
+fn read_path_list(source: &Path) -> Result<Vec<PathBuf>> {
+    let contents = if source == Path::new("-") {
+        std::io::read_to_string(std::io::stdin())
+            .map_err(|e| anyhow::anyhow!("Failed to read the project roots from stdin: {e}"))?
     } else {
-        Cleaner::print_summary(&result);
+        fs::read_to_string(source)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", source.display()))?
+    };
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| expand_tilde(Path::new(line)))
+        .collect())
+}
+
+/// Apply the size, age and protection filters, with the options overridden
+/// by the `.clean-dev-dirs.toml` files of the subtree each project is in.
+///
+/// With `--stale-only`, `--keep-days` applies to artifacts rather than whole
+/// projects, so local `keep_days` overrides are ignored.
+fn classify_by_subtree(
+    projects: Vec<Project>,
+    args: &Cli,
+    file_config: &FileConfig,
+    dirs: &[PathBuf],
+    stale_only: bool,
+) -> Result<FilterOutcome> {
+    let mut local_configs = LocalConfigs::new(dirs);
+    let mut groups: Vec<(LocalFilterConfig, Vec<Project>)> = Vec::new();
+    for project in projects {
+        let local = local_configs.resolve(&project.root_path)?;
+        match groups.iter_mut().find(|(key, _)| *key == local) {
+            Some((_, group)) => group.push(project),
+            None => groups.push((local, vec![project])),
+        }
+    }
+
+    let mut outcome = FilterOutcome::default();
+    for (local, group) in groups {
+        let mut options = args.local_filter_options(file_config, &local);
+        options.top = None;
+        options.free = None;
+        if stale_only {
+            options.keep_days = 0;
+        }
+
+        let group_outcome = classify_projects(group, &options)?;
+        outcome.kept.extend(group_outcome.kept);
+        outcome.skipped.extend(group_outcome.skipped);
+    }
+    Ok(outcome)
+}
+
+/// Apply the size, age, git history, outdatedness, running package manager
+/// and open file filters.
+///
+/// With `--stale-only`, the artifact sizes of each project are narrowed to
+/// its stale artifacts, and projects without any are dropped.
+fn classify(
+    mut projects: Vec<Project>,
+    args: &Cli,
+    file_config: &FileConfig,
+    dirs: &[PathBuf],
+    stale_only: Option<&StaleCriteria>,
+) -> Result<FilterOutcome> {
+    if let Some(criteria) = stale_only {
+        projects = projects
+            .into_par_iter()
+            .filter_map(|mut project| {
+                // Caches hold no build outputs to judge, they are left alone
+                project
+                    .build_arts
+                    .retain(|artifact| artifact.category() == ArtifactCategory::Build);
+                for artifact in &mut project.build_arts {
+                    artifact.size = sweep::stale_entries(&artifact.path, criteria)
+                        .iter()
+                        .map(|entry| entry.size)
+                        .sum();
+                }
+                (project.total_size() > 0).then_some(project)
+            })
+            .collect();
+    }
+
+    // `--top` and `--free` keep the largest projects once all the filters
+    // below are applied
+    let filter_options = args.filter_options(file_config);
+    let free = filter_options.free.as_deref().map(parse_size).transpose()?;
+    let mut outcome = classify_by_subtree(projects, args, file_config, dirs, stale_only.is_some())?;
+    if let Some(git_filter) = args.git_change_filter() {
+        outcome.skip_by_git(&git_filter);
     }
+    if args.only_outdated() {
+        outcome.skip_up_to_date();
+    }
+    if args.skip_dirty() {
+        outcome.skip_dirty();
+    }
+    if !args.force() {
+        outcome.skip_busy(&processes::package_manager_dirs());
+    }
+    if args.check_in_use() {
+        outcome.skip_in_use(&processes::open_files()?);
+    }
+    if let Some(top) = filter_options.top {
+        outcome.keep_largest(top);
+    }
+    if let Some(target) = free {
+        outcome.keep_until_freed(target);
+        let total: u64 = outcome.kept.iter().map(Project::total_size).sum();
+        if total < target && !args.json() {
+            tracing::warn!(
+                "only {} can be freed, less than the --free target of {}",
+                format_size(total),
+                format_size(target)
+            );
+        }
+    }
+    Ok(outcome)
+}
+
+/// Apply the `--width`, `--no-pager` and `--no-progress` output settings.
+fn configure_output(args: &Cli) {
+    terminal::override_width(args.width());
+    pager::disable(args.no_pager());
+    progress::disable(args.no_progress());
+    if args.exact_sizes() {
+        size::set_format(SizeFormat::Exact);
+    }
+}
+
+/// Load the report signing key if `--sign-report` (or `sign = true`) is active.
+fn init_report_signer(args: &Cli, config: &FileConfig) -> Result<()> {
+    if !args.sign_report(config) {
+        return Ok(());
+    }
+
+    let Some(key_path) = args.signing_key(config) else {
+        bail!("--sign-report requires a key (--signing-key or `signing_key` in [report])");
+    };
+    // Only set here, so the cell is always empty at this point
+    let _ = REPORT_SIGNER.set(ReportSigner::from_key_file(&key_path)?);
+    Ok(())
+}
+
+/// Print a JSON report, timestamped and signed if `--sign-report` is active.
+fn print_json<T: Serialize>(output: &T) -> Result<()> {
+    let json = match REPORT_SIGNER.get() {
+        Some(signer) => {
+            serde_json::to_string_pretty(&signer.sign(output, &ReportMetadata::current())?)?
+        }
+        None => serde_json::to_string_pretty(output)?,
+    };
+    println!("{json}");
+    Ok(())
+}
+
+/// Verify a signed report and print the key it was signed with.
+fn verify_report(path: &Path) -> Result<()> {
+    let content = fs::read_to_string(path)?;
+    let public_key = report::verify_report(&content)?;
 
+    println!("{}", "✅ Report signature is valid".green());
+    println!("  Signed with public key: {}", public_key.bright_white());
     Ok(())
 }
+
+/// Run a subcommand instead of a scan.
+fn run_command(args: &Cli, command: &Command) -> Result<ExitStatus> {
+    let history_dir = || {
+        history::history_dir().ok_or_else(|| {
+            anyhow::anyhow!("No data directory to keep the history in on this platform")
+        })
+    };
+
+    match command {
+        Command::Restore { run_id } => run_restore(&Manifest::load(&history_dir()?, run_id)?)?,
+        Command::History { action: None } => {
+            print_history(&history::list_runs(&history_dir()?)?)?;
+        }
+        Command::History {
+            action: Some(HistoryAction::Show { run_id }),
+        } => print_run(&Manifest::load(&history_dir()?, run_id)?),
+        Command::Clean { .. } | Command::Scan { .. } | Command::List { .. } => {
+            return run_clean(args);
+        }
+        Command::Stats { .. } => run_stats(args)?,
+        Command::Discover { min_size, .. } => run_discover(args, min_size)?,
+        Command::Apply { file } => return run_apply(args, file),
+        Command::Config { action } => run_config(args, action.clone().unwrap_or_default())?,
+        Command::Schedule { action } => run_schedule(args, action)?,
+        Command::Hook { action } => run_hook(action)?,
+        Command::WhyBig { project, top } => print_usage(&expand_tilde(project), *top)?,
+        Command::Completions { shell } => print_completions(*shell),
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Print the completion script for `shell`: the `completions` subcommand.
+fn print_completions(shell: Shell) {
+    clap_complete::generate(
+        shell,
+        &mut Cli::command_with_env(),
+        env!("CARGO_BIN_NAME"),
+        &mut std::io::stdout(),
+    );
+}
+
+/// Print the path of the configuration file, or the file itself.
+fn run_config(args: &Cli, action: ConfigAction) -> Result<()> {
+    let Some(path) = FileConfig::config_path() else {
+        bail!("No configuration directory on this platform");
+    };
+
+    match action {
+        ConfigAction::Path => println!("{}", path.display()),
+        ConfigAction::Show => {
+            let settings = args.settings(&FileConfig::load()?);
+            if args.json() {
+                return print_json(&settings);
+            }
+            print_settings(&settings, &path);
+        }
+        ConfigAction::Init => {
+            if path.exists() && !args.force() {
+                bail!(
+                    "{} already exists; use --force to overwrite it",
+                    path.display()
+                );
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, DEFAULT_CONFIG)?;
+            println!("📝 Wrote the default configuration to {}", path.display());
+        }
+        ConfigAction::Validate { file } => {
+            let file = file.unwrap_or(path);
+            let content = fs::read_to_string(&file)
+                .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", file.display()))?;
+            let problems = validate(&content);
+            if problems.is_empty() {
+                println!("✅ {} is valid", file.display());
+                return Ok(());
+            }
+            for problem in &problems {
+                let location = format!("{}:{}:{}", file.display(), problem.line, problem.column);
+                eprintln!("{} {}", format!("{location}:").bold(), problem.message);
+            }
+            bail!("{} problem(s) in {}", problems.len(), file.display());
+        }
+    }
+    Ok(())
+}
+
+/// Print the settings in effect as TOML, each followed by where it comes
+/// from. Unset settings are commented out.
+fn print_settings(settings: &[Setting], path: &Path) {
+    let lines: Vec<(Option<&str>, String, &str)> = settings
+        .iter()
+        .map(|setting| {
+            let (section, key) = match setting.key.split_once('.') {
+                Some((section, key)) => (Some(section), key),
+                None => (None, setting.key),
+            };
+            let line = setting.value.as_ref().map_or_else(
+                || format!("# {key} (not set)"),
+                |value| format!("{key} = {value}"),
+            );
+            (section, line, setting.source.label())
+        })
+        .collect();
+    let width = lines
+        .iter()
+        .map(|(_, line, _)| line.len())
+        .max()
+        .unwrap_or(0);
+
+    println!(
+        "{}",
+        format!(
+            "# Settings in effect: command line > environment > {} > defaults",
+            path.display()
+        )
+        .dimmed()
+    );
+    let mut current_section = None;
+    for (section, line, source) in lines {
+        if section != current_section {
+            println!("\n[{}]", section.unwrap_or_default());
+            current_section = section;
+        }
+        println!("{line:width$}  {}", format!("# {source}").dimmed());
+    }
+}
+
+/// Install, remove or show the scheduled cleanup.
+fn run_schedule(args: &Cli, action: &ScheduleAction) -> Result<()> {
+    let Some(scheduler) = Scheduler::current() else {
+        bail!("Scheduling cleanups is not supported on this platform");
+    };
+
+    match action {
+        ScheduleAction::Install { dirs, daily, .. } => {
+            let file_config = load_config(false);
+            let execution_options = args.execution_options(&file_config);
+            if execution_options.interactive || execution_options.tui {
+                bail!("Scheduled cleanups cannot be interactive");
+            }
+            if args.has_directory() {
+                bail!("Give the directories to clean after `schedule install`");
+            }
+
+            // The options given before `schedule` are passed on as they are
+            let raw_args: Vec<String> = std::env::args().skip(1).collect();
+            let schedule_at = raw_args.iter().position(|arg| arg == "schedule");
+            let after = schedule_at.map_or(&[][..], |index| &raw_args[index + 1..]);
+            if after
+                .iter()
+                .any(|arg| arg.starts_with('-') && arg != "--daily" && arg != "--weekly")
+            {
+                bail!("Give the options of the scheduled cleanup before `schedule`");
+            }
+            let dirs = if dirs.is_empty() {
+                args.directories(&file_config)
+            } else {
+                dirs.iter().map(|dir| expand_tilde(dir)).collect()
+            };
+            let mut command = vec![std::env::current_exe()?.to_string_lossy().into_owned()];
+            for dir in dirs {
+                command.push(std::path::absolute(dir)?.to_string_lossy().into_owned());
+            }
+            command.extend(raw_args.into_iter().take_while(|arg| arg != "schedule"));
+            if !execution_options.yes {
+                command.push("--yes".to_string());
+            }
+            if !args.scheduled() {
+                command.push("--scheduled".to_string());
+            }
+
+            let frequency = if *daily {
+                Frequency::Daily
+            } else {
+                Frequency::Weekly
+            };
+            scheduler.install(&command, frequency)?;
+            println!(
+                "⏰ Scheduled with {}, {}:",
+                scheduler.name(),
+                frequency.describe()
+            );
+            println!("  {}", command.join(" ").bright_white());
+            for file in scheduler.job_files()? {
+                println!("  📄 {}", file.display());
+            }
+        }
+        ScheduleAction::Remove => {
+            if scheduler.remove()? {
+                println!("{}", "🗑️  Removed the scheduled cleanup".green());
+            } else {
+                println!("No cleanup is scheduled");
+            }
+        }
+        ScheduleAction::Status => match scheduler.status()? {
+            Some(status) => {
+                println!("⏰ A cleanup is scheduled with {}:", scheduler.name());
+                for file in scheduler.job_files()? {
+                    println!("  📄 {}", file.display());
+                }
+                println!("{status}");
+            }
+            None => println!("No cleanup is scheduled"),
+        },
+    }
+    Ok(())
+}
+
+/// Install or remove the activity hooks, or record activity from them.
+fn run_hook(action: &HookAction) -> Result<()> {
+    let Some(log_path) = activity::activity_path() else {
+        bail!("No data directory to keep the activity log in on this platform");
+    };
+    let repo = |repo: &Option<PathBuf>| {
+        repo.as_deref()
+            .map_or_else(|| PathBuf::from("."), expand_tilde)
+    };
+
+    match action {
+        HookAction::Install { repo: dir } => {
+            let exe = std::env::current_exe()?;
+            let written = activity::install_hooks(&repo(dir), &exe)?;
+            println!("🪝 Installed the activity hooks:");
+            for file in written {
+                println!("  📄 {}", file.display());
+            }
+            println!(
+                "  Checkouts and merges are recorded in {}",
+                log_path.display()
+            );
+        }
+        HookAction::Remove { repo: dir } => {
+            let changed = activity::remove_hooks(&repo(dir))?;
+            if changed.is_empty() {
+                println!("No activity hooks are installed");
+            } else {
+                println!("{}", "🗑️  Removed the activity hooks".green());
+            }
+        }
+        HookAction::Record => {
+            // Hooks run at the root of the working tree
+            let repo = std::env::current_dir()?.canonicalize()?;
+            let mut log = ActivityLog::load(&log_path)?;
+            log.record(repo, chrono::Utc::now());
+            log.save(&log_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Use the activity recorded by the git hooks when telling how old projects
+/// are. A log that cannot be read is ignored.
+fn load_activity(json_mode: bool) {
+    let Some(path) = activity::activity_path() else {
+        return;
+    };
+    match ActivityLog::load(&path) {
+        std::result::Result::Ok(log) => activity::set_log(log),
+        Err(e) => {
+            if !json_mode {
+                tracing::warn!("Failed to load the activity log: {e:#}");
+            }
+        }
+    }
+}
+
+/// The sizes recorded by earlier runs, to reuse when sizing: none with
+/// `--no-size`, and an empty index with `--refresh`. An index that cannot be
+/// read is started anew.
+fn load_scan_index(args: &Cli) -> Option<ScanIndex> {
+    if !args.sizing() {
+        return None;
+    }
+    let path = scan_index::scan_index_path()?;
+    if args.refresh() {
+        return Some(ScanIndex::default());
+    }
+    match ScanIndex::load(&path) {
+        std::result::Result::Ok(index) => Some(index),
+        Err(e) => {
+            if !args.json() {
+                tracing::warn!("Failed to load the scan index: {e:#}");
+            }
+            Some(ScanIndex::default())
+        }
+    }
+}
+
+/// Record the sizes measured by `scanner` for the next run. Failing to do
+/// so only makes the next run slower.
+fn save_scan_index(scanner: &Scanner, json_mode: bool) {
+    let (Some(mut index), Some(path)) = (scanner.index(), scan_index::scan_index_path()) else {
+        return;
+    };
+    if let Err(e) = index.save(&path)
+        && !json_mode
+    {
+        tracing::warn!("Failed to save the scan index: {e:#}");
+    }
+}
+
+/// Use `threads` threads for scanning, or rayon's default when 0.
+fn configure_threads(threads: usize) -> Result<()> {
+    if threads > 0 {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()?;
+    }
+    Ok(())
+}
+
+/// Scan the directory and print breakdowns of the projects found, without
+/// cleaning anything.
+fn run_stats(args: &Cli) -> Result<()> {
+    let json_mode = args.json();
+    let file_config = load_config(json_mode);
+    configure_output(args);
+    load_activity(json_mode);
+
+    let dirs = args.directories(&file_config);
+    let scan_options = args.scan_options(&file_config);
+    configure_threads(scan_options.threads)?;
+
+    let scanner = Scanner::new(scan_options, args.project_filter(&file_config))
+        .with_path_filter(args.path_filter()?)
+        .with_artifacts(args.artifacts().to_vec())
+        .with_limits(args.scan_limits(&file_config)?)
+        .with_ignore_files(args.ignore_files())
+        .with_one_file_system(args.one_file_system())
+        .with_network_fs(args.allow_network_fs())
+        .with_max_depth(args.max_depth())
+        .with_min_depth(args.min_depth())
+        .with_du_mode(args.du_mode());
+    let scanner = match load_scan_index(args) {
+        Some(index) => scanner.with_index(index),
+        None => scanner,
+    };
+    let scanner = with_scan_spinner(scanner, json_mode);
+    let projects = scanner.scan_directories(&dirs);
+    save_scan_index(&scanner, json_mode);
+
+    let breakdown = Breakdown::new(&projects, SystemTime::now());
+    if json_mode {
+        return print_json(&breakdown);
+    }
+
+    print_scan_outcome(&scanner, false, projects.len());
+    if projects.is_empty() {
+        println!("{}", "✨ No development directories found!".green());
+    } else {
+        breakdown.print();
+    }
+    Ok(())
+}
+
+/// List the large directories under the directories to scan that no
+/// detector matches: the `discover` subcommand.
+///
+/// The artifacts of the projects of every type are left out, whatever
+/// `--project-type` and `--artifacts` select.
+fn run_discover(args: &Cli, min_size: &str) -> Result<()> {
+    let json_mode = args.json();
+    let file_config = load_config(json_mode);
+    configure_output(args);
+
+    let min_size = parse_size(min_size)?;
+    let dirs = args.directories(&file_config);
+    let scan_options = args.scan_options(&file_config);
+    configure_threads(scan_options.threads)?;
+
+    // Every known artifact is left out, even those of ignored projects
+    let scanner = with_scan_spinner(
+        Scanner::new(scan_options, ProjectFilter::All)
+            .with_ignore_files(false)
+            .with_one_file_system(args.one_file_system())
+            .with_network_fs(args.allow_network_fs()),
+        json_mode,
+    );
+    let known: Vec<PathBuf> = scanner
+        .find_projects(&dirs)
+        .into_iter()
+        .flat_map(|project| project.build_arts)
+        .map(|artifact| artifact.path)
+        .collect();
+    let unknown = discover::find_unknown_dirs(&dirs, &known, min_size);
+
+    if json_mode {
+        return print_json(&unknown);
+    }
+    discover::print(&unknown, min_size);
+    Ok(())
+}
+
+/// Show what takes the most space in the artifacts of `project`: the crates
+/// of a Rust target directory, or the packages of a `node_modules/`
+/// directory. `project` can also be the artifact directory itself.
+fn print_usage(project: &Path, top: usize) -> Result<()> {
+    let (artifact, is_node) = if project.join("Cargo.toml").is_file() {
+        (project.join("target"), false)
+    } else if project.join("package.json").is_file() {
+        (project.join("node_modules"), true)
+    } else {
+        (
+            project.to_path_buf(),
+            project
+                .file_name()
+                .is_some_and(|name| name == "node_modules"),
+        )
+    };
+    if !artifact.is_dir() {
+        bail!(
+            "{} has no target or node_modules directory",
+            project.display()
+        );
+    }
+
+    let (rows, total, unattributed) = if is_node {
+        let usage = node_usage::analyze(&artifact);
+        let rows: Vec<UsageRow> = usage
+            .packages
+            .iter()
+            .map(|package| UsageRow {
+                name: package.name.clone(),
+                size: package.size,
+                detail: (package.copies > 1).then(|| format!("{} copies", package.copies)),
+            })
+            .collect();
+        (rows, usage.total, usage.unattributed())
+    } else {
+        let usage = target_usage::analyze(&artifact);
+        let rows: Vec<UsageRow> = usage.crates.iter().map(crate_usage_row).collect();
+        (rows, usage.total, usage.unattributed())
+    };
+
+    println!(
+        "\n{} {}",
+        format!("🔍 {}:", artifact.display()).bold(),
+        format_size(total).bright_white().bold()
+    );
+    if rows.is_empty() {
+        let what = if is_node {
+            "packages"
+        } else {
+            "compiled crates"
+        };
+        println!("{}", format!("  No {what} found").yellow());
+        return Ok(());
+    }
+
+    let kind = if is_node { "package" } else { "crate" };
+    let unattributed_detail = if is_node {
+        "(.bin, caches)"
+    } else {
+        "(binaries, examples, docs)"
+    };
+    print_usage_rows(&rows, top, kind, (unattributed, unattributed_detail));
+    Ok(())
+}
+
+/// A line of the `why-big` listing.
+struct UsageRow {
+    /// Name of the crate or package.
+    name: String,
+
+    /// Bytes it uses.
+    size: u64,
+
+    /// How the size is made up, if worth showing.
+    detail: Option<String>,
+}
+
+/// The `why-big` line of a crate, with its size in each unit directory.
+fn crate_usage_row(crate_usage: &CrateUsage) -> UsageRow {
+    let mut dirs: Vec<(UnitDir, u64)> = UnitDir::ALL
+        .into_iter()
+        .map(|dir| (dir, crate_usage.in_dir(dir)))
+        .filter(|&(_, size)| size > 0)
+        .collect();
+    dirs.sort_by_key(|&(_, size)| std::cmp::Reverse(size));
+    let breakdown: Vec<String> = dirs
+        .iter()
+        .map(|(dir, size)| format!("{} {}", dir.label(), format_size(*size)))
+        .collect();
+
+    UsageRow {
+        name: crate_usage.name.clone(),
+        size: crate_usage.total(),
+        detail: Some(breakdown.join(", ")),
+    }
+}
+
+/// Print the `top` largest rows, a line adding up the others (each a
+/// `kind`), and the bytes not attributed to any row.
+fn print_usage_rows(rows: &[UsageRow], top: usize, kind: &str, unattributed: (u64, &str)) {
+    let width = rows
+        .iter()
+        .take(top)
+        .map(|row| row.name.len())
+        .fold("(not attributed)".len(), usize::max);
+    for row in rows.iter().take(top) {
+        let detail = row
+            .detail
+            .as_ref()
+            .map(|detail| format!("({detail})").dimmed().to_string())
+            .unwrap_or_default();
+        println!(
+            "  {:width$}  {:>10}  {detail}",
+            row.name,
+            format_size(row.size).bright_green()
+        );
+    }
+
+    let hidden = rows.len().saturating_sub(top);
+    if hidden > 0 {
+        let rest: u64 = rows[top..].iter().map(|row| row.size).sum();
+        println!(
+            "  {:width$}  {:>10}",
+            format!(
+                "({hidden} more {kind}{})",
+                if hidden == 1 { "" } else { "s" }
+            ),
+            format_size(rest)
+        );
+    }
+    let (size, detail) = unattributed;
+    println!(
+        "  {:width$}  {:>10}  {}",
+        "(not attributed)",
+        format_size(size),
+        detail.dimmed()
+    );
+}
+
+/// Restore the artifacts removed by the run of `manifest`.
+fn run_restore(manifest: &Manifest) -> Result<()> {
+    if !manifest.trashed {
+        let mut lines = vec![format!(
+            "\n{}",
+            format!(
+                "🗑️  Run {} deleted its artifacts permanently, they can only be rebuilt:",
+                manifest.run_id
+            )
+            .yellow()
+        )];
+        lines.extend(manifest.entries.iter().map(|entry| {
+            format!(
+                "  {} ({})",
+                entry.artifact_path.display(),
+                format_size(entry.size)
+            )
+        }));
+        pager::page(&lines);
+        return Ok(());
+    }
+
+    println!(
+        "\n{}",
+        format!("⏪ Restoring run {} from the trash:", manifest.run_id).bold()
+    );
+    let outcomes = history::restore(manifest);
+    let mut restored = 0;
+    for (entry, outcome) in manifest.entries.iter().zip(&outcomes) {
+        let path = entry.artifact_path.display();
+        match outcome {
+            RestoreOutcome::Restored => {
+                restored += 1;
+                println!("  {} {path}", "✅ Restored".green());
+            }
+            RestoreOutcome::AlreadyExists => {
+                println!(
+                    "  {} {path} already exists, left in the trash",
+                    "⏭️ ".yellow()
+                );
+            }
+            RestoreOutcome::NotInTrash => {
+                println!("  {} {path} is no longer in the trash", "❌".red());
+            }
+            RestoreOutcome::Deleted => {
+                println!("  {} {path} was deleted permanently", "❌".red());
+            }
+            RestoreOutcome::Failed(error) => {
+                println!("  {} {path}: {error}", "❌ Failed to restore".red());
+            }
+        }
+    }
+    println!(
+        "\n⏪ Restored {} of {} artifacts",
+        restored.to_string().green(),
+        manifest.entries.len()
+    );
+    Ok(())
+}
+
+/// Where a recorded run cleaned, for display.
+fn run_location(run: &Manifest) -> String {
+    run.root.as_ref().map_or_else(
+        || "global caches".to_string(),
+        |root| root.display().to_string(),
+    )
+}
+
+/// List the recorded runs, newest first, and the space they freed in total.
+fn print_history(runs: &[Manifest]) -> Result<()> {
+    let Some((total, since)) = history::total_freed(runs) else {
+        println!("{}", "No cleanup has been recorded yet".yellow());
+        return Ok(());
+    };
+
+    let mut lines = vec![format!("{}", "📜 Cleanup history:".bold())];
+    for run in runs.iter().rev() {
+        let mut line = format!(
+            "  {}  {}  {}  {} cleaned, {}  {}",
+            run.run_id.bright_white(),
+            run.created_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            run_location(run),
+            run.cleaned_count,
+            format_size(run.bytes_freed).bright_green(),
+            if run.trashed { "trashed" } else { "deleted" }.dimmed()
+        );
+        if !run.errors.is_empty() {
+            line.push_str(&format!("  ({} errors)", run.errors.len()).red().to_string());
+        }
+        if run.cancelled_count > 0 {
+            line.push_str(&"  (cancelled)".yellow().to_string());
+        }
+        lines.push(terminal::fit(&line));
+    }
+    lines.push(format!(
+        "\n💾 Total freed: {} over {} {} since {}",
+        format_size(total).bright_green().bold(),
+        runs.len(),
+        if runs.len() == 1 { "run" } else { "runs" },
+        since.with_timezone(&chrono::Local).format("%Y-%m-%d")
+    ));
+    lines.push(
+        "Show one with `clean-dev-dirs history show <RUN_ID>`"
+            .dimmed()
+            .to_string(),
+    );
+    pager::page(&lines);
+    Ok(())
+}
+
+/// Show the details of a recorded run: its projects, artifacts and errors.
+fn print_run(run: &Manifest) {
+    let mut lines = vec![
+        format!("{}", format!("📜 Run {}", run.run_id).bold()),
+        format!(
+            "  Date:      {}",
+            run.created_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+        ),
+        format!("  Directory: {}", run_location(run)),
+        format!(
+            "  Mode:      {}",
+            if run.trashed {
+                "moved to the trash"
+            } else {
+                "deleted permanently"
+            }
+        ),
+        if run.cancelled_count > 0 {
+            format!(
+                "  Cleaned:   {} (cancelled, {} left untouched)",
+                run.cleaned_count, run.cancelled_count
+            )
+        } else {
+            format!("  Cleaned:   {}", run.cleaned_count)
+        },
+        format!(
+            "  Freed:     {}",
+            format_size(run.bytes_freed).bright_green()
+        ),
+    ];
+
+    if !run.entries.is_empty() {
+        lines.push(format!("\n{}", "📂 Removed artifacts:".bold()));
+        for (project, entries) in run.by_project() {
+            lines.push(format!("  {}", project.display()));
+            for entry in entries {
+                let name = entry
+                    .artifact_path
+                    .strip_prefix(project)
+                    .unwrap_or(&entry.artifact_path);
+                lines.push(format!(
+                    "     {} ({})",
+                    name.display(),
+                    format_size(entry.size)
+                ));
+            }
+        }
+    }
+
+    if !run.errors.is_empty() {
+        lines.push(format!("\n{}", "⚠️  Errors:".yellow()));
+        lines.extend(run.errors.iter().map(|error| format!("  {}", error.red())));
+    }
+    pager::page(&lines);
+}
+
+/// Install a Ctrl+C (and termination signal) handler that cancels the
+/// returned token.
+///
+/// The first Ctrl+C asks the scan or cleanup in progress to stop and report
+/// partial results: a cleanup finishes the directories being removed, then
+/// prints and records what was freed. A second one exits immediately.
+fn install_cancel_handler() -> Result<CancellationToken> {
+    let cancellation = CancellationToken::new();
+    let handler_token = cancellation.clone();
+
+    ctrlc::set_handler(move || {
+        if handler_token.is_cancelled() {
+            exit(130);
+        }
+
+        let message = if CLEANING.load(Ordering::SeqCst) {
+            "Stopping once the directories being removed are done… press Ctrl+C again to \
+             exit immediately, without a summary or history record"
+        } else {
+            "Cancelling… press Ctrl+C again to exit immediately"
+        };
+        eprintln!("\n{}", message.yellow());
+        handler_token.cancel();
+    })?;
+
+    Ok(cancellation)
+}
+
+/// Load the configuration file, falling back to defaults on failure.
+fn load_config(json_mode: bool) -> FileConfig {
+    match FileConfig::load() {
+        std::result::Result::Ok(config) => config,
+        Err(e) => {
+            if !json_mode {
+                tracing::warn!("Failed to load config file: {e}");
+            }
+            FileConfig::default()
+        }
+    }
+}
+
+/// Emit an empty-projects result in JSON or human-readable form.
+fn print_empty_result(args: &Cli, message: &str, skipped: &[SkippedProject]) -> Result<ExitStatus> {
+    append_stats(args.append_csv(), || RunStats::dry_run(&[]));
+    if args.json() {
+        let output = JsonOutput::from_projects_dry_run(&[]).with_skipped(skipped);
+        print_json(&output)?;
+    } else if !args.lists_projects() {
+        println!("{}", message.green());
+    }
+    if args.scheduled() {
+        report_scheduled(&CleanResult::default(), "project", args.json());
+    }
+    Ok(ExitStatus::NothingFound)
+}
+
+/// Print the size in bytes, type and root directory of each project, separated
+/// by tabs, for the `list` subcommand.
+fn print_project_list(projects: &[Project]) {
+    for project in projects {
+        let kind = TYPE_COLUMNS
+            .iter()
+            .find(|(kind, _)| *kind == project.kind)
+            .map_or("", |&(_, name)| name);
+        println!(
+            "{}\t{kind}\t{}",
+            project.total_size(),
+            project.root_path.display()
+        );
+    }
+}
+
+/// Record every project that was filtered out in the log file.
+fn log_skipped(skipped: &[SkippedProject]) {
+    for SkippedProject { project, reason } in skipped {
+        tracing::info!(
+            target: RECORD,
+            path = %project.root_path.display(),
+            reason = reason.code(),
+            "skipped"
+        );
+    }
+}
+
+/// Print every project that was filtered out, tagged with its reason code.
+fn print_skipped(skipped: &[SkippedProject]) {
+    if skipped.is_empty() {
+        return;
+    }
+
+    let mut lines = vec![format!(
+        "\n{}",
+        format!("⏭️  Skipped {} projects:", skipped.len()).bold()
+    )];
+    for SkippedProject { project, reason } in skipped {
+        let line = format!(
+            "  {} {} ({})",
+            format!("[{}]", reason.code()).yellow(),
+            project,
+            reason.description().dimmed()
+        );
+        lines.push(terminal::fit(&line));
+    }
+    pager::page(&lines);
+}
+
+/// Warn about projects skipped because a package manager is running in them
+/// or, with `--skip-dirty`, because they have uncommitted changes.
+///
+/// Unlike the other skip reasons, these are reported even without
+/// `--verbose`: the user most likely expected them to be cleaned.
+fn warn_skips(skipped: &[SkippedProject]) {
+    for SkippedProject { project, reason } in skipped {
+        let note = match reason {
+            SkipReason::BuildInProgress => {
+                "build in progress — skipped (use --force to clean it anyway)"
+            }
+            SkipReason::Dirty => "uncommitted changes — skipped",
+            _ => continue,
+        };
+        let line = format!("{} {} {}", "⚠️ ".yellow(), project, note.yellow());
+        println!("{}", terminal::fit(&line));
+    }
+}
+
+/// Handle interactive (or `--tui`) project selection and the
+/// keep-executables prompt.
+///
+/// Returns `Ok(Some((projects, keep)))` with the projects (and build
+/// directories) to clean and the resolved flag, or `Ok(None)` when the user
+/// selected zero projects or quit the TUI (caller should exit).
+fn resolve_selection(
+    projects: Projects,
+    opts: &clean_dev_dirs::ExecutionOptions,
+) -> Result<Option<(Projects, bool)>> {
+    let mut keep = opts.keep_executables;
+
+    if opts.tui {
+        let Some(selection) = tui::select_projects(projects.as_slice(), keep)? else {
+            println!("{}", "✨ No projects selected for cleaning!".green());
+            return Ok(None);
+        };
+        return Ok(Some((
+            selection.projects.into(),
+            selection.keep_executables,
+        )));
+    }
+
+    if !opts.interactive {
+        return Ok(Some((projects, keep)));
+    }
+
+    let selected = projects.interactive_selection()?;
+    if selected.is_empty() {
+        println!("{}", "✨ No projects selected for cleaning!".green());
+        return Ok(None);
+    }
+
+    if !keep {
+        keep = Confirm::new("Keep compiled executables before cleaning?")
+            .with_default(false)
+            .prompt()?;
+    }
+
+    Ok(Some((selected.into(), keep)))
+}
+
+/// Ask the user to confirm cleaning `projects`, unless `--yes` was given.
+///
+/// Cleanups of at least the confirmation threshold, or of unknown size when
+/// the projects were not `sized`, require typing "delete"; smaller ones are
+/// confirmed with `y`, and nothing (the default) cancels. Answering `<type>-only` or `skip-<type>` narrows the cleanup down by
+/// project type, shows the breakdown of what remains and asks again.
+///
+/// Returns which of `projects` to clean, or `None` to clean nothing.
+fn confirm_cleanup(
+    projects: &Projects,
+    opts: &clean_dev_dirs::ExecutionOptions,
+    sized: bool,
+) -> Result<Option<Vec<bool>>> {
+    let mut selected = vec![true; projects.len()];
+    if opts.yes {
+        return Ok(Some(selected));
+    }
+    let threshold = confirmation_threshold(opts)?;
+    let action = if opts.use_trash {
+        "Move to the trash"
+    } else {
+        "Permanently delete"
+    };
+
+    loop {
+        let (count, total_size) = projects
+            .as_slice()
+            .iter()
+            .zip(&selected)
+            .filter(|&(_, &selected)| selected)
+            .fold((0, 0), |(count, size), (project, _)| {
+                (count + 1, size + project.total_size())
+            });
+        let summary = format!(
+            "{action} the build directories of {count} projects ({})?",
+            format_size(total_size)
+        );
+
+        match ask_confirmation(&summary, sized.then_some(total_size), threshold, true)? {
+            Some(Answer::Yes | Answer::Delete) => return Ok(Some(selected)),
+            Some(Answer::No) => return Ok(None),
+            Some(narrowing) => {
+                let narrowed: Vec<bool> = projects
+                    .as_slice()
+                    .iter()
+                    .zip(&selected)
+                    .map(|(project, &selected)| selected && narrowing.keeps(&project.kind))
+                    .collect();
+                if !narrowed.contains(&true) {
+                    eprintln!("{}", "No project would be left to clean.".yellow());
+                    continue;
+                }
+                selected = narrowed;
+                let (remaining, _) = keep_selected(projects, None, &selected);
+                println!("\n{}", "📊 Selected projects:".bold());
+                remaining.print_summary(remaining.get_total_size());
+            }
+            None => {}
+        }
+    }
+}
+
+/// Ask the user to confirm cleaning `caches`, unless `--yes` was given,
+/// with the same threshold for typing "delete" as [`confirm_cleanup`].
+///
+/// Returns whether to clean them.
+fn confirm_cache_cleanup(
+    caches: &[CacheLocation],
+    opts: &clean_dev_dirs::ExecutionOptions,
+) -> Result<bool> {
+    if opts.yes {
+        return Ok(true);
+    }
+    let threshold = confirmation_threshold(opts)?;
+    let total_size: u64 = caches.iter().map(|cache| cache.size).sum();
+    let summary = format!(
+        "Clean {} global caches ({})?",
+        caches.len(),
+        format_size(total_size)
+    );
+
+    loop {
+        match ask_confirmation(&summary, Some(total_size), threshold, false)? {
+            Some(Answer::Yes | Answer::Delete) => return Ok(true),
+            Some(Answer::No) => return Ok(false),
+            _ => {}
+        }
+    }
+}
+
+/// The size from which a cleanup must be confirmed by typing "delete".
+///
+/// # Errors
+///
+/// Fails if stdin is not a terminal to ask on, or the threshold is invalid.
+fn confirmation_threshold(opts: &clean_dev_dirs::ExecutionOptions) -> Result<u64> {
+    if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        bail!("Refusing to clean without confirmation: stdin is not a terminal, pass --yes");
+    }
+    Ok(parse_size(&opts.confirm_threshold)?)
+}
+
+/// Show the confirmation prompt for a cleanup of `size` once, `None` when
+/// it is unknown.
+///
+/// Returns the answer, or `None` after explaining what is expected when it
+/// was not understood, narrows down a cleanup that cannot be narrowed, or
+/// is `y` for a cleanup of unknown size or large enough to require "delete".
+fn ask_confirmation(
+    summary: &str,
+    size: Option<u64>,
+    threshold: u64,
+    narrowable: bool,
+) -> Result<Option<Answer>> {
+    let needs_delete = size.is_none_or(|size| size >= threshold);
+    let confirm = if needs_delete { "delete" } else { "y" };
+    let help = if narrowable {
+        format!("{confirm} to confirm, n to cancel, <type>-only or skip-<type> to narrow down")
+    } else {
+        format!("{confirm} to confirm, n to cancel")
+    };
+
+    let answer = match Text::new(summary).with_help_message(&help).prompt() {
+        Err(InquireError::OperationCanceled | InquireError::OperationInterrupted) => {
+            Some(Answer::No)
+        }
+        input => Answer::parse(&input?),
+    };
+    match answer {
+        Some(Answer::Yes) if needs_delete => {
+            let hint = if size.is_some() {
+                format!(
+                    "Type \"delete\" to confirm cleaning {} or more",
+                    format_size(threshold)
+                )
+            } else {
+                "Type \"delete\" to confirm cleaning without knowing the sizes (--no-size)"
+                    .to_string()
+            };
+            eprintln!("{}", hint.yellow());
+        }
+        Some(Answer::No) => {
+            eprintln!("{}", "Nothing was cleaned.".yellow());
+            return Ok(answer);
+        }
+        Some(Answer::Only(_) | Answer::Skip(_)) | None if !narrowable => {
+            eprintln!("{}", "Answer y, n or delete.".yellow());
+        }
+        None => eprintln!(
+            "{}",
+            "Answer y, n, delete, <type>-only or skip-<type>.".yellow()
+        ),
+        Some(_) => return Ok(answer),
+    }
+    Ok(None)
+}
+
+/// When build directories are moved to the trash, check that the trash has
+/// room for those copied to it from another file system, so that the
+/// cleanup does not fail halfway. If it does not, offer to delete them
+/// permanently instead; without a terminal to ask on, nothing is cleaned.
+///
+/// Returns whether to go on with the cleanup, after switching `opts` to
+/// permanent deletion if the user chose so. When the projects were not
+/// `sized`, the check cannot be made and a warning says so.
+fn confirm_trash_space(
+    projects: &Projects,
+    opts: &mut clean_dev_dirs::ExecutionOptions,
+    sized: bool,
+    json: bool,
+) -> Result<bool> {
+    if !opts.use_trash {
+        return Ok(true);
+    }
+    if !sized {
+        eprintln!(
+            "{}",
+            "⚠️  Sizes were not measured (--no-size), so whether the trash has room for the \
+             build directories was not checked"
+                .yellow()
+        );
+        return Ok(true);
+    }
+    let shortfalls = disk::trash_shortfalls(projects.as_slice().iter().flat_map(|project| {
+        project
+            .build_arts
+            .iter()
+            .map(|artifact| (artifact.path.as_path(), artifact.size))
+    }));
+    if shortfalls.is_empty() {
+        return Ok(true);
+    }
+
+    for shortfall in &shortfalls {
+        eprintln!(
+            "{}",
+            format!(
+                "⚠️  Not enough space for the trash at {}: {} would be copied there, {} is free",
+                shortfall.dir.display(),
+                format_size(shortfall.needed),
+                format_size(shortfall.available)
+            )
+            .yellow()
+        );
+    }
+    if opts.yes || json || !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        bail!(
+            "Not enough space in the trash; free some up or pass --permanent to delete the \
+             build directories permanently"
+        );
+    }
+
+    let permanent = Confirm::new("Delete the build directories permanently instead?")
+        .with_default(false)
+        .prompt()?;
+    if permanent {
+        opts.use_trash = false;
+    } else {
+        eprintln!("{}", "Nothing was cleaned.".yellow());
+    }
+    Ok(permanent)
+}
+
+/// Keep the projects, and their compressed size estimates, whose entry in
+/// `selected` is `true`.
+fn keep_selected(
+    projects: &Projects,
+    compressed: Option<&[u64]>,
+    selected: &[bool],
+) -> (Projects, Option<Vec<u64>>) {
+    let kept = projects
+        .as_slice()
+        .iter()
+        .zip(selected)
+        .filter(|&(_, &selected)| selected)
+        .map(|(project, _)| project.clone())
+        .collect::<Vec<_>>();
+    let compressed = compressed.map(|estimates| {
+        estimates
+            .iter()
+            .zip(selected)
+            .filter(|&(_, &selected)| selected)
+            .map(|(&estimate, _)| estimate)
+            .collect()
+    });
+    (kept.into(), compressed)
+}
+
+/// Estimate the compressed size of every project, printing them unless in
+/// JSON mode.
+fn estimate_compressed_sizes(projects: &Projects, json_mode: bool) -> Vec<u64> {
+    let estimates: Vec<u64> = projects
+        .as_slice()
+        .par_iter()
+        .map(compression::estimate_compressed_size)
+        .collect();
+
+    if !json_mode {
+        print_compressed_estimates(projects, &estimates);
+    }
+
+    estimates
+}
+
+/// Print the estimated compressed size of every project, next to its size.
+fn print_compressed_estimates(projects: &Projects, estimates: &[u64]) {
+    let mut lines = vec![format!(
+        "\n{}",
+        "🗜️  Estimated size if compressed (zstd):".bold()
+    )];
+    for (project, &estimate) in projects.as_slice().iter().zip(estimates) {
+        let size = project.total_size();
+        lines.push(terminal::fit(&format!("  {project}")));
+        lines.push(format!(
+            "     {} → {} {}",
+            format_size(size),
+            format!("~{}", format_size(estimate)).bright_white(),
+            format!("({})", compression_percentage(size, estimate)).dimmed()
+        ));
+    }
+    pager::page(&lines);
+
+    let total: u64 = projects.as_slice().iter().map(Project::total_size).sum();
+    let total_estimate: u64 = estimates.iter().sum();
+    println!(
+        "  💾 Total compressed: {} {}",
+        format!("~{}", format_size(total_estimate))
+            .bright_green()
+            .bold(),
+        format!("({})", compression_percentage(total, total_estimate)).dimmed()
+    );
+}
+
+/// Format `compressed` as a percentage of `size`, e.g. `"23% of original"`.
+fn compression_percentage(size: u64, compressed: u64) -> String {
+    if size == 0 {
+        return "100% of original".to_string();
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let percent = compressed as f64 / size as f64 * 100.0;
+    format!("{percent:.0}% of original")
+}
+
+/// Print dry-run results in JSON or human-readable format, after the files
+/// of `--list-files`.
+fn print_dry_run(
+    projects: &Projects,
+    skipped: &[SkippedProject],
+    compressed: Option<&[u64]>,
+    args: &Cli,
+) -> Result<()> {
+    if let Some(query) = args.list_files() {
+        print_file_list(projects, query, args.list_files_output())?;
+    }
+    append_stats(args.append_csv(), || RunStats::dry_run(projects.as_slice()));
+    if let Some(path) = args.plan() {
+        Plan::from_projects(projects.as_slice()).save(path)?;
+        if !args.json() {
+            println!("📝 Plan written to {}", path.display());
+        }
+    }
+    if args.json() {
+        let output = JsonOutput::from_projects_dry_run(projects.as_slice())
+            .with_skipped(skipped)
+            .with_compressed_estimates(compressed);
+        print_json(&output)?;
+    } else {
+        let size = projects.get_total_size();
+        println!(
+            "\n{} {}",
+            "🧪 Dry run complete!".yellow(),
+            format!("Would free up {}", format_size(size)).bright_white()
+        );
+    }
+    Ok(())
+}
+
+/// Print (or write to `output`) every file that would be deleted for the
+/// projects matching `query`.
+///
+/// Backs `--dry-run --list-files <PROJECT>`. All projects whose name or root
+/// path match `query` are included.
+fn print_file_list(projects: &Projects, query: &str, output: Option<&Path>) -> Result<()> {
+    let matching: Vec<&Project> = projects
+        .as_slice()
+        .iter()
+        .filter(|project| project_matches(project, query))
+        .collect();
+
+    if matching.is_empty() {
+        bail!("No project matching '{query}' was found");
+    }
+
+    let files: Vec<PathBuf> = matching
+        .iter()
+        .flat_map(|project| Cleaner::list_files(project))
+        .collect();
+    let listing = files.iter().fold(String::new(), |mut listing, file| {
+        listing.push_str(&file.to_string_lossy());
+        listing.push('\n');
+        listing
+    });
+
+    if let Some(path) = output {
+        fs::write(path, listing)?;
+        println!(
+            "\n📄 Wrote {} file paths to {}",
+            files.len(),
+            path.display()
+        );
+    } else {
+        let mut lines = vec![format!(
+            "\n{}",
+            format!("📄 Files that would be deleted ({}):", files.len()).bold()
+        )];
+        lines.extend(listing.lines().map(str::to_string));
+        pager::page(&lines);
+    }
+
+    Ok(())
+}
+
+/// Check whether a project is identified by `query` (its name or root path).
+fn project_matches(project: &Project, query: &str) -> bool {
+    if project.name.as_deref() == Some(query) {
+        return true;
+    }
+
+    let query_path = Path::new(query);
+    if project.root_path == query_path {
+        return true;
+    }
+
+    matches!(
+        (fs::canonicalize(query_path), fs::canonicalize(&project.root_path)),
+        (std::result::Result::Ok(a), std::result::Result::Ok(b)) if a == b
+    )
+}
+
+/// Append the statistics of this run to the `--append-csv` file, if any.
+///
+/// The run already happened, so failing to record it only warns.
+fn append_stats(path: Option<&Path>, stats: impl FnOnce() -> RunStats) {
+    if let Some(path) = path
+        && let Err(e) = stats::append(path, &stats())
+    {
+        tracing::warn!("Failed to append statistics: {e:#}");
+    }
+}
+
+/// How the outcome of a cleanup is reported.
+#[derive(Clone, Copy)]
+#[allow(clippy::struct_excessive_bools)]
+struct Reporting<'a> {
+    /// Print a single JSON document instead of human-readable output.
+    json: bool,
+
+    /// CSV file to append the run statistics to (`--append-csv`).
+    append_csv: Option<&'a Path>,
+
+    /// List the entries left in partially cleaned projects.
+    verbose: bool,
+
+    /// List retried paths and Defender exclusions (`--windows-defender-report`).
+    defender_report: bool,
+
+    /// End with a one-line summary for logs (`--scheduled`).
+    scheduled: bool,
+}
+
+impl<'a> Reporting<'a> {
+    fn new(args: &'a Cli, verbose: bool) -> Self {
+        Self {
+            json: args.json(),
+            append_csv: args.append_csv(),
+            verbose,
+            defender_report: args.windows_defender_report(),
+            scheduled: args.scheduled(),
+        }
+    }
+}
+
+/// Perform the actual cleanup and print results.
+fn run_cleanup(
+    projects: Projects,
+    skipped: &[SkippedProject],
+    compressed: Option<&[u64]>,
+    options: &CleanOptions,
+    dirs: &[PathBuf],
+    reporting: Reporting,
+) -> Result<ExitStatus> {
+    let snapshot: Vec<_> = projects.as_slice().to_vec();
+    // Free space is only compared on a single volume
+    let space_dir = match dirs {
+        [dir] => Some(dir.as_path()),
+        _ => None,
+    };
+    let free_before = space_dir.and_then(disk::available_space);
+    // Located before cleaning, while the build directories still exist
+    let trash_dirs = trash_destinations(&snapshot, options.removal_strategy);
+    CLEANING.store(true, Ordering::SeqCst);
+    let result = clean_with_progress(projects, options, reporting.json);
+    CLEANING.store(false, Ordering::SeqCst);
+    append_stats(reporting.append_csv, || {
+        RunStats::cleanup(&snapshot, &result)
+    });
+
+    if reporting.json {
+        let output = JsonOutput::from_projects_cleanup(&snapshot, &result)
+            .with_skipped(skipped)
+            .with_compressed_estimates(compressed);
+        print_json(&output)?;
+    } else {
+        print_clean_summary(&result, "projects");
+        print_trash_destinations(&result, &trash_dirs);
+        if reporting.verbose {
+            print_leftovers(&result);
+        }
+        print_rebuild_commands(&snapshot);
+        print_interference(&result, &snapshot, reporting.defender_report);
+
+        // Trashed items still occupy the volume, so only permanent deletions
+        // are expected to show up as free space.
+        if let Some(dir) = space_dir
+            && options.removal_strategy == RemovalStrategy::Permanent
+        {
+            print_space_check(dir, free_before, result.total_freed);
+        }
+    }
+
+    record_history(
+        &result,
+        options.removal_strategy,
+        dirs.first().map(PathBuf::as_path),
+        reporting.json,
+    );
+    if reporting.scheduled {
+        report_scheduled(&result, "project", reporting.json);
+    }
+    Ok(ExitStatus::of_cleanup(&result))
+}
+
+/// The trash directory each build directory of `projects` is moved to with
+/// `strategy`; none when deleting permanently.
+fn trash_destinations(
+    projects: &[Project],
+    strategy: RemovalStrategy,
+) -> HashMap<PathBuf, PathBuf> {
+    if strategy != RemovalStrategy::Trash {
+        return HashMap::new();
+    }
+    projects
+        .iter()
+        .flat_map(|project| &project.build_arts)
+        .filter_map(|artifact| {
+            let destination = disk::trash_destination(&artifact.path)?;
+            Some((artifact.path.clone(), destination.dir))
+        })
+        .collect()
+}
+
+/// Print which trash directories the build directories removed by `result`
+/// were moved to, so that they can be found there.
+fn print_trash_destinations(result: &CleanResult, destinations: &HashMap<PathBuf, PathBuf>) {
+    let mut moved: BTreeMap<&Path, (usize, u64)> = BTreeMap::new();
+    for removed in &result.removed {
+        // Stale entries are moved from inside their build directory
+        let Some(dir) = removed
+            .artifact_path
+            .ancestors()
+            .find_map(|path| destinations.get(path))
+        else {
+            continue;
+        };
+        let (count, size) = moved.entry(dir).or_default();
+        *count += 1;
+        *size += removed.size;
+    }
+    if moved.is_empty() {
+        return;
+    }
+
+    println!("\n{}", "🗑️  Moved to the trash:".bold());
+    for (dir, (count, size)) in moved {
+        println!(
+            "  {count} {} ({}) to {}",
+            if count == 1 {
+                "directory"
+            } else {
+                "directories"
+            },
+            format_size(size),
+            dir.display()
+        );
+    }
+}
+
+/// End a scheduled run with a one-line summary of `result`, counting
+/// `noun`s, and send it to the systemd journal where there is one.
+fn report_scheduled(result: &CleanResult, noun: &str, json: bool) {
+    let summary = result.summary_line(noun);
+    let priority = if result.errors.is_empty() && result.partial.is_empty() {
+        Priority::Info
+    } else {
+        Priority::Warning
+    };
+    let fields = [
+        ("CLEAN_DEV_DIRS_CLEANED", result.success_count.to_string()),
+        ("CLEAN_DEV_DIRS_PARTIAL", result.partial.len().to_string()),
+        ("CLEAN_DEV_DIRS_FAILED", result.errors.len().to_string()),
+        ("CLEAN_DEV_DIRS_BYTES_FREED", result.total_freed.to_string()),
+    ];
+
+    let journaled = journal::is_available()
+        && journal::send("clean-dev-dirs", priority, &summary, &fields)
+            .inspect_err(|e| tracing::warn!("Failed to write to the systemd journal: {e}"))
+            .is_ok();
+    // Under systemd, stdout goes to the journal too: say it only once
+    let already_journaled = journaled && journal::stdout_is_journal();
+    if !json && !already_journaled {
+        println!("clean-dev-dirs: {summary}");
+    }
+}
+
+/// Record the cleanup of `root` (`None` for global caches) in the history,
+/// for `clean-dev-dirs history` and `clean-dev-dirs restore`.
+///
+/// The cleanup already happened, so failing to record it only warns.
+fn record_history(
+    result: &CleanResult,
+    strategy: RemovalStrategy,
+    root: Option<&Path>,
+    json: bool,
+) {
+    let Some(dir) = history::history_dir() else {
+        return;
+    };
+    // Runs cancelled before anything was attempted are not worth recording
+    if result.success_count + result.partial.len() + result.errors.len() == 0 {
+        return;
+    }
+
+    let mut manifest = Manifest::from_cleanup(result, strategy, root);
+    if manifest.trashed
+        && let Err(e) = manifest.locate_in_trash()
+    {
+        tracing::warn!("Failed to locate the trashed artifacts: {e:#}");
+    }
+    if let Err(e) = manifest.save(&dir) {
+        tracing::warn!("Failed to record the cleanup: {e:#}");
+    } else if !json {
+        let (hint, command) = if manifest.trashed && !manifest.entries.is_empty() {
+            ("undo with", "restore")
+        } else {
+            ("see the details with", "history show")
+        };
+        println!(
+            "\n📜 Recorded as run {}; {hint} {}",
+            manifest.run_id.bright_white(),
+            format!("clean-dev-dirs {command} {}", manifest.run_id).bright_white()
+        );
+    }
+}
+
+/// Print the command restoring each cleaned project, for those that need one.
+fn print_rebuild_commands(projects: &[Project]) {
+    let commands: Vec<(&Project, String)> = projects
+        .iter()
+        .filter_map(|project| Some((project, rebuild_command(project)?)))
+        .collect();
+
+    if commands.is_empty() {
+        return;
+    }
+
+    let mut lines = vec![format!(
+        "\n{}",
+        "🔁 To rebuild, run in the project directory:".bold()
+    )];
+    for (project, command) in commands {
+        lines.push(format!("  {}", terminal::fit(&format!("{project}"))));
+        lines.push(format!("     {}", command.bright_white()));
+    }
+    pager::page(&lines);
+}
+
+/// Report deletions that an antivirus scanner may have interfered with.
+///
+/// On Windows, a hint is printed when the cleanup looks slowed down by
+/// Defender. With `--windows-defender-report`, the retried paths and the
+/// exclusion commands for the affected projects are listed.
+fn print_interference(result: &CleanResult, projects: &[Project], report: bool) {
+    if cfg!(windows) && !report && defender::interference_suspected(result) {
+        println!(
+            "\n{}",
+            "⚠️  Deletion was slow or often blocked by locked files.".yellow()
+        );
+        println!("  Windows Defender real-time protection may be scanning the build directories.");
+        println!("  Run with --windows-defender-report to see the affected paths and exclusions.");
+    }
+
+    if !report {
+        return;
+    }
+
+    println!("\n{}", "🛡️  Windows Defender report:".bold());
+    if result.retried.is_empty() {
+        println!("  No deletion was interfered with.");
+        return;
+    }
+
+    for record in &result.retried {
+        let outcome = if record.recovered {
+            "recovered".green()
+        } else {
+            "failed".red()
+        };
+        println!(
+            "  {} ({} transient failures, {outcome})",
+            record.path.display(),
+            record.transient_failures
+        );
+    }
+
+    println!("\n  To exclude the affected directories, run in an elevated PowerShell:");
+    for root in defender::interfered_roots(result, projects) {
+        println!("    {}", defender::exclusion_command(&root));
+    }
+}
+
+/// Collect the global caches requested on the command line and clean them.
+fn run_cache_mode(
+    args: &Cli,
+    opts: &clean_dev_dirs::ExecutionOptions,
+    cancellation: CancellationToken,
+) -> Result<ExitStatus> {
+    let mut caches = Vec::new();
+    if args.cargo_cache() {
+        caches.extend(cache::cargo_cache_locations());
+    }
+    if args.node_cache() {
+        caches.extend(cache::node_cache_locations());
+    }
+    if args.go_cache() {
+        caches.extend(cache::go_cache_locations());
+    }
+
+    let mut tool_cleaned_caches = Vec::new();
+    if args.pnpm_prune() {
+        tool_cleaned_caches.push(CacheKind::PnpmStore);
+    }
+    if args.go_clean() {
+        tool_cleaned_caches.extend([CacheKind::GoModCache, CacheKind::GoBuildCache]);
+    }
+
+    let options = CleanOptions {
+        keep_executables: false,
+        removal_strategy: RemovalStrategy::from_use_trash(opts.use_trash),
+        cancellation,
+        tool_cleaned_caches,
+        native_clean: None,
+        stale_only: None,
+        retry: opts.retry,
+        guard: opts.guard.clone(),
+        fs: FsHandle::default(),
+        hooks: ProjectHooks::default(),
+        recheck_size: args.recheck_size(),
+        audit_log: open_audit_log(args)?,
+    };
+
+    run_cache_cleanup(caches, opts, &options, Reporting::new(args, false))
+}
+
+/// List global caches and clean the selected ones.
+///
+/// Backs `--cargo-cache`, `--node-cache` and `--go-cache`: caches are reported with their
+/// sizes, optionally narrowed down interactively, and removed (or pruned)
+/// unless `--dry-run` is set.
+fn run_cache_cleanup(
+    caches: Vec<CacheLocation>,
+    opts: &clean_dev_dirs::ExecutionOptions,
+    options: &CleanOptions,
+    reporting: Reporting,
+) -> Result<ExitStatus> {
+    let json_mode = reporting.json;
+    if caches.is_empty() {
+        if json_mode {
+            let output = JsonCacheOutput::from_caches(&[], None);
+            print_json(&output)?;
+        } else {
+            println!("{}", "✨ No global caches found!".green());
+        }
+        if reporting.scheduled {
+            report_scheduled(&CleanResult::default(), "cache", json_mode);
+        }
+        return Ok(ExitStatus::NothingFound);
+    }
+
+    if !json_mode {
+        let total_size: u64 = caches.iter().map(|cache| cache.size).sum();
+        println!("\n{}", "🗄️  Found global caches:".bold());
+        for cache in &caches {
+            println!("{}", terminal::fit(&format!("  {cache}")));
+            println!("     Size: {}", format_size(cache.size).bright_white());
+        }
+        println!(
+            "\n{} {}",
+            "💾 Total space that can be reclaimed:".bold(),
+            format_size(total_size).bright_green().bold()
+        );
+    }
+
+    let caches = if opts.interactive {
+        let selected = select_caches(caches)?;
+        if selected.is_empty() {
+            println!("{}", "✨ No caches selected for cleaning!".green());
+            return Ok(ExitStatus::Aborted);
+        }
+        selected
+    } else {
+        caches
+    };
+
+    if opts.dry_run {
+        if json_mode {
+            let output = JsonCacheOutput::from_caches(&caches, None);
+            print_json(&output)?;
+        } else {
+            let size: u64 = caches.iter().map(|cache| cache.size).sum();
+            println!(
+                "\n{} {}",
+                "🧪 Dry run complete!".yellow(),
+                format!("Would free up {}", format_size(size)).bright_white()
+            );
+        }
+        return Ok(ExitStatus::Success);
+    }
+
+    if !json_mode && !confirm_cache_cleanup(&caches, opts)? {
+        return Ok(ExitStatus::Aborted);
+    }
+
+    CLEANING.store(true, Ordering::SeqCst);
+    let result = Cleaner::clean_caches(&caches, options);
+    CLEANING.store(false, Ordering::SeqCst);
+
+    if json_mode {
+        let output = JsonCacheOutput::from_caches(&caches, Some(&result));
+        print_json(&output)?;
+    } else {
+        print_clean_summary(&result, "caches");
+        print_interference(&result, &[], reporting.defender_report);
+    }
+
+    record_history(&result, options.removal_strategy, None, json_mode);
+    if reporting.scheduled {
+        report_scheduled(&result, "cache", json_mode);
+    }
+    Ok(ExitStatus::of_cleanup(&result))
+}
+
+/// Let the user pick which global caches to clean.
+fn select_caches(caches: Vec<CacheLocation>) -> Result<Vec<CacheLocation>> {
+    let width = terminal::width().map(|width| width.saturating_sub(terminal::SELECTION_MARGIN));
+    let items: Vec<String> = caches
+        .iter()
+        .map(|cache| {
+            let prefix = format!("{} (", cache.kind.label());
+            let suffix = format!(", {})", format_size(cache.size));
+            let path = terminal::fit_path_to(
+                &cache.path.display().to_string(),
+                &format!("{prefix}{suffix}"),
+                width,
+            );
+            format!("{prefix}{path}{suffix}")
+        })
+        .collect();
+    let defaults: Vec<usize> = (0..items.len()).collect();
+
+    let selections = MultiSelect::new("Select caches to clean:", items)
+        .with_default(&defaults)
+        .raw_prompt()?;
+
+    let mut caches: Vec<Option<CacheLocation>> = caches.into_iter().map(Some).collect();
+    Ok(selections
+        .iter()
+        .filter_map(|option| caches[option.index].take())
+        .collect())
+}
+
+/// Warn when the freed bytes don't show up as free space on the volume.
+///
+/// This mostly happens on APFS, where deleted data still referenced by local
+/// snapshots is reported as purgeable space rather than free space.
+fn print_space_check(dir: &Path, free_before: Option<u64>, freed: u64) {
+    let (Some(before), Some(after)) = (free_before, disk::available_space(dir)) else {
+        return;
+    };
+
+    let SpaceCheck::NotReflected { observed, missing } = SpaceCheck::evaluate(before, after, freed)
+    else {
+        return;
+    };
+
+    println!(
+        "\n{}",
+        "⚠️  Free space did not increase as much as expected".yellow()
+    );
+    println!(
+        "  Only {} of the {} deleted shows up as free space ({} unaccounted for).",
+        format_size(observed).yellow(),
+        format_size(freed),
+        format_size(missing)
+    );
+    print_purgeable_hint(missing);
+}
+
+/// Explain APFS purgeable space and how to release it.
+#[cfg(target_os = "macos")]
+fn print_purgeable_hint(missing: u64) {
+    println!("  On APFS, deleted data still referenced by local Time Machine snapshots is");
+    println!("  reported as purgeable space and released only when macOS needs it.");
+
+    if let Some(count) = disk::local_snapshot_count().filter(|&count| count > 0) {
+        println!("  {count} local snapshot(s) found. To release the space now, run:");
+        println!(
+            "    {}",
+            format!("tmutil thinlocalsnapshots / {missing} 4").bright_white()
+        );
+    }
+}
+
+/// Explain why free space may lag behind the deleted bytes.
+#[cfg(not(target_os = "macos"))]
+fn print_purgeable_hint(_missing: u64) {
+    println!("  Other processes may have written to the volume in the meantime, or the");
+    println!("  filesystem may release the space lazily (e.g. snapshots or open file handles).");
+}
@@ -0,0 +1,230 @@
+//! Space used by each package inside a `node_modules/` directory.
+//!
+//! Backs the `why-big` subcommand for Node.js projects. Every copy of a
+//! package is found, whether installed at the top level, nested in another
+//! package's `node_modules/`, or in pnpm's `.pnpm/` store, and the sizes of
+//! the copies of the same package are added up. A copy's size excludes its
+//! own nested `node_modules/`, which is attributed to the packages inside.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::sweep;
+
+/// Space used by every copy of one package.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackageUsage {
+    /// Name of the package, including its scope (e.g. `@babel/core`).
+    pub name: String,
+
+    /// Total bytes used by all copies.
+    pub size: u64,
+
+    /// Number of copies installed.
+    pub copies: usize,
+}
+
+/// Space used by a `node_modules/` directory, attributed to packages.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeModulesUsage {
+    /// Usage of each package, largest first.
+    pub packages: Vec<PackageUsage>,
+
+    /// Total size of the `node_modules/` directory, in bytes.
+    pub total: u64,
+}
+
+impl NodeModulesUsage {
+    /// Bytes not attributed to any package (e.g. `.bin/` or caches).
+    #[must_use]
+    pub fn unattributed(&self) -> u64 {
+        let attributed: u64 = self.packages.iter().map(|package| package.size).sum();
+        self.total.saturating_sub(attributed)
+    }
+}
+
+/// Attribute the space used by the `node_modules/` directory `node_modules`
+/// to packages.
+#[must_use]
+pub fn analyze(node_modules: &Path) -> NodeModulesUsage {
+    let mut packages = HashMap::new();
+    collect_packages(node_modules, &mut packages);
+
+    let mut packages: Vec<PackageUsage> = packages.into_values().collect();
+    packages.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+    NodeModulesUsage {
+        packages,
+        total: sweep::entry_size(node_modules),
+    }
+}
+
+/// Add the packages installed in `node_modules` (and, recursively, in their
+/// own `node_modules/`) to `packages`.
+///
+/// Symlinked packages (workspace links, pnpm's top level) are skipped: their
+/// target is either outside `node_modules` or found in the `.pnpm/` store.
+fn collect_packages(node_modules: &Path, packages: &mut HashMap<String, PackageUsage>) {
+    for (name, path) in package_dirs(node_modules) {
+        let usage = packages
+            .entry(name.clone())
+            .or_insert_with(|| PackageUsage {
+                name,
+                ..PackageUsage::default()
+            });
+        usage.size += package_size(&path);
+        usage.copies += 1;
+
+        collect_packages(&path.join("node_modules"), packages);
+    }
+
+    // pnpm keeps the actual packages in `.pnpm/<name>@<version>/node_modules/`
+    for store_entry in read_dirs(&node_modules.join(".pnpm")) {
+        collect_packages(&store_entry.join("node_modules"), packages);
+    }
+}
+
+/// The package directories directly inside `node_modules`, with their
+/// names; scoped packages are one level deeper (`@scope/name`).
+fn package_dirs(node_modules: &Path) -> Vec<(String, PathBuf)> {
+    let mut dirs = Vec::new();
+    for path in read_dirs(node_modules) {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let name = name.to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        if name.starts_with('@') {
+            for scoped in read_dirs(&path) {
+                if let Some(package) = scoped.file_name().and_then(|name| name.to_str()) {
+                    dirs.push((format!("{name}/{package}"), scoped.clone()));
+                }
+            }
+        } else {
+            dirs.push((name, path));
+        }
+    }
+    dirs
+}
+
+/// The subdirectories of `dir`, not following symlinks.
+fn read_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| entry.path())
+        .collect()
+}
+
+/// Size of a package directory, without its nested `node_modules/`.
+fn package_size(package: &Path) -> u64 {
+    WalkDir::new(package)
+        .into_iter()
+        .filter_entry(|entry| !(entry.depth() == 1 && entry.file_name() == "node_modules"))
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    fn write(path: &Path, len: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; len]).unwrap();
+    }
+
+    #[test]
+    fn test_analyze_sums_nested_copies() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_modules = temp_dir.path().join("node_modules");
+        write(&node_modules.join("lodash/lodash.js"), 500);
+        write(&node_modules.join("react/index.js"), 300);
+        write(
+            &node_modules.join("react/node_modules/lodash/lodash.js"),
+            400,
+        );
+        write(&node_modules.join("@babel/core/lib/index.js"), 200);
+        write(&node_modules.join(".bin/tsc"), 10);
+        write(&node_modules.join(".package-lock.json"), 20);
+
+        let usage = analyze(&node_modules);
+
+        let summary: Vec<(&str, u64, usize)> = usage
+            .packages
+            .iter()
+            .map(|package| (package.name.as_str(), package.size, package.copies))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("lodash", 900, 2),
+                ("react", 300, 1),
+                ("@babel/core", 200, 1)
+            ]
+        );
+        assert_eq!(usage.total, 1430);
+        assert_eq!(usage.unattributed(), 30);
+    }
+
+    #[test]
+    fn test_analyze_pnpm_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_modules = temp_dir.path().join("node_modules");
+        let store = node_modules.join(".pnpm");
+        write(
+            &store.join("lodash@4.17.21/node_modules/lodash/lodash.js"),
+            500,
+        );
+        write(
+            &store.join("lodash@3.10.1/node_modules/lodash/lodash.js"),
+            400,
+        );
+        write(
+            &store.join("@types+node@20.0.0/node_modules/@types/node/index.d.ts"),
+            100,
+        );
+
+        let usage = analyze(&node_modules);
+
+        assert_eq!(usage.packages[0].name, "lodash");
+        assert_eq!(usage.packages[0].size, 900);
+        assert_eq!(usage.packages[0].copies, 2);
+        assert_eq!(usage.packages[1].name, "@types/node");
+        assert_eq!(usage.unattributed(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_analyze_skips_symlinked_packages() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_modules = temp_dir.path().join("node_modules");
+        write(
+            &node_modules.join(".pnpm/lodash@4.17.21/node_modules/lodash/lodash.js"),
+            500,
+        );
+        std::os::unix::fs::symlink(
+            node_modules.join(".pnpm/lodash@4.17.21/node_modules/lodash"),
+            node_modules.join("lodash"),
+        )
+        .unwrap();
+
+        let usage = analyze(&node_modules);
+
+        assert_eq!(usage.packages.len(), 1);
+        assert_eq!(usage.packages[0].copies, 1);
+    }
+}
@@ -7,10 +7,29 @@
 
 use std::collections::BTreeMap;
 
-use humansize::{DECIMAL, format_size};
 use serde::Serialize;
 
-use crate::project::{Project, ProjectType};
+use crate::cache::{CacheKind, CacheLocation};
+use crate::cleaner::{CleanResult, PartialClean};
+use crate::filtering::{SkipReason, SkippedProject};
+use crate::project::{ArtifactCategory, Project, ProjectType, detect_workspaces, rebuild_command};
+use crate::utils::format_size;
+use crate::utils::retry::RetryRecord;
+
+/// Format of the results, chosen with `--output`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OutputFormat {
+    /// Human-readable text (the default)
+    #[default]
+    Text,
+
+    /// A single JSON document once done, like --json
+    Json,
+
+    /// One JSON object per line for each project, printed as soon as it is sized
+    Ndjson,
+}
 
 /// Top-level JSON output emitted when `--json` is active.
 #[derive(Serialize)]
@@ -21,6 +40,9 @@ pub struct JsonOutput {
     /// List of projects that were found (and matched filters).
     pub projects: Vec<JsonProjectEntry>,
 
+    /// Projects that were found but filtered out, each with a reason code.
+    pub skipped: Vec<JsonSkippedEntry>,
+
     /// Aggregated summary statistics.
     pub summary: JsonSummary,
 
@@ -36,21 +58,62 @@ pub struct JsonProjectEntry {
     /// Project name extracted from config files, or `null`.
     pub name: Option<String>,
 
-    /// Project type (`"rust"`, `"node"`, `"python"`, `"go"`, `"java"`, `"cpp"`, `"swift"`, `"dot_net"`).
+    /// Project type (`"rust"`, `"node"`, `"python"`, `"go"`, `"java"`, `"cpp"`, `"swift"`, `"dot_net"`, `"ocaml"`, `"unity"`, `"unreal"`).
     #[serde(rename = "type")]
     pub project_type: ProjectType,
 
     /// Absolute path to the project root directory.
     pub root_path: String,
 
-    /// Absolute path to the build artifacts directory.
+    /// Absolute path to the (first) build artifacts directory.
     pub build_artifacts_path: String,
 
-    /// Size of the build artifacts in bytes.
+    /// Total size of all build artifacts in bytes.
     pub build_artifacts_size: u64,
 
     /// Human-readable formatted size (e.g. `"1.23 GB"`).
     pub build_artifacts_size_formatted: String,
+
+    /// Every build artifacts directory that is cleaned for this project.
+    pub build_artifacts: Vec<JsonArtifactEntry>,
+
+    /// Estimated size of the build artifacts once compressed with zstd, in
+    /// bytes. Present only with `--estimate-compressed`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compressed_size_estimate: Option<u64>,
+
+    /// Command restoring the project after cleaning, to be run in its root
+    /// directory (e.g. `"npm ci"`). Present only after a cleanup, for
+    /// projects that need one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rebuild_command: Option<String>,
+}
+
+/// A single build artifacts directory in the JSON output.
+#[derive(Serialize)]
+pub struct JsonArtifactEntry {
+    /// Absolute path to the build artifacts directory.
+    pub path: String,
+
+    /// Size of the directory in bytes.
+    pub size: u64,
+
+    /// Human-readable formatted size.
+    pub size_formatted: String,
+
+    /// Whether the artifact is a build output or a generic tool cache.
+    pub category: ArtifactCategory,
+}
+
+/// A project that was filtered out, with a machine-readable reason code.
+#[derive(Serialize)]
+pub struct JsonSkippedEntry {
+    /// The skipped project.
+    #[serde(flatten)]
+    pub project: JsonProjectEntry,
+
+    /// Why the project was skipped (e.g. `"SKIPPED_BY_SIZE"`, `"UNOWNED"`).
+    pub reason: SkipReason,
 }
 
 /// Aggregated summary across all matched projects.
@@ -67,6 +130,53 @@ pub struct JsonSummary {
 
     /// Per-type breakdown (key is the project type name).
     pub by_type: BTreeMap<String, JsonTypeSummary>,
+
+    /// Rollups of the workspaces (monorepos) containing several of the
+    /// projects. Omitted when there are none.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub workspaces: Vec<JsonWorkspaceSummary>,
+}
+
+/// Rollup of a workspace and its member projects.
+#[derive(Serialize)]
+pub struct JsonWorkspaceSummary {
+    /// Workspace name (its root directory name).
+    pub name: String,
+
+    /// Absolute path to the workspace root.
+    pub root_path: String,
+
+    /// Number of member projects.
+    pub member_count: usize,
+
+    /// Total size of the members' build artifacts in bytes.
+    pub total_size: u64,
+
+    /// Human-readable formatted total size.
+    pub total_size_formatted: String,
+
+    /// Per-member breakdown.
+    pub members: Vec<JsonWorkspaceMember>,
+}
+
+/// A member project of a workspace rollup.
+#[derive(Serialize)]
+pub struct JsonWorkspaceMember {
+    /// Project name extracted from config files, or `null`.
+    pub name: Option<String>,
+
+    /// Project type.
+    #[serde(rename = "type")]
+    pub project_type: ProjectType,
+
+    /// Absolute path to the project root directory.
+    pub root_path: String,
+
+    /// Total size of the project's build artifacts in bytes.
+    pub size: u64,
+
+    /// Human-readable formatted size.
+    pub size_formatted: String,
 }
 
 /// Per-project-type count and size.
@@ -99,6 +209,92 @@ pub struct JsonCleanupResult {
 
     /// Error messages for projects that failed.
     pub errors: Vec<String>,
+
+    /// Projects whose build directories could only be partly removed, with
+    /// the entries left behind.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub partially_cleaned: Vec<PartialClean>,
+
+    /// Number of projects left untouched because the cleanup was cancelled.
+    pub cancelled_count: usize,
+
+    /// Projects left untouched because their `pre_project` hook failed, with
+    /// the reason. Omitted when there are none.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,
+
+    /// Paths whose removal failed transiently and was retried. Omitted when
+    /// there are none.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub retried_paths: Vec<RetryRecord>,
+}
+
+/// JSON output emitted for global cache modes (e.g. `--cargo-cache`).
+#[derive(Serialize)]
+pub struct JsonCacheOutput {
+    /// The execution mode: `"dry_run"` or `"cleanup"`.
+    pub mode: String,
+
+    /// Global caches that were found.
+    pub caches: Vec<JsonCacheEntry>,
+
+    /// Total size of all caches in bytes.
+    pub total_size: u64,
+
+    /// Human-readable formatted total size.
+    pub total_size_formatted: String,
+
+    /// Cleanup results. Present only when an actual cleanup was performed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cleanup: Option<JsonCleanupResult>,
+}
+
+/// A single global cache entry in the JSON output.
+#[derive(Serialize)]
+pub struct JsonCacheEntry {
+    /// Cache kind (e.g. `"cargo_registry_cache"`, `"sccache"`).
+    pub kind: CacheKind,
+
+    /// Absolute path to the cache directory.
+    pub path: String,
+
+    /// Size of the cache in bytes.
+    pub size: u64,
+
+    /// Human-readable formatted size.
+    pub size_formatted: String,
+}
+
+impl JsonCacheOutput {
+    /// Build a `JsonCacheOutput` from the given caches.
+    ///
+    /// Pass the [`CleanResult`] when the caches were actually cleaned, or
+    /// `None` for a dry run.
+    #[must_use]
+    pub fn from_caches(caches: &[CacheLocation], clean_result: Option<&CleanResult>) -> Self {
+        let total_size = caches.iter().map(|cache| cache.size).sum();
+
+        Self {
+            mode: if clean_result.is_some() {
+                "cleanup"
+            } else {
+                "dry_run"
+            }
+            .to_string(),
+            caches: caches
+                .iter()
+                .map(|cache| JsonCacheEntry {
+                    kind: cache.kind,
+                    path: cache.path.display().to_string(),
+                    size: cache.size,
+                    size_formatted: format_size(cache.size),
+                })
+                .collect(),
+            total_size,
+            total_size_formatted: format_size(total_size),
+            cleanup: clean_result.map(JsonCleanupResult::from_clean_result),
+        }
+    }
 }
 
 impl JsonOutput {
@@ -111,6 +307,7 @@ impl JsonOutput {
                 .iter()
                 .map(JsonProjectEntry::from_project)
                 .collect(),
+            skipped: Vec::new(),
             summary: JsonSummary::from_projects(projects),
             cleanup: None,
         }
@@ -118,20 +315,47 @@ impl JsonOutput {
 
     /// Build a `JsonOutput` from a slice of projects after a cleanup operation.
     #[must_use]
-    pub fn from_projects_cleanup(
-        projects: &[Project],
-        clean_result: &crate::cleaner::CleanResult,
-    ) -> Self {
+    pub fn from_projects_cleanup(projects: &[Project], clean_result: &CleanResult) -> Self {
         Self {
             mode: "cleanup".to_string(),
             projects: projects
                 .iter()
-                .map(JsonProjectEntry::from_project)
+                .map(|project| JsonProjectEntry {
+                    rebuild_command: rebuild_command(project),
+                    ..JsonProjectEntry::from_project(project)
+                })
                 .collect(),
+            skipped: Vec::new(),
             summary: JsonSummary::from_projects(projects),
             cleanup: Some(JsonCleanupResult::from_clean_result(clean_result)),
         }
     }
+
+    /// Attach compressed size estimates, one per entry in `projects`, in order.
+    ///
+    /// Passing `None` leaves the output unchanged.
+    #[must_use]
+    pub fn with_compressed_estimates(mut self, estimates: Option<&[u64]>) -> Self {
+        if let Some(estimates) = estimates {
+            for (entry, &estimate) in self.projects.iter_mut().zip(estimates) {
+                entry.compressed_size_estimate = Some(estimate);
+            }
+        }
+        self
+    }
+
+    /// Attach the projects that were filtered out, with their reason codes.
+    #[must_use]
+    pub fn with_skipped(mut self, skipped: &[SkippedProject]) -> Self {
+        self.skipped = skipped
+            .iter()
+            .map(|skipped| JsonSkippedEntry {
+                project: JsonProjectEntry::from_project(&skipped.project),
+                reason: skipped.reason,
+            })
+            .collect();
+        self
+    }
 }
 
 impl JsonProjectEntry {
@@ -142,9 +366,25 @@ impl JsonProjectEntry {
             name: project.name.clone(),
             project_type: project.kind.clone(),
             root_path: project.root_path.display().to_string(),
-            build_artifacts_path: project.build_arts.path.display().to_string(),
-            build_artifacts_size: project.build_arts.size,
-            build_artifacts_size_formatted: format_size(project.build_arts.size, DECIMAL),
+            build_artifacts_path: project
+                .artifact_paths()
+                .next()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            build_artifacts_size: project.total_size(),
+            build_artifacts_size_formatted: format_size(project.total_size()),
+            build_artifacts: project
+                .build_arts
+                .iter()
+                .map(|artifact| JsonArtifactEntry {
+                    path: artifact.path.display().to_string(),
+                    size: artifact.size,
+                    size_formatted: format_size(artifact.size),
+                    category: artifact.category(),
+                })
+                .collect(),
+            compressed_size_estimate: None,
+            rebuild_command: None,
         }
     }
 }
@@ -165,19 +405,22 @@ impl JsonSummary {
                 ProjectType::Cpp => "cpp",
                 ProjectType::Swift => "swift",
                 ProjectType::DotNet => "dotnet",
+                ProjectType::OCaml => "ocaml",
+                ProjectType::Unity => "unity",
+                ProjectType::Unreal => "unreal",
             };
 
             let entry = by_type.entry(key.to_string()).or_insert((0, 0));
             entry.0 += 1;
-            entry.1 += project.build_arts.size;
+            entry.1 += project.total_size();
         }
 
-        let total_size: u64 = projects.iter().map(|p| p.build_arts.size).sum();
+        let total_size: u64 = projects.iter().map(Project::total_size).sum();
 
         Self {
             total_projects: projects.len(),
             total_size,
-            total_size_formatted: format_size(total_size, DECIMAL),
+            total_size_formatted: format_size(total_size),
             by_type: by_type
                 .into_iter()
                 .map(|(k, (count, size))| {
@@ -186,11 +429,43 @@ impl JsonSummary {
                         JsonTypeSummary {
                             count,
                             size,
-                            size_formatted: format_size(size, DECIMAL),
+                            size_formatted: format_size(size),
                         },
                     )
                 })
                 .collect(),
+            workspaces: detect_workspaces(projects)
+                .into_iter()
+                .map(|workspace| {
+                    let total_size = workspace.total_size(projects);
+                    JsonWorkspaceSummary {
+                        name: workspace.name(),
+                        root_path: workspace.root.display().to_string(),
+                        member_count: workspace.members.len(),
+                        total_size,
+                        total_size_formatted: format_size(total_size),
+                        members: workspace
+                            .members
+                            .iter()
+                            .map(|&member| JsonWorkspaceMember::from_project(&projects[member]))
+                            .collect(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl JsonWorkspaceMember {
+    /// Convert a `Project` into a `JsonWorkspaceMember`.
+    #[must_use]
+    pub fn from_project(project: &Project) -> Self {
+        Self {
+            name: project.name.clone(),
+            project_type: project.kind.clone(),
+            root_path: project.root_path.display().to_string(),
+            size: project.total_size(),
+            size_formatted: format_size(project.total_size()),
         }
     }
 }
@@ -198,13 +473,17 @@ impl JsonSummary {
 impl JsonCleanupResult {
     /// Convert a `CleanResult` into a `JsonCleanupResult`.
     #[must_use]
-    pub fn from_clean_result(result: &crate::cleaner::CleanResult) -> Self {
+    pub fn from_clean_result(result: &CleanResult) -> Self {
         Self {
             success_count: result.success_count,
             failure_count: result.errors.len(),
             total_freed: result.total_freed,
-            total_freed_formatted: format_size(result.total_freed, DECIMAL),
+            total_freed_formatted: format_size(result.total_freed),
             errors: result.errors.clone(),
+            partially_cleaned: result.partial.clone(),
+            cancelled_count: result.cancelled_count,
+            skipped: result.skipped.clone(),
+            retried_paths: result.retried.clone(),
         }
     }
 }
@@ -0,0 +1,442 @@
+//! Inline multi-select picker with fuzzy search.
+//!
+//! Backs `--interactive`. Compared to a plain multi-select prompt, typing
+//! filters the list with fuzzy matching (against each option's search text,
+//! e.g. a project's name and path), and the listed options can be selected,
+//! deselected or inverted at once, which keeps big scans manageable.
+//!
+//! Options are listed in groups, each under a header that toggles the whole
+//! group.
+//!
+//! # Keys
+//!
+//! | Key | Action |
+//! |-----|--------|
+//! | any character | Filter the list (fuzzy) |
+//! | `↑`/`↓`, `PgUp`/`PgDn`, `Home`/`End` | Move the cursor |
+//! | `Space` | Select or deselect the option (or group) under the cursor |
+//! | `→`, `Ctrl+A` | Select all listed options |
+//! | `←`, `Ctrl+D` | Deselect all listed options |
+//! | `Tab` | Invert the selection of the listed options |
+//! | `Enter` | Confirm |
+//! | `Esc`, `Ctrl+C` | Cancel |
+
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use ratatui::{
+    Frame, TerminalOptions, Viewport,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState},
+};
+
+use crate::error::{Error, Result};
+
+/// Maximum number of options shown at once.
+const MAX_LISTED: usize = 15;
+
+/// Help line shown below the options.
+const HELP: &str = "type to filter · space toggle · → all · ← none · tab invert · enter confirm";
+
+/// An option of the picker.
+pub struct PickerItem {
+    /// Text shown for the option.
+    pub label: String,
+
+    /// Text the filter is matched against (e.g. a project's name and path).
+    pub search: String,
+}
+
+/// A group of options, listed under a header.
+pub struct PickerGroup {
+    /// Text shown in the group's header.
+    pub label: String,
+
+    /// Options of the group.
+    pub items: Vec<PickerItem>,
+}
+
+/// Let the user select options, all of them selected initially.
+///
+/// Options are numbered across groups, in order. Returns the indices of the
+/// selected options, in order.
+///
+/// # Errors
+///
+/// Returns an error if the terminal cannot be used, or if the user cancels.
+pub fn pick(prompt: &str, groups: Vec<PickerGroup>) -> Result<Vec<usize>> {
+    let mut picker = Picker::new(groups);
+    let height = picker.rows.len().min(MAX_LISTED) + 2;
+    let mut terminal = ratatui::try_init_with_options(TerminalOptions {
+        viewport: Viewport::Inline(u16::try_from(height).unwrap_or(u16::MAX)),
+    })?;
+
+    let outcome = loop {
+        if let Err(error) = terminal.draw(|frame| picker.render(frame, prompt)) {
+            break Err(error);
+        }
+
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                match picker.handle_key(key) {
+                    Outcome::Continue => {}
+                    outcome => break Ok(outcome),
+                }
+            }
+            Ok(_) => {}
+            Err(error) => break Err(error),
+        }
+    };
+
+    terminal.clear()?;
+    ratatui::try_restore()?;
+
+    if outcome? == Outcome::Cancel {
+        return Err(Error::SelectionCancelled);
+    }
+
+    let selected = picker.selected();
+    println!("? {prompt} {} selected", selected.len());
+    Ok(selected)
+}
+
+/// Result of handling a key press.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Continue,
+    Confirm,
+    Cancel,
+}
+
+/// A line of the list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Row {
+    /// Header of the group at this index.
+    Header(usize),
+    /// Option at this index.
+    Item(usize),
+}
+
+/// State of the picker.
+struct Picker {
+    headers: Vec<String>,
+    items: Vec<PickerItem>,
+    /// Group of each option.
+    group_of: Vec<usize>,
+    checked: Vec<bool>,
+    filter: String,
+    /// Listed lines: each group with listed options, followed by them (best
+    /// matches first).
+    rows: Vec<Row>,
+    list: ListState,
+    matcher: SkimMatcherV2,
+}
+
+impl Picker {
+    fn new(groups: Vec<PickerGroup>) -> Self {
+        let mut headers = Vec::new();
+        let mut items = Vec::new();
+        let mut group_of = Vec::new();
+        for (g, group) in groups.into_iter().enumerate() {
+            headers.push(group.label);
+            group_of.extend(std::iter::repeat_n(g, group.items.len()));
+            items.extend(group.items);
+        }
+
+        let mut picker = Self {
+            headers,
+            checked: vec![true; items.len()],
+            items,
+            group_of,
+            filter: String::new(),
+            rows: Vec::new(),
+            list: ListState::default(),
+            matcher: SkimMatcherV2::default(),
+        };
+        picker.refresh();
+        picker
+    }
+
+    /// Recompute the listed lines after the filter changed.
+    fn refresh(&mut self) {
+        let mut scored: Vec<(usize, i64, usize)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| {
+                let score = if self.filter.is_empty() {
+                    0
+                } else {
+                    self.matcher.fuzzy_match(&item.search, &self.filter)?
+                };
+                Some((self.group_of[i], score, i))
+            })
+            .collect();
+        scored.sort_by_key(|&(g, score, i)| (g, std::cmp::Reverse(score), i));
+
+        self.rows.clear();
+        for (g, _, i) in scored {
+            if self
+                .rows
+                .last()
+                .is_none_or(|&row| self.group_of_row(row) != g)
+            {
+                self.rows.push(Row::Header(g));
+            }
+            self.rows.push(Row::Item(i));
+        }
+
+        self.list.select((!self.rows.is_empty()).then_some(0));
+    }
+
+    fn group_of_row(&self, row: Row) -> usize {
+        match row {
+            Row::Header(g) => g,
+            Row::Item(i) => self.group_of[i],
+        }
+    }
+
+    fn selected(&self) -> Vec<usize> {
+        (0..self.items.len()).filter(|&i| self.checked[i]).collect()
+    }
+
+    /// Listed options, of the group `group` only if given.
+    fn listed(&self, group: Option<usize>) -> Vec<usize> {
+        self.rows
+            .iter()
+            .filter_map(|&row| match row {
+                Row::Item(i) if group.is_none_or(|g| self.group_of[i] == g) => Some(i),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let cursor = self.list.selected().unwrap_or(0);
+        let last = self.rows.len() - 1;
+        self.list
+            .select(Some(cursor.saturating_add_signed(delta).min(last)));
+    }
+
+    fn update_listed(&mut self, update: impl Fn(bool) -> bool) {
+        for i in self.listed(None) {
+            self.checked[i] = update(self.checked[i]);
+        }
+    }
+
+    /// Toggle the line under the cursor. A header selects the listed options
+    /// of its group, or deselects them if they were all selected.
+    fn toggle(&mut self) {
+        match self.list.selected().and_then(|row| self.rows.get(row)) {
+            Some(&Row::Item(i)) => self.checked[i] = !self.checked[i],
+            Some(&Row::Header(g)) => {
+                let members = self.listed(Some(g));
+                let select = !members.iter().all(|&i| self.checked[i]);
+                for i in members {
+                    self.checked[i] = select;
+                }
+            }
+            None => {}
+        }
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Char('c') if control => return Outcome::Cancel,
+            KeyCode::Esc => return Outcome::Cancel,
+            KeyCode::Enter => return Outcome::Confirm,
+            KeyCode::Up => self.move_cursor(-1),
+            KeyCode::Down => self.move_cursor(1),
+            KeyCode::PageUp => self.move_cursor(-(MAX_LISTED as isize)),
+            KeyCode::PageDown => self.move_cursor(MAX_LISTED as isize),
+            KeyCode::Home => self.move_cursor(isize::MIN),
+            KeyCode::End => self.move_cursor(isize::MAX),
+            KeyCode::Char(' ') => self.toggle(),
+            KeyCode::Right => self.update_listed(|_| true),
+            KeyCode::Char('a') if control => self.update_listed(|_| true),
+            KeyCode::Left => self.update_listed(|_| false),
+            KeyCode::Char('d') if control => self.update_listed(|_| false),
+            KeyCode::Tab => self.update_listed(|checked| !checked),
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.refresh();
+            }
+            KeyCode::Char(c) if !control => {
+                self.filter.push(c);
+                self.refresh();
+            }
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    fn render(&mut self, frame: &mut Frame, prompt: &str) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let selected = self.checked.iter().filter(|&&checked| checked).count();
+        frame.render_widget(
+            Line::from(vec![
+                Span::from("? ").green(),
+                Span::from(prompt).bold(),
+                Span::from(format!(" {}", self.filter)),
+                Span::from(format!("  ({selected}/{} selected)", self.items.len())).dim(),
+            ]),
+            header,
+        );
+
+        let items = self.rows.iter().map(|&row| match row {
+            Row::Header(g) => {
+                let members = self.listed(Some(g));
+                let selected = members.iter().filter(|&&i| self.checked[i]).count();
+                let mark = match selected {
+                    0 => "[ ] ",
+                    n if n == members.len() => "[x] ",
+                    _ => "[-] ",
+                };
+                ListItem::new(Line::from(format!("{mark}{}", self.headers[g]).bold()))
+            }
+            Row::Item(i) => {
+                let mark = if self.checked[i] { "[x] " } else { "[ ] " };
+                ListItem::new(format!("  {mark}{}", self.items[i].label))
+            }
+        });
+        let list = List::new(items)
+            .highlight_symbol("> ")
+            .highlight_style(Style::new().add_modifier(Modifier::BOLD).cyan());
+        frame.render_stateful_widget(list, body, &mut self.list);
+
+        frame.render_widget(Line::from(HELP).dim(), footer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(label: &str, searches: &[&str]) -> PickerGroup {
+        PickerGroup {
+            label: label.to_string(),
+            items: searches
+                .iter()
+                .map(|search| PickerItem {
+                    label: (*search).to_string(),
+                    search: (*search).to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn picker(searches: &[&str]) -> Picker {
+        Picker::new(vec![group("All", searches)])
+    }
+
+    fn press(picker: &mut Picker, code: KeyCode) -> Outcome {
+        picker.handle_key(KeyEvent::from(code))
+    }
+
+    fn type_filter(picker: &mut Picker, filter: &str) {
+        for c in filter.chars() {
+            press(picker, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_filter() {
+        let mut picker = picker(&["api /dev/api", "web /dev/web-app", "tools /opt/tools"]);
+
+        type_filter(&mut picker, "dvwb");
+        assert_eq!(picker.listed(None), [1]);
+
+        press(&mut picker, KeyCode::Backspace);
+        press(&mut picker, KeyCode::Backspace);
+        assert_eq!(picker.listed(None), [0, 1]);
+    }
+
+    #[test]
+    fn test_select_all_none_and_invert_apply_to_listed_options() {
+        let mut picker = picker(&["api", "web", "tools"]);
+
+        press(&mut picker, KeyCode::Left);
+        assert!(picker.selected().is_empty());
+
+        type_filter(&mut picker, "ap");
+        press(&mut picker, KeyCode::Right);
+        assert_eq!(picker.selected(), [0]);
+
+        press(&mut picker, KeyCode::Backspace);
+        press(&mut picker, KeyCode::Backspace);
+        press(&mut picker, KeyCode::Tab);
+        assert_eq!(picker.selected(), [1, 2]);
+
+        picker.handle_key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL));
+        assert_eq!(picker.selected(), [0, 1, 2]);
+        picker.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+        assert!(picker.selected().is_empty());
+    }
+
+    #[test]
+    fn test_toggle_and_confirm() {
+        let mut picker = picker(&["api", "web"]);
+
+        // The first line is the group header
+        press(&mut picker, KeyCode::Down);
+        press(&mut picker, KeyCode::Down);
+        press(&mut picker, KeyCode::Char(' '));
+        assert_eq!(picker.selected(), [0]);
+        assert_eq!(press(&mut picker, KeyCode::Enter), Outcome::Confirm);
+        assert_eq!(press(&mut picker, KeyCode::Esc), Outcome::Cancel);
+    }
+
+    #[test]
+    fn test_groups_are_listed_under_headers() {
+        let mut picker = Picker::new(vec![
+            group("Rust", &["api", "cli"]),
+            group("Node", &["web"]),
+        ]);
+        assert_eq!(
+            picker.rows,
+            [
+                Row::Header(0),
+                Row::Item(0),
+                Row::Item(1),
+                Row::Header(1),
+                Row::Item(2)
+            ]
+        );
+
+        // Headers of groups without matches are hidden
+        type_filter(&mut picker, "web");
+        assert_eq!(picker.rows, [Row::Header(1), Row::Item(2)]);
+    }
+
+    #[test]
+    fn test_header_toggles_its_group() {
+        let mut picker = Picker::new(vec![
+            group("Rust", &["api", "cli"]),
+            group("Node", &["web"]),
+        ]);
+
+        press(&mut picker, KeyCode::Char(' '));
+        assert_eq!(picker.selected(), [2]);
+
+        // A partially selected group is selected entirely
+        press(&mut picker, KeyCode::Down);
+        press(&mut picker, KeyCode::Char(' '));
+        press(&mut picker, KeyCode::Up);
+        press(&mut picker, KeyCode::Char(' '));
+        assert_eq!(picker.selected(), [0, 1, 2]);
+    }
+}
@@ -0,0 +1,336 @@
+//! Cleanup plans: a dry run written to a file, and applied later.
+//!
+//! `--dry-run --plan FILE` writes the projects that would be cleaned and
+//! their artifacts, with sizes, to a JSON file that can be reviewed or
+//! approved before `apply FILE` cleans them. Since the tree may have changed
+//! in between, each project is checked again before cleaning: it must still
+//! be detected as a project of the same type (so its marker file is still
+//! there), and each artifact must still exist with a size close to the
+//! planned one. Projects that fail a check are left untouched.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::project::{BuildArtifacts, Project, ProjectType, projects::TYPE_ENTRIES};
+use crate::utils::format_size;
+
+/// Version of the plan file format.
+pub const PLAN_VERSION: u32 = 1;
+
+/// How far the size of an artifact may drift from the planned size, as a
+/// fraction of it, before the project counts as changed.
+pub const SIZE_TOLERANCE: f64 = 0.2;
+
+/// The projects a dry run would have cleaned.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    /// Version of the file format, [`PLAN_VERSION`].
+    pub version: u32,
+
+    /// Time the plan was written.
+    pub created_at: DateTime<Utc>,
+
+    /// The projects to clean.
+    pub projects: Vec<PlannedProject>,
+}
+
+/// A project to clean, as recorded in a [`Plan`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedProject {
+    /// Name of the project, if known.
+    pub name: Option<String>,
+
+    /// Type of the project.
+    #[serde(rename = "type")]
+    pub kind: ProjectType,
+
+    /// Absolute path of the project root.
+    pub root_path: PathBuf,
+
+    /// The artifacts to remove.
+    pub artifacts: Vec<PlannedArtifact>,
+}
+
+/// An artifact to remove, as recorded in a [`Plan`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedArtifact {
+    /// Absolute path of the artifact.
+    pub path: PathBuf,
+
+    /// Size of the artifact when the plan was written, in bytes.
+    pub size: u64,
+}
+
+/// Why a planned project is no longer cleaned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The project root is gone, or no longer holds a project (its marker
+    /// file was removed).
+    NotFound,
+
+    /// The project is now detected as another type.
+    TypeChanged(ProjectType),
+
+    /// An artifact is gone.
+    ArtifactGone(PathBuf),
+
+    /// The size of an artifact drifted by more than [`SIZE_TOLERANCE`].
+    SizeChanged {
+        /// The artifact.
+        path: PathBuf,
+
+        /// Its size in the plan, in bytes.
+        planned: u64,
+
+        /// Its size now, in bytes.
+        current: u64,
+    },
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => f.write_str("no longer a project"),
+            Self::TypeChanged(kind) => {
+                let label = TYPE_ENTRIES
+                    .iter()
+                    .find(|(entry_kind, _, _)| entry_kind == kind)
+                    .map_or("another", |&(_, _, label)| label);
+                write!(f, "now detected as a {label} project")
+            }
+            Self::ArtifactGone(path) => write!(f, "{} is gone", path.display()),
+            Self::SizeChanged {
+                path,
+                planned,
+                current,
+            } => write!(
+                f,
+                "{} changed size ({} → {})",
+                path.display(),
+                format_size(*planned),
+                format_size(*current)
+            ),
+        }
+    }
+}
+
+impl Plan {
+    /// The plan of cleaning `projects`, with their paths made absolute.
+    #[must_use]
+    pub fn from_projects(projects: &[Project]) -> Self {
+        let absolute = |path: &Path| std::path::absolute(path).unwrap_or_else(|_| path.into());
+        Self {
+            version: PLAN_VERSION,
+            created_at: Utc::now(),
+            projects: projects
+                .iter()
+                .map(|project| PlannedProject {
+                    name: project.name.clone(),
+                    kind: project.kind.clone(),
+                    root_path: absolute(&project.root_path),
+                    artifacts: project
+                        .build_arts
+                        .iter()
+                        .map(|artifact| PlannedArtifact {
+                            path: absolute(&artifact.path),
+                            size: artifact.size,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Read the plan at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is not a plan, or was
+    /// written by an incompatible version.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(Error::file("read", path))?;
+        let plan: Self = serde_json::from_str(&contents).map_err(|source| Error::Invalid {
+            kind: "cleanup plan",
+            path: path.to_path_buf(),
+            source,
+        })?;
+        if plan.version != PLAN_VERSION {
+            return Err(Error::Other(format!(
+                "{} is a version {} plan, this version of clean-dev-dirs reads version {PLAN_VERSION}",
+                path.display(),
+                plan.version
+            )));
+        }
+        Ok(plan)
+    }
+
+    /// Write the plan to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json + "\n").map_err(Error::file("write", path))
+    }
+
+    /// The roots of the planned projects.
+    pub fn roots(&self) -> impl Iterator<Item = &Path> {
+        self.projects
+            .iter()
+            .map(|project| project.root_path.as_path())
+    }
+
+    /// The deepest directory holding every planned project, or `None` if
+    /// the plan is empty.
+    #[must_use]
+    pub fn common_root(&self) -> Option<PathBuf> {
+        let mut roots = self.roots();
+        let first = roots.next()?.to_path_buf();
+        Some(roots.fold(first, |common, root| {
+            common
+                .ancestors()
+                .find(|ancestor| root.starts_with(ancestor))
+                .unwrap_or_else(|| Path::new(""))
+                .to_path_buf()
+        }))
+    }
+}
+
+impl PlannedProject {
+    /// The project as planned, with the planned sizes.
+    #[must_use]
+    pub fn to_project(&self) -> Project {
+        Project::new(
+            self.kind.clone(),
+            self.root_path.clone(),
+            self.artifacts
+                .iter()
+                .map(|artifact| BuildArtifacts {
+                    path: artifact.path.clone(),
+                    size: artifact.size,
+                })
+                .collect(),
+            self.name.clone(),
+        )
+    }
+
+    /// Check the planned project against `current`, the project detected at
+    /// its root now with every planned artifact sized, if any.
+    ///
+    /// Returns the project to clean, holding only the planned artifacts
+    /// with their current sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`Mismatch`] found.
+    pub fn verify(&self, current: Option<&Project>) -> Result<Project, Mismatch> {
+        let current = current.ok_or(Mismatch::NotFound)?;
+        if current.kind != self.kind {
+            return Err(Mismatch::TypeChanged(current.kind.clone()));
+        }
+
+        let mut build_arts = Vec::with_capacity(self.artifacts.len());
+        for planned in &self.artifacts {
+            let artifact = current
+                .build_arts
+                .iter()
+                .find(|artifact| artifact.path == planned.path)
+                .ok_or_else(|| Mismatch::ArtifactGone(planned.path.clone()))?;
+            #[allow(clippy::cast_precision_loss)]
+            if artifact.size.abs_diff(planned.size) as f64 > planned.size as f64 * SIZE_TOLERANCE {
+                return Err(Mismatch::SizeChanged {
+                    path: planned.path.clone(),
+                    planned: planned.size,
+                    current: artifact.size,
+                });
+            }
+            build_arts.push(artifact.clone());
+        }
+
+        Ok(Project::new(
+            current.kind.clone(),
+            current.root_path.clone(),
+            build_arts,
+            current.name.clone(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project(size: u64) -> Project {
+        Project::new(
+            ProjectType::Rust,
+            PathBuf::from("/work/app"),
+            vec![BuildArtifacts {
+                path: PathBuf::from("/work/app/target"),
+                size,
+            }],
+            Some("app".to_string()),
+        )
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("plan.json");
+        let plan = Plan::from_projects(&[project(1000)]);
+
+        plan.save(&path).unwrap();
+        assert_eq!(Plan::load(&path).unwrap(), plan);
+        assert_eq!(
+            plan.roots().collect::<Vec<_>>(),
+            vec![Path::new("/work/app")]
+        );
+        assert_eq!(plan.common_root(), Some(PathBuf::from("/work/app")));
+
+        fs::write(
+            &path,
+            r#"{"version": 99, "created_at": "2026-01-01T00:00:00Z", "projects": []}"#,
+        )
+        .unwrap();
+        assert!(Plan::load(&path).is_err());
+        fs::write(&path, "[]").unwrap();
+        assert!(Plan::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let planned = &Plan::from_projects(&[project(1000)]).projects[0];
+
+        let verified = planned.verify(Some(&project(1100))).unwrap();
+        assert_eq!(verified.total_size(), 1100);
+
+        assert_eq!(planned.verify(None).err(), Some(Mismatch::NotFound));
+        assert_eq!(
+            planned.verify(Some(&project(1500))).err(),
+            Some(Mismatch::SizeChanged {
+                path: PathBuf::from("/work/app/target"),
+                planned: 1000,
+                current: 1500,
+            })
+        );
+
+        let mut moved = project(1000);
+        moved.build_arts[0].path = PathBuf::from("/work/app/build");
+        assert_eq!(
+            planned.verify(Some(&moved)).err(),
+            Some(Mismatch::ArtifactGone(PathBuf::from("/work/app/target")))
+        );
+
+        let mut node = project(1000);
+        node.kind = ProjectType::Node;
+        assert_eq!(
+            planned.verify(Some(&node)).err(),
+            Some(Mismatch::TypeChanged(ProjectType::Node))
+        );
+    }
+}
@@ -10,11 +10,20 @@
 //! - [`Projects`] - A collection of projects with batch operations
 //! - [`ProjectType`] - Enumeration of supported project types (Rust, Node.js)
 //! - [`BuildArtifacts`] - Information about build directories and their sizes
+//! - [`Workspace`] - A workspace (monorepo) grouping several projects
+//! - [`rebuild_command`] - The command restoring a project after cleaning
 
 #[allow(clippy::module_inception)]
 // This is acceptable as it is the main module for project management
 pub mod project;
 pub mod projects;
+pub mod rebuild;
+pub mod workspace;
 
-pub use project::{BuildArtifacts, Project, ProjectType};
+pub use project::{
+    ArtifactCategory, BuildArtifacts, CACHE_ARTIFACTS, DEPENDENCY_ARTIFACTS, Project, ProjectType,
+    TSBUILDINFO_EXTENSION,
+};
 pub use projects::Projects;
+pub use rebuild::rebuild_command;
+pub use workspace::{Workspace, detect_workspaces};
@@ -5,17 +5,17 @@
 
 use std::{
     fmt::{Display, Formatter, Result},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Enumeration of supported development project types.
 ///
 /// This enum distinguishes between different types of development projects
 /// that the tool can detect and clean. Each project type has its own
 /// characteristic files and build directories.
-#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ProjectType {
     /// Rust project with Cargo.toml and target/ directory
@@ -66,6 +66,27 @@ pub enum ProjectType {
     /// .NET projects are identified by the presence of `.csproj` project files
     /// alongside `bin/` and/or `obj/` output directories.
     DotNet,
+
+    /// `OCaml` project with dune-project and `_build`/ directory
+    ///
+    /// `OCaml` projects built with dune are identified by the presence of a
+    /// `dune-project` file alongside the `_build/` directory.
+    #[serde(rename = "ocaml")]
+    OCaml,
+
+    /// Unity project with `ProjectSettings/ProjectVersion.txt` and generated directories
+    ///
+    /// Unity projects are identified by the `ProjectSettings/ProjectVersion.txt`
+    /// file. Their `Library/`, `Temp/`, `obj/` and `Logs/` directories are all
+    /// regenerated by the editor and are cleaned together.
+    Unity,
+
+    /// Unreal Engine project with a `.uproject` file and generated directories
+    ///
+    /// Unreal projects are identified by a `*.uproject` file. Their
+    /// `Intermediate/`, `DerivedDataCache/`, `Saved/` and `Binaries/`
+    /// directories are offered as separate cleanable artifacts.
+    Unreal,
 }
 
 /// Information about build artifacts that can be cleaned.
@@ -88,6 +109,60 @@ pub struct BuildArtifacts {
     pub size: u64,
 }
 
+/// Names of the generic per-project cache directories and files (linters,
+/// test runners, coverage reports, …) cleaned along with a project's build
+/// artifacts.
+pub const CACHE_ARTIFACTS: &[&str] = &[
+    ".cache",
+    ".eslintcache",
+    ".tsbuildinfo",
+    ".pytest_cache",
+    ".ruff_cache",
+    ".tox",
+    "coverage",
+];
+
+/// Names of the directories holding a project's installed dependencies
+/// rather than its own build outputs (selected with `--artifacts deps`).
+pub const DEPENDENCY_ARTIFACTS: &[&str] = &["node_modules", "vendor", "venv", ".venv"];
+
+/// Extension of TypeScript's incremental build information files, also
+/// treated as cache artifacts (e.g. `tsconfig.tsbuildinfo`).
+pub const TSBUILDINFO_EXTENSION: &str = "tsbuildinfo";
+
+/// Category of a build artifact.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactCategory {
+    /// Output of the project's build tool or package manager (`target/`,
+    /// `node_modules/`, …).
+    Build,
+
+    /// Generic cache of a development tool (see [`CACHE_ARTIFACTS`]).
+    Cache,
+}
+
+impl BuildArtifacts {
+    /// Category of the artifact, derived from its name.
+    #[must_use]
+    pub fn category(&self) -> ArtifactCategory {
+        let is_cache = self
+            .path
+            .file_name()
+            .is_some_and(|name| CACHE_ARTIFACTS.iter().any(|cache| name == *cache))
+            || self
+                .path
+                .extension()
+                .is_some_and(|extension| extension == TSBUILDINFO_EXTENSION);
+
+        if is_cache {
+            ArtifactCategory::Cache
+        } else {
+            ArtifactCategory::Build
+        }
+    }
+}
+
 /// Representation of a development project with cleanable build artifacts.
 ///
 /// This struct encapsulates all information about a development project,
@@ -104,11 +179,12 @@ pub struct Project {
     /// For Node.js projects, this is the directory containing `package.json`.
     pub root_path: PathBuf,
 
-    /// The build directory to be cleaned and its metadata
+    /// The build directories to be cleaned and their metadata
     ///
-    /// Contains information about the `target/` or `node_modules/` directory
-    /// that is a candidate for cleanup, including its path and total size.
-    pub build_arts: BuildArtifacts,
+    /// Most ecosystems have a single build directory (`target/`, `node_modules/`,
+    /// …), but some (e.g. Unity) spread regenerable artifacts over several
+    /// directories, all of which are removed when the project is cleaned.
+    pub build_arts: Vec<BuildArtifacts>,
 
     /// Name of the project extracted from configuration files
     ///
@@ -129,7 +205,7 @@ impl Project {
     ///
     /// * `kind` - The type of project (Rust or Node.js)
     /// * `root_path` - Path to the project's root directory
-    /// * `build_arts` - Information about the build artifacts to be cleaned
+    /// * `build_arts` - The build artifact directories to be cleaned
     /// * `name` - Optional project name extracted from configuration files
     ///
     /// # Returns
@@ -141,10 +217,10 @@ impl Project {
     /// ```no_run
     /// # use std::path::PathBuf;
     /// # use crate::project::{Project, ProjectType, BuildArtifacts};
-    /// let build_arts = BuildArtifacts {
+    /// let build_arts = vec![BuildArtifacts {
     ///     path: PathBuf::from("/path/to/project/target"),
     ///     size: 1024,
-    /// };
+    /// }];
     ///
     /// let project = Project::new(
     ///     ProjectType::Rust,
@@ -157,7 +233,7 @@ impl Project {
     pub const fn new(
         kind: ProjectType,
         root_path: PathBuf,
-        build_arts: BuildArtifacts,
+        build_arts: Vec<BuildArtifacts>,
         name: Option<String>,
     ) -> Self {
         Self {
@@ -167,6 +243,19 @@ impl Project {
             name,
         }
     }
+
+    /// Total size of all build artifact directories, in bytes.
+    #[must_use]
+    pub fn total_size(&self) -> u64 {
+        self.build_arts.iter().map(|artifact| artifact.size).sum()
+    }
+
+    /// Iterate over the paths of all build artifact directories.
+    pub fn artifact_paths(&self) -> impl Iterator<Item = &Path> {
+        self.build_arts
+            .iter()
+            .map(|artifact| artifact.path.as_path())
+    }
 }
 
 impl Display for Project {
@@ -188,6 +277,9 @@ impl Display for Project {
     /// - `⚙️ my-cpp-project (/path/to/project)`
     /// - `🐦 my-swift-project (/path/to/project)`
     /// - `🔷 my-dotnet-project (/path/to/project)`
+    /// - `🐫 my-ocaml-project (/path/to/project)`
+    /// - `🎮 my-unity-project (/path/to/project)`
+    /// - `🕹️ my-unreal-project (/path/to/project)`
     /// - `🦀 /path/to/unnamed/project` (when no name is available)
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         let icon = match self.kind {
@@ -199,6 +291,9 @@ impl Display for Project {
             ProjectType::Cpp => "⚙️",
             ProjectType::Swift => "🐦",
             ProjectType::DotNet => "🔷",
+            ProjectType::OCaml => "🐫",
+            ProjectType::Unity => "🎮",
+            ProjectType::Unreal => "🕹️",
         };
 
         if let Some(name) = &self.name {
@@ -233,7 +328,7 @@ mod tests {
         Project::new(
             kind,
             PathBuf::from(root_path),
-            create_test_build_artifacts(build_path, size),
+            vec![create_test_build_artifacts(build_path, size)],
             name,
         )
     }
@@ -248,6 +343,9 @@ mod tests {
         assert_eq!(ProjectType::Cpp, ProjectType::Cpp);
         assert_eq!(ProjectType::Swift, ProjectType::Swift);
         assert_eq!(ProjectType::DotNet, ProjectType::DotNet);
+        assert_eq!(ProjectType::OCaml, ProjectType::OCaml);
+        assert_eq!(ProjectType::Unity, ProjectType::Unity);
+        assert_eq!(ProjectType::Unreal, ProjectType::Unreal);
 
         assert_ne!(ProjectType::Rust, ProjectType::Node);
         assert_ne!(ProjectType::Node, ProjectType::Python);
@@ -256,6 +354,9 @@ mod tests {
         assert_ne!(ProjectType::Java, ProjectType::Cpp);
         assert_ne!(ProjectType::Cpp, ProjectType::Swift);
         assert_ne!(ProjectType::Swift, ProjectType::DotNet);
+        assert_ne!(ProjectType::DotNet, ProjectType::OCaml);
+        assert_ne!(ProjectType::OCaml, ProjectType::Unity);
+        assert_ne!(ProjectType::Unity, ProjectType::Unreal);
     }
 
     #[test]
@@ -279,10 +380,10 @@ mod tests {
         assert_eq!(project.kind, ProjectType::Rust);
         assert_eq!(project.root_path, PathBuf::from("/path/to/project"));
         assert_eq!(
-            project.build_arts.path,
+            project.build_arts[0].path,
             PathBuf::from("/path/to/project/target")
         );
-        assert_eq!(project.build_arts.size, 1024);
+        assert_eq!(project.build_arts[0].size, 1024);
         assert_eq!(project.name, Some("test-project".to_string()));
     }
 
@@ -375,6 +476,39 @@ mod tests {
 
         let expected = "🔷 my-dotnet-app (/path/to/dotnet-project)";
         assert_eq!(format!("{dotnet_project}"), expected);
+
+        let ocaml_project = create_test_project(
+            ProjectType::OCaml,
+            "/path/to/ocaml-project",
+            "/path/to/ocaml-project/_build",
+            4096,
+            Some("my-ocaml-app".to_string()),
+        );
+
+        let expected = "🐫 my-ocaml-app (/path/to/ocaml-project)";
+        assert_eq!(format!("{ocaml_project}"), expected);
+
+        let unity_project = create_test_project(
+            ProjectType::Unity,
+            "/path/to/unity-project",
+            "/path/to/unity-project/Library",
+            4096,
+            Some("my-unity-game".to_string()),
+        );
+
+        let expected = "🎮 my-unity-game (/path/to/unity-project)";
+        assert_eq!(format!("{unity_project}"), expected);
+
+        let unreal_project = create_test_project(
+            ProjectType::Unreal,
+            "/path/to/unreal-project",
+            "/path/to/unreal-project/Intermediate",
+            4096,
+            Some("MyUnrealGame".to_string()),
+        );
+
+        let expected = "🕹\u{fe0f} MyUnrealGame (/path/to/unreal-project)";
+        assert_eq!(format!("{unreal_project}"), expected);
     }
 
     #[test]
@@ -416,8 +550,8 @@ mod tests {
 
         assert_eq!(original.kind, cloned.kind);
         assert_eq!(original.root_path, cloned.root_path);
-        assert_eq!(original.build_arts.path, cloned.build_arts.path);
-        assert_eq!(original.build_arts.size, cloned.build_arts.size);
+        assert_eq!(original.build_arts[0].path, cloned.build_arts[0].path);
+        assert_eq!(original.build_arts[0].size, cloned.build_arts[0].size);
         assert_eq!(original.name, cloned.name);
     }
 
@@ -440,7 +574,7 @@ mod tests {
             Some("empty-project".to_string()),
         );
 
-        assert_eq!(project.build_arts.size, 0);
+        assert_eq!(project.build_arts[0].size, 0);
         assert_eq!(format!("{project}"), "🐍 empty-project (/empty/project)");
     }
 
@@ -455,6 +589,30 @@ mod tests {
             Some("huge-project".to_string()),
         );
 
-        assert_eq!(project.build_arts.size, large_size);
+        assert_eq!(project.build_arts[0].size, large_size);
+    }
+
+    #[test]
+    fn test_project_with_multiple_artifacts() {
+        let project = Project::new(
+            ProjectType::Unity,
+            PathBuf::from("/game"),
+            vec![
+                create_test_build_artifacts("/game/Library", 3000),
+                create_test_build_artifacts("/game/Temp", 200),
+                create_test_build_artifacts("/game/Logs", 10),
+            ],
+            Some("game".to_string()),
+        );
+
+        assert_eq!(project.total_size(), 3210);
+        assert_eq!(
+            project.artifact_paths().collect::<Vec<_>>(),
+            vec![
+                Path::new("/game/Library"),
+                Path::new("/game/Temp"),
+                Path::new("/game/Logs"),
+            ]
+        );
     }
 }
@@ -4,21 +4,51 @@
 //! development projects and provides various operations on them, including
 //! interactive selection, summary reporting, and parallel iteration support.
 
-use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "cli")]
 use colored::Colorize;
-use humansize::{DECIMAL, format_size};
-use inquire::MultiSelect;
 use rayon::prelude::*;
 
+#[cfg(feature = "cli")]
+use crate::error::Result;
+#[cfg(feature = "cli")]
+use crate::picker::{self, PickerGroup, PickerItem};
 use crate::project::ProjectType;
+#[cfg(feature = "cli")]
+use crate::utils::{format_size, terminal};
 
 use super::Project;
+#[cfg(feature = "cli")]
+use super::{Workspace, detect_workspaces};
+
+/// An option of the selection dialog: its label and the `(project, artifact)`
+/// indices it covers.
+#[cfg(feature = "cli")]
+type SelectionOption = (String, Vec<(usize, usize)>);
+
+/// Supported project types with their icon and label.
+pub(crate) const TYPE_ENTRIES: &[(ProjectType, &str, &str)] = &[
+    (ProjectType::Rust, "🦀", "Rust"),
+    (ProjectType::Node, "📦", "Node.js"),
+    (ProjectType::Python, "🐍", "Python"),
+    (ProjectType::Go, "🐹", "Go"),
+    (ProjectType::Java, "☕", "Java/Kotlin"),
+    (ProjectType::Cpp, "⚙️", "C/C++"),
+    (ProjectType::Swift, "🐦", "Swift"),
+    (ProjectType::DotNet, "🔷", ".NET/C#"),
+    (ProjectType::OCaml, "🐫", "OCaml"),
+    (ProjectType::Unity, "🎮", "Unity"),
+    (ProjectType::Unreal, "🕹️", "Unreal"),
+];
 
 /// A collection of development projects with associated operations.
 ///
 /// The `Projects` struct wraps a vector of `Project` instances and provides
-/// higher-level operations such as interactive selection, summary reporting,
-/// and parallel processing support. It serves as the main data structure
+/// higher-level operations such as sorting, grouping, partitioning and
+/// merging, interactive selection, summary reporting, and parallel processing
+/// support. It serves as the main data structure
 /// for managing collections of projects throughout the application.
 pub struct Projects(Vec<Project>);
 
@@ -49,6 +79,25 @@ impl From<Vec<Project>> for Projects {
     }
 }
 
+impl IntoIterator for Projects {
+    type Item = Project;
+    type IntoIter = std::vec::IntoIter<Project>;
+
+    /// Iterate over the projects, taking them out of the collection.
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Projects {
+    type Item = &'a Project;
+    type IntoIter = std::slice::Iter<'a, Project>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
 impl IntoParallelIterator for Projects {
     type Iter = rayon::vec::IntoIter<Project>;
     type Item = Project;
@@ -127,25 +176,32 @@ impl Projects {
     /// ```
     #[must_use]
     pub fn get_total_size(&self) -> u64 {
-        self.0.iter().map(|p| p.build_arts.size).sum()
+        self.0.iter().map(Project::total_size).sum()
     }
 
     /// Present an interactive selection interface for choosing projects to clean.
     ///
     /// This method displays a multi-select dialog that allows users to choose
     /// which projects they want to clean. Each project is shown with its type
-    /// icon, path, and reclaimable space. All projects are selected by default.
+    /// icon, path, and reclaimable space. Projects with several build
+    /// directories (e.g. Unreal's `Intermediate/`, `Saved/`, …) are listed once
+    /// per directory with its own size, so each directory can be toggled
+    /// individually. The members of a workspace (e.g. the packages of an npm,
+    /// yarn or pnpm monorepo) are listed as a single entry with their
+    /// aggregated size, toggling the whole workspace at once. Everything is
+    /// selected by default.
     ///
     /// # Returns
     ///
-    /// - `Ok(Vec<Project>)` - The projects selected by the user
-    /// - `Err(anyhow::Error)` - If the interactive dialog fails or is canceled
+    /// - `Ok(Vec<Project>)` - The selected projects, each keeping only its selected build directories
+    /// - `Err(Error)` - If the interactive dialog fails or is canceled
     ///
     /// # Interface Details
     ///
     /// - Uses a colorful theme for better visual appeal
-    /// - Shows project type icons (🦀 Rust, 📦 Node.js, 🐍 Python, 🐹 Go, ☕ Java, ⚙️ C/C++, 🐦 Swift, 🔷 .NET)
-    /// - Displays project paths and sizes in human-readable format
+    /// - Shows project type icons (🦀 Rust, 📦 Node.js, 🐍 Python, 🐹 Go, ☕ Java, ⚙️ C/C++, 🐦 Swift, 🔷 .NET, 🐫 OCaml, 🎮 Unity, 🕹️ Unreal)
+    /// - Displays project paths and sizes in human-readable format, shortening
+    ///   paths that would not fit the terminal width
     /// - Allows toggling selections with space bar
     /// - Confirms selection with the Enter key
     ///
@@ -153,7 +209,6 @@ impl Projects {
     ///
     /// ```
     /// # use crate::Projects;
-    /// # use anyhow::Result;
     /// let selected_projects = projects.interactive_selection()?;
     /// println!("User selected {} projects", selected_projects.len());
     /// ```
@@ -162,47 +217,33 @@ impl Projects {
     ///
     /// This method can fail if:
     /// - The terminal doesn't support interactive input
-    /// - The user cancels the dialog (Ctrl+C)
+    /// - The user cancels the dialog (Esc or Ctrl+C)
     /// - There are I/O errors with the terminal
+    #[cfg(feature = "cli")]
     pub fn interactive_selection(&self) -> Result<Vec<Project>> {
-        let items: Vec<String> = self
-            .0
-            .iter()
-            .map(|p| {
-                let icon = icon_for_project_type(&p.kind);
-                format!(
-                    "{icon} {} ({})",
-                    p.root_path.display(),
-                    format_size(p.build_arts.size, DECIMAL)
-                )
-            })
-            .collect();
-
-        let defaults: Vec<usize> = (0..self.0.len()).collect();
-
-        let selections = MultiSelect::new("Select projects to clean:", items)
-            .with_default(&defaults)
-            .prompt()?;
-
-        Ok(selections
-            .iter()
-            .filter_map(|selected_item| {
-                self.0
-                    .iter()
-                    .enumerate()
-                    .find(|(_, p)| {
-                        let icon = icon_for_project_type(&p.kind);
-                        let expected = format!(
-                            "{icon} {} ({})",
-                            p.root_path.display(),
-                            format_size(p.build_arts.size, DECIMAL)
-                        );
-                        &expected == selected_item
-                    })
-                    .map(|(i, _)| i)
-            })
-            .map(|i| self.0[i].clone())
-            .collect())
+        let width = terminal::width().map(|width| width.saturating_sub(terminal::SELECTION_MARGIN));
+        let mut groups = Vec::new();
+        let mut entries = Vec::new();
+        for (label, options) in group_by_type(&self.0, selection_options(&self.0, width)) {
+            let items = options
+                .into_iter()
+                .map(|(label, option)| {
+                    let search = search_text(&self.0, &option);
+                    entries.push(option);
+                    PickerItem { label, search }
+                })
+                .collect();
+            groups.push(PickerGroup { label, items });
+        }
+
+        let selections = picker::pick("Select projects to clean:", groups)?;
+
+        Ok(select_artifacts(
+            &self.0,
+            selections
+                .iter()
+                .flat_map(|&option| entries[option].iter().copied()),
+        ))
     }
 
     /// Get the number of projects in the collection.
@@ -250,6 +291,73 @@ impl Projects {
         &self.0
     }
 
+    /// Iterate over the projects, in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Project> {
+        self.0.iter()
+    }
+
+    /// Keep only the projects for which `keep` returns `true`, in order.
+    pub fn retain(&mut self, keep: impl FnMut(&Project) -> bool) {
+        self.0.retain(keep);
+    }
+
+    /// Sort the projects by reclaimable space, largest first, as
+    /// `--sort size` does. Projects of the same size keep their order.
+    pub fn sort_by_size(&mut self) {
+        self.0
+            .sort_by_key(|project| std::cmp::Reverse(project.total_size()));
+    }
+
+    /// Split the projects by type, each group keeping the order of the
+    /// collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use clean_dev_dirs::{Project, ProjectType, Projects};
+    /// # use std::path::PathBuf;
+    /// let projects = Projects::from(vec![
+    ///     Project::new(ProjectType::Rust, PathBuf::from("/work/app"), vec![], None),
+    ///     Project::new(ProjectType::Node, PathBuf::from("/work/web"), vec![], None),
+    /// ]);
+    /// let groups = projects.group_by_type();
+    /// assert_eq!(groups[&ProjectType::Rust].len(), 1);
+    /// ```
+    #[must_use]
+    pub fn group_by_type(self) -> HashMap<ProjectType, Self> {
+        let mut groups: HashMap<ProjectType, Self> = HashMap::new();
+        for project in self.0 {
+            groups
+                .entry(project.kind.clone())
+                .or_insert_with(|| Self(Vec::new()))
+                .0
+                .push(project);
+        }
+        groups
+    }
+
+    /// Split the projects into those for which `predicate` returns `true`
+    /// and the others, each keeping the order of the collection.
+    #[must_use]
+    pub fn partition(self, predicate: impl FnMut(&Project) -> bool) -> (Self, Self) {
+        let (matching, others) = self.0.into_iter().partition(predicate);
+        (Self(matching), Self(others))
+    }
+
+    /// Append the projects of `other` that are not already in the
+    /// collection, e.g. to combine the results of several scans. A project
+    /// of the same type rooted at the same path counts as already there,
+    /// and is kept as it was.
+    pub fn merge(&mut self, other: Self) {
+        for project in other.0 {
+            if !self.0.iter().any(|existing| {
+                existing.kind == project.kind && existing.root_path == project.root_path
+            }) {
+                self.0.push(project);
+            }
+        }
+    }
+
     /// Print a detailed summary of the projects and their reclaimable space.
     ///
     /// This method analyzes the collection and prints statistics including:
@@ -257,6 +365,7 @@ impl Projects {
     /// - Number and total size of Node.js projects
     /// - Number and total size of Python projects
     /// - Number and total size of Go projects
+    /// - For each detected workspace, its total and a per-member breakdown
     /// - Total reclaimable space across all projects
     ///
     /// The output is formatted with colors and emoji icons for better readability.
@@ -284,24 +393,20 @@ impl Projects {
     ///   ⚙️ 1 C/C++ project (0.3 GB)
     ///   🐦 1 Swift project (0.2 GB)
     ///   🔷 1 .NET/C# project (0.1 GB)
+    ///   🐫 1 OCaml project (0.1 GB)
+    ///   🎮 1 Unity project (12.0 GB)
+    ///   🕹️ 1 Unreal project (40.0 GB)
+    ///   🗂️  Workspace monorepo: 2 members (1.2 GB)
+    ///       📦 packages/web (0.8 GB)
+    ///       📦 packages/api (0.4 GB)
     ///   💾 Total reclaimable space: 4.0 GB
     /// ```
+    #[cfg(feature = "cli")]
     pub fn print_summary(&self, total_size: u64) {
-        let type_entries: &[(ProjectType, &str, &str)] = &[
-            (ProjectType::Rust, "🦀", "Rust"),
-            (ProjectType::Node, "📦", "Node.js"),
-            (ProjectType::Python, "🐍", "Python"),
-            (ProjectType::Go, "🐹", "Go"),
-            (ProjectType::Java, "☕", "Java/Kotlin"),
-            (ProjectType::Cpp, "⚙️", "C/C++"),
-            (ProjectType::Swift, "🐦", "Swift"),
-            (ProjectType::DotNet, "🔷", ".NET/C#"),
-        ];
-
-        for (kind, icon, label) in type_entries {
+        for (kind, icon, label) in types_in_order(&self.0) {
             let (count, size) = self.0.iter().fold((0usize, 0u64), |(c, s), p| {
                 if &p.kind == kind {
-                    (c + 1, s + p.build_arts.size)
+                    (c + 1, s + p.total_size())
                 } else {
                     (c, s)
                 }
@@ -311,19 +416,89 @@ impl Projects {
                 println!(
                     "  {icon} {} {label} projects ({})",
                     count.to_string().bright_white(),
-                    format_size(size, DECIMAL).bright_white()
+                    format_size(size).bright_white()
                 );
             }
         }
 
+        self.print_workspaces();
+
         println!(
             "  💾 Total reclaimable space: {}",
-            format_size(total_size, DECIMAL).bright_green().bold()
+            format_size(total_size).bright_green().bold()
         );
     }
+
+    /// Print the number of projects and their total size under each of
+    /// `roots`, the directories they were found in.
+    #[cfg(feature = "cli")]
+    pub fn print_root_summary(&self, roots: &[PathBuf]) {
+        for (root, count, size) in self.totals_by_root(roots) {
+            println!(
+                "  📂 {}: {} projects ({})",
+                root.display(),
+                count.to_string().bright_white(),
+                format_size(size).bright_white()
+            );
+        }
+    }
+
+    /// The number of projects and their total size under each of `roots`,
+    /// each project counting for the first root it is under.
+    #[must_use]
+    pub fn totals_by_root<'a>(&self, roots: &'a [PathBuf]) -> Vec<(&'a Path, usize, u64)> {
+        let mut totals: Vec<_> = roots.iter().map(|root| (root.as_path(), 0, 0)).collect();
+        for project in &self.0 {
+            if let Some(total) = totals
+                .iter_mut()
+                .find(|(root, _, _)| project.root_path.starts_with(root))
+            {
+                total.1 += 1;
+                total.2 += project.total_size();
+            }
+        }
+        totals
+    }
+
+    /// Print a rollup line for each detected workspace, followed by an
+    /// indented line per member (its path relative to the workspace root).
+    #[cfg(feature = "cli")]
+    fn print_workspaces(&self) {
+        for workspace in detect_workspaces(&self.0) {
+            println!(
+                "  🗂️  Workspace {}: {} members ({})",
+                workspace.name().bold(),
+                workspace.members.len().to_string().bright_white(),
+                format_size(workspace.total_size(&self.0)).bright_white()
+            );
+
+            for &member in &workspace.members {
+                let project = &self.0[member];
+                let relative = project
+                    .root_path
+                    .strip_prefix(&workspace.root)
+                    .unwrap_or(&project.root_path);
+                let label = if relative.as_os_str().is_empty() {
+                    ".".to_string()
+                } else {
+                    relative.display().to_string()
+                };
+
+                println!(
+                    "{}",
+                    terminal::fit(&format!(
+                        "      {} {label} ({})",
+                        icon_for_project_type(&project.kind),
+                        format_size(project.total_size())
+                    ))
+                );
+            }
+        }
+    }
 }
 
 /// Return the icon for a given project type.
+#[cfg(feature = "cli")]
 const fn icon_for_project_type(kind: &ProjectType) -> &'static str {
     match kind {
         ProjectType::Rust => "🦀",
@@ -334,5 +509,438 @@ const fn icon_for_project_type(kind: &ProjectType) -> &'static str {
         ProjectType::Cpp => "⚙️",
         ProjectType::Swift => "🐦",
         ProjectType::DotNet => "🔷",
+        ProjectType::OCaml => "🐫",
+        ProjectType::Unity => "🎮",
+        ProjectType::Unreal => "🕹️",
+    }
+}
+
+/// Build the options of the selection dialog: a label and the
+/// `(project index, artifact index)` pairs it toggles.
+///
+/// Projects outside of any workspace get one option per build directory.
+/// The members of a workspace share a single option, placed where the first
+/// member would have been.
+#[cfg(feature = "cli")]
+fn selection_options(projects: &[Project], width: Option<usize>) -> Vec<SelectionOption> {
+    let workspaces = detect_workspaces(projects);
+    let mut workspace_of = vec![None; projects.len()];
+    for (w, workspace) in workspaces.iter().enumerate() {
+        for &member in &workspace.members {
+            workspace_of[member] = Some(w);
+        }
+    }
+
+    let mut listed = vec![false; workspaces.len()];
+    let mut options = Vec::new();
+
+    for (p, project) in projects.iter().enumerate() {
+        match workspace_of[p] {
+            Some(w) if !listed[w] => {
+                listed[w] = true;
+                let workspace = &workspaces[w];
+                let artifacts = workspace
+                    .members
+                    .iter()
+                    .flat_map(|&m| (0..projects[m].build_arts.len()).map(move |a| (m, a)))
+                    .collect();
+                options.push((workspace_label(workspace, projects, width), artifacts));
+            }
+            Some(_) => {}
+            None => {
+                for a in 0..project.build_arts.len() {
+                    options.push((selection_label(project, a, width), vec![(p, a)]));
+                }
+            }
+        }
+    }
+
+    options
+}
+
+/// Return the entries of the project types present in `projects`, in the
+/// order their first project appears.
+///
+/// The summary and the selection groups thus follow the order the projects
+/// were sorted in: with the default size order, the type of the biggest
+/// project comes first.
+#[cfg(feature = "cli")]
+fn types_in_order(projects: &[Project]) -> Vec<&'static (ProjectType, &'static str, &'static str)> {
+    let mut types: Vec<&(ProjectType, &str, &str)> = Vec::new();
+    for project in projects {
+        if !types.iter().any(|(kind, _, _)| *kind == project.kind)
+            && let Some(entry) = TYPE_ENTRIES
+                .iter()
+                .find(|(kind, _, _)| *kind == project.kind)
+        {
+            types.push(entry);
+        }
+    }
+    types
+}
+
+/// Group selection options by project type, in the order of the summary.
+///
+/// Each group comes with a header label holding the type's icon and name, and
+/// the number of projects and the space its options cover.
+#[cfg(feature = "cli")]
+fn group_by_type(
+    projects: &[Project],
+    options: Vec<SelectionOption>,
+) -> Vec<(String, Vec<SelectionOption>)> {
+    let types = types_in_order(projects);
+    let mut groups: Vec<_> = types.iter().map(|_| Vec::new()).collect();
+    for option in options {
+        let kind = &projects[option.1[0].0].kind;
+        if let Some(g) = types.iter().position(|(k, _, _)| k == kind) {
+            groups[g].push(option);
+        }
+    }
+
+    types
+        .into_iter()
+        .zip(groups)
+        .filter(|(_, options)| !options.is_empty())
+        .map(|((_, icon, label), options)| {
+            let entries = || options.iter().flat_map(|(_, entries)| entries);
+            let mut members: Vec<usize> = entries().map(|&(p, _)| p).collect();
+            members.sort_unstable();
+            members.dedup();
+            let size: u64 = entries()
+                .map(|&(p, a)| projects[p].build_arts[a].size)
+                .sum();
+            let header = format!(
+                "{icon} {label} ({} project{}, {})",
+                members.len(),
+                if members.len() == 1 { "" } else { "s" },
+                format_size(size)
+            );
+            (header, options)
+        })
+        .collect()
+}
+
+/// Build the text the selection filter matches an option against: the names
+/// and root paths of the projects it covers, and the paths of its artifacts.
+#[cfg(feature = "cli")]
+fn search_text(projects: &[Project], entries: &[(usize, usize)]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for &(p, a) in entries {
+        let project = &projects[p];
+        if let Some(name) = &project.name
+            && !parts.contains(name)
+        {
+            parts.push(name.clone());
+        }
+        let root = project.root_path.display().to_string();
+        if !parts.contains(&root) {
+            parts.push(root);
+        }
+        parts.push(project.build_arts[a].path.display().to_string());
+    }
+    parts.join(" ")
+}
+
+/// Build the label shown for a whole workspace in the selection dialog.
+#[cfg(feature = "cli")]
+fn workspace_label(workspace: &Workspace, projects: &[Project], width: Option<usize>) -> String {
+    let prefix = format!("🗂️  {} workspace (", workspace.name());
+    let suffix = format!(
+        ", {} packages, {})",
+        workspace.members.len(),
+        format_size(workspace.total_size(projects))
+    );
+    let root = terminal::fit_path_to(
+        &workspace.root.display().to_string(),
+        &format!("{prefix}{suffix}"),
+        width,
+    );
+
+    format!("{prefix}{root}{suffix}")
+}
+
+/// Build the label shown for one build directory in the selection dialog.
+///
+/// Single-directory projects are labelled by their root path; projects with
+/// several directories are labelled by each directory's path. The path is
+/// shortened so that the label fits in `width` columns.
+#[cfg(feature = "cli")]
+fn selection_label(project: &Project, artifact: usize, width: Option<usize>) -> String {
+    let icon = icon_for_project_type(&project.kind);
+    let build_arts = &project.build_arts[artifact];
+    let path = if project.build_arts.len() == 1 {
+        &project.root_path
+    } else {
+        &build_arts.path
+    };
+
+    let prefix = format!("{icon} ");
+    let suffix = format!(" ({})", format_size(build_arts.size));
+    let path = terminal::fit_path_to(
+        &path.display().to_string(),
+        &format!("{prefix}{suffix}"),
+        width,
+    );
+
+    format!("{prefix}{path}{suffix}")
+}
+
+/// Rebuild the list of projects from the selected `(project, artifact)` pairs.
+///
+/// Projects keep their original order and only the selected build
+/// directories; projects with no selected directory are dropped.
+#[cfg(feature = "cli")]
+fn select_artifacts(
+    projects: &[Project],
+    selected: impl IntoIterator<Item = (usize, usize)>,
+) -> Vec<Project> {
+    let mut chosen: Vec<Vec<usize>> = vec![Vec::new(); projects.len()];
+    for (p, a) in selected {
+        chosen[p].push(a);
+    }
+
+    projects
+        .iter()
+        .zip(chosen)
+        .filter(|(_, artifacts)| !artifacts.is_empty())
+        .map(|(project, artifacts)| {
+            let mut project = project.clone();
+            project.build_arts = artifacts
+                .into_iter()
+                .map(|a| project.build_arts[a].clone())
+                .collect();
+            project
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::BuildArtifacts;
+
+    fn create_project(root: &str, dirs: &[(&str, u64)]) -> Project {
+        Project::new(
+            ProjectType::Unreal,
+            PathBuf::from(root),
+            dirs.iter()
+                .map(|&(dir, size)| BuildArtifacts {
+                    path: PathBuf::from(root).join(dir),
+                    size,
+                })
+                .collect(),
+            None,
+        )
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_select_artifacts_keeps_only_selected_directories() {
+        let projects = vec![
+            create_project("/a", &[("Intermediate", 10), ("Saved", 5), ("Binaries", 1)]),
+            create_project("/b", &[("Intermediate", 7)]),
+        ];
+
+        let selected = select_artifacts(&projects, [(0, 0), (0, 2)]);
+
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].root_path, PathBuf::from("/a"));
+        assert_eq!(
+            selected[0].artifact_paths().collect::<Vec<_>>(),
+            vec![
+                PathBuf::from("/a/Intermediate"),
+                PathBuf::from("/a/Binaries")
+            ]
+        );
+        assert_eq!(selected[0].total_size(), 11);
+    }
+
+    #[test]
+    fn test_totals_by_root() {
+        let projects = Projects::from(vec![
+            create_project("/work/a", &[("Intermediate", 10)]),
+            create_project("/oss/b", &[("Intermediate", 7)]),
+            create_project("/work/c", &[("Saved", 5)]),
+        ]);
+        let roots = [
+            PathBuf::from("/work"),
+            PathBuf::from("/oss"),
+            PathBuf::from("/mnt"),
+        ];
+
+        assert_eq!(
+            projects.totals_by_root(&roots),
+            [
+                (Path::new("/work"), 2, 15),
+                (Path::new("/oss"), 1, 7),
+                (Path::new("/mnt"), 0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collection_methods() {
+        let mut projects = Projects::from(vec![
+            create_project("/work/a", &[("Intermediate", 10)]),
+            create_project("/work/b", &[("Intermediate", 30)]),
+            create_project("/work/c", &[("Saved", 20)]),
+        ]);
+        projects.sort_by_size();
+        let roots = |projects: &Projects| -> Vec<_> {
+            projects
+                .iter()
+                .map(|project| project.root_path.clone())
+                .collect()
+        };
+        assert_eq!(
+            roots(&projects),
+            [
+                PathBuf::from("/work/b"),
+                PathBuf::from("/work/c"),
+                PathBuf::from("/work/a")
+            ]
+        );
+
+        projects.merge(Projects::from(vec![
+            create_project("/work/a", &[("Saved", 1)]),
+            create_project("/work/d", &[("Saved", 5)]),
+        ]));
+        assert_eq!(projects.len(), 4);
+        assert_eq!(projects.get_total_size(), 65);
+
+        let (large, small) = projects.partition(|project| project.total_size() >= 20);
+        assert_eq!(
+            roots(&large),
+            [PathBuf::from("/work/b"), PathBuf::from("/work/c")]
+        );
+        assert_eq!(
+            roots(&small),
+            [PathBuf::from("/work/a"), PathBuf::from("/work/d")]
+        );
+
+        let mut small = small;
+        small.retain(|project| project.total_size() > 5);
+        assert_eq!(roots(&small), [PathBuf::from("/work/a")]);
+
+        let groups = large.group_by_type();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[&ProjectType::Unreal].len(), 2);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_select_artifacts_preserves_project_order() {
+        let projects = vec![
+            create_project("/a", &[("Intermediate", 10)]),
+            create_project("/b", &[("Intermediate", 7)]),
+        ];
+
+        let selected = select_artifacts(&projects, [(1, 0), (0, 0)]);
+
+        assert_eq!(selected[0].root_path, PathBuf::from("/a"));
+        assert_eq!(selected[1].root_path, PathBuf::from("/b"));
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_selection_label_uses_directory_for_multi_artifact_projects() {
+        let single = create_project("/single", &[("Intermediate", 1000)]);
+        let multi = create_project("/multi", &[("Intermediate", 1000), ("Saved", 2000)]);
+
+        assert_eq!(selection_label(&single, 0, None), "🕹️ /single (1 kB)");
+        assert_eq!(selection_label(&multi, 1, None), "🕹️ /multi/Saved (2 kB)");
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_selection_options_group_workspace_members() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mono = temp_dir.path().join("mono");
+        for dir in ["packages/a", "packages/b"] {
+            std::fs::create_dir_all(mono.join(dir)).unwrap();
+        }
+        std::fs::write(
+            mono.join("package.json"),
+            r#"{"workspaces": ["packages/*"]}"#,
+        )
+        .unwrap();
+
+        let path = |dir: &str| mono.join(dir).to_string_lossy().into_owned();
+        let projects = vec![
+            create_project("/solo", &[("Intermediate", 1000), ("Saved", 1000)]),
+            create_project(&path("packages/a"), &[("node_modules", 2000)]),
+            create_project("/other", &[("Intermediate", 1000)]),
+            create_project(&path("packages/b"), &[("node_modules", 3000)]),
+        ];
+
+        let options = selection_options(&projects, None);
+        let entries: Vec<_> = options.iter().map(|(_, entries)| entries.clone()).collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                vec![(0, 0)],
+                vec![(0, 1)],
+                vec![(1, 0), (3, 0)],
+                vec![(2, 0)]
+            ]
+        );
+        assert!(
+            options[2].0.starts_with("🗂️  mono workspace ("),
+            "{}",
+            options[2].0
+        );
+        assert!(
+            options[2].0.ends_with(", 2 packages, 5 kB)"),
+            "{}",
+            options[2].0
+        );
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_group_by_type_follows_project_order() {
+        let mut rust = create_project("/rust", &[("target", 1000)]);
+        rust.kind = ProjectType::Rust;
+        let projects = vec![
+            create_project("/game", &[("Intermediate", 1000), ("Saved", 2000)]),
+            rust,
+        ];
+
+        let groups = group_by_type(&projects, selection_options(&projects, None));
+        let headers: Vec<_> = groups.iter().map(|(header, _)| header.as_str()).collect();
+
+        assert_eq!(
+            headers,
+            ["🕹️ Unreal (1 project, 3 kB)", "🦀 Rust (1 project, 1 kB)"]
+        );
+        assert_eq!(groups[0].1.len(), 2);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_search_text_holds_names_and_paths() {
+        let mut project = create_project("/dev/api", &[("target", 1000), ("debug", 1000)]);
+        project.name = Some("api-server".to_string());
+
+        assert_eq!(
+            search_text(&[project], &[(0, 0), (0, 1)]),
+            format!(
+                "api-server /dev/api {} {}",
+                Path::new("/dev/api").join("target").display(),
+                Path::new("/dev/api").join("debug").display()
+            )
+        );
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_selection_label_shortens_paths_to_width() {
+        let project = create_project("/home/me/dev/clients/acme/web", &[("Intermediate", 1000)]);
+
+        let label = selection_label(&project, 0, Some(30));
+        assert!(label.starts_with("🕹️ …/"), "{label}");
+        assert!(label.ends_with("/acme/web (1 kB)"), "{label}");
+        assert!(console::measure_text_width(&label) <= 30, "{label}");
     }
 }
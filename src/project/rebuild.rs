@@ -0,0 +1,173 @@
+//! Commands restoring a project after its build artifacts were cleaned.
+//!
+//! Cleaning is only painless when the way back is obvious. This module
+//! derives, from a project's type and the lockfiles and wrappers found in its
+//! root directory, the command that rebuilds (or reinstalls) what was
+//! removed: `npm ci` for a Node.js project with a `package-lock.json`,
+//! `poetry install` for a Python project with a `poetry.lock`, and so on.
+
+use std::path::Path;
+
+use super::{Project, ProjectType};
+
+/// Python build directories holding an installed virtual environment.
+const PYTHON_VENV_DIRS: &[&str] = &["venv", ".venv"];
+
+/// Return the command restoring `project` after cleaning, to be run in its
+/// root directory.
+///
+/// Returns `None` when nothing needs to be run (Python caches are recreated
+/// automatically) or when the project is rebuilt from an editor (Unity and
+/// Unreal).
+#[must_use]
+pub fn rebuild_command(project: &Project) -> Option<String> {
+    let root = project.root_path.as_path();
+    let has = |file: &str| root.join(file).exists();
+
+    let command = match project.kind {
+        ProjectType::Rust => "cargo build",
+        ProjectType::Node => node_command(root),
+        ProjectType::Python => {
+            let venv_cleaned = project.artifact_paths().any(|path| {
+                path.file_name()
+                    .is_some_and(|name| PYTHON_VENV_DIRS.iter().any(|dir| name == *dir))
+            });
+            if !venv_cleaned {
+                return None;
+            }
+            python_command(root)
+        }
+        ProjectType::Go => "go mod vendor",
+        ProjectType::Java if has("pom.xml") => {
+            if has("mvnw") {
+                "./mvnw package"
+            } else {
+                "mvn package"
+            }
+        }
+        ProjectType::Java if has("gradlew") => "./gradlew build",
+        ProjectType::Java => "gradle build",
+        ProjectType::Cpp if has("CMakeLists.txt") => "cmake -B build && cmake --build build",
+        ProjectType::Cpp => "make",
+        ProjectType::Swift => "swift build",
+        ProjectType::DotNet => "dotnet build",
+        ProjectType::OCaml => "dune build",
+        ProjectType::Unity | ProjectType::Unreal => return None,
+    };
+
+    Some(command.to_string())
+}
+
+/// Install command of a Node.js project, chosen from its lockfile.
+fn node_command(root: &Path) -> &'static str {
+    let lockfiles = [
+        ("pnpm-lock.yaml", "pnpm install --frozen-lockfile"),
+        ("yarn.lock", "yarn install --frozen-lockfile"),
+        ("bun.lock", "bun install --frozen-lockfile"),
+        ("bun.lockb", "bun install --frozen-lockfile"),
+        ("package-lock.json", "npm ci"),
+    ];
+
+    lockfiles
+        .iter()
+        .find(|(lockfile, _)| root.join(lockfile).exists())
+        .map_or("npm install", |&(_, command)| command)
+}
+
+/// Install command of a Python project's virtual environment, chosen from
+/// its lockfile or dependency manifest.
+fn python_command(root: &Path) -> &'static str {
+    let manifests = [
+        ("uv.lock", "uv sync"),
+        ("poetry.lock", "poetry install"),
+        ("pdm.lock", "pdm install"),
+        ("Pipfile.lock", "pipenv install --deploy"),
+        ("Pipfile", "pipenv install"),
+        ("requirements.txt", "pip install -r requirements.txt"),
+    ];
+
+    manifests
+        .iter()
+        .find(|(manifest, _)| root.join(manifest).exists())
+        .map_or("pip install -e .", |&(_, command)| command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::BuildArtifacts;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn project(kind: ProjectType, root: &Path, files: &[&str], artifact: &str) -> Project {
+        for file in files {
+            fs::write(root.join(file), "").unwrap();
+        }
+        Project::new(
+            kind,
+            root.to_path_buf(),
+            vec![BuildArtifacts {
+                path: root.join(artifact),
+                size: 0,
+            }],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_node_command_follows_lockfile() {
+        let cases: &[(&[&str], &str)] = &[
+            (&["package.json"], "npm install"),
+            (&["package.json", "package-lock.json"], "npm ci"),
+            (
+                &["package.json", "yarn.lock"],
+                "yarn install --frozen-lockfile",
+            ),
+            (
+                &["package.json", "pnpm-lock.yaml"],
+                "pnpm install --frozen-lockfile",
+            ),
+        ];
+
+        for &(files, expected) in cases {
+            let temp_dir = TempDir::new().unwrap();
+            let project = project(ProjectType::Node, temp_dir.path(), files, "node_modules");
+            assert_eq!(rebuild_command(&project).as_deref(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_python_command_only_when_venv_was_cleaned() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let venv = project(ProjectType::Python, root, &["poetry.lock"], ".venv");
+        assert_eq!(rebuild_command(&venv).as_deref(), Some("poetry install"));
+
+        let cache = project(ProjectType::Python, root, &[], "__pycache__");
+        assert_eq!(rebuild_command(&cache), None);
+    }
+
+    #[test]
+    fn test_build_tool_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let rust = project(ProjectType::Rust, root, &["Cargo.toml"], "target");
+        assert_eq!(rebuild_command(&rust).as_deref(), Some("cargo build"));
+
+        let gradle = project(
+            ProjectType::Java,
+            root,
+            &["build.gradle", "gradlew"],
+            "build",
+        );
+        assert_eq!(rebuild_command(&gradle).as_deref(), Some("./gradlew build"));
+
+        let go = project(ProjectType::Go, root, &["go.mod"], "vendor");
+        assert_eq!(rebuild_command(&go).as_deref(), Some("go mod vendor"));
+
+        let unity = project(ProjectType::Unity, root, &[], "Library");
+        assert_eq!(rebuild_command(&unity), None);
+    }
+}
@@ -0,0 +1,174 @@
+//! Workspace (monorepo) grouping of projects.
+//!
+//! Projects found inside the same Cargo workspace, npm/yarn/pnpm workspace
+//! or Go workspace belong together: a monorepo is usually cleaned (or kept)
+//! as a whole. This module detects such workspaces from the manifests in the
+//! projects' ancestor directories, so that reports can roll their members up.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::Project;
+
+/// A workspace containing several of the found projects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Workspace {
+    /// Root directory of the workspace (the directory holding its manifest).
+    pub root: PathBuf,
+
+    /// Indices of the member projects in the slice passed to
+    /// [`detect_workspaces`], in order.
+    pub members: Vec<usize>,
+}
+
+impl Workspace {
+    /// Display name of the workspace: the name of its root directory.
+    #[must_use]
+    pub fn name(&self) -> String {
+        self.root.file_name().map_or_else(
+            || self.root.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        )
+    }
+
+    /// Total size of the build artifacts of all members, in bytes.
+    #[must_use]
+    pub fn total_size(&self, projects: &[Project]) -> u64 {
+        self.members
+            .iter()
+            .map(|&member| projects[member].total_size())
+            .sum()
+    }
+}
+
+/// Group projects by the workspace they belong to.
+///
+/// A project belongs to the nearest workspace among its root directory and
+/// that directory's ancestors. Only workspaces with at least two members are
+/// returned, sorted by root path.
+#[must_use]
+pub fn detect_workspaces(projects: &[Project]) -> Vec<Workspace> {
+    let mut is_workspace: HashMap<PathBuf, bool> = HashMap::new();
+    let mut members: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+
+    for (index, project) in projects.iter().enumerate() {
+        let workspace = project.root_path.ancestors().find(|dir| {
+            *is_workspace
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| is_workspace_root(dir))
+        });
+
+        if let Some(root) = workspace {
+            members.entry(root.to_path_buf()).or_default().push(index);
+        }
+    }
+
+    let mut workspaces: Vec<Workspace> = members
+        .into_iter()
+        .filter(|(_, members)| members.len() >= 2)
+        .map(|(root, members)| Workspace { root, members })
+        .collect();
+    workspaces.sort_by(|a, b| a.root.cmp(&b.root));
+    workspaces
+}
+
+/// Check whether `dir` holds a workspace manifest.
+///
+/// Recognized manifests are a `Cargo.toml` with a `[workspace]` table, a
+/// `package.json` with a `workspaces` field, `pnpm-workspace.yaml` and
+/// `go.work`.
+fn is_workspace_root(dir: &Path) -> bool {
+    if dir.join("pnpm-workspace.yaml").is_file() || dir.join("go.work").is_file() {
+        return true;
+    }
+
+    let cargo_workspace = fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .is_some_and(|manifest| manifest.contains_key("workspace"));
+
+    cargo_workspace
+        || fs::read_to_string(dir.join("package.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .is_some_and(|manifest| manifest.get("workspaces").is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{BuildArtifacts, ProjectType};
+    use tempfile::TempDir;
+
+    fn project(root: &Path, size: u64) -> Project {
+        Project::new(
+            ProjectType::Node,
+            root.to_path_buf(),
+            vec![BuildArtifacts {
+                path: root.join("node_modules"),
+                size,
+            }],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_detect_workspaces_groups_members() {
+        let temp_dir = TempDir::new().unwrap();
+        let mono = temp_dir.path().join("mono");
+        for dir in ["packages/a", "packages/b"] {
+            fs::create_dir_all(mono.join(dir)).unwrap();
+        }
+        fs::create_dir_all(temp_dir.path().join("standalone")).unwrap();
+        fs::write(
+            mono.join("package.json"),
+            r#"{"name": "mono", "workspaces": ["packages/*"]}"#,
+        )
+        .unwrap();
+
+        let projects = vec![
+            project(&mono.join("packages/a"), 100),
+            project(&temp_dir.path().join("standalone"), 10),
+            project(&mono, 1000),
+            project(&mono.join("packages/b"), 200),
+        ];
+
+        let workspaces = detect_workspaces(&projects);
+
+        assert_eq!(workspaces.len(), 1);
+        assert_eq!(workspaces[0].root, mono);
+        assert_eq!(workspaces[0].name(), "mono");
+        assert_eq!(workspaces[0].members, vec![0, 2, 3]);
+        assert_eq!(workspaces[0].total_size(&projects), 1300);
+    }
+
+    #[test]
+    fn test_single_member_workspaces_are_not_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("ws");
+        fs::create_dir_all(root.join("crates/core")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+
+        let projects = vec![project(&root, 100)];
+        assert!(detect_workspaces(&projects).is_empty());
+    }
+
+    #[test]
+    fn test_is_workspace_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        assert!(!is_workspace_root(dir));
+
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+        fs::write(dir.join("package.json"), r#"{"name": "a"}"#).unwrap();
+        assert!(!is_workspace_root(dir));
+
+        fs::write(dir.join("go.work"), "go 1.22\n").unwrap();
+        assert!(is_workspace_root(dir));
+    }
+}
@@ -0,0 +1,268 @@
+//! Timestamped and signed JSON reports.
+//!
+//! On shared build servers, cleanups sometimes have to leave an audit trail.
+//! This module stamps a JSON report with the time it was generated and the
+//! machine it was generated on, and signs it with an Ed25519 key so that the
+//! archived report can later be checked for tampering.
+//!
+//! # Signature scheme
+//!
+//! The signature covers the report *without* its `signature` field,
+//! serialized as compact JSON with object keys sorted. It is stored in the
+//! report itself:
+//!
+//! ```json
+//! "signature": {
+//!   "algorithm": "ed25519",
+//!   "public_key": "<base64>",
+//!   "signature": "<base64>"
+//! }
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::error::{Error, Result};
+
+/// Name of the signing algorithm recorded in signed reports.
+const ALGORITHM: &str = "ed25519";
+
+/// When and where a report was generated.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ReportMetadata {
+    /// Time the report was generated, in UTC.
+    pub generated_at: DateTime<Utc>,
+
+    /// Identifier of the machine the report was generated on (its hostname).
+    pub machine_id: String,
+}
+
+impl ReportMetadata {
+    /// Metadata for a report generated now, on this machine.
+    #[must_use]
+    pub fn current() -> Self {
+        Self {
+            generated_at: Utc::now(),
+            machine_id: gethostname::gethostname().to_string_lossy().into_owned(),
+        }
+    }
+}
+
+/// An Ed25519 key used to sign reports.
+pub struct ReportSigner {
+    key: SigningKey,
+}
+
+impl ReportSigner {
+    /// Create a signer from a 32-byte Ed25519 secret key (seed).
+    #[must_use]
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        Self {
+            key: SigningKey::from_bytes(seed),
+        }
+    }
+
+    /// Load a signer from a key file.
+    ///
+    /// The file must contain a base64-encoded 32-byte Ed25519 secret key, as
+    /// produced by `openssl rand -base64 32`. Surrounding whitespace is
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is not valid base64, or
+    /// does not decode to exactly 32 bytes.
+    pub fn from_key_file(path: &Path) -> Result<Self> {
+        let encoded = fs::read_to_string(path).map_err(Error::file("read signing key", path))?;
+        let bytes = STANDARD.decode(encoded.trim()).map_err(|e| {
+            Error::Report(format!(
+                "Signing key {} is not valid base64: {e}",
+                path.display()
+            ))
+        })?;
+        let seed: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            Error::Report(format!(
+                "Signing key {} must be 32 bytes, got {}",
+                path.display(),
+                bytes.len()
+            ))
+        })?;
+
+        Ok(Self::from_seed(&seed))
+    }
+
+    /// The base64-encoded public key matching this signer.
+    #[must_use]
+    pub fn public_key_base64(&self) -> String {
+        STANDARD.encode(self.key.verifying_key().as_bytes())
+    }
+
+    /// Stamp `report` with `metadata` and sign it.
+    ///
+    /// `report` must serialize to a JSON object. The metadata is added as a
+    /// `metadata` field and the signature as a `signature` field.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `report` cannot be serialized or is not an object.
+    pub fn sign<T: Serialize>(&self, report: &T, metadata: &ReportMetadata) -> Result<Value> {
+        let mut value = serde_json::to_value(report)?;
+        let Value::Object(fields) = &mut value else {
+            return Err(report_error("Only JSON objects can be signed"));
+        };
+        fields.insert("metadata".to_string(), serde_json::to_value(metadata)?);
+
+        let signature = self.key.sign(&serde_json::to_vec(&value)?);
+
+        if let Value::Object(fields) = &mut value {
+            fields.insert(
+                "signature".to_string(),
+                json!({
+                    "algorithm": ALGORITHM,
+                    "public_key": self.public_key_base64(),
+                    "signature": STANDARD.encode(signature.to_bytes()),
+                }),
+            );
+        }
+
+        Ok(value)
+    }
+}
+
+/// Verify a signed JSON report.
+///
+/// # Returns
+///
+/// The base64-encoded public key the report was signed with. Compare it with
+/// the expected key: the signature only proves that the report was not
+/// modified since it was signed by the holder of that key.
+///
+/// # Errors
+///
+/// Returns an error if the report is not valid JSON, has no well-formed
+/// `signature` field, or the signature does not match its content.
+pub fn verify_report(report: &str) -> Result<String> {
+    let mut value: Value = serde_json::from_str(report)
+        .map_err(|e| Error::Report(format!("Report is not valid JSON: {e}")))?;
+    let signature = value
+        .as_object_mut()
+        .and_then(|fields| fields.remove("signature"))
+        .ok_or_else(|| report_error("Report is not signed"))?;
+
+    let field = |name: &str| {
+        signature
+            .get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::Report(format!("Report signature has no `{name}`")))
+    };
+
+    let algorithm = field("algorithm")?;
+    if algorithm != ALGORITHM {
+        return Err(Error::Report(format!(
+            "Unsupported signature algorithm `{algorithm}`"
+        )));
+    }
+
+    let public_key = field("public_key")?;
+    let key_bytes: [u8; 32] = STANDARD
+        .decode(public_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| report_error("Malformed public key"))?;
+    let key =
+        VerifyingKey::from_bytes(&key_bytes).map_err(|_| report_error("Malformed public key"))?;
+
+    let signature_bytes: [u8; 64] = STANDARD
+        .decode(field("signature")?)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| report_error("Malformed signature"))?;
+
+    key.verify(
+        &serde_json::to_vec(&value)?,
+        &Signature::from_bytes(&signature_bytes),
+    )
+    .map_err(|_| report_error("Signature does not match the report content"))?;
+
+    Ok(public_key.to_string())
+}
+
+/// A report error with `message`.
+fn report_error(message: &str) -> Error {
+    Error::Report(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> ReportMetadata {
+        ReportMetadata {
+            generated_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            machine_id: "build-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_signed_report_verifies() {
+        let signer = ReportSigner::from_seed(&[7; 32]);
+        let report = signer
+            .sign(&json!({"mode": "cleanup", "total": 42}), &metadata())
+            .unwrap();
+
+        assert_eq!(report["metadata"]["machine_id"], "build-01");
+        assert_eq!(report["metadata"]["generated_at"], "2023-11-14T22:13:20Z");
+
+        let pretty = serde_json::to_string_pretty(&report).unwrap();
+        assert_eq!(verify_report(&pretty).unwrap(), signer.public_key_base64());
+    }
+
+    #[test]
+    fn test_tampered_report_fails_verification() {
+        let signer = ReportSigner::from_seed(&[7; 32]);
+        let mut report = signer
+            .sign(&json!({"mode": "cleanup", "total": 42}), &metadata())
+            .unwrap();
+        report["total"] = json!(43);
+
+        assert!(verify_report(&report.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_unsigned_report_fails_verification() {
+        assert!(verify_report(r#"{"mode": "dry_run"}"#).is_err());
+        assert!(verify_report("not json").is_err());
+    }
+
+    #[test]
+    fn test_sign_rejects_non_objects() {
+        let signer = ReportSigner::from_seed(&[7; 32]);
+        assert!(signer.sign(&[1, 2, 3], &metadata()).is_err());
+    }
+
+    #[test]
+    fn test_from_key_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        let valid = temp_dir.path().join("valid.key");
+        fs::write(&valid, format!("{}\n", STANDARD.encode([7u8; 32]))).unwrap();
+        let signer = ReportSigner::from_key_file(&valid).unwrap();
+        assert_eq!(
+            signer.public_key_base64(),
+            ReportSigner::from_seed(&[7; 32]).public_key_base64()
+        );
+
+        let short = temp_dir.path().join("short.key");
+        fs::write(&short, STANDARD.encode([7u8; 16])).unwrap();
+        assert!(ReportSigner::from_key_file(&short).is_err());
+
+        assert!(ReportSigner::from_key_file(&temp_dir.path().join("missing.key")).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! Sizes of build directories kept between runs.
+//!
+//! Sizing walks every file of every build directory, which makes up most of
+//! a scan. The scan index (`~/.cache/clean-dev-dirs/scan-index.json` on
+//! Linux) records, for each build directory sized, its modification time,
+//! its size and the type of its project. On the next run, a directory whose
+//! modification time has not changed is not walked again: its recorded size
+//! is used. `--refresh` ignores the index and sizes everything anew.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::scan::DuMode;
+use crate::error::{Error, Result};
+use crate::project::ProjectType;
+
+/// The file the scan index is kept in, if the platform has a cache
+/// directory.
+#[must_use]
+pub fn scan_index_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("clean-dev-dirs").join("scan-index.json"))
+}
+
+/// A build directory recorded in a [`ScanIndex`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Modification time of the directory when it was sized.
+    pub modified: DateTime<Utc>,
+
+    /// Size of the directory, in bytes.
+    pub size: u64,
+
+    /// How the size was measured.
+    pub du_mode: DuMode,
+
+    /// Type of the project the directory belongs to.
+    pub project_type: ProjectType,
+}
+
+/// Recorded sizes per build directory.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScanIndex {
+    entries: BTreeMap<PathBuf, IndexEntry>,
+}
+
+impl ScanIndex {
+    /// Load the index from `path`. A missing file holds no sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid index.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|source| Error::Invalid {
+                kind: "scan index",
+                path: path.to_path_buf(),
+                source,
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::file("read", path)(e)),
+        }
+    }
+
+    /// Write the index to `path`, creating its directory if needed.
+    ///
+    /// Directories that no longer exist, e.g. because they were cleaned,
+    /// are dropped first. The index is written to a temporary file that
+    /// then replaces `path`, so runs finishing at the same time never leave
+    /// a partly written index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or the file cannot be written.
+    pub fn save(&mut self, path: &Path) -> Result<()> {
+        self.entries.retain(|dir, _| dir.exists());
+
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(dir).map_err(Error::file("create", dir))?;
+
+        let temp = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&temp, serde_json::to_string(self)?).map_err(Error::file("write", &temp))?;
+        fs::rename(&temp, path).map_err(Error::file("write", path))
+    }
+
+    /// The recorded size of the directory at `path`, if it was measured with
+    /// `du_mode` and has not been modified since.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use clean_dev_dirs::config::scan::DuMode;
+    /// # use clean_dev_dirs::project::ProjectType;
+    /// # use clean_dev_dirs::scan_index::ScanIndex;
+    /// # use std::path::Path;
+    /// # use std::time::{Duration, SystemTime};
+    /// let mut index = ScanIndex::default();
+    /// let built = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    /// let target = Path::new("/work/app/target");
+    /// index.record(target, built, 4096, DuMode::Apparent, ProjectType::Rust);
+    ///
+    /// assert_eq!(index.size(target, built, DuMode::Apparent), Some(4096));
+    /// assert_eq!(index.size(target, SystemTime::now(), DuMode::Apparent), None);
+    /// assert_eq!(index.size(target, built, DuMode::Disk), None);
+    /// ```
+    #[must_use]
+    pub fn size(&self, path: &Path, modified: SystemTime, du_mode: DuMode) -> Option<u64> {
+        self.entries
+            .get(&absolute(path))
+            .filter(|entry| {
+                entry.modified == DateTime::<Utc>::from(modified) && entry.du_mode == du_mode
+            })
+            .map(|entry| entry.size)
+    }
+
+    /// Record that the directory at `path`, of a `project_type` project,
+    /// measured `size` bytes with `du_mode` when last modified at `modified`.
+    pub fn record(
+        &mut self,
+        path: &Path,
+        modified: SystemTime,
+        size: u64,
+        du_mode: DuMode,
+        project_type: ProjectType,
+    ) {
+        self.entries.insert(
+            absolute(path),
+            IndexEntry {
+                modified: modified.into(),
+                size,
+                du_mode,
+                project_type,
+            },
+        );
+    }
+
+    /// Number of directories recorded.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no directory has been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// `path` made absolute, so that the index does not depend on the
+/// directory the run was started from.
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_index_roundtrip() -> Result<()> {
+        let temp = TempDir::new()?;
+        let path = temp.path().join("cache").join("scan-index.json");
+        assert_eq!(ScanIndex::load(&path)?, ScanIndex::default());
+
+        let target = temp.path().join("app").join("target");
+        fs::create_dir_all(&target)?;
+        let modified = fs::metadata(&target)?.modified()?;
+
+        let mut index = ScanIndex::default();
+        index.record(&target, modified, 1234, DuMode::Disk, ProjectType::Rust);
+        index.record(
+            &temp.path().join("gone").join("node_modules"),
+            modified,
+            10,
+            DuMode::Disk,
+            ProjectType::Node,
+        );
+        index.save(&path)?;
+
+        let loaded = ScanIndex::load(&path)?;
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.size(&target, modified, DuMode::Disk), Some(1234));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_index() -> Result<()> {
+        let temp = TempDir::new()?;
+        let path = temp.path().join("scan-index.json");
+        fs::write(&path, "not json")?;
+        assert!(ScanIndex::load(&path).is_err());
+        Ok(())
+    }
+}
@@ -6,20 +6,30 @@
 //! gracefully.
 
 use std::{
-    fs,
-    path::Path,
-    sync::{Arc, Mutex},
+    collections::{HashMap, HashSet},
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Instant, SystemTime},
 };
 
-use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
 use serde_json::{Value, from_str};
-use walkdir::{DirEntry, WalkDir};
 
 use crate::{
-    config::{ProjectFilter, ScanOptions},
-    project::{BuildArtifacts, Project, ProjectType},
+    cancellation::CancellationToken,
+    config::{
+        ProjectFilter, ScanOptions,
+        filter::ArtifactSelector,
+        scan::{DuMode, ScanLimit, ScanLimits},
+    },
+    project::{BuildArtifacts, CACHE_ARTIFACTS, Project, ProjectType, TSBUILDINFO_EXTENSION},
+    scan_index::ScanIndex,
+    utils::{
+        cleanignore::CleanIgnore, duration::format_duration, gitignore::GitIgnore,
+        mounts::NetworkMounts, path_filter::PathFilter, paths,
+    },
+    vfs::{Entry, EntryKind, FsHandle, WalkError, WalkOptions},
 };
 
 /// Directory scanner for detecting development projects.
@@ -29,14 +39,378 @@ use crate::{
 /// build artifacts. It supports configurable filtering and parallel processing
 /// for efficient scanning of large directory structures.
 pub struct Scanner {
+    /// Directories searched by [`Scanner::scan`].
+    roots: Vec<PathBuf>,
+
     /// Configuration options for scanning behavior
     scan_options: ScanOptions,
 
     /// Filter to restrict scanning to specific project types
     project_filter: ProjectFilter,
 
-    /// When `true`, suppresses progress spinner output (used by `--json` mode).
-    quiet: bool,
+    /// Whether symbolic links to directories are walked into, and mount
+    /// points crossed.
+    walk_options: WalkOptions,
+
+    /// Depth below the roots past which directories are not searched.
+    max_depth: Option<usize>,
+
+    /// Depth below the roots above which no project is reported.
+    min_depth: usize,
+
+    /// Whether mounted network file systems are walked into
+    /// (`--allow-network-fs`).
+    network_fs: bool,
+
+    /// Threads the scan runs on, with [`ScanOptions::threads`] set; the
+    /// global rayon pool otherwise.
+    pool: Option<rayon::ThreadPool>,
+
+    /// Token checked during traversal and sizing to stop the scan early.
+    cancellation: CancellationToken,
+
+    /// `--include` and `--exclude` globs restricting the paths scanned.
+    path_filter: PathFilter,
+
+    /// `--artifacts` selectors restricting the artifacts reported; empty
+    /// selects every artifact.
+    artifacts: Vec<ArtifactSelector>,
+
+    /// `--scan-budget` and `--max-dirs` limits on the traversal.
+    limits: ScanLimits,
+
+    /// Whether directories ignored by `.gitignore` and `.ignore` files are
+    /// skipped (not with `--no-ignore`).
+    ignore_files: bool,
+
+    /// Whether [`Scanner::size_projects`] measures the artifacts (not with
+    /// `--no-size`).
+    sizing: bool,
+
+    /// How sizes are measured (`--du-mode`).
+    du_mode: DuMode,
+
+    /// Device and inode numbers of the files with several hard links
+    /// already counted, with [`DuMode::Disk`].
+    hard_links: Mutex<HashSet<(u64, u64)>>,
+
+    /// Sizes recorded by earlier runs, reused for the artifacts that were
+    /// not modified since, and updated with the artifacts sized.
+    index: Option<Mutex<ScanIndex>>,
+
+    /// Directories skipped by the last scan, by reason.
+    skip_stats: Mutex<SkipStats>,
+
+    /// Why the last scan stopped early, if it did.
+    truncation: Mutex<Option<ScanTruncation>>,
+
+    /// File system to scan, the local one by default.
+    fs: FsHandle,
+
+    /// Told about the progress of the scan.
+    observer: Option<Arc<Observer>>,
+}
+
+/// Configures a [`Scanner`] step by step, from [`Scanner::builder`].
+///
+/// Settings left out keep their defaults: all project types, no depth
+/// limit, symbolic links not followed and the global rayon pool. The
+/// scanner built can be configured further with its `with_*` methods.
+///
+/// # Examples
+///
+/// ```
+/// # use clean_dev_dirs::{ProjectFilter, Scanner};
+/// let scanner = Scanner::builder()
+///     .root("/work")
+///     .filter(ProjectFilter::Rust)
+///     .follow_symlinks(true)
+///     .max_depth(6)
+///     .threads(8)
+///     .build();
+/// let projects = scanner.scan();
+/// ```
+#[derive(Default)]
+#[must_use]
+pub struct ScannerBuilder {
+    roots: Vec<PathBuf>,
+    scan_options: ScanOptions,
+    project_filter: ProjectFilter,
+    walk_options: WalkOptions,
+    max_depth: Option<usize>,
+    min_depth: usize,
+    network_fs: bool,
+}
+
+impl ScannerBuilder {
+    /// Add `root` to the directories searched by [`Scanner::scan`].
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    /// Look for projects of the types selected by `project_filter` only.
+    pub const fn filter(mut self, project_filter: ProjectFilter) -> Self {
+        self.project_filter = project_filter;
+        self
+    }
+
+    /// Walk into the directories that symbolic links point to, instead of
+    /// skipping the links. Loops are detected and not followed.
+    pub const fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.walk_options.follow_links = follow_symlinks;
+        self
+    }
+
+    /// Stay on the file system of each root, not descending into mount
+    /// points below it (`--one-file-system`).
+    pub const fn one_file_system(mut self, one_file_system: bool) -> Self {
+        self.walk_options.same_file_system = one_file_system;
+        self
+    }
+
+    /// Walk into mounted network file systems (NFS, SMB, FUSE) instead of
+    /// skipping them.
+    pub const fn network_fs(mut self, network_fs: bool) -> Self {
+        self.network_fs = network_fs;
+        self
+    }
+
+    /// Search no deeper than `max_depth` directories below each root; `0`
+    /// only looks at the roots themselves.
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Report only the projects at least `min_depth` directories below each
+    /// root; `1` leaves out a project at the root itself.
+    pub const fn min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+
+    /// Run the scan on `threads` threads instead of the global rayon pool;
+    /// `0` keeps the global pool.
+    pub const fn threads(mut self, threads: usize) -> Self {
+        self.scan_options.threads = threads;
+        self
+    }
+
+    /// Skip the directories named `skip` and everything below them.
+    pub fn skip(mut self, skip: impl Into<PathBuf>) -> Self {
+        self.scan_options.skip.push(skip.into());
+        self
+    }
+
+    /// Log the projects filtered out (`--verbose`).
+    pub const fn verbose(mut self, verbose: bool) -> Self {
+        self.scan_options.verbose = verbose;
+        self
+    }
+
+    /// Create the scanner.
+    ///
+    /// If a pool of [`ScannerBuilder::threads`] threads cannot be created,
+    /// the scan runs on the global rayon pool.
+    pub fn build(self) -> Scanner {
+        let pool = (self.scan_options.threads > 0)
+            .then(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(self.scan_options.threads)
+                    .build()
+                    .ok()
+            })
+            .flatten();
+        Scanner {
+            roots: self.roots,
+            scan_options: self.scan_options,
+            project_filter: self.project_filter,
+            walk_options: self.walk_options,
+            max_depth: self.max_depth,
+            min_depth: self.min_depth,
+            network_fs: self.network_fs,
+            pool,
+            cancellation: CancellationToken::new(),
+            path_filter: PathFilter::default(),
+            artifacts: Vec::new(),
+            limits: ScanLimits::default(),
+            ignore_files: true,
+            sizing: true,
+            du_mode: DuMode::Apparent,
+            hard_links: Mutex::new(HashSet::new()),
+            index: None,
+            skip_stats: Mutex::new(SkipStats::default()),
+            truncation: Mutex::new(None),
+            fs: FsHandle::default(),
+            observer: None,
+        }
+    }
+}
+
+/// Callback given to [`Scanner::with_observer`].
+type Observer = dyn Fn(ScanEvent<'_>) + Send + Sync;
+
+/// A step of a scan, reported to the observer set with
+/// [`Scanner::with_observer`], e.g. to draw progress.
+///
+/// Events about projects come from the scanning threads, in no particular
+/// order.
+#[derive(Clone, Copy)]
+pub enum ScanEvent<'a> {
+    /// The search for projects under the scanned directories started.
+    WalkStarted,
+
+    /// The search for projects under the scanned directories is over.
+    WalkFinished {
+        /// Whether the scan was cancelled.
+        cancelled: bool,
+
+        /// Why the search stopped early, if a scan limit did stop it.
+        truncation: Option<ScanTruncation>,
+    },
+
+    /// A project was found. Its artifacts are not sized yet.
+    ProjectFound(&'a Project),
+
+    /// The artifacts of a project were sized.
+    SizeComputed(&'a Project),
+}
+
+/// Why the scanner did not search a directory for projects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DirSkip {
+    /// The directory is hidden.
+    Hidden,
+
+    /// The directory is a build, cache or version control directory.
+    Blocklist,
+
+    /// The directory is in the `--skip` list.
+    SkipList,
+
+    /// The directory is not allowed by `--include` or `--exclude`.
+    PathFilter,
+
+    /// The directory is inside a `node_modules/` directory.
+    NodeModules,
+
+    /// The directory is ignored by a `.gitignore` or `.ignore` file.
+    Ignored,
+
+    /// The directory is the mount point of a network file system.
+    NetworkFs,
+}
+
+/// Number of directories the scanner did not search for projects, by reason.
+///
+/// Logged at the info level, shown with `--verbose`, so that a project
+/// missing from the results can be traced back to an over-aggressive
+/// exclusion.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SkipStats {
+    /// Hidden directories (starting with `.`), except `.cargo`.
+    pub hidden: usize,
+
+    /// Build, cache and version control directories, e.g. `target` or `.git`.
+    pub blocklist: usize,
+
+    /// Directories in the `--skip` list.
+    pub skip_list: usize,
+
+    /// Directories not allowed by `--include` or `--exclude`.
+    pub path_filter: usize,
+
+    /// Directories inside a `node_modules/` directory.
+    pub node_modules: usize,
+
+    /// Directories ignored by a `.gitignore` or `.ignore` file.
+    pub ignored: usize,
+
+    /// Mount points of network file systems (not with `--allow-network-fs`).
+    pub network_fs: usize,
+}
+
+impl SkipStats {
+    /// Count one directory skipped for `reason`.
+    const fn record(&mut self, reason: DirSkip) {
+        let count = match reason {
+            DirSkip::Hidden => &mut self.hidden,
+            DirSkip::Blocklist => &mut self.blocklist,
+            DirSkip::SkipList => &mut self.skip_list,
+            DirSkip::PathFilter => &mut self.path_filter,
+            DirSkip::NodeModules => &mut self.node_modules,
+            DirSkip::Ignored => &mut self.ignored,
+            DirSkip::NetworkFs => &mut self.network_fs,
+        };
+        *count += 1;
+    }
+
+    /// Total number of directories skipped.
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        self.hidden
+            + self.blocklist
+            + self.skip_list
+            + self.path_filter
+            + self.node_modules
+            + self.ignored
+            + self.network_fs
+    }
+}
+
+impl fmt::Display for SkipStats {
+    /// Format as e.g. `Skipped 12 directories: 10 inside node_modules, 2 hidden`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reasons = [
+            (self.node_modules, "inside node_modules"),
+            (self.blocklist, "build, cache or VCS"),
+            (self.hidden, "hidden"),
+            (self.skip_list, "in --skip list"),
+            (self.path_filter, "filtered by --include/--exclude"),
+            (self.ignored, "ignored by .gitignore/.ignore"),
+            (self.network_fs, "on a network file system"),
+        ];
+        let details: Vec<String> = reasons
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, reason)| format!("{count} {reason}"))
+            .collect();
+
+        write!(f, "Skipped {} directories", self.total())?;
+        if !details.is_empty() {
+            write!(f, ": {}", details.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A scan stopped by one of its [`ScanLimits`] before covering the whole
+/// tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScanTruncation {
+    /// The limit that was reached.
+    pub limit: ScanLimit,
+
+    /// Number of directories visited before stopping.
+    pub dirs_visited: u64,
+}
+
+impl fmt::Display for ScanTruncation {
+    /// Format as e.g. `Scan stopped after 5m (1204 directories visited)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.limit {
+            ScanLimit::Budget(budget) => write!(
+                f,
+                "Scan stopped after {} (--scan-budget), {} directories visited",
+                format_duration(budget),
+                self.dirs_visited
+            ),
+            ScanLimit::MaxDirs(max_dirs) => {
+                write!(f, "Scan stopped after {max_dirs} directories (--max-dirs)")
+            }
+        }
+    }
 }
 
 impl Scanner {
@@ -64,30 +438,220 @@ impl Scanner {
     /// let scanner = Scanner::new(scan_options, ProjectFilter::All);
     /// ```
     #[must_use]
-    pub const fn new(scan_options: ScanOptions, project_filter: ProjectFilter) -> Self {
-        Self {
+    pub fn new(scan_options: ScanOptions, project_filter: ProjectFilter) -> Self {
+        ScannerBuilder {
             scan_options,
             project_filter,
-            quiet: false,
+            ..ScannerBuilder::default()
         }
+        .build()
+    }
+
+    /// Start configuring a scanner, see [`ScannerBuilder`].
+    pub fn builder() -> ScannerBuilder {
+        ScannerBuilder::default()
+    }
+
+    /// Observe `cancellation` while scanning.
+    ///
+    /// Once the token is cancelled, [`Scanner::scan_directory`] stops walking
+    /// and sizing and returns the projects that were fully processed so far,
+    /// instead of discarding them.
+    #[must_use]
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Restrict scanning to the paths allowed by `path_filter`.
+    ///
+    /// Directories that are not included, or excluded, are not searched for
+    /// projects, and excluded build artifacts are dropped from the projects
+    /// found, e.g. `--exclude '**/.venv'` keeps virtual environments.
+    #[must_use]
+    pub fn with_path_filter(mut self, path_filter: PathFilter) -> Self {
+        self.path_filter = path_filter;
+        self
+    }
+
+    /// Report only the artifacts chosen by `artifacts` (`--artifacts`).
+    ///
+    /// Other artifacts are not sized, and projects without any selected
+    /// artifact are dropped.
+    #[must_use]
+    pub fn with_artifacts(mut self, artifacts: Vec<ArtifactSelector>) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
+    /// Stay on the file system of each root, not descending into mount
+    /// points below it, e.g. network mounts or backup volumes
+    /// (`--one-file-system`).
+    #[must_use]
+    pub const fn with_one_file_system(mut self, one_file_system: bool) -> Self {
+        self.walk_options.same_file_system = one_file_system;
+        self
     }
 
-    /// Enable or disable quiet mode (suppresses progress spinner).
+    /// Walk into mounted network file systems (NFS, SMB, FUSE) instead of
+    /// skipping them (`--allow-network-fs`).
     ///
-    /// When quiet mode is active the scanning spinner is hidden, which is
-    /// required for `--json` output so that only the final JSON is printed.
+    /// By default, a directory below a root that is the mount point of a
+    /// network file system is neither searched nor reported, and a warning
+    /// names it. A root on a network file system is still scanned.
     #[must_use]
-    pub const fn with_quiet(mut self, quiet: bool) -> Self {
-        self.quiet = quiet;
+    pub const fn with_network_fs(mut self, network_fs: bool) -> Self {
+        self.network_fs = network_fs;
         self
     }
 
+    /// Search no deeper than `max_depth` directories below each root, if
+    /// set (`--max-depth`); `0` only looks at the roots themselves.
+    #[must_use]
+    pub const fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Report only the projects at least `min_depth` directories below each
+    /// root (`--min-depth`). Shallower directories are still walked through.
+    #[must_use]
+    pub const fn with_min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+
+    /// Stop the traversal once one of `limits` is reached.
+    ///
+    /// The projects found in the directories visited so far are still sized
+    /// and returned; [`Scanner::truncation`] tells whether the scan was cut
+    /// short.
+    #[must_use]
+    pub const fn with_limits(mut self, limits: ScanLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Skip the directories ignored by `.gitignore` and `.ignore` files
+    /// (see [`crate::utils::gitignore`]), as by default, or search them too
+    /// (`--no-ignore`).
+    #[must_use]
+    pub const fn with_ignore_files(mut self, ignore_files: bool) -> Self {
+        self.ignore_files = ignore_files;
+        self
+    }
+
+    /// Measure the build artifacts found, as by default, or leave their
+    /// size at 0, standing for an unknown size (`--no-size`).
+    ///
+    /// Without sizing, empty artifacts are kept, as they cannot be told
+    /// apart from the others.
+    #[must_use]
+    pub const fn with_sizing(mut self, sizing: bool) -> Self {
+        self.sizing = sizing;
+        self
+    }
+
+    /// Measure the length of files, as by default, or the space they take
+    /// up on disk (`--du-mode`).
+    #[must_use]
+    pub const fn with_du_mode(mut self, du_mode: DuMode) -> Self {
+        self.du_mode = du_mode;
+        self
+    }
+
+    /// Reuse the sizes recorded in `index` for the artifacts not modified
+    /// since, and record those sized; see [`Scanner::index`].
+    #[must_use]
+    pub fn with_index(mut self, index: ScanIndex) -> Self {
+        self.index = Some(Mutex::new(index));
+        self
+    }
+
+    /// Scan `fs` instead of the local file system.
+    #[must_use]
+    pub fn with_fs(mut self, fs: FsHandle) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Call `observer` with each [`ScanEvent`] of the scan, e.g. to draw
+    /// progress or to stream results while the scan goes on
+    /// (`--output ndjson`). The scanner itself prints nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use clean_dev_dirs::{ProjectFilter, ScanOptions, Scanner};
+    /// # use clean_dev_dirs::scanner::ScanEvent;
+    /// # use std::path::Path;
+    /// # use std::sync::Arc;
+    /// # use std::sync::atomic::{AtomicU64, Ordering};
+    /// let reclaimable = Arc::new(AtomicU64::new(0));
+    /// let total = Arc::clone(&reclaimable);
+    /// let scanner = Scanner::new(ScanOptions::default(), ProjectFilter::All)
+    ///     .with_observer(move |event| {
+    ///         if let ScanEvent::SizeComputed(project) = event {
+    ///             total.fetch_add(project.total_size(), Ordering::Relaxed);
+    ///         }
+    ///     });
+    /// scanner.scan_directory(Path::new("."));
+    /// println!("{} bytes reclaimable", reclaimable.load(Ordering::Relaxed));
+    /// ```
+    #[must_use]
+    pub fn with_observer(
+        mut self,
+        observer: impl Fn(ScanEvent<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Report `event` to the observer, if any.
+    fn notify(&self, event: ScanEvent<'_>) {
+        if let Some(observer) = &self.observer {
+            observer(event);
+        }
+    }
+
+    /// Return why the last [`Scanner::scan_directory`] call stopped before
+    /// covering the whole tree, or `None` if it covered all of it (or was
+    /// cancelled).
+    #[must_use]
+    pub fn truncation(&self) -> Option<ScanTruncation> {
+        *self
+            .truncation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Return the index given to [`Scanner::with_index`], with the sizes of
+    /// the artifacts sized since, to be saved for the next run.
+    #[must_use]
+    pub fn index(&self) -> Option<ScanIndex> {
+        self.index
+            .as_ref()
+            .map(|index| index.lock().unwrap_or_else(PoisonError::into_inner).clone())
+    }
+
+    /// Return the number of directories skipped by the last
+    /// [`Scanner::scan_directory`] call, by reason.
+    #[must_use]
+    pub fn skip_stats(&self) -> SkipStats {
+        *self
+            .skip_stats
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
     /// Scan a directory tree for development projects.
     ///
     /// This method performs a recursive scan of the specified directory to find
     /// development projects. It operates in two phases:
     /// 1. Directory traversal to identify potential projects
+    ///    ([`Scanner::find_projects`])
     /// 2. Parallel size calculation for build directories
+    ///    ([`Scanner::size_projects`])
     ///
     /// # Arguments
     ///
@@ -96,13 +660,11 @@ impl Scanner {
     /// # Returns
     ///
     /// A vector of `Project` instances representing all detected projects with
-    /// non-zero build directory sizes.
-    ///
-    /// # Panics
-    ///
-    /// This method may panic if the progress bar template string is invalid,
-    /// though this should not occur under normal circumstances as the template
-    /// is hardcoded and valid.
+    /// non-zero build directory sizes. Projects and build artifacts ignored by
+    /// a `.cleanignore` file (see [`crate::utils::cleanignore`]) are left
+    /// out. If the scan was cancelled (see
+    /// [`Scanner::with_cancellation`]), only the projects whose sizes were
+    /// completely calculated before cancellation are returned.
     ///
     /// # Examples
     ///
@@ -119,66 +681,343 @@ impl Scanner {
     /// size calculation to maximize performance on systems with multiple cores
     /// and fast storage.
     pub fn scan_directory(&self, root: &Path) -> Vec<Project> {
-        let errors = Arc::new(Mutex::new(Vec::<String>::new()));
+        self.scan_directories(&[root.to_path_buf()])
+    }
+
+    /// Scan the directories given to [`ScannerBuilder::root`], as
+    /// [`Scanner::scan_directories`] does.
+    pub fn scan(&self) -> Vec<Project> {
+        self.scan_directories(&self.roots)
+    }
 
-        let progress = if self.quiet {
-            ProgressBar::hidden()
+    /// Scan several directory trees for development projects, as
+    /// [`Scanner::scan_directory`] does for one.
+    ///
+    /// A project found under more than one of `roots` (because a root is
+    /// inside another, or through different spellings of the same path) is
+    /// only returned once, with the `.cleanignore` files of the first root it
+    /// is under applying to it. The scan limits apply to all the trees
+    /// together.
+    pub fn scan_directories(&self, roots: &[PathBuf]) -> Vec<Project> {
+        self.in_pool(|| self.size_projects(self.find_projects(roots)))
+    }
+
+    /// Scan the directory tree at `root` like [`Scanner::scan_directory`],
+    /// on a blocking thread of the tokio runtime, yielding each project as
+    /// soon as its artifacts are sized, in no particular order.
+    ///
+    /// Must be called from within a tokio runtime. Dropping the stream does
+    /// not stop the scan; cancel the token given to
+    /// [`Scanner::with_cancellation`] to stop it. Once the stream ends,
+    /// [`Scanner::truncation`] and [`Scanner::skip_stats`] describe the scan.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use clean_dev_dirs::Scanner;
+    /// # use std::sync::Arc;
+    /// # use tokio_stream::StreamExt;
+    /// # async fn run() {
+    /// let scanner = Arc::new(Scanner::builder().build());
+    /// let mut projects = Arc::clone(&scanner).scan_directory_async("/work".into());
+    /// while let Some(project) = projects.next().await {
+    ///     println!("{project}: {} bytes", project.total_size());
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub fn scan_directory_async(
+        self: Arc<Self>,
+        root: PathBuf,
+    ) -> impl tokio_stream::Stream<Item = Project> + Send + 'static {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        tokio::task::spawn_blocking(move || {
+            self.in_pool(|| {
+                self.find_projects(&[root])
+                    .into_par_iter()
+                    .filter_map(|project| self.size_project(project))
+                    .for_each(|project| {
+                        // The stream was dropped: the projects are not wanted
+                        let _ = sender.send(project);
+                    });
+            });
+        });
+        tokio_stream::wrappers::UnboundedReceiverStream::new(receiver)
+    }
+
+    /// Run `op` on the pool of [`ScannerBuilder::threads`] threads, if any.
+    fn in_pool<T: Send>(&self, op: impl FnOnce() -> T + Send) -> T {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Find the projects under `roots` without sizing their build artifacts.
+    ///
+    /// This is the first phase of [`Scanner::scan_directories`]: the returned
+    /// projects have their artifacts narrowed by `.cleanignore` files, the
+    /// path filter and [`Scanner::with_artifacts`], but most artifact sizes
+    /// are still 0. Cheap filters, such as the modification time checked by
+    /// `--keep-days`, can then drop projects before the costly
+    /// [`Scanner::size_projects`] phase walks their artifacts.
+    pub fn find_projects(&self, roots: &[PathBuf]) -> Vec<Project> {
+        let errors = Arc::new(Mutex::new(Vec::<String>::new()));
+        let cleanignore = CleanIgnore::with_fs(self.fs.clone());
+        let gitignore = GitIgnore::with_fs(self.fs.clone());
+
+        self.notify(ScanEvent::WalkStarted);
+
+        // Find all potential project directories. Projects detected while
+        // walking, to tell which directories are their artifacts, are kept
+        // for the detection below.
+        let mut skip_stats = SkipStats::default();
+        let started = Instant::now();
+        let mut dirs_visited = 0;
+        let mut truncation = None;
+        let detected = Mutex::new(HashMap::new());
+        let network_mounts = if self.network_fs {
+            NetworkMounts::default()
         } else {
-            let pb = ProgressBar::new_spinner();
-            pb.set_style(
-                ProgressStyle::default_spinner()
-                    .template("{spinner:.green} {msg}")
-                    .unwrap(),
-            );
-            pb.set_message("Scanning directories...");
-            pb
+            NetworkMounts::detect()
         };
+        let (detected_ref, errors_ref, mounts_ref) = (&detected, &errors, &network_mounts);
+        let candidates: Vec<_> = roots
+            .iter()
+            .flat_map(|root| {
+                let prune = move |entry: &Entry| {
+                    self.is_prunable(entry, mounts_ref, detected_ref, errors_ref)
+                };
+                self.fs
+                    .walk_pruned(root, self.walk_options, Box::new(prune))
+                    .map(move |entry| (root, entry))
+            })
+            .take_while(|(_, entry)| {
+                if self.cancellation.is_cancelled() {
+                    return false;
+                }
+                let is_dir = entry.as_ref().is_ok_and(Entry::is_dir);
+                truncation = self
+                    .limits
+                    .exceeded(started.elapsed(), dirs_visited + u64::from(is_dir))
+                    .map(|limit| ScanTruncation {
+                        limit,
+                        dirs_visited,
+                    });
+                dirs_visited += u64::from(is_dir);
+                truncation.is_none()
+            })
+            .filter_map(|(root, entry)| entry.ok().map(|entry| (root, entry)))
+            .filter(|(_, entry)| entry.depth >= self.min_depth)
+            .filter(|(root, entry)| {
+                if entry.depth > 0 && network_mounts.is_mount_point(&entry.path) {
+                    tracing::warn!(
+                        "Skipped network file system at {} (use --allow-network-fs to scan it)",
+                        entry.path.display()
+                    );
+                    skip_stats.record(DirSkip::NetworkFs);
+                    return false;
+                }
+                let reason = self.skip_reason(entry).or_else(|| {
+                    (self.ignore_files && entry.is_dir() && gitignore.is_ignored(root, &entry.path))
+                        .then_some(DirSkip::Ignored)
+                });
+                let Some(reason) = reason else {
+                    return true;
+                };
+                if entry.is_dir() {
+                    tracing::trace!("Skipped {} ({reason:?})", entry.path.display());
+                    skip_stats.record(reason);
+                }
+                false
+            })
+            .collect();
 
-        // Find all potential project directories
-        let potential_projects: Vec<_> = WalkDir::new(root)
-            .into_iter()
-            .filter_map(Result::ok)
-            .filter(|entry| self.should_scan_entry(entry))
-            .collect::<Vec<_>>()
+        let detected = detected
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        let potential_projects: Vec<_> = candidates
             .into_par_iter()
-            .filter_map(|entry| self.detect_project(&entry, &errors))
+            .filter_map(|(root, entry)| {
+                let project = detected
+                    .get(&entry.path)
+                    .map_or_else(|| self.detect_project(&entry, &errors), Clone::clone)?;
+                tracing::debug!("Found {project}");
+                Some((root, project))
+            })
             .collect();
 
-        progress.finish_with_message("✅ Directory scan complete");
+        self.notify(ScanEvent::WalkFinished {
+            cancelled: self.cancellation.is_cancelled(),
+            truncation,
+        });
 
-        // Process projects in parallel to calculate sizes
-        let projects_with_sizes: Vec<_> = potential_projects
+        let projects: Vec<_> = potential_projects
             .into_par_iter()
-            .filter_map(|mut project| {
-                if project.build_arts.size == 0 {
-                    project.build_arts.size =
-                        self.calculate_build_dir_size(&project.build_arts.path);
-                }
+            .filter_map(|(root, project)| self.select_artifacts(&cleanignore, root, project))
+            .collect();
+        let projects = if roots.len() > 1 {
+            self.deduplicate(projects)
+        } else {
+            projects
+        };
+
+        for error in errors.lock().unwrap().iter() {
+            tracing::info!("{error}");
+        }
+        tracing::info!("{skip_stats}");
+        self.record_walk(skip_stats, truncation);
 
-                if project.build_arts.size > 0 {
-                    Some(project)
+        for project in &projects {
+            self.notify(ScanEvent::ProjectFound(project));
+        }
+        projects
+    }
+
+    /// Detect the projects rooted at exactly `paths`, without searching the
+    /// directories below them: the first phase of a scan when the project
+    /// roots come from a list (`--paths-from`) rather than a directory walk.
+    ///
+    /// Paths that are not directories, or hold no project of the selected
+    /// types, are left out and logged, shown with `--verbose`. As with
+    /// [`Scanner::find_projects`], the artifacts are narrowed but not sized,
+    /// and a path listed twice gives a single project.
+    pub fn find_listed_projects(&self, paths: &[PathBuf]) -> Vec<Project> {
+        let errors = Arc::new(Mutex::new(Vec::<String>::new()));
+        let cleanignore = CleanIgnore::with_fs(self.fs.clone());
+
+        let projects: Vec<_> = paths
+            .par_iter()
+            .filter_map(|path| {
+                if self.cancellation.is_cancelled() {
+                    return None;
+                }
+                let entry = Entry {
+                    path: path.clone(),
+                    depth: 0,
+                    kind: EntryKind::Dir,
+                };
+                let project = if self.fs.is_dir(path) {
+                    self.detect_project(&entry, &errors)
                 } else {
                     None
+                };
+                if project.is_none() {
+                    errors
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(format!("No project found at {}", path.display()));
                 }
+                self.select_artifacts(&cleanignore, path, project?)
             })
             .collect();
 
-        // Print errors if verbose
-        if self.scan_options.verbose {
-            let errors = errors.lock().unwrap();
-            for error in errors.iter() {
-                eprintln!("{}", error.red());
+        for error in errors.lock().unwrap_or_else(PoisonError::into_inner).iter() {
+            tracing::info!("{error}");
+        }
+        self.record_walk(SkipStats::default(), None);
+
+        let projects = self.deduplicate(projects);
+        for project in &projects {
+            self.notify(ScanEvent::ProjectFound(project));
+        }
+        projects
+    }
+
+    /// Narrow the artifacts of `project`, found under `root`, to those
+    /// selected by [`Scanner::with_artifacts`] and neither excluded by the
+    /// path filter nor ignored by a `.cleanignore` file.
+    ///
+    /// Returns `None` if the project itself is ignored or has no artifact
+    /// left.
+    fn select_artifacts(
+        &self,
+        cleanignore: &CleanIgnore,
+        root: &Path,
+        mut project: Project,
+    ) -> Option<Project> {
+        if cleanignore.is_ignored(root, &project.root_path) {
+            return None;
+        }
+        project.build_arts.retain(|artifact| {
+            ArtifactSelector::any_selects(&self.artifacts, &project.kind, artifact)
+                && !self.path_filter.is_excluded(&artifact.path)
+                && !cleanignore.is_ignored(root, &artifact.path)
+        });
+        (!project.build_arts.is_empty()).then_some(project)
+    }
+
+    /// Keep the directories skipped and the truncation of the last walk,
+    /// for [`Scanner::skip_stats`] and [`Scanner::truncation`].
+    fn record_walk(&self, skip_stats: SkipStats, truncation: Option<ScanTruncation>) {
+        *self
+            .skip_stats
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = skip_stats;
+        *self
+            .truncation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = truncation;
+    }
+
+    /// Keep the first of the projects with the same root directory, once
+    /// symbolic links and relative paths are resolved.
+    fn deduplicate(&self, projects: Vec<Project>) -> Vec<Project> {
+        let mut seen = HashSet::new();
+        projects
+            .into_iter()
+            .filter(|project| {
+                let path = self
+                    .fs
+                    .canonicalize(&project.root_path)
+                    .unwrap_or_else(|_| project.root_path.clone());
+                seen.insert(path)
+            })
+            .collect()
+    }
+
+    /// Size the build artifacts of `projects` in parallel, the second phase
+    /// of [`Scanner::scan_directories`].
+    ///
+    /// Artifacts that are empty are dropped, and so are the projects left
+    /// without any. If the scan is cancelled, only the projects whose sizes
+    /// were completely calculated before cancellation are returned. Nothing
+    /// is measured if sizing was turned off with [`Scanner::with_sizing`].
+    #[must_use]
+    pub fn size_projects(&self, projects: Vec<Project>) -> Vec<Project> {
+        projects
+            .into_par_iter()
+            .filter_map(|project| self.size_project(project))
+            .collect()
+    }
+
+    /// Size the build artifacts of `project`, or return `None` if it has no
+    /// artifact left or the scan was cancelled, see
+    /// [`Scanner::size_projects`].
+    fn size_project(&self, mut project: Project) -> Option<Project> {
+        if self.sizing {
+            for artifact in &mut project.build_arts {
+                // Sizes compared during detection are apparent ones
+                if artifact.size == 0 || self.du_mode == DuMode::Disk {
+                    // Drop projects whose sizing was interrupted by cancellation
+                    artifact.size = self.indexed_size(&project.kind, &artifact.path)?;
+                }
             }
+            project.build_arts.retain(|artifact| artifact.size > 0);
         }
 
-        projects_with_sizes
+        if project.build_arts.is_empty() {
+            return None;
+        }
+        self.notify(ScanEvent::SizeComputed(&project));
+        Some(project)
     }
 
     /// Calculate the total size of a build directory.
     ///
     /// This method recursively traverses the specified directory and sums up
     /// the sizes of all files contained within it. It handles errors gracefully
-    /// and optionally reports them in verbose mode.
+    /// and logs them at the info level, shown with `--verbose`.
     ///
     /// # Arguments
     ///
@@ -186,39 +1025,231 @@ impl Scanner {
     ///
     /// # Returns
     ///
-    /// The total size of all files in the directory, in bytes. Returns 0 if
-    /// the directory doesn't exist or cannot be accessed.
+    /// The total size of all files in the directory, in bytes. Returns
+    /// `Some(0)` if the directory doesn't exist or cannot be accessed, and
+    /// `None` if the scan was cancelled before the size was fully calculated.
     ///
     /// # Performance
     ///
     /// This method can be CPU and I/O intensive for large directories with
     /// many files. It's designed to be called in parallel for multiple
-    /// directories to maximize throughput.
-    fn calculate_build_dir_size(&self, path: &Path) -> u64 {
-        if !path.exists() {
-            return 0;
+    /// directories to maximize throughput, and sizes the entries of the
+    /// directory in parallel too, so that a single huge directory (e.g. a
+    /// 20 GB `node_modules/`) is spread over the thread pool.
+    fn calculate_build_dir_size(&self, path: &Path) -> Option<u64> {
+        if self.cancellation.is_cancelled() {
+            return None;
         }
 
-        let mut total_size = 0u64;
+        if !self.fs.exists(path) {
+            return Some(0);
+        }
 
-        for entry in WalkDir::new(path) {
-            match entry {
-                Ok(entry) => {
-                    if entry.file_type().is_file()
-                        && let Ok(metadata) = entry.metadata()
-                    {
-                        total_size += metadata.len();
-                    }
-                }
-                Err(e) => {
-                    if self.scan_options.verbose {
-                        eprintln!("Warning: {e}");
+        let path = self.fs.extended(path);
+        // A build directory that is a symbolic link or an NTFS junction is
+        // sized as the link, never as its target
+        if self
+            .fs
+            .entry_kind(&path)
+            .is_ok_and(|kind| kind == EntryKind::Other)
+        {
+            return self.entry_size(&path, EntryKind::Other).ok();
+        }
+        let Ok(entries) = self.fs.read_dir(&path) else {
+            // Not a directory, or an unreadable one whose error is logged
+            return self.walk_size(&path);
+        };
+        let own_size = self.entry_size(&path, EntryKind::Dir).unwrap_or(0);
+        entries
+            .into_par_iter()
+            .map(|entry| match self.fs.entry_kind(&entry) {
+                // Symbolic links are not followed, as in the walk
+                Ok(EntryKind::Other) => self.entry_size(&entry, EntryKind::Other).ok(),
+                _ => self.walk_size(&entry),
+            })
+            .sum::<Option<u64>>()
+            .map(|size| own_size + size)
+    }
+
+    /// Return the size of the artifact at `path`, of a `kind` project, from
+    /// the index if it was not modified since it was recorded, or else with
+    /// [`Scanner::calculate_build_dir_size`], recording it.
+    fn indexed_size(&self, kind: &ProjectType, path: &Path) -> Option<u64> {
+        let Some(index) = &self.index else {
+            return self.calculate_build_dir_size(path);
+        };
+        let lock = || index.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // Read before sizing, so that changes made meanwhile are seen next time
+        let modified = self.artifact_modified(path);
+        if let Some(modified) = modified
+            && let Some(size) = lock().size(path, modified, self.du_mode)
+        {
+            return Some(size);
+        }
+
+        let size = self.calculate_build_dir_size(path)?;
+        if let Some(modified) = modified {
+            lock().record(path, modified, size, self.du_mode, kind.clone());
+        }
+        Some(size)
+    }
+
+    /// Return the latest modification time of the artifact directory at
+    /// `path` and of the directories up to two levels below it, or `None` if
+    /// one cannot be read.
+    ///
+    /// Adding or removing an entry updates the modification time of its
+    /// directory, so builds and installs, which write to e.g.
+    /// `target/debug/deps/` or `node_modules/.pnpm/`, show up without walking
+    /// the whole tree. Changes deeper down are only noticed with `--refresh`.
+    fn artifact_modified(&self, path: &Path) -> Option<SystemTime> {
+        let mut latest = self.fs.modified(path).ok()?;
+        let mut pending = vec![(path.to_path_buf(), 0)];
+        while let Some((dir, depth)) = pending.pop() {
+            for entry in self.fs.read_dir(&dir).ok()? {
+                if self.fs.entry_kind(&entry).ok()? == EntryKind::Dir {
+                    latest = latest.max(self.fs.modified(&entry).ok()?);
+                    if depth < 1 {
+                        pending.push((entry, depth + 1));
                     }
                 }
             }
         }
+        Some(latest)
+    }
+
+    /// Sum up the sizes of the files in the tree at `path` on the current
+    /// thread, see [`Scanner::calculate_build_dir_size`].
+    fn walk_size(&self, path: &Path) -> Option<u64> {
+        let mut total_size = 0u64;
+
+        for entry in self.fs.walk(path, false) {
+            if self.cancellation.is_cancelled() {
+                return None;
+            }
+
+            let size = match entry {
+                Ok(entry) => self
+                    .entry_size(&entry.path, entry.kind)
+                    .map_err(|error| WalkError {
+                        path: entry.path,
+                        error,
+                    }),
+                Err(e) => Err(e),
+            };
+
+            match size.or_else(|e| self.size_too_deep(&e).ok_or(e)) {
+                Ok(size) => total_size += size,
+                Err(e) => tracing::info!("{e}"),
+            }
+        }
+
+        Some(total_size)
+    }
+
+    /// Return the size of the entry at `path`, of kind `kind`, as measured
+    /// with [`Scanner::with_du_mode`].
+    ///
+    /// On disk, a file with several hard links only counts the first time
+    /// it is seen during the scan, and directories count too, as with `du`.
+    fn entry_size(&self, path: &Path, kind: EntryKind) -> io::Result<u64> {
+        match (self.du_mode, kind) {
+            (DuMode::Apparent, EntryKind::File) => self.fs.file_len(path),
+            (DuMode::Apparent, EntryKind::Dir | EntryKind::Other) => Ok(0),
+            (DuMode::Disk, _) => {
+                let usage = self.fs.disk_usage(path)?;
+                let first_seen = usage.link.is_none_or(|link| {
+                    self.hard_links
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .insert(link)
+                });
+                Ok(if first_seen { usage.bytes } else { 0 })
+            }
+        }
+    }
+
+    /// Measure the entry behind a walk error with
+    /// [`FileSystem::size_deep`], if its path was too long to be read.
+    fn size_too_deep(&self, error: &WalkError) -> Option<u64> {
+        if !paths::is_too_long(&error.error) {
+            return None;
+        }
+
+        self.fs.size_deep(&error.path).ok()
+    }
+
+    /// Check whether the walk may skip the contents of the directory
+    /// `entry`, which cannot hold any project.
+    ///
+    /// Everything inside a `node_modules/` directory or a network file
+    /// system mounted at one of `network_mounts` is skipped anyway, and
+    /// the build directories of a project hold no projects of their own: a
+    /// candidate directory (see [`Scanner::may_be_artifact`]) is pruned if
+    /// the project in its parent directory, detected here and recorded in
+    /// `detected`, lists it among its artifacts. Sizes are computed
+    /// separately, so walking these directories only costs time.
+    fn is_prunable(
+        &self,
+        entry: &Entry,
+        network_mounts: &NetworkMounts,
+        detected: &Mutex<HashMap<PathBuf, Option<Project>>>,
+        errors: &Arc<Mutex<Vec<String>>>,
+    ) -> bool {
+        let path = entry.path.as_path();
+        if self
+            .max_depth
+            .is_some_and(|max_depth| entry.depth >= max_depth)
+        {
+            return true;
+        }
+        if entry.depth == 0 {
+            return false;
+        }
+        if path.file_name().is_some_and(|name| name == "node_modules")
+            || network_mounts.is_mount_point(path)
+        {
+            return true;
+        }
+        let Some(parent) = path.parent().filter(|_| Self::may_be_artifact(path)) else {
+            return false;
+        };
 
-        total_size
+        let mut detected = detected.lock().unwrap_or_else(PoisonError::into_inner);
+        detected
+            .entry(parent.to_path_buf())
+            .or_insert_with(|| {
+                let parent = Entry {
+                    path: parent.to_path_buf(),
+                    depth: entry.depth - 1,
+                    kind: EntryKind::Dir,
+                };
+                self.detect_project(&parent, errors)
+            })
+            .as_ref()
+            .is_some_and(|project| project.artifact_paths().any(|artifact| artifact == path))
+    }
+
+    /// Check whether `path` is named like a build directory of some
+    /// ecosystem: an excluded or hidden directory, or one of the Unity,
+    /// Unreal and .NET build directories.
+    fn may_be_artifact(path: &Path) -> bool {
+        const OTHER_ARTIFACTS: [&str; 8] = [
+            "Library",
+            "Temp",
+            "Logs",
+            "Intermediate",
+            "DerivedDataCache",
+            "Saved",
+            "Binaries",
+            "bin",
+        ];
+
+        Self::is_excluded_directory(path)
+            || path.file_name().is_some_and(|name| {
+                paths::is_hidden(name) || OTHER_ARTIFACTS.iter().any(|other| name == *other)
+            })
     }
 
     /// Detect a Node.js project in the specified directory.
@@ -250,13 +1281,13 @@ impl Scanner {
         let package_json = path.join("package.json");
         let node_modules = path.join("node_modules");
 
-        if package_json.exists() && node_modules.exists() {
+        if self.fs.exists(&package_json) && self.fs.exists(&node_modules) {
             let name = self.extract_node_project_name(&package_json, errors);
 
-            let build_arts = BuildArtifacts {
+            let build_arts = vec![BuildArtifacts {
                 path: path.join("node_modules"),
                 size: 0, // Will be calculated later
-            };
+            }];
 
             return Some(Project::new(
                 ProjectType::Node,
@@ -295,46 +1326,100 @@ impl Scanner {
     /// - **C/C++ projects**: Presence of `CMakeLists.txt` or `Makefile` with `build/`
     /// - **Swift projects**: Presence of `Package.swift` with `.build/`
     /// - **.NET/C# projects**: Presence of `.csproj` files with `bin/` or `obj/`
-    fn detect_project(
-        &self,
-        entry: &DirEntry,
-        errors: &Arc<Mutex<Vec<String>>>,
-    ) -> Option<Project> {
-        let path = entry.path();
-
-        if !entry.file_type().is_dir() {
+    /// - **`OCaml` projects**: Presence of `dune-project` with `_build/`
+    /// - **Unity projects**: Presence of `ProjectSettings/ProjectVersion.txt` with any of
+    ///   `Library/`, `Temp/`, `obj/` or `Logs/`
+    /// - **Unreal projects**: Presence of a `.uproject` file with any of `Intermediate/`,
+    ///   `DerivedDataCache/`, `Saved/` or `Binaries/`
+    fn detect_project(&self, entry: &Entry, errors: &Arc<Mutex<Vec<String>>>) -> Option<Project> {
+        let path = entry.path.as_path();
+
+        if !entry.is_dir() {
             return None;
         }
 
         // Detectors are tried in order; the first match wins.
         // More specific ecosystems are checked before more generic ones
-        // (e.g. Java before C/C++, since both can use `build/`).
-        self.try_detect(ProjectFilter::Rust, || {
-            self.detect_rust_project(path, errors)
-        })
-        .or_else(|| {
-            self.try_detect(ProjectFilter::Node, || {
-                self.detect_node_project(path, errors)
+        // (e.g. Java before C/C++, since both can use `build/`, and Unity
+        // before .NET, since Unity generates `.csproj` files and `obj/`).
+        let project = self
+            .try_detect(ProjectFilter::Rust, || {
+                self.detect_rust_project(path, errors)
             })
-        })
-        .or_else(|| {
-            self.try_detect(ProjectFilter::Java, || {
-                self.detect_java_project(path, errors)
+            .or_else(|| {
+                self.try_detect(ProjectFilter::Node, || {
+                    self.detect_node_project(path, errors)
+                })
             })
-        })
-        .or_else(|| {
-            self.try_detect(ProjectFilter::Swift, || {
-                self.detect_swift_project(path, errors)
+            .or_else(|| {
+                self.try_detect(ProjectFilter::Java, || {
+                    self.detect_java_project(path, errors)
+                })
             })
-        })
-        .or_else(|| self.try_detect(ProjectFilter::DotNet, || Self::detect_dotnet_project(path)))
-        .or_else(|| {
-            self.try_detect(ProjectFilter::Python, || {
-                self.detect_python_project(path, errors)
+            .or_else(|| {
+                self.try_detect(ProjectFilter::Swift, || {
+                    self.detect_swift_project(path, errors)
+                })
+            })
+            .or_else(|| {
+                self.try_detect(ProjectFilter::Unity, || {
+                    self.detect_unity_project(path, errors)
+                })
+            })
+            .or_else(|| self.try_detect(ProjectFilter::Unreal, || self.detect_unreal_project(path)))
+            .or_else(|| self.try_detect(ProjectFilter::DotNet, || self.detect_dotnet_project(path)))
+            .or_else(|| {
+                self.try_detect(ProjectFilter::OCaml, || {
+                    self.detect_ocaml_project(path, errors)
+                })
             })
+            .or_else(|| {
+                self.try_detect(ProjectFilter::Python, || {
+                    self.detect_python_project(path, errors)
+                })
+            })
+            .or_else(|| self.try_detect(ProjectFilter::Go, || self.detect_go_project(path, errors)))
+            .or_else(|| {
+                self.try_detect(ProjectFilter::Cpp, || self.detect_cpp_project(path, errors))
+            });
+
+        project.map(|mut project| {
+            let caches = self.cache_artifacts(&project);
+            project.build_arts.extend(caches);
+            project
         })
-        .or_else(|| self.try_detect(ProjectFilter::Go, || self.detect_go_project(path, errors)))
-        .or_else(|| self.try_detect(ProjectFilter::Cpp, || self.detect_cpp_project(path, errors)))
+    }
+
+    /// Collect the generic caches (see [`CACHE_ARTIFACTS`]) found in the root
+    /// directory of `project` that are not already among its artifacts, as
+    /// cache artifacts whose size will be calculated later.
+    ///
+    /// Nothing is collected for a project in the home directory, whose
+    /// `.cache/` is the user's cache directory rather than the project's.
+    fn cache_artifacts(&self, project: &Project) -> Vec<BuildArtifacts> {
+        let root = &project.root_path;
+        if dirs::home_dir().is_some_and(|home| &home == root) {
+            return Vec::new();
+        }
+
+        let tsbuildinfo_files = self.fs.read_dir(root).into_iter().flatten().filter(|path| {
+            self.fs.is_file(path)
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == TSBUILDINFO_EXTENSION)
+        });
+
+        CACHE_ARTIFACTS
+            .iter()
+            .map(|name| root.join(name))
+            .filter(|path| self.fs.exists(path))
+            .chain(tsbuildinfo_files)
+            .filter(|path| !project.artifact_paths().any(|artifact| artifact == path))
+            .map(|path| BuildArtifacts {
+                path,
+                size: 0, // Will be calculated later
+            })
+            .collect()
     }
 
     /// Run a detector only if the current project filter allows it.
@@ -382,13 +1467,13 @@ impl Scanner {
         let cargo_toml = path.join("Cargo.toml");
         let target_dir = path.join("target");
 
-        if cargo_toml.exists() && target_dir.exists() {
+        if self.fs.exists(&cargo_toml) && self.fs.exists(&target_dir) {
             let name = self.extract_rust_project_name(&cargo_toml, errors);
 
-            let build_arts = BuildArtifacts {
+            let build_arts = vec![BuildArtifacts {
                 path: path.join("target"),
                 size: 0, // Will be calculated later
-            };
+            }];
 
             return Some(Project::new(
                 ProjectType::Rust,
@@ -471,35 +1556,31 @@ impl Scanner {
     /// # Error Handling
     ///
     /// This method handles both file I/O errors and JSON parsing errors gracefully.
-    /// Errors are optionally reported to the shared error collection in verbose mode.
+    /// Errors are added to the shared error collection, logged after the scan.
     fn extract_node_project_name(
         &self,
         package_json: &Path,
         errors: &Arc<Mutex<Vec<String>>>,
     ) -> Option<String> {
-        match fs::read_to_string(package_json) {
+        match self.fs.read_to_string(package_json) {
             Ok(content) => match from_str::<Value>(&content) {
                 Ok(json) => json
                     .get("name")
                     .and_then(|v| v.as_str())
                     .map(std::string::ToString::to_string),
-                Err(e) => {
-                    if self.scan_options.verbose {
-                        errors
-                            .lock()
-                            .unwrap()
-                            .push(format!("Error parsing {}: {e}", package_json.display()));
-                    }
-                    None
-                }
-            },
-            Err(e) => {
-                if self.scan_options.verbose {
+                Err(e) => {
                     errors
                         .lock()
                         .unwrap()
-                        .push(format!("Error reading {}: {e}", package_json.display()));
+                        .push(format!("Error parsing {}: {e}", package_json.display()));
+                    None
                 }
+            },
+            Err(e) => {
+                errors
+                    .lock()
+                    .unwrap()
+                    .push(format!("Error reading {}: {e}", package_json.display()));
                 None
             }
         }
@@ -510,19 +1591,12 @@ impl Scanner {
         line.starts_with("name") && line.contains('=')
     }
 
-    /// Log a file reading error if verbose mode is enabled.
-    fn log_file_error(
-        &self,
-        file_path: &Path,
-        error: &std::io::Error,
-        errors: &Arc<Mutex<Vec<String>>>,
-    ) {
-        if self.scan_options.verbose {
-            errors
-                .lock()
-                .unwrap()
-                .push(format!("Error reading {}: {error}", file_path.display()));
-        }
+    /// Add a file reading error to the errors logged after the scan.
+    fn log_file_error(file_path: &Path, error: &std::io::Error, errors: &Arc<Mutex<Vec<String>>>) {
+        errors
+            .lock()
+            .unwrap()
+            .push(format!("Error reading {}: {error}", file_path.display()));
     }
 
     /// Parse the name field from TOML content.
@@ -541,16 +1615,17 @@ impl Scanner {
         file_path: &Path,
         errors: &Arc<Mutex<Vec<String>>>,
     ) -> Option<String> {
-        match fs::read_to_string(file_path) {
+        match self.fs.read_to_string(file_path) {
             Ok(content) => Some(content),
             Err(e) => {
-                self.log_file_error(file_path, &e, errors);
+                Self::log_file_error(file_path, &e, errors);
                 None
             }
         }
     }
 
-    /// Determine if a directory entry should be scanned for projects.
+    /// Determine whether a directory entry should be scanned for projects, and
+    /// if not, why.
     ///
     /// This method implements the filtering logic to decide whether a directory
     /// should be traversed during the scanning process. It applies various
@@ -563,13 +1638,15 @@ impl Scanner {
     ///
     /// # Returns
     ///
-    /// - `true` if the directory should be scanned
-    /// - `false` if the directory should be skipped
+    /// - `None` if the directory should be scanned
+    /// - `Some(reason)` if the directory should be skipped
     ///
     /// # Exclusion Rules
     ///
     /// The following directories are excluded from scanning:
     /// - Directories in the user-specified skip list
+    /// - Directories not matching the `--include` globs, or matching an
+    ///   `--exclude` glob
     /// - Any directory inside a `node_modules/` directory (to avoid deep nesting)
     /// - Hidden directories (starting with `.`) except `.cargo`
     /// - Common build/temporary directories: `target`, `build`, `dist`, `out`, etc.
@@ -583,12 +1660,17 @@ impl Scanner {
     /// - Python coverage files
     /// - Node.js modules (already handled above but added for completeness)
     /// - .NET `obj/` directory
-    fn should_scan_entry(&self, entry: &DirEntry) -> bool {
-        let path = entry.path();
+    /// - `OCaml` dune `_build/` directory
+    fn skip_reason(&self, entry: &Entry) -> Option<DirSkip> {
+        let path = entry.path.as_path();
 
         // Early return if path is in skip list
         if self.is_path_in_skip_list(path) {
-            return false;
+            return Some(DirSkip::SkipList);
+        }
+
+        if !self.path_filter.allows(path) {
+            return Some(DirSkip::PathFilter);
         }
 
         // Skip any directory inside a node_modules directory
@@ -596,16 +1678,16 @@ impl Scanner {
             .ancestors()
             .any(|ancestor| ancestor.file_name().and_then(|n| n.to_str()) == Some("node_modules"))
         {
-            return false;
+            return Some(DirSkip::NodeModules);
         }
 
         // Skip hidden directories (except .cargo for Rust)
         if Self::is_hidden_directory_to_skip(path) {
-            return false;
+            return Some(DirSkip::Hidden);
         }
 
         // Skip common non-project directories
-        !Self::is_excluded_directory(path)
+        Self::is_excluded_directory(path).then_some(DirSkip::Blocklist)
     }
 
     /// Check if a path is in the skip list
@@ -623,8 +1705,7 @@ impl Scanner {
     /// Check if directory is hidden and should be skipped
     fn is_hidden_directory_to_skip(path: &Path) -> bool {
         path.file_name()
-            .and_then(|n| n.to_str())
-            .is_some_and(|name| name.starts_with('.') && name != ".cargo")
+            .is_some_and(|name| paths::is_hidden(name) && name != ".cargo")
     }
 
     /// Check if directory is in the excluded list
@@ -651,6 +1732,7 @@ impl Scanner {
             ".coverage",
             "node_modules",
             "obj",
+            "_build",
         ];
 
         path.file_name()
@@ -706,7 +1788,9 @@ impl Scanner {
         ];
 
         // Check if any config file exists
-        let has_config = config_files.iter().any(|&file| path.join(file).exists());
+        let has_config = config_files
+            .iter()
+            .any(|&file| self.fs.exists(&path.join(file)));
 
         if !has_config {
             return None;
@@ -719,23 +1803,22 @@ impl Scanner {
         for &dir_name in &build_dirs {
             let dir_path = path.join(dir_name);
 
-            if dir_path.exists()
-                && dir_path.is_dir()
-                && let Ok(size) = Self::calculate_directory_size(&dir_path)
-                && size > largest_size
-            {
-                largest_size = size;
-                largest_build_dir = Some(dir_path);
+            if self.fs.is_dir(&dir_path) {
+                let size = self.fs.tree_size(&dir_path);
+                if size > largest_size {
+                    largest_size = size;
+                    largest_build_dir = Some(dir_path);
+                }
             }
         }
 
         if let Some(build_path) = largest_build_dir {
             let name = self.extract_python_project_name(path, errors);
 
-            let build_arts = BuildArtifacts {
+            let build_arts = vec![BuildArtifacts {
                 path: build_path,
                 size: largest_size,
-            };
+            }];
 
             return Some(Project::new(
                 ProjectType::Python,
@@ -773,13 +1856,13 @@ impl Scanner {
         let go_mod = path.join("go.mod");
         let vendor_dir = path.join("vendor");
 
-        if go_mod.exists() && vendor_dir.exists() {
+        if self.fs.exists(&go_mod) && self.fs.exists(&vendor_dir) {
             let name = self.extract_go_project_name(&go_mod, errors);
 
-            let build_arts = BuildArtifacts {
+            let build_arts = vec![BuildArtifacts {
                 path: path.join("vendor"),
                 size: 0, // Will be calculated later
-            };
+            }];
 
             return Some(Project::new(
                 ProjectType::Go,
@@ -832,7 +1915,7 @@ impl Scanner {
         errors: &Arc<Mutex<Vec<String>>>,
     ) -> Option<String> {
         let pyproject_toml = path.join("pyproject.toml");
-        if !pyproject_toml.exists() {
+        if !self.fs.exists(&pyproject_toml) {
             return None;
         }
 
@@ -847,7 +1930,7 @@ impl Scanner {
         errors: &Arc<Mutex<Vec<String>>>,
     ) -> Option<String> {
         let setup_py = path.join("setup.py");
-        if !setup_py.exists() {
+        if !self.fs.exists(&setup_py) {
             return None;
         }
 
@@ -862,7 +1945,7 @@ impl Scanner {
         errors: &Arc<Mutex<Vec<String>>>,
     ) -> Option<String> {
         let setup_cfg = path.join("setup.cfg");
-        if !setup_cfg.exists() {
+        if !self.fs.exists(&setup_cfg) {
             return None;
         }
 
@@ -910,8 +1993,7 @@ impl Scanner {
     /// Fallback to directory name
     fn fallback_to_directory_name(path: &Path) -> Option<String> {
         path.file_name()
-            .and_then(|name| name.to_str())
-            .map(std::string::ToString::to_string)
+            .map(|name| name.to_string_lossy().into_owned())
     }
 
     /// Extract the project name from a `go.mod` file.
@@ -976,13 +2058,13 @@ impl Scanner {
         let target_dir = path.join("target");
 
         // Maven project: pom.xml + target/
-        if pom_xml.exists() && target_dir.exists() {
+        if self.fs.exists(&pom_xml) && self.fs.exists(&target_dir) {
             let name = self.extract_java_maven_project_name(&pom_xml, errors);
 
-            let build_arts = BuildArtifacts {
+            let build_arts = vec![BuildArtifacts {
                 path: target_dir,
                 size: 0,
-            };
+            }];
 
             return Some(Project::new(
                 ProjectType::Java,
@@ -993,17 +2075,17 @@ impl Scanner {
         }
 
         // Gradle project: build.gradle(.kts) + build/
-        let has_gradle =
-            path.join("build.gradle").exists() || path.join("build.gradle.kts").exists();
+        let has_gradle = self.fs.exists(&path.join("build.gradle"))
+            || self.fs.exists(&path.join("build.gradle.kts"));
         let build_dir = path.join("build");
 
-        if has_gradle && build_dir.exists() {
+        if has_gradle && self.fs.exists(&build_dir) {
             let name = self.extract_java_gradle_project_name(path, errors);
 
-            let build_arts = BuildArtifacts {
+            let build_arts = vec![BuildArtifacts {
                 path: build_dir,
                 size: 0,
-            };
+            }];
 
             return Some(Project::new(
                 ProjectType::Java,
@@ -1050,7 +2132,7 @@ impl Scanner {
     ) -> Option<String> {
         for settings_file in &["settings.gradle", "settings.gradle.kts"] {
             let settings_path = path.join(settings_file);
-            if settings_path.exists()
+            if self.fs.exists(&settings_path)
                 && let Some(content) = self.read_file_content(&settings_path, errors)
             {
                 for line in content.lines() {
@@ -1082,24 +2164,24 @@ impl Scanner {
     fn detect_cpp_project(&self, path: &Path, errors: &Arc<Mutex<Vec<String>>>) -> Option<Project> {
         let build_dir = path.join("build");
 
-        if !build_dir.exists() {
+        if !self.fs.exists(&build_dir) {
             return None;
         }
 
         let cmake_file = path.join("CMakeLists.txt");
         let makefile = path.join("Makefile");
 
-        if cmake_file.exists() || makefile.exists() {
-            let name = if cmake_file.exists() {
+        if self.fs.exists(&cmake_file) || self.fs.exists(&makefile) {
+            let name = if self.fs.exists(&cmake_file) {
                 self.extract_cpp_cmake_project_name(&cmake_file, errors)
             } else {
                 Self::fallback_to_directory_name(path)
             };
 
-            let build_arts = BuildArtifacts {
+            let build_arts = vec![BuildArtifacts {
                 path: build_dir,
                 size: 0,
-            };
+            }];
 
             return Some(Project::new(
                 ProjectType::Cpp,
@@ -1161,13 +2243,13 @@ impl Scanner {
         let package_swift = path.join("Package.swift");
         let build_dir = path.join(".build");
 
-        if package_swift.exists() && build_dir.exists() {
+        if self.fs.exists(&package_swift) && self.fs.exists(&build_dir) {
             let name = self.extract_swift_project_name(&package_swift, errors);
 
-            let build_arts = BuildArtifacts {
+            let build_arts = vec![BuildArtifacts {
                 path: build_dir,
                 size: 0,
-            };
+            }];
 
             return Some(Project::new(
                 ProjectType::Swift,
@@ -1209,42 +2291,43 @@ impl Scanner {
     ///
     /// 1. At least one `.csproj` file exists in the directory
     /// 2. At least one of `bin/` or `obj/` directories exists
-    fn detect_dotnet_project(path: &Path) -> Option<Project> {
+    fn detect_dotnet_project(&self, path: &Path) -> Option<Project> {
         let bin_dir = path.join("bin");
         let obj_dir = path.join("obj");
 
-        let has_build_dir = bin_dir.exists() || obj_dir.exists();
+        let has_build_dir = self.fs.exists(&bin_dir) || self.fs.exists(&obj_dir);
         if !has_build_dir {
             return None;
         }
 
-        let csproj_file = Self::find_file_with_extension(path, "csproj")?;
+        let csproj_file = self.find_file_with_extension(path, "csproj")?;
 
         // Pick the larger of bin/ and obj/ as the primary build artifact
-        let (build_path, precomputed_size) = match (bin_dir.exists(), obj_dir.exists()) {
-            (true, true) => {
-                let bin_size = Self::calculate_directory_size(&bin_dir).unwrap_or(0);
-                let obj_size = Self::calculate_directory_size(&obj_dir).unwrap_or(0);
-                if obj_size >= bin_size {
-                    (obj_dir, obj_size)
-                } else {
-                    (bin_dir, bin_size)
+        let (build_path, precomputed_size) =
+            match (self.fs.exists(&bin_dir), self.fs.exists(&obj_dir)) {
+                (true, true) => {
+                    let bin_size = self.fs.tree_size(&bin_dir);
+                    let obj_size = self.fs.tree_size(&obj_dir);
+                    if obj_size >= bin_size {
+                        (obj_dir, obj_size)
+                    } else {
+                        (bin_dir, bin_size)
+                    }
                 }
-            }
-            (true, false) => (bin_dir, 0),
-            (false, true) => (obj_dir, 0),
-            (false, false) => return None,
-        };
+                (true, false) => (bin_dir, 0),
+                (false, true) => (obj_dir, 0),
+                (false, false) => return None,
+            };
 
         let name = csproj_file
             .file_stem()
             .and_then(|s| s.to_str())
             .map(std::string::ToString::to_string);
 
-        let build_arts = BuildArtifacts {
+        let build_arts = vec![BuildArtifacts {
             path: build_path,
             size: precomputed_size,
-        };
+        }];
 
         Some(Project::new(
             ProjectType::DotNet,
@@ -1254,42 +2337,230 @@ impl Scanner {
         ))
     }
 
-    /// Find the first file with a given extension in a directory.
-    fn find_file_with_extension(dir: &Path, extension: &str) -> Option<std::path::PathBuf> {
-        let entries = fs::read_dir(dir).ok()?;
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() && path.extension().and_then(|e| e.to_str()) == Some(extension) {
-                return Some(path);
-            }
+    /// Detect a Unity project in the specified directory.
+    ///
+    /// This method checks for the `ProjectSettings/ProjectVersion.txt` file
+    /// written by the Unity editor, and collects every editor-generated
+    /// directory that exists as a build artifact.
+    ///
+    /// # Detection Criteria
+    ///
+    /// 1. `ProjectSettings/ProjectVersion.txt` file exists
+    /// 2. At least one of `Library/`, `Temp/`, `obj/` or `Logs/` exists
+    fn detect_unity_project(
+        &self,
+        path: &Path,
+        errors: &Arc<Mutex<Vec<String>>>,
+    ) -> Option<Project> {
+        const UNITY_ARTIFACT_DIRS: [&str; 4] = ["Library", "Temp", "obj", "Logs"];
+
+        let project_settings = path.join("ProjectSettings");
+        if !self.fs.exists(&project_settings.join("ProjectVersion.txt")) {
+            return None;
+        }
+
+        let build_arts = self.existing_artifact_dirs(path, &UNITY_ARTIFACT_DIRS);
+        if build_arts.is_empty() {
+            return None;
+        }
+
+        let name = self.extract_unity_project_name(&project_settings, errors);
+
+        Some(Project::new(
+            ProjectType::Unity,
+            path.to_path_buf(),
+            build_arts,
+            name,
+        ))
+    }
+
+    /// Extract the project name from `ProjectSettings/ProjectSettings.asset`.
+    ///
+    /// Looks for the `productName:` entry, falling back to the directory name
+    /// when the asset file is missing or has no product name.
+    fn extract_unity_project_name(
+        &self,
+        project_settings: &Path,
+        errors: &Arc<Mutex<Vec<String>>>,
+    ) -> Option<String> {
+        let project_root = project_settings.parent()?;
+        let settings_asset = project_settings.join("ProjectSettings.asset");
+
+        if !self.fs.exists(&settings_asset) {
+            return Self::fallback_to_directory_name(project_root);
+        }
+
+        self.read_file_content(&settings_asset, errors)
+            .and_then(|content| {
+                content.lines().find_map(|line| {
+                    line.trim()
+                        .strip_prefix("productName:")
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string)
+                })
+            })
+            .or_else(|| Self::fallback_to_directory_name(project_root))
+    }
+
+    /// Detect an Unreal Engine project in the specified directory.
+    ///
+    /// This method checks for a `.uproject` descriptor and collects every
+    /// generated directory that exists as a separate build artifact, so each
+    /// one is sized and can be selected individually.
+    ///
+    /// # Detection Criteria
+    ///
+    /// 1. At least one `.uproject` file exists in the directory
+    /// 2. At least one of `Intermediate/`, `DerivedDataCache/`, `Saved/` or `Binaries/` exists
+    fn detect_unreal_project(&self, path: &Path) -> Option<Project> {
+        const UNREAL_ARTIFACT_DIRS: [&str; 4] =
+            ["Intermediate", "DerivedDataCache", "Saved", "Binaries"];
+
+        let build_arts = self.existing_artifact_dirs(path, &UNREAL_ARTIFACT_DIRS);
+        if build_arts.is_empty() {
+            return None;
+        }
+
+        let uproject_file = self.find_file_with_extension(path, "uproject")?;
+
+        let name = uproject_file
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(std::string::ToString::to_string);
+
+        Some(Project::new(
+            ProjectType::Unreal,
+            path.to_path_buf(),
+            build_arts,
+            name,
+        ))
+    }
+
+    /// Collect the subdirectories of `path` named in `dirs` that exist, as
+    /// build artifacts whose size will be calculated later.
+    fn existing_artifact_dirs(&self, path: &Path, dirs: &[&str]) -> Vec<BuildArtifacts> {
+        dirs.iter()
+            .map(|dir| path.join(dir))
+            .filter(|dir| self.fs.is_dir(dir))
+            .map(|dir| BuildArtifacts {
+                path: dir,
+                size: 0, // Will be calculated later
+            })
+            .collect()
+    }
+
+    /// Detect an `OCaml` project in the specified directory.
+    ///
+    /// This method checks for a `dune-project` file and the `_build/`
+    /// directory created by dune to identify `OCaml` projects.
+    ///
+    /// # Detection Criteria
+    ///
+    /// 1. `dune-project` file exists
+    /// 2. `_build/` directory exists
+    fn detect_ocaml_project(
+        &self,
+        path: &Path,
+        errors: &Arc<Mutex<Vec<String>>>,
+    ) -> Option<Project> {
+        let dune_project = path.join("dune-project");
+        let build_dir = path.join("_build");
+
+        if self.fs.exists(&dune_project) && self.fs.exists(&build_dir) {
+            let name = self.extract_ocaml_project_name(&dune_project, errors);
+
+            let build_arts = vec![BuildArtifacts {
+                path: build_dir,
+                size: 0,
+            }];
+
+            return Some(Project::new(
+                ProjectType::OCaml,
+                path.to_path_buf(),
+                build_arts,
+                name,
+            ));
         }
+
         None
     }
 
-    /// Calculate the size of a directory recursively.
+    /// Extract the project name from a `dune-project` file.
     ///
-    /// This is a helper method used for Python and .NET projects to determine which
-    /// cache directory is the largest and should be the primary cleanup target.
-    fn calculate_directory_size(dir_path: &Path) -> std::io::Result<u64> {
-        let mut total_size = 0;
+    /// Looks for a top-level `(name ...)` stanza, falling back to the
+    /// directory name when the stanza is absent or the file cannot be read.
+    fn extract_ocaml_project_name(
+        &self,
+        dune_project: &Path,
+        errors: &Arc<Mutex<Vec<String>>>,
+    ) -> Option<String> {
+        self.read_file_content(dune_project, errors)
+            .and_then(|content| Self::extract_name_from_dune_project(&content))
+            .or_else(|| Self::fallback_to_directory_name(dune_project.parent()?))
+    }
+
+    /// Extract the value of the top-level `(name foo)` stanza of a
+    /// `dune-project` file. Stanzas nested in another one, such as the
+    /// `(name ...)` of a `(package ...)`, are not considered.
+    fn extract_name_from_dune_project(content: &str) -> Option<String> {
+        let tokens = Self::dune_tokens(content);
+        let mut depth = 0usize;
+        for (i, &token) in tokens.iter().enumerate() {
+            match token {
+                "(" => {
+                    if depth == 0
+                        && let ["name", name, ")", ..] = &tokens[i + 1..]
+                        && !matches!(*name, "(" | ")")
+                    {
+                        return Some(name.trim_matches('"').to_string());
+                    }
+                    depth += 1;
+                }
+                ")" => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        None
+    }
 
-        for entry in fs::read_dir(dir_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                total_size += Self::calculate_directory_size(&path).unwrap_or(0);
-            } else {
-                total_size += entry.metadata()?.len();
+    /// Split the S-expressions of a dune file into parentheses and atoms,
+    /// leaving out `;` comments.
+    fn dune_tokens(content: &str) -> Vec<&str> {
+        let mut tokens = Vec::new();
+        for line in content.lines() {
+            let mut rest = line.split(';').next().unwrap_or_default();
+            loop {
+                rest = rest.trim_start();
+                let Some(first) = rest.chars().next() else {
+                    break;
+                };
+                let len = if matches!(first, '(' | ')') {
+                    1
+                } else {
+                    rest.find(|c: char| c.is_whitespace() || matches!(c, '(' | ')'))
+                        .unwrap_or(rest.len())
+                };
+                tokens.push(&rest[..len]);
+                rest = &rest[len..];
             }
         }
+        tokens
+    }
 
-        Ok(total_size)
+    /// Find the first file with a given extension in a directory.
+    fn find_file_with_extension(&self, dir: &Path, extension: &str) -> Option<std::path::PathBuf> {
+        self.fs.read_dir(dir).ok()?.into_iter().find(|path| {
+            self.fs.is_file(path) && path.extension().and_then(|e| e.to_str()) == Some(extension)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::project::ArtifactCategory;
+    use std::fs;
     use std::path::PathBuf;
     use tempfile::TempDir;
 
@@ -1447,50 +2718,432 @@ mod tests {
     }
 
     #[test]
-    fn test_extract_name_from_python_content() {
-        let content = "from setuptools import setup\nsetup(\n    name=\"my-pkg\",\n)\n";
-        assert_eq!(
-            Scanner::extract_name_from_python_content(content),
-            Some("my-pkg".to_string())
-        );
+    fn test_extract_name_from_python_content() {
+        let content = "from setuptools import setup\nsetup(\n    name=\"my-pkg\",\n)\n";
+        assert_eq!(
+            Scanner::extract_name_from_python_content(content),
+            Some("my-pkg".to_string())
+        );
+
+        let no_name = "from setuptools import setup\nsetup(version=\"1.0\")\n";
+        assert_eq!(Scanner::extract_name_from_python_content(no_name), None);
+    }
+
+    #[test]
+    fn test_fallback_to_directory_name() {
+        assert_eq!(
+            Scanner::fallback_to_directory_name(Path::new("/some/project-name")),
+            Some("project-name".to_string())
+        );
+        assert_eq!(
+            Scanner::fallback_to_directory_name(Path::new("/some/my_app")),
+            Some("my_app".to_string())
+        );
+    }
+
+    #[test]
+    fn test_is_path_in_skip_list() {
+        let scanner = Scanner::new(
+            ScanOptions {
+                verbose: false,
+                threads: 1,
+                skip: vec![PathBuf::from("skip-me"), PathBuf::from("also-skip")],
+            },
+            ProjectFilter::All,
+        );
+
+        assert!(scanner.is_path_in_skip_list(Path::new("/root/skip-me/project")));
+        assert!(scanner.is_path_in_skip_list(Path::new("/root/also-skip")));
+        assert!(!scanner.is_path_in_skip_list(Path::new("/root/keep-me")));
+        assert!(!scanner.is_path_in_skip_list(Path::new("/root/src")));
+    }
+
+    #[test]
+    fn test_is_path_in_empty_skip_list() {
+        let scanner = default_scanner(ProjectFilter::All);
+        assert!(!scanner.is_path_in_skip_list(Path::new("/any/path")));
+    }
+
+    #[test]
+    fn test_scan_directory_records_skip_stats() {
+        let tmp = TempDir::new().unwrap();
+        // The temporary directory itself is hidden
+        let root = &tmp.path().join("root");
+        create_file(&root.join("app/target/dummy"), "content");
+        create_file(&root.join("web/node_modules/dep/index.js"), "content");
+        create_file(&root.join(".hidden/notes"), "content");
+        create_file(&root.join("skip-me/notes"), "content");
+
+        let scanner = Scanner::new(
+            ScanOptions {
+                verbose: false,
+                threads: 1,
+                skip: vec![PathBuf::from("skip-me")],
+            },
+            ProjectFilter::All,
+        );
+        scanner.scan_directory(root);
+
+        // Files inside skipped directories are not counted, and
+        // `node_modules/` is not walked
+        assert_eq!(
+            scanner.skip_stats(),
+            SkipStats {
+                hidden: 1,
+                blocklist: 1,
+                skip_list: 1,
+                path_filter: 0,
+                node_modules: 1,
+                ignored: 0,
+                network_fs: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_artifacts_of_detected_projects_are_not_walked() {
+        let tmp = TempDir::new().unwrap();
+        let root = &tmp.path().join("root");
+        create_file(&root.join("app/Cargo.toml"), "[package]\nname = \"app\"");
+        // `cargo package` leaves a copy of the project in `target/`
+        let packaged = root.join("app/target/package/app-0.1.0");
+        create_file(&packaged.join("Cargo.toml"), "[package]\nname = \"app\"");
+        create_file(&packaged.join("target/dummy"), "content");
+        // A `build/` directory that is no artifact is still searched
+        create_file(
+            &root.join("build/tool/Cargo.toml"),
+            "[package]\nname = \"tool\"",
+        );
+        create_file(&root.join("build/tool/target/dummy"), "content");
+
+        let scanner = default_scanner(ProjectFilter::All);
+        let mut roots: Vec<_> = scanner
+            .scan_directory(root)
+            .into_iter()
+            .map(|project| project.root_path)
+            .collect();
+        roots.sort();
+        assert_eq!(roots, [root.join("app"), root.join("build/tool")]);
+        // `build`, `app/target` and `build/tool/target`, but nothing inside
+        // `app/target`
+        assert_eq!(scanner.skip_stats().blocklist, 3);
+    }
+
+    #[test]
+    fn test_ignored_directories_are_not_searched() {
+        let tmp = TempDir::new().unwrap();
+        let root = &tmp.path().join("root");
+        create_file(&root.join(".gitignore"), "third_party/\n");
+        for project in ["app", "third_party/lib"] {
+            create_file(
+                &root.join(project).join("Cargo.toml"),
+                "[package]\nname = \"x\"",
+            );
+            create_file(&root.join(project).join("target/dummy"), "content");
+        }
+
+        let scanner = default_scanner(ProjectFilter::All);
+        let projects = scanner.scan_directory(root);
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].root_path, root.join("app"));
+        assert_eq!(scanner.skip_stats().ignored, 2);
+
+        let scanner = default_scanner(ProjectFilter::All).with_ignore_files(false);
+        assert_eq!(scanner.scan_directory(root).len(), 2);
+    }
+
+    #[test]
+    fn test_without_sizing_artifacts_are_not_measured() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        create_file(&root.join("app/Cargo.toml"), "[package]\nname = \"app\"");
+        create_file(&root.join("app/target/dummy"), "content");
+        create_file(&root.join("web/package.json"), "{}");
+        fs::create_dir_all(root.join("web/node_modules")).unwrap();
+
+        let scanner = default_scanner(ProjectFilter::All).with_sizing(false);
+        let mut projects = scanner.scan_directory(root);
+        projects.sort_by_key(|project| project.root_path.clone());
+
+        // Empty artifacts are kept, as they cannot be told apart
+        assert_eq!(projects.len(), 2);
+        assert!(projects.iter().all(|project| project.total_size() == 0));
+    }
+
+    #[test]
+    fn test_skip_stats_display() {
+        assert_eq!(SkipStats::default().to_string(), "Skipped 0 directories");
+
+        let stats = SkipStats {
+            hidden: 2,
+            node_modules: 10,
+            ..SkipStats::default()
+        };
+        assert_eq!(
+            stats.to_string(),
+            "Skipped 12 directories: 10 inside node_modules, 2 hidden"
+        );
+    }
+
+    #[test]
+    fn test_find_projects_defers_sizing() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        create_file(
+            &base.join("app/Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"",
+        );
+        create_file(&base.join("app/target/dummy"), "content");
+        create_file(
+            &base.join("empty/Cargo.toml"),
+            "[package]\nname = \"empty\"\nversion = \"0.1.0\"",
+        );
+        fs::create_dir_all(base.join("empty/target")).unwrap();
+
+        let scanner = default_scanner(ProjectFilter::Rust);
+        let found = scanner.find_projects(&[base.to_path_buf()]);
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|project| project.total_size() == 0));
+
+        let sized = scanner.size_projects(found);
+        assert_eq!(sized.len(), 1);
+        assert_eq!(sized[0].name.as_deref(), Some("app"));
+        assert_eq!(sized[0].total_size(), 7);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_size_projects_reuses_indexed_sizes() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+        create_file(
+            &base.join("app/Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"",
+        );
+        create_file(&base.join("app/target/debug/deps/libapp.rlib"), "content");
+        // Built an hour ago
+        fs::File::open(base.join("app/target/debug"))
+            .unwrap()
+            .set_modified(SystemTime::now() - std::time::Duration::from_secs(3600))
+            .unwrap();
+
+        let scanner = default_scanner(ProjectFilter::Rust).with_index(ScanIndex::default());
+        let sized = scanner.size_projects(scanner.find_projects(&[base.to_path_buf()]));
+        assert_eq!(sized[0].total_size(), 7);
+        let index = scanner.index().unwrap();
+        assert_eq!(index.len(), 1);
+
+        // Rewriting a file leaves the directories' modification times alone
+        create_file(
+            &base.join("app/target/debug/deps/libapp.rlib"),
+            "content, rebuilt",
+        );
+        let scanner = default_scanner(ProjectFilter::Rust).with_index(index);
+        let sized = scanner.size_projects(scanner.find_projects(&[base.to_path_buf()]));
+        assert_eq!(sized[0].total_size(), 7);
+
+        // A new file in a directory close to the top is noticed
+        create_file(&base.join("app/target/debug/app"), "binary");
+        let scanner = default_scanner(ProjectFilter::Rust).with_index(scanner.index().unwrap());
+        let sized = scanner.size_projects(scanner.find_projects(&[base.to_path_buf()]));
+        assert_eq!(sized[0].total_size(), 22);
+    }
+
+    #[test]
+    fn test_observer_is_told_about_each_step() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+        for name in ["one", "two", "empty"] {
+            create_file(
+                &base.join(name).join("Cargo.toml"),
+                &format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\""),
+            );
+            fs::create_dir_all(base.join(name).join("target")).unwrap();
+        }
+        create_file(&base.join("one/target/dummy"), "content");
+        create_file(&base.join("two/target/dummy"), "content");
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&events);
+        let scanner = default_scanner(ProjectFilter::Rust).with_observer(move |event| {
+            let event = match event {
+                ScanEvent::WalkStarted => "started".to_string(),
+                ScanEvent::WalkFinished {
+                    cancelled,
+                    truncation,
+                } => format!("finished {cancelled} {}", truncation.is_some()),
+                ScanEvent::ProjectFound(project) => {
+                    format!("found {}", project.name.as_deref().unwrap())
+                }
+                ScanEvent::SizeComputed(project) => {
+                    format!("sized {}", project.name.as_deref().unwrap())
+                }
+            };
+            seen.lock().unwrap().push(event);
+        });
+        assert_eq!(scanner.scan_directory(base).len(), 2);
+
+        let events = events.lock().unwrap().clone();
+        assert_eq!(events[..2], ["started", "finished false false"]);
+        let mut sized: Vec<_> = events.iter().filter(|e| e.starts_with("sized")).collect();
+        sized.sort();
+        assert_eq!(sized, ["sized one", "sized two"]);
+        for project in ["one", "two"] {
+            let found = events.iter().position(|e| *e == format!("found {project}"));
+            let sized = events.iter().position(|e| *e == format!("sized {project}"));
+            assert!(found.unwrap() < sized.unwrap());
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_scan_directory_async() {
+        use tokio_stream::StreamExt;
+
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+        for name in ["one", "two", "empty"] {
+            create_file(
+                &base.join(name).join("Cargo.toml"),
+                &format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\""),
+            );
+            fs::create_dir_all(base.join(name).join("target")).unwrap();
+        }
+        create_file(&base.join("one/target/dummy"), "content");
+        create_file(&base.join("two/target/dummy"), "content");
+
+        let scanner = Arc::new(default_scanner(ProjectFilter::Rust));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let mut names: Vec<_> = runtime.block_on(async {
+            Arc::clone(&scanner)
+                .scan_directory_async(base.to_path_buf())
+                .map(|project| project.name.unwrap())
+                .collect::<Vec<_>>()
+                .await
+        });
+        names.sort();
+
+        assert_eq!(names, ["one", "two"]);
+        assert_eq!(scanner.truncation(), None);
+    }
+
+    #[test]
+    fn test_builder_max_depth() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+        for dir in ["shallow", "a/b/deep"] {
+            create_file(
+                &base.join(dir).join("Cargo.toml"),
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"",
+            );
+            create_file(&base.join(dir).join("target/dummy"), "content");
+        }
 
-        let no_name = "from setuptools import setup\nsetup(version=\"1.0\")\n";
-        assert_eq!(Scanner::extract_name_from_python_content(no_name), None);
+        let scan = |max_depth| {
+            let mut found: Vec<_> = Scanner::builder()
+                .root(base)
+                .filter(ProjectFilter::Rust)
+                .max_depth(max_depth)
+                .threads(2)
+                .build()
+                .scan()
+                .into_iter()
+                .map(|project| project.root_path)
+                .collect();
+            found.sort();
+            found
+        };
+
+        assert_eq!(scan(0), Vec::<PathBuf>::new());
+        assert_eq!(scan(2), [base.join("shallow")]);
+        assert_eq!(scan(3), [base.join("a/b/deep"), base.join("shallow")]);
     }
 
     #[test]
-    fn test_fallback_to_directory_name() {
-        assert_eq!(
-            Scanner::fallback_to_directory_name(Path::new("/some/project-name")),
-            Some("project-name".to_string())
-        );
-        assert_eq!(
-            Scanner::fallback_to_directory_name(Path::new("/some/my_app")),
-            Some("my_app".to_string())
-        );
+    fn test_min_depth() {
+        let tmp = TempDir::new().unwrap();
+        let base = &tmp.path().join("projects");
+        for dir in ["", "shallow", "a/b/deep"] {
+            create_file(
+                &base.join(dir).join("Cargo.toml"),
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"",
+            );
+            create_file(&base.join(dir).join("target/dummy"), "content");
+        }
+
+        let scan = |min_depth| {
+            let mut found: Vec<_> = default_scanner(ProjectFilter::Rust)
+                .with_min_depth(min_depth)
+                .scan_directory(base)
+                .into_iter()
+                .map(|project| project.root_path)
+                .collect();
+            found.sort();
+            found
+        };
+
+        assert_eq!(scan(0).len(), 3);
+        assert_eq!(scan(1), [base.join("a/b/deep"), base.join("shallow")]);
+        assert_eq!(scan(2), [base.join("a/b/deep")]);
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_is_path_in_skip_list() {
-        let scanner = Scanner::new(
-            ScanOptions {
-                verbose: false,
-                threads: 1,
-                skip: vec![PathBuf::from("skip-me"), PathBuf::from("also-skip")],
-            },
-            ProjectFilter::All,
+    fn test_builder_follow_symlinks() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+        create_file(
+            &base.join("elsewhere/app/Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"",
         );
+        create_file(&base.join("elsewhere/app/target/dummy"), "content");
+        fs::create_dir(base.join("work")).unwrap();
+        std::os::unix::fs::symlink(base.join("elsewhere"), base.join("work/linked")).unwrap();
+
+        let scan = |follow_symlinks| {
+            Scanner::builder()
+                .root(base.join("work"))
+                .follow_symlinks(follow_symlinks)
+                .build()
+                .scan()
+        };
 
-        assert!(scanner.is_path_in_skip_list(Path::new("/root/skip-me/project")));
-        assert!(scanner.is_path_in_skip_list(Path::new("/root/also-skip")));
-        assert!(!scanner.is_path_in_skip_list(Path::new("/root/keep-me")));
-        assert!(!scanner.is_path_in_skip_list(Path::new("/root/src")));
+        assert!(scan(false).is_empty());
+        let found = scan(true);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].root_path, base.join("work/linked/app"));
     }
 
     #[test]
-    fn test_is_path_in_empty_skip_list() {
+    fn test_find_listed_projects_does_not_search() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        create_file(
+            &base.join("app/Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"",
+        );
+        create_file(&base.join("app/target/dummy"), "content");
+        create_file(
+            &base.join("app/nested/Cargo.toml"),
+            "[package]\nname = \"nested\"\nversion = \"0.1.0\"",
+        );
+        create_file(&base.join("app/nested/target/dummy"), "content");
+
         let scanner = default_scanner(ProjectFilter::All);
-        assert!(!scanner.is_path_in_skip_list(Path::new("/any/path")));
+        let found = scanner.find_listed_projects(&[
+            base.join("app"),
+            base.join("app/."),
+            base.to_path_buf(),
+            base.join("missing"),
+        ]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name.as_deref(), Some("app"));
+        assert_eq!(found[0].total_size(), 0);
+        assert_eq!(scanner.size_projects(found)[0].total_size(), 7);
     }
 
     // ── Scanning with special path characters ───────────────────────────
@@ -1875,6 +3528,302 @@ mod tests {
         assert_eq!(projects[0].name.as_deref(), Some("Lib"));
     }
 
+    // ── Unreal project detection tests ───────────────────────────────────
+
+    #[test]
+    fn test_detect_unreal_project_with_per_directory_sizes() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        let project = base.join("ShooterGame");
+        create_file(
+            &project.join("ShooterGame.uproject"),
+            "{\n  \"FileVersion\": 3\n}",
+        );
+        create_file(&project.join("Source/ShooterGame/Game.cpp"), "// code");
+        create_file(&project.join("Intermediate/Build/obj.o"), "0123456789");
+        create_file(&project.join("DerivedDataCache/cache.ddp"), "01234");
+        create_file(&project.join("Saved/Logs/ShooterGame.log"), "012");
+        create_file(&project.join("Binaries/Win64/ShooterGame.exe"), "0");
+
+        let scanner = default_scanner(ProjectFilter::All);
+        let projects = scanner.scan_directory(base);
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].kind, ProjectType::Unreal);
+        assert_eq!(projects[0].name.as_deref(), Some("ShooterGame"));
+
+        let sizes: Vec<_> = projects[0]
+            .build_arts
+            .iter()
+            .map(|artifact| (artifact.path.clone(), artifact.size))
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![
+                (project.join("Intermediate"), 10),
+                (project.join("DerivedDataCache"), 5),
+                (project.join("Saved"), 3),
+                (project.join("Binaries"), 1),
+            ]
+        );
+        assert_eq!(projects[0].total_size(), 19);
+    }
+
+    // ── Cache artifact tests ─────────────────────────────────────────────
+
+    #[test]
+    fn test_cache_artifacts_attached_with_their_own_size() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        let project = base.join("web");
+        create_file(&project.join("package.json"), r#"{"name": "web"}"#);
+        create_file(&project.join("node_modules/dep/index.js"), "0123456789");
+        create_file(&project.join(".eslintcache"), "01234");
+        create_file(&project.join("tsconfig.tsbuildinfo"), "012");
+        create_file(&project.join("coverage/lcov.info"), "01");
+
+        let scanner = default_scanner(ProjectFilter::All);
+        let projects = scanner.scan_directory(base);
+        assert_eq!(projects.len(), 1);
+
+        let artifacts: Vec<_> = projects[0]
+            .build_arts
+            .iter()
+            .map(|artifact| (artifact.path.clone(), artifact.size, artifact.category()))
+            .collect();
+        assert_eq!(
+            artifacts,
+            vec![
+                (project.join("node_modules"), 10, ArtifactCategory::Build),
+                (project.join(".eslintcache"), 5, ArtifactCategory::Cache),
+                (project.join("coverage"), 2, ArtifactCategory::Cache),
+                (
+                    project.join("tsconfig.tsbuildinfo"),
+                    3,
+                    ArtifactCategory::Cache
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cache_artifacts_not_duplicated() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        // `.pytest_cache` is the Python project's main artifact already
+        let project = base.join("py");
+        create_file(&project.join("requirements.txt"), "requests");
+        create_file(&project.join(".pytest_cache/v/cache/nodeids"), "[]");
+        create_file(&project.join(".ruff_cache/content"), "0");
+
+        let scanner = default_scanner(ProjectFilter::Python);
+        let projects = scanner.scan_directory(base);
+        assert_eq!(projects.len(), 1);
+
+        let paths: Vec<_> = projects[0].artifact_paths().collect();
+        assert_eq!(
+            paths,
+            [project.join(".pytest_cache"), project.join(".ruff_cache")]
+        );
+    }
+
+    #[test]
+    fn test_unreal_project_requires_uproject_file() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        create_file(&base.join("not-unreal/Intermediate/file"), "data");
+        create_file(&base.join("not-unreal/Binaries/tool"), "data");
+
+        let scanner = default_scanner(ProjectFilter::Unreal);
+        assert!(scanner.scan_directory(base).is_empty());
+    }
+
+    // ── OCaml project detection tests ────────────────────────────────────
+
+    #[test]
+    fn test_detect_ocaml_project() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        let project = base.join("ocaml-app");
+        create_file(
+            &project.join("dune-project"),
+            "(lang dune 3.0)\n(name my_ocaml_app)\n",
+        );
+        create_file(&project.join("_build/default/bin/main.exe"), "binary");
+
+        let scanner = default_scanner(ProjectFilter::OCaml);
+        let projects = scanner.scan_directory(base);
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].kind, ProjectType::OCaml);
+        assert_eq!(projects[0].name.as_deref(), Some("my_ocaml_app"));
+        assert_eq!(projects[0].build_arts[0].path, project.join("_build"));
+    }
+
+    #[test]
+    fn test_detect_ocaml_project_without_name_stanza() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        let project = base.join("unnamed-dune");
+        create_file(&project.join("dune-project"), "(lang dune 3.0)\n");
+        create_file(&project.join("_build/log"), "build log");
+
+        let scanner = default_scanner(ProjectFilter::OCaml);
+        let projects = scanner.scan_directory(base);
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name.as_deref(), Some("unnamed-dune"));
+    }
+
+    #[test]
+    fn test_ocaml_project_requires_build_dir() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        create_file(&base.join("fresh/dune-project"), "(lang dune 3.0)\n");
+
+        let scanner = default_scanner(ProjectFilter::OCaml);
+        assert!(scanner.scan_directory(base).is_empty());
+    }
+
+    #[test]
+    fn test_extract_name_from_dune_project() {
+        let name = |content| Scanner::extract_name_from_dune_project(content);
+
+        assert_eq!(
+            name("(lang dune 3.0)\n(name foo)\n"),
+            Some("foo".to_string())
+        );
+        assert_eq!(
+            name("(lang dune 3.0) (name  bar )"),
+            Some("bar".to_string())
+        );
+        assert_eq!(name("(lang dune 3.0)"), None);
+        assert_eq!(name("(name)"), None);
+        assert_eq!(name("(names a b)\n(namespace x)"), None);
+        assert_eq!(
+            name("(lang dune 3.0)\n(package\n  (name lib_pkg))\n; (name commented)\n"),
+            None
+        );
+        assert_eq!(
+            name("(package\n (name lib_pkg))\n(name\n  app)"),
+            Some("app".to_string())
+        );
+    }
+
+    #[test]
+    fn test_detect_ocaml_project_with_unreadable_dune_project() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        // A directory cannot be read as a file
+        let project = base.join("broken-dune");
+        fs::create_dir_all(project.join("dune-project")).unwrap();
+        create_file(&project.join("_build/log"), "build log");
+
+        let scanner = default_scanner(ProjectFilter::OCaml);
+        let projects = scanner.scan_directory(base);
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name.as_deref(), Some("broken-dune"));
+    }
+
+    // ── Unity project detection tests ────────────────────────────────────
+
+    #[test]
+    fn test_detect_unity_project_collects_all_artifact_dirs() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        let project = base.join("unity-game");
+        create_file(
+            &project.join("ProjectSettings/ProjectVersion.txt"),
+            "m_EditorVersion: 2022.3.10f1\n",
+        );
+        create_file(
+            &project.join("ProjectSettings/ProjectSettings.asset"),
+            "PlayerSettings:\n  companyName: Acme\n  productName: Space Game\n",
+        );
+        create_file(&project.join("Assembly-CSharp.csproj"), "<Project />");
+        create_file(&project.join("Library/ArtifactDB"), "artifacts");
+        create_file(&project.join("Temp/UnityLockfile"), "lock");
+        create_file(&project.join("obj/Debug/Assembly-CSharp.dll"), "dll");
+        create_file(&project.join("Logs/AssetImportWorker0.log"), "log");
+
+        let scanner = default_scanner(ProjectFilter::All);
+        let projects = scanner.scan_directory(base);
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].kind, ProjectType::Unity);
+        assert_eq!(projects[0].name.as_deref(), Some("Space Game"));
+
+        let mut paths: Vec<_> = projects[0].artifact_paths().collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                project.join("Library"),
+                project.join("Logs"),
+                project.join("Temp"),
+                project.join("obj"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_unity_project_falls_back_to_directory_name() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        let project = base.join("my-unity-proj");
+        create_file(
+            &project.join("ProjectSettings/ProjectVersion.txt"),
+            "m_EditorVersion: 2022.3.10f1\n",
+        );
+        create_file(&project.join("Library/ArtifactDB"), "artifacts");
+
+        let scanner = default_scanner(ProjectFilter::Unity);
+        let projects = scanner.scan_directory(base);
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name.as_deref(), Some("my-unity-proj"));
+        assert_eq!(projects[0].build_arts.len(), 1);
+    }
+
+    #[test]
+    fn test_unity_project_drops_empty_artifact_dirs() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        let project = base.join("unity-empty-logs");
+        create_file(
+            &project.join("ProjectSettings/ProjectVersion.txt"),
+            "m_EditorVersion: 2022.3.10f1\n",
+        );
+        create_file(&project.join("Library/ArtifactDB"), "artifacts");
+        fs::create_dir_all(project.join("Logs")).unwrap();
+
+        let scanner = default_scanner(ProjectFilter::Unity);
+        let projects = scanner.scan_directory(base);
+        assert_eq!(projects.len(), 1);
+        assert_eq!(
+            projects[0].artifact_paths().collect::<Vec<_>>(),
+            vec![project.join("Library")]
+        );
+    }
+
+    #[test]
+    fn test_unity_project_requires_project_version() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+
+        create_file(&base.join("not-unity/Library/file"), "data");
+        create_file(&base.join("not-unity/ProjectSettings/Other.asset"), "x");
+
+        let scanner = default_scanner(ProjectFilter::Unity);
+        assert!(scanner.scan_directory(base).is_empty());
+    }
+
     // ── Excluded directory tests ─────────────────────────────────────────
 
     #[test]
@@ -1882,6 +3831,11 @@ mod tests {
         assert!(Scanner::is_excluded_directory(Path::new("/some/obj")));
     }
 
+    #[test]
+    fn test_dune_build_directory_is_excluded() {
+        assert!(Scanner::is_excluded_directory(Path::new("/some/_build")));
+    }
+
     // ── Cross-platform calculate_build_dir_size ─────────────────────────
 
     #[test]
@@ -1891,7 +3845,7 @@ mod tests {
         fs::create_dir_all(&empty_dir).unwrap();
 
         let scanner = default_scanner(ProjectFilter::All);
-        assert_eq!(scanner.calculate_build_dir_size(&empty_dir), 0);
+        assert_eq!(scanner.calculate_build_dir_size(&empty_dir), Some(0));
     }
 
     #[test]
@@ -1899,7 +3853,7 @@ mod tests {
         let scanner = default_scanner(ProjectFilter::All);
         assert_eq!(
             scanner.calculate_build_dir_size(Path::new("/nonexistent/path")),
-            0
+            Some(0)
         );
     }
 
@@ -1914,25 +3868,106 @@ mod tests {
 
         let scanner = default_scanner(ProjectFilter::All);
         let size = scanner.calculate_build_dir_size(&dir);
-        assert_eq!(size, 12);
+        assert_eq!(size, Some(12));
+    }
+
+    #[test]
+    fn test_calculate_build_dir_size_of_many_entries() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("node_modules");
+        for index in 0..20 {
+            create_file(&dir.join(format!("pkg{index}/index.js")), "12345");
+        }
+        create_file(&dir.join(".package-lock.json"), "{}");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("pkg0"), dir.join("linked")).unwrap();
+
+        let scanner = default_scanner(ProjectFilter::All);
+        assert_eq!(scanner.calculate_build_dir_size(&dir), Some(102));
+        let file = dir.join(".package-lock.json");
+        assert_eq!(scanner.calculate_build_dir_size(&file), Some(2));
+    }
+
+    #[test]
+    fn test_calculate_build_dir_size_cancelled() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("nested");
+        create_file(&dir.join("file1.txt"), "hello");
+
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        let scanner = default_scanner(ProjectFilter::All).with_cancellation(cancellation);
+        assert_eq!(scanner.calculate_build_dir_size(&dir), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_calculate_build_dir_size_of_a_link() {
+        let tmp = TempDir::new().unwrap();
+        create_file(&tmp.path().join("elsewhere/index.js"), "12345");
+        let dir = tmp.path().join("node_modules");
+        std::os::unix::fs::symlink(tmp.path().join("elsewhere"), &dir).unwrap();
+
+        let scanner = default_scanner(ProjectFilter::All);
+        assert_eq!(scanner.calculate_build_dir_size(&dir), Some(0));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_calculate_build_dir_size_deeper_than_path_max() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("deep");
+        create_file(&dir.join("file1.txt"), "hello");
+        crate::utils::paths::create_deep_tree(&dir, 1000);
+
+        let scanner = default_scanner(ProjectFilter::All);
+        assert_eq!(scanner.calculate_build_dir_size(&dir), Some(1005));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_calculate_build_dir_size_on_disk_counts_hard_links_once() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("node_modules");
+        create_file(&dir.join("a/index.js"), &"x".repeat(10_000));
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::hard_link(dir.join("a/index.js"), dir.join("b/index.js")).unwrap();
+
+        let apparent = default_scanner(ProjectFilter::All);
+        assert_eq!(apparent.calculate_build_dir_size(&dir), Some(20_000));
+
+        // The directories and the file, once
+        let blocks = |path: &Path| {
+            use std::os::unix::fs::MetadataExt;
+            fs::metadata(path).unwrap().blocks() * 512
+        };
+        let expected = blocks(&dir)
+            + blocks(&dir.join("a"))
+            + blocks(&dir.join("b"))
+            + blocks(&dir.join("a/index.js"));
+
+        let disk = default_scanner(ProjectFilter::All).with_du_mode(DuMode::Disk);
+        assert_eq!(disk.calculate_build_dir_size(&dir), Some(expected));
     }
 
-    // ── Quiet mode ──────────────────────────────────────────────────────
+    // ── Cancellation ────────────────────────────────────────────────────
 
     #[test]
-    fn test_scanner_quiet_mode() {
+    fn test_scan_directory_cancelled_before_start_returns_nothing() {
         let tmp = TempDir::new().unwrap();
         let base = tmp.path();
 
-        let project = base.join("quiet-project");
         create_file(
-            &project.join("Cargo.toml"),
-            "[package]\nname = \"quiet\"\nversion = \"0.1.0\"",
+            &base.join("proj/Cargo.toml"),
+            "[package]\nname = \"proj\"\nversion = \"0.1.0\"",
         );
-        create_file(&project.join("target/dummy"), "content");
+        create_file(&base.join("proj/target/dummy"), "content");
 
-        let scanner = default_scanner(ProjectFilter::Rust).with_quiet(true);
-        let projects = scanner.scan_directory(base);
-        assert_eq!(projects.len(), 1);
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        let scanner = default_scanner(ProjectFilter::All).with_cancellation(cancellation);
+        assert!(scanner.scan_directory(base).is_empty());
     }
 }
@@ -0,0 +1,464 @@
+//! Periodic cleanups with the platform's job scheduler.
+//!
+//! Backs the `schedule` subcommand. A cleanup is scheduled daily or weekly,
+//! at 10:00, as a systemd user timer on Linux, a launchd agent on macOS, or
+//! a Scheduled Task on Windows. Missed runs (e.g. while the machine was
+//! asleep or off) are caught up on by systemd and launchd, not by the Task
+//! Scheduler.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{Error, Result};
+
+/// Name of the scheduled job (systemd units and Scheduled Task).
+pub const JOB_NAME: &str = "clean-dev-dirs";
+
+/// Label of the launchd agent.
+const LAUNCHD_LABEL: &str = "com.tomplanche.clean-dev-dirs";
+
+/// Hour of the day the cleanup runs at, in local time.
+const HOUR: u8 = 10;
+
+/// How often the scheduled cleanup runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Frequency {
+    /// Every day.
+    Daily,
+
+    /// Every Monday.
+    #[default]
+    Weekly,
+}
+
+impl Frequency {
+    /// Human-readable description, e.g. `"every Monday at 10:00"`.
+    #[must_use]
+    pub fn describe(self) -> String {
+        match self {
+            Self::Daily => format!("every day at {HOUR}:00"),
+            Self::Weekly => format!("every Monday at {HOUR}:00"),
+        }
+    }
+}
+
+/// A platform's job scheduler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheduler {
+    /// systemd user timers (Linux).
+    Systemd,
+
+    /// launchd user agents (macOS).
+    Launchd,
+
+    /// The Windows Task Scheduler.
+    TaskScheduler,
+}
+
+impl Scheduler {
+    /// The scheduler of the current platform, if it has a supported one.
+    #[must_use]
+    pub const fn current() -> Option<Self> {
+        if cfg!(target_os = "macos") {
+            Some(Self::Launchd)
+        } else if cfg!(windows) {
+            Some(Self::TaskScheduler)
+        } else if cfg!(target_os = "linux") {
+            Some(Self::Systemd)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the scheduler, for display.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Systemd => "a systemd user timer",
+            Self::Launchd => "a launchd agent",
+            Self::TaskScheduler => "a Scheduled Task",
+        }
+    }
+
+    /// Files describing the scheduled job; none for the Task Scheduler,
+    /// which keeps its tasks itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the home or configuration directory is unknown.
+    pub fn job_files(self) -> Result<Vec<PathBuf>> {
+        Ok(match self {
+            Self::Systemd => {
+                let units = systemd_unit_dir()?;
+                vec![
+                    units.join(format!("{JOB_NAME}.service")),
+                    units.join(format!("{JOB_NAME}.timer")),
+                ]
+            }
+            Self::Launchd => vec![launchd_plist_path()?],
+            Self::TaskScheduler => Vec::new(),
+        })
+    }
+
+    /// Schedule `command` (the program followed by its arguments) to run
+    /// at `frequency`, replacing a previously scheduled cleanup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the job files cannot be written, or if the
+    /// scheduler rejects the job.
+    pub fn install(self, command: &[String], frequency: Frequency) -> Result<()> {
+        match self {
+            Self::Systemd => {
+                let [service, timer] = &self.job_files()?[..] else {
+                    unreachable!("systemd jobs have a service and a timer");
+                };
+                write_file(service, &systemd_service(command))?;
+                write_file(timer, &systemd_timer(frequency))?;
+                let enabled = run("systemctl", &["--user", "daemon-reload"]).and_then(|_| {
+                    run(
+                        "systemctl",
+                        &["--user", "enable", "--now", &format!("{JOB_NAME}.timer")],
+                    )
+                });
+                if let Err(e) = enabled {
+                    // Leave nothing behind that `status` would report
+                    let _ = fs::remove_file(service);
+                    let _ = fs::remove_file(timer);
+                    return Err(e);
+                }
+            }
+            Self::Launchd => {
+                let plist = launchd_plist_path()?;
+                let log = dirs::home_dir()
+                    .ok_or_else(|| Error::Other("No home directory".to_string()))?
+                    .join("Library/Logs")
+                    .join(format!("{JOB_NAME}.log"));
+                // A previous version of the job has to be unloaded first
+                let _ = run("launchctl", &["unload", &plist.to_string_lossy()]);
+                write_file(&plist, &launchd_plist(command, frequency, &log))?;
+                if let Err(e) = run("launchctl", &["load", "-w", &plist.to_string_lossy()]) {
+                    let _ = fs::remove_file(&plist);
+                    return Err(e);
+                }
+            }
+            Self::TaskScheduler => {
+                let args = schtasks_create_args(command, frequency);
+                run(
+                    "schtasks",
+                    &args.iter().map(String::as_str).collect::<Vec<_>>(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the scheduled cleanup, and return whether there was one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the job files cannot be deleted, or if the
+    /// scheduler fails to remove the job.
+    pub fn remove(self) -> Result<bool> {
+        match self {
+            Self::Systemd | Self::Launchd => {
+                let files: Vec<PathBuf> = self
+                    .job_files()?
+                    .into_iter()
+                    .filter(|file| file.exists())
+                    .collect();
+                if files.is_empty() {
+                    return Ok(false);
+                }
+
+                // The job may already be stopped or unloaded
+                let _ = if self == Self::Systemd {
+                    run(
+                        "systemctl",
+                        &["--user", "disable", "--now", &format!("{JOB_NAME}.timer")],
+                    )
+                } else {
+                    run("launchctl", &["unload", "-w", &files[0].to_string_lossy()])
+                };
+                for file in &files {
+                    fs::remove_file(file).map_err(Error::file("remove", file))?;
+                }
+                if self == Self::Systemd {
+                    let _ = run("systemctl", &["--user", "daemon-reload"]);
+                }
+                Ok(true)
+            }
+            Self::TaskScheduler => {
+                if self.status()?.is_none() {
+                    return Ok(false);
+                }
+                run("schtasks", &["/Delete", "/TN", JOB_NAME, "/F"])?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Describe the scheduled cleanup as the scheduler reports it (e.g.
+    /// its next run), or return `None` if none is scheduled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the home or configuration directory is unknown.
+    pub fn status(self) -> Result<Option<String>> {
+        let report = |result: Result<String>| result.unwrap_or_else(|e| e.display_chain());
+        Ok(match self {
+            Self::Systemd => self.job_files()?.iter().all(|file| file.exists()).then(|| {
+                report(run(
+                    "systemctl",
+                    &[
+                        "--user",
+                        "list-timers",
+                        "--all",
+                        &format!("{JOB_NAME}.timer"),
+                    ],
+                ))
+            }),
+            Self::Launchd => launchd_plist_path()?
+                .exists()
+                .then(|| report(run("launchctl", &["list", LAUNCHD_LABEL]))),
+            Self::TaskScheduler => run(
+                "schtasks",
+                &["/Query", "/TN", JOB_NAME, "/V", "/FO", "LIST"],
+            )
+            .ok(),
+        })
+    }
+}
+
+/// Directory of the systemd user units.
+fn systemd_unit_dir() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .ok_or_else(|| Error::Other("No configuration directory on this platform".to_string()))?
+        .join("systemd/user"))
+}
+
+/// Path of the launchd agent's property list.
+fn launchd_plist_path() -> Result<PathBuf> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| Error::Other("No home directory".to_string()))?
+        .join("Library/LaunchAgents")
+        .join(format!("{LAUNCHD_LABEL}.plist")))
+}
+
+/// Write `contents` to `path`, creating its parent directories.
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(Error::file("create", parent))?;
+    }
+    fs::write(path, contents).map_err(Error::file("write", path))
+}
+
+/// Run `program` with `args` and return its standard output.
+fn run(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|source| Error::Spawn {
+            command: program.to_string(),
+            source,
+        })?;
+
+    if !output.status.success() {
+        return Err(Error::Command {
+            command: format!("{program} {}", args.join(" ")),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// The systemd service running `command` once.
+fn systemd_service(command: &[String]) -> String {
+    let exec_start: Vec<String> = command
+        .iter()
+        .map(|arg| {
+            let escaped = arg
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('%', "%%")
+                .replace('$', "$$");
+            format!("\"{escaped}\"")
+        })
+        .collect();
+
+    format!(
+        "[Unit]\n\
+         Description=Clean development build directories\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         ExecStart={}\n",
+        exec_start.join(" ")
+    )
+}
+
+/// The systemd timer starting the service at `frequency`.
+fn systemd_timer(frequency: Frequency) -> String {
+    let (description, calendar) = match frequency {
+        Frequency::Daily => ("Daily", format!("*-*-* {HOUR}:00:00")),
+        Frequency::Weekly => ("Weekly", format!("Mon *-*-* {HOUR}:00:00")),
+    };
+
+    format!(
+        "[Unit]\n\
+         Description={description} cleanup of development build directories\n\
+         \n\
+         [Timer]\n\
+         OnCalendar={calendar}\n\
+         Persistent=true\n\
+         \n\
+         [Install]\n\
+         WantedBy=timers.target\n"
+    )
+}
+
+/// The launchd agent running `command` at `frequency`, logging to `log`.
+fn launchd_plist(command: &[String], frequency: Frequency, log: &Path) -> String {
+    let mut arguments = String::new();
+    for arg in command {
+        let _ = writeln!(arguments, "        <string>{}</string>", xml_escape(arg));
+    }
+    let weekday = match frequency {
+        Frequency::Daily => String::new(),
+        Frequency::Weekly => {
+            "        <key>Weekday</key>\n        <integer>1</integer>\n".to_string()
+        }
+    };
+    let log = xml_escape(&log.to_string_lossy());
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{LAUNCHD_LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+{arguments}    </array>
+    <key>StartCalendarInterval</key>
+    <dict>
+{weekday}        <key>Hour</key>
+        <integer>{HOUR}</integer>
+        <key>Minute</key>
+        <integer>0</integer>
+    </dict>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#
+    )
+}
+
+/// Escape the characters with a special meaning in XML.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Arguments of `schtasks` creating the task running `command` at
+/// `frequency`.
+fn schtasks_create_args(command: &[String], frequency: Frequency) -> Vec<String> {
+    let task: Vec<String> = command
+        .iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("\"{arg}\"")
+            } else {
+                arg.clone()
+            }
+        })
+        .collect();
+
+    let mut args: Vec<String> = ["/Create", "/F", "/TN", JOB_NAME, "/TR"]
+        .map(String::from)
+        .to_vec();
+    args.push(task.join(" "));
+    match frequency {
+        Frequency::Daily => args.extend(["/SC", "DAILY"].map(String::from)),
+        Frequency::Weekly => args.extend(["/SC", "WEEKLY", "/D", "MON"].map(String::from)),
+    }
+    args.extend(["/ST".to_string(), format!("{HOUR}:00")]);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command() -> Vec<String> {
+        ["/usr/bin/clean-dev-dirs", "/home/me/My Projects", "--yes"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    #[test]
+    fn test_systemd_service() {
+        let service = systemd_service(&command());
+        assert!(service.contains(
+            "ExecStart=\"/usr/bin/clean-dev-dirs\" \"/home/me/My Projects\" \"--yes\"\n"
+        ));
+
+        let service = systemd_service(&["50%$\"".to_string()]);
+        assert!(service.contains("ExecStart=\"50%%$$\\\"\"\n"));
+    }
+
+    #[test]
+    fn test_systemd_timer() {
+        assert!(systemd_timer(Frequency::Weekly).contains("OnCalendar=Mon *-*-* 10:00:00\n"));
+        assert!(systemd_timer(Frequency::Daily).contains("OnCalendar=*-*-* 10:00:00\n"));
+        assert!(systemd_timer(Frequency::Daily).contains("Persistent=true\n"));
+    }
+
+    #[test]
+    fn test_launchd_plist() {
+        let log = PathBuf::from("/Users/me/Library/Logs/clean-dev-dirs.log");
+        let plist = launchd_plist(&command(), Frequency::Weekly, &log);
+        assert!(plist.contains("<string>/home/me/My Projects</string>"));
+        assert!(plist.contains("<key>Weekday</key>"));
+        assert!(plist.contains(&format!("<string>{}</string>", log.display())));
+
+        let plist = launchd_plist(&["a&b".to_string()], Frequency::Daily, &log);
+        assert!(plist.contains("<string>a&amp;b</string>"));
+        assert!(!plist.contains("Weekday"));
+    }
+
+    #[test]
+    fn test_schtasks_create_args() {
+        let args = schtasks_create_args(&command(), Frequency::Weekly);
+        assert_eq!(
+            args,
+            [
+                "/Create",
+                "/F",
+                "/TN",
+                "clean-dev-dirs",
+                "/TR",
+                "/usr/bin/clean-dev-dirs \"/home/me/My Projects\" --yes",
+                "/SC",
+                "WEEKLY",
+                "/D",
+                "MON",
+                "/ST",
+                "10:00",
+            ]
+        );
+
+        let args = schtasks_create_args(&command(), Frequency::Daily);
+        assert!(args.ends_with(&["/SC", "DAILY", "/ST", "10:00"].map(String::from)));
+    }
+}
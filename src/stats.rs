@@ -0,0 +1,279 @@
+//! CSV time series of run statistics.
+//!
+//! Backs `--append-csv`: every run appends one row to a CSV file, so that
+//! the growth of build artifacts can be charted in Grafana or a spreadsheet
+//! without a metrics stack. The schema is stable; new columns are only ever
+//! added at the end:
+//!
+//! ```text
+//! timestamp,mode,projects,bytes_reclaimable,bytes_freed,freed_rust,freed_node,...
+//! 2026-01-05T09:30:00Z,cleanup,12,4200000000,4100000000,3000000000,1100000000,...
+//! ```
+//!
+//! `timestamp` is in UTC (RFC 3339), `mode` is `dry_run` or `cleanup` like
+//! in the JSON output, and `freed_<type>` are the bytes freed per project
+//! type, in the column order of [`TYPE_COLUMNS`].
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::cleaner::CleanResult;
+use crate::error::{Error, Result};
+use crate::project::{Project, ProjectType};
+
+/// Project types with a `freed_<type>` column, in column order.
+pub const TYPE_COLUMNS: [(ProjectType, &str); 11] = [
+    (ProjectType::Rust, "rust"),
+    (ProjectType::Node, "node"),
+    (ProjectType::Python, "python"),
+    (ProjectType::Go, "go"),
+    (ProjectType::Java, "java"),
+    (ProjectType::Cpp, "cpp"),
+    (ProjectType::Swift, "swift"),
+    (ProjectType::DotNet, "dotnet"),
+    (ProjectType::OCaml, "ocaml"),
+    (ProjectType::Unity, "unity"),
+    (ProjectType::Unreal, "unreal"),
+];
+
+/// Statistics of one run, written as one CSV row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunStats {
+    /// Time the run finished.
+    pub timestamp: DateTime<Utc>,
+
+    /// `"dry_run"` or `"cleanup"`.
+    pub mode: &'static str,
+
+    /// Number of projects selected for cleaning.
+    pub projects: usize,
+
+    /// Total size of their build artifacts, in bytes.
+    pub bytes_reclaimable: u64,
+
+    /// Bytes freed per project type, in the order of [`TYPE_COLUMNS`].
+    pub freed_by_type: [u64; TYPE_COLUMNS.len()],
+}
+
+impl RunStats {
+    /// Statistics of a dry run over `projects`, which frees nothing.
+    #[must_use]
+    pub fn dry_run(projects: &[Project]) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            mode: "dry_run",
+            projects: projects.len(),
+            bytes_reclaimable: projects.iter().map(Project::total_size).sum(),
+            freed_by_type: [0; TYPE_COLUMNS.len()],
+        }
+    }
+
+    /// Statistics of the cleanup of `projects`, with outcome `result`.
+    #[must_use]
+    pub fn cleanup(projects: &[Project], result: &CleanResult) -> Self {
+        Self {
+            mode: "cleanup",
+            freed_by_type: TYPE_COLUMNS.map(|(project_type, _)| {
+                result
+                    .freed_by_type
+                    .get(&project_type)
+                    .copied()
+                    .unwrap_or(0)
+            }),
+            ..Self::dry_run(projects)
+        }
+    }
+
+    /// Total bytes freed.
+    #[must_use]
+    pub fn bytes_freed(&self) -> u64 {
+        self.freed_by_type.iter().sum()
+    }
+
+    /// The CSV header line, without line terminator.
+    #[must_use]
+    pub fn header() -> String {
+        let mut columns = vec![
+            "timestamp".to_string(),
+            "mode".to_string(),
+            "projects".to_string(),
+            "bytes_reclaimable".to_string(),
+            "bytes_freed".to_string(),
+        ];
+        columns.extend(TYPE_COLUMNS.iter().map(|(_, name)| format!("freed_{name}")));
+        columns.join(",")
+    }
+
+    /// The CSV row of these statistics, without line terminator.
+    #[must_use]
+    pub fn row(&self) -> String {
+        let mut fields = vec![
+            self.timestamp.to_rfc3339_opts(SecondsFormat::Secs, true),
+            self.mode.to_string(),
+            self.projects.to_string(),
+            self.bytes_reclaimable.to_string(),
+            self.bytes_freed().to_string(),
+        ];
+        fields.extend(self.freed_by_type.iter().map(ToString::to_string));
+        fields.join(",")
+    }
+}
+
+/// Append `stats` as a row to the CSV file at `path`.
+///
+/// The file is created with a header line if it does not exist or is empty.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or written, or if it starts
+/// with a different header (e.g. it is not a `--append-csv` file).
+pub fn append(path: &Path, stats: &RunStats) -> Result<()> {
+    let header = RunStats::header();
+    let existing = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(Error::file("read", path)(e)),
+    };
+
+    let mut lines = Vec::new();
+    match existing.lines().next() {
+        None => lines.push(header),
+        // Columns are only ever appended, so older files stay valid
+        Some(first) if header.starts_with(first) => {}
+        Some(_) => {
+            return Err(Error::Other(format!(
+                "{} is not a statistics file: its header does not match",
+                path.display()
+            )));
+        }
+    }
+    lines.push(stats.row());
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(Error::file("open", path))?;
+    let separator = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    writeln!(file, "{separator}{}", lines.join("\n")).map_err(Error::file("write to", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    use crate::project::BuildArtifacts;
+
+    fn project(kind: ProjectType, size: u64) -> Project {
+        Project::new(
+            kind,
+            PathBuf::from("/app"),
+            vec![BuildArtifacts {
+                path: PathBuf::from("/app/build"),
+                size,
+            }],
+            None,
+        )
+    }
+
+    fn fixed_time(mut stats: RunStats) -> RunStats {
+        stats.timestamp = DateTime::parse_from_rfc3339("2026-01-05T09:30:00Z")
+            .unwrap()
+            .to_utc();
+        stats
+    }
+
+    #[test]
+    fn test_header_is_stable() {
+        assert_eq!(
+            RunStats::header(),
+            "timestamp,mode,projects,bytes_reclaimable,bytes_freed,freed_rust,freed_node,\
+             freed_python,freed_go,freed_java,freed_cpp,freed_swift,freed_dotnet,\
+             freed_ocaml,freed_unity,freed_unreal"
+        );
+    }
+
+    #[test]
+    fn test_dry_run_row() {
+        let projects = [
+            project(ProjectType::Rust, 300),
+            project(ProjectType::Node, 200),
+        ];
+        let stats = fixed_time(RunStats::dry_run(&projects));
+
+        assert_eq!(
+            stats.row(),
+            "2026-01-05T09:30:00Z,dry_run,2,500,0,0,0,0,0,0,0,0,0,0,0,0"
+        );
+    }
+
+    #[test]
+    fn test_cleanup_row_has_freed_bytes_by_type() {
+        let projects = [
+            project(ProjectType::Rust, 300),
+            project(ProjectType::Node, 200),
+        ];
+        let result = CleanResult {
+            success_count: 2,
+            total_freed: 450,
+            freed_by_type: HashMap::from([(ProjectType::Rust, 300), (ProjectType::Node, 150)]),
+            estimated_size: 500,
+            errors: Vec::new(),
+            partial: Vec::new(),
+            cancelled_count: 0,
+            skipped: Vec::new(),
+            cleaned: Vec::new(),
+            failed: Vec::new(),
+            failed_caches: Vec::new(),
+            elapsed: Duration::ZERO,
+            retried: Vec::new(),
+            removed: Vec::new(),
+        };
+        let stats = fixed_time(RunStats::cleanup(&projects, &result));
+
+        assert_eq!(stats.bytes_freed(), 450);
+        assert_eq!(
+            stats.row(),
+            "2026-01-05T09:30:00Z,cleanup,2,500,450,300,150,0,0,0,0,0,0,0,0,0"
+        );
+    }
+
+    #[test]
+    fn test_append_writes_header_once() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("stats.csv");
+        let stats = RunStats::dry_run(&[project(ProjectType::Go, 10)]);
+
+        append(&path, &stats).unwrap();
+        append(&path, &stats).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], RunStats::header());
+        assert_eq!(lines[1], stats.row());
+        assert!(contents.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_append_refuses_foreign_csv() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("other.csv");
+        fs::write(&path, "name,email\n").unwrap();
+
+        assert!(append(&path, &RunStats::dry_run(&[])).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "name,email\n");
+    }
+}
@@ -0,0 +1,327 @@
+//! Stale-artifact detection inside Rust `target/` directories.
+//!
+//! Deleting a whole `target/` directory also throws away the incremental
+//! state of the builds that are still in use. Like `cargo sweep`, this module
+//! instead picks out the individual compilation units that are stale: those
+//! not rebuilt for a while, and those built by a Rust toolchain that is no
+//! longer installed (and therefore can never be reused).
+//!
+//! Units are found in the profile directories of a target directory (any
+//! directory holding a `.fingerprint/` folder, such as `target/debug` or
+//! `target/x86_64-unknown-linux-gnu/release`), in their `deps/`, `build/`,
+//! `.fingerprint/`, `incremental/` and `examples/` subdirectories.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use walkdir::WalkDir;
+
+/// Subdirectories of a profile directory that hold per-unit entries.
+const UNIT_DIRS: &[&str] = &["deps", "build", ".fingerprint", "incremental", "examples"];
+
+/// Bytes of a `.rmeta`/`.rlib` file searched for the rustc version string.
+const VERSION_SEARCH_BYTES: u64 = 64 * 1024;
+
+/// Criteria deciding which artifacts inside `target/` are stale.
+#[derive(Clone, Debug, Default)]
+pub struct StaleCriteria {
+    /// Artifacts last modified before this time are stale. `None` disables
+    /// the age check.
+    pub cutoff: Option<SystemTime>,
+
+    /// Version strings (`rustc -V` output) of the installed toolchains.
+    /// Artifacts built by any other toolchain are stale. `None` disables the
+    /// toolchain check.
+    pub installed_toolchains: Option<HashSet<String>>,
+}
+
+impl StaleCriteria {
+    /// Criteria for artifacts older than `keep_days` days (`0` disables the
+    /// age check) or built by a toolchain that is not installed.
+    ///
+    /// The toolchain check is disabled if the installed toolchains cannot be
+    /// determined.
+    #[must_use]
+    pub fn new(keep_days: u32) -> Self {
+        let cutoff = (keep_days > 0)
+            .then(|| SystemTime::now() - Duration::from_secs(u64::from(keep_days) * 24 * 60 * 60));
+
+        Self {
+            cutoff,
+            installed_toolchains: installed_toolchains(),
+        }
+    }
+}
+
+/// A stale file or directory inside a target directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaleEntry {
+    /// Path of the entry.
+    pub path: PathBuf,
+
+    /// Total size of the entry in bytes.
+    pub size: u64,
+}
+
+/// List the stale entries of a `target/` directory.
+///
+/// An entry is stale if its most recent modification time is before
+/// [`StaleCriteria::cutoff`], or if it belongs to a unit whose `.rmeta` or
+/// `.rlib` was built by a toolchain missing from
+/// [`StaleCriteria::installed_toolchains`]. Units are matched by the hash
+/// suffix Cargo appends to their names (`libserde-1a2b3c4d.rlib`,
+/// `.fingerprint/serde-1a2b3c4d/`).
+#[must_use]
+pub fn stale_entries(target: &Path, criteria: &StaleCriteria) -> Vec<StaleEntry> {
+    let mut stale = Vec::new();
+
+    for profile in profile_dirs(target) {
+        let entries: Vec<PathBuf> = UNIT_DIRS
+            .iter()
+            .filter_map(|dir| fs::read_dir(profile.join(dir)).ok())
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .collect();
+
+        let foreign_units: HashSet<&str> =
+            criteria
+                .installed_toolchains
+                .as_ref()
+                .map_or_else(HashSet::new, |installed| {
+                    entries
+                        .iter()
+                        .filter(|path| {
+                            rustc_version(path).is_some_and(|version| !installed.contains(&version))
+                        })
+                        .filter_map(|path| unit_hash(path))
+                        .collect()
+                });
+
+        for path in &entries {
+            let foreign = unit_hash(path).is_some_and(|hash| foreign_units.contains(hash));
+            let old = criteria
+                .cutoff
+                .is_some_and(|cutoff| last_modified(path).is_some_and(|time| time < cutoff));
+
+            if foreign || old {
+                stale.push(StaleEntry {
+                    path: path.clone(),
+                    size: entry_size(path),
+                });
+            }
+        }
+    }
+
+    stale
+}
+
+/// Find the profile directories of a target directory.
+pub(crate) fn profile_dirs(target: &Path) -> Vec<PathBuf> {
+    WalkDir::new(target)
+        .min_depth(1)
+        .max_depth(3)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_dir() && entry.path().join(".fingerprint").is_dir())
+        .map(walkdir::DirEntry::into_path)
+        .collect()
+}
+
+/// Extract the unit hash from an entry name, e.g. `1a2b3c4d` from
+/// `libserde-1a2b3c4d.rlib` or `serde-1a2b3c4d`.
+fn unit_hash(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    let stem = name.split('.').next()?;
+    let (_, hash) = stem.rsplit_once('-')?;
+
+    (!hash.is_empty() && hash.chars().all(|c| c.is_ascii_hexdigit())).then_some(hash)
+}
+
+/// Read the rustc version string embedded in a `.rmeta` or `.rlib` file,
+/// such as `rustc 1.80.0 (051478957 2024-07-21)`.
+fn rustc_version(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_str()?;
+    if extension != "rmeta" && extension != "rlib" {
+        return None;
+    }
+
+    let mut header = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(VERSION_SEARCH_BYTES)
+        .read_to_end(&mut header)
+        .ok()?;
+
+    let start = header
+        .windows(b"rustc ".len())
+        .position(|window| window == b"rustc ")?;
+    let end = start + header[start..].iter().position(|&byte| byte == b')')?;
+
+    String::from_utf8(header[start..=end].to_vec()).ok()
+}
+
+/// Return the most recent modification time of a file, or of any file
+/// below a directory.
+fn last_modified(path: &Path) -> Option<SystemTime> {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+}
+
+/// Sum the sizes of a file, or of all files below a directory.
+pub(crate) fn entry_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Return the version strings of all installed Rust toolchains.
+///
+/// Uses `rustup` when available, and falls back to the `rustc` on `PATH`.
+fn installed_toolchains() -> Option<HashSet<String>> {
+    let toolchains = command_stdout("rustup", &["toolchain", "list"]).map(|list| {
+        list.lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .filter_map(|toolchain| command_stdout("rustup", &["run", toolchain, "rustc", "-V"]))
+            .collect::<HashSet<_>>()
+    });
+
+    toolchains
+        .filter(|versions| !versions.is_empty())
+        .or_else(|| command_stdout("rustc", &["-V"]).map(|version| HashSet::from([version])))
+}
+
+/// Run a command and return its trimmed stdout if it succeeds.
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|stdout| stdout.trim().to_string())
+        .filter(|stdout| !stdout.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Create a `target/debug` layout with one unit built by `version`.
+    fn target_with_unit(root: &Path, hash: &str, version: &str) -> PathBuf {
+        let target = root.join("target");
+        let debug = target.join("debug");
+        for dir in ["deps", ".fingerprint", "incremental"] {
+            fs::create_dir_all(debug.join(dir)).unwrap();
+        }
+
+        let mut rmeta = b"rust\0\0\0\x0a\0\0\0\0\0\0\0\0#".to_vec();
+        rmeta.extend_from_slice(version.as_bytes());
+        rmeta.extend_from_slice(&[0xc1, 0x02, 0xba]);
+        fs::write(debug.join(format!("deps/libdemo-{hash}.rmeta")), rmeta).unwrap();
+        fs::write(debug.join(format!("deps/demo-{hash}.d")), "deps").unwrap();
+        fs::create_dir(debug.join(format!(".fingerprint/demo-{hash}"))).unwrap();
+        fs::write(
+            debug.join(format!(".fingerprint/demo-{hash}/lib-demo")),
+            "fp",
+        )
+        .unwrap();
+
+        target
+    }
+
+    fn criteria(cutoff: Option<SystemTime>, installed: &[&str]) -> StaleCriteria {
+        StaleCriteria {
+            cutoff,
+            installed_toolchains: Some(installed.iter().map(ToString::to_string).collect()),
+        }
+    }
+
+    #[test]
+    fn test_unit_hash() {
+        assert_eq!(
+            unit_hash(Path::new("libserde-1a2b3c4d.rlib")),
+            Some("1a2b3c4d")
+        );
+        assert_eq!(unit_hash(Path::new("serde-1a2b3c4d")), Some("1a2b3c4d"));
+        assert_eq!(unit_hash(Path::new("build-script-build")), None);
+        assert_eq!(unit_hash(Path::new("demo")), None);
+    }
+
+    #[test]
+    fn test_recent_artifacts_of_installed_toolchain_are_kept() {
+        let temp_dir = TempDir::new().unwrap();
+        let version = "rustc 1.80.0 (051478957 2024-07-21)";
+        let target = target_with_unit(temp_dir.path(), "abc123", version);
+
+        let cutoff = SystemTime::now() - Duration::from_hours(1);
+        assert!(stale_entries(&target, &criteria(Some(cutoff), &[version])).is_empty());
+    }
+
+    #[test]
+    fn test_artifacts_of_uninstalled_toolchain_are_stale() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = target_with_unit(
+            temp_dir.path(),
+            "abc123",
+            "rustc 1.70.0 (90c541806 2023-05-31)",
+        );
+
+        let stale = stale_entries(
+            &target,
+            &criteria(None, &["rustc 1.80.0 (051478957 2024-07-21)"]),
+        );
+        let mut names: Vec<_> = stale
+            .iter()
+            .map(|entry| {
+                entry
+                    .path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        names.sort();
+
+        assert_eq!(
+            names,
+            ["demo-abc123", "demo-abc123.d", "libdemo-abc123.rmeta"]
+        );
+        assert!(stale.iter().all(|entry| entry.size > 0));
+    }
+
+    #[test]
+    fn test_old_artifacts_are_stale() {
+        let temp_dir = TempDir::new().unwrap();
+        let version = "rustc 1.80.0 (051478957 2024-07-21)";
+        let target = target_with_unit(temp_dir.path(), "abc123", version);
+
+        // Everything was written before a cutoff in the future
+        let cutoff = SystemTime::now() + Duration::from_hours(1);
+        let stale = stale_entries(&target, &criteria(Some(cutoff), &[version]));
+        assert_eq!(stale.len(), 3);
+    }
+
+    #[test]
+    fn test_non_target_directories_are_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("debug/deps")).unwrap();
+        fs::write(temp_dir.path().join("debug/deps/libdemo-abc123.rlib"), "").unwrap();
+
+        let cutoff = SystemTime::now() + Duration::from_hours(1);
+        assert!(stale_entries(temp_dir.path(), &criteria(Some(cutoff), &[])).is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+//! Space used by each crate inside a Rust `target/` directory.
+//!
+//! Backs the `why-big` subcommand. The compilation units in the `deps/`,
+//! `build/`, `incremental/` and `.fingerprint/` subdirectories of every
+//! profile directory (see [`sweep`](crate::sweep)) are named after their
+//! crate, followed by the hash Cargo appends (`liblibrocksdb_sys-1a2b3c4d.rlib`,
+//! `build/librocksdb-sys-1a2b3c4d/`), so their sizes can be added up per
+//! crate. Everything else (final binaries, examples, docs) is counted as
+//! not attributed.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::sweep;
+
+/// Subdirectory of a profile directory holding compilation units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitDir {
+    /// `deps/`: libraries, dependency info and test binaries.
+    Deps,
+
+    /// `build/`: build scripts and their output (e.g. compiled C code).
+    Build,
+
+    /// `incremental/`: incremental compilation state.
+    Incremental,
+
+    /// `.fingerprint/`: Cargo's freshness tracking.
+    Fingerprint,
+}
+
+impl UnitDir {
+    /// All unit directories, in display order.
+    pub const ALL: [Self; 4] = [
+        Self::Deps,
+        Self::Build,
+        Self::Incremental,
+        Self::Fingerprint,
+    ];
+
+    /// Name of the subdirectory inside a profile directory.
+    #[must_use]
+    pub const fn dir_name(self) -> &'static str {
+        match self {
+            Self::Deps => "deps",
+            Self::Build => "build",
+            Self::Incremental => "incremental",
+            Self::Fingerprint => ".fingerprint",
+        }
+    }
+
+    /// Short label for display, e.g. `"build"`.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Fingerprint => "fingerprint",
+            _ => self.dir_name(),
+        }
+    }
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Space used by the units of one crate, across all profiles.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CrateUsage {
+    /// Name of the crate, with dashes when its package name has them
+    /// (e.g. `librocksdb-sys`).
+    pub name: String,
+
+    /// Bytes used per unit directory, indexed like [`UnitDir::ALL`].
+    pub by_dir: [u64; UnitDir::ALL.len()],
+}
+
+impl CrateUsage {
+    /// Total bytes used by the crate.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.by_dir.iter().sum()
+    }
+
+    /// Bytes used in `dir`.
+    #[must_use]
+    pub const fn in_dir(&self, dir: UnitDir) -> u64 {
+        self.by_dir[dir.index()]
+    }
+}
+
+/// Space used by a target directory, attributed to crates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TargetUsage {
+    /// Usage of each crate, largest first.
+    pub crates: Vec<CrateUsage>,
+
+    /// Total size of the target directory, in bytes.
+    pub total: u64,
+}
+
+impl TargetUsage {
+    /// Bytes not attributed to any crate.
+    #[must_use]
+    pub fn unattributed(&self) -> u64 {
+        let attributed: u64 = self.crates.iter().map(CrateUsage::total).sum();
+        self.total.saturating_sub(attributed)
+    }
+}
+
+/// Attribute the space used by the `target/` directory `target` to crates.
+#[must_use]
+pub fn analyze(target: &Path) -> TargetUsage {
+    // Units of the same crate are spelled with underscores in `deps/` and
+    // `incremental/`, and with dashes in `build/` and `.fingerprint/`
+    let mut crates: HashMap<String, CrateUsage> = HashMap::new();
+
+    for profile in sweep::profile_dirs(target) {
+        for dir in UnitDir::ALL {
+            let Ok(entries) = profile.join(dir.dir_name()).read_dir() else {
+                continue;
+            };
+            for entry in entries.filter_map(Result::ok) {
+                let file_name = entry.file_name();
+                let Some(name) = file_name.to_str().and_then(unit_crate_name) else {
+                    continue;
+                };
+                let usage = crates
+                    .entry(name.replace('-', "_"))
+                    .or_insert_with(|| CrateUsage {
+                        name: name.to_string(),
+                        ..CrateUsage::default()
+                    });
+                if name.contains('-') {
+                    usage.name = name.to_string();
+                }
+                usage.by_dir[dir.index()] += sweep::entry_size(&entry.path());
+            }
+        }
+    }
+
+    let mut crates: Vec<CrateUsage> = crates
+        .into_values()
+        .filter(|usage| usage.total() > 0)
+        .collect();
+    crates.sort_by(|a, b| b.total().cmp(&a.total()).then_with(|| a.name.cmp(&b.name)));
+
+    TargetUsage {
+        crates,
+        total: sweep::entry_size(target),
+    }
+}
+
+/// Extract the crate name from the name of a unit entry, e.g. `serde` from
+/// `libserde-1a2b3c4d.rlib`, `serde-1a2b3c4d.d` or `serde-1a2b3c4d`.
+///
+/// Returns `None` for entries without a hash suffix, which are not units.
+///
+/// # Examples
+///
+/// ```
+/// # use clean_dev_dirs::target_usage::unit_crate_name;
+/// assert_eq!(unit_crate_name("libserde-1a2b3c4d.rlib"), Some("serde"));
+/// assert_eq!(unit_crate_name("librocksdb-sys-1a2b3c4d"), Some("librocksdb-sys"));
+/// assert_eq!(unit_crate_name("build-script-build"), None);
+/// ```
+#[must_use]
+pub fn unit_crate_name(file_name: &str) -> Option<&str> {
+    let (stem, extension) = file_name
+        .split_once('.')
+        .map_or((file_name, ""), |(stem, extension)| (stem, extension));
+    let (name, hash) = stem.rsplit_once('-')?;
+    let is_hash = hash.len() >= 8 && hash.chars().all(|c| c.is_ascii_alphanumeric());
+    if name.is_empty() || !is_hash {
+        return None;
+    }
+
+    // Libraries get a `lib` prefix on every platform but Windows
+    let is_library = matches!(extension, "rlib" | "rmeta" | "so" | "dylib" | "a");
+    Some(
+        name.strip_prefix("lib")
+            .filter(|stripped| is_library && !stripped.is_empty())
+            .unwrap_or(name),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    fn write(path: &Path, len: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; len]).unwrap();
+    }
+
+    #[test]
+    fn test_unit_crate_name() {
+        assert_eq!(unit_crate_name("libserde-1a2b3c4d.rlib"), Some("serde"));
+        assert_eq!(unit_crate_name("libserde-1a2b3c4d.rmeta"), Some("serde"));
+        assert_eq!(unit_crate_name("serde-1a2b3c4d.d"), Some("serde"));
+        assert_eq!(unit_crate_name("serde_json-1a2b3c4d"), Some("serde_json"));
+        assert_eq!(unit_crate_name("libc-1a2b3c4d"), Some("libc"));
+        assert_eq!(unit_crate_name("liblibc-1a2b3c4d.rlib"), Some("libc"));
+        assert_eq!(
+            unit_crate_name("app-2x3kq0qz4o9ye"),
+            Some("app"),
+            "incremental directories use base-36 hashes"
+        );
+        assert_eq!(unit_crate_name("build-script-build"), None);
+        assert_eq!(unit_crate_name("app"), None);
+        assert_eq!(unit_crate_name("-1a2b3c4d"), None);
+    }
+
+    #[test]
+    fn test_analyze_attributes_units_to_crates() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        let debug = target.join("debug");
+        write(&debug.join("deps/liblibrocksdb_sys-1a2b3c4d.rlib"), 300);
+        write(&debug.join("deps/librocksdb_sys-1a2b3c4d.d"), 10);
+        write(
+            &debug.join("build/librocksdb-sys-5e6f7a8b/out/librocksdb.a"),
+            5000,
+        );
+        write(&debug.join(".fingerprint/librocksdb-sys-1a2b3c4d/lib"), 20);
+        write(&debug.join("deps/libserde-9a8b7c6d.rlib"), 100);
+        write(&debug.join("incremental/app-2x3kq0qz4o9ye/s-1/query"), 700);
+        write(&debug.join("app"), 40);
+        write(&target.join("release/.fingerprint/serde-0f0f0f0f/lib"), 5);
+
+        let usage = analyze(&target);
+
+        let names: Vec<&str> = usage.crates.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["librocksdb-sys", "app", "serde"]);
+
+        let rocksdb = &usage.crates[0];
+        assert_eq!(rocksdb.in_dir(UnitDir::Build), 5000);
+        assert_eq!(rocksdb.in_dir(UnitDir::Deps), 310);
+        assert_eq!(rocksdb.in_dir(UnitDir::Fingerprint), 20);
+        assert_eq!(rocksdb.total(), 5330);
+        assert_eq!(usage.crates[2].total(), 105, "profiles are added up");
+
+        assert_eq!(usage.total, 6175);
+        assert_eq!(usage.unattributed(), 40);
+    }
+
+    #[test]
+    fn test_analyze_non_target_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        write(&temp_dir.path().join("src/main.rs"), 10);
+
+        let usage = analyze(temp_dir.path());
+
+        assert!(usage.crates.is_empty());
+        assert_eq!(usage.unattributed(), 10);
+    }
+}
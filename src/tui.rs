@@ -0,0 +1,786 @@
+//! Full-screen terminal interface for selecting projects (`--tui`).
+//!
+//! The multi-select prompt of `--interactive` becomes hard to use with
+//! hundreds of projects. This module shows the projects in a table that can
+//! be sorted by size, age, type or path and filtered by typing, keeps a live
+//! total of the space reclaimable by the marked projects, and asks for
+//! confirmation before anything is cleaned.
+//!
+//! With [`select_streamed_projects`], the table opens as soon as the scan
+//! finds the first projects: they are listed with a spinner until their
+//! size is known, and dropped if they turn out to be empty or filtered out.
+//!
+//! # Keys
+//!
+//! | Key | Action |
+//! |-----|--------|
+//! | `↑`/`↓`, `k`/`j`, `PgUp`/`PgDn`, `g`/`G` | Move the cursor |
+//! | `Space` | Mark or unmark the project under the cursor |
+//! | `a` / `n` | Mark / unmark all listed projects |
+//! | `s` / `r` | Cycle the sort column / reverse the order |
+//! | `/` | Filter by path, name or type (`Enter` keeps it, `Esc` clears it) |
+//! | `Enter` | Review the marked projects before cleaning |
+//! | `q`, `Esc` | Quit without cleaning |
+
+use std::path::Path;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, SystemTime};
+
+use ratatui::{
+    Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Row, Table, TableState},
+};
+
+use crate::error::Result;
+use crate::filtering::last_modified;
+use crate::project::{Project, ProjectType};
+use crate::utils::format_size;
+
+/// Number of rows a `PgUp`/`PgDn` moves the cursor.
+const PAGE: usize = 20;
+
+/// Frames of the spinner shown in place of the size of pending projects.
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// How often the table is redrawn while the scan goes on.
+const TICK: Duration = Duration::from_millis(100);
+
+/// A change to the projects listed by [`select_streamed_projects`].
+pub enum StreamEvent {
+    /// A project was found; its size is still being measured.
+    Found(Project),
+
+    /// A project found earlier was sized and is to be listed.
+    Sized(Project),
+
+    /// A project found earlier is not to be listed, because its build
+    /// directories are empty or it was filtered out.
+    Dropped(Project),
+}
+
+/// Projects picked in the TUI, and whether to keep their executables.
+pub struct TuiSelection {
+    /// The marked projects, in their original order.
+    pub projects: Vec<Project>,
+
+    /// Whether compiled executables should be kept before cleaning.
+    pub keep_executables: bool,
+}
+
+/// Let the user pick projects to clean in a full-screen table.
+///
+/// All projects are marked initially. `keep_executables` is the initial
+/// state of the "keep executables" toggle of the confirmation screen.
+///
+/// Returns `Ok(None)` if the user quit without confirming.
+///
+/// # Errors
+///
+/// Returns an error if the terminal cannot be set up or read from.
+pub fn select_projects(
+    projects: &[Project],
+    keep_executables: bool,
+) -> Result<Option<TuiSelection>> {
+    run(App::new(projects.to_vec(), keep_executables), None)
+}
+
+/// Let the user pick projects to clean in a full-screen table while the
+/// scan sending `events` goes on.
+///
+/// The scan is over once every sender of `events` is dropped; projects
+/// still pending then are removed. Projects found are marked initially, but
+/// only those sized by the time the user confirms are returned.
+///
+/// Returns `Ok(None)` if the user quit without confirming.
+///
+/// # Errors
+///
+/// Returns an error if the terminal cannot be set up or read from.
+pub fn select_streamed_projects(
+    events: &Receiver<StreamEvent>,
+    keep_executables: bool,
+) -> Result<Option<TuiSelection>> {
+    run(App::new(Vec::new(), keep_executables), Some(events))
+}
+
+/// Show `app` until the user quits or confirms, applying `events` as they
+/// come if the scan is still going on.
+fn run(mut app: App, events: Option<&Receiver<StreamEvent>>) -> Result<Option<TuiSelection>> {
+    app.scanning = events.is_some();
+    let mut terminal = ratatui::try_init()?;
+
+    let outcome = loop {
+        if let Some(events) = events {
+            app.receive(events);
+        }
+        if let Err(error) = terminal.draw(|frame| app.render(frame)) {
+            break Err(error);
+        }
+
+        // Redraw regularly while projects keep coming and spinners turn
+        let ready = if app.scanning {
+            event::poll(TICK)
+        } else {
+            Ok(true)
+        };
+        match ready.and_then(|ready| ready.then(event::read).transpose()) {
+            Ok(Some(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                match app.handle_key(key) {
+                    Outcome::Continue => {}
+                    outcome => break Ok(outcome),
+                }
+            }
+            Ok(_) => {}
+            Err(error) => break Err(error),
+        }
+    };
+
+    ratatui::try_restore()?;
+
+    Ok((outcome? == Outcome::Clean).then(|| TuiSelection {
+        projects: app.marked_projects(),
+        keep_executables: app.keep_executables,
+    }))
+}
+
+/// Column the table is sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SortColumn {
+    Size,
+    Age,
+    Type,
+    Path,
+}
+
+impl SortColumn {
+    /// The column sorted by after this one when cycling with `s`.
+    const fn next(self) -> Self {
+        match self {
+            Self::Size => Self::Age,
+            Self::Age => Self::Type,
+            Self::Type => Self::Path,
+            Self::Path => Self::Size,
+        }
+    }
+}
+
+/// What the TUI is currently doing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Browse,
+    Filter,
+    Confirm,
+}
+
+/// Result of handling a key press.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Continue,
+    Quit,
+    Clean,
+}
+
+/// State of the TUI.
+struct App {
+    projects: Vec<Project>,
+    modified: Vec<Option<SystemTime>>,
+    marked: Vec<bool>,
+    /// Whether each project is still being sized.
+    pending: Vec<bool>,
+    /// Whether the scan is still going on.
+    scanning: bool,
+    /// Number of frames drawn, to turn the spinners.
+    tick: usize,
+    sort: SortColumn,
+    reverse: bool,
+    filter: String,
+    mode: Mode,
+    /// Indices of the listed projects, filtered and sorted.
+    visible: Vec<usize>,
+    table: TableState,
+    keep_executables: bool,
+}
+
+impl App {
+    fn new(projects: Vec<Project>, keep_executables: bool) -> Self {
+        let mut app = Self {
+            modified: projects.iter().map(last_modified).collect(),
+            marked: vec![true; projects.len()],
+            pending: vec![false; projects.len()],
+            projects,
+            scanning: false,
+            tick: 0,
+            sort: SortColumn::Size,
+            reverse: false,
+            filter: String::new(),
+            mode: Mode::Browse,
+            visible: Vec::new(),
+            table: TableState::default(),
+            keep_executables,
+        };
+        app.refresh();
+        app
+    }
+
+    /// Apply the events received so far, and notice the end of the scan.
+    fn receive(&mut self, events: &Receiver<StreamEvent>) {
+        let mut changed = false;
+        loop {
+            match events.try_recv() {
+                Ok(event) => {
+                    self.apply(event);
+                    changed = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.finish_scan();
+                    changed = true;
+                    break;
+                }
+            }
+        }
+        if changed {
+            self.refresh();
+        }
+    }
+
+    /// List, size or remove a project as told by `event`.
+    fn apply(&mut self, event: StreamEvent) {
+        match event {
+            StreamEvent::Found(project) => self.push(project, true),
+            StreamEvent::Sized(project) => match self.position(&project.root_path) {
+                Some(i) => {
+                    self.modified[i] = last_modified(&project);
+                    self.projects[i] = project;
+                    self.pending[i] = false;
+                }
+                None => self.push(project, false),
+            },
+            StreamEvent::Dropped(project) => {
+                if let Some(i) = self.position(&project.root_path) {
+                    self.remove(i);
+                }
+            }
+        }
+    }
+
+    /// Remove the projects left pending once the scan is over.
+    fn finish_scan(&mut self) {
+        self.scanning = false;
+        while let Some(i) = self.pending.iter().position(|&pending| pending) {
+            self.remove(i);
+        }
+    }
+
+    fn push(&mut self, project: Project, pending: bool) {
+        self.modified.push(last_modified(&project));
+        self.projects.push(project);
+        self.marked.push(true);
+        self.pending.push(pending);
+    }
+
+    fn remove(&mut self, i: usize) {
+        self.projects.remove(i);
+        self.modified.remove(i);
+        self.marked.remove(i);
+        self.pending.remove(i);
+    }
+
+    fn position(&self, root: &Path) -> Option<usize> {
+        self.projects
+            .iter()
+            .position(|project| project.root_path == root)
+    }
+
+    /// Recompute the listed projects after the filter, the sort or the
+    /// projects changed.
+    fn refresh(&mut self) {
+        let filter = self.filter.to_lowercase();
+        self.visible = (0..self.projects.len())
+            .filter(|&i| filter.is_empty() || search_text(&self.projects[i]).contains(&filter))
+            .collect();
+
+        let projects = &self.projects;
+        match self.sort {
+            SortColumn::Size => self
+                .visible
+                .sort_by_key(|&i| std::cmp::Reverse(projects[i].total_size())),
+            SortColumn::Age => self.visible.sort_by_key(|&i| self.modified[i]),
+            SortColumn::Type => self.visible.sort_by_key(|&i| type_label(&projects[i].kind)),
+            SortColumn::Path => self
+                .visible
+                .sort_by(|&a, &b| projects[a].root_path.cmp(&projects[b].root_path)),
+        }
+        if self.reverse {
+            self.visible.reverse();
+        }
+
+        let cursor = self.table.selected().unwrap_or(0);
+        self.table.select(if self.visible.is_empty() {
+            None
+        } else {
+            Some(cursor.min(self.visible.len() - 1))
+        });
+    }
+
+    /// Total size of the marked projects, in bytes.
+    fn marked_size(&self) -> u64 {
+        self.marked_indices()
+            .map(|i| self.projects[i].total_size())
+            .sum()
+    }
+
+    /// Indices of the marked projects, leaving out those still pending.
+    fn marked_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.projects.len()).filter(|&i| self.marked[i] && !self.pending[i])
+    }
+
+    fn pending_count(&self) -> usize {
+        self.pending.iter().filter(|&&pending| pending).count()
+    }
+
+    fn marked_projects(&self) -> Vec<Project> {
+        self.marked_indices()
+            .map(|i| self.projects[i].clone())
+            .collect()
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let last = self.visible.len() - 1;
+        let cursor = self.table.selected().unwrap_or(0);
+        self.table
+            .select(Some(cursor.saturating_add_signed(delta).min(last)));
+    }
+
+    fn set_visible_marks(&mut self, marked: bool) {
+        for &i in &self.visible {
+            self.marked[i] = marked;
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Outcome::Quit;
+        }
+
+        match self.mode {
+            Mode::Browse => self.handle_browse_key(key.code),
+            Mode::Filter => {
+                self.handle_filter_key(key.code);
+                Outcome::Continue
+            }
+            Mode::Confirm => self.handle_confirm_key(key.code),
+        }
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn handle_browse_key(&mut self, code: KeyCode) -> Outcome {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Outcome::Quit,
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1),
+            KeyCode::PageUp => self.move_cursor(-(PAGE as isize)),
+            KeyCode::PageDown => self.move_cursor(PAGE as isize),
+            KeyCode::Home | KeyCode::Char('g') => self.move_cursor(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.move_cursor(isize::MAX),
+            KeyCode::Char(' ') => {
+                if let Some(&i) = self.table.selected().and_then(|row| self.visible.get(row)) {
+                    self.marked[i] = !self.marked[i];
+                    self.move_cursor(1);
+                }
+            }
+            KeyCode::Char('a') => self.set_visible_marks(true),
+            KeyCode::Char('n') => self.set_visible_marks(false),
+            KeyCode::Char('s') => {
+                self.sort = self.sort.next();
+                self.refresh();
+            }
+            KeyCode::Char('r') => {
+                self.reverse = !self.reverse;
+                self.refresh();
+            }
+            KeyCode::Char('/') => self.mode = Mode::Filter,
+            KeyCode::Enter if self.marked_indices().next().is_some() => {
+                self.mode = Mode::Confirm;
+            }
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    fn handle_filter_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Enter => self.mode = Mode::Browse,
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.mode = Mode::Browse;
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+            }
+            KeyCode::Char(c) => self.filter.push(c),
+            _ => return,
+        }
+        self.refresh();
+    }
+
+    const fn handle_confirm_key(&mut self, code: KeyCode) -> Outcome {
+        match code {
+            KeyCode::Char('y') | KeyCode::Enter => return Outcome::Clean,
+            KeyCode::Char('e') => self.keep_executables = !self.keep_executables,
+            KeyCode::Char('n') | KeyCode::Esc => self.mode = Mode::Browse,
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        self.tick = self.tick.wrapping_add(1);
+        let marked = self.marked_indices().count();
+        let mut status = vec![
+            Span::from(" clean-dev-dirs ").bold().reversed(),
+            Span::from(format!(
+                "  {marked}/{} marked, {} reclaimable",
+                self.projects.len() - self.pending_count(),
+                format_size(self.marked_size())
+            )),
+        ];
+        if self.scanning {
+            status.push(
+                Span::from(format!(
+                    "  · scanning, {} being sized",
+                    self.pending_count()
+                ))
+                .dim(),
+            );
+        }
+        frame.render_widget(Line::from(status), header);
+
+        self.render_table(frame, body);
+
+        let help = match self.mode {
+            Mode::Browse => {
+                "space mark · a/n all/none · s sort · r reverse · / filter · enter clean · q quit"
+                    .to_string()
+            }
+            Mode::Filter => format!("filter: {}█  (enter keep · esc clear)", self.filter),
+            Mode::Confirm => String::new(),
+        };
+        frame.render_widget(Line::from(help).dim(), footer);
+
+        if self.mode == Mode::Confirm {
+            self.render_confirmation(frame);
+        }
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: Rect) {
+        let now = SystemTime::now();
+        let rows = self.visible.iter().map(|&i| {
+            let project = &self.projects[i];
+            let mark = if self.marked[i] { "[x]" } else { "[ ]" };
+            let size = if self.pending[i] {
+                SPINNER[(self.tick + i) % SPINNER.len()].to_string()
+            } else {
+                format_size(project.total_size())
+            };
+            Row::new(vec![
+                mark.to_string(),
+                size,
+                self.modified[i].map_or_else(|| "?".to_string(), |time| format_age(now, time)),
+                type_label(&project.kind).to_string(),
+                project.root_path.display().to_string(),
+            ])
+        });
+
+        let arrow = if self.reverse { "▲" } else { "▼" };
+        let title = |column: SortColumn, name: &str| {
+            if self.sort == column {
+                format!("{name} {arrow}")
+            } else {
+                name.to_string()
+            }
+        };
+        let header = Row::new(vec![
+            String::new(),
+            title(SortColumn::Size, "Size"),
+            title(SortColumn::Age, "Age"),
+            title(SortColumn::Type, "Type"),
+            title(SortColumn::Path, "Path"),
+        ])
+        .bold();
+
+        let filter = if self.filter.is_empty() {
+            String::new()
+        } else {
+            format!(" filter: {} ", self.filter)
+        };
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(3),
+                Constraint::Length(10),
+                Constraint::Length(6),
+                Constraint::Length(11),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .block(Block::bordered().title(filter))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("› ");
+
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+
+    fn render_confirmation(&self, frame: &mut Frame) {
+        let [area] = Layout::horizontal([Constraint::Length(56)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        let [area] = Layout::vertical([Constraint::Length(7)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let marked = self.marked_indices().count();
+        let keep = if self.keep_executables { "[x]" } else { "[ ]" };
+        let text = vec![
+            Line::from(format!(
+                "Clean {marked} projects, freeing {}?",
+                format_size(self.marked_size())
+            ))
+            .bold(),
+            Line::from(""),
+            Line::from(format!("{keep} keep compiled executables (e)")),
+            Line::from(""),
+            Line::from("y/enter clean · n/esc back").dim(),
+        ];
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(text).block(
+                Block::bordered()
+                    .title(" Confirm ")
+                    .border_style(Style::new().fg(Color::Yellow)),
+            ),
+            area,
+        );
+    }
+}
+
+/// Lowercase text a project is matched against when filtering.
+fn search_text(project: &Project) -> String {
+    format!(
+        "{} {} {}",
+        project.root_path.display(),
+        project.name.as_deref().unwrap_or_default(),
+        type_label(&project.kind)
+    )
+    .to_lowercase()
+}
+
+/// Short type name shown in the table.
+const fn type_label(kind: &ProjectType) -> &'static str {
+    match kind {
+        ProjectType::Rust => "Rust",
+        ProjectType::Node => "Node.js",
+        ProjectType::Python => "Python",
+        ProjectType::Go => "Go",
+        ProjectType::Java => "Java/Kotlin",
+        ProjectType::Cpp => "C/C++",
+        ProjectType::Swift => "Swift",
+        ProjectType::DotNet => ".NET/C#",
+        ProjectType::OCaml => "OCaml",
+        ProjectType::Unity => "Unity",
+        ProjectType::Unreal => "Unreal",
+    }
+}
+
+/// Format the time elapsed between `time` and `now` compactly, e.g. `3d`.
+fn format_age(now: SystemTime, time: SystemTime) -> String {
+    let days = now.duration_since(time).unwrap_or_default().as_secs() / (24 * 60 * 60);
+
+    match days {
+        0 => "today".to_string(),
+        1..365 => format!("{days}d"),
+        _ => format!("{}y", days / 365),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::BuildArtifacts;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn project(root: &str, kind: ProjectType, size: u64) -> Project {
+        Project::new(
+            kind,
+            PathBuf::from(root),
+            vec![BuildArtifacts {
+                path: PathBuf::from(root).join("build"),
+                size,
+            }],
+            None,
+        )
+    }
+
+    fn projects() -> Vec<Project> {
+        vec![
+            project("/dev/api", ProjectType::Rust, 300),
+            project("/dev/web", ProjectType::Node, 500),
+            project("/dev/tools", ProjectType::Python, 100),
+        ]
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Outcome {
+        app.handle_key(KeyEvent::from(code))
+    }
+
+    fn visible_roots(app: &App) -> Vec<String> {
+        app.visible
+            .iter()
+            .map(|&i| app.projects[i].root_path.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_sorting() {
+        let mut app = App::new(projects(), false);
+        assert_eq!(visible_roots(&app), ["/dev/web", "/dev/api", "/dev/tools"]);
+
+        press(&mut app, KeyCode::Char('s'));
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.sort, SortColumn::Type);
+        assert_eq!(visible_roots(&app), ["/dev/web", "/dev/tools", "/dev/api"]);
+
+        press(&mut app, KeyCode::Char('s'));
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(visible_roots(&app), ["/dev/web", "/dev/tools", "/dev/api"]);
+    }
+
+    #[test]
+    fn test_filtering() {
+        let mut app = App::new(projects(), false);
+
+        press(&mut app, KeyCode::Char('/'));
+        for c in "RUST".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(visible_roots(&app), ["/dev/api"]);
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.mode, Mode::Browse);
+        assert_eq!(app.visible.len(), 3);
+    }
+
+    #[test]
+    fn test_marking_updates_reclaimable_total() {
+        let mut app = App::new(projects(), false);
+        assert_eq!(app.marked_size(), 900);
+
+        // Unmark the largest project, under the cursor
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(app.marked_size(), 400);
+
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.marked_size(), 0);
+        // Nothing to confirm
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, Mode::Browse);
+
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(app.marked_size(), 900);
+    }
+
+    #[test]
+    fn test_confirmation() {
+        let mut app = App::new(projects(), false);
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, Mode::Confirm);
+
+        press(&mut app, KeyCode::Char('e'));
+        assert!(app.keep_executables);
+        assert_eq!(press(&mut app, KeyCode::Char('y')), Outcome::Clean);
+
+        let roots: Vec<_> = app
+            .marked_projects()
+            .iter()
+            .map(|project| project.root_path.clone())
+            .collect();
+        assert_eq!(
+            roots,
+            [PathBuf::from("/dev/web"), PathBuf::from("/dev/tools")]
+        );
+    }
+
+    #[test]
+    fn test_quit() {
+        let mut app = App::new(projects(), false);
+        assert_eq!(press(&mut app, KeyCode::Char('q')), Outcome::Quit);
+        assert_eq!(
+            app.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Outcome::Quit
+        );
+    }
+
+    #[test]
+    fn test_streamed_projects() {
+        let (sender, events) = std::sync::mpsc::channel();
+        let mut app = App::new(Vec::new(), false);
+        app.scanning = true;
+
+        let projects = projects();
+        let found = |project: &Project| Project {
+            build_arts: Vec::new(),
+            ..project.clone()
+        };
+        for project in &projects {
+            sender.send(StreamEvent::Found(found(project))).unwrap();
+        }
+        sender
+            .send(StreamEvent::Sized(projects[1].clone()))
+            .unwrap();
+        sender
+            .send(StreamEvent::Dropped(found(&projects[2])))
+            .unwrap();
+        app.receive(&events);
+
+        assert_eq!(visible_roots(&app), ["/dev/web", "/dev/api"]);
+        assert_eq!(app.pending_count(), 1);
+        // Only sized projects count and can be cleaned
+        assert_eq!(app.marked_size(), 500);
+        assert_eq!(app.marked_projects().len(), 1);
+
+        drop(sender);
+        app.receive(&events);
+        assert!(!app.scanning);
+        assert_eq!(visible_roots(&app), ["/dev/web"]);
+    }
+
+    #[test]
+    fn test_format_age() {
+        let now = SystemTime::now();
+        let day = Duration::from_hours(24);
+
+        assert_eq!(format_age(now, now), "today");
+        assert_eq!(format_age(now, now - day * 3), "3d");
+        assert_eq!(format_age(now, now - day * 800), "2y");
+    }
+}
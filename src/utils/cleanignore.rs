@@ -0,0 +1,247 @@
+//! `.cleanignore` files.
+//!
+//! A `.cleanignore` file in any scanned directory lists, in gitignore syntax,
+//! paths below that directory that must never be cleaned: projects whose
+//! root is ignored are not reported at all, and ignored build artifacts are
+//! left out of their project. As with gitignore:
+//! - Blank lines and lines starting with `#` are ignored.
+//! - A pattern without a `/` (other than a trailing one) matches at any
+//!   depth, e.g. `experiments` or `*.bak`; other patterns are relative to the
+//!   directory of the `.cleanignore` file, e.g. `/legacy` or `apps/old`.
+//! - `*` does not cross directory separators while `**` does.
+//! - A pattern starting with `!` re-includes what an earlier one ignored.
+//! - Everything below an ignored directory is ignored too, and a
+//!   `.cleanignore` file deeper in the tree overrides the ones above it.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::vfs::FsHandle;
+
+/// Name of the files listing paths to ignore.
+pub const CLEANIGNORE_FILE: &str = ".cleanignore";
+
+/// The patterns of one `.cleanignore` file.
+#[derive(Debug)]
+struct IgnoreFile {
+    /// One glob per pattern, in file order.
+    globs: GlobSet,
+
+    /// Whether each pattern re-includes (`!`) rather than ignores.
+    negated: Vec<bool>,
+}
+
+impl IgnoreFile {
+    /// Parse the contents of a `.cleanignore` file.
+    ///
+    /// Invalid patterns are skipped, like git does.
+    fn parse(contents: &str) -> Self {
+        let mut builder = GlobSetBuilder::new();
+        let mut negated = Vec::new();
+
+        for line in contents.lines() {
+            let Some((pattern, is_negated)) = parse_line(line) else {
+                continue;
+            };
+            let glob = GlobBuilder::new(&pattern)
+                .literal_separator(true)
+                .backslash_escape(true)
+                .build();
+            if let Ok(glob) = glob {
+                builder.add(glob);
+                negated.push(is_negated);
+            }
+        }
+
+        Self {
+            globs: builder.build().unwrap_or_else(|_| GlobSet::empty()),
+            negated,
+        }
+    }
+
+    /// Return whether the last pattern matching `relative` ignores it
+    /// (`Some(true)`) or re-includes it (`Some(false)`), if any matches.
+    fn matched(&self, relative: &Path) -> Option<bool> {
+        self.globs
+            .matches(relative)
+            .into_iter()
+            .max()
+            .map(|index| !self.negated[index])
+    }
+}
+
+/// Turn a line of a `.cleanignore` file into a glob and whether it is
+/// negated, or `None` for blank lines and comments.
+fn parse_line(line: &str) -> Option<(String, bool)> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (line, negated) = line
+        .strip_prefix('!')
+        .map_or((line, false), |rest| (rest, true));
+    // `\#` and `\!` escape a leading `#` or `!`
+    let line = line
+        .strip_prefix('\\')
+        .filter(|rest| rest.starts_with(['#', '!']))
+        .unwrap_or(line);
+
+    glob_pattern(line).map(|glob| (glob, negated))
+}
+
+/// Turn a gitignore-style pattern into a glob relative to the directory of
+/// the file it is in, or `None` if it is empty.
+pub(crate) fn glob_pattern(pattern: &str) -> Option<String> {
+    let pattern = pattern.strip_suffix('/').unwrap_or(pattern);
+    if pattern.is_empty() {
+        return None;
+    }
+
+    Some(match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if pattern.contains('/') => pattern.to_string(),
+        None => format!("**/{pattern}"),
+    })
+}
+
+/// The `.cleanignore` files of a scanned tree, read on first use.
+#[derive(Debug, Default)]
+pub struct CleanIgnore {
+    /// Parsed `.cleanignore` file of each directory looked at, if it has one.
+    files: Mutex<HashMap<PathBuf, Option<Arc<IgnoreFile>>>>,
+
+    /// File system the files are read from.
+    fs: FsHandle,
+}
+
+impl CleanIgnore {
+    /// Create an empty cache of `.cleanignore` files.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty cache of the `.cleanignore` files of `fs`.
+    #[must_use]
+    pub fn with_fs(fs: FsHandle) -> Self {
+        Self {
+            fs,
+            ..Self::default()
+        }
+    }
+
+    /// Check whether `path`, below `root`, is ignored by the `.cleanignore`
+    /// files of `root` and the directories in between.
+    #[must_use]
+    pub fn is_ignored(&self, root: &Path, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return false;
+        };
+
+        // A path is ignored if it, or any directory above it, is ignored
+        let mut current = root.to_path_buf();
+        relative.components().any(|component| {
+            current.push(component);
+            self.is_ignored_itself(root, &current)
+        })
+    }
+
+    /// Check whether `path` itself is ignored, the deepest `.cleanignore`
+    /// file with a matching pattern deciding.
+    fn is_ignored_itself(&self, root: &Path, path: &Path) -> bool {
+        let mut ignored = false;
+        for dir in path.ancestors().skip(1) {
+            if let Some(file) = self.file(dir)
+                && let Ok(relative) = path.strip_prefix(dir)
+                && let Some(matched) = file.matched(relative)
+            {
+                ignored = matched;
+                break;
+            }
+            if dir == root {
+                break;
+            }
+        }
+        ignored
+    }
+
+    /// Return the parsed `.cleanignore` file of `dir`, if it has one.
+    fn file(&self, dir: &Path) -> Option<Arc<IgnoreFile>> {
+        let mut files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        files
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                self.fs
+                    .read_to_string(&dir.join(CLEANIGNORE_FILE))
+                    .ok()
+                    .map(|contents| Arc::new(IgnoreFile::parse(&contents)))
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFs;
+
+    fn ignored(file: &IgnoreFile, path: &str) -> Option<bool> {
+        file.matched(Path::new(path))
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(parse_line("# comment"), None);
+        assert_eq!(parse_line("   "), None);
+        assert_eq!(parse_line("legacy"), Some(("**/legacy".into(), false)));
+        assert_eq!(parse_line("legacy/"), Some(("**/legacy".into(), false)));
+        assert_eq!(parse_line("/legacy"), Some(("legacy".into(), false)));
+        assert_eq!(parse_line("apps/old"), Some(("apps/old".into(), false)));
+        assert_eq!(parse_line("!keep"), Some(("**/keep".into(), true)));
+        assert_eq!(parse_line("\\#hash"), Some(("**/#hash".into(), false)));
+    }
+
+    #[test]
+    fn test_last_matching_pattern_wins() {
+        let file = IgnoreFile::parse("experiments\n!experiments\nold-*\n");
+
+        assert_eq!(ignored(&file, "experiments"), Some(false));
+        assert_eq!(ignored(&file, "a/b/old-app"), Some(true));
+        assert_eq!(ignored(&file, "app"), None);
+    }
+
+    #[test]
+    fn test_anchored_patterns() {
+        let file = IgnoreFile::parse("/legacy\napps/*\n");
+
+        assert_eq!(ignored(&file, "legacy"), Some(true));
+        assert_eq!(ignored(&file, "sub/legacy"), None);
+        assert_eq!(ignored(&file, "apps/web"), Some(true));
+        // `*` does not cross directory separators
+        assert_eq!(ignored(&file, "apps/web/sub"), None);
+    }
+
+    #[test]
+    fn test_ignored_directories_and_nested_files() {
+        let root = Path::new("/root");
+        let fs = MemoryFs::new()
+            .with_dir("/root/work/keep")
+            .with_file("/root/.cleanignore", "work\nscratch\n")
+            .with_file("/root/work/.cleanignore", "!keep\n")
+            .with_dir("/root/play/scratch")
+            .with_file("/root/play/.cleanignore", "!scratch\n");
+
+        let cleanignore = CleanIgnore::with_fs(FsHandle::new(Arc::new(fs)));
+        assert!(!cleanignore.is_ignored(root, &root.join("app")));
+        // Everything below an ignored directory is ignored
+        assert!(cleanignore.is_ignored(root, &root.join("work/app")));
+        assert!(cleanignore.is_ignored(root, &root.join("work/keep")));
+        // A deeper file overrides the ones above it
+        assert!(!cleanignore.is_ignored(root, &root.join("play/scratch")));
+        assert!(cleanignore.is_ignored(root, &root.join("scratch/app")));
+    }
+}
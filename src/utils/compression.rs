@@ -0,0 +1,150 @@
+//! Compressed size estimation.
+//!
+//! Archiving build artifacts instead of deleting them is only worthwhile when
+//! they compress well. Compressing whole `target/` directories just to find
+//! out would take longer than rebuilding them, so this module compresses a
+//! sample of each directory with zstd and extrapolates the ratio to the full
+//! size.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::project::Project;
+
+/// Bytes read from the start of each sampled file.
+const SAMPLE_BYTES_PER_FILE: u64 = 64 * 1024;
+
+/// Upper bound on the bytes sampled per build directory.
+const MAX_SAMPLE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// zstd level used for the estimate (zstd's default).
+const ZSTD_LEVEL: i32 = 3;
+
+/// Estimate the size of a project's build artifacts once compressed.
+///
+/// Each build directory is estimated separately with
+/// [`compression_ratio`]; directories that cannot be sampled are assumed to
+/// be incompressible.
+///
+/// # Returns
+///
+/// The estimated compressed size in bytes, never more than
+/// [`Project::total_size`].
+#[must_use]
+pub fn estimate_compressed_size(project: &Project) -> u64 {
+    project
+        .build_arts
+        .iter()
+        .map(|artifact| {
+            compression_ratio(&artifact.path).map_or(artifact.size, |ratio| {
+                #[allow(
+                    clippy::cast_precision_loss,
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss
+                )]
+                let estimate = (artifact.size as f64 * ratio) as u64;
+                estimate
+            })
+        })
+        .sum()
+}
+
+/// Estimate how well the files below `dir` compress with zstd.
+///
+/// Up to [`SAMPLE_BYTES_PER_FILE`] bytes are read from files spread evenly
+/// across the directory, until [`MAX_SAMPLE_BYTES`] have been collected; the
+/// sample is then compressed as a single stream.
+///
+/// # Returns
+///
+/// The compressed-to-original size ratio, capped at `1.0`, or `None` if no
+/// file could be read.
+#[must_use]
+pub fn compression_ratio(dir: &Path) -> Option<f64> {
+    let files: Vec<PathBuf> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(walkdir::DirEntry::into_path)
+        .collect();
+
+    let max_files = usize::try_from(MAX_SAMPLE_BYTES / SAMPLE_BYTES_PER_FILE).unwrap_or(1);
+    let step = files.len().div_ceil(max_files).max(1);
+
+    let mut sample = Vec::new();
+    for path in files.iter().step_by(step) {
+        if let Ok(file) = File::open(path) {
+            // Unreadable files are skipped, the sample is only an estimate
+            let _ = file.take(SAMPLE_BYTES_PER_FILE).read_to_end(&mut sample);
+        }
+
+        if sample.len() as u64 >= MAX_SAMPLE_BYTES {
+            break;
+        }
+    }
+
+    if sample.is_empty() {
+        return None;
+    }
+
+    let compressed = zstd::bulk::compress(&sample, ZSTD_LEVEL).ok()?;
+
+    #[allow(clippy::cast_precision_loss)]
+    let ratio = compressed.len() as f64 / sample.len() as f64;
+    Some(ratio.min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{BuildArtifacts, ProjectType};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compression_ratio_of_repetitive_data_is_small() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("zeros.bin"), vec![0u8; 100_000]).unwrap();
+
+        let ratio = compression_ratio(temp_dir.path()).unwrap();
+        assert!(ratio < 0.01, "ratio was {ratio}");
+    }
+
+    #[test]
+    fn test_compression_ratio_of_empty_dir_is_none() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(compression_ratio(temp_dir.path()).is_none());
+        assert!(compression_ratio(&temp_dir.path().join("missing")).is_none());
+    }
+
+    #[test]
+    fn test_estimate_compressed_size_falls_back_to_full_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("zeros.bin"), vec![0u8; 100_000]).unwrap();
+
+        let project = Project::new(
+            ProjectType::Rust,
+            temp_dir.path().to_path_buf(),
+            vec![
+                BuildArtifacts {
+                    path: target,
+                    size: 100_000,
+                },
+                BuildArtifacts {
+                    path: temp_dir.path().join("missing"),
+                    size: 500,
+                },
+            ],
+            None,
+        );
+
+        let estimate = estimate_compressed_size(&project);
+        assert!(estimate >= 500, "missing directory must count in full");
+        assert!(estimate < 2_000, "estimate was {estimate}");
+    }
+}
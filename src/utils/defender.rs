@@ -0,0 +1,175 @@
+//! Diagnosis of antivirus interference with deletions.
+//!
+//! Windows Defender's real-time protection scans files as they are touched,
+//! including while they are being deleted. On large `node_modules/` or
+//! `target/` directories this slows deletion to a crawl and produces bursts
+//! of sharing violations. This module decides, from the retry telemetry and
+//! throughput of a cleanup, whether suggesting Defender exclusions is
+//! warranted, and formats those exclusions.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::cleaner::CleanResult;
+use crate::project::Project;
+
+/// Number of transient failures from which interference is suspected.
+const MIN_TRANSIENT_FAILURES: u32 = 5;
+
+/// Throughput (bytes per second) below which a long cleanup is considered
+/// abnormally slow.
+const SLOW_THROUGHPUT: f64 = 5_000_000.0;
+
+/// Minimum duration of a cleanup before its throughput is judged.
+const MIN_SLOW_DURATION: Duration = Duration::from_secs(30);
+
+/// Check whether a cleanup looks like it was slowed down by an antivirus
+/// scanner.
+///
+/// That is the case when transient failures (sharing violations, busy files)
+/// were frequent, or when a long cleanup freed space very slowly.
+#[must_use]
+pub fn interference_suspected(result: &CleanResult) -> bool {
+    let transient_failures: u32 = result
+        .retried
+        .iter()
+        .map(|record| record.transient_failures)
+        .sum();
+    if transient_failures >= MIN_TRANSIENT_FAILURES {
+        return true;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let throughput = result.total_freed as f64 / result.elapsed.as_secs_f64();
+    result.elapsed >= MIN_SLOW_DURATION && throughput < SLOW_THROUGHPUT
+}
+
+/// Return the roots of the projects containing a retried path, to be
+/// suggested as exclusions.
+///
+/// Paths outside every project (e.g. global caches) are returned as is.
+#[must_use]
+pub fn interfered_roots(result: &CleanResult, projects: &[Project]) -> BTreeSet<PathBuf> {
+    result
+        .retried
+        .iter()
+        .map(|record| {
+            projects
+                .iter()
+                .map(|project| project.root_path.as_path())
+                .find(|root| record.path.starts_with(root))
+                .unwrap_or(&record.path)
+                .to_path_buf()
+        })
+        .collect()
+}
+
+/// PowerShell command excluding `path` from Windows Defender scans.
+#[must_use]
+pub fn exclusion_command(path: &Path) -> String {
+    format!(
+        "Add-MpPreference -ExclusionPath \"{}\"",
+        path.display().to_string().replace('"', "`\"")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{BuildArtifacts, ProjectType};
+    use crate::utils::retry::RetryRecord;
+
+    fn result(freed: u64, elapsed: Duration, failures: &[(&str, u32)]) -> CleanResult {
+        CleanResult {
+            success_count: 1,
+            total_freed: freed,
+            freed_by_type: std::collections::HashMap::new(),
+            estimated_size: freed,
+            errors: Vec::new(),
+            partial: Vec::new(),
+            cancelled_count: 0,
+            skipped: Vec::new(),
+            cleaned: Vec::new(),
+            failed: Vec::new(),
+            failed_caches: Vec::new(),
+            elapsed,
+            retried: failures
+                .iter()
+                .map(|&(path, transient_failures)| RetryRecord {
+                    path: PathBuf::from(path),
+                    transient_failures,
+                    recovered: true,
+                })
+                .collect(),
+            removed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_interference_suspected_from_transient_failures() {
+        let fast = Duration::from_secs(1);
+
+        assert!(!interference_suspected(&result(1_000_000, fast, &[])));
+        assert!(!interference_suspected(&result(
+            1_000_000,
+            fast,
+            &[("/a", 2)]
+        )));
+        assert!(interference_suspected(&result(
+            1_000_000,
+            fast,
+            &[("/a", 3), ("/b", 2)]
+        )));
+    }
+
+    #[test]
+    fn test_interference_suspected_from_throughput() {
+        let slow = Duration::from_mins(1);
+
+        assert!(interference_suspected(&result(60_000_000, slow, &[])));
+        assert!(!interference_suspected(&result(6_000_000_000, slow, &[])));
+        // Short cleanups are never judged by throughput
+        assert!(!interference_suspected(&result(
+            1_000,
+            Duration::from_secs(5),
+            &[]
+        )));
+    }
+
+    #[test]
+    fn test_interfered_roots_maps_paths_to_projects() {
+        let project = Project::new(
+            ProjectType::Node,
+            PathBuf::from("/work/app"),
+            vec![BuildArtifacts {
+                path: PathBuf::from("/work/app/node_modules"),
+                size: 0,
+            }],
+            None,
+        );
+        let result = result(
+            0,
+            Duration::ZERO,
+            &[
+                ("/work/app/node_modules/a/index.js", 1),
+                ("/work/app/node_modules/b", 1),
+                ("/cache/npm", 1),
+            ],
+        );
+
+        let roots: Vec<_> = interfered_roots(&result, &[project]).into_iter().collect();
+        assert_eq!(
+            roots,
+            vec![PathBuf::from("/cache/npm"), PathBuf::from("/work/app")]
+        );
+    }
+
+    #[test]
+    fn test_exclusion_command() {
+        assert_eq!(
+            exclusion_command(Path::new(r"C:\dev\app")),
+            r#"Add-MpPreference -ExclusionPath "C:\dev\app""#
+        );
+    }
+}
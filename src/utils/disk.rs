@@ -0,0 +1,392 @@
+//! Disk space inspection utilities.
+//!
+//! This module provides helpers for querying the free space of the volume
+//! holding a given path, and for checking whether the bytes removed during a
+//! cleanup actually show up as free space afterwards.
+//!
+//! On macOS, APFS keeps deleted blocks referenced by local Time Machine
+//! snapshots as *purgeable* space: the data is gone from the user's point of
+//! view, but `df` does not move until the system decides to thin the
+//! snapshots. The helpers here let the CLI detect and explain that situation.
+//!
+//! Moving to the trash frees nothing, and items the trash cannot simply
+//! rename into place are copied there: [`trash_shortfalls`] tells, before
+//! anything is moved, whether the trash has room for them.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::utils::mounts;
+
+/// Fraction of the freed bytes that must be visible as free space for the
+/// cleanup to be considered fully reclaimed.
+const RECLAIMED_RATIO: f64 = 0.5;
+
+/// Outcome of comparing free space before and after a cleanup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpaceCheck {
+    /// The free space increased by (roughly) the amount that was deleted.
+    Reclaimed {
+        /// Increase in free space observed on the volume, in bytes.
+        observed: u64,
+    },
+
+    /// Most of the deleted bytes did not show up as free space.
+    ///
+    /// On APFS this typically means the blocks are held by local snapshots
+    /// and are reported as purgeable space instead of free space.
+    NotReflected {
+        /// Increase in free space observed on the volume, in bytes.
+        observed: u64,
+
+        /// Bytes that were deleted but are not (yet) reported as free.
+        missing: u64,
+    },
+}
+
+impl SpaceCheck {
+    /// Compare the free space before and after a cleanup with the number of
+    /// bytes that were deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - Available bytes on the volume before cleaning
+    /// * `after` - Available bytes on the volume after cleaning
+    /// * `freed` - Bytes removed by the cleanup
+    ///
+    /// # Returns
+    ///
+    /// [`SpaceCheck::NotReflected`] when less than half of `freed` shows up as
+    /// new free space, [`SpaceCheck::Reclaimed`] otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use clean_dev_dirs::utils::disk::SpaceCheck;
+    /// let check = SpaceCheck::evaluate(1_000, 1_900, 1_000);
+    /// assert_eq!(check, SpaceCheck::Reclaimed { observed: 900 });
+    /// ```
+    #[must_use]
+    pub fn evaluate(before: u64, after: u64, freed: u64) -> Self {
+        let observed = after.saturating_sub(before);
+
+        #[allow(clippy::cast_precision_loss)]
+        let reclaimed = observed as f64 >= freed as f64 * RECLAIMED_RATIO;
+
+        if reclaimed {
+            Self::Reclaimed { observed }
+        } else {
+            Self::NotReflected {
+                observed,
+                missing: freed.saturating_sub(observed),
+            }
+        }
+    }
+}
+
+/// Return the number of bytes available to the current user on the volume
+/// that contains `path`.
+///
+/// Returns `None` if the path does not exist or the volume cannot be queried.
+#[must_use]
+pub fn available_space(path: &Path) -> Option<u64> {
+    fs4::available_space(path).ok()
+}
+
+/// Where the system trash puts an item moved to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashDestination {
+    /// The trash directory, e.g. `~/.local/share/Trash` or
+    /// `/data/.Trash-1000`.
+    pub dir: PathBuf,
+
+    /// Whether the item is copied there, then deleted, because the trash is
+    /// on another file system. Copied items take space on the volume of the
+    /// trash until it is emptied.
+    pub copied: bool,
+}
+
+/// Where the system trash would put the item at `path`.
+///
+/// On Linux and the BSDs, items on the file system of the home directory go
+/// to the home trash (`$XDG_DATA_HOME/Trash`) and the others to a trash at
+/// the root of their own file system. On macOS, items of the volume of the
+/// home directory go to `~/.Trash`, and on Windows to the recycle bin of
+/// their drive.
+///
+/// Returns `None` if the trash cannot be located.
+#[must_use]
+pub fn trash_destination(path: &Path) -> Option<TrashDestination> {
+    #[cfg(all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    ))]
+    {
+        let (home, home_trash) = match std::env::var_os("XDG_DATA_HOME").filter(|v| !v.is_empty()) {
+            Some(data_home) => {
+                let data_home = PathBuf::from(data_home);
+                let trash = data_home.join("Trash");
+                (data_home, trash)
+            }
+            None => {
+                let home = dirs::home_dir()?;
+                let trash = home.join(".local/share/Trash");
+                (home, trash)
+            }
+        };
+        let mount_point = mounts::mount_point_of(path)?;
+        let home_mount_point = mounts::mount_point_of(&home)?;
+
+        // Fedora Silverblue and its siblings mount /var/home below /
+        if mount_point == home_mount_point
+            || (mount_point == Path::new("/var/home") && home_mount_point == Path::new("/"))
+        {
+            let copied = matches!(
+                (device(path), device(&home_trash)),
+                (Some(item), Some(trash)) if item != trash
+            );
+            return Some(TrashDestination {
+                dir: home_trash,
+                copied,
+            });
+        }
+
+        let uid = current_uid()?;
+        let shared = mount_point.join(".Trash").join(uid.to_string());
+        let dir = if shared.is_dir() {
+            shared
+        } else {
+            mount_point.join(format!(".Trash-{uid}"))
+        };
+        Some(TrashDestination { dir, copied: false })
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let home = dirs::home_dir()?;
+        let dir = if device(path)? == device(&home)? {
+            home.join(".Trash")
+        } else {
+            mounts::mount_point_of(path)?.join(".Trashes")
+        };
+        Some(TrashDestination { dir, copied: false })
+    }
+
+    #[cfg(windows)]
+    {
+        Some(TrashDestination {
+            dir: mounts::mount_point_of(path)?.join("$Recycle.Bin"),
+            copied: false,
+        })
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// A trash without room for the items that would be copied to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashShortfall {
+    /// The trash directory.
+    pub dir: PathBuf,
+
+    /// Bytes that would be copied there.
+    pub needed: u64,
+
+    /// Bytes available on the volume of the trash.
+    pub available: u64,
+}
+
+/// The trashes that lack the space to hold the `items` (paths and sizes)
+/// that would be copied to them. Items renamed into a trash on their own
+/// file system need no space and are not counted.
+#[must_use]
+pub fn trash_shortfalls<'a>(
+    items: impl IntoIterator<Item = (&'a Path, u64)>,
+) -> Vec<TrashShortfall> {
+    shortfalls(
+        items
+            .into_iter()
+            .filter_map(|(path, size)| Some((trash_destination(path)?, size))),
+        |dir| {
+            dir.ancestors()
+                .find(|ancestor| ancestor.exists())
+                .and_then(available_space)
+        },
+    )
+}
+
+/// The trashes among `destinations` whose copied items add up to more than
+/// `available` tells is free.
+fn shortfalls(
+    destinations: impl Iterator<Item = (TrashDestination, u64)>,
+    available: impl Fn(&Path) -> Option<u64>,
+) -> Vec<TrashShortfall> {
+    let mut needed: BTreeMap<PathBuf, u64> = BTreeMap::new();
+    for (destination, size) in destinations.filter(|(destination, _)| destination.copied) {
+        *needed.entry(destination.dir).or_default() += size;
+    }
+
+    needed
+        .into_iter()
+        .filter_map(|(dir, needed)| {
+            let available = available(&dir)?;
+            (needed > available).then_some(TrashShortfall {
+                dir,
+                needed,
+                available,
+            })
+        })
+        .collect()
+}
+
+/// The device of the file system holding `path`, or of its closest existing
+/// ancestor.
+#[cfg(unix)]
+fn device(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    path.ancestors()
+        .find_map(|ancestor| std::fs::metadata(ancestor).ok())
+        .map(|metadata| metadata.dev())
+}
+
+/// The user id the trash directories of other file systems are named after.
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn current_uid() -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+
+    // `/proc/self` belongs to the user running the process; elsewhere, the
+    // home directory is assumed to belong to them
+    std::fs::metadata("/proc/self")
+        .ok()
+        .or_else(|| std::fs::metadata(dirs::home_dir()?).ok())
+        .map(|metadata| metadata.uid())
+}
+
+/// Count the local Time Machine snapshots on the boot volume.
+///
+/// Local snapshots are what keeps deleted data around as purgeable space on
+/// APFS. Returns `None` if `tmutil` is unavailable or fails.
+#[cfg(target_os = "macos")]
+#[must_use]
+pub fn local_snapshot_count() -> Option<usize> {
+    let output = std::process::Command::new("tmutil")
+        .args(["listlocalsnapshots", "/"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| line.contains("com.apple"))
+            .count(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_fully_reclaimed() {
+        assert_eq!(
+            SpaceCheck::evaluate(1_000, 2_000, 1_000),
+            SpaceCheck::Reclaimed { observed: 1_000 }
+        );
+    }
+
+    #[test]
+    fn test_evaluate_not_reflected() {
+        assert_eq!(
+            SpaceCheck::evaluate(1_000, 1_100, 1_000),
+            SpaceCheck::NotReflected {
+                observed: 100,
+                missing: 900,
+            }
+        );
+    }
+
+    #[test]
+    fn test_evaluate_free_space_decreased() {
+        // Other processes may have written data in the meantime
+        assert_eq!(
+            SpaceCheck::evaluate(2_000, 1_000, 500),
+            SpaceCheck::NotReflected {
+                observed: 0,
+                missing: 500,
+            }
+        );
+    }
+
+    #[test]
+    fn test_evaluate_nothing_freed() {
+        assert_eq!(
+            SpaceCheck::evaluate(1_000, 1_000, 0),
+            SpaceCheck::Reclaimed { observed: 0 }
+        );
+    }
+
+    #[test]
+    fn test_shortfalls() {
+        let home_trash = PathBuf::from("/home/me/.local/share/Trash");
+        let destinations = [
+            (
+                TrashDestination {
+                    dir: home_trash.clone(),
+                    copied: true,
+                },
+                600,
+            ),
+            (
+                TrashDestination {
+                    dir: home_trash.clone(),
+                    copied: true,
+                },
+                600,
+            ),
+            (
+                TrashDestination {
+                    dir: PathBuf::from("/data/.Trash-1000"),
+                    copied: false,
+                },
+                5_000,
+            ),
+        ];
+
+        assert_eq!(
+            shortfalls(destinations.clone().into_iter(), |_| Some(1_000)),
+            [TrashShortfall {
+                dir: home_trash,
+                needed: 1_200,
+                available: 1_000,
+            }]
+        );
+        assert!(shortfalls(destinations.clone().into_iter(), |_| Some(2_000)).is_empty());
+        assert!(shortfalls(destinations.into_iter(), |_| None).is_empty());
+    }
+
+    #[test]
+    fn test_available_space_current_dir() {
+        assert!(available_space(Path::new(".")).is_some());
+    }
+
+    #[test]
+    fn test_available_space_nonexistent_path() {
+        assert!(available_space(Path::new("/nonexistent/path/for/sure")).is_none());
+    }
+}
@@ -0,0 +1,172 @@
+//! Duration and count parsing utilities.
+//!
+//! This module provides functions for parsing human-readable durations (like
+//! "5m" or "1h30m") and counts (like "1M" or "500K"), used by the scan
+//! limits.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// Parse a human-readable duration.
+///
+/// A duration is one or more numbers, each followed by a unit: `h` (hours),
+/// `m` (minutes) or `s` (seconds), e.g. "90s", "5m" or "1h30m". A plain
+/// number is a number of seconds.
+///
+/// # Errors
+///
+/// Returns an error if the string is empty, has an unknown unit, or
+/// overflows.
+///
+/// # Examples
+///
+/// ```
+/// # use clean_dev_dirs::utils::duration::parse_duration;
+/// # use std::time::Duration;
+/// assert_eq!(parse_duration("5m").unwrap(), Duration::from_mins(5));
+/// assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_mins(90));
+/// ```
+pub fn parse_duration(duration_str: &str) -> Result<Duration> {
+    let trimmed = duration_str.trim();
+    if trimmed.is_empty() {
+        return Err(Error::DurationParse(format!(
+            "Invalid duration: {duration_str:?}"
+        )));
+    }
+    if let Ok(secs) = trimmed.parse() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total: u64 = 0;
+    let mut rest = trimmed;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(digits);
+        let unit = tail.chars().next();
+        let multiplier = match unit.map(|c| c.to_ascii_lowercase()) {
+            Some('h') => 3600,
+            Some('m') => 60,
+            Some('s') => 1,
+            _ => {
+                return Err(Error::DurationParse(format!(
+                    "Invalid duration: {duration_str:?} (use e.g. 90s, 5m or 1h30m)"
+                )));
+            }
+        };
+        let Ok(number) = number.parse::<u64>() else {
+            return Err(Error::DurationParse(format!(
+                "Invalid duration: {duration_str:?} (use e.g. 90s, 5m or 1h30m)"
+            )));
+        };
+        total = number
+            .checked_mul(multiplier)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(|| Error::DurationParse(format!("Duration overflow: {duration_str:?}")))?;
+        rest = &tail[1..];
+    }
+
+    Ok(Duration::from_secs(total))
+}
+
+/// Format a duration the way [`parse_duration`] accepts it, e.g. "1h30m",
+/// ignoring fractions of a second.
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let parts = [(secs / 3600, "h"), (secs / 60 % 60, "m"), (secs % 60, "s")];
+    let mut formatted = String::new();
+    for (value, unit) in parts.into_iter().filter(|(value, _)| *value > 0) {
+        let _ = write!(formatted, "{value}{unit}");
+    }
+    if formatted.is_empty() {
+        "0s".to_string()
+    } else {
+        formatted
+    }
+}
+
+/// Parse a human-readable count, with an optional `K` (thousand), `M`
+/// (million) or `G` (billion) suffix, e.g. "500K" or "1M".
+///
+/// # Errors
+///
+/// Returns an error if the number is invalid or overflows.
+///
+/// # Examples
+///
+/// ```
+/// # use clean_dev_dirs::utils::duration::parse_count;
+/// assert_eq!(parse_count("1M").unwrap(), 1_000_000);
+/// assert_eq!(parse_count("2500").unwrap(), 2_500);
+/// ```
+pub fn parse_count(count_str: &str) -> Result<u64> {
+    let trimmed = count_str.trim();
+    let (number, multiplier) = match trimmed.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&trimmed[..trimmed.len() - 1], 1_000),
+        Some('M') => (&trimmed[..trimmed.len() - 1], 1_000_000),
+        Some('G') => (&trimmed[..trimmed.len() - 1], 1_000_000_000),
+        _ => (trimmed, 1),
+    };
+    let Ok(number) = number.parse::<u64>() else {
+        return Err(Error::DurationParse(format!(
+            "Invalid count: {count_str:?} (use e.g. 5000, 500K or 1M)"
+        )));
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| Error::DurationParse(format!("Count overflow: {count_str:?}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_mins(5));
+        assert_eq!(parse_duration("2H").unwrap(), Duration::from_hours(2));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_mins(90));
+        assert_eq!(parse_duration("1m30s").unwrap(), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn test_parse_duration_invalid() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("5m3").is_err());
+        assert!(parse_duration("-5m").is_err());
+    }
+
+    #[test]
+    fn test_format_duration_round_trips() {
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+        assert_eq!(format_duration(Duration::from_mins(5)), "5m");
+        assert_eq!(format_duration(Duration::from_secs(5410)), "1h30m10s");
+        for input in ["90s", "5m", "1h30m"] {
+            let duration = parse_duration(input).unwrap();
+            assert_eq!(
+                parse_duration(&format_duration(duration)).unwrap(),
+                duration
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("0").unwrap(), 0);
+        assert_eq!(parse_count("2500").unwrap(), 2_500);
+        assert_eq!(parse_count("500K").unwrap(), 500_000);
+        assert_eq!(parse_count("1M").unwrap(), 1_000_000);
+        assert_eq!(parse_count("2g").unwrap(), 2_000_000_000);
+        assert!(parse_count("").is_err());
+        assert!(parse_count("1.5M").is_err());
+        assert!(parse_count("lots").is_err());
+    }
+}
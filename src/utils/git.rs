@@ -0,0 +1,269 @@
+//! Git queries for the `--changed-since` / `--unchanged-since`,
+//! `--skip-dirty` and `--git-age` filters.
+//!
+//! In a monorepo, whether a project is still being worked on is better told
+//! by its git history than by build directory timestamps. This module asks
+//! `git` whether any tracked file below a directory differs from a given ref,
+//! or from the last commit, and when that last commit was made.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use crate::error::{Error, Result};
+
+/// Check whether any tracked file below `dir` changed since `git_ref`.
+///
+/// Both commits made since `git_ref` and uncommitted modifications count as
+/// changes; untracked files do not, so build directories missing from
+/// `.gitignore` don't make every project look changed.
+///
+/// # Errors
+///
+/// Returns an error if `git` cannot be run, `dir` is not inside a git
+/// repository, or `git_ref` is unknown to that repository.
+pub fn has_changes_since(dir: &Path, git_ref: &str) -> Result<bool> {
+    if git_ref.starts_with('-') {
+        return Err(Error::Git(format!("Invalid git ref `{git_ref}`")));
+    }
+
+    // Resolving the ref first also checks that `dir` is in a repository:
+    // outside of one, `git diff` silently falls back to `--no-index` mode.
+    let commit = git_output(
+        dir,
+        &["rev-parse", "--verify", &format!("{git_ref}^{{commit}}")],
+    )
+    .map_err(|e| {
+        Error::Git(format!(
+            "Unknown git ref `{git_ref}` in {}: {}",
+            dir.display(),
+            e.display_chain()
+        ))
+    })?;
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["diff", "--quiet", &commit, "--", "."])
+        .output()
+        .map_err(|source| Error::Spawn {
+            command: "git diff".to_string(),
+            source,
+        })?;
+
+    match output.status.code() {
+        Some(0) => Ok(false),
+        Some(1) => Ok(true),
+        _ => Err(Error::Git(format!(
+            "`git diff {git_ref}` failed in {}: {}",
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))),
+    }
+}
+
+/// Check whether `dir` has uncommitted changes.
+///
+/// Modified, staged, deleted and untracked files below `dir` count, except
+/// in the `excluded` directories (its build directories, which may be
+/// missing from `.gitignore`). A directory outside any git repository has no uncommitted changes.
+///
+/// # Errors
+///
+/// Returns an error if `git status` cannot be run or fails.
+pub fn has_uncommitted_changes(dir: &Path, excluded: &[&Path]) -> Result<bool> {
+    if !dir
+        .ancestors()
+        .any(|ancestor| ancestor.join(".git").exists())
+    {
+        return Ok(false);
+    }
+
+    let exclusions: Vec<String> = excluded
+        .iter()
+        .filter_map(|path| path.strip_prefix(dir).ok())
+        .map(|relative| format!(":(exclude){}", relative.display()))
+        .collect();
+    let mut args = vec![
+        "status",
+        "--porcelain",
+        "--untracked-files=normal",
+        "--",
+        ".",
+    ];
+    args.extend(exclusions.iter().map(String::as_str));
+
+    let status = git_output(dir, &args).map_err(|e| {
+        Error::Git(format!(
+            "`git status` failed in {}: {}",
+            dir.display(),
+            e.display_chain()
+        ))
+    })?;
+    Ok(!status.is_empty())
+}
+
+/// Return the time of the last commit touching a file below `dir`.
+///
+/// # Errors
+///
+/// Returns an error if `git` cannot be run, `dir` is not inside a git
+/// repository, or no commit touches it.
+pub fn last_commit_time(dir: &Path) -> Result<SystemTime> {
+    let timestamp = git_output(dir, &["log", "-1", "--format=%ct", "--", "."]).map_err(|e| {
+        Error::Git(format!(
+            "`git log` failed in {}: {}",
+            dir.display(),
+            e.display_chain()
+        ))
+    })?;
+    if timestamp.is_empty() {
+        return Err(Error::Git(format!("No commit touches {}", dir.display())));
+    }
+
+    let seconds = timestamp
+        .parse()
+        .map_err(|e| Error::Git(format!("Invalid commit timestamp `{timestamp}`: {e}")))?;
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// Run a git command in `dir` and return its trimmed stdout.
+fn git_output(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|source| Error::Spawn {
+            command: "git".to_string(),
+            source,
+        })?;
+
+    if !output.status.success() {
+        return Err(Error::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args([
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "-c",
+                "commit.gpgsign=false",
+            ])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    /// A repository with two projects, `a` and `b`, and a `v1` tag.
+    fn repo() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for project in ["a", "b"] {
+            fs::create_dir(root.join(project)).unwrap();
+            fs::write(root.join(project).join("lib.rs"), "").unwrap();
+        }
+
+        git(root, &["init", "--quiet"]);
+        git(root, &["add", "."]);
+        git(root, &["commit", "--quiet", "-m", "initial"]);
+        git(root, &["tag", "v1"]);
+        temp_dir
+    }
+
+    #[test]
+    fn test_unchanged_project() {
+        let temp_dir = repo();
+        assert!(!has_changes_since(&temp_dir.path().join("a"), "v1").unwrap());
+    }
+
+    #[test]
+    fn test_committed_and_uncommitted_changes() {
+        let temp_dir = repo();
+        let root = temp_dir.path();
+
+        fs::write(root.join("a/lib.rs"), "fn a() {}").unwrap();
+        git(root, &["commit", "--quiet", "-am", "change a"]);
+        fs::write(root.join("b/lib.rs"), "fn b() {}").unwrap();
+
+        assert!(has_changes_since(&root.join("a"), "v1").unwrap());
+        assert!(has_changes_since(&root.join("b"), "v1").unwrap());
+        assert!(!has_changes_since(&root.join("a"), "HEAD").unwrap());
+    }
+
+    #[test]
+    fn test_untracked_files_are_ignored() {
+        let temp_dir = repo();
+        let target = temp_dir.path().join("a/target");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("build.o"), "").unwrap();
+
+        assert!(!has_changes_since(&temp_dir.path().join("a"), "v1").unwrap());
+    }
+
+    #[test]
+    fn test_uncommitted_changes() {
+        let temp_dir = repo();
+        let root = temp_dir.path();
+        let target = root.join("a/target");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("build.o"), "").unwrap();
+
+        assert!(!has_uncommitted_changes(&root.join("a"), &[&target]).unwrap());
+        assert!(has_uncommitted_changes(&root.join("a"), &[]).unwrap());
+
+        fs::write(root.join("b/lib.rs"), "fn b() {}").unwrap();
+        assert!(has_uncommitted_changes(&root.join("b"), &[]).unwrap());
+        assert!(!has_uncommitted_changes(&root.join("a"), &[&target]).unwrap());
+
+        let outside = TempDir::new().unwrap();
+        assert!(!has_uncommitted_changes(outside.path(), &[]).unwrap());
+    }
+
+    #[test]
+    fn test_last_commit_time() {
+        let temp_dir = repo();
+        let root = temp_dir.path();
+        git(root, &["commit", "--quiet", "--allow-empty", "-m", "later"]);
+        fs::create_dir(root.join("c")).unwrap();
+
+        let time = last_commit_time(&root.join("a")).unwrap();
+        let age = SystemTime::now().duration_since(time).unwrap();
+        assert!(age < Duration::from_hours(1), "{age:?}");
+
+        // Nothing was committed below `c`, and nothing outside a repository
+        assert!(last_commit_time(&root.join("c")).is_err());
+        let outside = TempDir::new().unwrap();
+        assert!(last_commit_time(outside.path()).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        let temp_dir = repo();
+        let project = temp_dir.path().join("a");
+
+        assert!(has_changes_since(&project, "no-such-ref").is_err());
+        assert!(has_changes_since(&project, "--output=x").is_err());
+
+        let outside = TempDir::new().unwrap();
+        assert!(has_changes_since(outside.path(), "HEAD").is_err());
+    }
+}
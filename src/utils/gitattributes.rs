@@ -0,0 +1,263 @@
+//! `clean-dev-dirs=keep` attributes in `.gitattributes` files.
+//!
+//! Repositories can protect build directories in-tree, next to the
+//! attributes they already manage:
+//!
+//! ```gitattributes
+//! /vendor/target  clean-dev-dirs=keep
+//! tools/**        clean-dev-dirs=keep
+//! ```
+//!
+//! Patterns follow the gitattributes rules: a pattern without a `/` (other
+//! than a trailing one) matches at any depth, other patterns are relative to
+//! the directory of the `.gitattributes` file, and the last line setting the
+//! attribute wins, a deeper file overriding the ones above it.
+//! `-clean-dev-dirs`, `!clean-dev-dirs` or any other value take the
+//! protection back. Unlike git, a directory marked as kept also protects
+//! everything below it. Only the `.gitattributes` files of the repository
+//! holding a path are read.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::utils::cleanignore::glob_pattern;
+
+/// Name of the files holding git attributes.
+pub const GITATTRIBUTES_FILE: &str = ".gitattributes";
+
+/// The attribute protecting paths from cleaning when set to [`KEEP_VALUE`].
+pub const KEEP_ATTRIBUTE: &str = "clean-dev-dirs";
+
+/// The value of [`KEEP_ATTRIBUTE`] protecting paths from cleaning.
+pub const KEEP_VALUE: &str = "keep";
+
+/// The lines of one `.gitattributes` file that mention [`KEEP_ATTRIBUTE`].
+#[derive(Debug)]
+struct AttributesFile {
+    /// One glob per line, in file order.
+    globs: GlobSet,
+
+    /// Whether each line marks the paths as kept, rather than unmarks them.
+    keeps: Vec<bool>,
+}
+
+impl AttributesFile {
+    /// Parse the contents of a `.gitattributes` file.
+    ///
+    /// Invalid patterns are skipped, like git does.
+    fn parse(contents: &str) -> Self {
+        let mut builder = GlobSetBuilder::new();
+        let mut keeps = Vec::new();
+
+        for line in contents.lines() {
+            let Some((pattern, keep)) = parse_line(line) else {
+                continue;
+            };
+            let glob = GlobBuilder::new(&pattern)
+                .literal_separator(true)
+                .backslash_escape(true)
+                .build();
+            if let Ok(glob) = glob {
+                builder.add(glob);
+                keeps.push(keep);
+            }
+        }
+
+        Self {
+            globs: builder.build().unwrap_or_else(|_| GlobSet::empty()),
+            keeps,
+        }
+    }
+
+    /// Return whether the last line matching `relative` marks it as kept
+    /// (`Some(true)`) or not (`Some(false)`), if any matches.
+    fn matched(&self, relative: &Path) -> Option<bool> {
+        self.globs
+            .matches(relative)
+            .into_iter()
+            .max()
+            .map(|index| self.keeps[index])
+    }
+}
+
+/// Turn a line of a `.gitattributes` file into a glob and whether it marks
+/// paths as kept, or `None` if it does not mention [`KEEP_ATTRIBUTE`].
+///
+/// Comments, macro definitions (`[attr]…`), quoted patterns and negative
+/// patterns, which git forbids, are skipped.
+fn parse_line(line: &str) -> Option<(String, bool)> {
+    let line = line.trim();
+    if line.starts_with(['#', '[', '"', '!']) {
+        return None;
+    }
+
+    let mut fields = line.split_whitespace();
+    let pattern = fields.next()?;
+    // The last mention of the attribute on the line wins
+    let keep = fields.rev().find_map(|attribute| {
+        let (name, value) = attribute
+            .split_once('=')
+            .unwrap_or_else(|| (attribute.trim_start_matches(['-', '!']), ""));
+        (name == KEEP_ATTRIBUTE).then_some(value == KEEP_VALUE)
+    })?;
+    glob_pattern(pattern).map(|glob| (glob, keep))
+}
+
+/// The `.gitattributes` files of the repositories looked at, read on first
+/// use.
+#[derive(Debug, Default)]
+pub struct GitAttributes {
+    /// Parsed attributes of each directory looked at, if it has a
+    /// `.gitattributes` file mentioning [`KEEP_ATTRIBUTE`].
+    files: Mutex<HashMap<PathBuf, Option<Arc<AttributesFile>>>>,
+}
+
+impl GitAttributes {
+    /// Create an empty cache of `.gitattributes` files.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether `path`, or a directory above it, is marked
+    /// `clean-dev-dirs=keep` by the `.gitattributes` files of its
+    /// repository.
+    #[must_use]
+    pub fn is_kept(&self, path: &Path) -> bool {
+        let Some(repo) = path.ancestors().find(|dir| dir.join(".git").exists()) else {
+            return false;
+        };
+
+        path.ancestors()
+            .take_while(|candidate| *candidate != repo)
+            .any(|candidate| self.is_kept_itself(repo, candidate))
+    }
+
+    /// Check whether `path` itself is marked as kept, the deepest file with
+    /// a matching line deciding.
+    fn is_kept_itself(&self, repo: &Path, path: &Path) -> bool {
+        for dir in path.ancestors().skip(1) {
+            if let Some(file) = self.file(dir)
+                && let Ok(relative) = path.strip_prefix(dir)
+                && let Some(keep) = file.matched(relative)
+            {
+                return keep;
+            }
+            if dir == repo {
+                break;
+            }
+        }
+        false
+    }
+
+    /// Return the parsed `.gitattributes` file of `dir`, if it has one
+    /// mentioning [`KEEP_ATTRIBUTE`].
+    fn file(&self, dir: &Path) -> Option<Arc<AttributesFile>> {
+        let mut files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        files
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                fs::read_to_string(dir.join(GITATTRIBUTES_FILE))
+                    .ok()
+                    .filter(|contents| contents.contains(KEEP_ATTRIBUTE))
+                    .map(|contents| Arc::new(AttributesFile::parse(&contents)))
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("target clean-dev-dirs=keep"),
+            Some(("**/target".into(), true))
+        );
+        assert_eq!(
+            parse_line("/vendor/**\ttext -diff clean-dev-dirs=keep"),
+            Some(("vendor/**".into(), true))
+        );
+        assert_eq!(
+            parse_line("target -clean-dev-dirs"),
+            Some(("**/target".into(), false))
+        );
+        assert_eq!(
+            parse_line("target clean-dev-dirs=keep !clean-dev-dirs"),
+            Some(("**/target".into(), false))
+        );
+        assert_eq!(
+            parse_line("target clean-dev-dirs=never"),
+            Some(("**/target".into(), false))
+        );
+        assert_eq!(parse_line("*.png binary"), None);
+        assert_eq!(parse_line("# target clean-dev-dirs=keep"), None);
+        assert_eq!(parse_line("[attr]keep clean-dev-dirs=keep"), None);
+        assert_eq!(parse_line("!target clean-dev-dirs=keep"), None);
+        assert_eq!(parse_line("target clean-dev-dirs-extra=keep"), None);
+    }
+
+    #[test]
+    fn test_last_matching_line_wins() {
+        let file =
+            AttributesFile::parse("target clean-dev-dirs=keep\napps/*/target -clean-dev-dirs\n");
+
+        assert_eq!(file.matched(Path::new("target")), Some(true));
+        assert_eq!(file.matched(Path::new("lib/target")), Some(true));
+        assert_eq!(file.matched(Path::new("apps/web/target")), Some(false));
+        assert_eq!(file.matched(Path::new("node_modules")), None);
+    }
+
+    #[test]
+    fn test_is_kept() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(repo.join("tools/gen/target")).unwrap();
+        fs::create_dir_all(repo.join("app/target")).unwrap();
+        fs::write(
+            repo.join(GITATTRIBUTES_FILE),
+            "/tools clean-dev-dirs=keep\n*.rs diff=rust\n",
+        )
+        .unwrap();
+        fs::write(
+            repo.join("tools/gen").join(GITATTRIBUTES_FILE),
+            "target -clean-dev-dirs\n",
+        )
+        .unwrap();
+
+        let attributes = GitAttributes::new();
+        assert!(!attributes.is_kept(&repo.join("app/target")));
+        // A kept directory protects everything below it
+        assert!(attributes.is_kept(&repo.join("tools/gen/target")));
+        assert!(attributes.is_kept(&repo.join("tools")));
+
+        // Files above the repository are not read
+        fs::write(
+            temp.path().join(GITATTRIBUTES_FILE),
+            "target clean-dev-dirs=keep\n",
+        )
+        .unwrap();
+        assert!(!GitAttributes::new().is_kept(&repo.join("app/target")));
+    }
+
+    #[test]
+    fn test_paths_outside_repositories_are_not_kept() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("app/target")).unwrap();
+        fs::write(
+            temp.path().join("app").join(GITATTRIBUTES_FILE),
+            "target clean-dev-dirs=keep\n",
+        )
+        .unwrap();
+
+        assert!(!GitAttributes::new().is_kept(&temp.path().join("app/target")));
+    }
+}
@@ -0,0 +1,157 @@
+//! `.gitignore` and `.ignore` files.
+//!
+//! Directories ignored by the `.gitignore` or `.ignore` file of a scanned
+//! directory are not searched for projects, so that checked-out third-party
+//! code, vendored trees or generated fixtures listed there do not show up as
+//! projects of their own. Patterns follow gitignore rules (see the `ignore`
+//! crate): the deepest file with a matching pattern decides, `.ignore` files
+//! take precedence over `.gitignore` files in the same directory, and
+//! everything below an ignored directory is ignored too.
+//!
+//! Only the files in the scanned directory and below it are read, whether or
+//! not they are inside a git repository; global excludes and
+//! `.git/info/exclude` are not.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::vfs::FsHandle;
+
+/// Names of the files listing paths to ignore, the one taking precedence
+/// last.
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// The `.gitignore` and `.ignore` files of a scanned tree, read on first use.
+#[derive(Debug, Default)]
+pub struct GitIgnore {
+    /// Patterns of the ignore files of each directory looked at, if it has
+    /// any.
+    files: Mutex<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+
+    /// Whether each directory looked at is ignored.
+    ignored: Mutex<HashMap<PathBuf, bool>>,
+
+    /// File system the files are read from.
+    fs: FsHandle,
+}
+
+impl GitIgnore {
+    /// Create an empty cache of the ignore files of `fs`.
+    #[must_use]
+    pub fn with_fs(fs: FsHandle) -> Self {
+        Self {
+            fs,
+            ..Self::default()
+        }
+    }
+
+    /// Check whether the directory `dir`, below `root`, is ignored by the
+    /// ignore files of `root` and the directories in between.
+    #[must_use]
+    pub fn is_ignored(&self, root: &Path, dir: &Path) -> bool {
+        if dir == root || !dir.starts_with(root) {
+            return false;
+        }
+        let cached = self
+            .ignored
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(dir)
+            .copied();
+        if let Some(ignored) = cached {
+            return ignored;
+        }
+
+        // A directory is ignored if it, or any directory above it, is ignored
+        let ignored = dir
+            .parent()
+            .is_some_and(|parent| self.is_ignored(root, parent))
+            || self.is_ignored_itself(root, dir);
+        self.ignored
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(dir.to_path_buf(), ignored);
+        ignored
+    }
+
+    /// Check whether `dir` itself is ignored, the deepest ignore file with a
+    /// matching pattern deciding.
+    fn is_ignored_itself(&self, root: &Path, dir: &Path) -> bool {
+        for ancestor in dir.ancestors().skip(1) {
+            if let Some(patterns) = self.patterns(ancestor) {
+                let matched = patterns.matched(dir, true);
+                if !matched.is_none() {
+                    return matched.is_ignore();
+                }
+            }
+            if ancestor == root {
+                break;
+            }
+        }
+        false
+    }
+
+    /// Return the patterns of the ignore files of `dir`, if it has any.
+    fn patterns(&self, dir: &Path) -> Option<Arc<Gitignore>> {
+        let mut files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        files
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| self.read(dir).map(Arc::new))
+            .clone()
+    }
+
+    /// Read the ignore files of `dir`, or `None` if it has none.
+    ///
+    /// Invalid patterns are skipped, like git does.
+    fn read(&self, dir: &Path) -> Option<Gitignore> {
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut found = false;
+        for name in IGNORE_FILES {
+            let path = dir.join(name);
+            let Ok(contents) = self.fs.read_to_string(&path) else {
+                continue;
+            };
+            found = true;
+            for line in contents.lines() {
+                let _ = builder.add_line(Some(path.clone()), line);
+            }
+        }
+        found.then(|| builder.build().ok()).flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFs;
+
+    #[test]
+    fn test_ignored_directories() {
+        let root = Path::new("/root");
+        let fs = MemoryFs::new()
+            .with_dir("/root/app")
+            .with_dir("/root/third_party/lib")
+            .with_dir("/root/fixtures/keep")
+            .with_file(
+                "/root/.gitignore",
+                "third_party/\nfixtures/*\n!fixtures/keep\n",
+            )
+            .with_dir("/root/web/generated")
+            .with_file("/root/web/.gitignore", "generated\n")
+            .with_file("/root/web/.ignore", "!generated\n");
+
+        let gitignore = GitIgnore::with_fs(FsHandle::new(Arc::new(fs)));
+        assert!(!gitignore.is_ignored(root, root));
+        assert!(!gitignore.is_ignored(root, &root.join("app")));
+        // Everything below an ignored directory is ignored
+        assert!(gitignore.is_ignored(root, &root.join("third_party")));
+        assert!(gitignore.is_ignored(root, &root.join("third_party/lib")));
+        assert!(gitignore.is_ignored(root, &root.join("fixtures/old")));
+        assert!(!gitignore.is_ignored(root, &root.join("fixtures/keep")));
+        // `.ignore` takes precedence over `.gitignore`
+        assert!(!gitignore.is_ignored(root, &root.join("web/generated")));
+    }
+}
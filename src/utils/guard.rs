@@ -0,0 +1,377 @@
+//! Last-resort protection against deleting the wrong directory.
+//!
+//! Whatever the scanner, the filters or a library caller decided, the
+//! [`Cleaner`](crate::cleaner::Cleaner) checks every path with a
+//! [`DeletionGuard`] right before removing it. Paths are checked once
+//! resolved, so a symlink to a home directory is caught as well.
+//!
+//! The build directories of a project are also checked against what the
+//! scanner saw ([`DeletionGuard::check_artifact`]), as a defense against
+//! detector bugs and against projects changed since they were scanned.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::project::{Project, ProjectType};
+use crate::vfs::{EntryKind, FileSystem};
+
+/// Entries of which at least one is found in every cargo `target/`
+/// directory that holds anything.
+const CARGO_TARGET_ENTRIES: &[&str] = &["CACHEDIR.TAG", ".rustc_info.json", "debug", "release"];
+
+/// Refuses to delete paths that are too shallow to be build artifacts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeletionGuard {
+    /// Minimum number of components (not counting the root or a drive
+    /// prefix) of a deleted path, e.g. 3 for `/home/me/target`.
+    pub min_depth: usize,
+
+    /// The user's home directory, which is never deleted, nor are its
+    /// ancestors.
+    pub home: Option<PathBuf>,
+}
+
+impl Default for DeletionGuard {
+    /// A minimum depth of 3, protecting the current user's home directory.
+    fn default() -> Self {
+        Self::with_min_depth(3)
+    }
+}
+
+impl DeletionGuard {
+    /// A guard with the given minimum depth, protecting the current user's
+    /// home directory.
+    #[must_use]
+    pub fn with_min_depth(min_depth: usize) -> Self {
+        Self {
+            min_depth,
+            home: dirs::home_dir(),
+        }
+    }
+
+    /// Check that `path` may be deleted from `fs`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path`, once resolved, is a file system root,
+    /// the home directory or one of its ancestors, or has fewer than
+    /// [`min_depth`](Self::min_depth) components.
+    pub fn check(&self, fs: &dyn FileSystem, path: &Path) -> Result<()> {
+        // A path that cannot be resolved (e.g. vanished) is checked as is
+        let resolved = normalize(
+            &fs.canonicalize(path)
+                .or_else(|_| std::path::absolute(path))
+                .map_err(Error::file("resolve", path))?,
+        );
+
+        let home = self
+            .home
+            .as_deref()
+            .map(|home| fs.canonicalize(home).unwrap_or_else(|_| home.to_path_buf()));
+        if resolved.parent().is_none() || home.is_some_and(|home| home.starts_with(&resolved)) {
+            return Err(Error::Refused {
+                path: resolved,
+                reason: "it is a root or home directory".to_string(),
+            });
+        }
+
+        let depth = depth(&resolved);
+        if depth < self.min_depth {
+            return Err(Error::Refused {
+                path: resolved,
+                reason: format!(
+                    "it is {depth} levels deep, less than the minimum of {}",
+                    self.min_depth
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that `artifact`, a build directory of `project`, may be
+    /// deleted from `fs`: besides [`DeletionGuard::check`], it must be
+    /// inside the project root, the file marking the project (e.g.
+    /// `Cargo.toml`) must still exist, and a Rust `target/` must hold
+    /// something cargo creates (`CACHEDIR.TAG`, `debug/`, ...) or nothing.
+    ///
+    /// A build directory that is a symbolic link is checked as the link,
+    /// which is all that is deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `artifact` fails any of these checks.
+    pub fn check_artifact(
+        &self,
+        fs: &dyn FileSystem,
+        project: &Project,
+        artifact: &Path,
+    ) -> Result<()> {
+        self.check(fs, artifact)?;
+
+        let refused = |reason: String| Error::Refused {
+            path: artifact.to_path_buf(),
+            reason,
+        };
+
+        let root = resolve(fs, &project.root_path)?;
+        let resolved = match (artifact.parent(), artifact.file_name()) {
+            (Some(parent), Some(name)) => resolve(fs, parent)?.join(name),
+            _ => resolve(fs, artifact)?,
+        };
+        if resolved == root || !resolved.starts_with(&root) {
+            return Err(refused(format!(
+                "it is not inside its project root {}",
+                project.root_path.display()
+            )));
+        }
+
+        let markers = markers(&project.kind);
+        if !has_marker(fs, &project.root_path, markers) {
+            return Err(refused(format!(
+                "its project has no {} anymore",
+                markers.join(" or ")
+            )));
+        }
+
+        let is_cargo_target = project.kind == ProjectType::Rust
+            && artifact.file_name().is_some_and(|name| name == "target");
+        if is_cargo_target
+            && fs
+                .entry_kind(artifact)
+                .is_ok_and(|kind| kind == EntryKind::Dir)
+            && !looks_like_cargo_target(fs, artifact)
+        {
+            return Err(refused(format!(
+                "it does not look like a cargo target directory (no {})",
+                CARGO_TARGET_ENTRIES.join(", ")
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// `path` made absolute and normalized, with symbolic links resolved if it
+/// exists.
+fn resolve(fs: &dyn FileSystem, path: &Path) -> Result<PathBuf> {
+    fs.canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .map(|path| normalize(&path))
+        .map_err(Error::file("resolve", path))
+}
+
+/// Files of which one marks a project of type `kind` in its root. Entries
+/// starting with `*.` match any file with that extension.
+const fn markers(kind: &ProjectType) -> &'static [&'static str] {
+    match kind {
+        ProjectType::Rust => &["Cargo.toml"],
+        ProjectType::Node => &["package.json"],
+        ProjectType::Python => &[
+            "requirements.txt",
+            "setup.py",
+            "pyproject.toml",
+            "setup.cfg",
+            "Pipfile",
+            "pipenv.lock",
+            "poetry.lock",
+        ],
+        ProjectType::Go => &["go.mod"],
+        ProjectType::Java => &["pom.xml", "build.gradle", "build.gradle.kts"],
+        ProjectType::Cpp => &["CMakeLists.txt", "Makefile"],
+        ProjectType::Swift => &["Package.swift"],
+        ProjectType::DotNet => &["*.csproj"],
+        ProjectType::OCaml => &["dune-project"],
+        ProjectType::Unity => &["ProjectSettings/ProjectVersion.txt"],
+        ProjectType::Unreal => &["*.uproject"],
+    }
+}
+
+/// Check whether one of `markers` exists in `root`.
+fn has_marker(fs: &dyn FileSystem, root: &Path, markers: &[&str]) -> bool {
+    markers
+        .iter()
+        .any(|marker| match marker.strip_prefix("*.") {
+            Some(extension) => fs.read_dir(root).is_ok_and(|entries| {
+                entries.iter().any(|entry| {
+                    entry.extension().is_some_and(|ext| ext == extension) && fs.is_file(entry)
+                })
+            }),
+            None => fs.exists(&root.join(marker)),
+        })
+}
+
+/// Check whether the directory `target` is empty or holds one of the
+/// [`CARGO_TARGET_ENTRIES`].
+fn looks_like_cargo_target(fs: &dyn FileSystem, target: &Path) -> bool {
+    fs.read_dir(target).is_ok_and(|entries| entries.is_empty())
+        || CARGO_TARGET_ENTRIES
+            .iter()
+            .any(|entry| fs.exists(&target.join(entry)))
+}
+
+/// Remove the `.` and `..` components of an absolute `path`.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Number of named components of `path`, e.g. 3 for `/home/me/target`.
+fn depth(path: &Path) -> usize {
+    path.components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::BuildArtifacts;
+    use crate::vfs::{MemoryFs, RealFs};
+
+    fn guard(min_depth: usize, home: &str) -> DeletionGuard {
+        DeletionGuard {
+            min_depth,
+            home: Some(PathBuf::from(home)),
+        }
+    }
+
+    #[test]
+    fn test_depth() {
+        assert_eq!(depth(Path::new("/")), 0);
+        assert_eq!(depth(Path::new("/home/me/target")), 3);
+        assert_eq!(depth(Path::new("/home/me/")), 2);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize(Path::new("/work/app/../target/./debug")),
+            Path::new("/work/target/debug")
+        );
+        assert_eq!(normalize(Path::new("/../..")), Path::new("/"));
+    }
+
+    #[test]
+    fn test_check_min_depth() {
+        let fs = MemoryFs::new();
+        let guard = guard(3, "/home/me");
+
+        assert!(guard.check(&fs, Path::new("/home/me/target")).is_ok());
+        assert!(guard.check(&fs, Path::new("/work/app/target")).is_ok());
+
+        let error = guard.check(&fs, Path::new("/work/target")).unwrap_err();
+        assert!(error.to_string().contains("2 levels deep"), "{error}");
+        assert!(guard.check(&fs, Path::new("/work/app/../target")).is_err());
+    }
+
+    #[test]
+    fn test_check_protects_root_and_home() {
+        let fs = MemoryFs::new();
+        let guard = guard(0, "/home/me");
+
+        assert!(guard.check(&fs, Path::new("/")).is_err());
+        assert!(guard.check(&fs, Path::new("/home/me")).is_err());
+        assert!(guard.check(&fs, Path::new("/home")).is_err());
+        assert!(guard.check(&fs, Path::new("/home/me/target")).is_ok());
+        assert!(guard.check(&fs, Path::new("/tmp")).is_ok());
+    }
+
+    #[test]
+    fn test_check_artifact() {
+        let project = |kind, root: &str, artifact: &str| {
+            Project::new(
+                kind,
+                PathBuf::from(root),
+                vec![BuildArtifacts {
+                    path: PathBuf::from(artifact),
+                    size: 0,
+                }],
+                None,
+            )
+        };
+        let fs = MemoryFs::new()
+            .with_file("/work/app/Cargo.toml", "[package]\nname = \"app\"")
+            .with_file("/work/app/target/CACHEDIR.TAG", "")
+            .with_file("/work/app/src/main.rs", "fn main() {}")
+            .with_dir("/work/gone/node_modules")
+            .with_file("/work/game/Game.uproject", "{}")
+            .with_dir("/work/game/Intermediate");
+        let guard = guard(3, "/home/me");
+        let check = |kind, root, artifact: &str| {
+            guard.check_artifact(&fs, &project(kind, root, artifact), Path::new(artifact))
+        };
+
+        assert!(check(ProjectType::Rust, "/work/app", "/work/app/target").is_ok());
+        assert!(check(ProjectType::Unreal, "/work/game", "/work/game/Intermediate").is_ok());
+
+        let refusals = [
+            (
+                ProjectType::Rust,
+                "/work/app",
+                "/work/other/target",
+                "not inside",
+            ),
+            (
+                ProjectType::Rust,
+                "/work/app",
+                "/work/app/../lib/target",
+                "not inside",
+            ),
+            (
+                ProjectType::Node,
+                "/work/gone",
+                "/work/gone/node_modules",
+                "no package.json",
+            ),
+        ];
+        for (kind, root, artifact, reason) in refusals {
+            let error = check(kind, root, artifact).unwrap_err();
+            assert!(error.to_string().contains(reason), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_check_artifact_of_a_rust_project_without_cargo_files() {
+        let fs = MemoryFs::new()
+            .with_file("/work/app/Cargo.toml", "[package]\nname = \"app\"")
+            .with_file("/work/app/target/notes.txt", "mine");
+        let project = Project::new(
+            ProjectType::Rust,
+            PathBuf::from("/work/app"),
+            Vec::new(),
+            None,
+        );
+
+        let error = guard(3, "/home/me")
+            .check_artifact(&fs, &project, Path::new("/work/app/target"))
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("cargo target directory"),
+            "{error}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_resolves_symlinks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let home = temp_dir.path().join("home/me");
+        std::fs::create_dir_all(&home).unwrap();
+        let link = home.join("project/target");
+        std::fs::create_dir_all(link.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(&home, &link).unwrap();
+
+        let guard = guard(0, home.to_str().unwrap());
+
+        assert!(guard.check(&RealFs, &link).is_err());
+        assert!(guard.check(&RealFs, &home.join("project")).is_ok());
+    }
+}
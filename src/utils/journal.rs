@@ -0,0 +1,137 @@
+//! Structured entries in the systemd journal.
+//!
+//! Entries are sent with journald's native protocol: a datagram of
+//! `FIELD=value` lines on its socket, values containing a newline being
+//! length-prefixed instead. Unlike lines written to stdout, the fields of
+//! an entry can be queried, e.g. `journalctl CLEAN_DEV_DIRS_FAILED=0`.
+
+use std::io;
+use std::path::Path;
+
+/// Socket journald receives native entries on.
+pub const SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Syslog priority of an entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Priority {
+    /// Something went wrong, but not fatally.
+    Warning = 4,
+
+    /// Normal operation.
+    Info = 6,
+}
+
+/// Whether a journal is running on this system.
+#[must_use]
+pub fn is_available() -> bool {
+    cfg!(target_os = "linux") && Path::new(SOCKET).exists()
+}
+
+/// Whether the standard output is connected to the journal, as for
+/// services started by systemd.
+#[must_use]
+pub fn stdout_is_journal() -> bool {
+    std::env::var_os("JOURNAL_STREAM").is_some()
+}
+
+/// Send an entry made of `message` and `fields` to the journal, tagged
+/// with `identifier` as its syslog identifier.
+///
+/// Field names must consist of uppercase letters, digits and underscores.
+///
+/// # Errors
+///
+/// Returns an error if the journal's socket cannot be written to, or
+/// [`io::ErrorKind::Unsupported`] on platforms without a journal.
+pub fn send(
+    identifier: &str,
+    priority: Priority,
+    message: &str,
+    fields: &[(&str, String)],
+) -> io::Result<()> {
+    let entry = encode(identifier, priority, message, fields);
+    send_to(Path::new(SOCKET), &entry)
+}
+
+#[cfg(target_os = "linux")]
+fn send_to(socket: &Path, entry: &[u8]) -> io::Result<()> {
+    let datagram = std::os::unix::net::UnixDatagram::unbound()?;
+    datagram.send_to(entry, socket).map(|_| ())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn send_to(_socket: &Path, _entry: &[u8]) -> io::Result<()> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+/// Encode an entry in the native protocol.
+fn encode(
+    identifier: &str,
+    priority: Priority,
+    message: &str,
+    fields: &[(&str, String)],
+) -> Vec<u8> {
+    let mut entry = Vec::new();
+    let standard = [
+        ("MESSAGE", message.to_string()),
+        ("PRIORITY", (priority as u8).to_string()),
+        ("SYSLOG_IDENTIFIER", identifier.to_string()),
+    ];
+    for (name, value) in standard.iter().chain(fields) {
+        entry.extend_from_slice(name.as_bytes());
+        if value.contains('\n') {
+            entry.push(b'\n');
+            entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            entry.push(b'=');
+        }
+        entry.extend_from_slice(value.as_bytes());
+        entry.push(b'\n');
+    }
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let entry = encode(
+            "clean-dev-dirs",
+            Priority::Info,
+            "cleaned 2 projects",
+            &[("CLEAN_DEV_DIRS_FAILED", "0".to_string())],
+        );
+        assert_eq!(
+            String::from_utf8(entry).unwrap(),
+            "MESSAGE=cleaned 2 projects\n\
+             PRIORITY=6\n\
+             SYSLOG_IDENTIFIER=clean-dev-dirs\n\
+             CLEAN_DEV_DIRS_FAILED=0\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_multiline_value() {
+        let entry = encode("app", Priority::Warning, "a\nb", &[]);
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(b"a\nb\nPRIORITY=4\nSYSLOG_IDENTIFIER=app\n");
+        assert_eq!(entry, expected);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_send_to_socket() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let socket = temp_dir.path().join("journal.socket");
+        let receiver = std::os::unix::net::UnixDatagram::bind(&socket).unwrap();
+
+        send_to(&socket, b"MESSAGE=hello\n").unwrap();
+
+        let mut buffer = [0; 64];
+        let len = receiver.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..len], b"MESSAGE=hello\n");
+    }
+}
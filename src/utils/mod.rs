@@ -1,8 +1,35 @@
 //! Utility functions and helpers.
 //!
 //! This module contains utility functions used throughout the application,
-//! such as size parsing and formatting helpers.
+//! such as size, duration and count parsing, formatting helpers, compressed size estimation, disk
+//! space inspection, git history queries, detection of running package
+//! managers and open files, network file systems, retries for transient deletion failures, diagnosis of antivirus
+//! interference, protection against deleting shallow paths, handling of
+//! unusual paths, path globs, `.cleanignore`, `.gitignore` and `.ignore`
+//! files, `clean-dev-dirs=keep` git attributes, systemd journal entries,
+//! paging, progress bars, and terminal width aware layout.
 
+pub mod cleanignore;
+pub mod compression;
+pub mod defender;
+pub mod disk;
+pub mod duration;
+pub mod git;
+pub mod gitattributes;
+pub mod gitignore;
+pub mod guard;
+pub mod journal;
+pub mod mounts;
+#[cfg(feature = "cli")]
+pub mod pager;
+pub mod path_filter;
+pub mod paths;
+pub mod processes;
+#[cfg(feature = "cli")]
+pub mod progress;
+pub mod retry;
 pub mod size;
+#[cfg(feature = "cli")]
+pub mod terminal;
 
-pub use size::parse_size;
+pub use size::{format_size, parse_size};
@@ -0,0 +1,330 @@
+//! Detection of network file systems and other mount points.
+//!
+//! Walking a mounted share is slow, and the build directories found on it
+//! may belong to someone else, so scans do not descend into NFS, SMB and
+//! FUSE mounts unless `--allow-network-fs` is given. The mounts are read
+//! from `/proc/self/mounts` on Linux and from the output of `mount` on
+//! macOS and the BSDs. Network drives are not detected on Windows.
+//!
+//! Build directories that are mount points themselves, such as a docker
+//! volume mounted on `node_modules/`, are detected too
+//! ([`is_mount_point`]), so that cleaning never deletes what is mounted
+//! there.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// File system types served over the network. FUSE mounts (`fuse`,
+/// `fuse.sshfs`, `macfuse`, ...) are matched by prefix, see
+/// [`is_network_fs_type`].
+const NETWORK_FS_TYPES: &[&str] = &[
+    "9p",
+    "acfs",
+    "afpfs",
+    "afs",
+    "ceph",
+    "cifs",
+    "davfs",
+    "glusterfs",
+    "gpfs",
+    "lustre",
+    "ncpfs",
+    "nfs",
+    "nfs4",
+    "smb3",
+    "smbfs",
+    "sshfs",
+    "webdav",
+];
+
+/// Check whether `fs_type`, as listed in the mount table, is a network or
+/// FUSE file system.
+///
+/// `fuseblk`, used by FUSE drivers of local disks such as `ntfs-3g`, is not.
+///
+/// # Examples
+///
+/// ```
+/// # use clean_dev_dirs::utils::mounts::is_network_fs_type;
+/// assert!(is_network_fs_type("nfs4"));
+/// assert!(is_network_fs_type("fuse.sshfs"));
+/// assert!(!is_network_fs_type("fuseblk"));
+/// assert!(!is_network_fs_type("ext4"));
+/// ```
+#[must_use]
+pub fn is_network_fs_type(fs_type: &str) -> bool {
+    NETWORK_FS_TYPES.contains(&fs_type)
+        || fs_type == "fuse"
+        || fs_type.starts_with("fuse.")
+        || fs_type.starts_with("fusefs")
+        || fs_type.ends_with("fuse")
+}
+
+/// Mount points of the network file systems mounted on the system.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NetworkMounts {
+    mount_points: HashSet<PathBuf>,
+}
+
+impl NetworkMounts {
+    /// Read the network file systems currently mounted. None are found if
+    /// the mount table cannot be read.
+    #[must_use]
+    pub fn detect() -> Self {
+        #[cfg(target_os = "linux")]
+        {
+            std::fs::read_to_string("/proc/self/mounts")
+                .map(|table| Self::from_proc_mounts(&table))
+                .unwrap_or_default()
+        }
+
+        #[cfg(all(unix, not(target_os = "linux")))]
+        {
+            std::process::Command::new("mount")
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| Self::from_mount_output(&String::from_utf8_lossy(&output.stdout)))
+                .unwrap_or_default()
+        }
+
+        #[cfg(not(unix))]
+        {
+            Self::default()
+        }
+    }
+
+    /// Parse a Linux mount table, as in `/proc/self/mounts`: one mount per
+    /// line, as `device mount-point type options dump pass`, with spaces
+    /// and other special characters of the mount point escaped in octal.
+    #[must_use]
+    pub fn from_proc_mounts(table: &str) -> Self {
+        let mount_points = proc_mounts(table)
+            .filter(|(_, fs_type)| is_network_fs_type(fs_type))
+            .map(|(mount_point, _)| mount_point)
+            .collect();
+        Self { mount_points }
+    }
+
+    /// Parse the output of `mount` on macOS and the BSDs: one mount per
+    /// line, as `device on mount-point (type, options...)`.
+    #[must_use]
+    pub fn from_mount_output(output: &str) -> Self {
+        let mount_points = output
+            .lines()
+            .filter_map(|line| {
+                let (_, rest) = line.split_once(" on ")?;
+                let (mount_point, details) = rest.rsplit_once(" (")?;
+                let fs_type = details.split([',', ')']).next()?.trim();
+                is_network_fs_type(fs_type).then(|| PathBuf::from(mount_point))
+            })
+            .collect();
+        Self { mount_points }
+    }
+
+    /// Check whether `path` is the mount point of a network file system.
+    /// Relative paths are resolved against the current directory.
+    #[must_use]
+    pub fn is_mount_point(&self, path: &Path) -> bool {
+        !self.mount_points.is_empty()
+            && std::path::absolute(path).is_ok_and(|path| self.mount_points.contains(&path))
+    }
+
+    /// Whether no network file system is mounted.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.mount_points.is_empty()
+    }
+}
+
+/// Check whether the directory at `path` is a mount point: the root of a
+/// file system mounted below its parent directory or, on Linux, a bind
+/// mount. Symbolic links are not.
+#[must_use]
+pub fn is_mount_point(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let Ok(metadata) = std::fs::symlink_metadata(path) else {
+            return false;
+        };
+        if !metadata.is_dir() {
+            return false;
+        }
+        let parent = std::path::absolute(path)
+            .ok()
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .and_then(|parent| std::fs::metadata(parent).ok());
+        if parent.is_some_and(|parent| parent.dev() != metadata.dev()) {
+            return true;
+        }
+    }
+
+    // Bind mounts of a directory of the same file system keep its device
+    #[cfg(target_os = "linux")]
+    {
+        let Ok(path) = std::fs::canonicalize(path) else {
+            return false;
+        };
+        if let Ok(table) = std::fs::read_to_string("/proc/self/mounts") {
+            return proc_mounts(&table).any(|(mount_point, _)| mount_point == path);
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+    false
+}
+
+/// The mount point of the file system holding `path`: the longest mount
+/// point of the mount table containing it on Linux, the topmost ancestor on
+/// the same device on other Unix systems, and the drive on Windows. Paths
+/// that do not exist yet are looked up through their closest existing
+/// ancestor.
+#[must_use]
+pub fn mount_point_of(path: &Path) -> Option<PathBuf> {
+    let path = std::path::absolute(path).ok()?;
+    let existing = path.ancestors().find(|ancestor| ancestor.exists())?;
+    let existing = std::fs::canonicalize(existing).unwrap_or_else(|_| existing.to_path_buf());
+
+    #[cfg(target_os = "linux")]
+    {
+        let table = std::fs::read_to_string("/proc/self/mounts").ok()?;
+        proc_mounts(&table)
+            .map(|(mount_point, _)| mount_point)
+            .filter(|mount_point| existing.starts_with(mount_point))
+            .max_by_key(|mount_point| mount_point.components().count())
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let device = std::fs::metadata(&existing).ok()?.dev();
+        existing
+            .ancestors()
+            .take_while(|ancestor| {
+                std::fs::metadata(ancestor).is_ok_and(|metadata| metadata.dev() == device)
+            })
+            .last()
+            .map(Path::to_path_buf)
+    }
+
+    #[cfg(windows)]
+    {
+        existing.ancestors().last().map(Path::to_path_buf)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = existing;
+        None
+    }
+}
+
+/// Mount points and file system types of a Linux mount table.
+fn proc_mounts(table: &str) -> impl Iterator<Item = (PathBuf, &str)> {
+    table.lines().filter_map(|line| {
+        let mut fields = line.split_whitespace().skip(1);
+        let mount_point = fields.next()?;
+        let fs_type = fields.next()?;
+        Some((PathBuf::from(unescape(mount_point)), fs_type))
+    })
+}
+
+/// Decode the octal escapes (`\040` for a space) of a mount point in a
+/// Linux mount table.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes.get(i + 1..i + 4).filter(|digits| {
+            bytes[i] == b'\\' && digits.iter().all(|digit| (b'0'..=b'7').contains(digit))
+        });
+        if let Some(digits) = escape {
+            let value = digits
+                .iter()
+                .fold(0u32, |value, digit| value * 8 + u32::from(digit - b'0'));
+            decoded.push(u8::try_from(value).unwrap_or(b'?'));
+            i += 4;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_proc_mounts() {
+        let table = "\
+/dev/nvme0n1p2 / ext4 rw,relatime 0 0
+server:/export/home /mnt/home nfs4 rw,vers=4.2 0 0
+//nas/shared\\040files /mnt/shared\\040files cifs rw 0 0
+sshfs#me@host: /home/me/remote fuse.sshfs rw 0 0
+/dev/sdb1 /media/windows fuseblk rw 0 0
+tmpfs /tmp tmpfs rw 0 0
+";
+        let mounts = NetworkMounts::from_proc_mounts(table);
+
+        assert!(mounts.is_mount_point(Path::new("/mnt/home")));
+        assert!(mounts.is_mount_point(Path::new("/mnt/shared files")));
+        assert!(mounts.is_mount_point(Path::new("/home/me/remote")));
+        assert!(!mounts.is_mount_point(Path::new("/mnt/home/project")));
+        assert!(!mounts.is_mount_point(Path::new("/media/windows")));
+        assert!(!mounts.is_mount_point(Path::new("/tmp")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_is_mount_point() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("node_modules");
+        std::fs::create_dir(&dir).unwrap();
+
+        assert!(is_mount_point(Path::new("/proc")));
+        assert!(!is_mount_point(&dir));
+        assert!(!is_mount_point(&tmp.path().join("missing")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_mount_point_of() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mount_point = mount_point_of(tmp.path()).unwrap();
+
+        assert!(
+            std::fs::canonicalize(tmp.path())
+                .unwrap()
+                .starts_with(&mount_point)
+        );
+        assert_eq!(
+            mount_point_of(&tmp.path().join("missing").join("node_modules")),
+            Some(mount_point)
+        );
+        assert_eq!(
+            mount_point_of(Path::new("/proc/self")),
+            Some(PathBuf::from("/proc"))
+        );
+    }
+
+    #[test]
+    fn test_from_mount_output() {
+        let output = "\
+/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)
+//me@nas._smb._tcp.local/Projects on /Volumes/Projects (smbfs, nodev, nosuid, mounted by me)
+nas:/export on /Volumes/nfs share (nfs, asynchronous)
+";
+        let mounts = NetworkMounts::from_mount_output(output);
+
+        assert!(mounts.is_mount_point(Path::new("/Volumes/Projects")));
+        assert!(mounts.is_mount_point(Path::new("/Volumes/nfs share")));
+        assert!(!mounts.is_mount_point(Path::new("/")));
+    }
+}
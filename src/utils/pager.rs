@@ -0,0 +1,129 @@
+//! Paging of long text listings.
+//!
+//! Listings that grow with the number of projects (skipped projects,
+//! compression estimates, rebuild commands, file lists) can run to thousands
+//! of lines and bury the summary printed around them. When stdout is a
+//! terminal and a listing is taller than it, the listing is shown through a
+//! pager instead: `$PAGER`, or `less` by default. Like git, `LESS` defaults
+//! to `FRX`, so that `less` exits right away on short output, keeps colors,
+//! and leaves the listing on screen once closed.
+
+use std::env;
+use std::io::{self, ErrorKind, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use console::Term;
+
+/// Pager used when `$PAGER` is not set.
+const DEFAULT_PAGER: &str = "less";
+
+/// `LESS` options set for the pager when the variable is not set.
+const DEFAULT_LESS: &str = "FRX";
+
+/// Whether paging is disabled (`--no-pager`).
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Disable paging for the rest of the process, if `disabled`.
+pub fn disable(disabled: bool) {
+    if disabled {
+        DISABLED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Print `lines`, through a pager if they do not fit in the terminal.
+///
+/// The lines are printed directly when paging is disabled, when stdout is
+/// not a terminal, or when the pager cannot be started.
+pub fn page(lines: &[String]) {
+    let term = Term::stdout();
+    let rows = term.size_checked().map(|(rows, _)| usize::from(rows));
+
+    if !DISABLED.load(Ordering::Relaxed)
+        && term.is_term()
+        && needs_paging(lines.len(), rows)
+        && let Some(command) = pager_command(env::var("PAGER").ok().as_deref())
+        && run_pager(&command, lines).is_ok()
+    {
+        return;
+    }
+
+    for line in lines {
+        println!("{line}");
+    }
+}
+
+/// Check whether `count` lines need paging on a terminal of `rows` rows.
+///
+/// A line is kept for the prompt or summary printed afterwards.
+const fn needs_paging(count: usize, rows: Option<usize>) -> bool {
+    match rows {
+        Some(rows) => count >= rows,
+        None => false,
+    }
+}
+
+/// Split the pager command from the value of `$PAGER` into a program and
+/// its arguments.
+///
+/// Returns `None` when paging was turned off with an empty `$PAGER` or
+/// `PAGER=cat`.
+fn pager_command(pager: Option<&str>) -> Option<Vec<String>> {
+    let command: Vec<String> = pager
+        .unwrap_or(DEFAULT_PAGER)
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+
+    match command.first().map(String::as_str) {
+        None | Some("cat") => None,
+        Some(_) => Some(command),
+    }
+}
+
+/// Write `lines` to the standard input of the pager and wait for it to exit.
+fn run_pager(command: &[String], lines: &[String]) -> io::Result<()> {
+    let mut pager = Command::new(&command[0]);
+    pager.args(&command[1..]).stdin(Stdio::piped());
+    if env::var_os("LESS").is_none() {
+        pager.env("LESS", DEFAULT_LESS);
+    }
+
+    let mut child = pager.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        for line in lines {
+            // The user may quit the pager before reading everything
+            match writeln!(stdin, "{line}") {
+                Err(error) if error.kind() == ErrorKind::BrokenPipe => break,
+                result => result?,
+            }
+        }
+    }
+    child.wait()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_paging() {
+        assert!(!needs_paging(10, Some(40)));
+        assert!(needs_paging(40, Some(40)));
+        assert!(needs_paging(1000, Some(40)));
+        assert!(!needs_paging(1000, None));
+    }
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(pager_command(None), Some(vec!["less".to_string()]));
+        assert_eq!(
+            pager_command(Some("less -S")),
+            Some(vec!["less".to_string(), "-S".to_string()])
+        );
+        assert_eq!(pager_command(Some("")), None);
+        assert_eq!(pager_command(Some("cat")), None);
+    }
+}
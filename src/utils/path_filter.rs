@@ -0,0 +1,148 @@
+//! Glob-based path filtering.
+//!
+//! Backs `--include` and `--exclude`. Globs are matched against absolute
+//! paths, `*` does not cross directory separators while `**` does, and a
+//! leading `~` stands for the home directory: `--exclude '**/experiments/**'`
+//! ignores every `experiments` directory, and `--include '~/work/**'` limits
+//! cleaning to projects under `~/work`.
+
+use std::path::{self, Path};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::config::file::expand_tilde;
+use crate::error::{Error, Result};
+
+/// Include and exclude globs restricting the paths considered.
+#[derive(Clone, Debug, Default)]
+pub struct PathFilter {
+    /// Paths must match one of these globs, if any were given.
+    include: Option<GlobSet>,
+
+    /// Paths matching one of these globs are ignored.
+    exclude: Option<GlobSet>,
+}
+
+impl PathFilter {
+    /// Compile the `include` and `exclude` glob patterns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the patterns is not a valid glob.
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    /// Check whether `path` matches an `--exclude` glob.
+    #[must_use]
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude
+            .as_ref()
+            .is_some_and(|exclude| exclude.is_match(absolute(path)))
+    }
+
+    /// Check whether `path` matches an `--include` glob, or no `--include`
+    /// glob was given.
+    #[must_use]
+    pub fn is_included(&self, path: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|include| include.is_match(absolute(path)))
+    }
+
+    /// Check whether `path` is included and not excluded.
+    #[must_use]
+    pub fn allows(&self, path: &Path) -> bool {
+        self.is_included(path) && !self.is_excluded(path)
+    }
+}
+
+/// Compile `patterns` into a single glob set, or `None` if there are none.
+fn compile(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let expanded = expand_tilde(Path::new(pattern));
+        let glob = GlobBuilder::new(&expanded.to_string_lossy())
+            .literal_separator(true)
+            .build()
+            .map_err(|source| Error::Glob {
+                pattern: pattern.clone(),
+                source,
+            })?;
+        builder.add(glob);
+    }
+
+    let set = builder.build().map_err(|source| Error::Glob {
+        pattern: patterns.join(", "),
+        source,
+    })?;
+    Ok(Some(set))
+}
+
+/// Make `path` absolute without touching the file system, so that relative
+/// scan paths match absolute globs.
+fn absolute(path: &Path) -> path::PathBuf {
+    path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> PathFilter {
+        let strings = |globs: &[&str]| globs.iter().map(ToString::to_string).collect::<Vec<_>>();
+        PathFilter::new(&strings(include), &strings(exclude)).unwrap()
+    }
+
+    #[test]
+    fn test_empty_filter_allows_everything() {
+        assert!(PathFilter::default().allows(Path::new("/any/path")));
+    }
+
+    #[test]
+    fn test_exclude_glob() {
+        let filter = filter(&[], &["**/experiments/**"]);
+
+        assert!(!filter.allows(Path::new("/home/me/experiments/demo")));
+        assert!(filter.allows(Path::new("/home/me/work/demo")));
+    }
+
+    #[test]
+    fn test_include_glob() {
+        let filter = filter(&["/work/*"], &[]);
+
+        assert!(filter.allows(Path::new("/work/demo")));
+        // `*` does not cross directory separators
+        assert!(!filter.allows(Path::new("/work/group/demo")));
+        assert!(!filter.allows(Path::new("/play/demo")));
+    }
+
+    #[test]
+    fn test_exclude_wins_over_include() {
+        let filter = filter(&["/work/**"], &["**/old"]);
+
+        assert!(filter.allows(Path::new("/work/demo")));
+        assert!(!filter.allows(Path::new("/work/old")));
+    }
+
+    #[test]
+    fn test_relative_paths_are_matched_as_absolute() {
+        let cwd = std::env::current_dir().unwrap();
+        let filter = filter(&[&format!("{}/**", cwd.display())], &[]);
+
+        assert!(filter.allows(Path::new("./demo")));
+    }
+
+    #[test]
+    fn test_invalid_glob_is_an_error() {
+        let err = PathFilter::new(&["[unclosed".to_string()], &[]).unwrap_err();
+        assert!(err.to_string().contains("[unclosed"));
+    }
+}
@@ -0,0 +1,493 @@
+//! Handling of unusual paths.
+//!
+//! Build directories can hold paths that the usual path-based calls handle
+//! poorly:
+//! - File names that are not valid UTF-8 (on Unix). They are displayed
+//!   lossily, but always handled through their raw bytes.
+//! - Trees deeper than `PATH_MAX` (4096 bytes on Linux), whose paths the
+//!   kernel refuses. They are measured and removed relative to directory file
+//!   descriptors instead ([`size_deep`], [`remove_deep`]).
+//! - Names ending with a dot or a space (on Windows), which Win32 path
+//!   normalization strips. They are reached through `\\?\` verbatim paths
+//!   ([`extended`]), which also lift the 260 character path limit.
+
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Return the file name of `path` for display, or the whole path if it has
+/// none.
+///
+/// Names that are not valid UTF-8 are converted lossily.
+#[must_use]
+pub fn display_name(path: &Path) -> Cow<'_, str> {
+    path.file_name()
+        .map_or_else(|| path.to_string_lossy(), OsStr::to_string_lossy)
+}
+
+/// Check whether a file name starts with a dot, be it valid UTF-8 or not.
+#[must_use]
+pub fn is_hidden(name: &OsStr) -> bool {
+    name.as_encoded_bytes().first() == Some(&b'.')
+}
+
+/// Check whether `error` was caused by a path too long for the system.
+#[cfg(unix)]
+#[must_use]
+pub fn is_too_long(error: &io::Error) -> bool {
+    error.raw_os_error() == Some(rustix::io::Errno::NAMETOOLONG.raw_os_error())
+}
+
+/// Paths are made [`extended`] instead on other platforms.
+#[cfg(not(unix))]
+#[must_use]
+pub const fn is_too_long(_error: &io::Error) -> bool {
+    false
+}
+
+/// Remove the file, or empty directory, at `path`.
+///
+/// A symbolic link is removed, never its target. On Windows, links to
+/// directories and NTFS junctions, which cannot be removed as files, are
+/// removed as directories. Entries that cannot be removed because they are
+/// read-only (as in Go module caches and some npm packages) are made
+/// writable and removed again: on Windows, the entry's read-only attribute
+/// is cleared; on Unix, where removal depends on the directory holding the
+/// entry, the owner is given write permission on that directory. Falls back
+/// to [`remove_deep`] when the path is too long.
+///
+/// # Errors
+///
+/// Returns the error of the removal.
+pub fn remove_entry(path: &Path, is_dir: bool) -> io::Result<()> {
+    match remove(path, is_dir) {
+        Err(error) if is_too_long(&error) => remove_deep(path),
+        Err(error) if error.kind() == io::ErrorKind::PermissionDenied && clear_readonly(path) => {
+            remove(path, is_dir)
+        }
+        removed => removed,
+    }
+}
+
+/// Remove the file, empty directory or link at `path`.
+fn remove(path: &Path, is_dir: bool) -> io::Result<()> {
+    if is_dir {
+        fs::remove_dir(path)
+    } else {
+        fs::remove_file(path).or_else(|error| {
+            if is_dir_link(path) {
+                fs::remove_dir(path)
+            } else {
+                Err(error)
+            }
+        })
+    }
+}
+
+/// Clear the read-only attribute of `path`, returning whether it was set.
+#[cfg(windows)]
+fn clear_readonly(path: &Path) -> bool {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return false;
+    };
+    let mut permissions = metadata.permissions();
+    if !permissions.readonly() {
+        return false;
+    }
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions).is_ok()
+}
+
+/// Give the owner write permission on the directory holding `path`,
+/// returning whether it was missing.
+#[cfg(unix)]
+fn clear_readonly(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    let parent = match path.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+        Some(parent) => parent,
+        None => return false,
+    };
+    let Ok(metadata) = fs::metadata(parent) else {
+        return false;
+    };
+    let mut permissions = metadata.permissions();
+    let mode = permissions.mode();
+    if mode & 0o200 != 0 {
+        return false;
+    }
+    permissions.set_mode(mode | 0o200);
+    fs::set_permissions(parent, permissions).is_ok()
+}
+
+/// Read-only entries are not handled on other platforms.
+#[cfg(not(any(unix, windows)))]
+const fn clear_readonly(_path: &Path) -> bool {
+    false
+}
+
+/// Check whether `path` is a symbolic link to a directory or an NTFS
+/// junction.
+#[cfg(windows)]
+fn is_dir_link(path: &Path) -> bool {
+    use std::os::windows::fs::FileTypeExt;
+
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink_dir())
+}
+
+/// Links to directories are removed as files outside of Windows.
+#[cfg(not(windows))]
+const fn is_dir_link(_path: &Path) -> bool {
+    false
+}
+
+/// Make `path` usable by file system calls whatever its length and names.
+///
+/// On Windows, the path is made absolute (without normalizing away trailing
+/// dots and spaces) and turned into a `\\?\` verbatim path.
+#[cfg(windows)]
+#[must_use]
+pub fn extended(path: &Path) -> Cow<'_, Path> {
+    use std::path::{Component, PathBuf};
+
+    let mut absolute = if path.is_absolute() {
+        PathBuf::new()
+    } else {
+        match std::env::current_dir() {
+            Ok(cwd) => cwd,
+            Err(_) => return Cow::Borrowed(path),
+        }
+    };
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            component => absolute.push(component),
+        }
+    }
+
+    absolute
+        .to_str()
+        .and_then(verbatim)
+        .map_or(Cow::Borrowed(path), |verbatim| {
+            Cow::Owned(PathBuf::from(verbatim))
+        })
+}
+
+/// Paths need no conversion outside of Windows.
+#[cfg(not(windows))]
+#[must_use]
+pub const fn extended(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// Turn an absolute Windows path (`C:\…` or `\\server\share\…`) into a
+/// verbatim one.
+///
+/// Returns `None` for paths that are already verbatim, device paths, and
+/// other paths that cannot be converted.
+#[cfg(any(windows, test))]
+fn verbatim(path: &str) -> Option<String> {
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        return Some(format!(r"\\?\UNC\{unc}"));
+    }
+
+    match path.as_bytes() {
+        [drive, b':', b'\\', ..] if drive.is_ascii_alphabetic() => Some(format!(r"\\?\{path}")),
+        _ => None,
+    }
+}
+
+/// Compute the total size of the files at `path` (a file, or a directory
+/// and its contents), however deep the path is.
+///
+/// Symbolic links are not followed.
+///
+/// # Errors
+///
+/// Returns the first error met while walking the tree.
+#[cfg(unix)]
+pub fn size_deep(path: &Path) -> io::Result<u64> {
+    let (parent, name) = deep::open_parent(path)?;
+    match deep::file_type(&parent, name)? {
+        rustix::fs::FileType::Directory => deep::size_at(&rustix::fs::openat(
+            &parent,
+            name,
+            deep::SUBDIR,
+            deep::NO_MODE,
+        )?),
+        rustix::fs::FileType::RegularFile => deep::file_size(&parent, name),
+        _ => Ok(0),
+    }
+}
+
+/// Remove the file or directory tree at `path`, however deep the path is.
+///
+/// Symbolic links are removed, not followed. A missing path is not an error.
+///
+/// # Errors
+///
+/// Returns the first error met while removing the tree.
+#[cfg(unix)]
+pub fn remove_deep(path: &Path) -> io::Result<()> {
+    let removed = deep::open_parent(path).and_then(|(parent, name)| {
+        let file_type = deep::file_type(&parent, name)?;
+        deep::remove_at(&parent, name, file_type)
+    });
+
+    match removed {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        removed => removed,
+    }
+}
+
+/// Only reached on Unix, where paths can be too long for the system.
+#[cfg(not(unix))]
+pub fn size_deep(_path: &Path) -> io::Result<u64> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Only reached on Unix, where paths can be too long for the system.
+#[cfg(not(unix))]
+pub fn remove_deep(_path: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// File descriptor based traversal, free of any path length limit.
+#[cfg(unix)]
+mod deep {
+    use std::ffi::{CString, OsStr};
+    use std::io;
+    use std::os::fd::OwnedFd;
+    use std::path::{Component, Path};
+
+    use rustix::fs::{AtFlags, CWD, Dir, FileType, Mode, OFlags, openat, statat, unlinkat};
+
+    /// Flags opening a directory met on the way to a path.
+    const DIR: OFlags = OFlags::RDONLY
+        .union(OFlags::DIRECTORY)
+        .union(OFlags::CLOEXEC);
+
+    /// Flags opening a directory inside a tree, without following links.
+    pub const SUBDIR: OFlags = DIR.union(OFlags::NOFOLLOW);
+
+    /// No file is ever created.
+    pub const NO_MODE: Mode = Mode::empty();
+
+    /// Open the parent directory of `path` one component at a time, and
+    /// return it with the file name of `path`.
+    pub fn open_parent(path: &Path) -> io::Result<(OwnedFd, &OsStr)> {
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+            return Err(io::ErrorKind::InvalidInput.into());
+        };
+
+        let start = if parent.has_root() { "/" } else { "." };
+        let mut dir = openat(CWD, start, DIR, NO_MODE)?;
+        for component in parent.components() {
+            let name = match component {
+                Component::Normal(name) => name,
+                Component::ParentDir => OsStr::new(".."),
+                Component::Prefix(_) | Component::RootDir | Component::CurDir => continue,
+            };
+            dir = openat(&dir, name, DIR, NO_MODE)?;
+        }
+
+        Ok((dir, name))
+    }
+
+    /// Return the type of the entry `name` in `dir`, without following links.
+    pub fn file_type<P: rustix::path::Arg>(dir: &OwnedFd, name: P) -> io::Result<FileType> {
+        Ok(FileType::from_raw_mode(
+            statat(dir, name, AtFlags::SYMLINK_NOFOLLOW)?.st_mode,
+        ))
+    }
+
+    /// Return the size of the file `name` in `dir`.
+    pub fn file_size<P: rustix::path::Arg>(dir: &OwnedFd, name: P) -> io::Result<u64> {
+        let size = statat(dir, name, AtFlags::SYMLINK_NOFOLLOW)?.st_size;
+        Ok(u64::try_from(size).unwrap_or(0))
+    }
+
+    /// List the entries of `dir` with their types.
+    ///
+    /// The entries are collected up front, so that only one file descriptor
+    /// per level of the tree is open at a time.
+    fn entries(dir: &OwnedFd) -> io::Result<Vec<(CString, FileType)>> {
+        let mut entries = Vec::new();
+        for entry in Dir::read_from(dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if name == c"." || name == c".." {
+                continue;
+            }
+
+            let file_type = match entry.file_type() {
+                FileType::Unknown => file_type(dir, name)?,
+                file_type => file_type,
+            };
+            entries.push((name.to_owned(), file_type));
+        }
+        Ok(entries)
+    }
+
+    /// Compute the total size of the files below `dir`.
+    pub fn size_at(dir: &OwnedFd) -> io::Result<u64> {
+        let mut size = 0;
+        for (name, file_type) in entries(dir)? {
+            size += match file_type {
+                FileType::Directory => size_at(&openat(dir, &name, SUBDIR, NO_MODE)?)?,
+                FileType::RegularFile => file_size(dir, &name)?,
+                _ => 0,
+            };
+        }
+        Ok(size)
+    }
+
+    /// Remove the entry `name` of `dir`, and its contents if it is a
+    /// directory.
+    pub fn remove_at<P: rustix::path::Arg + Copy>(
+        dir: &OwnedFd,
+        name: P,
+        file_type: FileType,
+    ) -> io::Result<()> {
+        if file_type != FileType::Directory {
+            return Ok(unlinkat(dir, name, AtFlags::empty())?);
+        }
+
+        let subdir = openat(dir, name, SUBDIR, NO_MODE)?;
+        for (entry, entry_type) in entries(&subdir)? {
+            remove_at(&subdir, entry.as_c_str(), entry_type)?;
+        }
+        drop(subdir);
+
+        Ok(unlinkat(dir, name, AtFlags::REMOVEDIR)?)
+    }
+
+    /// Create `depth` nested directories named `name` in `root`, with a file
+    /// of `size` bytes at the bottom, however long the resulting path.
+    #[cfg(test)]
+    pub fn create_tree(root: &Path, name: &std::ffi::CStr, depth: usize, size: usize) {
+        use rustix::fs::mkdirat;
+        use std::io::Write;
+
+        let mut dir = openat(CWD, root, DIR, NO_MODE).unwrap();
+        for _ in 0..depth {
+            mkdirat(&dir, name, Mode::RWXU).unwrap();
+            dir = openat(&dir, name, SUBDIR, NO_MODE).unwrap();
+        }
+
+        let file = openat(
+            &dir,
+            c"leaf.o",
+            OFlags::WRONLY | OFlags::CREATE | OFlags::CLOEXEC,
+            Mode::RUSR | Mode::WUSR,
+        )
+        .unwrap();
+        std::fs::File::from(file).write_all(&vec![0; size]).unwrap();
+    }
+}
+
+/// Create a tree of nested directories deeper than `PATH_MAX`, with a file of
+/// `size` bytes at the bottom.
+#[cfg(all(test, unix))]
+pub(crate) fn create_deep_tree(root: &Path, size: usize) {
+    let name = std::ffi::CString::new("d".repeat(200)).unwrap();
+    deep::create_tree(root, &name, 30, size);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_name() {
+        assert_eq!(display_name(Path::new("/projects/app")), "app");
+        assert_eq!(display_name(Path::new("/")), "/");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_utf8_names() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = OsStr::from_bytes(b".caf\xe9");
+        let path = Path::new("/projects").join(name);
+
+        assert_eq!(display_name(&path), ".caf\u{FFFD}");
+        assert!(is_hidden(name));
+        assert!(!is_hidden(OsStr::from_bytes(b"caf\xe9")));
+    }
+
+    #[test]
+    fn test_verbatim() {
+        assert_eq!(
+            verbatim(r"C:\Users\me\app.").as_deref(),
+            Some(r"\\?\C:\Users\me\app.")
+        );
+        assert_eq!(
+            verbatim(r"\\server\share\app").as_deref(),
+            Some(r"\\?\UNC\server\share\app")
+        );
+        assert_eq!(verbatim(r"\\?\C:\app"), None);
+        assert_eq!(verbatim(r"\\.\pipe\app"), None);
+        assert_eq!(verbatim(r"app\target"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_deep_tree_size_and_removal() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path().join("target");
+        fs::create_dir(&root).unwrap();
+        create_deep_tree(&root, 1000);
+
+        assert_eq!(size_deep(&root).unwrap(), 1000);
+
+        remove_deep(&root).unwrap();
+        assert!(!root.exists());
+        remove_deep(&root).unwrap();
+    }
+
+    #[test]
+    fn test_remove_read_only_entries() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("pkg");
+        let file = dir.join("go.mod");
+        fs::create_dir(&dir).unwrap();
+        fs::write(&file, "module example.com/pkg").unwrap();
+        for path in [&file, &dir] {
+            let mut permissions = fs::metadata(path).unwrap().permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(path, permissions).unwrap();
+        }
+
+        remove_entry(&file, false).unwrap();
+        remove_entry(&dir, true).unwrap();
+        assert!(fs::symlink_metadata(&dir).is_err());
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_trailing_dot_and_space_names() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        for name in ["build.", "build "] {
+            let dir = tmp.path().join(name);
+            fs::create_dir_all(extended(&dir.join("obj"))).unwrap();
+            fs::write(extended(&dir.join("obj").join("out.o")), "out").unwrap();
+
+            assert!(fs::metadata(extended(&dir)).unwrap().is_dir());
+            fs::remove_dir_all(extended(&dir)).unwrap();
+            assert!(fs::symlink_metadata(extended(&dir)).is_err());
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Detection of processes working in or using candidate projects.
+//!
+//! Deleting `target/` or `node_modules/` while `cargo build` or `npm install`
+//! is writing to it breaks the build at best and leaves a half-populated
+//! directory at worst. This module lists the working directories of running
+//! package-manager processes so that the projects they are working in can be
+//! skipped.
+//!
+//! With `--check-in-use`, the files held open by every process are listed
+//! too, so that build directories a running program still reads from (a dev
+//! server serving `node_modules/`, a binary running from `target/`) are
+//! skipped. They are read from `/proc` on Linux and from `lsof` on macOS
+//! and the BSDs. They cannot be listed on Windows, so `--check-in-use` is
+//! refused there rather than finding nothing in use.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::error::Result;
+
+/// Executable names of the package managers that are looked for.
+const PACKAGE_MANAGERS: &[&str] = &["cargo", "npm", "pnpm", "pip", "pip3", "go"];
+
+/// Return the working directories of every running package-manager process.
+///
+/// Processes whose working directory cannot be read (typically those owned
+/// by other users) are ignored.
+#[must_use]
+pub fn package_manager_dirs() -> Vec<PathBuf> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_cwd(UpdateKind::Always)
+            .with_cmd(UpdateKind::Always),
+    );
+
+    system
+        .processes()
+        .values()
+        .filter(|process| {
+            is_package_manager(process.name())
+                || process
+                    .cmd()
+                    .first()
+                    .is_some_and(|arg0| is_package_manager(arg0))
+        })
+        .filter_map(|process| process.cwd().map(Path::to_path_buf))
+        .collect()
+}
+
+/// Check whether a process name or `argv[0]` refers to a known package manager.
+///
+/// Only the file stem is compared, so `/usr/bin/cargo` and `cargo.exe` both
+/// match.
+#[must_use]
+pub fn is_package_manager(name: &OsStr) -> bool {
+    Path::new(name)
+        .file_stem()
+        .and_then(OsStr::to_str)
+        .is_some_and(|stem| PACKAGE_MANAGERS.contains(&stem))
+}
+
+/// A file held open, or mapped in memory, by a running process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpenFile {
+    /// Identifier of the process.
+    pub pid: u32,
+
+    /// Name of the process, e.g. `node`.
+    pub process: String,
+
+    /// The file.
+    pub path: PathBuf,
+}
+
+/// Return the files held open by running processes, other than this one.
+///
+/// Processes whose files cannot be listed (typically those owned by other
+/// users) are ignored.
+///
+/// # Errors
+///
+/// Returns an error if open files cannot be listed at all: `lsof` cannot be
+/// run, or the platform (Windows) offers no way to list them.
+pub fn open_files() -> Result<Vec<OpenFile>> {
+    Ok(list_open_files()?
+        .into_iter()
+        .filter(|file| file.pid != std::process::id())
+        .collect())
+}
+
+/// List the files held open by every process, from `/proc`.
+#[cfg(target_os = "linux")]
+#[allow(clippy::unnecessary_wraps)]
+fn list_open_files() -> Result<Vec<OpenFile>> {
+    Ok(proc_open_files())
+}
+
+/// List the files held open by every process with `lsof`, which exits with
+/// 1 when some processes could not be inspected.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn list_open_files() -> Result<Vec<OpenFile>> {
+    std::process::Command::new("lsof")
+        .args(["-n", "-P", "-w", "-F", "pcn"])
+        .output()
+        .map(|output| parse_lsof(&String::from_utf8_lossy(&output.stdout)))
+        .map_err(|source| crate::Error::Spawn {
+            command: "lsof".to_string(),
+            source,
+        })
+}
+
+/// Open files cannot be listed on this platform.
+#[cfg(not(unix))]
+fn list_open_files() -> Result<Vec<OpenFile>> {
+    Err(crate::Error::Other(
+        "files held open by other processes cannot be listed on this platform, so \
+         --check-in-use is not supported"
+            .to_string(),
+    ))
+}
+
+/// Read the files each process holds open (`/proc/<pid>/fd`) or has mapped
+/// in memory (`/proc/<pid>/maps`, e.g. native Node.js modules).
+#[cfg(target_os = "linux")]
+fn proc_open_files() -> Vec<OpenFile> {
+    use std::collections::BTreeSet;
+    use std::fs;
+
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let pids = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok());
+
+    pids.flat_map(|pid| {
+        let proc_dir = PathBuf::from(format!("/proc/{pid}"));
+        let process = fs::read_to_string(proc_dir.join("comm"))
+            .map(|comm| comm.trim_end().to_string())
+            .unwrap_or_default();
+
+        let mut paths = BTreeSet::new();
+        if let Ok(fds) = fs::read_dir(proc_dir.join("fd")) {
+            paths.extend(
+                fds.filter_map(Result::ok)
+                    .filter_map(|fd| fs::read_link(fd.path()).ok())
+                    .filter(|path| path.is_absolute()),
+            );
+        }
+        if let Ok(maps) = fs::read_to_string(proc_dir.join("maps")) {
+            paths.extend(
+                maps.lines()
+                    .filter_map(|line| line.split_whitespace().nth(5))
+                    .filter(|path| path.starts_with('/'))
+                    .map(PathBuf::from),
+            );
+        }
+
+        paths.into_iter().map(move |path| OpenFile {
+            pid,
+            process: process.clone(),
+            path,
+        })
+    })
+    .collect()
+}
+
+/// Parse the output of `lsof -F pcn`: a `p<pid>` line per process, then a
+/// `c<command>` line, then an `n<name>` line per open file.
+#[cfg(any(all(unix, not(target_os = "linux")), test))]
+fn parse_lsof(output: &str) -> Vec<OpenFile> {
+    let mut open_files = Vec::new();
+    let (mut pid, mut process) = (None, String::new());
+    for line in output.lines() {
+        match line.split_at_checked(1) {
+            Some(("p", value)) => pid = value.parse().ok(),
+            Some(("c", value)) => value.clone_into(&mut process),
+            Some(("n", value)) if value.starts_with('/') => {
+                if let Some(pid) = pid {
+                    open_files.push(OpenFile {
+                        pid,
+                        process: process.clone(),
+                        path: PathBuf::from(value),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    open_files
+}
+
+/// Return the first of `open_files` inside `dir`, if any.
+///
+/// `dir` is resolved first, as the paths of open files are absolute and
+/// canonical.
+#[must_use]
+pub fn open_file_below<'a>(dir: &Path, open_files: &'a [OpenFile]) -> Option<&'a OpenFile> {
+    let dir = resolve(dir);
+    open_files.iter().find(|file| file.path.starts_with(&dir))
+}
+
+/// Check whether any of `dirs` lies inside `root` (or is `root` itself).
+///
+/// `root` is resolved first, as project roots are relative when the scan
+/// root was. `dirs` must already be resolved (see [`resolve`]), once for
+/// all the projects checked against them.
+#[must_use]
+pub fn is_busy(root: &Path, dirs: &[PathBuf]) -> bool {
+    let root = resolve(root);
+    dirs.iter().any(|dir| dir.starts_with(&root))
+}
+
+/// `path` made absolute, with its symbolic links resolved if it exists.
+#[must_use]
+pub fn resolve(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_package_manager_matches_known_names() {
+        for name in ["cargo", "npm", "pnpm", "pip", "pip3", "go"] {
+            assert!(is_package_manager(OsStr::new(name)), "{name}");
+        }
+        assert!(is_package_manager(OsStr::new("/usr/local/bin/cargo")));
+        assert!(is_package_manager(OsStr::new("cargo.exe")));
+    }
+
+    #[test]
+    fn test_is_package_manager_rejects_other_processes() {
+        for name in ["cargo-watch", "node", "gopls", "bash", ""] {
+            assert!(!is_package_manager(OsStr::new(name)), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_parse_lsof() {
+        let output = "p412\ncnode\nn/\nn/work/web/node_modules/esbuild/bin/esbuild\n\
+                      nlocalhost:5173\np977\ncapp\nn/work/app/target/debug/app\n";
+        let open_files = parse_lsof(output);
+
+        assert_eq!(open_files.len(), 3);
+        let found = open_file_below(Path::new("/work/app/target"), &open_files).unwrap();
+        assert_eq!((found.pid, found.process.as_str()), (977, "app"));
+        let found = open_file_below(Path::new("/work/web/node_modules"), &open_files).unwrap();
+        assert_eq!(found.process, "node");
+        assert!(open_file_below(Path::new("/work/api/node_modules"), &open_files).is_none());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_open_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("held.txt");
+        std::fs::write(&file, "held").unwrap();
+        let _held = std::fs::File::open(&file).unwrap();
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .stdin(std::fs::File::open(&file).unwrap())
+            .spawn()
+            .unwrap();
+        // Until it has exec'd, the child still carries the test thread's name
+        let comm = format!("/proc/{}/comm", child.id());
+        while std::fs::read_to_string(&comm).unwrap().trim() != "sleep" {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let open_files = open_files().unwrap();
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        // Only the child counts, not this process
+        let found = open_file_below(dir.path(), &open_files).unwrap();
+        assert_eq!((found.pid, found.process.as_str()), (child.id(), "sleep"));
+
+        // The paths of open files are canonical, the directory may not be
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(dir.path(), &link).unwrap();
+        assert!(open_file_below(&link, &open_files).is_some());
+    }
+
+    #[test]
+    fn test_is_busy() {
+        let root = Path::new("/home/user/project");
+        let dirs = vec![PathBuf::from("/home/user/project/crates/core")];
+
+        assert!(is_busy(root, &dirs));
+        assert!(is_busy(root, &[root.to_path_buf()]));
+        assert!(!is_busy(Path::new("/home/user/other"), &dirs));
+        assert!(!is_busy(Path::new("/home/user/proj"), &dirs));
+        assert!(!is_busy(root, &[]));
+    }
+}
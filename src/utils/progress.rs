@@ -0,0 +1,109 @@
+//! Progress bar drawing.
+//!
+//! Progress bars are drawn on stderr, and only when it is a terminal. Every
+//! redraw goes over the wire in an SSH session, and frequent redraws make the
+//! tool unusable over high-latency links. In an SSH session, the round-trip
+//! time to the terminal is therefore measured once, with a cursor position
+//! query, and the refresh rate lowered to match it.
+
+use std::io::{self, IsTerminal};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use console::Term;
+use indicatif::{ProgressBar, ProgressDrawTarget};
+use ratatui::crossterm::cursor;
+
+/// Refresh rate of progress bars on a local terminal, in redraws per second.
+const LOCAL_REFRESH_RATE: u8 = 20;
+
+/// Refresh rate in an SSH session whose latency could not be measured.
+const REMOTE_REFRESH_RATE: u8 = 4;
+
+/// Progress bars are redrawn at most once every `REDRAW_LATENCY_FACTOR`
+/// round trips to the terminal.
+const REDRAW_LATENCY_FACTOR: u32 = 4;
+
+/// Whether progress bars are disabled (`--no-progress`).
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Refresh rate of progress bars, measured on first use.
+static REFRESH_RATE: OnceLock<u8> = OnceLock::new();
+
+/// Disable progress bars for the rest of the process, if `disabled`.
+pub fn disable(disabled: bool) {
+    if disabled {
+        DISABLED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Create a progress bar of `len` steps (a spinner if `None`).
+///
+/// The bar is hidden when progress bars are disabled or stderr is not a
+/// terminal.
+#[must_use]
+pub fn new(len: Option<u64>) -> ProgressBar {
+    if DISABLED.load(Ordering::Relaxed) || !Term::stderr().is_term() {
+        return ProgressBar::hidden();
+    }
+
+    let refresh_rate = *REFRESH_RATE.get_or_init(refresh_rate);
+    ProgressBar::with_draw_target(len, ProgressDrawTarget::stderr_with_hz(refresh_rate))
+}
+
+/// Choose the refresh rate of progress bars for the current terminal.
+fn refresh_rate() -> u8 {
+    if !in_ssh_session() {
+        return LOCAL_REFRESH_RATE;
+    }
+
+    terminal_latency().map_or(REMOTE_REFRESH_RATE, refresh_rate_for)
+}
+
+/// Check whether the process runs in an SSH session.
+fn in_ssh_session() -> bool {
+    ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+        .iter()
+        .any(|var| std::env::var_os(var).is_some())
+}
+
+/// Measure the round-trip time to the terminal, by querying the cursor
+/// position.
+///
+/// Returns `None` if stdin or stdout is not a terminal, or if the terminal
+/// does not answer.
+fn terminal_latency() -> Option<Duration> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return None;
+    }
+
+    let start = Instant::now();
+    cursor::position().ok()?;
+    Some(start.elapsed())
+}
+
+/// Compute the refresh rate for a terminal `latency` away, between 1 and
+/// [`LOCAL_REFRESH_RATE`] redraws per second.
+fn refresh_rate_for(latency: Duration) -> u8 {
+    let interval = latency * REDRAW_LATENCY_FACTOR;
+    let rate = Duration::from_secs(1).as_millis() / interval.as_millis().max(1);
+    u8::try_from(rate).map_or(LOCAL_REFRESH_RATE, |rate| rate.clamp(1, LOCAL_REFRESH_RATE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_rate_for_latency() {
+        assert_eq!(refresh_rate_for(Duration::ZERO), LOCAL_REFRESH_RATE);
+        assert_eq!(
+            refresh_rate_for(Duration::from_millis(5)),
+            LOCAL_REFRESH_RATE
+        );
+        assert_eq!(refresh_rate_for(Duration::from_millis(50)), 5);
+        assert_eq!(refresh_rate_for(Duration::from_millis(125)), 2);
+        assert_eq!(refresh_rate_for(Duration::from_secs(1)), 1);
+    }
+}
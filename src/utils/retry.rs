@@ -0,0 +1,503 @@
+//! Retries for transient deletion failures.
+//!
+//! Deleting large build directories occasionally fails for reasons that go
+//! away on their own: on Windows, antivirus scanners briefly hold files open
+//! (`ERROR_SHARING_VIOLATION`); on NFS, files still open elsewhere are
+//! "silly-renamed" to `.nfsXXXX` and keep their directory busy for a moment.
+//! Instead of failing the whole project on the first such error, removals are
+//! retried per file and directory with exponential backoff.
+//!
+//! Every path that needed a retry is recorded in a [`RetryTelemetry`], so
+//! that persistent interference (such as an antivirus scanner) can be
+//! diagnosed after the cleanup.
+
+use std::cell::Cell;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::utils::paths;
+use crate::vfs::{FileSystem, RealFs, WalkError};
+
+/// How often, and how patiently, transient failures are retried.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of retries after the first failed attempt (`0` disables
+    /// retrying).
+    pub retries: u32,
+
+    /// Delay before the first retry; doubled before each further retry.
+    pub initial_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Three retries, after 100 ms, 200 ms and 400 ms.
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            initial_delay: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    #[must_use]
+    pub const fn none() -> Self {
+        Self {
+            retries: 0,
+            initial_delay: Duration::ZERO,
+        }
+    }
+
+    /// Run `operation`, retrying it while it fails with an error for which
+    /// `is_transient` returns `true`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first non-transient error, or the last error once all
+    /// retries are used up.
+    pub fn run<T, E>(
+        &self,
+        is_transient: impl Fn(&E) -> bool,
+        mut operation: impl FnMut() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut delay = self.initial_delay;
+        let mut retries_left = self.retries;
+
+        loop {
+            match operation() {
+                Err(error) if retries_left > 0 && is_transient(&error) => {
+                    thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                    retries_left -= 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A path whose removal hit transient failures.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RetryRecord {
+    /// The file or directory that failed to be removed at first.
+    pub path: PathBuf,
+
+    /// Number of transient failures seen for this path.
+    pub transient_failures: u32,
+
+    /// Whether the path was eventually removed.
+    pub recovered: bool,
+}
+
+/// Shared collector of [`RetryRecord`]s.
+///
+/// Cloning the collector is cheap and every clone records into the same
+/// list, so it can be handed to parallel workers.
+#[derive(Clone, Debug, Default)]
+pub struct RetryTelemetry {
+    records: Arc<Mutex<Vec<RetryRecord>>>,
+}
+
+impl RetryTelemetry {
+    /// Create an empty collector.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `operation` on `path` with `policy`, recording the path if it
+    /// failed transiently at least once.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `operation` once retrying gave up.
+    pub fn run<T, E>(
+        &self,
+        path: &Path,
+        policy: &RetryPolicy,
+        is_transient: impl Fn(&E) -> bool,
+        operation: impl FnMut() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let transient_failures = Cell::new(0);
+        let result = policy.run(
+            |error| {
+                let transient = is_transient(error);
+                if transient {
+                    transient_failures.set(transient_failures.get() + 1);
+                }
+                transient
+            },
+            operation,
+        );
+
+        if transient_failures.get() > 0 {
+            self.records
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(RetryRecord {
+                    path: path.to_path_buf(),
+                    transient_failures: transient_failures.get(),
+                    recovered: result.is_ok(),
+                });
+        }
+
+        result
+    }
+
+    /// Return (and clear) the records collected so far.
+    #[must_use]
+    pub fn take(&self) -> Vec<RetryRecord> {
+        std::mem::take(&mut *self.records.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Check whether an I/O error is likely to go away when retried.
+///
+/// Covers busy resources (`EBUSY`), directories that are not empty yet
+/// (NFS silly-renames), interrupted calls, and on Windows sharing and lock
+/// violations as well as the "access denied" errors antivirus scanners cause.
+#[must_use]
+pub fn is_transient(error: &io::Error) -> bool {
+    if matches!(
+        error.kind(),
+        ErrorKind::ResourceBusy
+            | ErrorKind::DirectoryNotEmpty
+            | ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+    ) {
+        return true;
+    }
+
+    // ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+    cfg!(windows) && matches!(error.raw_os_error(), Some(5 | 32 | 33))
+}
+
+/// Remove a directory and all of its contents, retrying each entry on
+/// transient errors.
+///
+/// Entries are removed bottom-up, so a file that stays locked only fails
+/// its own removal (and that of its parent directories) after the retries
+/// are used up. Entries that disappear concurrently are not an error.
+///
+/// # Errors
+///
+/// Returns the first entry whose removal failed through all retries, e.g.
+/// the file an editor kept open, rather than `path` itself.
+pub fn remove_dir_all(
+    path: &Path,
+    policy: &RetryPolicy,
+    telemetry: &RetryTelemetry,
+) -> Result<(), Leftover> {
+    remove_dir_all_lenient(&RealFs, path, policy, telemetry)
+        .into_iter()
+        .next()
+        .map_or(Ok(()), Err)
+}
+
+/// An entry that could not be removed.
+#[derive(Debug)]
+pub struct Leftover {
+    /// Path of the entry.
+    pub path: PathBuf,
+
+    /// Error that persisted through all retries.
+    pub error: io::Error,
+}
+
+/// Remove a directory and all of its contents like [`remove_dir_all`], but
+/// carry on past the entries that cannot be removed.
+///
+/// Returns the entries left behind. The directories holding them are left
+/// too, without being reported or attempted.
+pub fn remove_dir_all_lenient(
+    fs: &dyn FileSystem,
+    path: &Path,
+    policy: &RetryPolicy,
+    telemetry: &RetryTelemetry,
+) -> Vec<Leftover> {
+    let mut leftovers: Vec<Leftover> = Vec::new();
+
+    for entry in fs.walk(&fs.extended(path), true) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(WalkError { path, error }) => {
+                // Directories too deep to be read by path are removed whole
+                let error = if paths::is_too_long(&error) {
+                    match fs.remove_entry(&path, true) {
+                        Ok(()) => continue,
+                        Err(error) => error,
+                    }
+                } else {
+                    error
+                };
+                leftovers.push(Leftover { path, error });
+                continue;
+            }
+        };
+        let path = entry.path.as_path();
+        let is_dir = entry.is_dir();
+
+        if is_dir
+            && leftovers
+                .iter()
+                .any(|leftover| leftover.path.starts_with(path))
+        {
+            continue;
+        }
+
+        let removed = telemetry.run(path, policy, is_transient, || fs.remove_entry(path, is_dir));
+
+        match removed {
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => leftovers.push(Leftover {
+                path: path.to_path_buf(),
+                error,
+            }),
+            Ok(()) => {}
+        }
+    }
+
+    leftovers
+}
+
+/// Remove a file, or a directory and its contents, retrying on transient
+/// errors.
+///
+/// # Errors
+///
+/// Returns the first entry whose removal failed through all retries.
+pub fn remove_path(
+    path: &Path,
+    policy: &RetryPolicy,
+    telemetry: &RetryTelemetry,
+) -> Result<(), Leftover> {
+    if path.is_dir() && !path.is_symlink() {
+        remove_dir_all(path, policy, telemetry)
+    } else {
+        telemetry
+            .run(path, policy, is_transient, || {
+                paths::remove_entry(&paths::extended(path), false)
+            })
+            .map_err(|error| Leftover {
+                path: path.to_path_buf(),
+                error,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    use crate::vfs::MemoryFs;
+
+    fn fast_policy(retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            retries,
+            initial_delay: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn test_run_retries_transient_errors() {
+        let attempts = Cell::new(0);
+        let result = fast_policy(3).run(is_transient, || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                Err(io::Error::from(ErrorKind::ResourceBusy))
+            } else {
+                Ok(attempts.get())
+            }
+        });
+
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_run_gives_up_after_retries() {
+        let attempts = Cell::new(0);
+        let result: io::Result<()> = fast_policy(2).run(is_transient, || {
+            attempts.set(attempts.get() + 1);
+            Err(io::Error::from(ErrorKind::ResourceBusy))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[test]
+    fn test_run_does_not_retry_permanent_errors() {
+        let attempts = Cell::new(0);
+        let result: io::Result<()> = fast_policy(3).run(is_transient, || {
+            attempts.set(attempts.get() + 1);
+            Err(io::Error::from(ErrorKind::NotFound))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_remove_dir_all_lenient_keeps_going() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        let locked = target.join("locked");
+        fs::create_dir_all(&locked).unwrap();
+        fs::write(locked.join("stuck"), "stuck").unwrap();
+        fs::write(target.join("removable"), "removable").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o555)).unwrap();
+
+        // Permissions do not apply to root
+        if fs::write(locked.join("probe"), "").is_ok() {
+            return;
+        }
+
+        let leftovers = remove_dir_all_lenient(
+            &RealFs,
+            &target,
+            &RetryPolicy::default(),
+            &RetryTelemetry::new(),
+        );
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let paths: Vec<_> = leftovers.iter().map(|leftover| &leftover.path).collect();
+        assert_eq!(paths, [&locked.join("stuck")]);
+        assert!(!target.join("removable").exists());
+    }
+
+    #[test]
+    fn test_remove_dir_all_lenient_reports_locked_entries() {
+        let fs = MemoryFs::new()
+            .with_file("/app/target/locked/stuck", "stuck")
+            .with_file("/app/target/removable", "removable")
+            .with_locked("/app/target/locked/stuck");
+        let target = Path::new("/app/target");
+
+        let leftovers =
+            remove_dir_all_lenient(&fs, target, &fast_policy(1), &RetryTelemetry::new());
+
+        let paths: Vec<_> = leftovers.iter().map(|leftover| &leftover.path).collect();
+        assert_eq!(paths, [Path::new("/app/target/locked/stuck")]);
+        assert!(!fs.exists(&target.join("removable")));
+        // The directories holding the leftover are left too
+        assert!(fs.is_dir(&target.join("locked")));
+    }
+
+    #[test]
+    fn test_remove_dir_all() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir_all(target.join("debug/deps")).unwrap();
+        fs::write(target.join("debug/deps/libfoo.rlib"), "rlib").unwrap();
+        fs::write(target.join("CACHEDIR.TAG"), "tag").unwrap();
+
+        let telemetry = RetryTelemetry::new();
+        remove_dir_all(&target, &RetryPolicy::default(), &telemetry).unwrap();
+        assert!(telemetry.take().is_empty());
+        assert!(!target.exists());
+        assert!(temp_dir.path().exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_remove_dir_all_handles_trees_deeper_than_path_max() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir(&target).unwrap();
+        paths::create_deep_tree(&target, 100);
+
+        remove_dir_all(&target, &RetryPolicy::default(), &RetryTelemetry::new()).unwrap();
+        assert!(!target.exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_remove_dir_all_removes_links_not_their_targets() {
+        let temp_dir = TempDir::new().unwrap();
+        let elsewhere = temp_dir.path().join("elsewhere");
+        fs::create_dir(&elsewhere).unwrap();
+        fs::write(elsewhere.join("keep.txt"), "keep").unwrap();
+        let target = temp_dir.path().join("target");
+        std::os::unix::fs::symlink(&elsewhere, &target).unwrap();
+
+        remove_dir_all(&target, &RetryPolicy::default(), &RetryTelemetry::new()).unwrap();
+        assert!(fs::symlink_metadata(&target).is_err());
+        assert!(elsewhere.join("keep.txt").exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_remove_path_reports_the_entry_left_behind() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let stale = temp_dir.path().join("incremental");
+        let locked = stale.join("locked");
+        fs::create_dir_all(&locked).unwrap();
+        fs::write(locked.join("stuck"), "stuck").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o555)).unwrap();
+
+        // Permissions do not apply to root
+        if fs::write(locked.join("probe"), "").is_ok() {
+            return;
+        }
+
+        let leftover = remove_path(&stale, &fast_policy(0), &RetryTelemetry::new()).unwrap_err();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(leftover.path, locked.join("stuck"));
+        assert_eq!(leftover.error.kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn test_remove_path_handles_files_and_missing_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("file.d");
+        fs::write(&file, "deps").unwrap();
+
+        let telemetry = RetryTelemetry::new();
+        remove_path(&file, &RetryPolicy::none(), &telemetry).unwrap();
+        assert!(!file.exists());
+        assert!(remove_path(&file, &RetryPolicy::none(), &telemetry).is_err());
+    }
+
+    #[test]
+    fn test_telemetry_records_retried_paths() {
+        let telemetry = RetryTelemetry::new();
+        let attempts = Cell::new(0);
+
+        let result = telemetry.run(Path::new("/busy"), &fast_policy(3), is_transient, || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                Err(io::Error::from(ErrorKind::ResourceBusy))
+            } else {
+                Ok(())
+            }
+        });
+        assert!(result.is_ok());
+
+        let _: io::Result<()> =
+            telemetry.run(Path::new("/ok"), &fast_policy(3), is_transient, || Ok(()));
+
+        assert_eq!(
+            telemetry.take(),
+            vec![RetryRecord {
+                path: PathBuf::from("/busy"),
+                transient_failures: 2,
+                recovered: true,
+            }]
+        );
+        assert!(telemetry.take().is_empty());
+    }
+}
@@ -1,9 +1,85 @@
-//! Size parsing and manipulation utilities.
+//! Size parsing and formatting utilities.
 //!
 //! This module provides functions for parsing human-readable size strings
-//! (like "100MB" or "1.5GiB") into byte values.
+//! (like "100MB" or "1.5GiB") into byte values, and for formatting byte
+//! counts for display, in decimal units up to exabytes.
 
-use anyhow::Result;
+use std::sync::OnceLock;
+
+use crate::error::{Error, Result};
+use humansize::{DECIMAL, FormatSizeOptions};
+
+/// How sizes are displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SizeFormat {
+    /// Two decimals, none for whole values (e.g. `4.20 TB`, `4 GB`).
+    #[default]
+    Short,
+
+    /// Always two decimals, followed by the exact number of bytes, for
+    /// audits (e.g. `4.20 TB (4,200,000,000,000 bytes)`).
+    Exact,
+
+    /// `unknown`, for sizes that were not calculated (`--no-size`).
+    Unknown,
+}
+
+/// Format chosen with [`set_format`].
+static FORMAT: OnceLock<SizeFormat> = OnceLock::new();
+
+/// Use `format` for the sizes displayed for the rest of the process.
+///
+/// Only the first call has an effect.
+pub fn set_format(format: SizeFormat) {
+    let _ = FORMAT.set(format);
+}
+
+/// Format `bytes` for display, in the format set with [`set_format`].
+///
+/// # Examples
+///
+/// ```
+/// # use clean_dev_dirs::utils::format_size;
+/// assert_eq!(format_size(1_500_000), "1.50 MB");
+/// assert_eq!(format_size(3_200_000_000_000_000), "3.20 PB");
+/// ```
+#[must_use]
+pub fn format_size(bytes: u64) -> String {
+    format_size_as(bytes, FORMAT.get().copied().unwrap_or_default())
+}
+
+/// Format `bytes` for display in `format`.
+#[must_use]
+pub fn format_size_as(bytes: u64, format: SizeFormat) -> String {
+    match format {
+        SizeFormat::Short => humansize::format_size(bytes, DECIMAL),
+        SizeFormat::Exact => {
+            // Whole bytes have no decimals to show
+            let zeroes = if bytes < 1000 { 0 } else { 2 };
+            let options = FormatSizeOptions::from(DECIMAL).decimal_zeroes(zeroes);
+            let unit = if bytes == 1 { "byte" } else { "bytes" };
+            format!(
+                "{} ({} {unit})",
+                humansize::format_size(bytes, options),
+                group_thousands(bytes)
+            )
+        }
+        SizeFormat::Unknown => "unknown".to_string(),
+    }
+}
+
+/// Write `number` with commas between groups of thousands.
+fn group_thousands(number: u64) -> String {
+    let digits = number.to_string();
+    let mut grouped = String::with_capacity(digits.len() * 4 / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
 
 /// Parse a human-readable size string into bytes.
 ///
@@ -17,7 +93,7 @@ use anyhow::Result;
 /// # Returns
 ///
 /// - `Ok(u64)` - The size in bytes
-/// - `Err(anyhow::Error)` - If the string format is invalid or causes overflow
+/// - `Err(Error::SizeParse)` - If the string format is invalid or causes overflow
 ///
 /// # Errors
 ///
@@ -31,7 +107,7 @@ use anyhow::Result;
 ///
 /// ```
 /// # use clean_dev_dirs::utils::parse_size;
-/// # use anyhow::Result;
+/// # use crate::error::{Error, Result};
 /// # fn main() -> Result<()> {
 /// assert_eq!(parse_size("100KB")?, 100_000);
 /// assert_eq!(parse_size("1.5MB")?, 1_500_000);
@@ -84,7 +160,9 @@ fn parse_size_unit(size_str: &str) -> (&str, u64) {
 fn parse_decimal_size(number_str: &str, multiplier: u64) -> Result<u64> {
     let parts: Vec<&str> = number_str.split('.').collect();
     if parts.len() != 2 {
-        return Err(anyhow::anyhow!("Invalid decimal format: {number_str}"));
+        return Err(Error::SizeParse(format!(
+            "Invalid decimal format: {number_str}"
+        )));
     }
 
     let integer_part: u64 = parts[0].parse().unwrap_or(0);
@@ -101,37 +179,90 @@ fn parse_decimal_size(number_str: &str, multiplier: u64) -> Result<u64> {
 fn parse_fractional_part(fractional_str: &str) -> Result<u64> {
     let fractional_digits = fractional_str.len();
     if fractional_digits > 9 {
-        return Err(anyhow::anyhow!("Too many decimal places: {fractional_str}"));
+        return Err(Error::SizeParse(format!(
+            "Too many decimal places: {fractional_str}"
+        )));
     }
 
-    let fractional_part: u64 = fractional_str.parse()?;
-    let fractional_multiplier = 10u64.pow(9 - u32::try_from(fractional_digits)?);
+    let fractional_part = parse_number(fractional_str)?;
+    // At most 9 digits, checked above
+    let fractional_multiplier = 10u64.pow(u32::try_from(9 - fractional_digits).unwrap_or(0));
 
     Ok(fractional_part * fractional_multiplier)
 }
 
 /// Parse an integer size value.
 fn parse_integer_size(number_str: &str, multiplier: u64) -> Result<u64> {
-    let number: u64 = number_str.parse()?;
+    let number = parse_number(number_str)?;
     multiply_with_overflow_check(number, multiplier)
 }
 
+/// Parse the digits of a size.
+fn parse_number(number_str: &str) -> Result<u64> {
+    number_str
+        .parse()
+        .map_err(|e| Error::SizeParse(format!("Invalid size number {number_str:?}: {e}")))
+}
+
 /// Multiply two values with overflow checking.
 fn multiply_with_overflow_check(a: u64, b: u64) -> Result<u64> {
     a.checked_mul(b)
-        .ok_or_else(|| anyhow::anyhow!("Size value overflow: {a} * {b}"))
+        .ok_or_else(|| Error::SizeParse(format!("Size value overflow: {a} * {b}")))
 }
 
 /// Add two values with overflow checking.
 fn add_with_overflow_check(a: u64, b: u64) -> Result<u64> {
     a.checked_add(b)
-        .ok_or_else(|| anyhow::anyhow!("Final overflow: {a} + {b}"))
+        .ok_or_else(|| Error::SizeParse(format!("Final overflow: {a} + {b}")))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_size_scales_past_gigabytes() {
+        assert_eq!(format_size_as(999, SizeFormat::Short), "999 B");
+        assert_eq!(format_size_as(4_000_000_000, SizeFormat::Short), "4 GB");
+        assert_eq!(
+            format_size_as(4_200_000_000_000, SizeFormat::Short),
+            "4.20 TB"
+        );
+        assert_eq!(
+            format_size_as(12_340_000_000_000_000, SizeFormat::Short),
+            "12.34 PB"
+        );
+        assert_eq!(format_size_as(u64::MAX, SizeFormat::Short), "18.45 EB");
+    }
+
+    #[test]
+    fn test_format_size_exact() {
+        assert_eq!(
+            format_size_as(4_000_000_000_000, SizeFormat::Exact),
+            "4.00 TB (4,000,000,000,000 bytes)"
+        );
+        assert_eq!(format_size_as(1, SizeFormat::Exact), "1 B (1 byte)");
+        assert_eq!(format_size_as(999, SizeFormat::Exact), "999 B (999 bytes)");
+        assert_eq!(
+            format_size_as(1500, SizeFormat::Exact),
+            "1.50 kB (1,500 bytes)"
+        );
+    }
+
+    #[test]
+    fn test_format_size_unknown() {
+        assert_eq!(format_size_as(0, SizeFormat::Unknown), "unknown");
+        assert_eq!(format_size_as(1500, SizeFormat::Unknown), "unknown");
+    }
+
+    #[test]
+    fn test_group_thousands() {
+        assert_eq!(group_thousands(0), "0");
+        assert_eq!(group_thousands(100), "100");
+        assert_eq!(group_thousands(1000), "1,000");
+        assert_eq!(group_thousands(1_234_567), "1,234,567");
+    }
+
     #[test]
     fn test_parse_size_zero() {
         assert_eq!(parse_size("0").unwrap(), 0);
@@ -0,0 +1,153 @@
+//! Terminal width detection and width-aware line layout.
+//!
+//! Long project paths make list lines wrap badly on narrow terminals. Lines
+//! are therefore fitted to the terminal width: paths are shortened from the
+//! front (the end of a path identifies it best) and other lines are cut with
+//! an ellipsis. When stdout is not a terminal, nothing is shortened unless a
+//! width was set explicitly with `--width`.
+
+use std::sync::OnceLock;
+
+use console::{Term, measure_text_width, truncate_str};
+
+/// Marker inserted where text was cut.
+const ELLIPSIS: &str = "…";
+
+/// Columns taken by the cursor, indentation and checkbox (`>   [x] `) in
+/// front of each option of an interactive selection list.
+pub const SELECTION_MARGIN: usize = 8;
+
+/// Columns always left to a shortened path, however narrow the terminal.
+const MIN_PATH_WIDTH: usize = 12;
+
+/// Width set with `--width`, overriding detection. `0` means unlimited.
+static WIDTH_OVERRIDE: OnceLock<usize> = OnceLock::new();
+
+/// Override the detected terminal width for the rest of the process, if
+/// `width` is given.
+///
+/// A width of `0` disables fitting altogether. Only the first override has
+/// an effect.
+pub fn override_width(width: Option<usize>) {
+    if let Some(width) = width {
+        let _ = WIDTH_OVERRIDE.set(width);
+    }
+}
+
+/// Width available for output lines, in columns.
+///
+/// Returns the width set with [`override_width`], or else the width of the
+/// terminal attached to stdout. `None` means lines must not be shortened.
+#[must_use]
+pub fn width() -> Option<usize> {
+    match WIDTH_OVERRIDE.get() {
+        Some(0) => None,
+        Some(&width) => Some(width),
+        None => Term::stdout()
+            .size_checked()
+            .map(|(_, columns)| usize::from(columns)),
+    }
+}
+
+/// Cut `line` to the output width, ending it with an ellipsis if it was too
+/// long.
+///
+/// Color codes are preserved and not counted towards the width.
+#[must_use]
+pub fn fit(line: &str) -> String {
+    fit_to(line, width())
+}
+
+/// Cut `line` to `width` columns, ending it with an ellipsis if it was too
+/// long. `None` leaves the line untouched.
+#[must_use]
+pub fn fit_to(line: &str, width: Option<usize>) -> String {
+    width.map_or_else(
+        || line.to_string(),
+        |width| truncate_str(line, width, ELLIPSIS).into_owned(),
+    )
+}
+
+/// Shorten `path` so that a line holding it and `rest` (the other text of
+/// the line) fits in `width` columns. `None` leaves the path untouched.
+///
+/// At least a few columns are always left to the path, so on very narrow
+/// terminals the line may still not fit.
+#[must_use]
+pub fn fit_path_to(path: &str, rest: &str, width: Option<usize>) -> String {
+    width.map_or_else(
+        || path.to_string(),
+        |width| {
+            let budget = width.saturating_sub(measure_text_width(rest));
+            shorten_path(path, budget.max(MIN_PATH_WIDTH))
+        },
+    )
+}
+
+/// Shorten `path` to at most `max_width` columns by replacing its beginning
+/// with an ellipsis.
+///
+/// The cut is moved to the next path separator when that still leaves
+/// something to show, so `/home/me/dev/app/target` becomes `…/app/target`
+/// rather than `…e/dev/app/target`.
+#[must_use]
+pub fn shorten_path(path: &str, max_width: usize) -> String {
+    if measure_text_width(path) <= max_width {
+        return path.to_string();
+    }
+
+    let budget = max_width.saturating_sub(measure_text_width(ELLIPSIS));
+    let mut start = path.len();
+    for (index, _) in path.char_indices().rev() {
+        if measure_text_width(&path[index..]) > budget {
+            break;
+        }
+        start = index;
+    }
+
+    let tail = &path[start..];
+    let tail = tail
+        .find(['/', '\\'])
+        .filter(|&separator| separator > 0 && separator + 1 < tail.len())
+        .map_or(tail, |separator| &tail[separator..]);
+
+    format!("{ELLIPSIS}{tail}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_to() {
+        assert_eq!(fit_to("short line", Some(20)), "short line");
+        assert_eq!(fit_to("a rather long line", Some(10)), "a rather …");
+        assert_eq!(fit_to("a rather long line", None), "a rather long line");
+    }
+
+    #[test]
+    fn test_fit_path_to_reserves_room_for_the_rest_of_the_line() {
+        let path = "/home/me/dev/app/target";
+        assert_eq!(fit_path_to(path, "  (1.2 GB)", Some(24)), "…/app/target");
+        assert_eq!(fit_path_to(path, "  (1.2 GB)", Some(80)), path);
+        assert_eq!(fit_path_to(path, "  (1.2 GB)", None), path);
+        // The path keeps a minimum width on absurdly narrow terminals
+        assert_eq!(fit_path_to(path, "  (1.2 GB)", Some(5)), "…/app/target");
+    }
+
+    #[test]
+    fn test_shorten_path_keeps_short_paths() {
+        assert_eq!(shorten_path("/dev/app", 20), "/dev/app");
+    }
+
+    #[test]
+    fn test_shorten_path_cuts_at_separators() {
+        assert_eq!(shorten_path("/home/me/dev/app/target", 14), "…/app/target");
+        assert_eq!(shorten_path(r"C:\Users\me\dev\app", 10), r"…\dev\app");
+    }
+
+    #[test]
+    fn test_shorten_path_cuts_long_components() {
+        assert_eq!(shorten_path("/a/very-long-component", 8), "…mponent");
+    }
+}
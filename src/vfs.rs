@@ -0,0 +1,902 @@
+//! File system backends.
+//!
+//! The scanner and the cleaner access the file system through the
+//! [`FileSystem`] trait rather than [`std::fs`] directly. [`RealFs`] is the
+//! local file system; [`MemoryFs`] is an in-memory tree, so that traversal,
+//! project detection and deletion can be tested quickly and deterministically
+//! without creating temporary directories. Other backends (e.g. a remote
+//! machine) only need to implement the handful of required methods.
+//!
+//! Operations that only make sense on the local file system, such as moving
+//! to the trash, running `cargo clean` or preserving executables, still go
+//! to it directly.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use walkdir::WalkDir;
+
+use crate::utils::{mounts, paths};
+
+/// Kind of a file system entry, without following symbolic links.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A regular file.
+    File,
+
+    /// A directory.
+    Dir,
+
+    /// A symbolic link, or anything else that is neither a file nor a
+    /// directory.
+    Other,
+}
+
+/// An entry found while walking a tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// Path of the entry, starting with the root of the walk.
+    pub path: PathBuf,
+
+    /// Number of directories between the root of the walk and the entry
+    /// (`0` for the root itself).
+    pub depth: usize,
+
+    /// Kind of the entry.
+    pub kind: EntryKind,
+}
+
+impl Entry {
+    /// Whether the entry is a directory.
+    #[must_use]
+    pub fn is_dir(&self) -> bool {
+        self.kind == EntryKind::Dir
+    }
+
+    /// Whether the entry is a regular file.
+    #[must_use]
+    pub fn is_file(&self) -> bool {
+        self.kind == EntryKind::File
+    }
+}
+
+/// The space a file takes up on disk, see [`FileSystem::disk_usage`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// Bytes allocated to the file: less than its length for a sparse file,
+    /// more for a file that does not fill its last block.
+    pub bytes: u64,
+
+    /// Device and inode numbers of a file with several hard links, whose
+    /// space is only taken up once.
+    pub link: Option<(u64, u64)>,
+}
+
+/// An entry that could not be read while walking a tree.
+#[derive(Debug)]
+pub struct WalkError {
+    /// Path of the entry.
+    pub path: PathBuf,
+
+    /// Why it could not be read.
+    pub error: io::Error,
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.error)
+    }
+}
+
+/// A walk over a tree, see [`FileSystem::walk`].
+pub type Walk<'a> = Box<dyn Iterator<Item = Result<Entry, WalkError>> + 'a>;
+
+/// Tells [`FileSystem::walk_pruned`] which directories not to descend into.
+pub type Prune<'a> = Box<dyn Fn(&Entry) -> bool + 'a>;
+
+/// How [`FileSystem::walk_pruned`] treats links and mount points. Backends
+/// without them ignore these options.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WalkOptions {
+    /// Walk symbolic links as the entries they point to.
+    pub follow_links: bool,
+
+    /// Do not descend into directories on another file system than the
+    /// root, e.g. network mounts or backup volumes (`--one-file-system`).
+    pub same_file_system: bool,
+}
+
+/// Access to a file system.
+///
+/// Symbolic links are never followed: they are reported as
+/// [`EntryKind::Other`] and removed rather than their target.
+pub trait FileSystem: Send + Sync {
+    /// Return the kind of the entry at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no entry at `path` or it cannot be read.
+    fn entry_kind(&self, path: &Path) -> io::Result<EntryKind>;
+
+    /// Return the size in bytes of the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no file at `path` or it cannot be read.
+    fn file_len(&self, path: &Path) -> io::Result<u64>;
+
+    /// Return the paths of the entries of the directory at `path`, in no
+    /// particular order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no directory at `path` or it cannot be
+    /// read.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Read the file at `path` as UTF-8 text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no file at `path`, it cannot be read, or
+    /// it is not valid UTF-8.
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// Remove the file, or empty directory, at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be removed, e.g.
+    /// [`ErrorKind::DirectoryNotEmpty`] for a directory that still has
+    /// entries.
+    fn remove_entry(&self, path: &Path, is_dir: bool) -> io::Result<()>;
+
+    /// Return the total size of the files below `path`, for trees too deep
+    /// to be walked by path (see [`paths::is_too_long`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree cannot be measured; backends without
+    /// path length limits return [`ErrorKind::Unsupported`].
+    fn size_deep(&self, _path: &Path) -> io::Result<u64> {
+        Err(io::Error::from(ErrorKind::Unsupported))
+    }
+
+    /// Return the space the entry at `path` takes up on disk.
+    ///
+    /// Defaults to the length of a file and nothing for anything else, for
+    /// backends without blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no entry at `path` or it cannot be read.
+    fn disk_usage(&self, path: &Path) -> io::Result<DiskUsage> {
+        let bytes = match self.entry_kind(path)? {
+            EntryKind::File => self.file_len(path)?,
+            EntryKind::Dir | EntryKind::Other => 0,
+        };
+        Ok(DiskUsage { bytes, link: None })
+    }
+
+    /// Return the last modification time of the entry at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no entry at `path` or it cannot be read;
+    /// backends without modification times return
+    /// [`ErrorKind::Unsupported`].
+    fn modified(&self, _path: &Path) -> io::Result<SystemTime> {
+        Err(io::Error::from(ErrorKind::Unsupported))
+    }
+
+    /// Make `path` usable by this file system whatever its length and names,
+    /// see [`paths::extended`].
+    fn extended<'a>(&self, path: &'a Path) -> Cow<'a, Path> {
+        Cow::Borrowed(path)
+    }
+
+    /// Return the absolute form of `path`, with symbolic links resolved
+    /// where the backend has them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` cannot be resolved, e.g. because it does
+    /// not exist.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        std::path::absolute(path)
+    }
+
+    /// Check whether the directory at `path` is a mount point, see
+    /// [`mounts::is_mount_point`]. Backends without mounts have none.
+    fn is_mount_point(&self, _path: &Path) -> bool {
+        false
+    }
+
+    /// Check whether there is an entry at `path`.
+    fn exists(&self, path: &Path) -> bool {
+        self.entry_kind(path).is_ok()
+    }
+
+    /// Check whether there is a directory at `path`.
+    fn is_dir(&self, path: &Path) -> bool {
+        self.entry_kind(path)
+            .is_ok_and(|kind| kind == EntryKind::Dir)
+    }
+
+    /// Check whether there is a regular file at `path`.
+    fn is_file(&self, path: &Path) -> bool {
+        self.entry_kind(path)
+            .is_ok_and(|kind| kind == EntryKind::File)
+    }
+
+    /// Walk the tree at `root`, yielding `root` itself first and every
+    /// directory before its entries, or after them if `contents_first`.
+    ///
+    /// Entries that cannot be read are yielded as errors and the walk goes
+    /// on with the rest of the tree.
+    fn walk(&self, root: &Path, contents_first: bool) -> Walk<'_> {
+        Box::new(TreeWalk {
+            fs: self,
+            pending: vec![Pending::Visit(root.to_path_buf(), 0)],
+            contents_first,
+            prune: None,
+        })
+    }
+
+    /// Walk the tree at `root` like [`FileSystem::walk`], every directory
+    /// before its entries, but without descending into the directories for
+    /// which `prune` returns `true`. Those directories are still yielded.
+    ///
+    /// `options` tells whether links are followed and mount points crossed.
+    fn walk_pruned<'a>(&'a self, root: &Path, _options: WalkOptions, prune: Prune<'a>) -> Walk<'a> {
+        Box::new(TreeWalk {
+            fs: self,
+            pending: vec![Pending::Visit(root.to_path_buf(), 0)],
+            contents_first: false,
+            prune: Some(prune),
+        })
+    }
+
+    /// Return the total size of the files below `path`, skipping the
+    /// entries that cannot be read.
+    fn tree_size(&self, path: &Path) -> u64 {
+        self.walk(path, false)
+            .filter_map(Result::ok)
+            .filter(Entry::is_file)
+            .filter_map(|entry| self.file_len(&entry.path).ok())
+            .sum()
+    }
+}
+
+/// A step of a [`TreeWalk`].
+enum Pending {
+    /// An entry to read, with its depth.
+    Visit(PathBuf, usize),
+
+    /// A directory whose entries were all yielded, to yield last.
+    Leave(Entry),
+
+    /// An error to yield.
+    Failed(WalkError),
+}
+
+/// Depth-first walk built on [`FileSystem::read_dir`], with the entries of
+/// each directory in lexicographic order.
+struct TreeWalk<'a, F: FileSystem + ?Sized> {
+    fs: &'a F,
+    pending: Vec<Pending>,
+    contents_first: bool,
+    prune: Option<Prune<'a>>,
+}
+
+impl<F: FileSystem + ?Sized> Iterator for TreeWalk<'_, F> {
+    type Item = Result<Entry, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (path, depth) = match self.pending.pop()? {
+                Pending::Visit(path, depth) => (path, depth),
+                Pending::Leave(entry) => return Some(Ok(entry)),
+                Pending::Failed(error) => return Some(Err(error)),
+            };
+
+            let kind = match self.fs.entry_kind(&path) {
+                Ok(kind) => kind,
+                Err(error) => return Some(Err(WalkError { path, error })),
+            };
+            let entry = Entry { path, depth, kind };
+            if !entry.is_dir() || self.prune.as_ref().is_some_and(|prune| prune(&entry)) {
+                return Some(Ok(entry));
+            }
+
+            let children = self.fs.read_dir(&entry.path);
+            let path = entry.path.clone();
+            let entry = if self.contents_first {
+                self.pending.push(Pending::Leave(entry));
+                None
+            } else {
+                Some(entry)
+            };
+            match children {
+                Ok(mut children) => {
+                    children.sort_unstable_by(|a, b| b.cmp(a));
+                    self.pending.extend(
+                        children
+                            .into_iter()
+                            .map(|child| Pending::Visit(child, depth + 1)),
+                    );
+                }
+                Err(error) => self
+                    .pending
+                    .push(Pending::Failed(WalkError { path, error })),
+            }
+            if let Some(entry) = entry {
+                return Some(Ok(entry));
+            }
+        }
+    }
+}
+
+/// The local file system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RealFs;
+
+impl FileSystem for RealFs {
+    fn entry_kind(&self, path: &Path) -> io::Result<EntryKind> {
+        let file_type = fs::symlink_metadata(path)?.file_type();
+        Ok(if file_type.is_dir() {
+            EntryKind::Dir
+        } else if file_type.is_file() {
+            EntryKind::File
+        } else {
+            EntryKind::Other
+        })
+    }
+
+    fn file_len(&self, path: &Path) -> io::Result<u64> {
+        fs::symlink_metadata(path).map(|metadata| metadata.len())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    /// Falls back to [`paths::remove_deep`] when the path is too long.
+    fn remove_entry(&self, path: &Path, is_dir: bool) -> io::Result<()> {
+        paths::remove_entry(path, is_dir)
+    }
+
+    fn size_deep(&self, path: &Path) -> io::Result<u64> {
+        paths::size_deep(path)
+    }
+
+    /// Counts the blocks allocated (`st_blocks`), like `du`.
+    #[cfg(unix)]
+    fn disk_usage(&self, path: &Path) -> io::Result<DiskUsage> {
+        use std::os::unix::fs::MetadataExt;
+
+        let metadata = fs::symlink_metadata(path)?;
+        Ok(DiskUsage {
+            // `st_blocks` is in 512-byte units, whatever the block size
+            bytes: metadata.blocks() * 512,
+            link: (metadata.is_file() && metadata.nlink() > 1)
+                .then(|| (metadata.dev(), metadata.ino())),
+        })
+    }
+
+    /// Rounds the length up to the default NTFS cluster size; compressed
+    /// files and hard links are not recognized.
+    #[cfg(windows)]
+    fn disk_usage(&self, path: &Path) -> io::Result<DiskUsage> {
+        const CLUSTER_SIZE: u64 = 4096;
+
+        Ok(DiskUsage {
+            bytes: fs::symlink_metadata(path)?
+                .len()
+                .next_multiple_of(CLUSTER_SIZE),
+            link: None,
+        })
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        fs::symlink_metadata(path)?.modified()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+
+    fn extended<'a>(&self, path: &'a Path) -> Cow<'a, Path> {
+        paths::extended(path)
+    }
+
+    fn is_mount_point(&self, path: &Path) -> bool {
+        mounts::is_mount_point(path)
+    }
+
+    /// A `root` that is a symbolic link or an NTFS junction is yielded as
+    /// such and not walked into, so that sizing or removing it never reaches
+    /// its target. File systems mounted below `root` are not walked into
+    /// either, nor yielded.
+    fn walk(&self, root: &Path, contents_first: bool) -> Walk<'_> {
+        let root = root.to_path_buf();
+        Box::new(
+            WalkDir::new(&root)
+                .follow_root_links(false)
+                .same_file_system(true)
+                .contents_first(contents_first)
+                .into_iter()
+                .map(move |entry| walk_entry(entry, &root)),
+        )
+    }
+
+    /// On Windows, the tree is read through the `\\?\` form of `root` (see
+    /// [`paths::extended`]), so that paths longer than 260 characters and
+    /// names ending with a dot or a space are walked too. Entries are still
+    /// yielded below `root` as given.
+    fn walk_pruned<'a>(&'a self, root: &Path, options: WalkOptions, prune: Prune<'a>) -> Walk<'a> {
+        let walked = paths::extended(root).into_owned();
+        Box::new(PrunedWalkDir {
+            inner: WalkDir::new(&walked)
+                .follow_links(options.follow_links)
+                .same_file_system(options.same_file_system)
+                .into_iter(),
+            root: root.to_path_buf(),
+            walked,
+            prune,
+        })
+    }
+}
+
+/// Convert an entry of a [`WalkDir`] walk of `root`.
+fn walk_entry(entry: walkdir::Result<walkdir::DirEntry>, root: &Path) -> Result<Entry, WalkError> {
+    match entry {
+        Ok(entry) => {
+            let file_type = entry.file_type();
+            let kind = if file_type.is_dir() {
+                EntryKind::Dir
+            } else if file_type.is_file() {
+                EntryKind::File
+            } else {
+                EntryKind::Other
+            };
+            Ok(Entry {
+                depth: entry.depth(),
+                path: entry.into_path(),
+                kind,
+            })
+        }
+        Err(error) => {
+            let path = error.path().unwrap_or(root).to_path_buf();
+            let error = error
+                .into_io_error()
+                .unwrap_or_else(|| io::Error::other("file system loop"));
+            Err(WalkError { path, error })
+        }
+    }
+}
+
+/// [`WalkDir`] walk not descending into the directories `prune` selects.
+struct PrunedWalkDir<'a> {
+    inner: walkdir::IntoIter,
+    root: PathBuf,
+
+    /// `root` as walked, in its extended form on Windows.
+    walked: PathBuf,
+    prune: Prune<'a>,
+}
+
+impl PrunedWalkDir<'_> {
+    /// `path`, found below the walked root, below `root` instead.
+    fn below_root(&self, path: PathBuf) -> PathBuf {
+        match path.strip_prefix(&self.walked) {
+            Ok(rest) if self.walked != self.root => {
+                if rest.as_os_str().is_empty() {
+                    self.root.clone()
+                } else {
+                    self.root.join(rest)
+                }
+            }
+            _ => path,
+        }
+    }
+}
+
+impl Iterator for PrunedWalkDir<'_> {
+    type Item = Result<Entry, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = match walk_entry(self.inner.next()?, &self.root) {
+            Ok(entry) => Ok(Entry {
+                path: self.below_root(entry.path),
+                ..entry
+            }),
+            Err(error) => Err(WalkError {
+                path: self.below_root(error.path),
+                ..error
+            }),
+        };
+        if entry
+            .as_ref()
+            .is_ok_and(|entry| entry.is_dir() && (self.prune)(entry))
+        {
+            self.inner.skip_current_dir();
+        }
+        Some(entry)
+    }
+}
+
+/// A node of a [`MemoryFs`].
+#[derive(Clone, Debug)]
+enum Node {
+    Dir,
+    File { contents: String, len: u64 },
+}
+
+/// An in-memory file system, for tests.
+///
+/// Entries are added with the `with_*` builder methods, which create the
+/// missing parent directories. Paths are used as given, so they should be
+/// absolute, e.g. `/work/app/Cargo.toml`.
+///
+/// ```
+/// # use std::path::Path;
+/// # use clean_dev_dirs::vfs::{FileSystem, MemoryFs};
+/// let fs = MemoryFs::new()
+///     .with_file("/work/app/Cargo.toml", "[package]\nname = \"app\"")
+///     .with_file_of_len("/work/app/target/debug/app", 4096);
+///
+/// assert!(fs.is_dir(Path::new("/work/app/target")));
+/// assert_eq!(fs.tree_size(Path::new("/work/app/target")), 4096);
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    nodes: Mutex<BTreeMap<PathBuf, Node>>,
+
+    /// Entries whose removal fails with [`ErrorKind::PermissionDenied`].
+    locked: HashSet<PathBuf>,
+
+    /// Directories reported as mount points.
+    mount_points: HashSet<PathBuf>,
+}
+
+impl MemoryFs {
+    /// Create an empty file system.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a directory at `path`.
+    #[must_use]
+    pub fn with_dir(self, path: impl AsRef<Path>) -> Self {
+        self.insert(path.as_ref(), Node::Dir);
+        self
+    }
+
+    /// Add a file at `path` holding `contents`.
+    #[must_use]
+    pub fn with_file(self, path: impl AsRef<Path>, contents: &str) -> Self {
+        let len = contents.len() as u64;
+        self.insert(
+            path.as_ref(),
+            Node::File {
+                contents: contents.to_string(),
+                len,
+            },
+        );
+        self
+    }
+
+    /// Add an empty-looking file at `path` that reports a size of `len`
+    /// bytes, e.g. a large build artifact.
+    #[must_use]
+    pub fn with_file_of_len(self, path: impl AsRef<Path>, len: u64) -> Self {
+        self.insert(
+            path.as_ref(),
+            Node::File {
+                contents: String::new(),
+                len,
+            },
+        );
+        self
+    }
+
+    /// Make the removal of the entry at `path` fail, like a file locked by
+    /// another process.
+    #[must_use]
+    pub fn with_locked(mut self, path: impl AsRef<Path>) -> Self {
+        self.locked.insert(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Report the directory at `path` as a mount point, like a docker
+    /// volume.
+    #[must_use]
+    pub fn with_mount_point(mut self, path: impl AsRef<Path>) -> Self {
+        self.mount_points.insert(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Insert `node` at `path`, creating the missing parent directories.
+    fn insert(&self, path: &Path, node: Node) {
+        let mut nodes = self.nodes();
+        for ancestor in path.ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty() {
+                break;
+            }
+            nodes.entry(ancestor.to_path_buf()).or_insert(Node::Dir);
+        }
+        nodes.insert(path.to_path_buf(), node);
+    }
+
+    fn nodes(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, Node>> {
+        self.nodes.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn node(&self, path: &Path) -> io::Result<Node> {
+        self.nodes()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::from(ErrorKind::NotFound))
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn entry_kind(&self, path: &Path) -> io::Result<EntryKind> {
+        Ok(match self.node(path)? {
+            Node::Dir => EntryKind::Dir,
+            Node::File { .. } => EntryKind::File,
+        })
+    }
+
+    fn file_len(&self, path: &Path) -> io::Result<u64> {
+        match self.node(path)? {
+            Node::File { len, .. } => Ok(len),
+            Node::Dir => Err(io::Error::from(ErrorKind::IsADirectory)),
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        if !matches!(self.node(path)?, Node::Dir) {
+            return Err(io::Error::from(ErrorKind::NotADirectory));
+        }
+        Ok(self
+            .nodes()
+            .range(path.to_path_buf()..)
+            .skip(1)
+            .take_while(|(child, _)| child.starts_with(path))
+            .filter(|(child, _)| child.parent() == Some(path))
+            .map(|(child, _)| child.clone())
+            .collect())
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match self.node(path)? {
+            Node::File { contents, .. } => Ok(contents),
+            Node::Dir => Err(io::Error::from(ErrorKind::IsADirectory)),
+        }
+    }
+
+    fn is_mount_point(&self, path: &Path) -> bool {
+        self.mount_points.contains(path)
+    }
+
+    fn remove_entry(&self, path: &Path, is_dir: bool) -> io::Result<()> {
+        let kind = self.entry_kind(path)?;
+        let error = if self.locked.contains(path) {
+            ErrorKind::PermissionDenied
+        } else if kind == EntryKind::Dir && !is_dir {
+            ErrorKind::IsADirectory
+        } else if kind != EntryKind::Dir && is_dir {
+            ErrorKind::NotADirectory
+        } else if is_dir && !self.read_dir(path)?.is_empty() {
+            ErrorKind::DirectoryNotEmpty
+        } else {
+            self.nodes().remove(path);
+            return Ok(());
+        };
+        Err(io::Error::from(error))
+    }
+}
+
+/// A shared [`FileSystem`], the local one by default.
+#[derive(Clone)]
+pub struct FsHandle(Arc<dyn FileSystem>);
+
+impl FsHandle {
+    /// Share `fs`.
+    #[must_use]
+    pub fn new(fs: Arc<dyn FileSystem>) -> Self {
+        Self(fs)
+    }
+}
+
+impl Default for FsHandle {
+    fn default() -> Self {
+        Self(Arc::new(RealFs))
+    }
+}
+
+impl fmt::Debug for FsHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FsHandle")
+    }
+}
+
+impl Deref for FsHandle {
+    type Target = dyn FileSystem;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample() -> MemoryFs {
+        MemoryFs::new()
+            .with_file("/work/b/file.txt", "hello")
+            .with_file_of_len("/work/a/big.bin", 1000)
+            .with_dir("/work/a/empty")
+    }
+
+    fn walked(fs: &dyn FileSystem, root: &str, contents_first: bool) -> Vec<String> {
+        fs.walk(Path::new(root), contents_first)
+            .map(|entry| entry.unwrap().path.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_memory_fs_entries() {
+        let fs = sample();
+
+        assert!(fs.is_dir(Path::new("/work/a")));
+        assert!(fs.is_file(Path::new("/work/b/file.txt")));
+        assert!(!fs.exists(Path::new("/work/c")));
+        assert_eq!(
+            fs.read_to_string(Path::new("/work/b/file.txt")).unwrap(),
+            "hello"
+        );
+        assert_eq!(fs.file_len(Path::new("/work/a/big.bin")).unwrap(), 1000);
+
+        let mut children = fs.read_dir(Path::new("/work")).unwrap();
+        children.sort();
+        assert_eq!(
+            children,
+            [PathBuf::from("/work/a"), PathBuf::from("/work/b")]
+        );
+        assert_eq!(fs.tree_size(Path::new("/work")), 1005);
+    }
+
+    #[test]
+    fn test_walk_orders() {
+        let fs = sample();
+
+        assert_eq!(
+            walked(&fs, "/work", false),
+            [
+                "/work",
+                "/work/a",
+                "/work/a/big.bin",
+                "/work/a/empty",
+                "/work/b",
+                "/work/b/file.txt",
+            ]
+        );
+        assert_eq!(
+            walked(&fs, "/work", true),
+            [
+                "/work/a/big.bin",
+                "/work/a/empty",
+                "/work/a",
+                "/work/b/file.txt",
+                "/work/b",
+                "/work",
+            ]
+        );
+        let depths: Vec<usize> = fs
+            .walk(Path::new("/work/a"), false)
+            .map(|entry| entry.unwrap().depth)
+            .collect();
+        assert_eq!(depths, [0, 1, 1]);
+    }
+
+    #[test]
+    fn test_walk_pruned() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("work");
+        fs::create_dir_all(root.join("a/empty")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        fs::write(root.join("b/file.txt"), "hello").unwrap();
+        let memory = MemoryFs::new()
+            .with_dir(root.join("a/empty"))
+            .with_file(root.join("b/file.txt"), "hello");
+
+        for fs in [&RealFs as &dyn FileSystem, &memory] {
+            let mut walked: Vec<PathBuf> = fs
+                .walk_pruned(
+                    &root,
+                    WalkOptions::default(),
+                    Box::new(|entry| entry.path.ends_with("a")),
+                )
+                .map(|entry| entry.unwrap().path)
+                .collect();
+            walked.sort();
+            assert_eq!(
+                walked,
+                [
+                    root.clone(),
+                    root.join("a"),
+                    root.join("b"),
+                    root.join("b/file.txt")
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_memory_fs_removal() {
+        let fs = sample().with_locked("/work/b/file.txt");
+
+        let not_empty = fs.remove_entry(Path::new("/work/a"), true).unwrap_err();
+        assert_eq!(not_empty.kind(), ErrorKind::DirectoryNotEmpty);
+        let locked = fs
+            .remove_entry(Path::new("/work/b/file.txt"), false)
+            .unwrap_err();
+        assert_eq!(locked.kind(), ErrorKind::PermissionDenied);
+
+        fs.remove_entry(Path::new("/work/a/big.bin"), false)
+            .unwrap();
+        fs.remove_entry(Path::new("/work/a/empty"), true).unwrap();
+        fs.remove_entry(Path::new("/work/a"), true).unwrap();
+        assert!(!fs.exists(Path::new("/work/a")));
+        assert_eq!(walked(&fs, "/work", false).len(), 3);
+    }
+
+    #[test]
+    fn test_real_fs_matches_memory_fs() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("work");
+        fs::create_dir_all(root.join("a/empty")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        fs::write(root.join("b/file.txt"), "hello").unwrap();
+
+        let real = RealFs;
+        assert!(real.is_dir(&root.join("a")));
+        assert!(real.is_file(&root.join("b/file.txt")));
+        assert_eq!(
+            real.read_to_string(&root.join("b/file.txt")).unwrap(),
+            "hello"
+        );
+        assert_eq!(real.tree_size(&root), 5);
+
+        let mut walked: Vec<(PathBuf, usize, EntryKind)> = real
+            .walk(&root, false)
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.path, entry.depth, entry.kind)
+            })
+            .collect();
+        walked.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(walked.len(), 5);
+        assert_eq!(walked[0], (root.clone(), 0, EntryKind::Dir));
+        assert_eq!(walked[4], (root.join("b/file.txt"), 2, EntryKind::File));
+    }
+}
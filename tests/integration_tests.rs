@@ -7,9 +7,12 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
+use clean_dev_dirs::config::filter::ArtifactSelector;
+use clean_dev_dirs::config::scan::{ScanLimit, ScanLimits};
 use clean_dev_dirs::config::{ProjectFilter, ScanOptions};
-use clean_dev_dirs::project::{BuildArtifacts, ProjectType};
-use clean_dev_dirs::scanner::Scanner;
+use clean_dev_dirs::project::{BuildArtifacts, Project, ProjectType};
+use clean_dev_dirs::scanner::{ScanTruncation, Scanner};
+use clean_dev_dirs::utils::path_filter::PathFilter;
 
 /// Helper function to create a temporary directory structure for testing
 fn create_test_directory() -> TempDir {
@@ -170,8 +173,8 @@ fn test_scanner_finds_rust_projects() {
     for project in &projects {
         assert_eq!(project.kind, ProjectType::Rust);
         assert!(project.name.is_some());
-        assert!(project.build_arts.path.ends_with("target"));
-        assert!(project.build_arts.size > 0);
+        assert!(project.build_arts[0].path.ends_with("target"));
+        assert!(project.total_size() > 0);
     }
 }
 
@@ -198,8 +201,8 @@ fn test_scanner_finds_node_projects() {
     for project in &projects {
         assert_eq!(project.kind, ProjectType::Node);
         assert!(project.name.is_some());
-        assert!(project.build_arts.path.ends_with("node_modules"));
-        assert!(project.build_arts.size > 0);
+        assert!(project.build_arts[0].path.ends_with("node_modules"));
+        assert!(project.total_size() > 0);
     }
 }
 
@@ -225,8 +228,8 @@ fn test_scanner_finds_python_projects() {
 
     for project in &projects {
         assert_eq!(project.kind, ProjectType::Python);
-        assert!(project.build_arts.path.ends_with("__pycache__"));
-        assert!(project.build_arts.size > 0);
+        assert!(project.build_arts[0].path.ends_with("__pycache__"));
+        assert!(project.total_size() > 0);
     }
 }
 
@@ -253,8 +256,8 @@ fn test_scanner_finds_go_projects() {
     for project in &projects {
         assert_eq!(project.kind, ProjectType::Go);
         assert!(project.name.is_some());
-        assert!(project.build_arts.path.ends_with("vendor"));
-        assert!(project.build_arts.size > 0);
+        assert!(project.build_arts[0].path.ends_with("vendor"));
+        assert!(project.total_size() > 0);
     }
 }
 
@@ -315,6 +318,378 @@ fn test_scanner_skips_directories() {
     assert!(projects[0].root_path.ends_with("rust-project"));
 }
 
+#[test]
+fn test_scanner_respects_cleanignore_files() {
+    let temp_dir = create_test_directory();
+    let base_path = temp_dir.path();
+
+    create_rust_project(base_path, "app");
+    create_rust_project(&base_path.join("experiments"), "demo");
+    let python_path = create_python_project(base_path, "tool");
+    create_file(&python_path.join(".venv").join("pyvenv.cfg"), "home = /usr");
+    create_file(
+        &base_path.join(".cleanignore"),
+        "# keep these\nexperiments/\n",
+    );
+    create_file(&python_path.join(".cleanignore"), "/.venv\n");
+
+    let scan_options = ScanOptions {
+        verbose: false,
+        threads: 1,
+        skip: vec![],
+    };
+    let scanner = Scanner::new(scan_options, ProjectFilter::All);
+    let mut projects = scanner.scan_directory(base_path);
+    projects.sort_by(|a, b| a.root_path.cmp(&b.root_path));
+
+    assert_eq!(projects.len(), 2);
+    assert!(projects[0].root_path.ends_with("app"));
+    assert!(projects[1].root_path.ends_with("tool"));
+    assert!(
+        projects[1]
+            .artifact_paths()
+            .all(|path| !path.ends_with(".venv"))
+    );
+}
+
+#[test]
+fn test_scanner_applies_path_globs() {
+    let temp_dir = create_test_directory();
+    let base_path = temp_dir.path();
+
+    create_rust_project(&base_path.join("work"), "app");
+    create_rust_project(&base_path.join("work").join("experiments"), "demo");
+    create_rust_project(&base_path.join("play"), "game");
+
+    let scan_options = ScanOptions {
+        verbose: false,
+        threads: 1,
+        skip: vec![],
+    };
+    let path_filter = PathFilter::new(
+        &[format!("{}/work/**", base_path.display())],
+        &["**/experiments/**".to_string()],
+    )
+    .unwrap();
+
+    let scanner = Scanner::new(scan_options, ProjectFilter::Rust).with_path_filter(path_filter);
+    let projects = scanner.scan_directory(base_path);
+
+    assert_eq!(projects.len(), 1);
+    assert!(projects[0].root_path.ends_with("work/app"));
+}
+
+#[test]
+fn test_scan_and_clean_in_memory() {
+    use std::sync::Arc;
+
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner, RemovalStrategy};
+    use clean_dev_dirs::vfs::{FileSystem, FsHandle, MemoryFs};
+
+    let fs = Arc::new(
+        MemoryFs::new()
+            .with_file("/work/app/Cargo.toml", "[package]\nname = \"app\"\n")
+            .with_file_of_len("/work/app/target/debug/app", 4000)
+            .with_file("/work/web/package.json", r#"{"name": "web"}"#)
+            .with_file_of_len("/work/web/node_modules/left-pad/index.js", 1000)
+            .with_file_of_len("/work/web/node_modules/lib/stuck.js", 500)
+            .with_locked("/work/web/node_modules/lib/stuck.js")
+            .with_file("/work/notes/README.md", "not a project"),
+    );
+    let scan_options = ScanOptions {
+        verbose: false,
+        threads: 1,
+        skip: vec![],
+    };
+
+    let scanner = Scanner::new(scan_options, ProjectFilter::All).with_fs(FsHandle::new(fs.clone()));
+    let mut projects = scanner.scan_directory(Path::new("/work"));
+    projects.sort_by_key(|project| project.root_path.clone());
+
+    assert_eq!(projects.len(), 2);
+    assert_eq!(projects[0].kind, ProjectType::Rust);
+    assert_eq!(projects[0].name.as_deref(), Some("app"));
+    assert_eq!(projects[0].total_size(), 4000);
+    assert_eq!(projects[1].kind, ProjectType::Node);
+    assert_eq!(projects[1].name.as_deref(), Some("web"));
+    assert_eq!(projects[1].total_size(), 1500);
+
+    let options = CleanOptions {
+        removal_strategy: RemovalStrategy::Permanent,
+        fs: FsHandle::new(fs.clone()),
+        ..CleanOptions::default()
+    };
+    let result = Cleaner::clean_projects(projects.into(), &options);
+
+    assert_eq!(result.total_freed, 5000);
+    assert_eq!(result.cleaned.len(), 1);
+    assert_eq!(result.cleaned[0].0.name.as_deref(), Some("app"));
+    assert_eq!(result.cleaned[0].1, 4000);
+    assert!(result.failed.is_empty());
+    assert!(!fs.exists(Path::new("/work/app/target")));
+    assert!(fs.exists(Path::new("/work/app/Cargo.toml")));
+    // The locked file is left behind, and the project partially cleaned
+    assert_eq!(result.partial.len(), 1);
+    assert!(fs.exists(Path::new("/work/web/node_modules/lib/stuck.js")));
+    assert!(!fs.exists(Path::new("/work/web/node_modules/left-pad")));
+}
+
+#[test]
+fn test_clean_reuses_scanned_sizes_unless_rechecked() {
+    use std::sync::Arc;
+
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner, RemovalStrategy};
+    use clean_dev_dirs::vfs::{FsHandle, MemoryFs};
+
+    for (recheck_size, freed) in [(false, 1000), (true, 4000)] {
+        let fs = MemoryFs::new()
+            .with_file("/work/app/Cargo.toml", "[package]\nname = \"app\"\n")
+            .with_file_of_len("/work/app/target/debug/app", 4000);
+        // The target directory grew since the scan measured it
+        let project = Project::new(
+            ProjectType::Rust,
+            PathBuf::from("/work/app"),
+            vec![BuildArtifacts {
+                path: PathBuf::from("/work/app/target"),
+                size: 1000,
+            }],
+            Some("app".to_string()),
+        );
+
+        let options = CleanOptions {
+            removal_strategy: RemovalStrategy::Permanent,
+            fs: FsHandle::new(Arc::new(fs)),
+            recheck_size,
+            ..CleanOptions::default()
+        };
+        let result = Cleaner::clean_projects(vec![project].into(), &options);
+
+        assert_eq!(result.success_count, 1);
+        assert_eq!(result.total_freed, freed);
+    }
+}
+
+#[test]
+fn test_clean_result_summary_line() {
+    use clean_dev_dirs::cleaner::CleanResult;
+
+    let mut result = CleanResult::default();
+    assert_eq!(
+        result.summary_line("project"),
+        "cleaned 0 projects, freed 0 B"
+    );
+
+    result.success_count = 1;
+    result.total_freed = 4_200_000_000;
+    result.errors.push("Failed to clean /work/app".to_string());
+    result.cancelled_count = 2;
+    assert_eq!(
+        result.summary_line("cache"),
+        "cleaned 1 cache, 1 failed, 2 cancelled, freed 4.20 GB"
+    );
+}
+
+#[test]
+fn test_cleaner_refuses_shallow_paths() {
+    use std::sync::Arc;
+
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner, RemovalStrategy};
+    use clean_dev_dirs::project::Project;
+    use clean_dev_dirs::vfs::{FileSystem, FsHandle, MemoryFs};
+
+    let fs = Arc::new(
+        MemoryFs::new()
+            .with_file_of_len("/app/target/debug/app", 4000)
+            .with_file_of_len("/work/web/node_modules/index.js", 1000)
+            .with_file("/work/web/package.json", "{}"),
+    );
+    let artifact = |path: &str, size| BuildArtifacts {
+        path: PathBuf::from(path),
+        size,
+    };
+    let projects = vec![
+        Project::new(
+            ProjectType::Rust,
+            PathBuf::from("/app"),
+            vec![artifact("/app/target", 4000)],
+            None,
+        ),
+        Project::new(
+            ProjectType::Node,
+            PathBuf::from("/work/web"),
+            vec![artifact("/work/web/node_modules", 1000)],
+            None,
+        ),
+    ];
+
+    let options = CleanOptions {
+        removal_strategy: RemovalStrategy::Permanent,
+        fs: FsHandle::new(fs.clone()),
+        ..CleanOptions::default()
+    };
+    let result = Cleaner::clean_projects(projects.into(), &options);
+
+    assert_eq!(result.success_count, 1);
+    assert_eq!(result.total_freed, 1000);
+    assert_eq!(result.errors.len(), 1);
+    assert!(result.errors[0].contains("refusing to delete /app/target"));
+    let [(project, error)] = result.failed.as_slice() else {
+        panic!("expected one failed project, got {}", result.failed.len());
+    };
+    assert_eq!(project.root_path, PathBuf::from("/app"));
+    let clean_dev_dirs::Error::Clean { path, source } = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(path, &PathBuf::from("/app/target"));
+    assert!(matches!(**source, clean_dev_dirs::Error::Refused { .. }));
+    assert!(fs.exists(Path::new("/app/target/debug/app")));
+}
+
+#[test]
+fn test_failed_removals_carry_the_io_error() {
+    use std::io::ErrorKind;
+    use std::sync::Arc;
+
+    use clean_dev_dirs::cache::{CacheKind, CacheLocation};
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner, RemovalStrategy};
+    use clean_dev_dirs::project::Project;
+    use clean_dev_dirs::vfs::{FsHandle, MemoryFs};
+
+    let fs = Arc::new(
+        MemoryFs::new()
+            .with_file("/work/web/package.json", "{}")
+            .with_file_of_len("/work/web/node_modules/lib/stuck.js", 500)
+            .with_locked("/work/web/node_modules/lib/stuck.js")
+            .with_file_of_len("/home/dev/.npm/_cacache/index", 300)
+            .with_locked("/home/dev/.npm/_cacache/index"),
+    );
+    let options = CleanOptions {
+        removal_strategy: RemovalStrategy::Permanent,
+        fs: FsHandle::new(fs),
+        ..CleanOptions::default()
+    };
+
+    let project = Project::new(
+        ProjectType::Node,
+        PathBuf::from("/work/web"),
+        vec![BuildArtifacts {
+            path: PathBuf::from("/work/web/node_modules"),
+            size: 500,
+        }],
+        None,
+    );
+    let result = Cleaner::clean_projects(vec![project].into(), &options);
+    assert_eq!(result.failed.len(), 1);
+    assert_eq!(
+        result.failed[0].1.io_error().map(std::io::Error::kind),
+        Some(ErrorKind::PermissionDenied)
+    );
+
+    let cache = CacheLocation {
+        kind: CacheKind::NpmCache,
+        path: PathBuf::from("/home/dev/.npm/_cacache"),
+        size: 300,
+    };
+    let result = Cleaner::clean_caches(&[cache], &options);
+    assert_eq!(result.errors.len(), 1);
+    let [(cache, error)] = result.failed_caches.as_slice() else {
+        panic!(
+            "expected one failed cache, got {}",
+            result.failed_caches.len()
+        );
+    };
+    assert_eq!(cache.kind, CacheKind::NpmCache);
+    assert_eq!(
+        error.io_error().map(std::io::Error::kind),
+        Some(ErrorKind::PermissionDenied)
+    );
+}
+
+#[test]
+fn test_scanner_stops_at_max_dirs() {
+    let temp_dir = create_test_directory();
+    let base_path = temp_dir.path();
+    for name in ["a", "b", "c"] {
+        create_rust_project(base_path, name);
+    }
+    let scan_options = ScanOptions {
+        verbose: false,
+        threads: 1,
+        skip: vec![],
+    };
+
+    let scanner = Scanner::new(scan_options.clone(), ProjectFilter::All);
+    assert_eq!(scanner.scan_directory(base_path).len(), 3);
+    assert!(scanner.truncation().is_none());
+
+    // The root and one project directory are visited before the limit
+    let scanner = Scanner::new(scan_options, ProjectFilter::All).with_limits(ScanLimits {
+        budget: None,
+        max_dirs: Some(2),
+    });
+    assert_eq!(scanner.scan_directory(base_path).len(), 1);
+    assert_eq!(
+        scanner.truncation(),
+        Some(ScanTruncation {
+            limit: ScanLimit::MaxDirs(2),
+            dirs_visited: 2,
+        })
+    );
+}
+
+#[test]
+fn test_scanner_selects_artifact_kinds() {
+    let temp_dir = create_test_directory();
+    let base_path = temp_dir.path();
+
+    let node_path = create_node_project(base_path, "web");
+    create_file(&node_path.join(".cache").join("babel.json"), "{}");
+    create_rust_project(base_path, "app");
+    let python_path = create_python_project(base_path, "tool");
+    create_file(
+        &python_path.join(".pytest_cache").join("README.md"),
+        "cache",
+    );
+
+    let scan_options = ScanOptions {
+        verbose: false,
+        threads: 1,
+        skip: vec![],
+    };
+    // Caches everywhere, dependencies only for Node.js
+    let artifacts = ["caches", "node:deps"]
+        .iter()
+        .map(|selector| selector.parse::<ArtifactSelector>().unwrap())
+        .collect();
+
+    let scanner = Scanner::new(scan_options, ProjectFilter::All).with_artifacts(artifacts);
+    let mut projects = scanner.scan_directory(base_path);
+    projects.sort_by_key(|project| project.root_path.clone());
+
+    // The Rust project has no cache and is dropped
+    assert_eq!(projects.len(), 2);
+    let artifact_names = |index: usize| -> Vec<String> {
+        let mut names: Vec<String> = projects[index]
+            .build_arts
+            .iter()
+            .map(|artifact| {
+                artifact
+                    .path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        names.sort();
+        names
+    };
+    assert!(projects[0].root_path.ends_with("tool"));
+    assert_eq!(artifact_names(0), [".pytest_cache"]);
+    assert!(projects[1].root_path.ends_with("web"));
+    assert_eq!(artifact_names(1), [".cache", "node_modules"]);
+}
+
 #[test]
 fn test_scanner_calculates_build_directory_sizes() {
     let temp_dir = create_test_directory();
@@ -339,7 +714,7 @@ fn test_scanner_calculates_build_directory_sizes() {
     assert_eq!(projects.len(), 1);
 
     let project = &projects[0];
-    assert!(project.build_arts.size > 1000); // Should include our large file
+    assert!(project.total_size() > 1000); // Should include our large file
 }
 
 #[test]
@@ -935,6 +1310,661 @@ fn test_removal_strategy_from_bool() {
     assert!(matches!(permanent, RemovalStrategy::Permanent));
 }
 
+#[test]
+fn test_list_files_returns_every_file_in_build_dir() {
+    use clean_dev_dirs::cleaner::Cleaner;
+
+    let temp_dir = create_test_directory();
+    let project_path = create_rust_project(temp_dir.path(), "listed-project");
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::Rust,
+    );
+    let projects = scanner.scan_directory(temp_dir.path());
+    assert_eq!(projects.len(), 1);
+
+    let files = Cleaner::list_files(&projects[0]);
+    let target = project_path.join("target");
+    assert_eq!(
+        files,
+        vec![
+            target.join("debug").join("build.log"),
+            target.join("release").join("binary"),
+        ]
+    );
+
+    // Listing must not delete anything
+    assert!(target.exists());
+}
+
+#[test]
+fn test_cleaning_unity_project_removes_every_artifact_dir() {
+    use clean_dev_dirs::Projects;
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner};
+
+    let temp_dir = create_test_directory();
+    let project_path = temp_dir.path().join("unity-game");
+
+    create_file(
+        &project_path.join("ProjectSettings/ProjectVersion.txt"),
+        "m_EditorVersion: 2022.3.10f1\n",
+    );
+    create_file(&project_path.join("Assets/Player.cs"), "class Player {}");
+    for dir in ["Library", "Temp", "obj", "Logs"] {
+        create_file(&project_path.join(dir).join("generated"), "regenerable");
+    }
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::Unity,
+    );
+    let projects = scanner.scan_directory(temp_dir.path());
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].build_arts.len(), 4);
+
+    let result = Cleaner::clean_projects(Projects::from(projects), &CleanOptions::default());
+
+    assert_eq!(result.success_count, 1);
+    assert!(result.errors.is_empty());
+    for dir in ["Library", "Temp", "obj", "Logs"] {
+        assert!(!project_path.join(dir).exists(), "{dir} should be removed");
+    }
+    assert!(project_path.join("Assets/Player.cs").exists());
+    assert!(project_path.join("ProjectSettings").exists());
+}
+
+#[test]
+fn test_clean_with_reports_every_project() {
+    use clean_dev_dirs::cleaner::{CleanEvent, CleanOptions, Cleaner};
+
+    let temp_dir = create_test_directory();
+    let first = create_rust_project(temp_dir.path(), "first");
+    let second = create_node_project(temp_dir.path(), "second");
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::All,
+    );
+    let projects = scanner.scan_directory(temp_dir.path());
+    assert_eq!(projects.len(), 2);
+
+    let mut started = None;
+    let mut cleaned = Vec::new();
+    let result = Cleaner::clean_with(
+        projects.into(),
+        &CleanOptions::default(),
+        |event| match event {
+            CleanEvent::Started { total_projects, .. } => started = Some(*total_projects),
+            CleanEvent::ProjectCleaned { project, freed } => {
+                assert!(*freed > 0);
+                cleaned.push(project.root_path.clone());
+            }
+            CleanEvent::ProjectPartiallyCleaned { leftovers, .. } => {
+                panic!("unexpected leftovers: {leftovers:?}")
+            }
+            CleanEvent::ProjectFailed { error, .. } => panic!("unexpected failure: {error}"),
+            CleanEvent::ProjectCancelled { .. } => panic!("nothing was cancelled"),
+            CleanEvent::ProjectSkipped { reason, .. } => panic!("unexpected skip: {reason}"),
+        },
+    );
+
+    cleaned.sort();
+    assert_eq!(started, Some(2));
+    assert_eq!(cleaned, vec![first.clone(), second.clone()]);
+    assert_eq!(result.success_count, 2);
+    assert_eq!(result.cancelled_count, 0);
+    assert!(!first.join("target").exists());
+    assert!(!second.join("node_modules").exists());
+}
+
+#[test]
+fn test_clean_with_cancelled_token_leaves_projects_untouched() {
+    use clean_dev_dirs::CancellationToken;
+    use clean_dev_dirs::cleaner::{CleanEvent, CleanOptions, Cleaner};
+
+    let temp_dir = create_test_directory();
+    let project_path = create_rust_project(temp_dir.path(), "kept");
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::Rust,
+    );
+    let projects = scanner.scan_directory(temp_dir.path());
+
+    let cancellation = CancellationToken::new();
+    cancellation.cancel();
+    let options = CleanOptions {
+        cancellation,
+        ..CleanOptions::default()
+    };
+
+    let mut cancelled = 0;
+    let result = Cleaner::clean_with(projects.into(), &options, |event| {
+        if let CleanEvent::ProjectCancelled { .. } = event {
+            cancelled += 1;
+        }
+    });
+
+    assert_eq!(cancelled, 1);
+    assert_eq!(result.success_count, 0);
+    assert_eq!(result.cancelled_count, 1);
+    assert_eq!(result.total_freed, 0);
+    assert!(project_path.join("target").exists());
+}
+
+#[test]
+fn test_native_clean_cargo_args() {
+    use clean_dev_dirs::cleaner::NativeClean;
+
+    assert_eq!(NativeClean::default().cargo_args(), vec!["clean"]);
+    assert!(!NativeClean::default().is_partial());
+
+    let narrowed = NativeClean {
+        release: true,
+        packages: vec!["core".to_string()],
+    };
+    assert_eq!(
+        narrowed.cargo_args(),
+        vec!["clean", "--release", "-p", "core"]
+    );
+    assert!(narrowed.is_partial());
+}
+
+#[test]
+#[cfg(unix)]
+fn test_unremovable_files_make_a_partial_clean() {
+    use clean_dev_dirs::Projects;
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner};
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = create_test_directory();
+    let project_path = create_rust_project(temp_dir.path(), "partial");
+    let locked = project_path.join("target").join("locked");
+    create_file(&locked.join("stuck.o"), "stuck");
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::Rust,
+    );
+    let projects = scanner.scan_directory(temp_dir.path());
+
+    std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555)).unwrap();
+    // Permissions do not apply to root
+    if std::fs::write(locked.join("probe"), "").is_ok() {
+        return;
+    }
+
+    let result = Cleaner::clean_projects(Projects::from(projects), &CleanOptions::default());
+    std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    assert_eq!(result.success_count, 0);
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.partial.len(), 1);
+    assert_eq!(result.partial[0].leftovers, vec![locked.join("stuck.o")]);
+    assert_eq!(result.total_freed, result.partial[0].freed);
+    assert!(result.partial[0].freed > 0);
+    assert!(result.partial[0].freed < result.partial[0].size);
+    assert!(!project_path.join("target").join("debug").exists());
+}
+
+#[test]
+fn test_projects_with_mounted_build_directories_are_skipped() {
+    use std::sync::Arc;
+
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner, RemovalStrategy};
+    use clean_dev_dirs::vfs::{FileSystem, FsHandle, MemoryFs};
+
+    let fs = Arc::new(
+        MemoryFs::new()
+            .with_file("/work/app/Cargo.toml", "[package]\nname = \"app\"\n")
+            .with_file_of_len("/work/app/target/debug/app", 4000)
+            .with_file("/work/web/package.json", "{}")
+            .with_file_of_len("/work/web/node_modules/index.js", 1000)
+            .with_mount_point("/work/web/node_modules"),
+    );
+    let artifact = |path: &str, size| BuildArtifacts {
+        path: PathBuf::from(path),
+        size,
+    };
+    let projects = vec![
+        Project::new(
+            ProjectType::Rust,
+            PathBuf::from("/work/app"),
+            vec![artifact("/work/app/target", 4000)],
+            None,
+        ),
+        Project::new(
+            ProjectType::Node,
+            PathBuf::from("/work/web"),
+            vec![artifact("/work/web/node_modules", 1000)],
+            None,
+        ),
+    ];
+
+    let options = CleanOptions {
+        removal_strategy: RemovalStrategy::Permanent,
+        fs: FsHandle::new(fs.clone()),
+        ..CleanOptions::default()
+    };
+    let result = Cleaner::clean_projects(projects.into(), &options);
+
+    assert_eq!(result.success_count, 1);
+    assert_eq!(result.total_freed, 4000);
+    assert_eq!(result.skipped.len(), 1);
+    assert!(result.skipped[0].contains("/work/web/node_modules is a mount point"));
+    let removed: Vec<_> = result
+        .removed
+        .iter()
+        .map(|removed| removed.artifact_path.clone())
+        .collect();
+    assert_eq!(removed, vec![PathBuf::from("/work/app/target")]);
+    assert!(fs.exists(Path::new("/work/web/node_modules/index.js")));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_symlinked_build_directories_are_removed_as_links() {
+    use clean_dev_dirs::Projects;
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner};
+
+    let temp_dir = create_test_directory();
+    let shared = temp_dir.path().join("shared-cache").join("node_modules");
+    create_file(&shared.join("lib").join("index.js"), "module.exports = 1;");
+    let project_path = temp_dir.path().join("web");
+    create_file(&project_path.join("package.json"), r#"{"name": "web"}"#);
+    std::os::unix::fs::symlink(&shared, project_path.join("node_modules")).unwrap();
+
+    let projects = vec![Project::new(
+        ProjectType::Node,
+        project_path.clone(),
+        vec![BuildArtifacts {
+            path: project_path.join("node_modules"),
+            size: 0,
+        }],
+        None,
+    )];
+    let result = Cleaner::clean_projects(Projects::from(projects), &CleanOptions::default());
+
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.success_count, 1);
+    assert!(fs::symlink_metadata(project_path.join("node_modules")).is_err());
+    assert!(shared.join("lib").join("index.js").exists());
+}
+
+#[test]
+#[cfg(unix)]
+fn test_non_utf8_project_paths_are_handled() {
+    use clean_dev_dirs::Projects;
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner};
+    use clean_dev_dirs::output::JsonOutput;
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let temp_dir = create_test_directory();
+    let base_path = temp_dir.path().join(OsStr::from_bytes(b"caf\xe9"));
+    // Some file systems (e.g. APFS) only accept UTF-8 names
+    if fs::create_dir(&base_path).is_err() {
+        return;
+    }
+    let project_path = create_rust_project(&base_path, "app");
+    create_file(
+        &project_path
+            .join("target")
+            .join(OsStr::from_bytes(b"lib\xff.rlib")),
+        "rlib",
+    );
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::Rust,
+    );
+    let projects = scanner.scan_directory(temp_dir.path());
+
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].root_path, project_path);
+    assert!(projects[0].total_size() > 4);
+    assert!(projects[0].to_string().contains("caf\u{FFFD}"));
+
+    let json = serde_json::to_string(&JsonOutput::from_projects_dry_run(&projects)).unwrap();
+    assert!(json.contains("caf\u{FFFD}"));
+
+    let result = Cleaner::clean_projects(Projects::from(projects), &CleanOptions::default());
+    assert_eq!(result.success_count, 1);
+    assert!(!project_path.join("target").exists());
+}
+
+#[test]
+fn test_native_clean_runs_cargo_clean() {
+    use clean_dev_dirs::Projects;
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner, NativeClean};
+
+    let temp_dir = create_test_directory();
+    let project_path = create_rust_project(temp_dir.path(), "native");
+    create_file(&project_path.join("src").join("lib.rs"), "");
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::Rust,
+    );
+    let projects = scanner.scan_directory(temp_dir.path());
+    let estimated = projects[0].total_size();
+
+    let options = CleanOptions {
+        native_clean: Some(NativeClean::default()),
+        ..CleanOptions::default()
+    };
+    let result = Cleaner::clean_projects(Projects::from(projects), &options);
+
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.success_count, 1);
+    assert_eq!(result.total_freed, estimated);
+    assert!(!project_path.join("target").exists());
+    assert!(project_path.join("Cargo.toml").exists());
+}
+
+#[test]
+fn test_json_summary_rolls_up_workspace_members() {
+    use clean_dev_dirs::output::JsonSummary;
+
+    let temp_dir = create_test_directory();
+    let mono = temp_dir.path().join("mono");
+    create_file(
+        &mono.join("package.json"),
+        r#"{"name": "mono", "private": true, "workspaces": ["packages/*"]}"#,
+    );
+    create_node_project(&mono.join("packages"), "web");
+    create_node_project(&mono.join("packages"), "api");
+    create_node_project(temp_dir.path(), "standalone");
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::Node,
+    );
+    let projects = scanner.scan_directory(temp_dir.path());
+    assert_eq!(projects.len(), 3);
+
+    let summary = JsonSummary::from_projects(&projects);
+    assert_eq!(summary.workspaces.len(), 1);
+
+    let workspace = &summary.workspaces[0];
+    assert_eq!(workspace.name, "mono");
+    assert_eq!(workspace.member_count, 2);
+    assert_eq!(
+        workspace.total_size,
+        workspace
+            .members
+            .iter()
+            .map(|member| member.size)
+            .sum::<u64>()
+    );
+
+    let json = serde_json::to_value(&summary).unwrap();
+    assert_eq!(
+        json["workspaces"][0]["members"].as_array().unwrap().len(),
+        2
+    );
+}
+
+#[test]
+fn test_native_clean_partial_failure_does_not_delete_target() {
+    use clean_dev_dirs::Projects;
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner, NativeClean};
+
+    let temp_dir = create_test_directory();
+    // No src/ directory: the manifest has no targets, so `cargo clean` fails
+    let project_path = create_rust_project(temp_dir.path(), "broken");
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::Rust,
+    );
+    let projects = scanner.scan_directory(temp_dir.path());
+
+    let options = CleanOptions {
+        native_clean: Some(NativeClean {
+            release: true,
+            packages: Vec::new(),
+        }),
+        ..CleanOptions::default()
+    };
+    let result = Cleaner::clean_projects(Projects::from(projects), &options);
+
+    assert_eq!(result.success_count, 0);
+    assert_eq!(result.errors.len(), 1);
+    assert!(project_path.join("target").join("release").exists());
+}
+
+#[test]
+fn test_audit_log_records_deleted_build_directories() {
+    use clean_dev_dirs::Projects;
+    use clean_dev_dirs::audit::{AuditLog, AuditOutcome, AuditRecord};
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner};
+    use std::sync::Arc;
+
+    let temp_dir = create_test_directory();
+    let project_path = create_rust_project(temp_dir.path(), "audited");
+    let log_path = temp_dir.path().join("audit.jsonl");
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::Rust,
+    );
+    let projects = scanner.scan_directory(temp_dir.path());
+
+    let options = CleanOptions {
+        audit_log: Some(Arc::new(AuditLog::open(&log_path).unwrap())),
+        ..CleanOptions::default()
+    };
+    let result = Cleaner::clean_projects(Projects::from(projects), &options);
+    assert_eq!(result.success_count, 1);
+
+    let records: Vec<AuditRecord> = fs::read_to_string(&log_path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let outcomes: Vec<AuditOutcome> = records.iter().map(|record| record.outcome).collect();
+    assert_eq!(outcomes, [AuditOutcome::Started, AuditOutcome::Deleted]);
+    for record in &records {
+        assert_eq!(record.path, project_path.join("target"));
+        assert_eq!(record.project.as_deref(), Some(project_path.as_path()));
+        assert_eq!(record.project_type, Some(ProjectType::Rust));
+    }
+}
+
+#[test]
+fn test_audit_log_records_the_stale_entries_removed() {
+    use clean_dev_dirs::Projects;
+    use clean_dev_dirs::audit::{AuditLog, AuditOutcome, AuditRecord};
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner, RemovalStrategy};
+    use clean_dev_dirs::sweep::StaleCriteria;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+
+    let temp_dir = create_test_directory();
+    let project_path = create_rust_project(temp_dir.path(), "swept");
+    let target = project_path.join("target");
+    let debug = target.join("debug");
+    create_file(
+        &debug.join(".fingerprint/serde-1a2b3c4d/lib"),
+        "fingerprint",
+    );
+    create_file(&debug.join("deps/libserde-1a2b3c4d.rlib"), "rlib");
+    let log_path = temp_dir.path().join("audit.jsonl");
+
+    let projects = vec![Project::new(
+        ProjectType::Rust,
+        project_path.clone(),
+        vec![BuildArtifacts {
+            path: target.clone(),
+            size: 1_000_000,
+        }],
+        None,
+    )];
+    let options = CleanOptions {
+        removal_strategy: RemovalStrategy::Permanent,
+        // Everything is older than an hour from now
+        stale_only: Some(StaleCriteria {
+            cutoff: Some(SystemTime::now() + Duration::from_secs(3600)),
+            installed_toolchains: None,
+        }),
+        audit_log: Some(Arc::new(AuditLog::open(&log_path).unwrap())),
+        ..CleanOptions::default()
+    };
+    let result = Cleaner::clean_projects(Projects::from(projects), &options);
+    assert_eq!(result.success_count, 1, "{:?}", result.errors);
+
+    let records: Vec<AuditRecord> = fs::read_to_string(&log_path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let (started, removed): (Vec<_>, Vec<_>) = records
+        .iter()
+        .partition(|record| record.outcome == AuditOutcome::Started);
+    assert_eq!(started.len(), 1);
+    assert_eq!(started[0].path, target);
+    let mut removed: Vec<_> = removed
+        .iter()
+        .map(|record| (record.path.clone(), record.outcome))
+        .collect();
+    removed.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        removed,
+        [
+            (
+                debug.join(".fingerprint/serde-1a2b3c4d"),
+                AuditOutcome::Deleted
+            ),
+            (
+                debug.join("deps/libserde-1a2b3c4d.rlib"),
+                AuditOutcome::Deleted
+            ),
+        ]
+    );
+    assert!(target.exists());
+}
+
+#[test]
+fn test_audit_log_leaves_out_mounted_build_directories() {
+    use clean_dev_dirs::audit::AuditLog;
+    use clean_dev_dirs::cleaner::{CleanOptions, Cleaner, RemovalStrategy};
+    use clean_dev_dirs::vfs::{FsHandle, MemoryFs};
+    use std::sync::Arc;
+
+    let temp_dir = create_test_directory();
+    let log_path = temp_dir.path().join("audit.jsonl");
+    let memory = Arc::new(
+        MemoryFs::new()
+            .with_file("/work/web/package.json", "{}")
+            .with_file_of_len("/work/web/node_modules/index.js", 1000)
+            .with_mount_point("/work/web/node_modules"),
+    );
+    let projects = vec![Project::new(
+        ProjectType::Node,
+        PathBuf::from("/work/web"),
+        vec![BuildArtifacts {
+            path: PathBuf::from("/work/web/node_modules"),
+            size: 1000,
+        }],
+        None,
+    )];
+
+    let options = CleanOptions {
+        removal_strategy: RemovalStrategy::Permanent,
+        fs: FsHandle::new(memory),
+        audit_log: Some(Arc::new(AuditLog::open(&log_path).unwrap())),
+        ..CleanOptions::default()
+    };
+    let result = Cleaner::clean_projects(projects.into(), &options);
+
+    assert_eq!(result.skipped.len(), 1);
+    assert_eq!(fs::read_to_string(&log_path).unwrap(), "");
+}
+
+/// `path`, absolute, relative to the current directory instead, as the scan
+/// root `.` and the paths below it are.
+#[cfg(unix)]
+fn relative_to_current_dir(path: &Path) -> PathBuf {
+    let current_dir = std::env::current_dir().unwrap();
+    let mut relative: PathBuf = current_dir.components().skip(1).map(|_| "..").collect();
+    relative.push(path.strip_prefix("/").unwrap());
+    relative
+}
+
+#[test]
+#[cfg(unix)]
+fn test_skip_busy_with_a_relative_scan_root() {
+    use clean_dev_dirs::filtering::{FilterOutcome, SkipReason};
+
+    let temp_dir = create_test_directory();
+    let project_path = create_node_project(temp_dir.path(), "web");
+    let root = relative_to_current_dir(temp_dir.path());
+    assert!(root.is_relative());
+
+    let scanner = Scanner::new(
+        ScanOptions {
+            verbose: false,
+            threads: 1,
+            skip: vec![],
+        },
+        ProjectFilter::Node,
+    );
+    let mut outcome = FilterOutcome {
+        kept: scanner.scan_directory(&root),
+        skipped: Vec::new(),
+    };
+    assert_eq!(outcome.kept.len(), 1);
+    assert!(outcome.kept[0].root_path.is_relative());
+
+    // Working directories of processes are absolute
+    outcome.skip_busy(&[project_path]);
+
+    assert!(outcome.kept.is_empty());
+    assert_eq!(outcome.skipped[0].reason, SkipReason::BuildInProgress);
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 // Parallel scanning consistency (cross-platform)
 // ═══════════════════════════════════════════════════════════════════════